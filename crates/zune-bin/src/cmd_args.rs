@@ -355,7 +355,13 @@ fn add_operations() -> (Vec<Arg>, ArgGroup) {
             .help_heading(HELP_HEADING)
             .allow_negative_numbers(false)
             .help("Rotate image by 90,180 or 270")
-            .value_parser(value_parser!(f32))
+            .value_parser(value_parser!(f32)),
+        Arg::new("recipe")
+            .long("recipe")
+            .help_heading(HELP_HEADING)
+            .value_name("file")
+            .help("Run the operations stored in a JSON recipe file")
+            .value_parser(value_parser!(String))
     ];
     args.sort_unstable_by(|x, y| x.get_id().cmp(y.get_id()));
 
@@ -22,6 +22,7 @@ use zune_imageprocs::gamma::Gamma;
 use zune_imageprocs::hsv_adjust::HsvAdjust;
 use zune_imageprocs::invert::Invert;
 use zune_imageprocs::mirror::{Mirror, MirrorMode};
+use zune_imageprocs::recipe::Recipe;
 use zune_imageprocs::resize::{Resize, ResizeMethod};
 use zune_imageprocs::rotate::Rotate;
 use zune_imageprocs::spatial::SpatialOps;
@@ -204,6 +205,17 @@ pub fn parse_options(
         let value = *args.get_one::<f32>(argument).unwrap();
         workflow.chain_operations(Box::new(Rotate::new(value)));
         debug!("Added rotate argument with value {}", value);
+    } else if argument == "recipe" {
+        let file = args.get_one::<String>(argument).unwrap();
+        let contents = std::fs::read_to_string(file).map_err(|x| format!("{file}: {x}"))?;
+        let recipe: Recipe =
+            serde_json::from_str(&contents).map_err(|x| format!("Invalid recipe {file}: {x}"))?;
+
+        recipe.chain_to(workflow).map_err(|x| format!("{x:?}"))?;
+        debug!(
+            "Added {} operations from recipe {file}",
+            recipe.operations.len()
+        );
     }
 
     Ok(())
@@ -106,7 +106,6 @@ use zune_core::log::{trace, warn};
 use zune_core::options::DecoderOptions;
 
 use crate::common::{BmpCompression, BmpPixelFormat, PROFILE_EMBEDDED, PROFILE_LINKED};
-use crate::utils::expand_bits_to_byte;
 use crate::BmpDecoderErrors;
 
 /// Probe some bytes to see
@@ -204,7 +203,7 @@ where
     comp:                 BmpCompression,
     ihszie:               u32,
     hsize:                u32,
-    palette:              Vec<PaletteEntry>,
+    palette:              [PaletteEntry; 256],
     depth:                u16,
     is_alpha:             bool,
     palette_numbers:      usize,
@@ -219,6 +218,8 @@ where
     /// Some passes may directly output bgra, but others don't (e.g palette),
     /// so for now we handle those paths separately
     image_in_bgra:        bool,
+    /// Whether paletted images are decoded to their palette indices
+    keep_indices:         bool,
     /// The bytes of an ICC embedded profile if it exists
     icc_bytes:            Option<Vec<u8>>,
     /// Color primaries if present
@@ -263,11 +264,12 @@ where
             ihszie: 0,
             hsize: 0,
             depth: 0,
-            palette: vec![],
+            palette: [PaletteEntry::default(); 256],
             is_alpha: false,
             palette_numbers: 0,
             convert_rgba_to_bgra: false,
             image_in_bgra: false,
+            keep_indices: false,
             icc_bytes: None,
             color_primaries: None
         }
@@ -522,6 +524,14 @@ where
         if self.pix_fmt == BmpPixelFormat::None {
             return Err(BmpDecoderErrors::GenericStatic("Unsupported Pixel format"));
         }
+        let components = self.pix_fmt.num_components();
+
+        if let Some((limit, found, max)) =
+            self.options
+                .exceeded_limit(self.width, self.height, components, 1)
+        {
+            return Err(BmpDecoderErrors::TooLargeDimensions(limit, max, found));
+        }
 
         let p = self.hsize.wrapping_sub(self.ihszie).wrapping_sub(14);
 
@@ -552,8 +562,6 @@ where
                 if p < colors * 3 {
                     return Err(BmpDecoderErrors::GenericStatic("Invalid Palette entries"));
                 }
-                self.palette.resize(256, PaletteEntry::default());
-
                 self.palette.iter_mut().take(colors as usize).for_each(|x| {
                     let [b, g, r] = self
                         .bytes
@@ -565,8 +573,6 @@ where
                     x.blue = b;
                 });
             } else {
-                self.palette.resize(256, PaletteEntry::default());
-
                 self.palette.iter_mut().take(colors as usize).for_each(|x| {
                     let [b, g, r, _] = self
                         .bytes
@@ -608,7 +614,19 @@ where
         }
         self.width
             .checked_mul(self.height)?
-            .checked_mul(self.pix_fmt.num_components())
+            .checked_mul(self.out_components())
+    }
+
+    /// Number of bytes per pixel in the output
+    fn out_components(&self) -> usize {
+        if self.keeps_indices() {
+            return 1;
+        }
+        self.pix_fmt.num_components()
+    }
+    /// Return true if the image is paletted and decodes to palette indices
+    fn keeps_indices(&self) -> bool {
+        self.keep_indices && self.pix_fmt == BmpPixelFormat::PAL8
     }
 
     /// Return the BMP bit depth
@@ -644,6 +662,9 @@ where
         if !self.decoded_headers {
             return None;
         }
+        if self.keeps_indices() {
+            return Some(ColorSpace::Luma);
+        }
 
         #[cfg(feature = "rgb_inverse")]
         if self.convert_rgba_to_bgra {
@@ -667,6 +688,16 @@ where
         self.convert_rgba_to_bgra = yes;
         self
     }
+    /// Whether the decoder should return the palette indices of paletted images
+    /// instead of expanding them to colors
+    ///
+    /// If true, paletted images are decoded as [`ColorSpace::Luma`] with one index
+    /// per pixel, see [`palette`](Self::palette) for the colors. Other images are
+    /// not affected
+    pub fn keep_indices(&mut self, yes: bool) -> &mut Self {
+        self.keep_indices = yes;
+        self
+    }
     /// Decode an image returning the decoded bytes as an
     /// allocated `Vec<u8>` or an error if decoding could not be completed
     ///
@@ -695,6 +726,24 @@ where
         self.color_primaries
     }
 
+    /// Return the palette of a paletted image or `None` if the image has no palette
+    ///
+    /// Entries are in RGBA order with an opaque alpha. Decoding expands indices
+    /// to the colors of the palette unless [`keep_indices`](Self::keep_indices) is set.
+    pub fn palette(&self) -> Option<Vec<[u8; 4]>> {
+        if !self.decoded_headers || self.pix_fmt != BmpPixelFormat::PAL8 {
+            return None;
+        }
+        let entries = self
+            .palette
+            .iter()
+            .take(self.palette_numbers)
+            .map(|x| [x.red, x.green, x.blue, 255])
+            .collect();
+
+        Some(entries)
+    }
+
     /// Return a reference to the ICC profile of the image if present or `None` if not
     ///
     ///
@@ -707,6 +756,12 @@ where
     /// Decode an encoded image into a buffer or return an error
     /// if something bad occurred
     ///
+    /// The buffer should be at least [`output_buf_size`](Self::output_buf_size) bytes long.
+    ///
+    /// Decoding into a buffer does not allocate, so this can be used where there is no
+    /// heap. The only allocation the decoder makes is copying an embedded ICC profile
+    /// when decoding headers of BMP v5 images that carry one.
+    ///
     /// Also see [`decode`](Self::decode) which allocates and decodes into buffer
     pub fn decode_into(&mut self, buf: &mut [u8]) -> Result<(), BmpDecoderErrors> {
         #[cfg(feature = "rgb_inverse")]
@@ -736,13 +791,18 @@ where
         let buf = &mut buf[0..output_size];
 
         if self.comp == BmpCompression::RLE4 || self.comp == BmpCompression::RLE8 {
-            let scanline_data = self.decode_rle()?;
+            // palette indices are decoded into the end of the output and expanded
+            // in place, so no scratch buffer is needed
+            let indices_start = output_size - self.width * self.height;
+
+            buf[indices_start..].fill(0);
+            self.decode_rle(&mut buf[indices_start..])?;
 
-            if self.pix_fmt == BmpPixelFormat::PAL8 {
-                self.expand_palette(&scanline_data, buf, false);
-                // flip, orientation isn't good
-                self.flip_vertically = true;
+            if self.pix_fmt == BmpPixelFormat::PAL8 && !self.keeps_indices() {
+                self.expand_palette_in_place(buf, indices_start);
             }
+            // rle rows are already top to bottom
+            self.flip_vertically = false;
         } else {
             match self.depth {
                 8 | 16 | 24 | 32 => {
@@ -933,29 +993,47 @@ where
                             "Bit Depths less than 8 must have a palette"
                         ));
                     }
-                    let width_bytes = ((self.width + 7) >> 3) << 3;
-
-                    // temporary location for an scaled down image width, bytes are read here
-                    // before expanding them in a separate pass
-                    let in_width_bytes = ((self.width * usize::from(self.depth)) + 7) / 8;
-                    let mut in_width_buf = vec![0_u8; in_width_bytes];
-
-                    let scanline_size = width_bytes * 3;
-                    let mut scanline_bytes = vec![0_u8; scanline_size];
-
-                    for out_bytes in
-                        buf.rchunks_exact_mut((3 + usize::from(self.is_alpha)) * self.width)
-                    {
-                        // read a whole width scanline before expanding
-                        self.bytes.read_exact_bytes(&mut in_width_buf)?;
-
-                        expand_bits_to_byte(
-                            self.depth as usize,
-                            true,
-                            &in_width_buf,
-                            &mut scanline_bytes
-                        );
-                        self.expand_palette(&scanline_bytes, out_bytes, true);
+                    let depth = usize::from(self.depth);
+                    let mask = (1_u8 << depth) - 1;
+                    let keep_indices = self.keeps_indices();
+                    let components = if keep_indices {
+                        1
+                    } else {
+                        3 + usize::from(self.is_alpha)
+                    };
+
+                    let in_width_bytes = (self.width * depth).div_ceil(8);
+                    // scanlines are read through a small buffer on the stack, so decoding
+                    // doesn't allocate
+                    let mut in_bytes = [0_u8; 64];
+
+                    for out_bytes in buf.rchunks_exact_mut(components * self.width) {
+                        let mut pixels = out_bytes.chunks_exact_mut(components);
+                        let mut remaining = in_width_bytes;
+
+                        while remaining > 0 {
+                            let in_bytes = &mut in_bytes[..remaining.min(64)];
+                            self.bytes.read_exact_bytes(in_bytes)?;
+                            remaining -= in_bytes.len();
+
+                            // indices are packed from the most significant bit
+                            for byte in in_bytes.iter() {
+                                for shift in (0..8 / depth).rev() {
+                                    let index = (byte >> (shift * depth)) & mask;
+
+                                    if let Some(pixel) = pixels.next() {
+                                        if keep_indices {
+                                            pixel[0] = index;
+                                            continue;
+                                        }
+                                        let entry = self.palette[usize::from(index)];
+                                        let color =
+                                            [entry.red, entry.green, entry.blue, entry.alpha];
+                                        pixel.copy_from_slice(&color[..components]);
+                                    }
+                                }
+                            }
+                        }
                     }
                     self.flip_vertically ^= true;
                 }
@@ -980,9 +1058,8 @@ where
             // usually assumes that
             //
             // This code undoes the effect of the above flips.
-            let length = self.width * self.pix_fmt.num_components();
+            let length = self.width * self.out_components();
 
-            let mut scanline = vec![0; length];
             let mid = buf.len() / 2;
             let (in_img_top, in_img_bottom) = buf.split_at_mut(mid);
 
@@ -990,18 +1067,13 @@ where
                 .chunks_exact_mut(length)
                 .zip(in_img_bottom.rchunks_exact_mut(length))
             {
-                // write in dim to scanlines
-                scanline.copy_from_slice(in_dim);
-                // write out dim to in dim
-                in_dim.copy_from_slice(out_dim);
-                // copy previous scanline to in dim
-                out_dim.copy_from_slice(&scanline);
+                in_dim.swap_with_slice(out_dim);
             }
         }
         // If we are to preserve BGRA, some paths may not honor that, (i think palette)
         // paths that honor that set `self.image_in_bgra to be true, if that is true
         // we know that the image is in bgra (which only matters if we are to preserve bgra feature)
-        if PRESERVE_BGRA && !self.image_in_bgra {
+        if PRESERVE_BGRA && !self.image_in_bgra && !self.keeps_indices() {
             // image is not in bgr(a), convert it to bgr(a)
             // depends on the colorspace. We should only have 3 or 4 components
             match self.pix_fmt.into_colorspace().num_components() {
@@ -1023,53 +1095,18 @@ where
         Ok(())
     }
 
-    /// Expand paletted bmp images to full version
-    ///
+    /// Expand palette indices stored at the end of `buf` to colors, in place
     ///
-    /// # Arguments
-    /// - in_bytes: Palette entry indices
-    /// - buf: Where to write the bytes to
-    /// - unpad: Whether to take padding bytes into account, this is important since
-    ///  callers like RLE bytes do not take padding into account but for non-rle data
-    /// we must take it into account
-    ///
-    fn expand_palette(&self, in_bytes: &[u8], buf: &mut [u8], unpad: bool) {
-        let palette: &[PaletteEntry; 256] = &self.palette[0..256].try_into().unwrap();
-
-        let pad = usize::from(unpad) * (((-(self.width as i32)) as u32) & 3) as usize;
-
-        if self.is_alpha {
-            // bmp rounds up each line to be a multiple of 4, padding the end if necessary
-            // remove padding bytes
+    /// Indices start at `indices_start` and are expanded front to back, each color
+    /// is written before the index it overwrites is read so no scratch space is needed
+    fn expand_palette_in_place(&self, buf: &mut [u8], indices_start: usize) {
+        let components = 3 + usize::from(self.is_alpha);
 
-            for (out_stride, in_stride) in buf
-                .rchunks_exact_mut(self.width * 4)
-                .take(self.height)
-                .zip(in_bytes.chunks_exact(self.width + pad))
-            {
-                for (pal_byte, chunks) in in_stride.iter().zip(out_stride.chunks_exact_mut(4)) {
-                    let entry = palette[usize::from(*pal_byte)];
-
-                    chunks[0] = entry.red;
-                    chunks[1] = entry.green;
-                    chunks[2] = entry.blue;
-                    chunks[3] = entry.alpha;
-                }
-            }
-        } else {
-            for (out_stride, in_stride) in buf
-                .rchunks_exact_mut(self.width * 3)
-                .take(self.height)
-                .zip(in_bytes.chunks_exact(self.width + pad))
-            {
-                for (pal_byte, chunks) in in_stride.iter().zip(out_stride.chunks_exact_mut(3)) {
-                    let entry = palette[usize::from(*pal_byte)];
+        for i in 0..buf.len() - indices_start {
+            let entry = self.palette[usize::from(buf[indices_start + i])];
+            let color = [entry.red, entry.green, entry.blue, entry.alpha];
 
-                    chunks[0] = entry.red;
-                    chunks[1] = entry.green;
-                    chunks[2] = entry.blue;
-                }
-            }
+            buf[i * components..(i + 1) * components].copy_from_slice(&color[..components]);
         }
     }
     // RUST borrowing rules
@@ -1077,11 +1114,18 @@ where
         &mut self, buf: &mut [u8], unpad: bool
     ) -> Result<(), ZByteIoError> {
         //let in_bytes = self.bytes.remaining_bytes()?;
-        let palette: &[PaletteEntry; 256] = &self.palette[0..256].try_into().unwrap();
+        let palette = &self.palette;
 
         let pad = usize::from(unpad) * (((-(self.width as i32)) as u32) & 3) as usize;
 
-        if self.is_alpha {
+        if self.keeps_indices() {
+            for out_stride in buf.rchunks_exact_mut(self.width).take(self.height) {
+                for index in out_stride.iter_mut() {
+                    *index = self.bytes.read_u8();
+                }
+                self.bytes.skip(pad)?;
+            }
+        } else if self.is_alpha {
             // bmp rounds up each line to be a multiple of 4, padding the end if necessary
             // remove padding bytes
 
@@ -1113,7 +1157,11 @@ where
         }
         Ok(())
     }
-    fn decode_rle(&mut self) -> Result<Vec<u8>, BmpDecoderErrors> {
+    /// Decode run length encoded palette indices into `pixels`, one byte per index
+    /// with rows from top to bottom
+    ///
+    /// `pixels` must hold `width * height` zeroed bytes
+    fn decode_rle(&mut self, pixels: &mut [u8]) -> Result<(), BmpDecoderErrors> {
         // Docs are from imagine crate
         //
         // * If the first byte is **non-zero** it's the number of times that the second
@@ -1135,12 +1183,6 @@ where
         //
         // Code is from ffmpeg
 
-        // Allocate space for our RLE storage
-
-        // for depths less than 8(4 only), allocate full space for the expanded bits
-        let depth = if self.depth < 8 { 8 } else { self.depth };
-        let mut pixels = vec![0; ((self.width * self.height * usize::from(depth)) + 7) >> 3];
-
         //let rt = temp_scanline.len();
         let mut line = (self.height - 1) as i32;
         let mut output = &mut pixels[(line as usize) * self.width..];
@@ -1181,7 +1223,7 @@ where
                         continue;
                     } else if stream_byte == 1 {
                         // decode is done
-                        return Ok(pixels);
+                        return Ok(());
                     } else if stream_byte == 2 {
                         // reposition frame decode coordinates
                         stream_byte = self.bytes.read_u8();
@@ -1246,7 +1288,7 @@ where
                     }
                 }
             }
-            Ok(pixels)
+            Ok(())
         } else {
             let (mut p1, mut p2);
             // loop until no more bytes are left
@@ -1261,7 +1303,7 @@ where
                         if line < 0 {
                             return if self.bytes.get_u16_be() == 1 {
                                 // end of picture
-                                Ok(pixels)
+                                Ok(())
                             } else {
                                 // panic!();
                                 let msg = "Next line is beyond picture bounds";
@@ -1282,7 +1324,7 @@ where
                         continue;
                     } else if p2 == 1 {
                         // end of picture
-                        return Ok(pixels);
+                        return Ok(());
                     } else if p2 == 2 {
                         // skip
                         p1 = self.bytes.read_u8();
@@ -1406,7 +1448,7 @@ where
                 }
             }
             warn!("RLE warning, no end of picture code");
-            Ok(pixels)
+            Ok(())
         }
     }
 }
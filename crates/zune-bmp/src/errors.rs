@@ -7,7 +7,7 @@
  */
 
 use alloc::string::String;
-use core::fmt::{Debug, Formatter};
+use core::fmt::{Debug, Display, Formatter};
 
 use zune_core::bytestream::ZByteIoError;
 
@@ -66,6 +66,14 @@ impl Debug for BmpDecoderErrors {
     }
 }
 
+impl Display for BmpDecoderErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl core::error::Error for BmpDecoderErrors {}
+
 impl From<ZByteIoError> for BmpDecoderErrors {
     fn from(value: ZByteIoError) -> Self {
         BmpDecoderErrors::IoErrors(value)
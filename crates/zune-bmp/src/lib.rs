@@ -84,4 +84,3 @@ pub use crate::errors::BmpDecoderErrors;
 mod common;
 mod decoder;
 mod errors;
-mod utils;
@@ -0,0 +1,48 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Check that palette indices decode to the same colors as expanded images
+use std::path::Path;
+
+use zune_bmp::BmpDecoder;
+use zune_core::bytestream::ZCursor;
+use zune_core::colorspace::ColorSpace;
+
+#[test]
+fn keep_indices_matches_expanded() {
+    let images = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../test-images/bmp");
+
+    let names = [
+        "pal1.bmp",
+        "pal2.bmp",
+        "pal4.bmp",
+        "pal4rle.bmp",
+        "pal8.bmp",
+        "pal8rle.bmp",
+        "pal8topdown.bmp",
+        "pal8os2.bmp",
+        "pal8w126.bmp"
+    ];
+    for name in names {
+        let data = std::fs::read(images.join(name)).unwrap();
+
+        let mut decoder = BmpDecoder::new(ZCursor::new(&data));
+        decoder.keep_indices(true);
+        let indices = decoder.decode().unwrap();
+        assert_eq!(decoder.colorspace(), Some(ColorSpace::Luma), "{name}");
+
+        let palette = decoder.palette().unwrap();
+        let expanded: Vec<u8> = indices
+            .iter()
+            .flat_map(|x| palette[usize::from(*x)][..3].to_vec())
+            .collect();
+
+        let colors = BmpDecoder::new(ZCursor::new(&data)).decode().unwrap();
+        assert_eq!(expanded, colors, "{name}");
+    }
+}
@@ -0,0 +1,64 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Check that decoding into a caller provided buffer doesn't allocate
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::path::Path;
+
+use zune_bmp::BmpDecoder;
+use zune_core::bytestream::ZCursor;
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|x| x.set(x.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[test]
+fn decode_into_does_not_allocate() {
+    let images = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../test-images/bmp");
+
+    for name in [
+        "pal1.bmp",
+        "pal4.bmp",
+        "pal4rle.bmp",
+        "pal8.bmp",
+        "pal8rle.bmp",
+        "pal8topdown.bmp",
+        "rgb16-565.bmp",
+        "rgb24.bmp",
+        "rgba32-1.bmp"
+    ] {
+        let data = std::fs::read(images.join(name)).unwrap();
+        let mut decoder = BmpDecoder::new(ZCursor::new(&data));
+        decoder.decode_headers().unwrap();
+
+        let mut pixels = vec![0; decoder.output_buf_size().unwrap()];
+
+        let before = ALLOCATIONS.with(Cell::get);
+        decoder.decode_into(&mut pixels).unwrap();
+        let after = ALLOCATIONS.with(Cell::get);
+
+        assert_eq!(before, after, "{name} allocated while decoding");
+    }
+}
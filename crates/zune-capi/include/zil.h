@@ -152,44 +152,48 @@ typedef enum ZStatusType {
   /**
    * Everything is okay, operation succeeded
    */
-  Ok = 0,
+  ZilOk = 0,
   /**
    * The buffer passed to a function wasn't enough to
    * store the results
    */
-  NotEnoughSpaceInDest,
+  ZilNotEnoughSpaceInDest,
   /**
    * An error that doesn't fit into a specific genre
    */
-  Generic,
+  ZilGeneric,
   /**
    * An error originating from decoding
    */
-  DecodeErrors,
+  ZilDecodeErrors,
   /**
    * An error originating from Input output errors
    */
-  IoErrors,
+  ZilIoErrors,
   /**
    * Malloc failed
    */
-  MallocFailed,
+  ZilMallocFailed,
   /**
    * Status is null, indicates the passed status value is null
    * useful when we have been asked for status code but
    * passed a null status
    */
-  NullStatus,
+  ZilNullStatus,
   /**
    * Image is null
    *
    * An operation expecting a non_null image got a null image
    */
-  ImageIsNull,
+  ZilImageIsNull,
   /**
    * Image operation failed
    */
-  ImageOperationError,
+  ZilImageOperationError,
+  /**
+   * Image encoding failed
+   */
+  ZilEncodeErrors,
 } ZStatusType;
 
 /**
@@ -197,6 +201,21 @@ typedef enum ZStatusType {
  */
 typedef struct Image Image;
 
+/**
+ * Pipeline, batch image processing
+ *
+ * A pipeline provides an idiomatic way to do batch image processing
+ * it can load multiple images (by queueing decoders) and batch apply an operation
+ * to all the images and then encode images to multiple specified format.
+ *
+ * A pipeline accepts anything that implements [IntoImage](crate::traits::IntoImage) and
+ * it has to own the image for the duration of it's lifetime, but can return references to it
+ * via  [`images`](crate::pipelines::Pipeline::images) and
+ *  [`images_mut`](crate::pipelines::Pipeline::images_mut)
+ *
+ */
+typedef struct Pipeline Pipeline;
+
 /**
  * A status indicator that tells you more about things that went wrong
  *
@@ -222,6 +241,8 @@ typedef struct ZStatus {
 
 typedef struct Image ZImage;
 
+typedef struct Pipeline ZPipeline;
+
 /**
  * Image metadata details
  */
@@ -489,37 +510,37 @@ void zil_imgproc_crop(ZImage *image,
 void zil_imgproc_exposure(ZImage *image, float exposure, float black_point, struct ZStatus *status);
 
 /**
- * Flip an image by reflecting pixels on its x-axis
+ * Flop an image by reflecting pixels on its y-axis
  *
  * \code
  * old image     new image
  * ┌─────────┐   ┌──────────┐
- * │a b c d e│   │j i h g f │
- * │f g h i j│   │e d c b a │
+ * │a b c d e│   │e d b c a │
+ * │f g h i j│   │j i h g f │
  * └─────────┘   └──────────┘
+ *
  * \endcode
  *
- * \param image: Image to flip
+ * \param image: Image to flop
  * \param status: Image execution reporter
  */
-void zil_imgproc_flip(ZImage *image, struct ZStatus *status);
+void zil_imgproc_flip_horizontal(ZImage *image, struct ZStatus *status);
 
 /**
- * Flop an image by reflecting pixels on its y-axis
+ * Flip an image by reflecting pixels on its x-axis
  *
  * \code
  * old image     new image
  * ┌─────────┐   ┌──────────┐
- * │a b c d e│   │e d b c a │
- * │f g h i j│   │j i h g f │
+ * │a b c d e│   │j i h g f │
+ * │f g h i j│   │e d c b a │
  * └─────────┘   └──────────┘
- *
  * \endcode
  *
- * \param image: Image to flop
+ * \param image: Image to flip
  * \param status: Image execution reporter
  */
-void zil_imgproc_flop(ZImage *image, struct ZStatus *status);
+void zil_imgproc_flip_mirror_x_axis(ZImage *image, struct ZStatus *status);
 
 /**
  * Gamma adjust an image
@@ -720,6 +741,120 @@ void zil_imread_into(const char *file,
  */
 void *zil_malloc(size_t size);
 
+/**
+ * Add a contrast adjustment to the pipeline, see `zil_imgproc_adjust_contrast`
+ *
+ * if `pipeline` is null, nothing happens, as for the other `zil_pipeline_add_` functions
+ */
+void zil_pipeline_add_adjust_contrast(ZPipeline *pipeline, float contrast);
+
+/**
+ * Add a brightness adjustment to the pipeline, see `zil_imgproc_brighten`
+ */
+void zil_pipeline_add_brighten(ZPipeline *pipeline, float value);
+
+/**
+ * Add a depth conversion to the pipeline, see `zil_imgproc_change_depth`
+ */
+void zil_pipeline_add_change_depth(ZPipeline *pipeline, enum ZImageDepth to);
+
+/**
+ * Add a colorspace conversion to the pipeline, see `zil_imgproc_convert_colorspace`
+ */
+void zil_pipeline_add_convert_colorspace(ZPipeline *pipeline, enum ZImageColorspace to);
+
+/**
+ * Add a crop to the pipeline, see `zil_imgproc_crop`
+ */
+void zil_pipeline_add_crop(ZPipeline *pipeline,
+                           size_t new_width,
+                           size_t new_height,
+                           size_t x,
+                           size_t y);
+
+/**
+ * Add an exposure adjustment to the pipeline, see `zil_imgproc_exposure`
+ */
+void zil_pipeline_add_exposure(ZPipeline *pipeline, float exposure, float black_point);
+
+/**
+ * Add a flip on the y-axis to the pipeline, see `zil_imgproc_flip_horizontal`
+ */
+void zil_pipeline_add_flip_horizontal(ZPipeline *pipeline);
+
+/**
+ * Add a flip on the x-axis to the pipeline, see `zil_imgproc_flip_mirror_x_axis`
+ */
+void zil_pipeline_add_flip_mirror_x_axis(ZPipeline *pipeline);
+
+/**
+ * Add a gamma adjustment to the pipeline, see `zil_imgproc_gamma`
+ */
+void zil_pipeline_add_gamma(ZPipeline *pipeline, float gamma);
+
+/**
+ * Add a gaussian blur to the pipeline, see `zil_imgproc_gaussian_blur`
+ */
+void zil_pipeline_add_gaussian_blur(ZPipeline *pipeline, float sigma);
+
+/**
+ * Add a pixel inversion to the pipeline, see `zil_imgproc_invert`
+ */
+void zil_pipeline_add_invert(ZPipeline *pipeline);
+
+/**
+ * Add a median blur to the pipeline, see `zil_imgproc_median_blur`
+ */
+void zil_pipeline_add_median_blur(ZPipeline *pipeline, size_t radius);
+
+/**
+ * Add a contrast stretch to the pipeline, see `zil_imgproc_stretch_contrast`
+ */
+void zil_pipeline_add_stretch_contrast(ZPipeline *pipeline, float lower, float higher);
+
+/**
+ * Add a transpose to the pipeline, see `zil_imgproc_transpose`
+ */
+void zil_pipeline_add_transpose(ZPipeline *pipeline);
+
+/**
+ * Run the operations of the pipeline, in the order they were added, on an image
+ *
+ * Consecutive point operations, e.g. brighten, gamma and invert, are fused and run in
+ * a single pass over the image.
+ *
+ * The pipeline isn't changed, so it can run on other images afterwards
+ *
+ * \param pipeline: Non-null pipeline
+ * \param image: Non-null image, modified in place
+ * \param status: Reports whether the operations succeeded, should not be null
+ */
+void zil_pipeline_execute(const ZPipeline *pipeline, ZImage *image, struct ZStatus *status);
+
+/**
+ * Free a pipeline and the operations added to it
+ *
+ * \param pipeline: A pipeline created by `zil_pipeline_new`, can be null
+ */
+void zil_pipeline_free(ZPipeline *pipeline);
+
+/**
+ * Create a new pipeline with no operations
+ *
+ * Free it with `zil_pipeline_free`
+ */
+ZPipeline *zil_pipeline_new(void);
+
+/**
+ * Set the number of threads the operations of the pipeline run on
+ *
+ * A value of `0`, the default, uses the library's thread pool
+ *
+ * \param pipeline: A pipeline, if null nothing happens
+ * \param threads: Number of threads
+ */
+void zil_pipeline_set_threads(ZPipeline *pipeline, size_t threads);
+
 /**
  * Read image headers from a file and return common information such as width, height depth and colorspace
  *
@@ -808,6 +943,26 @@ enum ZImageColorspace zil_zimg_colorspace(ZImage *image, struct ZStatus *status)
  */
 enum ZImageDepth zil_zimg_depth(ZImage *image, struct ZStatus *status);
 
+/**
+ * \brief Encode an image into memory
+ *
+ * \param image: The image to encode
+ *
+ * \param format: The image format to use for encoding
+ *
+ * \param output_size: After encoding, the value stored will be the number of encoded bytes,
+ * can be null
+ *
+ * \param status: Image operation status, query this to know if the operation succeeded
+ *
+ * \returns A pointer to the encoded bytes, allocated with `zil_malloc` and freed with `zil_free`,
+ * or null if the image couldn't be encoded
+ */
+uint8_t *zil_zimg_encode(const ZImage *image,
+                         enum ZImageFormat format,
+                         size_t *output_size,
+                         struct ZStatus *status);
+
 /**
  * Free an image
  *
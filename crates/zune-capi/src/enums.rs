@@ -189,6 +189,6 @@ impl From<ColorSpace> for ZImageColorspace {
 ///
 /// \returns ImageDepth with a value of ImageDepth::Unknown
 #[no_mangle]
-extern "C" fn zil_imdepth_new() -> ZImageDepth {
+pub extern "C" fn zil_imdepth_new() -> ZImageDepth {
     ZImageDepth::ZilUnknownDepth
 }
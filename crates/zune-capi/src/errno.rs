@@ -31,8 +31,9 @@ pub enum ZStatusType {
     /// An operation expecting a non_null image got a null image
     ZilImageIsNull,
     /// Image operation failed
-    ZilImageOperationError // Image encoding failed
-                           //ImageEncodingFailed
+    ZilImageOperationError,
+    /// Image encoding failed
+    ZilEncodeErrors
 }
 
 /// A status indicator that tells you more about things that went wrong
@@ -160,8 +161,7 @@ pub extern "C" fn zil_status_message(status: *const ZStatus) -> *const c_char {
 #[no_mangle]
 pub extern "C" fn zil_status_free(status: *mut ZStatus) {
     if !status.is_null() {
-        unsafe { zil_free((*status).message.cast()) }
-        // free object
+        // free object, this frees the message
         unsafe { status.drop_in_place() }
 
         // free memory holding it
@@ -11,12 +11,12 @@ use zune_image::errors::ImageErrors;
 use crate::enums::{ZImageColorspace, ZImageDepth, ZImageFormat};
 use crate::errno::ZStatusType::{ZilDecodeErrors, ZilImageIsNull, ZilIoErrors};
 use crate::errno::{ZStatus, ZStatusType};
-use crate::utils::zil_free;
+use crate::utils::{zil_free, zil_malloc};
 use crate::ZImage;
 
 /// Get the image width from the image
 #[no_mangle]
-extern "C" fn zil_zimg_width(image: *mut ZImage, status: *mut ZStatus) -> usize {
+pub extern "C" fn zil_zimg_width(image: *mut ZImage, status: *mut ZStatus) -> usize {
     if image.is_null() {
         if !status.is_null() {
             unsafe { *status = ZStatus::new("Image null", ZilImageIsNull) };
@@ -28,7 +28,7 @@ extern "C" fn zil_zimg_width(image: *mut ZImage, status: *mut ZStatus) -> usize
 
 /// Get image height from image
 #[no_mangle]
-extern "C" fn zil_zimg_height(image: *mut ZImage, status: *mut ZStatus) -> usize {
+pub extern "C" fn zil_zimg_height(image: *mut ZImage, status: *mut ZStatus) -> usize {
     if image.is_null() {
         if !status.is_null() {
             unsafe { *status = ZStatus::new("Image null", ZilImageIsNull) };
@@ -40,7 +40,7 @@ extern "C" fn zil_zimg_height(image: *mut ZImage, status: *mut ZStatus) -> usize
 
 /// Get image depth from image
 #[no_mangle]
-extern "C" fn zil_zimg_depth(image: *mut ZImage, status: *mut ZStatus) -> ZImageDepth {
+pub extern "C" fn zil_zimg_depth(image: *mut ZImage, status: *mut ZStatus) -> ZImageDepth {
     if image.is_null() {
         if !status.is_null() {
             unsafe { *status = ZStatus::new("Image null", ZilImageIsNull) };
@@ -52,7 +52,9 @@ extern "C" fn zil_zimg_depth(image: *mut ZImage, status: *mut ZStatus) -> ZImage
 
 /// Get image colorspace from image
 #[no_mangle]
-extern "C" fn zil_zimg_colorspace(image: *mut ZImage, status: *mut ZStatus) -> ZImageColorspace {
+pub extern "C" fn zil_zimg_colorspace(
+    image: *mut ZImage, status: *mut ZStatus
+) -> ZImageColorspace {
     if image.is_null() {
         if !status.is_null() {
             unsafe { *status = ZStatus::new("Image null", ZilImageIsNull) };
@@ -70,7 +72,7 @@ extern "C" fn zil_zimg_colorspace(image: *mut ZImage, status: *mut ZStatus) -> Z
 ///
 /// \returns the number of bytes needed to store the image or 0 in case image is null
 #[no_mangle]
-extern "C" fn zil_zimg_get_out_buffer_size(image: *mut ZImage, status: *mut ZStatus) -> usize {
+pub extern "C" fn zil_zimg_get_out_buffer_size(image: *mut ZImage, status: *mut ZStatus) -> usize {
     if image.is_null() {
         if !status.is_null() {
             unsafe { *status = ZStatus::new("Image null", ZilImageIsNull) };
@@ -322,13 +324,70 @@ pub extern "C" fn zil_zimg_write_to_disk_with_format(
     }
 }
 
+/// \brief Encode an image into memory
+///
+/// \param image: The image to encode
+///
+/// \param format: The image format to use for encoding
+///
+/// \param output_size: After encoding, the value stored will be the number of encoded bytes,
+/// can be null
+///
+/// \param status: Image operation status, query this to know if the operation succeeded
+///
+/// \returns A pointer to the encoded bytes, allocated with `zil_malloc` and freed with `zil_free`,
+/// or null if the image couldn't be encoded
+#[no_mangle]
+pub extern "C" fn zil_zimg_encode(
+    image: *const ZImage, format: ZImageFormat, output_size: *mut usize, status: *mut ZStatus
+) -> *mut u8 {
+    if image.is_null() {
+        if !status.is_null() {
+            unsafe { *status = ZStatus::new("Image null", ZilImageIsNull) };
+        }
+        return ptr::null_mut();
+    }
+    let image = unsafe { &*image };
+
+    let encoded = match image.write_to_vec(format.to_format()) {
+        Ok(encoded) => encoded,
+        Err(e) => {
+            if !status.is_null() {
+                unsafe { *status = ZStatus::new(e.to_string(), ZStatusType::ZilEncodeErrors) };
+            }
+            return ptr::null_mut();
+        }
+    };
+    let output = unsafe { zil_malloc(encoded.len()) }.cast::<u8>();
+
+    if output.is_null() {
+        if !status.is_null() {
+            let msg = format!(
+                "Malloc failed to allocate buffer with size of {}",
+                encoded.len()
+            );
+            unsafe { *status = ZStatus::new(msg, ZStatusType::ZilMallocFailed) };
+        }
+        return ptr::null_mut();
+    }
+    unsafe { output.copy_from_nonoverlapping(encoded.as_ptr(), encoded.len()) };
+
+    if !output_size.is_null() {
+        unsafe { *output_size = encoded.len() };
+    }
+    if !status.is_null() {
+        unsafe { *status = ZStatus::okay() };
+    }
+    output
+}
+
 /// Create a new copy of the image independent from the previous
 /// one and return it
 ///
 /// \param image: The image to clone
 /// \returns: A fresh new copy of the image if everything goes well, otherwise null to indicate faliure
 #[no_mangle]
-extern "C" fn zil_zimg_clone(image: *const ZImage) -> *mut ZImage {
+pub extern "C" fn zil_zimg_clone(image: *const ZImage) -> *mut ZImage {
     if image.is_null() {
         return ptr::null_mut();
     }
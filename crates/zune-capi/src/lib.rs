@@ -1,16 +1,19 @@
 //! C bindings to zune-image
 
 use zune_image::image::Image;
+use zune_image::pipelines::Pipeline;
 
 mod enums;
 mod errno;
 mod image;
 mod improc;
 mod imread;
+mod pipeline;
 mod structs;
 mod utils;
 
 pub type ZImage = Image;
+pub type ZPipeline = Pipeline;
 #[no_mangle]
 #[cfg(target_os = "windows")]
 pub extern "C" fn __chkstk() {}
@@ -72,3 +75,49 @@ fn test_status_works() {
         zil_free(c.cast());
     }
 }
+
+#[test]
+fn test_pipeline_and_encode() {
+    use crate::enums::{ZImageColorspace, ZImageFormat};
+    use crate::errno::{zil_status_free, zil_status_new, zil_status_ok};
+    use crate::image::{zil_zimg_encode, zil_zimg_free, zil_zimg_from_u8};
+    use crate::pipeline::*;
+    use crate::utils::zil_free;
+
+    let pixels = [10_u8; 4 * 4 * 3];
+
+    let image = zil_zimg_from_u8(
+        pixels.as_ptr(),
+        pixels.len(),
+        4,
+        4,
+        ZImageColorspace::ZilRGB
+    );
+    let pipeline = zil_pipeline_new();
+    zil_pipeline_add_invert(pipeline);
+    zil_pipeline_add_crop(pipeline, 2, 2, 0, 0);
+
+    unsafe {
+        let status = zil_status_new();
+
+        // the pipeline can run any number of times
+        zil_pipeline_execute(pipeline, image, status);
+        zil_pipeline_execute(pipeline, image, status);
+        assert!(zil_status_ok(status));
+        assert_eq!((*image).dimensions(), (2, 2));
+        assert_eq!((*image).flatten_to_u8()[0][0], 10);
+
+        zil_pipeline_execute(pipeline, std::ptr::null_mut(), status);
+        assert!(!zil_status_ok(status));
+
+        let mut size = 0;
+        let encoded = zil_zimg_encode(image, ZImageFormat::ZilQOI, &mut size, status);
+        assert!(zil_status_ok(status));
+        assert_eq!(&std::slice::from_raw_parts(encoded, size)[..4], b"qoif");
+
+        zil_free(encoded.cast());
+        zil_status_free(status);
+    }
+    zil_pipeline_free(pipeline);
+    zil_zimg_free(image);
+}
@@ -0,0 +1,193 @@
+//! Pipelines, a list of operations run one after the other on images
+//!
+//! A pipeline is created once, operations are added to it and it can then
+//! run on any number of images.
+use std::ffi::c_float;
+
+use zune_image::core_filters::colorspace::ColorspaceConv;
+use zune_image::core_filters::depth::Depth;
+use zune_image::pipelines::Pipeline;
+use zune_image::traits::OperationsTrait;
+use zune_imageprocs::brighten::Brighten;
+use zune_imageprocs::contrast::Contrast;
+use zune_imageprocs::crop::Crop;
+use zune_imageprocs::exposure::Exposure;
+use zune_imageprocs::flip::{Flip, FlipDirection};
+use zune_imageprocs::gamma::Gamma;
+use zune_imageprocs::gaussian_blur::GaussianBlur;
+use zune_imageprocs::invert::Invert;
+use zune_imageprocs::median::Median;
+use zune_imageprocs::stretch_contrast::StretchContrast;
+use zune_imageprocs::transpose::Transpose;
+
+use crate::enums::{ZImageColorspace, ZImageDepth};
+use crate::errno::{ZStatus, ZStatusType};
+use crate::{ZImage, ZPipeline};
+
+fn add_operation<T>(pipeline: *mut ZPipeline, operation: T)
+where
+    T: OperationsTrait + 'static
+{
+    if pipeline.is_null() {
+        return;
+    }
+    unsafe { (*pipeline).chain_operations(Box::new(operation)) };
+}
+
+/// Create a new pipeline with no operations
+///
+/// Free it with `zil_pipeline_free`
+#[no_mangle]
+pub extern "C" fn zil_pipeline_new() -> *mut ZPipeline {
+    Box::into_raw(Box::new(Pipeline::new()))
+}
+
+/// Free a pipeline and the operations added to it
+///
+/// \param pipeline: A pipeline created by `zil_pipeline_new`, can be null
+#[no_mangle]
+pub extern "C" fn zil_pipeline_free(pipeline: *mut ZPipeline) {
+    if !pipeline.is_null() {
+        drop(unsafe { Box::from_raw(pipeline) });
+    }
+}
+
+/// Set the number of threads the operations of the pipeline run on
+///
+/// A value of `0`, the default, uses the library's thread pool
+///
+/// \param pipeline: A pipeline, if null nothing happens
+/// \param threads: Number of threads
+#[no_mangle]
+pub extern "C" fn zil_pipeline_set_threads(pipeline: *mut ZPipeline, threads: usize) {
+    if !pipeline.is_null() {
+        unsafe { (*pipeline).set_threads(threads) };
+    }
+}
+
+/// Run the operations of the pipeline, in the order they were added, on an image
+///
+/// Consecutive point operations, e.g. brighten, gamma and invert, are fused and run in
+/// a single pass over the image.
+///
+/// The pipeline isn't changed, so it can run on other images afterwards
+///
+/// \param pipeline: Non-null pipeline
+/// \param image: Non-null image, modified in place
+/// \param status: Reports whether the operations succeeded, should not be null
+#[no_mangle]
+pub extern "C" fn zil_pipeline_execute(
+    pipeline: *const ZPipeline, image: *mut ZImage, status: *mut ZStatus
+) {
+    if status.is_null() {
+        return;
+    }
+    if pipeline.is_null() || image.is_null() {
+        let msg = if pipeline.is_null() { "Pipeline is null" } else { "Image is null" };
+        unsafe { *status = ZStatus::new(msg, ZStatusType::ZilImageIsNull) };
+        return;
+    }
+    let pipeline = unsafe { &*pipeline };
+    let image = unsafe { &mut *image };
+
+    match pipeline.apply(std::slice::from_mut(image)) {
+        Ok(()) => unsafe { *status = ZStatus::okay() },
+        Err(err) => unsafe {
+            *status = ZStatus::new(err.to_string(), ZStatusType::ZilImageOperationError)
+        }
+    }
+}
+
+/// Add a contrast adjustment to the pipeline, see `zil_imgproc_adjust_contrast`
+///
+/// if `pipeline` is null, nothing happens, as for the other `zil_pipeline_add_` functions
+#[no_mangle]
+pub extern "C" fn zil_pipeline_add_adjust_contrast(pipeline: *mut ZPipeline, contrast: c_float) {
+    add_operation(pipeline, Contrast::new(contrast));
+}
+
+/// Add a brightness adjustment to the pipeline, see `zil_imgproc_brighten`
+#[no_mangle]
+pub extern "C" fn zil_pipeline_add_brighten(pipeline: *mut ZPipeline, value: f32) {
+    add_operation(pipeline, Brighten::new(value));
+}
+
+/// Add a depth conversion to the pipeline, see `zil_imgproc_change_depth`
+#[no_mangle]
+pub extern "C" fn zil_pipeline_add_change_depth(pipeline: *mut ZPipeline, to: ZImageDepth) {
+    add_operation(pipeline, Depth::new(to.to_depth()));
+}
+
+/// Add a colorspace conversion to the pipeline, see `zil_imgproc_convert_colorspace`
+#[no_mangle]
+pub extern "C" fn zil_pipeline_add_convert_colorspace(
+    pipeline: *mut ZPipeline, to: ZImageColorspace
+) {
+    add_operation(pipeline, ColorspaceConv::new(to.to_colorspace()));
+}
+
+/// Add a crop to the pipeline, see `zil_imgproc_crop`
+#[no_mangle]
+pub extern "C" fn zil_pipeline_add_crop(
+    pipeline: *mut ZPipeline, new_width: usize, new_height: usize, x: usize, y: usize
+) {
+    add_operation(pipeline, Crop::new(new_width, new_height, x, y));
+}
+
+/// Add an exposure adjustment to the pipeline, see `zil_imgproc_exposure`
+#[no_mangle]
+pub extern "C" fn zil_pipeline_add_exposure(
+    pipeline: *mut ZPipeline, exposure: f32, black_point: f32
+) {
+    add_operation(pipeline, Exposure::new(exposure, black_point));
+}
+
+/// Add a flip on the x-axis to the pipeline, see `zil_imgproc_flip_mirror_x_axis`
+#[no_mangle]
+pub extern "C" fn zil_pipeline_add_flip_mirror_x_axis(pipeline: *mut ZPipeline) {
+    add_operation(pipeline, Flip::new(FlipDirection::MirrorXAxis));
+}
+
+/// Add a flip on the y-axis to the pipeline, see `zil_imgproc_flip_horizontal`
+#[no_mangle]
+pub extern "C" fn zil_pipeline_add_flip_horizontal(pipeline: *mut ZPipeline) {
+    add_operation(pipeline, Flip::new(FlipDirection::Horizontal));
+}
+
+/// Add a gamma adjustment to the pipeline, see `zil_imgproc_gamma`
+#[no_mangle]
+pub extern "C" fn zil_pipeline_add_gamma(pipeline: *mut ZPipeline, gamma: f32) {
+    add_operation(pipeline, Gamma::new(gamma));
+}
+
+/// Add a gaussian blur to the pipeline, see `zil_imgproc_gaussian_blur`
+#[no_mangle]
+pub extern "C" fn zil_pipeline_add_gaussian_blur(pipeline: *mut ZPipeline, sigma: f32) {
+    add_operation(pipeline, GaussianBlur::new(sigma));
+}
+
+/// Add a pixel inversion to the pipeline, see `zil_imgproc_invert`
+#[no_mangle]
+pub extern "C" fn zil_pipeline_add_invert(pipeline: *mut ZPipeline) {
+    add_operation(pipeline, Invert);
+}
+
+/// Add a median blur to the pipeline, see `zil_imgproc_median_blur`
+#[no_mangle]
+pub extern "C" fn zil_pipeline_add_median_blur(pipeline: *mut ZPipeline, radius: usize) {
+    add_operation(pipeline, Median::new(radius));
+}
+
+/// Add a contrast stretch to the pipeline, see `zil_imgproc_stretch_contrast`
+#[no_mangle]
+pub extern "C" fn zil_pipeline_add_stretch_contrast(
+    pipeline: *mut ZPipeline, lower: f32, higher: f32
+) {
+    add_operation(pipeline, StretchContrast::new(lower, higher));
+}
+
+/// Add a transpose to the pipeline, see `zil_imgproc_transpose`
+#[no_mangle]
+pub extern "C" fn zil_pipeline_add_transpose(pipeline: *mut ZPipeline) {
+    add_operation(pipeline, Transpose::new());
+}
@@ -40,6 +40,12 @@ pub enum BitDepth {
     ///
     /// Uses f32 to store data
     Float32,
+    /// Floating point 16 bit data (half floats), range is 0.0 to 1.0
+    ///
+    /// Half the memory of [`Float32`](Self::Float32) for HDR images, which are often
+    /// half floats to begin with. Rust has no stable `f16` type, so `zune-image` stores
+    /// the bits of the values as its own `F16` type.
+    Float16,
     /// Bit depth information is unknown
     Unknown
 }
@@ -60,7 +66,10 @@ pub enum BitType {
     U16,
     /// Images represented using a [`f32`] as their
     /// underlying pixel storage
-    F32
+    F32,
+    /// Images represented using half floats as their
+    /// underlying pixel storage, see [`BitDepth::Float16`]
+    F16
 }
 
 impl BitType {
@@ -69,7 +78,8 @@ impl BitType {
         match self {
             BitType::U8 => BitDepth::Eight,
             BitType::U16 => BitDepth::Sixteen,
-            BitType::F32 => BitDepth::Float32
+            BitType::F32 => BitDepth::Float32,
+            BitType::F16 => BitDepth::Float16
         }
     }
 }
@@ -95,6 +105,7 @@ impl BitDepth {
             Self::Eight => (1 << 08) - 1,
             Self::Sixteen => u16::MAX,
             Self::Float32 => 1,
+            Self::Float16 => 1,
             Self::Unknown => 0,
         }
     }
@@ -124,6 +135,7 @@ impl BitDepth {
             Self::Eight => BitType::U8,
             Self::Sixteen => BitType::U16,
             Self::Float32 => BitType::F32,
+            Self::Float16 => BitType::F16,
             Self::Unknown => panic!("Unknown bit type")
         }
     }
@@ -147,6 +159,7 @@ impl BitDepth {
             Self::Eight => core::mem::size_of::<u8>(),
             Self::Sixteen => core::mem::size_of::<u16>(),
             Self::Float32 => core::mem::size_of::<f32>(),
+            Self::Float16 => core::mem::size_of::<u16>(),
             Self::Unknown => panic!("Unknown bit type")
         }
     }
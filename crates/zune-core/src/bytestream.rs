@@ -19,8 +19,10 @@ pub use traits::*;
 pub use writer::ZWriter;
 
 pub use crate::bytestream::reader::no_std_readers::*;
+#[cfg(feature = "std")]
+pub use crate::bytestream::reader::stream_reader::ZStreamReader;
 //use crate::bytestream::reader::std_readers::*;
-pub use crate::bytestream::reader::ZByteIoError;
+pub use crate::bytestream::reader::{ZByteIoError, ZSeekFrom};
 
 mod reader;
 mod traits;
@@ -5,6 +5,7 @@ use core::fmt::Formatter;
 
 pub(crate) mod no_std_readers;
 pub(crate) mod std_readers;
+pub(crate) mod stream_reader;
 use crate::bytestream::ZByteReaderTrait;
 
 /// Enumeration of possible methods to seek within an I/O object.
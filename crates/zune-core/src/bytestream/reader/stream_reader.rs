@@ -0,0 +1,258 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+#![cfg(feature = "std")]
+
+use std::io;
+use std::io::Read;
+
+use crate::bytestream::reader::{ZByteIoError, ZSeekFrom};
+use crate::bytestream::ZByteReaderTrait;
+
+/// Bytes requested from the underlying reader at once
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Default number of bytes kept before the current position
+const DEFAULT_HISTORY: usize = 64 * 1024;
+
+/// Reads from anything implementing [`Read`], without requiring [`Seek`](std::io::Seek)
+///
+/// This lets decoders consume network responses, pipes and decompressors as the bytes
+/// arrive instead of buffering the whole file first.
+///
+/// Bytes are read in chunks and only the last `history` bytes before the current
+/// position are kept, so the decoder can rewind a few bytes, as most of them do while
+/// parsing headers, but seeking further back or from the end of the stream is an error.
+/// Bytes skipped by seeking forward are kept until the next read, so decoders can
+/// skip ahead to peek at bytes and come back.
+///
+/// Formats that parse their input front to back, e.g. PPM, QOI, farbfeld, PNG and
+/// baseline JPEG, decode from this reader. Formats that jump around their file need
+/// a seekable source instead.
+///
+/// # Example
+/// ```
+/// use std::io::Read;
+///
+/// use zune_core::bytestream::{ZReader, ZStreamReader};
+///
+/// // a `Read` that isn't `Seek`
+/// let source = std::io::repeat(7).take(10);
+/// let mut reader = ZReader::new(ZStreamReader::new(source));
+///
+/// assert_eq!(reader.read_fixed_bytes_or_error::<4>().unwrap(), [7; 4]);
+/// reader.rewind(2).unwrap();
+/// assert_eq!(reader.position().unwrap(), 2);
+/// ```
+pub struct ZStreamReader<R: Read> {
+    inner:    R,
+    /// Bytes read from `inner`, `buffer[0]` is at `offset` in the stream
+    buffer:   Vec<u8>,
+    offset:   u64,
+    /// Index of the next byte in `buffer`
+    position: usize,
+    history:  usize,
+    /// Position before the last forward seek, kept until the next read
+    pinned:   Option<u64>
+}
+
+impl<R: Read> ZStreamReader<R> {
+    /// Create a reader keeping the default history of 64 KiB
+    pub fn new(inner: R) -> ZStreamReader<R> {
+        ZStreamReader::with_history(inner, DEFAULT_HISTORY)
+    }
+
+    /// Create a reader keeping `history` bytes before the current position
+    /// for rewinds
+    pub fn with_history(inner: R, history: usize) -> ZStreamReader<R> {
+        ZStreamReader {
+            inner,
+            buffer: Vec::new(),
+            offset: 0,
+            position: 0,
+            history,
+            pinned: None
+        }
+    }
+
+    /// Destroy this reader, returning the underlying reader
+    ///
+    /// Bytes already read from it but not consumed are lost
+    pub fn consume(self) -> R {
+        self.inner
+    }
+
+    /// Number of buffered bytes after the current position
+    fn available(&self) -> usize {
+        self.buffer.len() - self.position
+    }
+
+    /// Read a chunk from the underlying reader, returning how many bytes were read,
+    /// zero at the end of the stream
+    fn read_chunk(&mut self) -> Result<usize, ZByteIoError> {
+        // drop bytes that can no longer be rewound to
+        let mut keep = self.position.saturating_sub(self.history);
+        if let Some(pinned) = self.pinned {
+            keep = keep.min(pinned.saturating_sub(self.offset) as usize);
+        }
+        if keep > 0 {
+            self.buffer.drain(..keep);
+            self.offset += keep as u64;
+            self.position -= keep;
+        }
+        let start = self.buffer.len();
+        self.buffer.resize(start + CHUNK_SIZE, 0);
+
+        loop {
+            match self.inner.read(&mut self.buffer[start..]) {
+                Ok(bytes) => {
+                    self.buffer.truncate(start + bytes);
+                    return Ok(bytes);
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    self.buffer.truncate(start);
+                    return Err(ZByteIoError::from(e));
+                }
+            }
+        }
+    }
+
+    /// Buffer at least `size` bytes after the current position, returning how many
+    /// are available, fewer only at the end of the stream
+    fn fill(&mut self, size: usize) -> Result<usize, ZByteIoError> {
+        while self.available() < size {
+            if self.read_chunk()? == 0 {
+                break;
+            }
+        }
+        Ok(self.available())
+    }
+
+    fn current_position(&self) -> u64 {
+        self.offset + self.position as u64
+    }
+}
+
+impl<R: Read> ZByteReaderTrait for ZStreamReader<R> {
+    #[inline(always)]
+    fn read_byte_no_error(&mut self) -> u8 {
+        self.pinned = None;
+
+        if self.position < self.buffer.len() || matches!(self.fill(1), Ok(1..)) {
+            let byte = self.buffer[self.position];
+            self.position += 1;
+            return byte;
+        }
+        0
+    }
+
+    fn read_exact_bytes(&mut self, buf: &mut [u8]) -> Result<(), ZByteIoError> {
+        self.pinned = None;
+        self.peek_exact_bytes(buf)?;
+        self.position += buf.len();
+        Ok(())
+    }
+
+    #[inline]
+    fn read_const_bytes<const N: usize>(&mut self, buf: &mut [u8; N]) -> Result<(), ZByteIoError> {
+        self.read_exact_bytes(buf)
+    }
+
+    fn read_const_bytes_no_error<const N: usize>(&mut self, buf: &mut [u8; N]) {
+        let _ = self.read_const_bytes(buf);
+    }
+
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<usize, ZByteIoError> {
+        self.pinned = None;
+        let bytes_read = self.peek_bytes(buf)?;
+        self.position += bytes_read;
+        Ok(bytes_read)
+    }
+
+    fn peek_bytes(&mut self, buf: &mut [u8]) -> Result<usize, ZByteIoError> {
+        if self.available() == 0 {
+            self.read_chunk()?;
+        }
+        let bytes = self.available().min(buf.len());
+        buf[..bytes].copy_from_slice(&self.buffer[self.position..self.position + bytes]);
+
+        Ok(bytes)
+    }
+
+    fn peek_exact_bytes(&mut self, buf: &mut [u8]) -> Result<(), ZByteIoError> {
+        let available = self.fill(buf.len())?;
+
+        if available < buf.len() {
+            return Err(ZByteIoError::NotEnoughBytes(available, buf.len()));
+        }
+        buf.copy_from_slice(&self.buffer[self.position..self.position + buf.len()]);
+        Ok(())
+    }
+
+    fn z_seek(&mut self, from: ZSeekFrom) -> Result<u64, ZByteIoError> {
+        let target = match from {
+            ZSeekFrom::Start(n) => n,
+            ZSeekFrom::Current(n) => self
+                .current_position()
+                .checked_add_signed(n)
+                .ok_or(ZByteIoError::SeekError("Negative seek"))?,
+            ZSeekFrom::End(_) => {
+                return Err(ZByteIoError::SeekError(
+                    "Cannot seek from the end of a stream"
+                ))
+            }
+        };
+        if target < self.offset {
+            return Err(ZByteIoError::SeekErrorOwned(format!(
+                "Cannot seek back to {target}, the stream only keeps bytes from {}",
+                self.offset
+            )));
+        }
+        let distance = usize::try_from(target - self.offset).map_err(ZByteIoError::from)?;
+        // buffer the skipped bytes, decoders often skip ahead to peek and come back
+        if target > self.current_position() {
+            self.pinned = Some(self.current_position());
+        }
+        if distance > self.buffer.len() {
+            let skipped = usize::try_from(target - self.current_position())?;
+
+            if self.fill(skipped)? < skipped {
+                return Err(ZByteIoError::SeekErrorOwned(format!(
+                    "Cannot seek to {target}, the stream ends at {}",
+                    self.offset + self.buffer.len() as u64
+                )));
+            }
+        }
+        // filling may have dropped bytes from the front
+        self.position = (target - self.offset) as usize;
+
+        Ok(target)
+    }
+
+    fn is_eof(&mut self) -> Result<bool, ZByteIoError> {
+        Ok(self.fill(1)? == 0)
+    }
+
+    fn z_position(&mut self) -> Result<u64, ZByteIoError> {
+        Ok(self.current_position())
+    }
+
+    fn read_remaining(&mut self, sink: &mut Vec<u8>) -> Result<usize, ZByteIoError> {
+        let buffered = self.available();
+        sink.extend_from_slice(&self.buffer[self.position..]);
+
+        let rest = self.inner.read_to_end(sink).map_err(ZByteIoError::from)?;
+        // everything was consumed, nothing is left to rewind to
+        self.offset += (self.buffer.len() + rest) as u64;
+        self.buffer.clear();
+        self.position = 0;
+        self.pinned = None;
+
+        Ok(buffered + rest)
+    }
+}
@@ -19,13 +19,21 @@ pub enum ColorSpace {
     /// Red, Green, Blue, Alpha
     RGBA,
     /// YUV colorspace
+    ///
+    /// Uses full range BT.601 coefficients, as in JPEG, with the chroma channels centered
+    /// around half the maximum value
     YCbCr,
     /// Grayscale colorspace
     Luma,
     /// Grayscale with alpha colorspace
     LumaA,
+    /// Adobe YCCK, [`CMYK`](ColorSpace::CMYK) with the inverted CMY channels encoded as
+    /// [`YCbCr`](ColorSpace::YCbCr) and the black channel kept as is
     YCCK,
     /// Cyan , Magenta, Yellow, Black
+    ///
+    /// Channels store inverted ink amounts as Adobe CMYK JPEGs do,
+    /// the maximum value of a depth means no ink and zero means full ink.
     CMYK,
     /// Blue, Green, Red
     BGR,
@@ -44,6 +52,42 @@ pub enum ColorSpace {
     /// Conversion from RGB to HSV and back matches that of Python [colorsys](https://docs.python.org/3/library/colorsys.html) module
     /// Color type is expected to be in floating point
     HSV,
+    /// CIE L\*a\*b\*, relative to the D65 white point of sRGB
+    ///
+    /// Channels are normalized to fit every bit depth:
+    /// `L/100`, `(a+128)/255` and `(b+128)/255`, so that zero `a` and `b` (a neutral color)
+    /// are roughly in the middle of the range.
+    ///
+    /// Conversion to and from RGB assumes sRGB primaries and transfer characteristics
+    Lab,
+    /// CIE LCh, the cylindrical form of [`Lab`](ColorSpace::Lab)
+    ///
+    /// Channels are normalized to fit every bit depth:
+    /// `L/100`, `C/181.02` (the largest chroma `a` and `b` can encode) and `h/360`
+    LCh,
+    /// OKLab, a perceptual colorspace by Björn Ottosson
+    ///
+    /// It is more perceptually uniform than [`Lab`](ColorSpace::Lab), which makes it a better
+    /// choice for gradients, blending and picking colors.
+    ///
+    /// Channels are normalized to fit every bit depth: `L`, `a+0.5` and `b+0.5`
+    OKLab,
+    /// OKLCh, the cylindrical form of [`OKLab`](ColorSpace::OKLab)
+    ///
+    /// Channels are normalized to fit every bit depth: `L`, `C*2` and `h/360`
+    OKLCh,
+    /// CIE 1931 XYZ
+    ///
+    /// Channels are relative to the reference white of the image, i.e. `X/Xw`, `Y/Yw` and `Z/Zw`,
+    /// so that the reference white is 1 in every channel and every sRGB color fits every bit depth.
+    ///
+    /// The reference white is stored in the image metadata, and defaults to [`WhitePoint::D65`]
+    XYZ,
+    /// Raw sensor data behind a Bayer color filter array
+    ///
+    /// A single channel where every pixel holds one of red, green or blue, laid out
+    /// in 2x2 tiles described by the [`CfaPattern`]. Demosaic the image to get RGB
+    Bayer(CfaPattern),
     /// Multiple arbitrary image channels.
     ///
     /// This introduces **limited** support for multi-band/multichannel images
@@ -63,9 +107,18 @@ impl ColorSpace {
     /// E.g. RGB returns 3 since it contains R,G and B colors to make up a pixel
     pub const fn num_components(&self) -> usize {
         match self {
-            Self::RGB | Self::YCbCr | Self::BGR | Self::HSV | Self::HSL => 3,
+            Self::RGB
+            | Self::YCbCr
+            | Self::BGR
+            | Self::HSV
+            | Self::HSL
+            | Self::Lab
+            | Self::LCh
+            | Self::OKLab
+            | Self::OKLCh
+            | Self::XYZ => 3,
             Self::RGBA | Self::YCCK | Self::CMYK | Self::BGRA | Self::ARGB => 4,
-            Self::Luma => 1,
+            Self::Luma | Self::Bayer(_) => 1,
             Self::LumaA => 2,
             Self::Unknown => 0,
             Self::MultiBand(n) => n.get() as usize
@@ -104,8 +157,8 @@ impl ColorSpace {
 /// Encapsulates all colorspaces supported by
 /// the library
 ///
-/// This explicitly leaves out multi-band images
-pub static ALL_COLORSPACES: [ColorSpace; 12] = [
+/// This explicitly leaves out multi-band and Bayer images
+pub static ALL_COLORSPACES: [ColorSpace; 17] = [
     ColorSpace::RGB,
     ColorSpace::RGBA,
     ColorSpace::LumaA,
@@ -117,9 +170,43 @@ pub static ALL_COLORSPACES: [ColorSpace; 12] = [
     ColorSpace::YCbCr,
     ColorSpace::ARGB,
     ColorSpace::HSL,
-    ColorSpace::HSV
+    ColorSpace::HSV,
+    ColorSpace::Lab,
+    ColorSpace::LCh,
+    ColorSpace::OKLab,
+    ColorSpace::OKLCh,
+    ColorSpace::XYZ
 ];
 
+/// Layout of a Bayer color filter array
+///
+/// Names the colors of the top left 2x2 tile, row by row
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CfaPattern {
+    /// Red, green on the first row, green, blue on the second
+    RGGB,
+    /// Blue, green on the first row, green, red on the second
+    BGGR,
+    /// Green, red on the first row, blue, green on the second
+    GRBG,
+    /// Green, blue on the first row, red, green on the second
+    GBRG
+}
+
+impl CfaPattern {
+    /// The color filtered at pixel `(x, y)`, 0 for red, 1 for green and 2 for blue
+    pub const fn color_at(self, x: usize, y: usize) -> usize {
+        let tile = match self {
+            CfaPattern::RGGB => [[0, 1], [1, 2]],
+            CfaPattern::BGGR => [[2, 1], [1, 0]],
+            CfaPattern::GRBG => [[1, 0], [2, 1]],
+            CfaPattern::GBRG => [[1, 2], [0, 1]]
+        };
+        tile[y & 1][x & 1]
+    }
+}
+
 /// Color characteristics
 ///
 /// Gives more information about values in a certain
@@ -136,8 +223,165 @@ pub enum ColorCharacteristics {
     sRGB,
     /// Linear transfer characteristics
     /// The image is in linear colorspace
-    Linear
+    Linear,
+    /// SMPTE ST 2084 perceptual quantizer, used by HDR10 content
+    ///
+    /// Encodes absolute display light where 1.0 is 10000 nits
+    PQ,
+    /// ARIB STD-B67 hybrid log-gamma, used by HDR broadcasts
+    ///
+    /// Encodes relative scene light
+    HLG
+}
+
+impl ColorCharacteristics {
+    /// Return the transfer characteristics for a code point of
+    /// ITU-T H.273 (as stored in PNG cICP chunks, AVIF and video bitstreams)
+    ///
+    /// Returns `None` for code points without a matching variant
+    pub const fn from_h273(transfer: u8) -> Option<ColorCharacteristics> {
+        match transfer {
+            8 => Some(ColorCharacteristics::Linear),
+            13 => Some(ColorCharacteristics::sRGB),
+            16 => Some(ColorCharacteristics::PQ),
+            18 => Some(ColorCharacteristics::HLG),
+            _ => None
+        }
+    }
+}
+/// A reference white, the color considered white under some illuminant
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum WhitePoint {
+    /// Horizon light, used by ICC profiles as the profile connection space white
+    D50,
+    /// Mid-morning daylight
+    D55,
+    /// Noon daylight, the white point of sRGB, Display P3 and Rec.2020
+    D65,
+    /// Equal energy illuminant
+    E,
+    /// A white point given by its CIE 1931 `x` and `y` chromaticity coordinates
+    Custom(f32, f32)
+}
+
+impl WhitePoint {
+    /// Return the `x` and `y` chromaticity coordinates of the white point
+    pub const fn chromaticity(self) -> (f32, f32) {
+        match self {
+            WhitePoint::D50 => (0.345_67, 0.358_50),
+            WhitePoint::D55 => (0.332_42, 0.347_43),
+            WhitePoint::D65 => (0.312_71, 0.329_02),
+            WhitePoint::E => (1.0 / 3.0, 1.0 / 3.0),
+            WhitePoint::Custom(x, y) => (x, y)
+        }
+    }
+    /// Return the XYZ tristimulus values of the white point, with `Y` normalized to 1
+    pub fn to_xyz(self) -> [f32; 3] {
+        let (x, y) = self.chromaticity();
+
+        [x / y, 1.0, (1.0 - x - y) / y]
+    }
+    /// Create a white point from XYZ tristimulus values, e.g. the media white point of an ICC profile
+    ///
+    /// Only the chromaticity is kept, the luminance is discarded
+    pub fn from_xyz(xyz: [f32; 3]) -> WhitePoint {
+        let sum = xyz[0] + xyz[1] + xyz[2];
+
+        WhitePoint::Custom(xyz[0] / sum, xyz[1] / sum)
+    }
+}
+
+/// A chromatic adaptation transform
+///
+/// These transforms predict how a color seen under one white point looks under another
+/// by scaling the color in a cone response like space.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum ChromaticAdaptation {
+    /// The Bradford transform, used by ICC profiles, the default
+    #[default]
+    Bradford,
+    /// The transform of the CIECAM02 color appearance model
+    CAT02,
+    /// The von Kries transform, scaling the LMS cone responses
+    VonKries,
+    /// Scale XYZ values directly, this is the least accurate transform
+    XyzScaling
+}
+
+/// Coefficients weighting red, green and blue when converting RGB to luma
+///
+/// Different standards weight the channels differently, matching the
+/// coefficients to the primaries of the image gives the most accurate luminance
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum LumaCoefficients {
+    /// ITU-R BT.601, used by JPEG and standard definition video, the default
+    #[default]
+    BT601,
+    /// ITU-R BT.709, matches the sRGB primaries
+    BT709,
+    /// ITU-R BT.2020, for Rec.2020 images
+    BT2020,
+    /// Equal weights for all channels, i.e. the average of the channels
+    Equal
+}
+
+impl LumaCoefficients {
+    /// Return the red, green and blue weights, they add up to 1
+    pub const fn coefficients(self) -> [f32; 3] {
+        match self {
+            LumaCoefficients::BT601 => [0.299, 0.587, 0.114],
+            LumaCoefficients::BT709 => [0.2126, 0.7152, 0.0722],
+            LumaCoefficients::BT2020 => [0.2627, 0.6780, 0.0593],
+            LumaCoefficients::Equal => [1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0]
+        }
+    }
+}
+
+/// The red, green and blue primaries of an RGB image
+///
+/// Primaries define the colors of the RGB channels and hence the gamut, the range of
+/// colors an RGB image can represent, wide gamut primaries can represent more
+/// saturated colors than sRGB.
+#[allow(non_camel_case_types)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum RgbPrimaries {
+    /// sRGB and Rec.709 primaries, the default for most images
+    #[default]
+    sRGB,
+    /// Display P3 primaries, used by many recent displays
+    DisplayP3,
+    /// Rec.2020 (BT.2020) primaries, used for UHD video
+    Rec2020
 }
+
+impl RgbPrimaries {
+    /// Return the primaries for a code point of ITU-T H.273
+    ///
+    /// Returns `None` for code points without a matching variant
+    pub const fn from_h273(primaries: u8) -> Option<RgbPrimaries> {
+        match primaries {
+            1 => Some(RgbPrimaries::sRGB),
+            9 => Some(RgbPrimaries::Rec2020),
+            12 => Some(RgbPrimaries::DisplayP3),
+            _ => None
+        }
+    }
+    /// Return the `x` and `y` chromaticity coordinates of the red, green and blue primaries
+    pub const fn chromaticities(self) -> [(f32, f32); 3] {
+        match self {
+            RgbPrimaries::sRGB => [(0.64, 0.33), (0.30, 0.60), (0.15, 0.06)],
+            RgbPrimaries::DisplayP3 => [(0.680, 0.320), (0.265, 0.690), (0.150, 0.060)],
+            RgbPrimaries::Rec2020 => [(0.708, 0.292), (0.170, 0.797), (0.131, 0.046)]
+        }
+    }
+    /// Return the white point of the primaries
+    pub const fn white_point(self) -> WhitePoint {
+        match self {
+            RgbPrimaries::sRGB | RgbPrimaries::DisplayP3 | RgbPrimaries::Rec2020 => WhitePoint::D65
+        }
+    }
+}
+
 /// Represents a single channel color primary.
 ///
 /// This can be viewed as a 3D coordinate of the color primary
@@ -0,0 +1,725 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! ICC color profile parsing and color conversion
+//!
+//! Images may carry an ICC profile describing how their pixel values map to real colors,
+//! ignoring it makes wide gamut images (e.g. Display P3 or Adobe RGB photos) look dull
+//! or oversaturated.
+//!
+//! This module parses version 2 and 4 profiles and builds transforms between them.
+//!
+//! # Supported profiles
+//! - Matrix/TRC RGB profiles (`rXYZ`,`gXYZ`,`bXYZ` colorants with `rTRC`,`gTRC`,`bTRC` curves)
+//! - Gray TRC profiles (`kTRC`)
+//! - LUT based profiles using `A2Bx`/`B2Ax` tags of type `mft1`, `mft2`, `mAB ` and `mBA `,
+//!   with a Lab or XYZ profile connection space. This covers most CMYK profiles
+//!
+//! # Example
+//! Convert a pixel from a profile embedded in an image to sRGB
+//! ```
+//! use zune_core::colorspace::RenderingIntent;
+//! use zune_core::icc::{IccProfile, IccTransform};
+//!
+//! // normally this comes from the image
+//! let profile = IccProfile::srgb();
+//! let transform = IccTransform::new(&profile, &IccProfile::srgb(), RenderingIntent::Perceptual).unwrap();
+//!
+//! let mut output = [0.0; 3];
+//! transform.transform(&[0.5, 0.2, 0.8], &mut output);
+//!
+//! assert!((output[0] - 0.5).abs() < 1e-3);
+//! ```
+//!
+//! This module is only available with the `std` feature, as it needs floating point
+//! functions not available in `core`.
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Display, Formatter};
+
+use crate::colorspace::RenderingIntent;
+pub use crate::icc::tags::ToneCurve;
+use crate::icc::tags::{read_s15_fixed16, read_u32, Lut, PcsEncoding};
+
+mod tags;
+
+/// The D50 white point of the profile connection space
+pub const D50_WHITE: [f32; 3] = [0.9642, 1.0, 0.8249];
+
+/// Errors that can occur when parsing a profile or creating a transform
+pub enum IccError {
+    /// The profile ended before a field could be read
+    // needed, found
+    NotEnoughBytes(usize, usize),
+    /// The profile is not an ICC profile
+    InvalidSignature,
+    /// The profile is an ICC profile but some data is invalid
+    Malformed(&'static str),
+    /// The profile uses a feature this library doesn't support
+    Unsupported(&'static str)
+}
+
+impl Debug for IccError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            IccError::NotEnoughBytes(needed, found) => {
+                writeln!(
+                    f,
+                    "Not enough bytes in profile, needed {needed} but found {found}"
+                )
+            }
+            IccError::InvalidSignature => writeln!(f, "Not an ICC profile, missing acsp signature"),
+            IccError::Malformed(err) => writeln!(f, "Malformed ICC profile: {err}"),
+            IccError::Unsupported(err) => writeln!(f, "Unsupported ICC profile: {err}")
+        }
+    }
+}
+
+impl Display for IccError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        writeln!(f, "{self:?}")
+    }
+}
+
+impl std::error::Error for IccError {}
+
+/// The device class of a profile
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum IccProfileClass {
+    /// Input devices, e.g scanners and cameras (`scnr`)
+    Input,
+    /// Display devices, e.g monitors (`mntr`)
+    Display,
+    /// Output devices, e.g printers (`prtr`)
+    Output,
+    /// Direct device to device conversions (`link`)
+    DeviceLink,
+    /// Color space conversions (`spac`)
+    ColorSpace,
+    /// Abstract color effects (`abst`)
+    Abstract,
+    /// Named color lists (`nmcl`)
+    NamedColor,
+    /// An unknown class, with its signature
+    Unknown(u32)
+}
+
+/// The color space of profile data or of the profile connection space
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum IccColorSpace {
+    /// CIE XYZ (`XYZ `)
+    XYZ,
+    /// CIE Lab (`Lab `)
+    Lab,
+    /// Red, Green, Blue (`RGB `)
+    RGB,
+    /// Grayscale (`GRAY`)
+    Gray,
+    /// Cyan, Magenta, Yellow, Black (`CMYK`)
+    CMYK,
+    /// Cyan, Magenta, Yellow (`CMY `)
+    CMY,
+    /// Any other color space, with its signature
+    Other(u32)
+}
+
+impl IccColorSpace {
+    fn from_signature(signature: u32) -> IccColorSpace {
+        match &signature.to_be_bytes() {
+            b"XYZ " => IccColorSpace::XYZ,
+            b"Lab " => IccColorSpace::Lab,
+            b"RGB " => IccColorSpace::RGB,
+            b"GRAY" => IccColorSpace::Gray,
+            b"CMYK" => IccColorSpace::CMYK,
+            b"CMY " => IccColorSpace::CMY,
+            _ => IccColorSpace::Other(signature)
+        }
+    }
+    /// Number of channels of this color space, or `None` if unknown
+    #[must_use]
+    pub const fn num_components(self) -> Option<usize> {
+        match self {
+            IccColorSpace::XYZ | IccColorSpace::Lab | IccColorSpace::RGB | IccColorSpace::CMY => {
+                Some(3)
+            }
+            IccColorSpace::Gray => Some(1),
+            IccColorSpace::CMYK => Some(4),
+            IccColorSpace::Other(_) => None
+        }
+    }
+}
+
+/// A parsed ICC profile
+#[derive(Clone, Debug, PartialEq)]
+pub struct IccProfile {
+    /// Profile version as (major, minor)
+    pub version:          (u8, u8),
+    /// Device class
+    pub class:            IccProfileClass,
+    /// Color space of the device data
+    pub color_space:      IccColorSpace,
+    /// Profile connection space, either XYZ or Lab
+    pub pcs:              IccColorSpace,
+    /// Rendering intent stored in the header
+    pub rendering_intent: RenderingIntent,
+    /// Profile description, if present
+    pub description:      Option<String>,
+    /// Media white point (`wtpt`), if present
+    pub white_point:      Option<[f32; 3]>,
+    /// Matrix/TRC colorants as columns `[red, green, blue]` of an RGB to XYZ matrix
+    colorants:            Option<[[f32; 3]; 3]>,
+    /// Red, green and blue tone curves, or the gray curve in the first position
+    curves:               Option<[ToneCurve; 3]>,
+    /// `A2B0`,`A2B1`,`A2B2` tags, indexed by intent
+    a_to_b:               [Option<Lut>; 3],
+    /// `B2A0`,`B2A1`,`B2A2` tags, indexed by intent
+    b_to_a:               [Option<Lut>; 3]
+}
+
+impl IccProfile {
+    /// Parse an ICC profile
+    ///
+    /// # Errors
+    /// If the data isn't an ICC profile, or any of the tags needed to convert colors are malformed
+    pub fn parse(data: &[u8]) -> Result<IccProfile, IccError> {
+        if data.len() < 132 {
+            return Err(IccError::NotEnoughBytes(132, data.len()));
+        }
+        if &data[36..40] != b"acsp" {
+            return Err(IccError::InvalidSignature);
+        }
+        let version = (data[8], data[9] >> 4);
+        let class = match &data[12..16] {
+            b"scnr" => IccProfileClass::Input,
+            b"mntr" => IccProfileClass::Display,
+            b"prtr" => IccProfileClass::Output,
+            b"link" => IccProfileClass::DeviceLink,
+            b"spac" => IccProfileClass::ColorSpace,
+            b"abst" => IccProfileClass::Abstract,
+            b"nmcl" => IccProfileClass::NamedColor,
+            _ => IccProfileClass::Unknown(read_u32(data, 12)?)
+        };
+        let color_space = IccColorSpace::from_signature(read_u32(data, 16)?);
+        let pcs = IccColorSpace::from_signature(read_u32(data, 20)?);
+        let rendering_intent = match read_u32(data, 64)? & 0xFFFF {
+            1 => RenderingIntent::RelativeColorimetric,
+            2 => RenderingIntent::Saturation,
+            3 => RenderingIntent::AbsoluteColorimetric,
+            _ => RenderingIntent::Perceptual
+        };
+
+        // tag table
+        let tag_count = read_u32(data, 128)? as usize;
+        let mut tags = Vec::with_capacity(tag_count.min(256));
+
+        for i in 0..tag_count {
+            let entry = 132 + i * 12;
+            let signature = read_u32(data, entry)?.to_be_bytes();
+            let offset = read_u32(data, entry + 4)? as usize;
+            let size = read_u32(data, entry + 8)? as usize;
+
+            let end = offset
+                .checked_add(size)
+                .ok_or(IccError::Malformed("Tag offset overflows"))?;
+            if end > data.len() {
+                return Err(IccError::NotEnoughBytes(end, data.len()));
+            }
+            tags.push((signature, &data[offset..end]));
+        }
+        let tag = |signature: &[u8; 4]| {
+            tags.iter()
+                .find(|(sig, _)| sig == signature)
+                .map(|(_, data)| *data)
+        };
+
+        let xyz_tag = |signature: &[u8; 4]| -> Result<Option<[f32; 3]>, IccError> {
+            match tag(signature) {
+                Some(data) => {
+                    if &data[..4.min(data.len())] != b"XYZ " {
+                        return Err(IccError::Malformed("Expected an XYZ tag"));
+                    }
+                    Ok(Some([
+                        read_s15_fixed16(data, 8)?,
+                        read_s15_fixed16(data, 12)?,
+                        read_s15_fixed16(data, 16)?
+                    ]))
+                }
+                None => Ok(None)
+            }
+        };
+        let curve_tag = |signature: &[u8; 4]| -> Result<Option<ToneCurve>, IccError> {
+            tag(signature)
+                .map(|data| ToneCurve::parse(data).map(|(curve, _)| curve))
+                .transpose()
+        };
+
+        let white_point = xyz_tag(b"wtpt")?;
+
+        let (colorants, curves) = match color_space {
+            IccColorSpace::RGB => {
+                match (
+                    xyz_tag(b"rXYZ")?,
+                    xyz_tag(b"gXYZ")?,
+                    xyz_tag(b"bXYZ")?,
+                    curve_tag(b"rTRC")?,
+                    curve_tag(b"gTRC")?,
+                    curve_tag(b"bTRC")?
+                ) {
+                    (Some(r), Some(g), Some(b), Some(rc), Some(gc), Some(bc)) => {
+                        (Some([r, g, b]), Some([rc, gc, bc]))
+                    }
+                    _ => (None, None)
+                }
+            }
+            IccColorSpace::Gray => (
+                None,
+                curve_tag(b"kTRC")?.map(|curve| [curve, ToneCurve::Identity, ToneCurve::Identity])
+            ),
+            _ => (None, None)
+        };
+
+        let device_channels = color_space.num_components();
+        let lut_tag = |signature: &[u8; 4],
+                       xyz_input: bool,
+                       inputs: Option<usize>,
+                       outputs: Option<usize>| {
+            match tag(signature) {
+                Some(data) => {
+                    let lut = Lut::parse(data, xyz_input)?;
+                    if inputs.is_some_and(|x| x != lut.input_channels)
+                        || outputs.is_some_and(|x| x != lut.output_channels)
+                    {
+                        return Err(IccError::Malformed(
+                            "LUT channels do not match the profile color space"
+                        ));
+                    }
+                    Ok(Some(lut))
+                }
+                None => Ok(None)
+            }
+        };
+        let pcs_is_xyz = pcs == IccColorSpace::XYZ;
+
+        let a_to_b = [
+            lut_tag(b"A2B0", false, device_channels, Some(3))?,
+            lut_tag(b"A2B1", false, device_channels, Some(3))?,
+            lut_tag(b"A2B2", false, device_channels, Some(3))?
+        ];
+        let b_to_a = [
+            lut_tag(b"B2A0", pcs_is_xyz, Some(3), device_channels)?,
+            lut_tag(b"B2A1", pcs_is_xyz, Some(3), device_channels)?,
+            lut_tag(b"B2A2", pcs_is_xyz, Some(3), device_channels)?
+        ];
+
+        Ok(IccProfile {
+            version,
+            class,
+            color_space,
+            pcs,
+            rendering_intent,
+            description: tag(b"desc").and_then(parse_description),
+            white_point,
+            colorants,
+            curves,
+            a_to_b,
+            b_to_a
+        })
+    }
+
+    /// Create the standard sRGB profile (IEC 61966-2.1)
+    #[must_use]
+    pub fn srgb() -> IccProfile {
+        let curve = ToneCurve::Parametric(
+            3,
+            [
+                2.4,
+                1.0 / 1.055,
+                0.055 / 1.055,
+                1.0 / 12.92,
+                0.04045,
+                0.0,
+                0.0
+            ]
+        );
+        IccProfile {
+            version:          (4, 3),
+            class:            IccProfileClass::Display,
+            color_space:      IccColorSpace::RGB,
+            pcs:              IccColorSpace::XYZ,
+            rendering_intent: RenderingIntent::Perceptual,
+            description:      Some(String::from("sRGB")),
+            white_point:      Some(D50_WHITE),
+            // Bradford adapted to D50
+            colorants:        Some([
+                [0.436_074, 0.222_504, 0.013_932],
+                [0.385_065, 0.716_879, 0.097_104],
+                [0.143_080, 0.060_617, 0.714_173]
+            ]),
+            curves:           Some([curve.clone(), curve.clone(), curve]),
+            a_to_b:           [None, None, None],
+            b_to_a:           [None, None, None]
+        }
+    }
+
+    /// Return true if colors can be converted from device values to the
+    /// profile connection space
+    #[must_use]
+    pub fn can_convert_from_device(&self) -> bool {
+        self.curves.is_some() || self.a_to_b.iter().any(Option::is_some)
+    }
+
+    /// Return true if colors can be converted from the profile connection
+    /// space to device values
+    #[must_use]
+    pub fn can_convert_to_device(&self) -> bool {
+        self.curves.is_some() || self.b_to_a.iter().any(Option::is_some)
+    }
+
+    /// Return true if this is a matrix/TRC (or gray TRC) profile
+    #[must_use]
+    pub fn is_matrix_shaper(&self) -> bool {
+        self.curves.is_some()
+    }
+
+    /// The red, green and blue colorants of a matrix/TRC profile, adapted to D50
+    #[must_use]
+    pub fn colorants(&self) -> Option<[[f32; 3]; 3]> {
+        self.colorants
+    }
+
+    /// The tone curves of a matrix/TRC profile, a gray profile only uses the first
+    #[must_use]
+    pub fn curves(&self) -> Option<&[ToneCurve; 3]> {
+        self.curves.as_ref()
+    }
+}
+
+/// Parse a `desc` (v2) or `mluc` (v4) tag into a string, returning the first record
+fn parse_description(data: &[u8]) -> Option<String> {
+    match data.get(..4)? {
+        b"desc" => {
+            let count = read_u32(data, 8).ok()? as usize;
+            let text = data.get(12..12 + count)?;
+            let text = text.split(|&x| x == 0).next().unwrap_or_default();
+            Some(String::from_utf8_lossy(text).into_owned())
+        }
+        b"mluc" => {
+            if read_u32(data, 8).ok()? == 0 {
+                return None;
+            }
+            let len = read_u32(data, 20).ok()? as usize;
+            let offset = read_u32(data, 24).ok()? as usize;
+            let units: Vec<u16> = data
+                .get(offset..offset + len)?
+                .chunks_exact(2)
+                .map(|x| u16::from_be_bytes([x[0], x[1]]))
+                .collect();
+            Some(String::from_utf16_lossy(&units))
+        }
+        _ => None
+    }
+}
+
+/// Converts device values to D50 XYZ
+#[derive(Clone, Debug)]
+enum ToPcs {
+    Rgb([ToneCurve; 3], [[f32; 3]; 3]),
+    Gray(ToneCurve),
+    Lut(Lut, PcsEncoding)
+}
+
+/// Converts D50 XYZ to device values
+#[derive(Clone, Debug)]
+enum FromPcs {
+    Rgb([ToneCurve; 3], [[f32; 3]; 3]),
+    Gray(ToneCurve),
+    Lut(Lut, PcsEncoding)
+}
+
+/// Select the best table for an intent, falling back to the perceptual table
+fn lut_for_intent(luts: &[Option<Lut>; 3], intent: RenderingIntent) -> Option<&Lut> {
+    let index = match intent {
+        RenderingIntent::Perceptual => 0,
+        RenderingIntent::RelativeColorimetric | RenderingIntent::AbsoluteColorimetric => 1,
+        RenderingIntent::Saturation => 2
+    };
+    luts[index].as_ref().or(luts[0].as_ref())
+}
+
+/// Number of samples used for inverted tone curves
+const INVERSE_SAMPLES: usize = 4096;
+
+/// A color transform between two ICC profiles
+///
+/// Values are normalized to 0..1 on both sides, with the channel order of the profiles'
+/// color spaces (e.g RGB, CMYK or a single gray channel)
+#[derive(Clone, Debug)]
+pub struct IccTransform {
+    input:           ToPcs,
+    output:          FromPcs,
+    input_channels:  usize,
+    output_channels: usize
+}
+
+impl IccTransform {
+    /// Create a transform converting colors described by `src` into colors described by `dst`
+    ///
+    /// # Arguments
+    /// - src: The profile of the input colors
+    /// - dst: The profile of the output colors
+    /// - intent: The rendering intent, used to select between lookup tables of a profile,
+    ///   missing tables fall back to the perceptual table, and then to matrix/TRC data
+    ///
+    /// # Errors
+    /// If either profile doesn't have the data needed for the conversion
+    pub fn new(
+        src: &IccProfile, dst: &IccProfile, intent: RenderingIntent
+    ) -> Result<IccTransform, IccError> {
+        let lab_pcs = |profile: &IccProfile| profile.pcs == IccColorSpace::Lab;
+
+        let input = if let Some(lut) = lut_for_intent(&src.a_to_b, intent) {
+            ToPcs::Lut(lut.clone(), lut.pcs_encoding(lab_pcs(src)))
+        } else {
+            match (&src.curves, src.colorants, src.color_space) {
+                (Some(curves), Some(colorants), IccColorSpace::RGB) => {
+                    ToPcs::Rgb(curves.clone(), transpose(colorants))
+                }
+                (Some(curves), _, IccColorSpace::Gray) => ToPcs::Gray(curves[0].clone()),
+                _ => {
+                    return Err(IccError::Unsupported(
+                        "Source profile can't convert device colors to PCS"
+                    ))
+                }
+            }
+        };
+        let output = if let Some(lut) = lut_for_intent(&dst.b_to_a, intent) {
+            FromPcs::Lut(lut.clone(), lut.pcs_encoding(lab_pcs(dst)))
+        } else {
+            match (&dst.curves, dst.colorants, dst.color_space) {
+                (Some(curves), Some(colorants), IccColorSpace::RGB) => {
+                    let matrix = invert_3x3(transpose(colorants))
+                        .ok_or(IccError::Malformed("Profile colorants are not invertible"))?;
+                    FromPcs::Rgb(curves.clone().map(|c| c.inverse(INVERSE_SAMPLES)), matrix)
+                }
+                (Some(curves), _, IccColorSpace::Gray) => {
+                    FromPcs::Gray(curves[0].inverse(INVERSE_SAMPLES))
+                }
+                _ => {
+                    return Err(IccError::Unsupported(
+                        "Destination profile can't convert PCS colors to device colors"
+                    ))
+                }
+            }
+        };
+        let input_channels = src
+            .color_space
+            .num_components()
+            .ok_or(IccError::Unsupported("Unknown source color space"))?;
+        let output_channels = dst
+            .color_space
+            .num_components()
+            .ok_or(IccError::Unsupported("Unknown destination color space"))?;
+
+        Ok(IccTransform {
+            input,
+            output,
+            input_channels,
+            output_channels
+        })
+    }
+
+    /// Number of input channels to [`transform`](Self::transform)
+    #[must_use]
+    pub const fn input_channels(&self) -> usize {
+        self.input_channels
+    }
+
+    /// Number of output channels of [`transform`](Self::transform)
+    #[must_use]
+    pub const fn output_channels(&self) -> usize {
+        self.output_channels
+    }
+
+    /// Convert a single color
+    ///
+    /// # Arguments
+    /// - input: `input_channels` values in 0..1
+    /// - output: Receives `output_channels` values in 0..1
+    ///
+    /// # Panics
+    /// If the slices are shorter than the channel counts
+    pub fn transform(&self, input: &[f32], output: &mut [f32]) {
+        let xyz = self.device_to_xyz(&input[..self.input_channels]);
+        self.xyz_to_device(xyz, &mut output[..self.output_channels]);
+    }
+
+    fn device_to_xyz(&self, input: &[f32]) -> [f32; 3] {
+        match &self.input {
+            ToPcs::Rgb(curves, matrix) => {
+                let linear = [
+                    curves[0].eval(input[0]),
+                    curves[1].eval(input[1]),
+                    curves[2].eval(input[2])
+                ];
+                mul_3x3(matrix, linear)
+            }
+            ToPcs::Gray(curve) => {
+                let y = curve.eval(input[0]);
+                D50_WHITE.map(|w| w * y)
+            }
+            ToPcs::Lut(lut, encoding) => {
+                let mut pcs = [0.0; 3];
+                lut.eval(input, &mut pcs);
+                decode_pcs(pcs, *encoding)
+            }
+        }
+    }
+
+    fn xyz_to_device(&self, xyz: [f32; 3], output: &mut [f32]) {
+        match &self.output {
+            FromPcs::Rgb(curves, matrix) => {
+                let linear = mul_3x3(matrix, xyz);
+                for ((out, curve), value) in output.iter_mut().zip(curves).zip(linear) {
+                    *out = curve.eval(value.clamp(0.0, 1.0));
+                }
+            }
+            FromPcs::Gray(curve) => {
+                output[0] = curve.eval((xyz[1] / D50_WHITE[1]).clamp(0.0, 1.0));
+            }
+            FromPcs::Lut(lut, encoding) => {
+                lut.eval(&encode_pcs(xyz, *encoding), output);
+            }
+        }
+    }
+}
+
+/// Scale of the 16 bit XYZ encoding in normalized values, 1.0 is stored as 0x8000
+const XYZ_SCALE: f32 = 65535.0 / 32768.0;
+/// Scale of the legacy 16 bit Lab encoding, 100 is stored as 0xFF00
+const LAB_LEGACY_SCALE: f32 = 65535.0 / 65280.0;
+
+fn decode_pcs(pcs: [f32; 3], encoding: PcsEncoding) -> [f32; 3] {
+    match encoding {
+        PcsEncoding::Xyz => pcs.map(|x| x * XYZ_SCALE),
+        PcsEncoding::Lab => lab_to_xyz([
+            pcs[0] * 100.0,
+            pcs[1] * 255.0 - 128.0,
+            pcs[2] * 255.0 - 128.0
+        ]),
+        PcsEncoding::LabLegacy => {
+            let [l, a, b] = pcs.map(|x| x * LAB_LEGACY_SCALE);
+            lab_to_xyz([l * 100.0, a * 255.0 - 128.0, b * 255.0 - 128.0])
+        }
+    }
+}
+
+fn encode_pcs(xyz: [f32; 3], encoding: PcsEncoding) -> [f32; 3] {
+    match encoding {
+        PcsEncoding::Xyz => xyz.map(|x| x / XYZ_SCALE),
+        PcsEncoding::Lab | PcsEncoding::LabLegacy => {
+            let [l, a, b] = xyz_to_lab(xyz);
+            let encoded = [l / 100.0, (a + 128.0) / 255.0, (b + 128.0) / 255.0];
+
+            if encoding == PcsEncoding::LabLegacy {
+                encoded.map(|x| x / LAB_LEGACY_SCALE)
+            } else {
+                encoded
+            }
+        }
+    }
+}
+
+/// Convert D50 XYZ to CIE Lab
+#[must_use]
+pub fn xyz_to_lab(xyz: [f32; 3]) -> [f32; 3] {
+    const EPSILON: f32 = 216.0 / 24389.0;
+    const KAPPA: f32 = 24389.0 / 27.0;
+
+    let f = |t: f32| {
+        if t > EPSILON {
+            t.cbrt()
+        } else {
+            (KAPPA * t + 16.0) / 116.0
+        }
+    };
+    let [fx, fy, fz] = [
+        f(xyz[0] / D50_WHITE[0]),
+        f(xyz[1] / D50_WHITE[1]),
+        f(xyz[2] / D50_WHITE[2])
+    ];
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+/// Convert CIE Lab to D50 XYZ
+#[must_use]
+pub fn lab_to_xyz(lab: [f32; 3]) -> [f32; 3] {
+    const EPSILON: f32 = 216.0 / 24389.0;
+    const KAPPA: f32 = 24389.0 / 27.0;
+
+    let fy = (lab[0] + 16.0) / 116.0;
+    let fx = fy + lab[1] / 500.0;
+    let fz = fy - lab[2] / 200.0;
+
+    let inverse = |f: f32| {
+        let cube = f * f * f;
+        if cube > EPSILON {
+            cube
+        } else {
+            (116.0 * f - 16.0) / KAPPA
+        }
+    };
+    [
+        inverse(fx) * D50_WHITE[0],
+        inverse(fy) * D50_WHITE[1],
+        inverse(fz) * D50_WHITE[2]
+    ]
+}
+
+fn transpose(m: [[f32; 3]; 3]) -> [[f32; 3]; 3] {
+    [
+        [m[0][0], m[1][0], m[2][0]],
+        [m[0][1], m[1][1], m[2][1]],
+        [m[0][2], m[1][2], m[2][2]]
+    ]
+}
+
+fn mul_3x3(m: &[[f32; 3]; 3], v: [f32; 3]) -> [f32; 3] {
+    m.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+}
+
+/// Invert a 3x3 matrix, returning `None` if it is singular
+#[must_use]
+pub fn invert_3x3(m: [[f32; 3]; 3]) -> Option<[[f32; 3]; 3]> {
+    let det = m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+        - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+        + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0]);
+
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let inv = 1.0 / det;
+    Some([
+        [
+            (m[1][1] * m[2][2] - m[1][2] * m[2][1]) * inv,
+            (m[0][2] * m[2][1] - m[0][1] * m[2][2]) * inv,
+            (m[0][1] * m[1][2] - m[0][2] * m[1][1]) * inv
+        ],
+        [
+            (m[1][2] * m[2][0] - m[1][0] * m[2][2]) * inv,
+            (m[0][0] * m[2][2] - m[0][2] * m[2][0]) * inv,
+            (m[0][2] * m[1][0] - m[0][0] * m[1][2]) * inv
+        ],
+        [
+            (m[1][0] * m[2][1] - m[1][1] * m[2][0]) * inv,
+            (m[0][1] * m[2][0] - m[0][0] * m[2][1]) * inv,
+            (m[0][0] * m[1][1] - m[0][1] * m[1][0]) * inv
+        ]
+    ])
+}
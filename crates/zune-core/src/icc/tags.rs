@@ -0,0 +1,568 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Parsing and evaluation of ICC tag types
+//!
+//! This contains the tone curve (`curv`, `para`) and lookup table
+//! (`mft1`, `mft2`, `mAB `, `mBA `) tag types, plus the byte reading helpers shared by the
+//! profile parser.
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::icc::IccError;
+
+/// Read a big endian u16 at `offset`
+pub(crate) fn read_u16(data: &[u8], offset: usize) -> Result<u16, IccError> {
+    match data.get(offset..offset + 2) {
+        Some(bytes) => Ok(u16::from_be_bytes([bytes[0], bytes[1]])),
+        None => Err(IccError::NotEnoughBytes(offset + 2, data.len()))
+    }
+}
+
+/// Read a big endian u32 at `offset`
+pub(crate) fn read_u32(data: &[u8], offset: usize) -> Result<u32, IccError> {
+    match data.get(offset..offset + 4) {
+        Some(bytes) => Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
+        None => Err(IccError::NotEnoughBytes(offset + 4, data.len()))
+    }
+}
+
+/// Read a big endian `s15Fixed16Number` at `offset`
+#[allow(clippy::cast_possible_wrap, clippy::cast_precision_loss)]
+pub(crate) fn read_s15_fixed16(data: &[u8], offset: usize) -> Result<f32, IccError> {
+    Ok(read_u32(data, offset)? as i32 as f32 / 65536.0)
+}
+
+/// Read a byte at `offset`
+pub(crate) fn read_u8(data: &[u8], offset: usize) -> Result<u8, IccError> {
+    data.get(offset)
+        .copied()
+        .ok_or(IccError::NotEnoughBytes(offset + 1, data.len()))
+}
+
+/// A one dimensional transfer function, mapping 0..1 to 0..1
+#[derive(Clone, Debug, PartialEq)]
+pub enum ToneCurve {
+    /// `y = x`
+    Identity,
+    /// `y = x^gamma`
+    Gamma(f32),
+    /// Evenly spaced samples of the curve, with linear interpolation between them
+    Table(Vec<f32>),
+    /// One of the ICC parametric curve types 0 to 4.
+    ///
+    /// Parameters are in order `[g, a, b, c, d, e, f]`, unused parameters are zero
+    Parametric(u16, [f32; 7])
+}
+
+impl ToneCurve {
+    /// Parse a `curv` or `para` tag starting at `data[0]`
+    ///
+    /// Returns the curve and the number of bytes it occupies
+    pub(crate) fn parse(data: &[u8]) -> Result<(ToneCurve, usize), IccError> {
+        match &read_u32(data, 0)?.to_be_bytes() {
+            b"curv" => {
+                let count = read_u32(data, 8)? as usize;
+                let curve = match count {
+                    0 => ToneCurve::Identity,
+                    1 => ToneCurve::Gamma(f32::from(read_u16(data, 12)?) / 256.0),
+                    _ => {
+                        let mut table = Vec::with_capacity(count.min(data.len()));
+                        for i in 0..count {
+                            table.push(f32::from(read_u16(data, 12 + i * 2)?) / 65535.0);
+                        }
+                        ToneCurve::Table(table)
+                    }
+                };
+                Ok((curve, 12 + count * 2))
+            }
+            b"para" => {
+                let kind = read_u16(data, 8)?;
+                let count = match kind {
+                    0 => 1,
+                    1 => 3,
+                    2 => 4,
+                    3 => 5,
+                    4 => 7,
+                    _ => return Err(IccError::Unsupported("Unknown parametric curve type"))
+                };
+                let mut params = [0.0; 7];
+                for (i, param) in params.iter_mut().take(count).enumerate() {
+                    *param = read_s15_fixed16(data, 12 + i * 4)?;
+                }
+                let curve = if kind == 0 {
+                    ToneCurve::Gamma(params[0])
+                } else {
+                    ToneCurve::Parametric(kind, params)
+                };
+                Ok((curve, 12 + count * 4))
+            }
+            _ => Err(IccError::Malformed("Expected a curv or para tag"))
+        }
+    }
+
+    /// Evaluate the curve at `x`, which is clamped to 0..1
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::many_single_char_names
+    )]
+    #[must_use]
+    pub fn eval(&self, x: f32) -> f32 {
+        let x = x.clamp(0.0, 1.0);
+
+        match self {
+            ToneCurve::Identity => x,
+            ToneCurve::Gamma(g) => x.powf(*g),
+            ToneCurve::Table(table) => match table.len() {
+                0 => x,
+                1 => table[0],
+                len => {
+                    let pos = x * (len - 1) as f32;
+                    let start = (pos as usize).min(len - 2);
+                    let frac = pos - start as f32;
+
+                    table[start] + (table[start + 1] - table[start]) * frac
+                }
+            },
+            ToneCurve::Parametric(kind, [g, a, b, c, d, e, f]) => {
+                let power = |v: f32| if v > 0.0 { v.powf(*g) } else { 0.0 };
+
+                match kind {
+                    1 => {
+                        if x >= -b / a {
+                            power(a * x + b)
+                        } else {
+                            0.0
+                        }
+                    }
+                    2 => {
+                        if x >= -b / a {
+                            power(a * x + b) + c
+                        } else {
+                            *c
+                        }
+                    }
+                    3 => {
+                        if x >= *d {
+                            power(a * x + b)
+                        } else {
+                            c * x
+                        }
+                    }
+                    4 => {
+                        if x >= *d {
+                            power(a * x + b) + e
+                        } else {
+                            c * x + f
+                        }
+                    }
+                    _ => x.powf(*g)
+                }
+            }
+        }
+    }
+
+    /// Return a tabulated inverse of this curve with `samples` entries
+    ///
+    /// The curve is assumed to be monotonic, non monotonic curves return an approximation
+    #[allow(clippy::cast_precision_loss)]
+    #[must_use]
+    pub fn inverse(&self, samples: usize) -> ToneCurve {
+        match self {
+            ToneCurve::Identity => ToneCurve::Identity,
+            ToneCurve::Gamma(g) if *g > 0.0 => ToneCurve::Gamma(1.0 / g),
+            _ => {
+                let samples = samples.max(2);
+                let increasing = self.eval(1.0) >= self.eval(0.0);
+                let table = (0..samples)
+                    .map(|i| {
+                        let target = i as f32 / (samples - 1) as f32;
+                        // bisection, the curve is monotonic
+                        let (mut lo, mut hi) = (0.0_f32, 1.0_f32);
+                        for _ in 0..24 {
+                            let mid = (lo + hi) / 2.0;
+                            if (self.eval(mid) < target) == increasing {
+                                lo = mid;
+                            } else {
+                                hi = mid;
+                            }
+                        }
+                        (lo + hi) / 2.0
+                    })
+                    .collect();
+                ToneCurve::Table(table)
+            }
+        }
+    }
+}
+
+/// A multidimensional color lookup table, with multilinear interpolation
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Clut {
+    /// Number of grid points in each input dimension
+    grid:    Vec<usize>,
+    outputs: usize,
+    /// Normalized output values, the last input dimension varies fastest
+    data:    Vec<f32>
+}
+
+impl Clut {
+    fn new(grid: Vec<usize>, outputs: usize, data: Vec<f32>) -> Result<Clut, IccError> {
+        let expected = grid
+            .iter()
+            .try_fold(outputs, |acc, &points| acc.checked_mul(points))
+            .ok_or(IccError::Malformed("CLUT is too large"))?;
+
+        if grid.contains(&0) {
+            return Err(IccError::Malformed("CLUT grid needs at least one point"));
+        }
+        if data.len() != expected {
+            return Err(IccError::Malformed("CLUT size does not match its grid"));
+        }
+        Ok(Clut {
+            grid,
+            outputs,
+            data
+        })
+    }
+
+    /// Read `count` samples of `precision` bytes each, normalized to 0..1
+    fn read_samples(
+        data: &[u8], offset: usize, count: usize, precision: usize
+    ) -> Result<Vec<f32>, IccError> {
+        let end = offset + count * precision;
+        if data.len() < end {
+            return Err(IccError::NotEnoughBytes(end, data.len()));
+        }
+        let samples = &data[offset..end];
+
+        Ok(if precision == 1 {
+            samples.iter().map(|&x| f32::from(x) / 255.0).collect()
+        } else {
+            samples
+                .chunks_exact(2)
+                .map(|x| f32::from(u16::from_be_bytes([x[0], x[1]])) / 65535.0)
+                .collect()
+        })
+    }
+
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn eval(&self, input: &[f32], output: &mut [f32]) {
+        let dims = self.grid.len();
+        // position of the lower grid corner and the fractional position in each dimension
+        let mut base = [0_usize; 16];
+        let mut frac = [0.0_f32; 16];
+        let mut strides = [0_usize; 16];
+
+        let mut stride = self.outputs;
+        for d in (0..dims).rev() {
+            strides[d] = stride;
+            stride *= self.grid[d];
+        }
+        for d in 0..dims {
+            let points = self.grid[d];
+            let pos = input[d].clamp(0.0, 1.0) * (points - 1) as f32;
+            let start = (pos as usize).min(points.saturating_sub(2));
+
+            base[d] = start;
+            frac[d] = pos - start as f32;
+        }
+        output[..self.outputs].fill(0.0);
+
+        // sum the contribution of every corner of the surrounding hypercube
+        for corner in 0..(1_usize << dims) {
+            let mut weight = 1.0;
+            let mut index = 0;
+
+            for d in 0..dims {
+                let upper = (corner >> d) & 1 == 1;
+                let step = usize::from(upper && self.grid[d] > 1);
+
+                weight *= if upper { frac[d] } else { 1.0 - frac[d] };
+                index += (base[d] + step) * strides[d];
+            }
+            if weight == 0.0 {
+                continue;
+            }
+            for (out, value) in output
+                .iter_mut()
+                .zip(&self.data[index..index + self.outputs])
+            {
+                *out += weight * value;
+            }
+        }
+    }
+}
+
+/// Read `channels` consecutive curve tables of `entries` samples each
+fn read_tables(
+    data: &[u8], offset: usize, channels: usize, entries: usize, precision: usize
+) -> Result<Vec<ToneCurve>, IccError> {
+    (0..channels)
+        .map(|i| {
+            Clut::read_samples(data, offset + i * entries * precision, entries, precision)
+                .map(ToneCurve::Table)
+        })
+        .collect()
+}
+
+/// A single processing element of a lookup table tag
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Stage {
+    /// One curve per channel
+    Curves(Vec<ToneCurve>),
+    /// A 3x3 matrix, stored row major, followed by three offsets
+    Matrix([f32; 12]),
+    /// A color lookup table
+    Clut(Clut)
+}
+
+impl Stage {
+    fn eval(&self, values: &mut [f32; 16], channels: &mut usize) {
+        match self {
+            Stage::Curves(curves) => {
+                for (value, curve) in values.iter_mut().zip(curves) {
+                    *value = curve.eval(*value);
+                }
+            }
+            Stage::Matrix(m) => {
+                let [x, y, z] = [values[0], values[1], values[2]];
+
+                for (i, value) in values.iter_mut().take(3).enumerate() {
+                    *value = m[i * 3] * x + m[i * 3 + 1] * y + m[i * 3 + 2] * z + m[9 + i];
+                }
+                *channels = 3;
+            }
+            Stage::Clut(clut) => {
+                let mut output = [0.0; 16];
+                clut.eval(&values[..*channels], &mut output);
+                values[..clut.outputs].copy_from_slice(&output[..clut.outputs]);
+                *channels = clut.outputs;
+            }
+        }
+    }
+}
+
+/// How the profile connection space values of a lookup table are encoded
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum PcsEncoding {
+    /// CIE XYZ, where 1.0 is stored as 0x8000 of 0xFFFF
+    Xyz,
+    /// CIE Lab, with L in 0..100 mapped to 0..1 and a,b in -128..127 mapped to 0..1
+    Lab,
+    /// The legacy 16 bit Lab encoding used by `mft2` tags, where 0xFF00 represents L=100
+    LabLegacy
+}
+
+/// A parsed lookup table tag (`mft1`, `mft2`, `mAB ` or `mBA `)
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Lut {
+    pub(crate) input_channels:  usize,
+    pub(crate) output_channels: usize,
+    pub(crate) stages:          Vec<Stage>,
+    /// Encoding used for 16 bit lab tables, the profile color space decides
+    /// whether this is Lab or XYZ
+    pub(crate) legacy_lab:      bool
+}
+
+impl Lut {
+    /// Parse a lookup table tag starting at `data[0]`
+    ///
+    /// `xyz_input` is true for tables converting from an XYZ profile connection space,
+    /// the matrix of `mft1`/`mft2` tables is only applied to such input
+    pub(crate) fn parse(data: &[u8], xyz_input: bool) -> Result<Lut, IccError> {
+        let signature = read_u32(data, 0)?.to_be_bytes();
+        let input_channels = usize::from(read_u8(data, 8)?);
+        let output_channels = usize::from(read_u8(data, 9)?);
+
+        if input_channels == 0
+            || input_channels > 15
+            || output_channels == 0
+            || output_channels > 15
+        {
+            return Err(IccError::Malformed("Invalid number of LUT channels"));
+        }
+
+        match &signature {
+            b"mft1" | b"mft2" => {
+                let wide = &signature == b"mft2";
+                let grid_points = usize::from(read_u8(data, 10)?);
+
+                let mut matrix = [0.0; 12];
+                for (i, value) in matrix.iter_mut().take(9).enumerate() {
+                    *value = read_s15_fixed16(data, 12 + i * 4)?;
+                }
+                let (precision, in_entries, out_entries, mut offset) = if wide {
+                    (
+                        2,
+                        usize::from(read_u16(data, 48)?),
+                        usize::from(read_u16(data, 50)?),
+                        52
+                    )
+                } else {
+                    (1, 256, 256, 48)
+                };
+                let input_curves =
+                    read_tables(data, offset, input_channels, in_entries, precision)?;
+                offset += input_channels * in_entries * precision;
+
+                let grid = vec![grid_points; input_channels];
+                let clut_len = grid_points
+                    .checked_pow(u32::try_from(input_channels).unwrap_or(u32::MAX))
+                    .and_then(|x| x.checked_mul(output_channels))
+                    .ok_or(IccError::Malformed("CLUT is too large"))?;
+                let clut_data = Clut::read_samples(data, offset, clut_len, precision)?;
+                offset += clut_len * precision;
+
+                let output_curves =
+                    read_tables(data, offset, output_channels, out_entries, precision)?;
+
+                let mut stages = Vec::new();
+                let is_identity = matrix[..9]
+                    .iter()
+                    .zip([1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0])
+                    .all(|(a, b)| (a - b).abs() < 1e-6);
+
+                if xyz_input && input_channels == 3 && !is_identity {
+                    stages.push(Stage::Matrix(matrix));
+                }
+                stages.push(Stage::Curves(input_curves));
+                stages.push(Stage::Clut(Clut::new(grid, output_channels, clut_data)?));
+                stages.push(Stage::Curves(output_curves));
+
+                Ok(Lut {
+                    input_channels,
+                    output_channels,
+                    stages,
+                    legacy_lab: wide
+                })
+            }
+            b"mAB " | b"mBA " => {
+                let a_to_b = &signature == b"mAB ";
+
+                let b_offset = read_u32(data, 12)? as usize;
+                let matrix_offset = read_u32(data, 16)? as usize;
+                let m_offset = read_u32(data, 20)? as usize;
+                let clut_offset = read_u32(data, 24)? as usize;
+                let a_offset = read_u32(data, 28)? as usize;
+
+                // A curves are on the device side, B curves on the PCS side
+                let (device_channels, pcs_channels) = if a_to_b {
+                    (input_channels, output_channels)
+                } else {
+                    (output_channels, input_channels)
+                };
+                if pcs_channels != 3 {
+                    return Err(IccError::Malformed("LUT must have three PCS channels"));
+                }
+                let curves = |offset: usize, count: usize| -> Result<Option<Stage>, IccError> {
+                    if offset == 0 {
+                        return Ok(None);
+                    }
+                    let mut curves = Vec::with_capacity(count);
+                    let mut position = offset;
+                    for _ in 0..count {
+                        let (curve, len) =
+                            ToneCurve::parse(data.get(position..).unwrap_or_default())?;
+                        curves.push(curve);
+                        // every curve is padded to a four byte boundary
+                        position += (len + 3) & !3;
+                    }
+                    Ok(Some(Stage::Curves(curves)))
+                };
+                let matrix = if matrix_offset == 0 {
+                    None
+                } else {
+                    let mut matrix = [0.0; 12];
+                    for (i, value) in matrix.iter_mut().enumerate() {
+                        *value = read_s15_fixed16(data, matrix_offset + i * 4)?;
+                    }
+                    Some(Stage::Matrix(matrix))
+                };
+                let clut = if clut_offset == 0 {
+                    None
+                } else {
+                    let (clut_in, clut_out) = if a_to_b {
+                        (device_channels, pcs_channels)
+                    } else {
+                        (pcs_channels, device_channels)
+                    };
+                    let grid = (0..clut_in)
+                        .map(|i| read_u8(data, clut_offset + i).map(usize::from))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let precision = usize::from(read_u8(data, clut_offset + 16)?);
+                    if precision != 1 && precision != 2 {
+                        return Err(IccError::Malformed("Invalid CLUT precision"));
+                    }
+                    let len = grid
+                        .iter()
+                        .try_fold(clut_out, |acc, &points| acc.checked_mul(points))
+                        .ok_or(IccError::Malformed("CLUT is too large"))?;
+                    let samples = Clut::read_samples(data, clut_offset + 20, len, precision)?;
+
+                    Some(Stage::Clut(Clut::new(grid, clut_out, samples)?))
+                };
+                let a_curves = curves(a_offset, device_channels)?;
+                let m_curves = curves(m_offset, pcs_channels)?;
+                let b_curves = curves(b_offset, pcs_channels)?;
+
+                if b_curves.is_none() {
+                    return Err(IccError::Malformed("LUT is missing required B curves"));
+                }
+                let ordered = if a_to_b {
+                    [a_curves, clut, m_curves, matrix, b_curves]
+                } else {
+                    [b_curves, matrix, m_curves, clut, a_curves]
+                };
+                Ok(Lut {
+                    input_channels,
+                    output_channels,
+                    stages: ordered.into_iter().flatten().collect(),
+                    legacy_lab: false
+                })
+            }
+            _ => Err(IccError::Unsupported("Unsupported lookup table type"))
+        }
+    }
+
+    /// Run the lookup table, `input` has `input_channels` values, `output` receives
+    /// `output_channels` values
+    pub(crate) fn eval(&self, input: &[f32], output: &mut [f32]) {
+        let mut values = [0.0; 16];
+        let mut channels = self.input_channels;
+
+        for (value, &x) in values
+            .iter_mut()
+            .zip(input.iter().take(self.input_channels))
+        {
+            *value = x.clamp(0.0, 1.0);
+        }
+        for stage in &self.stages {
+            stage.eval(&mut values, &mut channels);
+        }
+        for (out, value) in output
+            .iter_mut()
+            .zip(values.iter().take(self.output_channels))
+        {
+            *out = value.clamp(0.0, 1.0);
+        }
+    }
+
+    pub(crate) fn pcs_encoding(&self, lab: bool) -> PcsEncoding {
+        match (lab, self.legacy_lab) {
+            (false, _) => PcsEncoding::Xyz,
+            (true, false) => PcsEncoding::Lab,
+            (true, true) => PcsEncoding::LabLegacy
+        }
+    }
+}
@@ -13,8 +13,10 @@
 //!
 //! - A bytestream reader and writer with endian aware reads and writes
 //! - Colorspace and bit depth information shared by images
+//! - ICC profile parsing and color conversion (requires the `std` feature)
 //! - Image decoder and encoder options
 //! - A simple enum type to hold image decoding results.
+//! - Runtime selection of SIMD kernels
 //!
 //! This library is `#[no_std]` with `alloc` feature needed for defining `Vec`
 //! which we need for storing decoded  bytes.
@@ -57,6 +59,9 @@ pub use log;
 pub mod bit_depth;
 pub mod bytestream;
 pub mod colorspace;
+#[cfg(feature = "std")]
+pub mod icc;
 pub mod options;
 pub mod result;
 mod serde;
+pub mod simd;
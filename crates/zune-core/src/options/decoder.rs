@@ -11,6 +11,7 @@
 
 use crate::bit_depth::ByteEndian;
 use crate::colorspace::ColorSpace;
+use crate::simd::Isa;
 
 /// A decoder that can handle errors
 fn decoder_error_tolerance_mode() -> DecoderFlags {
@@ -32,6 +33,7 @@ fn fast_options() -> DecoderFlags {
         zune_use_neon:   true,
         zune_use_avx:    true,
         zune_use_avx2:   true,
+        zune_use_avx512: true,
         zune_use_sse2:   true,
         zune_use_sse3:   true,
         zune_use_sse41:  true,
@@ -39,7 +41,10 @@ fn fast_options() -> DecoderFlags {
         png_add_alpha_channel:     false,
         png_strip_16_bit_to_8_bit: false,
         png_decode_animated:       true,
-        jxl_decode_animated:       true
+        png_keep_indices:          false,
+        jxl_decode_animated:       true,
+
+        auto_orient: false
     }
 }
 
@@ -59,6 +64,7 @@ fn cmd_options() -> DecoderFlags {
         zune_use_neon:   true,
         zune_use_avx:    true,
         zune_use_avx2:   true,
+        zune_use_avx512: true,
         zune_use_sse2:   true,
         zune_use_sse3:   true,
         zune_use_sse41:  true,
@@ -67,7 +73,10 @@ fn cmd_options() -> DecoderFlags {
         png_strip_16_bit_to_8_bit: false,
 
         png_decode_animated: true,
-        jxl_decode_animated: true
+        png_keep_indices:    false,
+        jxl_decode_animated: true,
+
+        auto_orient: false
     }
 }
 
@@ -100,6 +109,8 @@ pub struct DecoderFlags {
     zune_use_avx:                 bool,
     /// Whether we should use avx2 instructions where possible.
     zune_use_avx2:                bool,
+    /// Whether we should use avx512 instructions where possible, on top of `zune_use_avx2`.
+    zune_use_avx512:              bool,
     /// Whether the png decoder should add alpha channel where possible.
     png_add_alpha_channel:        bool,
     /// Whether we should use neon instructions where possible.
@@ -108,7 +119,12 @@ pub struct DecoderFlags {
     png_strip_16_bit_to_8_bit:    bool,
     /// Decode all frames for an animated images
     png_decode_animated:          bool,
-    jxl_decode_animated:          bool
+    /// Whether the png decoder should return palette indices instead of colors
+    png_keep_indices:             bool,
+    jxl_decode_animated:          bool,
+    /// Whether images should be rotated and flipped to be upright
+    /// according to their exif orientation
+    auto_orient:                  bool
 }
 
 /// Decoder options
@@ -130,6 +146,29 @@ pub struct DecoderOptions {
     /// - Default value: 16384
     /// - Respected by: `all decoders`
     max_height:     usize,
+    /// Maximum number of pixels, width times height, of a single frame
+    ///
+    /// - Default value: unlimited
+    /// - Respected by: `all decoders`
+    max_pixels:     usize,
+    /// Maximum number of frames of animated images
+    ///
+    /// - Default value: unlimited
+    /// - Respected by: `all decoders`, still images have a single frame
+    max_frames:     usize,
+    /// Maximum number of bytes the decoded pixels of all frames may take up
+    ///
+    /// - Default value: unlimited
+    /// - Respected by: `all decoders`
+    max_memory:     usize,
+    /// Maximum number of threads a decoder may use for a single image
+    ///
+    /// `0` lets the decoder pick, usually a thread per cpu core,
+    /// `1` decodes on the calling thread only
+    ///
+    /// - Default value: 0
+    /// - Respected by: `jpeg` (images with restart markers) and `png` (large images)
+    max_threads:    usize,
     /// Output colorspace
     ///
     /// The jpeg decoder allows conversion to a separate colorspace
@@ -138,6 +177,9 @@ pub struct DecoderOptions {
     /// I.e you can convert a RGB jpeg image to grayscale without
     /// first decoding it to RGB to get
     ///
+    /// Setting this to `ColorSpace::YCbCr` returns the decoded samples of
+    /// YCbCr images without color conversion
+    ///
     /// - Default value: `ColorSpace::RGB`
     /// - Respected by: `jpeg`
     out_colorspace: ColorSpace,
@@ -208,6 +250,27 @@ impl DecoderOptions {
         self.max_height
     }
 
+    /// Get maximum number of pixels, width times height, of a single frame
+    pub const fn max_pixels(&self) -> usize {
+        self.max_pixels
+    }
+
+    /// Get maximum number of frames decoded from animated images
+    pub const fn max_frames(&self) -> usize {
+        self.max_frames
+    }
+
+    /// Get maximum number of bytes the decoded pixels of all frames may take up
+    pub const fn max_memory(&self) -> usize {
+        self.max_memory
+    }
+
+    /// Get maximum number of threads a decoder may use for a single image,
+    /// `0` if the decoder picks
+    pub const fn max_threads(&self) -> usize {
+        self.max_threads
+    }
+
     /// Return true whether the decoder should be in strict mode
     /// And reject most errors
     pub fn strict_mode(&self) -> bool {
@@ -247,6 +310,67 @@ impl DecoderOptions {
         self
     }
 
+    /// Set maximum number of pixels, width times height, of a single frame
+    ///
+    /// This rejects images that are within the maximum width and height
+    /// but too large in both
+    pub const fn set_max_pixels(mut self, pixels: usize) -> Self {
+        self.max_pixels = pixels;
+        self
+    }
+
+    /// Set maximum number of frames decoded from animated images
+    pub const fn set_max_frames(mut self, frames: usize) -> Self {
+        self.max_frames = frames;
+        self
+    }
+
+    /// Set maximum number of bytes the decoded pixels of all frames may take up
+    ///
+    /// The size is estimated from the dimensions, colorspace and depth of the image
+    /// before its pixels are decoded
+    pub const fn set_max_memory(mut self, bytes: usize) -> Self {
+        self.max_memory = bytes;
+        self
+    }
+
+    /// Check an image against the maximum pixels, frames and memory
+    ///
+    /// Decoders call this once the headers are known, before decoding pixels
+    ///
+    /// # Arguments
+    ///
+    /// * `width`, `height`: The dimensions of the image
+    /// * `bytes_per_pixel`: Bytes of a decoded pixel, i.e components times bytes per component
+    /// * `frames`: The number of frames that will be decoded, `1` for still images
+    ///
+    /// returns: The name of the first limit exceeded, the value found and the limit,
+    /// or `None` if the image is within the limits
+    pub fn exceeded_limit(
+        &self, width: usize, height: usize, bytes_per_pixel: usize, frames: usize
+    ) -> Option<(&'static str, usize, usize)> {
+        let pixels = width.saturating_mul(height);
+        let memory = pixels
+            .saturating_mul(bytes_per_pixel)
+            .saturating_mul(frames);
+
+        [
+            ("pixels", pixels, self.max_pixels),
+            ("frames", frames, self.max_frames),
+            ("memory", memory, self.max_memory)
+        ]
+        .into_iter()
+        .find(|(_, found, max)| found > max)
+    }
+
+    /// Set maximum number of threads a decoder may use for a single image
+    ///
+    /// `0` lets the decoder pick, `1` decodes on the calling thread only
+    pub const fn set_max_threads(mut self, threads: usize) -> Self {
+        self.max_threads = threads;
+        self
+    }
+
     /// Whether the routines can use unsafe platform specific
     /// intrinsics when necessary
     ///
@@ -266,6 +390,18 @@ impl DecoderOptions {
         self
     }
 
+    /// Whether the routines can use AVX-512 intrinsics on top of AVX2 ones
+    ///
+    /// They are only used on cpus which don't lower their clock speed
+    /// running them, see [`Isa::Avx512Bw`], this turns them off on others, e.g.
+    /// when other programs sharing the cores with the decoder suffer from them.
+    ///
+    /// - Respected by: `jpeg`
+    pub fn set_use_avx512(mut self, yes: bool) -> Self {
+        self.flags.zune_use_avx512 = yes;
+        self
+    }
+
     fn set_decoder_flags(mut self, flags: DecoderFlags) -> Self {
         self.flags = flags;
         self
@@ -312,6 +448,24 @@ impl DecoderOptions {
     pub const fn byte_endian(&self) -> ByteEndian {
         self.endianness
     }
+
+    /// Return true if decoded images should be rotated and flipped so
+    /// that they are upright according to their exif orientation
+    pub const fn auto_orient(&self) -> bool {
+        self.flags.auto_orient
+    }
+
+    /// Set whether decoded images should be rotated and flipped so that they
+    /// are upright according to their exif orientation
+    ///
+    /// The orientation is reset afterwards, so that viewers don't apply it
+    /// twice when the image is saved again
+    ///
+    /// - Respected by: `zune-image` when it reads exif metadata
+    pub const fn set_auto_orient(mut self, yes: bool) -> Self {
+        self.flags.auto_orient = yes;
+        self
+    }
 }
 
 /// PNG specific options
@@ -393,6 +547,22 @@ impl DecoderOptions {
         self.flags.png_decode_animated = yes;
         self
     }
+
+    /// Return true if the png decoder should return the palette indices of paletted
+    /// images instead of expanding them to colors
+    pub const fn png_get_keep_indices(&self) -> bool {
+        self.flags.png_keep_indices
+    }
+    /// Set whether the png decoder should return the palette indices of paletted
+    /// images instead of expanding them to colors
+    ///
+    /// Paletted images are then decoded as [`ColorSpace::Luma`] with one index per
+    /// pixel, the tRNS chunk and [`png_set_add_alpha_channel`](Self::png_set_add_alpha_channel)
+    /// are ignored for them. Other images are not affected
+    pub const fn png_set_keep_indices(mut self, yes: bool) -> Self {
+        self.flags.png_keep_indices = yes;
+        self
+    }
 }
 
 /// JPEG specific options
@@ -437,32 +607,9 @@ impl DecoderOptions {
     ///
     /// This checks for existence of SSE2 first and returns
     /// false if it's not present
-    #[allow(unreachable_code)]
     pub fn use_sse2(&self) -> bool {
-        let opt = self.flags.zune_use_sse2 | self.flags.zune_use_unsafe;
-        // options says no
-        if !opt {
-            return false;
-        }
-
-        #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
-        {
-            // where we can do runtime check if feature is present
-            #[cfg(feature = "std")]
-            {
-                if is_x86_feature_detected!("sse2") {
-                    return true;
-                }
-            }
-            // where we can't do runtime check if feature is present
-            // check if the compile feature had it enabled
-            #[cfg(all(not(feature = "std"), target_feature = "sse2"))]
-            {
-                return true;
-            }
-        }
-        // everything failed return false
-        false
+        // options says no, or the cpu doesn't have it
+        (self.flags.zune_use_sse2 | self.flags.zune_use_unsafe) && Isa::Sse2.is_supported()
     }
 
     /// Use SSE 3 paths where possible
@@ -470,146 +617,54 @@ impl DecoderOptions {
     ///
     /// This also checks for SSE3 support and returns false if
     /// it's not present
-    #[allow(unreachable_code)]
     pub fn use_sse3(&self) -> bool {
-        let opt = self.flags.zune_use_sse3 | self.flags.zune_use_unsafe;
-        // options says no
-        if !opt {
-            return false;
-        }
-
-        #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
-        {
-            // where we can do runtime check if feature is present
-            #[cfg(feature = "std")]
-            {
-                if is_x86_feature_detected!("sse3") {
-                    return true;
-                }
-            }
-            // where we can't do runtime check if feature is present
-            // check if the compile feature had it enabled
-            #[cfg(all(not(feature = "std"), target_feature = "sse3"))]
-            {
-                return true;
-            }
-        }
-        // everything failed return false
-        false
+        // options says no, or the cpu doesn't have it
+        (self.flags.zune_use_sse3 | self.flags.zune_use_unsafe) && Isa::Sse3.is_supported()
     }
 
     /// Use SSE4 paths where possible
     ///
     /// This also checks for sse 4.1 support and returns false if it
     /// is not present
-    #[allow(unreachable_code)]
     pub fn use_sse41(&self) -> bool {
-        let opt = self.flags.zune_use_sse41 | self.flags.zune_use_unsafe;
-        // options says no
-        if !opt {
-            return false;
-        }
-
-        #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
-        {
-            // where we can do runtime check if feature is present
-            #[cfg(feature = "std")]
-            {
-                if is_x86_feature_detected!("sse4.1") {
-                    return true;
-                }
-            }
-            // where we can't do runtime check if feature is present
-            // check if the compile feature had it enabled
-            #[cfg(all(not(feature = "std"), target_feature = "sse4.1"))]
-            {
-                return true;
-            }
-        }
-        // everything failed return false
-        false
+        // options says no, or the cpu doesn't have it
+        (self.flags.zune_use_sse41 | self.flags.zune_use_unsafe) && Isa::Sse41.is_supported()
     }
 
     /// Use AVX paths where possible
     ///
     /// This also checks for AVX support and returns false if it's
     /// not present
-    #[allow(unreachable_code)]
     pub fn use_avx(&self) -> bool {
-        let opt = self.flags.zune_use_avx | self.flags.zune_use_unsafe;
-        // options says no
-        if !opt {
-            return false;
-        }
-
-        #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
-        {
-            // where we can do runtime check if feature is present
-            #[cfg(feature = "std")]
-            {
-                if is_x86_feature_detected!("avx") {
-                    return true;
-                }
-            }
-            // where we can't do runitme check if feature is present
-            // check if the compile feature had it enabled
-            #[cfg(all(not(feature = "std"), target_feature = "avx"))]
-            {
-                return true;
-            }
-        }
-        // everything failed return false
-        false
+        // options says no, or the cpu doesn't have it
+        (self.flags.zune_use_avx | self.flags.zune_use_unsafe) && Isa::Avx.is_supported()
     }
 
     /// Use avx2 paths where possible
     ///
     /// This also checks for AVX2 support and returns false if it's not
     /// present
-    #[allow(unreachable_code)]
     pub fn use_avx2(&self) -> bool {
-        let opt = self.flags.zune_use_avx2 | self.flags.zune_use_unsafe;
-        // options says no
-        if !opt {
-            return false;
-        }
+        // options says no, or the cpu doesn't have it
+        (self.flags.zune_use_avx2 | self.flags.zune_use_unsafe) && Isa::Avx2.is_supported()
+    }
 
-        #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
-        {
-            // where we can do runtime check if feature is present
-            #[cfg(feature = "std")]
-            {
-                if is_x86_feature_detected!("avx2") {
-                    return true;
-                }
-            }
-            // where we can't do runitme check if feature is present
-            // check if the compile feature had it enabled
-            #[cfg(all(not(feature = "std"), target_feature = "avx2"))]
-            {
-                return true;
-            }
-        }
-        // everything failed return false
-        false
+    /// Use avx512 paths where possible
+    ///
+    /// This also checks for AVX-512 support, see [`Isa::Avx512Bw`], and returns
+    /// false if it's not present or avx2 paths aren't used
+    pub fn use_avx512(&self) -> bool {
+        // options says no, or the cpu doesn't have it
+        self.flags.zune_use_avx512 && self.use_avx2() && Isa::Avx512Bw.is_supported()
     }
 
-    #[allow(unreachable_code)]
+    /// Use NEON paths where possible
+    ///
+    /// This also checks for NEON support and returns false if it's not
+    /// present
     pub fn use_neon(&self) -> bool {
-        let opt = self.flags.zune_use_neon | self.flags.zune_use_unsafe;
-        // options says no
-        if !opt {
-            return false;
-        }
-
-        #[cfg(target_arch = "aarch64")]
-        {
-            // aarch64 implies neon on a compliant cpu
-            // but for real prod should do something better here
-            return true;
-        }
-        // everything failed return false
-        false
+        // options says no, or the cpu doesn't have it
+        (self.flags.zune_use_neon | self.flags.zune_use_unsafe) && Isa::Neon.is_supported()
     }
 }
 
@@ -635,7 +690,12 @@ impl Default for DecoderOptions {
     /// - All decoders
     ///     - max_width: 16536
     ///     - max_height: 16535
+    ///     - max_pixels: unlimited
+    ///     - max_frames: unlimited
+    ///     - max_memory: unlimited
+    ///     - max_threads: 0, decoders pick
     ///     - use_unsafe: Use unsafe intrinsics where possible.
+    ///     - auto_orient: False, the exif orientation is not applied
     ///
     /// - JPEG
     ///     - max_scans: 100 (progressive images only, artificial cap to prevent a specific DOS)
@@ -657,6 +717,10 @@ impl Default for DecoderOptions {
             out_colorspace: ColorSpace::RGB,
             max_width:      1 << 14,
             max_height:     1 << 14,
+            max_pixels:     usize::MAX,
+            max_frames:     usize::MAX,
+            max_memory:     usize::MAX,
+            max_threads:    0,
             max_scans:      100,
             deflate_limit:  1 << 30,
             flags:          decoder_error_tolerance_mode(),
@@ -183,7 +183,7 @@ impl EncoderOptions {
     /// The default value is false, and encoders that respect this try to preserve as much
     /// data as possible from one image to another
     pub const fn strip_metadata(&self) -> bool {
-        !self.flags.image_strip_metadata
+        self.flags.image_strip_metadata
     }
 }
 
@@ -211,6 +211,14 @@ impl EncoderOptions {
     ///
     /// Default is `false`
     pub fn set_jpeg_encode_progressive(mut self, yes: bool) -> Self {
+        self.flags.jpeg_encode_progressive = yes;
+        self
+    }
+
+    /// Set whether the jpeg encoder should optimize huffman tables to create smaller files
+    ///
+    /// Default is `false`
+    pub fn set_jpeg_optimized_huffman_tables(mut self, yes: bool) -> Self {
         self.flags.jpeg_optimize_huffman = yes;
         self
     }
@@ -10,16 +10,31 @@
 //! Serde support for serializing
 //! crate datastructures
 //!
-//! Implements serialize for
+//! Implements serialize and deserialize for
 //!  - ColorSpace
 //!  - BitDepth
+//!  - ByteEndian
 //!  - ColorCharacteristics
+//!  - WhitePoint
+//!  - RgbPrimaries
+//!  - RenderingIntent
+//!  - DecoderOptions
+//!  - EncoderOptions
+//!
+//! Enums are stored by the name of their variant, options as a map of
+//! their settings, keys missing when deserializing keep their default value.
 use alloc::format;
+use alloc::string::String;
+use core::fmt;
 
+use serde::de::{self, Deserialize, Deserializer, MapAccess, Visitor};
 use serde::ser::*;
 
-use crate::bit_depth::BitDepth;
-use crate::colorspace::{ColorCharacteristics, ColorSpace, RenderingIntent};
+use crate::bit_depth::{BitDepth, ByteEndian};
+use crate::colorspace::{
+    ColorCharacteristics, ColorSpace, RenderingIntent, RgbPrimaries, WhitePoint
+};
+use crate::options::{DecoderOptions, EncoderOptions};
 
 impl Serialize for ColorSpace {
     #[allow(clippy::uninlined_format_args)]
@@ -61,3 +76,327 @@ impl Serialize for RenderingIntent {
         serializer.serialize_str(&format!("{:?}", self))
     }
 }
+
+impl Serialize for WhitePoint {
+    #[allow(clippy::uninlined_format_args)]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        serializer.serialize_str(&format!("{:?}", self))
+    }
+}
+
+impl Serialize for RgbPrimaries {
+    #[allow(clippy::uninlined_format_args)]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        serializer.serialize_str(&format!("{:?}", self))
+    }
+}
+
+impl Serialize for ByteEndian {
+    #[allow(clippy::uninlined_format_args)]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        serializer.serialize_str(&format!("{:?}", self))
+    }
+}
+
+/// Implement deserialize for enums serialized as the name of their variant
+macro_rules! deserialize_by_name {
+    ($name:ident, [$($variant:ident),*]) => {
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>
+            {
+                struct NameVisitor;
+
+                impl<'de> Visitor<'de> for NameVisitor {
+                    type Value = $name;
+
+                    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                        formatter.write_str(concat!("a ", stringify!($name), " variant name"))
+                    }
+
+                    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+                    where
+                        E: de::Error
+                    {
+                        match v {
+                            $(stringify!($variant) => Ok($name::$variant),)*
+                            _ => Err(E::unknown_variant(v, &[$(stringify!($variant)),*]))
+                        }
+                    }
+                }
+                deserializer.deserialize_str(NameVisitor)
+            }
+        }
+    };
+}
+
+deserialize_by_name!(
+    ColorSpace,
+    [
+        RGB, RGBA, YCbCr, Luma, LumaA, YCCK, CMYK, BGR, BGRA, Unknown, ARGB, HSL, HSV, Lab, LCh,
+        OKLab, OKLCh, XYZ
+    ]
+);
+deserialize_by_name!(BitDepth, [Eight, Sixteen, Float32, Float16, Unknown]);
+deserialize_by_name!(ByteEndian, [LE, BE]);
+deserialize_by_name!(ColorCharacteristics, [sRGB, Linear, PQ, HLG]);
+deserialize_by_name!(
+    RenderingIntent,
+    [AbsoluteColorimetric, Saturation, RelativeColorimetric, Perceptual]
+);
+deserialize_by_name!(RgbPrimaries, [sRGB, DisplayP3, Rec2020]);
+
+impl<'de> Deserialize<'de> for WhitePoint {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>
+    {
+        struct WhitePointVisitor;
+
+        impl<'de> Visitor<'de> for WhitePointVisitor {
+            type Value = WhitePoint;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a white point name or Custom(x, y)")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error
+            {
+                match v {
+                    "D50" => return Ok(WhitePoint::D50),
+                    "D55" => return Ok(WhitePoint::D55),
+                    "D65" => return Ok(WhitePoint::D65),
+                    "E" => return Ok(WhitePoint::E),
+                    _ => ()
+                }
+                // custom white points are serialized as their debug value, `Custom(x, y)`
+                let coordinates = v
+                    .strip_prefix("Custom(")
+                    .and_then(|v| v.strip_suffix(')'))
+                    .and_then(|v| v.split_once(','))
+                    .and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)));
+
+                match coordinates {
+                    Some((x, y)) => Ok(WhitePoint::Custom(x, y)),
+                    None => Err(E::invalid_value(de::Unexpected::Str(v), &self))
+                }
+            }
+        }
+        deserializer.deserialize_str(WhitePointVisitor)
+    }
+}
+
+impl Serialize for DecoderOptions {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        const STRUCT_FIELDS: usize = 19;
+        let mut state = serializer.serialize_struct("DecoderOptions", STRUCT_FIELDS)?;
+
+        state.serialize_field("max_width", &self.max_width())?;
+        state.serialize_field("max_height", &self.max_height())?;
+        state.serialize_field("max_pixels", &self.max_pixels())?;
+        state.serialize_field("max_frames", &self.max_frames())?;
+        state.serialize_field("max_memory", &self.max_memory())?;
+        state.serialize_field("max_threads", &self.max_threads())?;
+        state.serialize_field("use_unsafe", &self.use_unsafe())?;
+        state.serialize_field("strict_mode", &self.strict_mode())?;
+        state.serialize_field("auto_orient", &self.auto_orient())?;
+        state.serialize_field("byte_endian", &self.byte_endian())?;
+        state.serialize_field("inflate_confirm_adler", &self.inflate_get_confirm_adler())?;
+        state.serialize_field("inflate_limit", &self.inflate_get_limit())?;
+        state.serialize_field("png_confirm_crc", &self.png_get_confirm_crc())?;
+        state.serialize_field("png_add_alpha_channel", &self.png_get_add_alpha_channel())?;
+        state.serialize_field("png_strip_to_8bit", &self.png_get_strip_to_8bit())?;
+        state.serialize_field("png_decode_animated", &self.png_decode_animated())?;
+        state.serialize_field("jpeg_max_scans", &self.jpeg_get_max_scans())?;
+        state.serialize_field("jpeg_out_colorspace", &self.jpeg_get_out_colorspace())?;
+        state.serialize_field("jxl_decode_animated", &self.jxl_decode_animated())?;
+
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for DecoderOptions {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>
+    {
+        const FIELDS: &[&str] = &[
+            "max_width",
+            "max_height",
+            "max_pixels",
+            "max_frames",
+            "max_memory",
+            "max_threads",
+            "use_unsafe",
+            "strict_mode",
+            "auto_orient",
+            "byte_endian",
+            "inflate_confirm_adler",
+            "inflate_limit",
+            "png_confirm_crc",
+            "png_add_alpha_channel",
+            "png_strip_to_8bit",
+            "png_decode_animated",
+            "jpeg_max_scans",
+            "jpeg_out_colorspace",
+            "jxl_decode_animated"
+        ];
+
+        struct OptionsVisitor;
+
+        impl<'de> Visitor<'de> for OptionsVisitor {
+            type Value = DecoderOptions;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map of decoder options")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>
+            {
+                let mut options = DecoderOptions::default();
+                // strict mode sets the individual checks, so apply those after it
+                let mut confirm_adler = None;
+                let mut confirm_crc = None;
+
+                while let Some(key) = map.next_key::<String>()? {
+                    options = match key.as_str() {
+                        "max_width" => options.set_max_width(map.next_value()?),
+                        "max_height" => options.set_max_height(map.next_value()?),
+                        "max_pixels" => options.set_max_pixels(map.next_value()?),
+                        "max_frames" => options.set_max_frames(map.next_value()?),
+                        "max_memory" => options.set_max_memory(map.next_value()?),
+                        "max_threads" => options.set_max_threads(map.next_value()?),
+                        "use_unsafe" => options.set_use_unsafe(map.next_value()?),
+                        "strict_mode" => options.set_strict_mode(map.next_value()?),
+                        "auto_orient" => options.set_auto_orient(map.next_value()?),
+                        "byte_endian" => options.set_byte_endian(map.next_value()?),
+                        "inflate_confirm_adler" => {
+                            confirm_adler = Some(map.next_value()?);
+                            options
+                        }
+                        "inflate_limit" => options.inflate_set_limit(map.next_value()?),
+                        "png_confirm_crc" => {
+                            confirm_crc = Some(map.next_value()?);
+                            options
+                        }
+                        "png_add_alpha_channel" => {
+                            options.png_set_add_alpha_channel(map.next_value()?)
+                        }
+                        "png_strip_to_8bit" => options.png_set_strip_to_8bit(map.next_value()?),
+                        "png_decode_animated" => {
+                            options.png_set_decode_animated(map.next_value()?)
+                        }
+                        "jpeg_max_scans" => options.jpeg_set_max_scans(map.next_value()?),
+                        "jpeg_out_colorspace" => {
+                            options.jpeg_set_out_colorspace(map.next_value()?)
+                        }
+                        "jxl_decode_animated" => {
+                            options.jxl_set_decode_animated(map.next_value()?)
+                        }
+                        _ => return Err(de::Error::unknown_field(&key, FIELDS))
+                    };
+                }
+                if let Some(yes) = confirm_adler {
+                    options = options.inflate_set_confirm_adler(yes);
+                }
+                if let Some(yes) = confirm_crc {
+                    options = options.png_set_confirm_crc(yes);
+                }
+                Ok(options)
+            }
+        }
+        deserializer.deserialize_struct("DecoderOptions", FIELDS, OptionsVisitor)
+    }
+}
+
+/// Width, height, colorspace and depth of encoder options are left out, encoders
+/// take them from the image they encode
+impl Serialize for EncoderOptions {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        const STRUCT_FIELDS: usize = 6;
+        let mut state = serializer.serialize_struct("EncoderOptions", STRUCT_FIELDS)?;
+
+        state.serialize_field("quality", &self.quality())?;
+        state.serialize_field("effort", &self.effort())?;
+        state.serialize_field("num_threads", &self.num_threads())?;
+        state.serialize_field("strip_metadata", &self.strip_metadata())?;
+        state.serialize_field("jpeg_encode_progressive", &self.jpeg_encode_progressive())?;
+        state.serialize_field(
+            "jpeg_optimized_huffman_tables",
+            &self.jpeg_optimized_huffman_tables()
+        )?;
+
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for EncoderOptions {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>
+    {
+        const FIELDS: &[&str] = &[
+            "quality",
+            "effort",
+            "num_threads",
+            "strip_metadata",
+            "jpeg_encode_progressive",
+            "jpeg_optimized_huffman_tables"
+        ];
+
+        struct OptionsVisitor;
+
+        impl<'de> Visitor<'de> for OptionsVisitor {
+            type Value = EncoderOptions;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map of encoder options")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>
+            {
+                let mut options = EncoderOptions::default();
+
+                while let Some(key) = map.next_key::<String>()? {
+                    options = match key.as_str() {
+                        "quality" => options.set_quality(map.next_value()?),
+                        "effort" => options.set_effort(map.next_value()?),
+                        "num_threads" => options.set_num_threads(map.next_value()?),
+                        "strip_metadata" => options.set_strip_metadata(map.next_value()?),
+                        "jpeg_encode_progressive" => {
+                            options.set_jpeg_encode_progressive(map.next_value()?)
+                        }
+                        "jpeg_optimized_huffman_tables" => {
+                            options.set_jpeg_optimized_huffman_tables(map.next_value()?)
+                        }
+                        _ => return Err(de::Error::unknown_field(&key, FIELDS))
+                    };
+                }
+                Ok(options)
+            }
+        }
+        deserializer.deserialize_struct("EncoderOptions", FIELDS, OptionsVisitor)
+    }
+}
@@ -0,0 +1,205 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Runtime selection of SIMD kernels
+//!
+//! Routines with SIMD versions register every version of a kernel in a
+//! [`Dispatcher`], from the most preferred to the scalar fallback, and the first
+//! one whose instruction set the cpu supports is picked on the first call and reused
+//! for every call after that.
+//!
+//! With the `std` feature, support is detected at runtime, without it only instruction
+//! sets enabled at compile time (e.g. via `-C target-feature`) are used.
+//!
+//! # Example
+//!
+//! ```
+//! use zune_core::simd::{Dispatcher, Isa};
+//!
+//! type SumFn = unsafe fn(&[u8]) -> u32;
+//!
+//! fn sum_scalar(values: &[u8]) -> u32 {
+//!     values.iter().map(|x| u32::from(*x)).sum()
+//! }
+//!
+//! // SAFETY: every kernel only uses instructions of its instruction set
+//! static SUM: Dispatcher<SumFn> = unsafe { Dispatcher::new("sum", &[(Isa::Scalar, sum_scalar)]) };
+//!
+//! fn sum(values: &[u8]) -> u32 {
+//!     // SAFETY: the dispatcher only returns kernels the cpu supports
+//!     unsafe { SUM.get()(values) }
+//! }
+//!
+//! assert_eq!(sum(&[1, 2, 3]), 6);
+//! assert_eq!(SUM.isa(), Isa::Scalar);
+//! ```
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::log::trace;
+
+/// Whether an x86 feature is present, at runtime with `std`,
+/// at compile time without it
+macro_rules! x86_detected {
+    ($feature:tt) => {{
+        #[cfg(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64")))]
+        let detected = std::is_x86_feature_detected!($feature);
+        #[cfg(all(not(feature = "std"), any(target_arch = "x86", target_arch = "x86_64")))]
+        let detected = cfg!(target_feature = $feature);
+        #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+        let detected = false;
+
+        detected
+    }};
+}
+
+/// An instruction set a kernel can be written for
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Isa {
+    /// Plain Rust, supported everywhere
+    Scalar,
+    /// x86 SSE2
+    Sse2,
+    /// x86 SSE3
+    Sse3,
+    /// x86 SSE4.1
+    Sse41,
+    /// x86 AVX
+    Avx,
+    /// x86 AVX2
+    Avx2,
+    /// x86 AVX2 together with FMA3, which are separate cpu features
+    Avx2Fma,
+    /// x86 AVX-512 with byte and word instructions, i.e. the F, BW, VL and VBMI subsets
+    ///
+    /// Cpus before Ice Lake (Skylake-X, Cascade Lake) lower their clock for a while
+    /// after running 512 bit instructions, which slows down everything around short
+    /// kernels like ours, so this is only reported as supported on cpus which also
+    /// have VBMI2, which arrived with Ice Lake and doesn't come with the penalty there
+    /// or on AMD Zen 4.
+    Avx512Bw,
+    /// aarch64 NEON
+    Neon
+}
+
+impl Isa {
+    /// Return true if the cpu running this supports the instruction set
+    ///
+    /// Instruction sets of other architectures, e.g. NEON on x86, are never supported,
+    /// and neither is AVX-512 on cpus which slow down running it, see [`Isa::Avx512Bw`]
+    pub fn is_supported(self) -> bool {
+        match self {
+            Isa::Scalar => true,
+            Isa::Sse2 => x86_detected!("sse2"),
+            Isa::Sse3 => x86_detected!("sse3"),
+            Isa::Sse41 => x86_detected!("sse4.1"),
+            Isa::Avx => x86_detected!("avx"),
+            Isa::Avx2 => x86_detected!("avx2"),
+            Isa::Avx2Fma => x86_detected!("avx2") && x86_detected!("fma"),
+            Isa::Avx512Bw => {
+                x86_detected!("avx512f")
+                    && x86_detected!("avx512bw")
+                    && x86_detected!("avx512vl")
+                    && x86_detected!("avx512vbmi")
+                    && x86_detected!("avx512vbmi2")
+            }
+            Isa::Neon => {
+                #[cfg(all(feature = "std", target_arch = "aarch64"))]
+                let detected = std::arch::is_aarch64_feature_detected!("neon");
+                #[cfg(all(not(feature = "std"), target_arch = "aarch64"))]
+                let detected = cfg!(target_feature = "neon");
+                #[cfg(not(target_arch = "aarch64"))]
+                let detected = false;
+
+                detected
+            }
+        }
+    }
+}
+
+/// Kernel index of a dispatcher that hasn't picked one yet
+const UNSELECTED: usize = usize::MAX;
+
+/// Versions of one kernel for different instruction sets, the first one the cpu
+/// supports is picked on the first call to [`get`](Self::get)
+///
+/// `F` is the function pointer type of the kernel, usually an `unsafe fn` since
+/// kernels using `#[target_feature]` are unsafe to call, see the [module docs](self)
+/// for an example
+pub struct Dispatcher<F: 'static> {
+    name:     &'static str,
+    kernels:  &'static [(Isa, F)],
+    selected: AtomicUsize
+}
+
+impl<F: Copy> Dispatcher<F> {
+    /// Create a new dispatcher for `kernels`, in order of preference
+    ///
+    /// `name` is used for logging which kernel was picked.
+    ///
+    /// # Panics
+    /// If the last kernel isn't an [`Isa::Scalar`] one, since
+    /// there would be nothing to fall back to
+    ///
+    /// # Safety
+    /// Calling a kernel must be safe on any cpu supporting its instruction set
+    pub const unsafe fn new(name: &'static str, kernels: &'static [(Isa, F)]) -> Dispatcher<F> {
+        assert!(
+            matches!(kernels.last(), Some((Isa::Scalar, _))),
+            "The last kernel should be a scalar one"
+        );
+        Dispatcher {
+            name,
+            kernels,
+            selected: AtomicUsize::new(UNSELECTED)
+        }
+    }
+
+    /// Return the kernel picked for this cpu
+    ///
+    /// The returned kernel is safe to call, as long as the kernels
+    /// were registered as described in [`new`](Self::new)
+    pub fn get(&self) -> F {
+        self.kernels[self.selected()].1
+    }
+
+    /// Return the instruction set of the kernel picked for this cpu
+    pub fn isa(&self) -> Isa {
+        self.kernels[self.selected()].0
+    }
+
+    /// Return the name of the routine
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Return every registered kernel, including ones the cpu doesn't support
+    ///
+    /// Useful for testing that kernels agree with each other
+    pub const fn kernels(&self) -> &'static [(Isa, F)] {
+        self.kernels
+    }
+
+    fn selected(&self) -> usize {
+        let selected = self.selected.load(Ordering::Relaxed);
+
+        if selected != UNSELECTED {
+            return selected;
+        }
+        // racing threads pick the same kernel, so storing it twice is fine
+        let selected = self
+            .kernels
+            .iter()
+            .position(|(isa, _)| isa.is_supported())
+            .unwrap_or(self.kernels.len() - 1);
+
+        trace!("Using {:?} {} kernel", self.kernels[selected].0, self.name);
+        self.selected.store(selected, Ordering::Relaxed);
+
+        selected
+    }
+}
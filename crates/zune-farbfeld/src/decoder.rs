@@ -56,6 +56,9 @@ where
     }
     /// Decode a header for this specific image
     pub fn decode_headers(&mut self) -> Result<(), FarbFeldErrors> {
+        if self.decoded_headers {
+            return Ok(());
+        }
         // read magic
 
         let magic_value = self.stream.get_u64_be_err()?.to_be_bytes();
@@ -77,6 +80,14 @@ where
         if self.width > self.options.max_width() {
             return Err(FarbFeldErrors::Generic("Image width is greater than max width. Bump up max_width in options to support such images"));
         }
+        // RGBA, 2 bytes per component
+        if self
+            .options
+            .exceeded_limit(self.width, self.height, 8, 1)
+            .is_some()
+        {
+            return Err(FarbFeldErrors::Generic("Image is larger than the max pixels or memory. Bump them up in options to support such images"));
+        }
 
         self.decoded_headers = true;
         Ok(())
@@ -103,8 +114,8 @@ where
     }
     /// Decode data writing it into the buffer as native endian
     ///
-    /// It is an error if the sink buffer is smaller than
-    /// [`output_buffer_size()`](Self::output_buffer_size)
+    /// It is an error if the sink buffer is smaller than half of
+    /// [`output_buffer_size()`](Self::output_buffer_size), which is in bytes
     ///
     /// # Arguments
     /// - `sink`: The output buffer which we will fill with bytes
@@ -120,9 +131,11 @@ where
         if !self.decoded_headers {
             self.decode_headers()?;
         }
+        // output_buffer_size is in bytes, the sink holds u16's
         let expected_len = self
             .output_buffer_size()
-            .ok_or(FarbFeldErrors::Generic("Overflowed int"))?;
+            .ok_or(FarbFeldErrors::Generic("Overflowed int"))?
+            / 2;
 
         if sink.len() < expected_len {
             return Err(FarbFeldErrors::Generic("Too small output buffer size"));
@@ -140,6 +153,37 @@ where
 
         Ok(())
     }
+
+    /// Decode data writing it into a byte buffer, each sample as two native endian bytes
+    ///
+    /// This is [`decode_into`](Self::decode_into) for callers whose buffers are bytes,
+    /// it is an error if the sink is smaller than
+    /// [`output_buffer_size()`](Self::output_buffer_size).
+    ///
+    /// Neither this nor [`decode_into`](Self::decode_into) allocate, so they can be used
+    /// on targets without a heap.
+    ///
+    /// # Arguments
+    /// - `sink`: The output buffer which we will fill with bytes
+    pub fn decode_into_bytes(&mut self, sink: &mut [u8]) -> Result<(), FarbFeldErrors> {
+        if !self.decoded_headers {
+            self.decode_headers()?;
+        }
+        let expected_len = self
+            .output_buffer_size()
+            .ok_or(FarbFeldErrors::Generic("Overflowed int"))?;
+
+        if sink.len() < expected_len {
+            return Err(FarbFeldErrors::Generic("Too small output buffer size"));
+        }
+
+        for datum in sink[..expected_len].chunks_exact_mut(2) {
+            let pix = self.stream.get_u16_be_err()?;
+            datum.copy_from_slice(&pix.to_ne_bytes());
+        }
+
+        Ok(())
+    }
     /// Decode a farbfeld data returning raw pixels or an error
     ///
     ///
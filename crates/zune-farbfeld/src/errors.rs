@@ -1,4 +1,4 @@
-use core::fmt::{Debug, Formatter};
+use core::fmt::{Debug, Display, Formatter};
 
 use zune_core::bytestream::ZByteIoError;
 
@@ -20,6 +20,14 @@ impl Debug for FarbFeldErrors {
     }
 }
 
+impl Display for FarbFeldErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl core::error::Error for FarbFeldErrors {}
+
 impl From<ZByteIoError> for FarbFeldErrors {
     fn from(value: ZByteIoError) -> Self {
         FarbFeldErrors::IoError(value)
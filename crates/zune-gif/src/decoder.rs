@@ -140,6 +140,13 @@ impl<T: ZByteReaderTrait> GifDecoder<T> {
                 "cannot calculate output dimensions"
             ))?;
 
+        // each call decodes one more RGBA frame
+        if let Some((limit, found, max)) =
+            self.options
+                .exceeded_limit(self.width, self.height, 4, self.frame_pos + 1)
+        {
+            return Err(GifDecoderErrors::TooLargeDimensions(limit, max, found));
+        }
         if output_size > output.len() {
             return Err(GifDecoderErrors::TooSmallSize(output_size, output.len()));
         }
@@ -202,6 +202,13 @@ where
                 self.width
             ));
         }
+        // RGB, a float per component
+        if let Some((limit, found, max)) =
+            self.options
+                .exceeded_limit(self.width, self.height, 3 * core::mem::size_of::<f32>(), 1)
+        {
+            return Err(HdrDecodeErrors::TooLargeDimensions(limit, max, found));
+        }
 
         trace!("Width: {}", self.width);
         trace!("Height: {}", self.height);
@@ -0,0 +1,226 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Decoding images from async code running on tokio
+//!
+//! Decoding a large image takes long enough to stall every other task scheduled on
+//! the same executor thread, so async services should decode on tokio's blocking
+//! thread pool instead.
+//!
+//! - [`Image::read_async`] reads all bytes from an [`AsyncRead`] and decodes them on the
+//!   blocking pool.
+//! - [`Image::open_async`] does the same for files.
+//! - [`spawn_blocking`] runs anything else, e.g. operations or encoding, on the blocking pool.
+//!
+//! Work started by these is tied to the returned future, dropping the future before it
+//! completes, e.g. when a request times out, cancels the work through its [`Progress`],
+//! so the blocking thread stops at the next progress check instead of finishing a decode
+//! nobody waits for.
+//!
+//! This requires the `tokio` feature.
+//!
+//! # Example
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use zune_core::options::DecoderOptions;
+//! use zune_image::image::Image;
+//!
+//! # async fn decode() {
+//! let file = tokio::fs::File::open("image.png").await.unwrap();
+//!
+//! // the decode is cancelled if it takes more than a second
+//! let image = tokio::time::timeout(
+//!     Duration::from_secs(1),
+//!     Image::read_async(file, DecoderOptions::default())
+//! )
+//! .await;
+//! # }
+//! ```
+use std::path::Path;
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+use zune_core::bytestream::ZCursor;
+use zune_core::options::DecoderOptions;
+
+use crate::errors::ImageErrors;
+use crate::image::Image;
+use crate::progress::{with_progress, Progress};
+
+/// Run `function` on tokio's blocking thread pool, reporting its progress to `progress`
+///
+/// Dropping the returned future before `function` returns cancels `progress`, which
+/// makes `function` return [`ImageErrors::Cancelled`] at its next progress check.
+/// Clones of `progress` share the cancellation, so a progress should not be shared
+/// with work that should outlive this future.
+///
+/// Panics in `function` are resumed when the future is awaited.
+///
+/// # Errors
+/// The errors `function` returns, or [`ImageErrors::Cancelled`] if the runtime shut
+/// down before it ran
+///
+/// # Example
+/// ```no_run
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_core::bit_depth::BitDepth;
+/// use zune_image::asynchronous::spawn_blocking;
+/// use zune_image::core_filters::depth::Depth;
+/// use zune_image::image::Image;
+/// use zune_image::progress::Progress;
+/// use zune_image::traits::OperationsTrait;
+///
+/// # async fn convert() {
+/// let mut image = Image::fill(0_u8, ColorSpace::RGB, 4000, 4000);
+///
+/// let image = spawn_blocking(Progress::new(), move || {
+///     Depth::new(BitDepth::Sixteen).execute(&mut image)?;
+///     Ok(image)
+/// })
+/// .await
+/// .unwrap();
+/// # }
+/// ```
+pub async fn spawn_blocking<R, F>(progress: Progress, function: F) -> Result<R, ImageErrors>
+where
+    R: Send + 'static,
+    F: FnOnce() -> Result<R, ImageErrors> + Send + 'static
+{
+    /// Cancels the work when the future is dropped before it completes
+    struct CancelOnDrop(Option<Progress>);
+
+    impl Drop for CancelOnDrop {
+        fn drop(&mut self) {
+            if let Some(progress) = self.0.take() {
+                progress.cancel();
+            }
+        }
+    }
+    let mut guard = CancelOnDrop(Some(progress.clone()));
+
+    let result = tokio::task::spawn_blocking(move || with_progress(progress, function)).await;
+    // finished, nothing left to cancel
+    guard.0 = None;
+
+    match result {
+        Ok(result) => result,
+        Err(error) if error.is_panic() => std::panic::resume_unwind(error.into_panic()),
+        Err(_) => Err(ImageErrors::Cancelled)
+    }
+}
+
+impl Image {
+    /// Read an encoded image from `src` and decode it on tokio's blocking thread pool
+    ///
+    /// All bytes are read into memory first, decoding starts once `src` reaches its end.
+    /// Dropping the returned future cancels the decode, see [`spawn_blocking`].
+    ///
+    /// # Errors
+    /// If reading from `src` fails, or the errors of [`Image::read`]
+    pub async fn read_async<R>(src: R, options: DecoderOptions) -> Result<Image, ImageErrors>
+    where
+        R: AsyncRead + Unpin
+    {
+        Self::read_async_with_progress(src, options, Progress::new()).await
+    }
+
+    /// Read an encoded image from `src` and decode it on tokio's blocking thread pool,
+    /// reporting decoding progress to `progress`
+    ///
+    /// Besides dropping the returned future, the decode can be cancelled with
+    /// [`Progress::cancel`] on a clone of `progress`.
+    ///
+    /// # Errors
+    /// If reading from `src` fails, or the errors of [`Image::read`]
+    pub async fn read_async_with_progress<R>(
+        mut src: R, options: DecoderOptions, progress: Progress
+    ) -> Result<Image, ImageErrors>
+    where
+        R: AsyncRead + Unpin
+    {
+        let mut bytes = Vec::new();
+        src.read_to_end(&mut bytes).await?;
+
+        spawn_blocking(progress, move || Image::read(ZCursor::new(bytes), options)).await
+    }
+
+    /// Read an encoded file and decode it on tokio's blocking thread pool
+    ///
+    /// This is the async version of [`Image::open`], dropping the returned future
+    /// cancels the decode.
+    ///
+    /// # Errors
+    /// If the file can't be read, or the errors of [`Image::read`]
+    pub async fn open_async<P: AsRef<Path>>(file: P) -> Result<Image, ImageErrors> {
+        let bytes = tokio::fs::read(file).await?;
+
+        spawn_blocking(Progress::new(), move || {
+            Image::read(ZCursor::new(bytes), DecoderOptions::default())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use zune_core::options::DecoderOptions;
+
+    use crate::asynchronous::spawn_blocking;
+    use crate::errors::ImageErrors;
+    use crate::image::Image;
+    use crate::progress::{check_cancelled, Progress};
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_read_async() {
+        let image = runtime()
+            .block_on(Image::read_async(
+                &b"P5 2 1 255 ab"[..],
+                DecoderOptions::default()
+            ))
+            .unwrap();
+
+        assert_eq!(image.dimensions(), (2, 1));
+    }
+
+    #[test]
+    fn test_dropping_cancels() {
+        let progress = Progress::new();
+        let stopped = Arc::new(AtomicBool::new(false));
+        let flag = stopped.clone();
+
+        let work = spawn_blocking(progress.clone(), move || {
+            while check_cancelled().is_ok() {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            flag.store(true, Ordering::Release);
+            Err::<(), _>(ImageErrors::Cancelled)
+        });
+        let runtime = runtime();
+        // timers need the runtime, so the timeout is made inside it
+        let result = runtime.block_on(async {
+            tokio::time::timeout(Duration::from_millis(20), work).await
+        });
+
+        assert!(result.is_err());
+        assert!(progress.is_cancelled());
+
+        // the runtime waits for blocking work when dropped
+        drop(runtime);
+        assert!(stopped.load(Ordering::Acquire));
+    }
+}
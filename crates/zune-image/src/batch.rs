@@ -0,0 +1,463 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Decoding and processing many images in parallel
+//!
+//! A thumbnailer, or anything else going over a directory of images, decodes every image,
+//! runs the same operations on it and saves the result. [`Batch`] does that over a set of
+//! worker threads, each taking the next input once it's done with the previous one.
+//!
+//! - Inputs are taken from the iterator as workers need them, so a long list, e.g.
+//!   from walking a directory, isn't held in memory.
+//! - Results wait in a queue of a [few images](Batch::set_queue_size). Once it's full,
+//!   workers wait for the results to be read, so a slow consumer doesn't pile up
+//!   decoded images.
+//! - Channels are allocated from a [`ScratchPool`] shared by the workers, so once the
+//!   first images are dropped the next ones reuse their memory.
+//!
+//! Results come back in the order images finish, along with the position of their
+//! input. Dropping the [`BatchResults`] stops the workers after the images they are on.
+//!
+//! A panic while decoding, processing or finishing an image becomes an error result for
+//! that input, the worker moves on to the next one.
+//!
+//! # Example
+//! Make thumbnails of some files, saving them on the worker threads
+//!
+//! ```no_run
+//! use zune_image::batch::Batch;
+//! use zune_image::core_filters::colorspace::ColorspaceConv;
+//! use zune_core::colorspace::ColorSpace;
+//!
+//! let files = ["a.jpg", "b.png", "c.qoi"];
+//!
+//! let mut batch = Batch::new();
+//! batch.chain_operations(Box::new(ColorspaceConv::new(ColorSpace::Luma)));
+//!
+//! let results = batch.run_with(files, |index, image| {
+//!     image.save(format!("thumbnail_{index}.png"))
+//! });
+//! for (index, result) in results {
+//!     if let Err(e) = result {
+//!         eprintln!("{}: {e:?}", files[index]);
+//!     }
+//! }
+//! ```
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::JoinHandle;
+
+use zune_core::bytestream::ZCursor;
+use zune_core::options::DecoderOptions;
+
+use crate::channel::{with_allocator, ScratchPool};
+use crate::errors::ImageErrors;
+use crate::image::Image;
+use crate::pipelines::run_operations;
+use crate::traits::{IntoImage, OperationsTrait};
+
+/// An image to be decoded by a [`Batch`]
+pub enum BatchInput {
+    /// A file, opened with [`Image::open_with_options`]
+    File(PathBuf),
+    /// An encoded image in memory
+    Memory(Vec<u8>),
+    /// An image that's already decoded
+    Image(Image),
+    /// A decoder of one's own
+    Decoder(Box<dyn IntoImage + Send>)
+}
+
+impl BatchInput {
+    fn decode(self, options: DecoderOptions) -> Result<Image, ImageErrors> {
+        match self {
+            BatchInput::File(path) => Image::open_with_options(path, options),
+            BatchInput::Memory(data) => Image::read(ZCursor::new(data), options),
+            BatchInput::Image(image) => Ok(image),
+            BatchInput::Decoder(mut decoder) => decoder.into_image()
+        }
+    }
+}
+
+impl From<PathBuf> for BatchInput {
+    fn from(path: PathBuf) -> Self {
+        BatchInput::File(path)
+    }
+}
+
+impl From<&Path> for BatchInput {
+    fn from(path: &Path) -> Self {
+        BatchInput::File(path.to_path_buf())
+    }
+}
+
+impl From<&str> for BatchInput {
+    fn from(path: &str) -> Self {
+        BatchInput::File(PathBuf::from(path))
+    }
+}
+
+impl From<String> for BatchInput {
+    fn from(path: String) -> Self {
+        BatchInput::File(PathBuf::from(path))
+    }
+}
+
+impl From<Vec<u8>> for BatchInput {
+    fn from(data: Vec<u8>) -> Self {
+        BatchInput::Memory(data)
+    }
+}
+
+impl From<Image> for BatchInput {
+    fn from(image: Image) -> Self {
+        BatchInput::Image(image)
+    }
+}
+
+/// Decode and process images over a set of worker threads
+///
+/// See the [module docs](self) for how inputs, results and memory are handled
+pub struct Batch {
+    operations: Vec<Box<dyn OperationsTrait>>,
+    options:    DecoderOptions,
+    threads:    usize,
+    queue_size: usize,
+    scratch:    Arc<ScratchPool>
+}
+
+impl Batch {
+    /// Create a batch with no operations, running on a thread per cpu core, or as many
+    /// as the [configured thread pool](crate::threads) has
+    pub fn new() -> Batch {
+        Batch {
+            operations: vec![],
+            options:    DecoderOptions::default(),
+            threads:    0,
+            queue_size: 0,
+            scratch:    Arc::new(ScratchPool::new())
+        }
+    }
+    /// Add an operation run on every image, in the order they are added
+    ///
+    /// Consecutive point operations are fused, like in a [`Pipeline`](crate::pipelines::Pipeline)
+    pub fn chain_operations(&mut self, operation: Box<dyn OperationsTrait>) -> &mut Batch {
+        self.operations.push(operation);
+        self
+    }
+    /// Set the options files and images in memory are decoded with
+    pub fn set_decoder_options(&mut self, options: DecoderOptions) -> &mut Batch {
+        self.options = options;
+        self
+    }
+    /// Set the number of worker threads
+    ///
+    /// A value of `0`, the default, starts as many as
+    /// [`available_parallelism`](std::thread::available_parallelism) returns.
+    /// With the `threads` feature, no more than
+    /// [`max_threads`](crate::threads::max_threads) are started, so a batch keeps to
+    /// the limit set with [`set_max_threads`](crate::threads::set_max_threads).
+    pub fn set_threads(&mut self, threads: usize) -> &mut Batch {
+        self.threads = threads;
+        self
+    }
+    /// Set how many results can wait to be read before workers stop and wait for the
+    /// consumer
+    ///
+    /// A value of `0`, the default, uses twice the number of threads. Every waiting
+    /// result holds an image, so this is what bounds memory use along with the number
+    /// of threads.
+    pub fn set_queue_size(&mut self, size: usize) -> &mut Batch {
+        self.queue_size = size;
+        self
+    }
+    /// Allocate channels from `pool`, instead of a pool of the batch's own
+    ///
+    /// Sharing a pool between batches run one after the other keeps the memory of the
+    /// first one for the next.
+    pub fn set_scratch_pool(&mut self, pool: Arc<ScratchPool>) -> &mut Batch {
+        self.scratch = pool;
+        self
+    }
+    /// Decode every input and run the operations on it, returning the images
+    ///
+    /// The images keep memory from the batch's [`ScratchPool`], which is reused
+    /// once they are dropped.
+    pub fn run<I>(self, inputs: I) -> BatchResults<Image>
+    where
+        I: IntoIterator,
+        I::IntoIter: Send + 'static,
+        I::Item: Into<BatchInput>
+    {
+        self.run_with(inputs, |_, image| Ok(image))
+    }
+    /// Decode every input, run the operations on it and pass it to `finish`,
+    /// returning what `finish` returns
+    ///
+    /// `finish` runs on the worker threads, with the position of the input and its
+    /// processed image, e.g. to encode or save the image there.
+    pub fn run_with<I, F, R>(self, inputs: I, finish: F) -> BatchResults<R>
+    where
+        I: IntoIterator,
+        I::IntoIter: Send + 'static,
+        I::Item: Into<BatchInput>,
+        F: Fn(usize, Image) -> Result<R, ImageErrors> + Send + Sync + 'static,
+        R: Send + 'static
+    {
+        let threads = match self.threads {
+            0 => std::thread::available_parallelism().map_or(1, |x| x.get()),
+            threads => threads
+        };
+        // workers block on the queue, so they get threads of their own instead of
+        // running on the pool, but no more than it has
+        #[cfg(feature = "threads")]
+        let threads = threads.min(crate::threads::max_threads()).max(1);
+        let queue_size = match self.queue_size {
+            0 => threads * 2,
+            size => size
+        };
+        let (sender, receiver) = sync_channel(queue_size);
+
+        let shared = Arc::new(Shared {
+            inputs: Mutex::new(inputs.into_iter().enumerate()),
+            operations: self.operations,
+            options: self.options,
+            scratch: self.scratch,
+            stopped: AtomicBool::new(false),
+            finish
+        });
+
+        let workers = (0..threads)
+            .map(|_| {
+                let shared = shared.clone();
+                let sender = sender.clone();
+
+                std::thread::spawn(move || shared.work(&sender))
+            })
+            .collect();
+
+        BatchResults {
+            receiver: Some(receiver),
+            stop: shared,
+            workers
+        }
+    }
+}
+
+impl Default for Batch {
+    fn default() -> Self {
+        Batch::new()
+    }
+}
+
+/// What the workers of a batch share
+struct Shared<I, F> {
+    inputs:     Mutex<core::iter::Enumerate<I>>,
+    operations: Vec<Box<dyn OperationsTrait>>,
+    options:    DecoderOptions,
+    scratch:    Arc<ScratchPool>,
+    stopped:    AtomicBool,
+    finish:     F
+}
+
+impl<I, F, R> Shared<I, F>
+where
+    I: Iterator,
+    I::Item: Into<BatchInput>,
+    F: Fn(usize, Image) -> Result<R, ImageErrors>
+{
+    fn work(&self, sender: &SyncSender<(usize, Result<R, ImageErrors>)>) {
+        while !self.stopped.load(Ordering::Relaxed) {
+            let next = self
+                .inputs
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .next();
+
+            let Some((index, input)) = next else {
+                return;
+            };
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                with_allocator(self.scratch.clone(), || self.process(index, input.into()))
+            }))
+            .unwrap_or_else(|panic| Err(panic_error(index, panic.as_ref())));
+
+            if sender.send((index, result)).is_err() {
+                // the results were dropped
+                return;
+            }
+        }
+    }
+
+    fn process(&self, index: usize, input: BatchInput) -> Result<R, ImageErrors> {
+        let mut image = [input.decode(self.options)?];
+
+        run_operations(&mut image, &self.operations, &None)?;
+
+        let [image] = image;
+        (self.finish)(index, image)
+    }
+}
+
+/// The error reported for an input whose processing panicked
+fn panic_error(index: usize, panic: &(dyn core::any::Any + Send)) -> ImageErrors {
+    let message = panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic");
+
+    ImageErrors::GenericString(format!("Processing input {index} panicked: {message}"))
+}
+
+/// Trait object for stopping the workers of a batch, whatever their input and result types
+trait Stop: Send + Sync {
+    fn stop(&self);
+}
+
+impl<I: Send, F: Send + Sync> Stop for Shared<I, F> {
+    fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// The results of a [`Batch`], the position of each input and what became of it
+///
+/// Iterating blocks until the next image is done, and ends once every input is.
+/// Dropping this stops the workers once they finish the images they are on, and waits
+/// for them. A panic of a worker outside of processing an image, e.g. in the input
+/// iterator, is raised again on drop.
+pub struct BatchResults<R> {
+    receiver: Option<Receiver<(usize, Result<R, ImageErrors>)>>,
+    stop:     Arc<dyn Stop>,
+    workers:  Vec<JoinHandle<()>>
+}
+
+impl<R> Iterator for BatchResults<R> {
+    type Item = (usize, Result<R, ImageErrors>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.as_ref()?.recv().ok()
+    }
+}
+
+impl<R> Drop for BatchResults<R> {
+    fn drop(&mut self) {
+        self.stop.stop();
+        // workers waiting for space in the queue see it's gone and stop
+        self.receiver = None;
+
+        for worker in self.workers.drain(..) {
+            if let Err(panic) = worker.join() {
+                // don't panic while already unwinding, that aborts
+                if !std::thread::panicking() {
+                    resume_unwind(panic);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::batch::{Batch, BatchInput};
+    use crate::core_filters::colorspace::ColorspaceConv;
+    use crate::image::Image;
+
+    #[test]
+    fn test_batch_processes_every_input() {
+        let inputs = (0..20).map(|i| Image::fill(i as u8, ColorSpace::RGB, 30, 20));
+
+        let mut batch = Batch::new();
+        batch
+            .chain_operations(Box::new(ColorspaceConv::new(ColorSpace::Luma)))
+            .set_threads(3);
+
+        let mut seen = [false; 20];
+
+        for (index, result) in batch.run(inputs) {
+            let image = result.unwrap();
+            assert_eq!(image.colorspace(), ColorSpace::Luma);
+            // gray stays gray, give or take rounding
+            assert!(image.flatten_to_u8()[0][0].abs_diff(index as u8) <= 1);
+            seen[index] = true;
+        }
+        assert!(seen.iter().all(|x| *x));
+    }
+
+    #[test]
+    fn test_batch_errors_and_backpressure() {
+        let started = Arc::new(AtomicUsize::new(0));
+        let counter = started.clone();
+
+        let inputs = (0..100).map(|i| match i {
+            3 => BatchInput::Memory(b"not an image".to_vec()),
+            _ => BatchInput::Image(Image::fill(0_u8, ColorSpace::RGB, 10, 10))
+        });
+        let mut batch = Batch::new();
+        batch.set_threads(2).set_queue_size(1);
+
+        let mut results = batch.run_with(inputs, move |index, _| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            Ok(index)
+        });
+        let first = results.by_ref().take(10).collect::<Vec<_>>();
+        assert!(first.iter().any(|(index, x)| *index == 3 && x.is_err()));
+
+        // workers wait for the results to be read, and stop once they are dropped
+        drop(results);
+        assert!(started.load(Ordering::Relaxed) < 20);
+    }
+
+    #[test]
+    fn test_batch_panics_become_errors() {
+        let inputs = (0..10).map(|_| Image::fill(0_u8, ColorSpace::RGB, 10, 10));
+
+        let mut batch = Batch::new();
+        batch.set_threads(2);
+
+        let results = batch.run_with(inputs, |index, _| {
+            assert_ne!(index, 4, "bad input");
+            Ok(index)
+        });
+        let mut count = 0;
+
+        for (index, result) in results {
+            match result {
+                Ok(value) => assert_eq!(value, index),
+                Err(e) => {
+                    assert_eq!(index, 4);
+                    assert!(format!("{e:?}").contains("bad input"));
+                }
+            }
+            count += 1;
+        }
+        // the worker that panicked kept going
+        assert_eq!(count, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "input iterator")]
+    fn test_batch_worker_panics_are_raised() {
+        let inputs = (0..10).map(|i| {
+            assert_ne!(i, 2, "input iterator");
+            Image::fill(0_u8, ColorSpace::RGB, 10, 10)
+        });
+        let mut batch = Batch::new();
+        batch.set_threads(1);
+
+        batch.run(inputs).for_each(drop);
+    }
+}
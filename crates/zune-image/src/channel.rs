@@ -19,14 +19,52 @@
 //! as separate bit depths.
 //! All are seen as u8 to it with the only difference being the latter is twice as big as the former.
 //!
-use std::alloc::{alloc_zeroed, dealloc, realloc, Layout};
-use std::any::TypeId;
-use std::fmt::{Debug, Formatter};
-use std::mem::size_of;
+//! # Copy on write
+//! Cloning a channel doesn't copy its data, clones share the same memory until one of
+//! them is written to, then the written channel gets its own copy. This makes cloning
+//! images for previews or branching pipelines cheap, see [`Channel::is_shared`] and
+//! [`Channel::make_unique`].
+//!
+//! # Allocators
+//! Channel memory comes from the system allocator unless a [`ChannelAllocator`] is used,
+//! see [`with_allocator`]. [`ScratchPool`] is one that reuses the memory of dropped
+//! channels, for loops processing many images.
+//!
+//! # Adopting buffers
+//! [`Channel::from_vec`] and [`Channel::from_box`] take over the memory of an existing
+//! vector without copying it, e.g. for frames handed over by a camera or another library.
+//! Such channels are aligned to their element type instead of [`MIN_ALIGNMENT`], and
+//! can only be reinterpreted as that type, like every other channel.
+//!
+//! # Constant channels
+//! Channels filled with one value, e.g. by [`Channel::from_elm`], [`Channel::fill`] or
+//! `Image::fill`, don't allocate or write their memory until they are first read or
+//! written. [`Channel::constant`] returns the value until the channel is written to,
+//! so operations can process the value once instead of every pixel.
+//!
+use alloc::alloc::{alloc_zeroed, dealloc, handle_alloc_error, realloc, Layout};
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::any::TypeId;
+use core::fmt::{Debug, Formatter};
+use core::mem::size_of;
+use core::ptr::null_mut;
+use core::sync::atomic::{AtomicPtr, Ordering};
 
 use bytemuck::{Pod, Zeroable};
 use zune_core::bit_depth::BitType;
 
+#[cfg(feature = "std")]
+pub use self::allocator::with_allocator;
+pub use self::allocator::ChannelAllocator;
+#[cfg(feature = "std")]
+pub use self::scratch::ScratchPool;
+
+mod allocator;
+#[cfg(feature = "std")]
+mod scratch;
+
 /// Minimum alignment for all types allocated in the channel
 ///
 /// This makes it possible to reinterpret the channel data safely
@@ -40,6 +78,9 @@ use zune_core::bit_depth::BitType;
 /// become aligned
 pub const MIN_ALIGNMENT: usize = 64;
 
+/// Largest element a channel can be lazily filled with
+const MAX_FILL_SIZE: usize = 16;
+
 /// Encapsulates errors that can occur
 /// when manipulating channels
 #[derive(Copy, Clone)]
@@ -54,7 +95,7 @@ pub enum ChannelErrors {
 }
 
 impl Debug for ChannelErrors {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
             ChannelErrors::UnalignedPointer(expected, found) => {
                 writeln!(f, "Channel pointer {expected} is not aligned to {found}")
@@ -80,41 +121,225 @@ impl Debug for ChannelErrors {
 ///
 /// Most of the operations in the channel work by calling
 /// `reinterpret` methods, both as reference and as mutable.
-#[derive(Eq)]
+///
+/// Clones share their memory until one of them is written to, see the
+/// [module docs](self)
 pub struct Channel {
-    ptr:      *mut u8,
-    length:   usize,
-    capacity: usize,
+    buffer:  Arc<RawBuffer>,
+    length:  usize,
     // type id for which the channel was created with
-    type_id:  TypeId,
-    layout:   Layout
+    type_id: TypeId
+}
+
+/// Memory backing one or more channels
+struct RawBuffer {
+    // null until first accessed for buffers of one value, see `fill`
+    ptr:       AtomicPtr<u8>,
+    capacity:  usize,
+    layout:    Layout,
+    // None for the system allocator
+    allocator: Option<Arc<dyn ChannelAllocator>>,
+    // the value every element has, until the buffer is written to
+    fill:      Option<Fill>
+}
+
+/// The bytes of one element, repeated over a buffer
+#[derive(Copy, Clone, PartialEq, Eq)]
+struct Fill {
+    bytes: [u8; MAX_FILL_SIZE],
+    size:  usize
+}
+
+impl Fill {
+    /// Return `None` if `T` is too large for a fill
+    fn new<T: Pod>(element: T) -> Option<Fill> {
+        let element = bytemuck::bytes_of(&element);
+        let mut bytes = [0; MAX_FILL_SIZE];
+
+        bytes.get_mut(..element.len())?.copy_from_slice(element);
+
+        Some(Fill {
+            bytes,
+            size: element.len()
+        })
+    }
 }
 
 // safety: The functions ae unsafe because the
 // compiler cannot see that we own the data since self.ptr is a *mut 8
 // which can be stored at a different location from the array,
-// but since we own it and we do not expose it, this is safe
-unsafe impl Send for Channel {}
+// but since we own it and we do not expose it, this is safe.
+// Shared buffers are only read, channels get their own copy before writing
+unsafe impl Send for RawBuffer {}
+
+unsafe impl Sync for RawBuffer {}
 
-unsafe impl Sync for Channel {}
+impl RawBuffer {
+    /// Allocates some bytes, aligned to MIN_ALIGNMENT
+    ///
+    /// Memory comes from `allocator`, or the system allocator if it's `None`
+    fn new(capacity: usize, allocator: Option<Arc<dyn ChannelAllocator>>) -> RawBuffer {
+        let buffer = RawBuffer::new_lazy(capacity, allocator, None);
+        buffer.ptr.store(buffer.allocate(), Ordering::Relaxed);
+        buffer
+    }
+    /// Describe a buffer of `capacity` bytes, allocated and filled with `fill`, or
+    /// zeroed if it's `None`, when first accessed
+    fn new_lazy(
+        capacity: usize, allocator: Option<Arc<dyn ChannelAllocator>>, fill: Option<Fill>
+    ) -> RawBuffer {
+        // zero sized allocations are undefined behaviour
+        let layout = Layout::from_size_align(capacity.max(1), MIN_ALIGNMENT).unwrap();
+
+        RawBuffer {
+            ptr: AtomicPtr::new(null_mut()),
+            capacity,
+            layout,
+            allocator,
+            fill
+        }
+    }
+    /// Allocate zeroed memory for the layout of this buffer
+    fn allocate(&self) -> *mut u8 {
+        // Safety
+        //  alloc zeroed == alloc + std::mem::zeroed()
+        // and we are bound by the zeroed trait, hence we are sure that
+        // for whatever type we are going to allocate for,
+        // it can be represented with a bit-representation of zero.
+        let ptr = match &self.allocator {
+            Some(allocator) => allocator.alloc_zeroed(self.layout),
+            None => unsafe { alloc_zeroed(self.layout) }
+        };
+        if ptr.is_null() {
+            handle_alloc_error(self.layout);
+        }
+        ptr
+    }
+    /// Return the memory of the buffer, allocating and filling it if it's
+    /// the first access
+    fn ptr(&self) -> *mut u8 {
+        let ptr = self.ptr.load(Ordering::Acquire);
+
+        if ptr.is_null() {
+            return self.materialize();
+        }
+        ptr
+    }
+    #[cold]
+    fn materialize(&self) -> *mut u8 {
+        let ptr = self.allocate();
+
+        if let Some(fill) = self.fill.filter(|x| x.bytes.iter().any(|y| *y != 0)) {
+            // Safety: the allocation is capacity bytes long
+            let memory = unsafe { core::slice::from_raw_parts_mut(ptr, self.capacity) };
+
+            for element in memory.chunks_exact_mut(fill.size) {
+                element.copy_from_slice(&fill.bytes[..fill.size]);
+            }
+        }
+        // clones on other threads may get here at the same time, the first one wins
+        match self
+            .ptr
+            .compare_exchange(null_mut(), ptr, Ordering::AcqRel, Ordering::Acquire)
+        {
+            Ok(_) => ptr,
+            Err(winner) => {
+                // Safety: nobody else has seen our allocation
+                unsafe { self.deallocate(ptr) };
+                winner
+            }
+        }
+    }
+    /// Return true if the memory is aligned for `T`, without allocating it
+    fn is_aligned<T>(&self) -> bool {
+        match self.ptr.load(Ordering::Acquire) {
+            ptr if ptr.is_null() => self.layout.align() >= size_of::<T>(),
+            ptr => is_aligned::<T>(ptr)
+        }
+    }
+    /// Free `ptr`, which must be memory of this buffer
+    unsafe fn deallocate(&self, ptr: *mut u8) {
+        match &self.allocator {
+            Some(allocator) => allocator.dealloc(ptr, self.layout),
+            None => dealloc(ptr, self.layout)
+        }
+    }
+    /// Take over the memory of a vector
+    ///
+    /// The vector must have allocated memory, i.e a non zero capacity and element size
+    fn from_vec<T>(vec: Vec<T>) -> RawBuffer {
+        let mut vec = core::mem::ManuallyDrop::new(vec);
+        // the layout the vector allocated with, which is what we free with
+        let layout = Layout::array::<T>(vec.capacity()).unwrap();
+        debug_assert!(layout.size() > 0);
+
+        RawBuffer {
+            ptr: AtomicPtr::new(vec.as_mut_ptr().cast()),
+            capacity: layout.size(),
+            layout,
+            allocator: None,
+            fill: None
+        }
+    }
+    /// Reallocate the pointer increasing it's capacity
+    unsafe fn realloc(&mut self, new_size: usize) {
+        // keep the alignment of adopted buffers, which may be lower than MIN_ALIGNMENT
+        let layout = Layout::from_size_align(new_size.max(1), self.layout.align()).unwrap();
+        let ptr = self.ptr();
+        // the new bytes are zeroes
+        self.fill = None;
+
+        match &self.allocator {
+            Some(_) => {
+                // custom allocators have no realloc, so move to a new buffer
+                let mut buffer = RawBuffer::new(new_size, self.allocator.clone());
+                buffer
+                    .ptr()
+                    .copy_from_nonoverlapping(ptr, self.capacity.min(new_size));
+                core::mem::swap(self, &mut buffer);
+            }
+            None => {
+                let ptr = realloc(ptr, self.layout, layout.size());
+                if ptr.is_null() {
+                    handle_alloc_error(layout);
+                }
+                *self.ptr.get_mut() = ptr;
+                self.capacity = new_size;
+                self.layout = layout;
+            }
+        }
+    }
+}
+
+impl Drop for RawBuffer {
+    fn drop(&mut self) {
+        let ptr = *self.ptr.get_mut();
+        // buffers that were never accessed have no memory
+        if ptr.is_null() {
+            return;
+        }
+        // safety
+        // - The same layout alignment we used for alloc is the same we are using for
+        //  dealloc, and the memory goes back to the allocator it came from
+        unsafe { self.deallocate(ptr) }
+    }
+}
 
 impl Clone for Channel {
+    /// Create a channel sharing memory with this channel
+    ///
+    /// The data is copied the first time one of them is written to
     fn clone(&self) -> Self {
-        let mut new_channel = Channel::new_with_capacity_and_type(self.capacity(), self.type_id);
-        // copy items by calling extend
-
-        // Safety:
-        //
-        // no-slop-bytes: U8's cannot have slop bytes
-        // ptr is valid since we own it, and memory is allocated by us.
-        //
-        unsafe {
-            new_channel.extend_unchecked(self.reinterpret_as_unchecked::<u8>());
+        Channel {
+            buffer:  Arc::clone(&self.buffer),
+            length:  self.length,
+            type_id: self.type_id
         }
-        new_channel
     }
 }
 
+impl Eq for Channel {}
+
 impl PartialEq for Channel {
     fn eq(&self, other: &Self) -> bool {
         // check if length matches
@@ -125,6 +350,14 @@ impl PartialEq for Channel {
         if self.type_id != other.type_id {
             return false;
         }
+        // clones that haven't been written to
+        if Arc::ptr_eq(&self.buffer, &other.buffer) {
+            return true;
+        }
+        // channels of one value
+        if self.buffer.fill.is_some() && self.buffer.fill == other.buffer.fill {
+            return true;
+        }
         unsafe {
             // interpret them as a bag of u8, and iterate
 
@@ -148,11 +381,11 @@ impl PartialEq for Channel {
 }
 
 impl Debug for Channel {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         // safety.
         // all types can alias u8,
         // length points to the length spanning the ptr
-        let slice = unsafe { std::slice::from_raw_parts(self.ptr, self.length) };
+        let slice = unsafe { core::slice::from_raw_parts(self.buffer.ptr(), self.length) };
         writeln!(f, "raw_bytes: {slice:?}")
     }
 }
@@ -163,8 +396,8 @@ impl Channel {
     ///
     /// It returns the number of raw bytes, not respecting
     /// type stored
-    pub const fn capacity(&self) -> usize {
-        self.capacity
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity
     }
     /// Return the length of the underlying array
     ///
@@ -186,10 +419,78 @@ impl Channel {
     ///
     #[allow(dead_code)]
     pub(crate) unsafe fn set_len(&mut self, len: usize) {
-        assert!(len <= self.capacity);
+        assert!(len <= self.capacity());
         self.length = len;
     }
 
+    /// Return true if this channel shares its memory with a clone
+    ///
+    /// Writing to a shared channel copies its data first
+    pub fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.buffer) > 1
+    }
+
+    /// Give this channel its own copy of its data if it shares it with a clone
+    ///
+    /// Methods that write to the channel do this, calling it beforehand moves
+    /// the copy out of e.g. a loop over the pixels
+    pub fn make_unique(&mut self) {
+        if Arc::get_mut(&mut self.buffer).is_none() {
+            let allocator = self.buffer.allocator.clone();
+
+            if let Some(fill) = self.buffer.fill {
+                // no need to copy what can be filled again
+                let buffer = RawBuffer::new_lazy(self.capacity(), allocator, Some(fill));
+                self.buffer = Arc::new(buffer);
+                return;
+            }
+            let buffer = RawBuffer::new(self.capacity(), allocator);
+            // Safety:
+            // - both buffers can hold length bytes, and are different allocations
+            unsafe {
+                buffer
+                    .ptr()
+                    .copy_from_nonoverlapping(self.buffer.ptr(), self.length);
+            }
+            self.buffer = Arc::new(buffer);
+        }
+    }
+
+    /// Return a pointer to the data of the channel that can be written to
+    fn ptr_mut(&mut self) -> *mut u8 {
+        self.make_unique();
+        // unique after the call above
+        let buffer = Arc::get_mut(&mut self.buffer).unwrap();
+        let ptr = buffer.ptr();
+        // the values may change from here on
+        buffer.fill = None;
+        ptr
+    }
+
+    /// Return the value of every element if the channel was filled with one value
+    /// and hasn't been written to since
+    ///
+    /// It returns `None` if `T` isn't the type of the channel, or the channel may
+    /// hold different values, see the [module docs](self#constant-channels)
+    ///
+    /// # Example
+    /// ```
+    /// use zune_image::channel::Channel;
+    /// let mut channel = Channel::from_elm(100, 90_u16);
+    /// assert_eq!(channel.constant::<u16>(), Some(90));
+    ///
+    /// channel.reinterpret_as_mut::<u16>().unwrap()[0] = 1;
+    /// assert_eq!(channel.constant::<u16>(), None);
+    /// ```
+    pub fn constant<T: 'static + Pod>(&self) -> Option<T> {
+        let fill = self.buffer.fill?;
+
+        if TypeId::of::<T>() != self.type_id || fill.size != size_of::<T>() {
+            return None;
+        }
+        Some(bytemuck::pod_read_unaligned(&fill.bytes[..fill.size]))
+    }
+
     /// Return true whether this channel length is zero
     ///
     /// # Example
@@ -202,35 +503,12 @@ impl Channel {
         self.length == 0
     }
 
-    /// Allocates some bytes using the system allocator.
-    /// but align it to MIN_ALIGNMENT
-    ///
-    /// It is not unsafe to call this, it's just left as unsafe
-    /// to remind one to be careful of what they are doing
-    unsafe fn alloc(size: usize) -> (*mut u8, Layout) {
-        let layout = Layout::from_size_align(size, MIN_ALIGNMENT).unwrap();
-        // Safety
-        //  alloc zeroed == alloc + std::mem::zeroed()
-        // and we are bound by the zeroed trait, hence we are sure that
-        // for whatever type we are going to allocate for,
-        // it can be represented with a bit-representation of zero.
-        (alloc_zeroed(layout), layout)
-    }
     /// Reallocate the pointer in place increasing
     /// it's capacity
     pub unsafe fn realloc(&mut self, new_size: usize) {
-        self.ptr = realloc(self.ptr, self.layout, new_size);
-        // set capacity to be new size
-        self.capacity = new_size;
-    }
-    /// Deallocate storage allocated for this channel
-    unsafe fn dealloc(&mut self) {
-        let layout = Layout::from_size_align(self.capacity, MIN_ALIGNMENT).unwrap();
-
-        // safety
-        // - The same layout alignment we used for alloc is the same we are using for
-        //  dealloc
-        dealloc(self.ptr, layout);
+        self.make_unique();
+        // unique after the call above
+        Arc::get_mut(&mut self.buffer).unwrap().realloc(new_size);
     }
 
     /// Create a new channel
@@ -289,6 +567,7 @@ impl Channel {
             BitType::U8 => TypeId::of::<u8>(),
             BitType::U16 => TypeId::of::<u16>(),
             BitType::F32 => TypeId::of::<f32>(),
+            BitType::F16 => TypeId::of::<crate::half::F16>(),
             _ => unimplemented!("Bit-depth :{:?}", depth)
         };
 
@@ -335,17 +614,72 @@ impl Channel {
     /// returns: Channel
     ///
     pub(crate) fn new_with_capacity_and_type(capacity: usize, type_id: TypeId) -> Channel {
-        let (ptr, layout) = unsafe { Self::alloc(capacity) };
+        Self::new_with_capacity_and_type_in(capacity, type_id, allocator::current_allocator())
+    }
 
+    fn new_with_capacity_and_type_in(
+        capacity: usize, type_id: TypeId, allocator: Option<Arc<dyn ChannelAllocator>>
+    ) -> Channel {
         Self {
-            ptr,
+            buffer: Arc::new(RawBuffer::new(capacity, allocator)),
             length: 0,
-            capacity,
-            type_id,
-            layout
+            type_id
         }
     }
 
+    /// Create a new channel with the specified length, allocated by
+    /// `allocator`
+    ///
+    /// Unlike [`with_allocator`], this doesn't change where other channels are allocated
+    ///
+    /// # Arguments
+    ///  - length: The length of the new channel
+    ///  - allocator: Where the memory of the channel comes from
+    pub fn new_with_length_in<T: 'static + Zeroable>(
+        length: usize, allocator: Arc<dyn ChannelAllocator>
+    ) -> Channel {
+        let mut channel =
+            Self::new_with_capacity_and_type_in(length, TypeId::of::<T>(), Some(allocator));
+        channel.length = length;
+        channel
+    }
+
+    /// Create a channel from a vector, taking over its memory without copying
+    ///
+    /// The channel is aligned to `T` and the spare capacity of the vector is kept,
+    /// see the [module docs](self#adopting-buffers). Empty vectors, which have
+    /// no memory to take over, allocate a new channel.
+    ///
+    /// # Example
+    /// ```
+    /// use zune_image::channel::Channel;
+    ///
+    /// let pixels = vec![1_u16, 2, 3];
+    /// let address = pixels.as_ptr();
+    /// let channel = Channel::from_vec(pixels);
+    ///
+    /// assert_eq!(channel.reinterpret_as::<u16>().unwrap(), &[1, 2, 3]);
+    /// assert_eq!(channel.reinterpret_as::<u16>().unwrap().as_ptr(), address);
+    /// ```
+    pub fn from_vec<T: 'static + Pod>(vec: Vec<T>) -> Channel {
+        let length = size_of_val(vec.as_slice());
+
+        if vec.capacity() == 0 || size_of::<T>() == 0 {
+            return Channel::new_with_length::<T>(length);
+        }
+        Channel {
+            buffer: Arc::new(RawBuffer::from_vec(vec)),
+            length,
+            type_id: TypeId::of::<T>()
+        }
+    }
+    /// Create a channel from a boxed slice, taking over its memory without copying
+    ///
+    /// See [`from_vec`](Self::from_vec)
+    pub fn from_box<T: 'static + Pod>(boxed: Box<[T]>) -> Channel {
+        Channel::from_vec(boxed.into_vec())
+    }
+
     ///  
     ///
     /// # Arguments
@@ -366,17 +700,29 @@ impl Channel {
     where
         T: Clone + Copy + 'static + Zeroable + Pod
     {
-        // new currently zeroes memory
-        let mut new_chan = Channel::new_with_length::<T>(length * size_of::<T>());
-
-        new_chan.fill(elm).unwrap();
+        let length = length * size_of::<T>();
+        let allocator = allocator::current_allocator();
 
-        new_chan
+        match Fill::new(elm) {
+            // allocated and filled when first accessed
+            Some(fill) => Channel {
+                buffer: Arc::new(RawBuffer::new_lazy(length, allocator, Some(fill))),
+                length,
+                type_id: TypeId::of::<T>()
+            },
+            None => {
+                let mut new_chan =
+                    Channel::new_with_capacity_and_type_in(length, TypeId::of::<T>(), allocator);
+                new_chan.length = length;
+                new_chan.fill(elm).unwrap();
+                new_chan
+            }
+        }
     }
     /// Return true if we can store `extra`
     /// items without resizing/reallocating
     fn has_capacity(&self, extra: usize) -> bool {
-        self.length.saturating_add(extra) <= self.capacity
+        self.length.saturating_add(extra) <= self.capacity()
     }
     /// Extend this channel with items from data
     ///
@@ -408,7 +754,7 @@ impl Channel {
             // reallocate to handle enough of the length.
             // realloc will set the new capacity
             // but as callers we have to set the new length
-            self.realloc(self.capacity.saturating_add(items).saturating_add(10));
+            self.realloc(self.capacity().saturating_add(items).saturating_add(10));
         }
         // now we have enough space, extend
 
@@ -416,7 +762,7 @@ impl Channel {
         // - self.ptr+length cannot overflow, since it's usize
         // -  data is valid for data size
         //
-        self.ptr.wrapping_add(self.length).copy_from(
+        self.ptr_mut().wrapping_add(self.length).copy_from(
             data.as_ptr().cast::<u8>(),
             data.len().saturating_mul(data_size)
         );
@@ -457,7 +803,8 @@ impl Channel {
         //  well aligned: You cannot have u8 having bad alignment as the least bit denomination
         // of alignment is a byte and u8==1 byte
         //
-        let new_slice = unsafe { std::slice::from_raw_parts_mut::<u8>(self.ptr, self.length) };
+        let new_slice =
+            unsafe { core::slice::from_raw_parts::<u8>(self.buffer.ptr(), self.length) };
 
         let (a, b, c) = new_slice.align_to();
 
@@ -476,7 +823,8 @@ impl Channel {
         //  validity: We own the data
         //  well aligned: You cannot have u8 having bad alignment
         //
-        let new_slice = unsafe { std::slice::from_raw_parts_mut::<u8>(self.ptr, self.length) };
+        let new_slice =
+            unsafe { core::slice::from_raw_parts_mut::<u8>(self.ptr_mut(), self.length) };
 
         let (a, b, c) = bytemuck::pod_align_to_mut(new_slice);
 
@@ -513,7 +861,7 @@ impl Channel {
             unsafe {
                 // extend
                 // use 3/2 formula
-                self.realloc(self.capacity.saturating_mul(size.saturating_mul(3)) / 2);
+                self.realloc(self.capacity().saturating_mul(size.saturating_mul(3)) / 2);
             }
         }
         // safety:
@@ -523,7 +871,7 @@ impl Channel {
             // pointer to u8 so that we can copy it
             let arr = [elm];
 
-            self.ptr
+            self.ptr_mut()
                 .add(self.length)
                 .copy_from(arr.as_ptr().cast(), size);
         }
@@ -533,6 +881,10 @@ impl Channel {
 
     /// Fill the channel with a specific element
     ///
+    /// Channels sharing their memory with a clone, or without memory yet, aren't
+    /// written, they get memory filled with the element when next accessed,
+    /// see [`constant`](Self::constant)
+    ///
     /// # Arguments
     ///
     /// * `element`:  The element to fill the channel
@@ -551,11 +903,25 @@ impl Channel {
     where
         T: Clone + Copy + 'static + Pod
     {
-        // reinterpret to be type T
-        let array = self.reinterpret_as_mut()?;
-        // Then fill elements
-        array.fill(element);
+        self.confirm_suspicions::<T>()?;
 
+        let Some(fill) = Fill::new(element) else {
+            // reinterpret to be type T, then fill elements
+            self.reinterpret_as_mut()?.fill(element);
+            return Ok(());
+        };
+        match Arc::get_mut(&mut self.buffer) {
+            // reuse the memory we own
+            Some(buffer) if !buffer.ptr.load(Ordering::Relaxed).is_null() => {
+                self.reinterpret_as_mut()?.fill(element);
+                Arc::get_mut(&mut self.buffer).unwrap().fill = Some(fill);
+            }
+            _ => {
+                let allocator = self.buffer.allocator.clone();
+                let buffer = RawBuffer::new_lazy(self.capacity(), allocator, Some(fill));
+                self.buffer = Arc::new(buffer);
+            }
+        }
         Ok(())
     }
     /// Confirm that data is aligned and
@@ -563,9 +929,9 @@ impl Channel {
     /// the type T can evenly divide length
     fn confirm_suspicions<T: 'static>(&self) -> Result<(), ChannelErrors> {
         // confirm the data is aligned for T
-        if !is_aligned::<T>(self.ptr) {
+        if !self.buffer.is_aligned::<T>() {
             return Err(ChannelErrors::UnalignedPointer(
-                self.ptr as usize,
+                self.buffer.ptr() as usize,
                 size_of::<T>()
             ));
         }
@@ -592,7 +958,7 @@ impl Channel {
     /// This is unsafe just as a remainder that the memory is just
     /// a bag of bytes and may not be just `&[u8]`.
     pub unsafe fn alias(&self) -> &[u8] {
-        std::slice::from_raw_parts(self.ptr, self.length)
+        core::slice::from_raw_parts(self.buffer.ptr(), self.length)
     }
 
     /// Return the raw memory layout of the channel as `mut &[u8]`
@@ -601,16 +967,7 @@ impl Channel {
     /// This is unsafe just as a remainder that the memory is just
     /// a bag of bytes and may not be just `mut &[u8]`.
     pub unsafe fn alias_mut(&mut self) -> &mut [u8] {
-        std::slice::from_raw_parts_mut(self.ptr, self.length)
-    }
-}
-
-impl Drop for Channel {
-    fn drop(&mut self) {
-        // dealloc storage
-        unsafe {
-            self.dealloc();
-        }
+        core::slice::from_raw_parts_mut(self.ptr_mut(), self.length)
     }
 }
 
@@ -651,4 +1008,136 @@ mod tests {
 
         assert_eq!(ch, ch2);
     }
+
+    #[test]
+    fn test_clones_copy_on_write() {
+        let mut ch = Channel::new::<u16>();
+        ch.extend::<u16>(&[10; 10]);
+
+        let mut ch2 = ch.clone();
+        assert!(ch.is_shared() && ch2.is_shared());
+
+        ch2.reinterpret_as_mut::<u16>().unwrap()[0] = 20;
+        assert!(!ch.is_shared() && !ch2.is_shared());
+        assert_eq!(ch.reinterpret_as::<u16>().unwrap(), [10; 10]);
+        assert_eq!(ch2.reinterpret_as::<u16>().unwrap()[..2], [20, 10]);
+
+        // growing a shared channel
+        let mut ch3 = ch.clone();
+        ch3.extend::<u16>(&[30; 100]);
+        assert_eq!(ch.len(), 20);
+        assert_eq!(ch3.reinterpret_as::<u16>().unwrap()[10..], [30; 100]);
+    }
+
+    #[test]
+    fn test_custom_allocator() {
+        use alloc::sync::Arc;
+        use core::sync::atomic::{AtomicUsize, Ordering};
+        use std::alloc::{GlobalAlloc, Layout, System};
+
+        use crate::channel::ChannelAllocator;
+
+        #[derive(Default)]
+        struct Live(AtomicUsize);
+
+        unsafe impl ChannelAllocator for Live {
+            fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+                self.0.fetch_add(1, Ordering::Relaxed);
+                unsafe { System.alloc_zeroed(layout) }
+            }
+            unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+                self.0.fetch_sub(1, Ordering::Relaxed);
+                System.dealloc(ptr, layout);
+            }
+        }
+        let live = Arc::new(Live::default());
+
+        let mut ch = Channel::new_with_length_in::<u8>(4, live.clone());
+        ch.fill(1_u8).unwrap();
+        // the copy on write comes from the same allocator
+        let mut ch2 = ch.clone();
+        ch2.extend::<u8>(&[2; 100]);
+        assert_eq!(live.0.load(Ordering::Relaxed), 2);
+        assert_eq!(ch2.reinterpret_as::<u8>().unwrap()[..6], [1, 1, 1, 1, 2, 2]);
+
+        drop((ch, ch2));
+        assert_eq!(live.0.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_scratch_pool() {
+        use alloc::sync::Arc;
+
+        use crate::channel::ScratchPool;
+
+        let pool = Arc::new(ScratchPool::new());
+
+        let mut ch = pool.channel::<u16>(200);
+        ch.fill(7_u16).unwrap();
+        ch.reinterpret_as_mut::<u16>().unwrap()[0] = 1;
+        drop(ch);
+        assert_eq!(pool.free_bytes(), 200);
+
+        // smaller channels reuse the block, zeroed
+        let ch = pool.channel::<u8>(50);
+        assert_eq!(ch.reinterpret_as::<u8>().unwrap(), [0; 50]);
+        assert_eq!((pool.system_allocations(), pool.free_bytes()), (1, 0));
+        // larger ones don't fit
+        let ch2 = pool.channel::<u8>(300);
+        assert_eq!(pool.system_allocations(), 2);
+        drop((ch, ch2));
+
+        // over the limit memory goes back to the system
+        let pool = Arc::new(ScratchPool::with_limit(250));
+        drop((pool.channel::<u8>(200), pool.channel::<u8>(100)));
+        assert_eq!(pool.free_bytes(), 200);
+        pool.clear();
+        assert_eq!(pool.free_bytes(), 0);
+    }
+
+    #[test]
+    fn test_from_vec_adopts_memory() {
+        let pixels: Vec<f32> = Vec::with_capacity(3);
+        let mut ch = Channel::from_vec(pixels);
+        assert!(ch.is_empty());
+
+        ch.extend::<f32>(&[1.0; 3]);
+        ch.extend::<f32>(&[2.0; 100]);
+        assert_eq!(ch.reinterpret_as::<f32>().unwrap()[2..4], [1.0, 2.0]);
+
+        let boxed: Box<[u16]> = vec![5; 7].into_boxed_slice();
+        let address = boxed.as_ptr();
+        let mut ch = Channel::from_box(boxed);
+        assert_eq!(ch.reinterpret_as::<u16>().unwrap().as_ptr(), address);
+        ch.reinterpret_as_mut::<u16>().unwrap()[6] = 1;
+        assert_eq!(ch.reinterpret_as::<u16>().unwrap(), [5, 5, 5, 5, 5, 5, 1]);
+
+        assert!(Channel::from_vec(Vec::<u8>::new()).is_empty());
+    }
+
+    #[test]
+    fn test_constant_channels() {
+        let ch = Channel::from_elm(1000, 7_u16);
+        assert_eq!(ch.constant::<u16>(), Some(7));
+        assert_eq!(ch.constant::<u8>(), None);
+
+        // clones and reads keep the value, writes drop it
+        let mut ch2 = ch.clone();
+        assert_eq!(ch2.reinterpret_as::<u16>().unwrap(), [7; 1000]);
+        assert_eq!(ch2.constant::<u16>(), Some(7));
+        ch2.reinterpret_as_mut::<u16>().unwrap()[999] = 8;
+        assert_eq!(ch2.constant::<u16>(), None);
+        assert_eq!(ch.reinterpret_as::<u16>().unwrap(), [7; 1000]);
+        assert_ne!(ch, ch2);
+
+        ch2.fill(9_u16).unwrap();
+        assert_eq!(ch2.constant::<u16>(), Some(9));
+        assert_eq!(ch2.reinterpret_as::<u16>().unwrap(), [9; 1000]);
+        assert_eq!(ch2, Channel::from_elm(1000, 9_u16));
+
+        let mut ch3 = Channel::from_elm(3, 1.5_f32);
+        ch3.push(2.5_f32);
+        assert_eq!(ch3.constant::<f32>(), None);
+        assert_eq!(ch3.reinterpret_as::<f32>().unwrap(), [1.5, 1.5, 1.5, 2.5]);
+    }
 }
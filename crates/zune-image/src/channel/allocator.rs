@@ -0,0 +1,120 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Custom allocators for channel memory
+//!
+//! Channels allocate through the system allocator by default, an allocator implementing
+//! [`ChannelAllocator`] can be used instead for a single channel with
+//! [`Channel::new_with_length_in`](crate::channel::Channel::new_with_length_in), or for all
+//! channels created by a closure, e.g. while decoding and processing an image, with
+//! [`with_allocator`].
+//!
+//! Every channel remembers the allocator its memory came from and returns the memory to
+//! it when dropped, copies made when a shared channel is written to come from the same allocator.
+//!
+//! Without the `std` feature there are no thread locals, [`with_allocator`] isn't available
+//! and only channels created with an explicit allocator use it.
+use alloc::alloc::Layout;
+use alloc::sync::Arc;
+#[cfg(feature = "std")]
+use core::cell::RefCell;
+
+/// Allocates the memory channels store their data in
+///
+/// This lets long-running servers keep pixel data in a pool instead of fragmenting the heap
+/// and embedded users place it in a specific memory region.
+///
+/// # Safety
+/// `alloc_zeroed` must return null or a pointer to zeroed memory valid for reads and writes
+/// of `layout.size()` bytes, aligned to `layout.align()`, that stays valid until it's passed
+/// to `dealloc`.
+///
+/// # Example
+/// Count the bytes in use
+/// ```
+/// use std::alloc::{Layout, System, GlobalAlloc};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+///
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::channel::{with_allocator, ChannelAllocator};
+/// use zune_image::image::Image;
+///
+/// #[derive(Default)]
+/// struct Counting(AtomicUsize);
+///
+/// unsafe impl ChannelAllocator for Counting {
+///     fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+///         self.0.fetch_add(layout.size(), Ordering::Relaxed);
+///         unsafe { System.alloc_zeroed(layout) }
+///     }
+///     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+///         self.0.fetch_sub(layout.size(), Ordering::Relaxed);
+///         System.dealloc(ptr, layout)
+///     }
+/// }
+///
+/// let counting = Arc::new(Counting::default());
+/// let pixels = vec![0_u8; 100 * 100 * 3];
+/// let image = with_allocator(counting.clone(), || {
+///     Image::from_u8(&pixels, 100, 100, ColorSpace::RGB)
+/// });
+/// assert!(counting.0.load(Ordering::Relaxed) >= 100 * 100);
+///
+/// drop(image);
+/// assert_eq!(counting.0.load(Ordering::Relaxed), 0);
+/// ```
+pub unsafe trait ChannelAllocator: Send + Sync {
+    /// Allocate zeroed memory for `layout`, returning null if it can't
+    fn alloc_zeroed(&self, layout: Layout) -> *mut u8;
+
+    /// Free memory allocated by this allocator
+    ///
+    /// # Safety
+    /// `ptr` must have been returned by [`alloc_zeroed`](Self::alloc_zeroed) of this
+    /// allocator with the same `layout`
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout);
+}
+
+#[cfg(feature = "std")]
+thread_local! {
+    static CURRENT: RefCell<Option<Arc<dyn ChannelAllocator>>> = const { RefCell::new(None) };
+}
+
+/// Allocate all channels created on this thread by `function` with `allocator`
+///
+/// Operations that split work between threads allocate their channels on those
+/// threads with the system allocator.
+///
+/// Calls can be nested, the previous allocator is restored when `function` returns
+#[cfg(feature = "std")]
+pub fn with_allocator<R>(allocator: Arc<dyn ChannelAllocator>, function: impl FnOnce() -> R) -> R {
+    /// Restores the previous allocator, even if `function` panics
+    struct Restore(Option<Arc<dyn ChannelAllocator>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            CURRENT.with(|x| *x.borrow_mut() = previous);
+        }
+    }
+    let _restore = Restore(CURRENT.with(|x| x.borrow_mut().replace(allocator)));
+
+    function()
+}
+
+/// The allocator set by [`with_allocator`] on this thread, if any
+pub(crate) fn current_allocator() -> Option<Arc<dyn ChannelAllocator>> {
+    #[cfg(feature = "std")]
+    {
+        CURRENT.with(|x| x.borrow().clone())
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        None
+    }
+}
@@ -0,0 +1,192 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! A pool of reusable channel memory
+//!
+//! Operations allocate their output, and often temporary channels, every time they run,
+//! which in a loop processing image after image means allocating and freeing the same
+//! sizes over and over. A [`ScratchPool`] keeps the memory of dropped channels and hands
+//! it out again to the next channel that fits, so after the first image a loop mostly
+//! runs without calling the system allocator.
+//!
+//! Memory is only returned to the system when the pool is dropped or [cleared](ScratchPool::clear),
+//! or when keeping it would go over the [limit](ScratchPool::with_limit).
+use alloc::alloc::{GlobalAlloc, Layout};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use std::alloc::System;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::channel::{Channel, ChannelAllocator};
+
+/// A [`ChannelAllocator`] reusing the memory of dropped channels
+///
+/// Use it for the channels created by a closure with
+/// [`with_allocator`](crate::channel::with_allocator), for the operations of a pipeline with
+/// [`Pipeline::set_scratch_pool`](crate::pipelines::Pipeline::set_scratch_pool), or for
+/// a single channel with [`ScratchPool::channel`].
+///
+/// A channel gets the smallest free block it fits in, blocks aren't split, so the pool ends up
+/// holding blocks the size of the largest channels it has seen. Reused blocks are zeroed
+/// before they are handed out.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+///
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::channel::{with_allocator, ScratchPool};
+/// use zune_image::image::Image;
+///
+/// let pool = Arc::new(ScratchPool::new());
+/// let pixels = vec![0_u8; 100 * 100 * 3];
+///
+/// for _ in 0..10 {
+///     let image = with_allocator(pool.clone(), || {
+///         Image::from_u8(&pixels, 100, 100, ColorSpace::RGB)
+///     });
+///     drop(image);
+/// }
+/// // three channels, allocated for the first image and reused by the others
+/// assert_eq!(pool.system_allocations(), 3);
+/// ```
+pub struct ScratchPool {
+    blocks: Mutex<Blocks>,
+    limit:  usize
+}
+
+#[derive(Default)]
+struct Blocks {
+    // memory nobody is using, ready to be handed out
+    free:        Vec<Block>,
+    // memory handed out, kept to know the real layout of blocks channels return
+    used:        Vec<Block>,
+    free_bytes:  usize,
+    allocations: usize
+}
+
+struct Block {
+    ptr:    *mut u8,
+    layout: Layout
+}
+
+// Safety: blocks are memory owned by the pool, only touched while holding the lock
+unsafe impl Send for Blocks {}
+
+impl ScratchPool {
+    /// Create a pool keeping all memory it's given back
+    pub fn new() -> ScratchPool {
+        ScratchPool::with_limit(usize::MAX)
+    }
+    /// Create a pool keeping at most `limit` bytes of free memory, dropped channels
+    /// that would go over it are freed
+    pub fn with_limit(limit: usize) -> ScratchPool {
+        ScratchPool {
+            blocks: Mutex::new(Blocks::default()),
+            limit
+        }
+    }
+    /// Create a zeroed channel of `length` bytes, storing `T`, from this pool
+    ///
+    /// Unlike [`with_allocator`](crate::channel::with_allocator), this doesn't change where
+    /// other channels are allocated, e.g. the temporaries of an operation.
+    pub fn channel<T: 'static + bytemuck::Zeroable>(self: &Arc<Self>, length: usize) -> Channel {
+        Channel::new_with_length_in::<T>(length, self.clone())
+    }
+    /// Return the number of bytes of free memory the pool holds
+    pub fn free_bytes(&self) -> usize {
+        self.lock().free_bytes
+    }
+    /// Return how many times the pool allocated from the system allocator, because no
+    /// free block was large enough
+    pub fn system_allocations(&self) -> usize {
+        self.lock().allocations
+    }
+    /// Free all memory not used by a channel
+    pub fn clear(&self) {
+        let mut blocks = self.lock();
+
+        for block in blocks.free.drain(..) {
+            // Safety: the block was allocated by the system allocator with its layout
+            unsafe { System.dealloc(block.ptr, block.layout) };
+        }
+        blocks.free_bytes = 0;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Blocks> {
+        // the blocks are consistent between calls, even if a thread panicked
+        self.blocks.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for ScratchPool {
+    fn default() -> Self {
+        ScratchPool::new()
+    }
+}
+
+unsafe impl ChannelAllocator for ScratchPool {
+    fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let mut blocks = self.lock();
+
+        let fitting = blocks
+            .free
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| x.layout.size() >= layout.size() && x.layout.align() >= layout.align())
+            .min_by_key(|(_, x)| x.layout.size())
+            .map(|(i, _)| i);
+
+        let block = match fitting {
+            Some(i) => {
+                let block = blocks.free.swap_remove(i);
+                blocks.free_bytes -= block.layout.size();
+                // Safety: the block is at least layout.size() bytes long
+                unsafe { block.ptr.write_bytes(0, layout.size()) };
+                block
+            }
+            None => {
+                // Safety: the layout has a non zero size, channels never allocate zero bytes
+                let ptr = unsafe { System.alloc_zeroed(layout) };
+
+                if ptr.is_null() {
+                    return ptr;
+                }
+                blocks.allocations += 1;
+                Block { ptr, layout }
+            }
+        };
+        let ptr = block.ptr;
+        blocks.used.push(block);
+
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, _: Layout) {
+        let mut blocks = self.lock();
+
+        let position = blocks.used.iter().position(|x| x.ptr == ptr);
+        let Some(block) = position.map(|i| blocks.used.swap_remove(i)) else {
+            debug_assert!(false, "Memory not allocated by this pool");
+            return;
+        };
+
+        if blocks.free_bytes.saturating_add(block.layout.size()) > self.limit {
+            System.dealloc(block.ptr, block.layout);
+            return;
+        }
+        blocks.free_bytes += block.layout.size();
+        blocks.free.push(block);
+    }
+}
+
+impl Drop for ScratchPool {
+    fn drop(&mut self) {
+        // channels keep the pool alive, so every block is free by now
+        self.clear();
+    }
+}
@@ -31,18 +31,30 @@
 //!
 #![allow(unused_imports, unused_variables, non_camel_case_types, dead_code)]
 
-use std::io::Cursor;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+#[cfg(feature = "std")]
 use std::path::Path;
 
-use zune_core::bytestream::{ZByteReaderTrait, ZByteWriterTrait, ZCursor, ZReader};
+use bytemuck::Pod;
+use zune_core::bit_depth::BitDepth;
+use zune_core::bytestream::{ZByteIoError, ZByteReaderTrait, ZByteWriterTrait, ZCursor, ZReader};
+use zune_core::colorspace::ColorSpace;
 use zune_core::log::trace;
 use zune_core::options::{DecoderOptions, EncoderOptions};
 
-use crate::codecs;
+use crate::channel::Channel;
+use crate::deinterleave::{deinterleave_u16_into, deinterleave_u8_into};
 use crate::errors::ImgEncodeErrors::ImageEncodeErrors;
-use crate::errors::{ImageErrors, ImgEncodeErrors};
-use crate::image::Image;
-use crate::traits::{DecoderTrait, EncoderTrait};
+use crate::errors::{ErrorKind, ImageErrors, ImgEncodeErrors};
+use crate::image::{checked_mul, Image};
+use crate::metadata::PixelLayout;
+use crate::position::Tracked;
+#[cfg(feature = "std")]
+use crate::progress::report;
+use crate::traits::{DecoderTrait, EncoderTrait, ZuneInts};
+use crate::{codecs, limits};
 
 pub mod bmp;
 mod exr;
@@ -54,6 +66,125 @@ pub mod png;
 pub mod ppm;
 pub mod psd;
 pub mod qoi;
+
+/// The kind of a reader error returned by a decoder
+pub(crate) fn io_error_kind(error: &ZByteIoError) -> ErrorKind {
+    match error {
+        ZByteIoError::SeekError(_) | ZByteIoError::SeekErrorOwned(_) => ErrorKind::Io,
+        ZByteIoError::NotEnoughBuffer(..) => ErrorKind::InvalidInput,
+        // running out of bytes means the file is truncated
+        ZByteIoError::NotEnoughBytes(..)
+        | ZByteIoError::TryFromIntError(_)
+        | ZByteIoError::Generic(_) => ErrorKind::Corrupt,
+        #[allow(unreachable_patterns)]
+        _ => ErrorKind::Io
+    }
+}
+
+/// Decode a single frame of interleaved pixels into `image`, in the image's layout
+///
+/// The channels of `image` are reused if it already holds a `width` by `height` frame of
+/// `colorspace` pixels of type `T`, otherwise they are replaced by new ones.
+///
+/// `decode` writes the interleaved pixels into the bytes it's given, in native endian.
+/// Planar images are decoded into a temporary buffer before being split into the channels
+pub(crate) fn decode_frame_into<T>(
+    image: &mut Image, width: usize, height: usize, colorspace: ColorSpace,
+    decode: impl FnOnce(&mut [u8]) -> Result<(), ImageErrors>
+) -> Result<(), ImageErrors>
+where
+    T: Copy + Default + 'static + Pod + ZuneInts<T>
+{
+    let layout = image.layout();
+    let pixels = checked_mul(width, height, 1, colorspace.num_components());
+
+    // number of channels and bytes in each
+    let (count, length) = match layout {
+        PixelLayout::Interleaved => (1, pixels * size_of::<T>()),
+        PixelLayout::Planar => (
+            colorspace.num_components(),
+            checked_mul(width, height, size_of::<T>(), 1)
+        )
+    };
+    let reusable = image.frames_len() == 1
+        && image.dimensions() == (width, height)
+        && image.colorspace() == colorspace
+        && image.depth() == T::depth()
+        && image.frames_ref()[0].channels_vec_ref().len() == count
+        && image.frames_ref()[0]
+            .channels_vec_ref()
+            .iter()
+            .all(|x| x.len() == length && x.reinterpret_as::<T>().is_ok());
+
+    if !reusable {
+        trace!("Image does not match the decoded image, allocating new channels");
+
+        let channels = vec![Channel::new_with_length::<T>(length); count];
+
+        *image = Image::new(channels, T::depth(), width, height, colorspace);
+        image.metadata.set_layout(layout);
+    }
+    let channels = image.frames_mut()[0].channels_vec();
+
+    match layout {
+        PixelLayout::Interleaved => {
+            decode(bytemuck::cast_slice_mut(
+                channels[0].reinterpret_as_mut::<T>()?
+            ))?;
+        }
+        PixelLayout::Planar => {
+            let mut interleaved = vec![T::default(); pixels];
+            decode(bytemuck::cast_slice_mut(&mut interleaved))?;
+
+            match T::depth() {
+                BitDepth::Eight => {
+                    deinterleave_u8_into(bytemuck::cast_slice(&interleaved), channels)?;
+                }
+                BitDepth::Sixteen => {
+                    deinterleave_u16_into(bytemuck::cast_slice(&interleaved), channels)?;
+                }
+                depth => {
+                    return Err(ImageErrors::ImageOperationNotImplemented(
+                        "decode_into_image",
+                        depth.bit_type()
+                    ))
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Limit the threads a decoder starts of its own to [`max_threads`](crate::threads::max_threads),
+/// see [`threads`](crate::threads)
+///
+/// Without the `threads` feature decoders stay on the calling thread
+#[cfg(any(feature = "jpeg", feature = "png"))]
+pub(crate) fn limit_decoder_threads(options: DecoderOptions) -> DecoderOptions {
+    #[cfg(feature = "threads")]
+    let limit = crate::threads::max_threads();
+    #[cfg(not(feature = "threads"))]
+    let limit = 1;
+
+    match options.max_threads() {
+        // decoders would pick the number of cores
+        0 => options.set_max_threads(limit),
+        threads => options.set_max_threads(threads.min(limit))
+    }
+}
+
+/// Limit the threads an encoder starts of its own to [`max_threads`](crate::threads::max_threads),
+/// see [`threads`](crate::threads)
+#[cfg(any(feature = "png", feature = "jpeg-xl"))]
+pub(crate) fn limit_encoder_threads(options: EncoderOptions) -> EncoderOptions {
+    #[cfg(feature = "threads")]
+    let limit = u8::try_from(crate::threads::max_threads()).unwrap_or(u8::MAX);
+    #[cfg(not(feature = "threads"))]
+    let limit = 1;
+
+    options.set_num_threads(options.num_threads().min(limit))
+}
+
 pub(crate) fn create_options_for_encoder(
     options: Option<EncoderOptions>, image: &Image
 ) -> EncoderOptions {
@@ -133,7 +264,8 @@ impl ImageFormat {
                 #[cfg(feature = "jpeg")]
                 {
                     Ok(Box::new(zune_jpeg::JpegDecoder::new_with_options(
-                        data, options
+                        data,
+                        limit_decoder_threads(options)
                     )))
                 }
                 #[cfg(not(feature = "jpeg"))]
@@ -146,7 +278,8 @@ impl ImageFormat {
                 #[cfg(feature = "png")]
                 {
                     Ok(Box::new(zune_png::PngDecoder::new_with_options(
-                        data, options
+                        data,
+                        limit_decoder_threads(options)
                     )))
                 }
                 #[cfg(not(feature = "png"))]
@@ -434,6 +567,7 @@ impl Image {
     /// // save to jpeg
     /// image.save("hello.jpg").unwrap();
     /// ```
+    #[cfg(feature = "std")]
     pub fn save<P: AsRef<Path>>(&self, file: P) -> Result<(), ImageErrors> {
         return if let Some(ext) = file.as_ref().extension() {
             if let Some(format) = ImageFormat::encoder_for_extension(ext.to_string_lossy()) {
@@ -484,6 +618,7 @@ impl Image {
     ///     Ok(())
     /// }
     /// ```
+    #[cfg(feature = "std")]
     pub fn save_to<P: AsRef<Path>>(&self, file: P, format: ImageFormat) -> Result<(), ImageErrors> {
         // open a file for which we will write directly to
         let mut file = std::io::BufWriter::new(
@@ -589,6 +724,7 @@ impl Image {
     /// otherwise it's an error to try and decode
     ///
     /// See also [read](Self::read) for reading from memory
+    #[cfg(feature = "std")]
     pub fn open<P: AsRef<Path>>(file: P) -> Result<Image, ImageErrors> {
         Self::open_with_options(file, DecoderOptions::default())
     }
@@ -609,12 +745,44 @@ impl Image {
     /// let options = DecoderOptions::default().set_strict_mode(true).set_max_width(100);
     /// let image = Image::open_with_options("/a/file.jpeg",options).unwrap();
     /// ```
+    #[cfg(feature = "std")]
     pub fn open_with_options<P: AsRef<Path>>(
         file: P, options: DecoderOptions
     ) -> Result<Image, ImageErrors> {
         let reader = std::io::BufReader::new(std::fs::File::open(file)?);
         Self::read(reader, options)
     }
+
+    /// Open an encoded file by memory mapping it instead of reading it
+    ///
+    /// This avoids copying the file contents through a read buffer. Files that can't be
+    /// mapped, like pipes, character devices or files reporting a length of zero
+    /// (e.g. under `/proc`) are read into memory instead
+    ///
+    /// # Safety
+    /// The decoder reads straight from the mapped pages, if the file is truncated or
+    /// modified by this or another process while it is being decoded the
+    /// behaviour is undefined and the process may be killed with `SIGBUS`.
+    ///
+    /// Only use this on files you know stay unchanged until this function returns
+    #[cfg(all(feature = "mmap", unix))]
+    pub unsafe fn open_mmap<P: AsRef<Path>>(
+        file: P, options: DecoderOptions
+    ) -> Result<Image, ImageErrors> {
+        let mut file = std::fs::File::open(file)?;
+        let metadata = file.metadata()?;
+
+        if !metadata.is_file() || metadata.len() == 0 {
+            // nothing to map, the length of these doesn't say how much can be read and
+            // pipes can't seek back after sniffing the format, so read them whole
+            let mut contents = Vec::new();
+            std::io::Read::read_to_end(&mut file, &mut contents)?;
+            return Self::read(ZCursor::new(contents), options);
+        }
+        // SAFETY: upheld by the caller
+        let map = crate::mmap::Mmap::map(&file)?;
+        Self::read(ZCursor::new(map), options)
+    }
     /// Open a new file from memory with the configured options
     ///  
     /// # Arguments
@@ -630,17 +798,79 @@ impl Image {
     /// // create a simple ppm p5 grayscale format
     /// let image = Image::read(ZCursor::new(b"P5 1 1 255 1"),DecoderOptions::default());
     ///```
+    /// - Decode from a source that can't seek, e.g. a pipe, as the bytes arrive
+    ///
+    ///```no_run
+    /// use zune_core::bytestream::ZStreamReader;
+    /// use zune_core::options::DecoderOptions;
+    /// use zune_image::image::Image;
+    ///
+    /// let image = Image::read(ZStreamReader::new(std::io::stdin()), DecoderOptions::default());
+    ///```
     pub fn read<T>(src: T, options: DecoderOptions) -> Result<Image, ImageErrors>
+    where
+        T: ZByteReaderTrait
+    {
+        Self::read_with_layout(src, options, PixelLayout::Planar)
+    }
+
+    /// Open a new file from memory with the configured options, storing
+    /// the channels in `layout`
+    ///
+    /// Decoding to [`PixelLayout::Interleaved`] keeps the pixels as the decoder produced them,
+    /// which is faster for images that are only converted to another format or displayed,
+    /// see [`DecoderTrait::decode_with_layout`]
+    ///
+    /// # Example
+    ///```no_run
+    /// use zune_core::bytestream::ZCursor;
+    /// use zune_core::options::DecoderOptions;
+    /// use zune_image::image::Image;
+    /// use zune_image::metadata::PixelLayout;
+    ///
+    /// let image = Image::read_with_layout(
+    ///     ZCursor::new(b"P5 1 1 255 1"),
+    ///     DecoderOptions::default(),
+    ///     PixelLayout::Interleaved
+    /// );
+    ///```
+    pub fn read_with_layout<T>(
+        src: T, options: DecoderOptions, layout: PixelLayout
+    ) -> Result<Image, ImageErrors>
     where
         T: ZByteReaderTrait
     {
         let decoder = ImageFormat::guess_format(src);
 
         if let Some(format) = decoder {
-            let mut image_decoder = format.0.decoder_with_options(format.1, options)?;
+            #[cfg(feature = "std")]
+            report("decode", 0.0)?;
+            let (src, position) = Tracked::new(format.1);
+            let at_position = |e: ImageErrors| e.at_offset(position.get());
+
+            let mut image_decoder = format
+                .0
+                .decoder_with_options(src, options)
+                .map_err(at_position)?;
+
+            if let Some(metadata) = image_decoder.read_headers().map_err(at_position)? {
+                limits::check_headers(&metadata, &options)?;
+            }
             // save format
-            let mut image = image_decoder.decode()?;
+            let mut image = image_decoder
+                .decode_with_layout(layout)
+                .map_err(at_position)?;
             image.metadata.format = Some(format.0);
+            // decoders without headers are checked after decoding, decoders of animated
+            // images check the frames while decoding them
+            limits::check_headers(&image.metadata, &options)?;
+            #[cfg(feature = "std")]
+            report("decode", 1.0)?;
+
+            #[cfg(feature = "metadata")]
+            if options.auto_orient() {
+                image.auto_orient()?;
+            }
             Ok(image)
         } else {
             Err(ImageErrors::ImageDecoderNotImplemented(
@@ -16,10 +16,10 @@ pub use zune_bmp::*;
 use zune_core::bytestream::ZByteReaderTrait;
 use zune_core::colorspace::ColorSpace;
 
-use crate::codecs::ImageFormat;
-use crate::errors::ImageErrors;
+use crate::codecs::{io_error_kind, ImageFormat};
+use crate::errors::{DecodeError, ErrorKind, ImageErrors};
 use crate::image::Image;
-use crate::metadata::ImageMetadata;
+use crate::metadata::{ImageMetadata, PixelLayout};
 use crate::traits::{DecodeInto, DecoderTrait};
 
 impl<T> DecoderTrait for BmpDecoder<T>
@@ -27,11 +27,18 @@ where
     T: ZByteReaderTrait
 {
     fn decode(&mut self) -> Result<Image, ImageErrors> {
+        self.decode_with_layout(PixelLayout::Planar)
+    }
+
+    fn decode_with_layout(&mut self, layout: PixelLayout) -> Result<Image, ImageErrors> {
         let pixels = self.decode()?;
         let (width, height) = self.dimensions().unwrap();
         let colorspace = self.colorspace().unwrap();
 
-        Ok(Image::from_u8(&pixels, width, height, colorspace))
+        Ok(match layout {
+            PixelLayout::Planar => Image::from_u8(&pixels, width, height, colorspace),
+            PixelLayout::Interleaved => Image::from_interleaved(&pixels, width, height, colorspace)
+        })
     }
 
     fn dimensions(&self) -> Option<(usize, usize)> {
@@ -68,7 +75,15 @@ where
 
 impl From<BmpDecoderErrors> for ImageErrors {
     fn from(value: BmpDecoderErrors) -> Self {
-        Self::ImageDecodeErrors(format!("bmp: {:?}", value))
+        let kind = match &value {
+            BmpDecoderErrors::TooLargeDimensions(..) => ErrorKind::LimitExceeded,
+            BmpDecoderErrors::TooSmallBuffer(..) => ErrorKind::InvalidInput,
+            BmpDecoderErrors::IoErrors(err) => io_error_kind(err),
+            _ => ErrorKind::Corrupt
+        };
+        DecodeError::new(ImageFormat::BMP, kind, format!("{value:?}"))
+            .set_source(value)
+            .into()
     }
 }
 
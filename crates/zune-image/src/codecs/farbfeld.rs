@@ -17,10 +17,10 @@ use zune_core::colorspace::ColorSpace;
 use zune_core::options::EncoderOptions;
 pub use zune_farbfeld::*;
 
-use crate::codecs::{create_options_for_encoder, ImageFormat};
-use crate::errors::{ImageErrors, ImgEncodeErrors};
+use crate::codecs::{create_options_for_encoder, io_error_kind, ImageFormat};
+use crate::errors::{DecodeError, ErrorKind, ImageErrors, ImgEncodeErrors};
 use crate::image::Image;
-use crate::metadata::ImageMetadata;
+use crate::metadata::{ImageMetadata, PixelLayout};
 use crate::traits::{DecodeInto, DecoderTrait, EncoderTrait};
 
 impl<T> DecoderTrait for FarbFeldDecoder<T>
@@ -28,11 +28,18 @@ where
     T: ZByteReaderTrait
 {
     fn decode(&mut self) -> Result<Image, ImageErrors> {
+        self.decode_with_layout(PixelLayout::Planar)
+    }
+
+    fn decode_with_layout(&mut self, layout: PixelLayout) -> Result<Image, ImageErrors> {
         let pixels = self.decode().map_err(ImageErrors::from)?;
         let colorspace = self.colorspace();
         let (width, height) = self.dimensions().unwrap();
 
-        let mut image = Image::from_u16(&pixels, width, height, colorspace);
+        let mut image = match layout {
+            PixelLayout::Planar => Image::from_u16(&pixels, width, height, colorspace),
+            PixelLayout::Interleaved => Image::from_interleaved(&pixels, width, height, colorspace)
+        };
 
         image.metadata.format = Some(ImageFormat::Farbfeld);
 
@@ -56,8 +63,7 @@ where
     }
 
     fn read_headers(&mut self) -> Result<Option<ImageMetadata>, crate::errors::ImageErrors> {
-        self.decode_headers()
-            .map_err(|e| ImageErrors::ImageDecodeErrors(format!("{:?}", e)))?;
+        self.decode_headers().map_err(ImageErrors::from)?;
 
         let (width, height) = self.dimensions().unwrap();
         let depth = self.bit_depth();
@@ -77,7 +83,13 @@ where
 
 impl From<FarbFeldErrors> for ImageErrors {
     fn from(value: FarbFeldErrors) -> Self {
-        Self::ImageDecodeErrors(format!("ff: {value:?}"))
+        let kind = match &value {
+            FarbFeldErrors::IoError(err) => io_error_kind(err),
+            FarbFeldErrors::Generic(_) => ErrorKind::Corrupt
+        };
+        DecodeError::new(ImageFormat::Farbfeld, kind, format!("{value:?}"))
+            .set_source(value)
+            .into()
     }
 }
 
@@ -173,7 +185,8 @@ where
         self.decode_headers()
             .map_err(<FarbFeldErrors as Into<ImageErrors>>::into)?;
 
-        // unwrap is okay because we successfully decoded image headers
-        Ok(self.output_buffer_size().unwrap())
+        // unwrap is okay because we successfully decoded image headers,
+        // the size is in bytes but the buffer is u16
+        Ok(self.output_buffer_size().unwrap() / 2)
     }
 }
@@ -14,10 +14,10 @@ use zune_core::colorspace::ColorSpace;
 use zune_core::options::EncoderOptions;
 pub use zune_hdr::*;
 
-use crate::codecs::{create_options_for_encoder, ImageFormat};
-use crate::errors::{ImageErrors, ImgEncodeErrors};
+use crate::codecs::{create_options_for_encoder, io_error_kind, ImageFormat};
+use crate::errors::{DecodeError, ErrorKind, ImageErrors, ImgEncodeErrors};
 use crate::image::Image;
-use crate::metadata::ImageMetadata;
+use crate::metadata::{ImageMetadata, PixelLayout};
 use crate::traits::{DecodeInto, DecoderTrait, EncoderTrait};
 
 impl<T> DecoderTrait for HdrDecoder<T>
@@ -25,11 +25,18 @@ where
     T: ZByteReaderTrait
 {
     fn decode(&mut self) -> Result<Image, ImageErrors> {
+        self.decode_with_layout(PixelLayout::Planar)
+    }
+
+    fn decode_with_layout(&mut self, layout: PixelLayout) -> Result<Image, ImageErrors> {
         let bytes = self.decode()?;
         let (width, height) = self.dimensions().unwrap();
         let colorspace = self.get_colorspace().unwrap();
 
-        Ok(Image::from_f32(&bytes, width, height, colorspace))
+        Ok(match layout {
+            PixelLayout::Planar => Image::from_f32(&bytes, width, height, colorspace),
+            PixelLayout::Interleaved => Image::from_interleaved(&bytes, width, height, colorspace)
+        })
     }
 
     fn dimensions(&self) -> Option<(usize, usize)> {
@@ -63,7 +70,16 @@ where
 
 impl From<HdrDecodeErrors> for ImageErrors {
     fn from(value: HdrDecodeErrors) -> Self {
-        Self::ImageDecodeErrors(format!("hdr: {value:?}"))
+        let kind = match &value {
+            HdrDecodeErrors::UnsupportedOrientation(..) => ErrorKind::Unsupported,
+            HdrDecodeErrors::TooLargeDimensions(..) => ErrorKind::LimitExceeded,
+            HdrDecodeErrors::TooSmallOutputArray(..) => ErrorKind::InvalidInput,
+            HdrDecodeErrors::IoErrors(err) => io_error_kind(err),
+            _ => ErrorKind::Corrupt
+        };
+        DecodeError::new(ImageFormat::HDR, kind, format!("{value:?}"))
+            .set_source(value)
+            .into()
     }
 }
 
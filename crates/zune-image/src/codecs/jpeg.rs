@@ -14,7 +14,7 @@
 //!
 //! The decoder and encoder both support metadata extraction and saving.
 //!
-use jpeg_encoder::{ColorType, EncodingError, JfifWrite};
+use jpeg_encoder::{ColorType, Density, EncodingError, JfifWrite};
 use zune_core::bit_depth::BitDepth;
 use zune_core::bytestream::{ZByteIoError, ZByteReaderTrait, ZByteWriterTrait, ZWriter};
 use zune_core::colorspace::ColorSpace;
@@ -23,10 +23,12 @@ use zune_core::options::EncoderOptions;
 use zune_jpeg::errors::DecodeErrors;
 pub use zune_jpeg::{ImageInfo, JpegDecoder};
 
-use crate::codecs::{create_options_for_encoder, ImageFormat};
-use crate::errors::{ImageErrors, ImgEncodeErrors};
+use crate::codecs::{create_options_for_encoder, decode_frame_into, io_error_kind, ImageFormat};
+use crate::errors::{DecodeError, ErrorKind, ImageErrors, ImgEncodeErrors};
 use crate::image::Image;
-use crate::metadata::ImageMetadata;
+use crate::metadata::{
+    photoshop_resources, ImageMetadata, IptcData, PixelLayout, Resolution, ResolutionUnit
+};
 use crate::traits::{DecodeInto, DecoderTrait, EncoderTrait};
 
 struct TempVt<'a, T: ZByteWriterTrait> {
@@ -42,6 +44,12 @@ impl<'a, T: ZByteWriterTrait> JfifWrite for TempVt<'a, T> {
 }
 impl<T: ZByteReaderTrait> DecoderTrait for zune_jpeg::JpegDecoder<T> {
     fn decode(&mut self) -> Result<Image, crate::errors::ImageErrors> {
+        self.decode_with_layout(PixelLayout::Planar)
+    }
+
+    fn decode_with_layout(
+        &mut self, layout: PixelLayout
+    ) -> Result<Image, crate::errors::ImageErrors> {
         let metadata = self.read_headers()?.unwrap();
 
         let pixels = self
@@ -51,12 +59,34 @@ impl<T: ZByteReaderTrait> DecoderTrait for zune_jpeg::JpegDecoder<T> {
         let colorspace = self.output_colorspace().unwrap();
         let (width, height) = self.dimensions().unwrap();
 
-        let mut image = Image::from_u8(&pixels, width, height, colorspace);
+        let mut image = match layout {
+            PixelLayout::Planar => Image::from_u8(&pixels, width, height, colorspace),
+            PixelLayout::Interleaved => Image::from_interleaved(&pixels, width, height, colorspace)
+        };
         image.metadata = metadata;
         image.metadata.colorspace = self.output_colorspace().unwrap();
+        image.metadata.set_layout(layout);
         Ok(image)
     }
 
+    fn decode_into_image(&mut self, image: &mut Image) -> Result<(), ImageErrors> {
+        let metadata = self.read_headers()?.unwrap();
+
+        let colorspace = self.output_colorspace().unwrap();
+        let (width, height) = self.dimensions().unwrap();
+        let layout = image.layout();
+
+        decode_frame_into::<u8>(image, width, height, colorspace, |out| {
+            self.decode_into(out)
+                .map_err(<DecodeErrors as Into<ImageErrors>>::into)
+        })?;
+
+        image.metadata = metadata;
+        image.metadata.colorspace = colorspace;
+        image.metadata.set_layout(layout);
+        Ok(())
+    }
+
     fn dimensions(&self) -> Option<(usize, usize)> {
         self.dimensions().map(|dims| (dims.0, dims.1))
     }
@@ -90,19 +120,46 @@ impl<T: ZByteReaderTrait> DecoderTrait for zune_jpeg::JpegDecoder<T> {
                 metadata.parse_raw_exif(exif)
             }
         }
+        if let Some(xmp) = self.xmp() {
+            metadata.set_xmp(String::from_utf8_lossy(xmp).into_owned());
+        }
+        if let Some(iptc) = self.iptc() {
+            metadata.set_iptc(IptcData::parse(iptc));
+        }
         if let Some(icc) = self.icc_profile() {
             metadata.set_icc_chunk(icc);
         }
+        if let Some(info) = self.info() {
+            let (x, y) = (f32::from(info.x_density), f32::from(info.y_density));
+
+            let unit = match info.density_unit {
+                1 => Some(ResolutionUnit::Inch),
+                2 => Some(ResolutionUnit::Centimeter),
+                // 1:1 is what encoders write when they have no density
+                _ if x != y => Some(ResolutionUnit::None),
+                _ => None
+            };
+            if let Some(unit) = unit.filter(|_| x > 0.0 && y > 0.0) {
+                metadata.set_resolution(Resolution::new(x, y, unit));
+            }
+        }
 
         Ok(Some(metadata))
     }
 }
 
-impl From<zune_jpeg::errors::DecodeErrors> for ImageErrors {
-    fn from(from: zune_jpeg::errors::DecodeErrors) -> Self {
-        let err = format!("jpg: {from:?}");
-
-        ImageErrors::ImageDecodeErrors(err)
+impl From<DecodeErrors> for ImageErrors {
+    fn from(from: DecodeErrors) -> Self {
+        let kind = match &from {
+            DecodeErrors::Unsupported(_) => ErrorKind::Unsupported,
+            DecodeErrors::LargeDimensions(_) => ErrorKind::LimitExceeded,
+            DecodeErrors::TooSmallOutput(..) => ErrorKind::InvalidInput,
+            DecodeErrors::IoErrors(err) => io_error_kind(err),
+            _ => ErrorKind::Corrupt
+        };
+        DecodeError::new(ImageFormat::JPEG, kind, format!("{from:?}"))
+            .set_source(from)
+            .into()
     }
 }
 // Okay I just need to really appreciate jpeg-encoder crate
@@ -171,6 +228,19 @@ impl EncoderTrait for JpegEncoder {
             encoder.set_progressive(options.jpeg_encode_progressive());
             encoder.set_optimized_huffman_tables(options.jpeg_optimized_huffman_tables());
 
+            if let Some(resolution) = image.metadata.resolution() {
+                let (x, y) = (resolution.x.round() as u16, resolution.y.round() as u16);
+
+                match resolution.unit {
+                    ResolutionUnit::Inch => encoder.set_density(Density::Inch { x, y }),
+                    ResolutionUnit::Centimeter => {
+                        encoder.set_density(Density::Centimeter { x, y });
+                    }
+                    // the encoder can't write an aspect ratio
+                    ResolutionUnit::None => ()
+                }
+            }
+
             #[cfg(feature = "metadata")]
             {
                 use exif::experimental::Writer;
@@ -179,24 +249,39 @@ impl EncoderTrait for JpegEncoder {
                     // explicit :)
                 } else if let Some(metadata) = &image.metadata.exif {
                     let mut writer = Writer::new();
-                    // write first tags for exif
-                    let mut buf = std::io::Cursor::new(b"Exif\x00\x00".to_vec());
-                    // set buffer position to be bytes written, to ensure we don't overwrite anything
-                    buf.set_position(6);
+                    // the writer computes offsets from the start of the buffer,
+                    // so the exif header is added after writing
+                    let mut buf = std::io::Cursor::new(vec![]);
 
                     for metadatum in metadata {
                         writer.push_field(metadatum);
                     }
                     let result = writer.write(&mut buf, false);
                     if result.is_ok() {
+                        let mut segment = b"Exif\x00\x00".to_vec();
+                        segment.extend_from_slice(buf.get_ref());
                         // add the exif tag to APP1 segment
-                        encoder.add_app_segment(1, buf.get_ref())?;
+                        encoder.add_app_segment(1, &segment)?;
                     } else {
                         warn!("Writing exif failed {:?}", result);
                     }
                 }
             }
 
+            // the profile is needed to display colors correctly, so it isn't stripped
+            if let Some(icc) = image.metadata.icc_chunk_for(image.colorspace()) {
+                encoder.add_icc_profile(icc)?;
+            }
+            if !options.strip_metadata() {
+                if let Some(xmp) = image.metadata.xmp() {
+                    let mut segment = b"http://ns.adobe.com/xap/1.0/\x00".to_vec();
+                    segment.extend_from_slice(xmp.as_bytes());
+                    encoder.add_app_segment(1, &segment)?;
+                }
+                if let Some(iptc) = image.metadata.iptc().filter(|x| !x.is_empty()) {
+                    encoder.add_app_segment(13, &photoshop_resources(&iptc.to_bytes()))?;
+                }
+            }
             encoder.encode(pixels, width as u16, height as u16, colorspace)?;
 
             Ok(writer.bytes_written())
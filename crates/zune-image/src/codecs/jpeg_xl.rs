@@ -31,8 +31,8 @@ use zune_core::options::{DecoderOptions, EncoderOptions};
 pub use zune_jpegxl::*;
 
 use crate::channel::Channel;
-use crate::codecs::{create_options_for_encoder, ImageFormat};
-use crate::errors::{ImageErrors, ImgEncodeErrors};
+use crate::codecs::{create_options_for_encoder, limit_encoder_threads, ImageFormat};
+use crate::errors::{DecodeError, ErrorKind, ImageErrors, ImgEncodeErrors};
 use crate::frame::Frame;
 use crate::image::Image;
 use crate::metadata::ImageMetadata;
@@ -69,7 +69,8 @@ impl EncoderTrait for JxlEncoder {
     fn encode_inner<T: ZByteWriterTrait>(
         &mut self, image: &Image, sink: T
     ) -> Result<usize, ImageErrors> {
-        let options = create_options_for_encoder(self.options, image);
+        // the encoder starts threads of its own, keep them within the thread pool's limit
+        let options = limit_encoder_threads(create_options_for_encoder(self.options, image));
 
         let data = &image.to_u8()[0];
 
@@ -117,6 +118,11 @@ impl From<JxlEncodeErrors> for ImgEncodeErrors {
     }
 }
 
+/// An error for data jxl-oxide couldn't decode
+fn corrupt(message: String) -> ImageErrors {
+    DecodeError::new(ImageFormat::JPEG_XL, ErrorKind::Corrupt, message).into()
+}
+
 pub struct JxlDecoder {
     inner:   jxl_oxide::JxlImage,
     options: DecoderOptions
@@ -124,9 +130,16 @@ pub struct JxlDecoder {
 
 impl JxlDecoder {
     pub fn try_new<R: Read>(source: R, options: DecoderOptions) -> Result<JxlDecoder, ImageErrors> {
-        let parser = jxl_oxide::JxlImage::builder()
+        let builder = jxl_oxide::JxlImage::builder();
+        // jxl-oxide creates a pool per image unless given one
+        #[cfg(feature = "threads")]
+        let builder = match crate::threads::thread_pool() {
+            Some(pool) => builder.pool(jxl_oxide::JxlThreadPool::with_rayon_thread_pool(pool)),
+            None => builder
+        };
+        let parser = builder
             .read(source)
-            .map_err(|x| ImageErrors::ImageDecodeErrors(format!("{:?}", x)))?;
+            .map_err(|x| corrupt(format!("{x:?}")))?;
 
         let decoder = JxlDecoder {
             inner: parser,
@@ -146,10 +159,11 @@ impl DecoderTrait for JxlDecoder {
         let mut total_frames = vec![];
 
         if color == ColorSpace::Unknown {
-            return Err(ImageErrors::ImageDecodeErrors(format!(
+            let msg = format!(
                 "Encountered unknown/unsupported colorspace {:?}",
                 self.inner.pixel_format()
-            )));
+            );
+            return Err(DecodeError::new(ImageFormat::JPEG_XL, ErrorKind::Unsupported, msg).into());
         }
         trace!("Image colorspace: {:?}", color);
         trace!("Image dimensions: ({},{})", w, h);
@@ -160,7 +174,9 @@ impl DecoderTrait for JxlDecoder {
                 w,
                 self.options.max_width()
             );
-            return Err(ImageErrors::ImageDecodeErrors(msg));
+            return Err(
+                DecodeError::new(ImageFormat::JPEG_XL, ErrorKind::LimitExceeded, msg).into()
+            );
         }
         if h > self.options.max_height() {
             let msg = format!(
@@ -168,7 +184,9 @@ impl DecoderTrait for JxlDecoder {
                 h,
                 self.options.max_height()
             );
-            return Err(ImageErrors::ImageDecodeErrors(msg));
+            return Err(
+                DecodeError::new(ImageFormat::JPEG_XL, ErrorKind::LimitExceeded, msg).into()
+            );
         }
 
         let taken = if self.options.jxl_decode_animated() {
@@ -176,12 +194,18 @@ impl DecoderTrait for JxlDecoder {
         } else {
             1
         };
+        // every frame is rendered before any is returned, so check them all first
+        let pixel_bytes = color.num_components() * size_of::<f32>();
+
+        if let Some((limit, found, max)) = self.options.exceeded_limit(w, h, pixel_bytes, taken) {
+            return Err(ImageErrors::LimitsExceeded(limit, found, max));
+        }
 
         for frame in 0..taken {
             let render = self
                 .inner
                 .render_frame(frame)
-                .map_err(|x| ImageErrors::ImageDecodeErrors(format!("{}", x)))?;
+                .map_err(|x| corrupt(format!("{x}")))?;
 
             // get the images
             let duration = render.duration();
@@ -200,7 +224,15 @@ impl DecoderTrait for JxlDecoder {
                 // then store it in frame_v
                 frame_v.push(chan);
             }
-            let frame = Frame::new(frame_v);
+            // durations are in ticks, frames without animation have none
+            let frame = match &self.inner.image_header().metadata.animation {
+                Some(animation) => Frame::new_with_duration(
+                    frame_v,
+                    duration as usize * animation.tps_denominator as usize,
+                    animation.tps_numerator as usize
+                ),
+                None => Frame::new(frame_v)
+            };
             total_frames.push(frame);
         }
         // then create a new image
@@ -208,6 +240,9 @@ impl DecoderTrait for JxlDecoder {
         if let Some(im_metadata) = metadata {
             image.metadata = im_metadata;
         }
+        if let Some(animation) = &self.inner.image_header().metadata.animation {
+            image.metadata.set_loop_count(animation.num_loops);
+        }
         Ok(image)
     }
 
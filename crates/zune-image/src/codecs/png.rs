@@ -12,22 +12,25 @@
 //! Represents an png image decoder and encoder
 use std::io::Cursor;
 
-use zune_core::bit_depth::BitDepth;
+use zune_core::bit_depth::{BitDepth, ByteEndian};
 use zune_core::bytestream::{ZByteReaderTrait, ZByteWriterTrait};
-use zune_core::colorspace::ColorSpace;
+use zune_core::colorspace::{ColorSpace, RgbPrimaries};
 use zune_core::log::warn;
 use zune_core::options::EncoderOptions;
 use zune_core::result::DecodingResult;
 use zune_png::error::PngDecodeErrors;
 pub use zune_png::*;
 
-use crate::codecs::{create_options_for_encoder, ImageFormat};
-use crate::errors::ImageErrors;
+use crate::codecs::{
+    create_options_for_encoder, decode_frame_into, io_error_kind, limit_encoder_threads,
+    ImageFormat
+};
 use crate::errors::ImageErrors::ImageDecodeErrors;
 use crate::errors::ImgEncodeErrors::ImageEncodeErrors;
+use crate::errors::{DecodeError, ErrorKind, ImageErrors};
 use crate::frame::Frame;
 use crate::image::Image;
-use crate::metadata::ImageMetadata;
+use crate::metadata::{ImageMetadata, PixelLayout, Resolution, ResolutionUnit};
 use crate::traits::{DecodeInto, DecoderTrait, EncoderTrait};
 
 impl<T> DecoderTrait for PngDecoder<T>
@@ -35,6 +38,10 @@ where
     T: ZByteReaderTrait
 {
     fn decode(&mut self) -> Result<Image, ImageErrors> {
+        self.decode_with_layout(PixelLayout::Planar)
+    }
+
+    fn decode_with_layout(&mut self, layout: PixelLayout) -> Result<Image, ImageErrors> {
         let metadata = self.read_headers()?.unwrap();
 
         let depth = self.depth().unwrap();
@@ -44,43 +51,15 @@ where
         if self.is_animated() && self.options().png_decode_animated() {
             // decode apng frames
             //let mut previous_frame
-            let info = self.info().unwrap().clone();
-            // the output, since we know that no frame will be bigger than the width and height, we can
-            // set this up outside of the loop.
-            let mut output = vec![0; info.width * info.height * colorspace.num_components()];
             let mut output_frames = Vec::new();
-            while self.more_frames() {
-                self.decode_headers()?;
-
-                let mut frame = self.frame_info().unwrap();
-                if frame.dispose_op == DisposeOp::Previous {
-                    // we don't clear our buffer, so output always contains the previous frame
-                    //
-                    // this means that there is no need to store the previous frame and copy it
-                    frame.dispose_op = DisposeOp::None;
-                }
-                let pix = self.decode()?;
-                match pix {
-                    DecodingResult::U8(pix) => {
-                        post_process_image(
-                            &info,
-                            colorspace,
-                            &frame,
-                            &pix,
-                            None,
-                            &mut output,
-                            None,
-                        )?;
-                        let duration = f64::from(frame.delay_num) / f64::from(frame.delay_denom);
-                        // then build a frame from that
-                        let im_frame = Frame::from_u8(&output, colorspace, usize::from(frame.delay_num),usize::from(frame.delay_denom));
-                        output_frames.push(im_frame);
-                    }
-                    _ => return Err(ImageDecodeErrors("The current image is an  Animated PNG but has a depth of 16, such an image isn't supported".to_string()))
-                }
+
+            while let Some(frame) = self.next_frame()? {
+                output_frames.push(frame);
             }
             let mut image = Image::new_frames(output_frames, depth, width, height, colorspace);
             image.metadata = metadata;
+            // frames are composed planar
+            image.convert_layout(layout)?;
 
             Ok(image)
         } else {
@@ -88,17 +67,64 @@ where
                 .decode()
                 .map_err(<error::PngDecodeErrors as Into<ImageErrors>>::into)?;
 
-            let mut image = match pixels {
-                DecodingResult::U8(data) => Image::from_u8(&data, width, height, colorspace),
-                DecodingResult::U16(data) => Image::from_u16(&data, width, height, colorspace),
+            let mut image = match (pixels, layout) {
+                (DecodingResult::U8(data), PixelLayout::Planar) => {
+                    Image::from_u8(&data, width, height, colorspace)
+                }
+                (DecodingResult::U16(data), PixelLayout::Planar) => {
+                    Image::from_u16(&data, width, height, colorspace)
+                }
+                (DecodingResult::U8(data), PixelLayout::Interleaved) => {
+                    Image::from_interleaved(&data, width, height, colorspace)
+                }
+                (DecodingResult::U16(data), PixelLayout::Interleaved) => {
+                    Image::from_interleaved(&data, width, height, colorspace)
+                }
                 _ => unreachable!()
             };
             // metadata
             image.metadata = metadata;
+            image.metadata.set_layout(layout);
 
             Ok(image)
         }
     }
+
+    fn decode_into_image(&mut self, image: &mut Image) -> Result<(), ImageErrors> {
+        let metadata = self.read_headers()?.unwrap();
+
+        if self.is_animated() && self.options().png_decode_animated() {
+            *image = self.decode_with_layout(image.layout())?;
+            return Ok(());
+        }
+        let (width, height) = self.dimensions().unwrap();
+        let colorspace = self.colorspace().unwrap();
+        let layout = image.layout();
+
+        // samples are written in native endian, as decode does
+        let endian = if cfg!(target_endian = "little") { ByteEndian::LE } else { ByteEndian::BE };
+        self.set_options(self.options().set_byte_endian(endian));
+
+        // 16 bit images stripped to 8 bits are decoded as u8
+        let depth = match self.output_buffer_size() {
+            Some(size) if size == width * height * colorspace.num_components() => BitDepth::Eight,
+            _ => BitDepth::Sixteen
+        };
+        let decode = |out: &mut [u8]| {
+            self.decode_into(out)
+                .map_err(<PngDecodeErrors as Into<ImageErrors>>::into)
+        };
+        if depth == BitDepth::Eight {
+            decode_frame_into::<u8>(image, width, height, colorspace, decode)?;
+        } else {
+            decode_frame_into::<u16>(image, width, height, colorspace, decode)?;
+        }
+        image.metadata = metadata;
+        image.metadata.set_depth(depth);
+        image.metadata.set_layout(layout);
+
+        Ok(())
+    }
     fn dimensions(&self) -> Option<(usize, usize)> {
         self.dimensions()
     }
@@ -135,20 +161,72 @@ where
                 metadata.parse_raw_exif(exif)
             }
         }
+        if let Some(xmp) = self.info().unwrap().xmp() {
+            metadata.set_xmp(String::from_utf8_lossy(xmp).into_owned());
+        }
+        if let Some(trc) = self.color_trc() {
+            metadata.set_color_trc(trc);
+        }
+        if let Some(primaries) = self
+            .info()
+            .unwrap()
+            .cicp
+            .and_then(|cicp| RgbPrimaries::from_h273(cicp[0]))
+        {
+            metadata.set_primaries(primaries);
+        }
         // load icc
         if let Some(icc) = &self.info().unwrap().icc_profile {
             metadata.set_icc_chunk(icc.to_owned());
         }
+        if let Some(plays) = self.num_plays() {
+            metadata.set_loop_count(plays);
+        }
+        if let Some(physical) = self.info().unwrap().physical_dimensions {
+            let resolution = if physical.unit_is_meter {
+                // stored as pixels per meter
+                let (x, y) = (physical.x as f32 / 100.0, physical.y as f32 / 100.0);
+                Resolution::new(x, y, ResolutionUnit::Centimeter)
+            } else {
+                Resolution::new(physical.x as f32, physical.y as f32, ResolutionUnit::None)
+            };
+            metadata.set_resolution(resolution);
+        }
 
         Ok(Some(metadata))
     }
-}
 
-impl From<zune_png::error::PngDecodeErrors> for ImageErrors {
-    fn from(from: zune_png::error::PngDecodeErrors) -> Self {
-        let err = format!("png: {from:?}");
+    fn next_frame(&mut self) -> Result<Option<Frame>, ImageErrors> {
+        self.decode_headers()?;
+        let colorspace = self.colorspace().unwrap();
 
-        ImageErrors::ImageDecodeErrors(err)
+        match self.decode_composed_frame()? {
+            Some((info, pixels)) => Ok(Some(Frame::from_u8(
+                pixels,
+                colorspace,
+                usize::from(info.delay_num),
+                usize::from(info.delay_denom)
+            ))),
+            None => Ok(None)
+        }
+    }
+
+    fn supports_frame_streaming(&self) -> bool {
+        self.is_animated() && self.options().png_decode_animated()
+    }
+}
+
+impl From<PngDecodeErrors> for ImageErrors {
+    fn from(from: PngDecodeErrors) -> Self {
+        let kind = match &from {
+            PngDecodeErrors::UnsupportedAPNGImage => ErrorKind::Unsupported,
+            PngDecodeErrors::TooSmallOutput(..) => ErrorKind::InvalidInput,
+            PngDecodeErrors::IoErrors(err) => io_error_kind(err),
+            _ => ErrorKind::Corrupt
+        };
+        DecodeError::new(ImageFormat::PNG, kind, format!("{from:?}"))
+            .set_source(from)
+            .into()
     }
 }
 
@@ -176,7 +254,8 @@ impl EncoderTrait for PngEncoder {
     fn encode_inner<T: ZByteWriterTrait>(
         &mut self, image: &Image, sink: T
     ) -> Result<usize, ImageErrors> {
-        let options = create_options_for_encoder(self.options, image);
+        // the encoder filters and compresses bands of rows on threads of its own
+        let options = limit_encoder_threads(create_options_for_encoder(self.options, image));
 
         let frame = &image.to_u8_be()[0];
 
@@ -205,6 +284,26 @@ impl EncoderTrait for PngEncoder {
                 }
             }
         }
+        // the profile is needed to display colors correctly, so it isn't stripped
+        if let Some(icc) = image.metadata.icc_chunk_for(image.colorspace()) {
+            encoder.add_icc_profile(icc);
+        }
+        if let Some(resolution) = image.metadata.resolution() {
+            let (x, y, unit_is_meter) = match resolution.pixels_per_meter() {
+                Some((x, y)) => (x, y, true),
+                None => (resolution.x, resolution.y, false)
+            };
+            encoder.set_physical_dimensions(PhysicalDimensions {
+                x: x.round() as u32,
+                y: y.round() as u32,
+                unit_is_meter
+            });
+        }
+        if !options.strip_metadata() {
+            if let Some(xmp) = image.metadata.xmp() {
+                encoder.add_xmp_segment(xmp.as_bytes());
+            }
+        }
         encoder
             .encode(sink)
             .map_err(|e| ImageErrors::EncodeErrors(ImageEncodeErrors(format!("{:?}", e))))
@@ -263,7 +362,7 @@ where
 #[cfg(test)]
 mod tests {
     use zune_core::bytestream::ZCursor;
-    use zune_core::colorspace::ColorSpace;
+    use zune_core::colorspace::{ColorSpace, RgbPrimaries};
     use zune_png::PngDecoder;
 
     use crate::codecs::png::PngEncoder;
@@ -15,10 +15,10 @@ use zune_core::options::EncoderOptions;
 use zune_core::result::DecodingResult;
 pub use zune_ppm::{PPMDecodeErrors, PPMDecoder, PPMEncodeErrors, PPMEncoder as PPMEnc};
 
-use crate::codecs::{create_options_for_encoder, ImageFormat};
-use crate::errors::{ImageErrors, ImgEncodeErrors};
+use crate::codecs::{create_options_for_encoder, io_error_kind, ImageFormat};
+use crate::errors::{DecodeError, ErrorKind, ImageErrors, ImgEncodeErrors};
 use crate::image::Image;
-use crate::metadata::ImageMetadata;
+use crate::metadata::{ImageMetadata, PixelLayout};
 use crate::traits::{DecoderTrait, EncoderTrait};
 
 #[derive(Copy, Clone, Default)]
@@ -94,16 +94,35 @@ where
     T: ZByteReaderTrait
 {
     fn decode(&mut self) -> Result<Image, ImageErrors> {
+        self.decode_with_layout(PixelLayout::Planar)
+    }
+
+    fn decode_with_layout(&mut self, layout: PixelLayout) -> Result<Image, ImageErrors> {
         let pixels = self.decode()?;
 
         let depth = self.bit_depth().unwrap();
         let (width, height) = self.dimensions().unwrap();
         let colorspace = self.colorspace().unwrap();
 
-        let mut image = match pixels {
-            DecodingResult::U8(data) => Image::from_u8(&data, width, height, colorspace),
-            DecodingResult::U16(data) => Image::from_u16(&data, width, height, colorspace),
-            DecodingResult::F32(data) => Image::from_f32(&data, width, height, colorspace),
+        let mut image = match (pixels, layout) {
+            (DecodingResult::U8(data), PixelLayout::Planar) => {
+                Image::from_u8(&data, width, height, colorspace)
+            }
+            (DecodingResult::U16(data), PixelLayout::Planar) => {
+                Image::from_u16(&data, width, height, colorspace)
+            }
+            (DecodingResult::F32(data), PixelLayout::Planar) => {
+                Image::from_f32(&data, width, height, colorspace)
+            }
+            (DecodingResult::U8(data), PixelLayout::Interleaved) => {
+                Image::from_interleaved(&data, width, height, colorspace)
+            }
+            (DecodingResult::U16(data), PixelLayout::Interleaved) => {
+                Image::from_interleaved(&data, width, height, colorspace)
+            }
+            (DecodingResult::F32(data), PixelLayout::Interleaved) => {
+                Image::from_interleaved(&data, width, height, colorspace)
+            }
             _ => unreachable!()
         };
 
@@ -146,11 +165,17 @@ where
 }
 
 #[cfg(feature = "ppm")]
-impl From<zune_ppm::PPMDecodeErrors> for ImageErrors {
-    fn from(from: zune_ppm::PPMDecodeErrors) -> Self {
-        let err = format!("ppm: {from:?}");
-
-        ImageErrors::ImageDecodeErrors(err)
+impl From<PPMDecodeErrors> for ImageErrors {
+    fn from(from: PPMDecodeErrors) -> Self {
+        let kind = match &from {
+            PPMDecodeErrors::UnsupportedImpl(_) => ErrorKind::Unsupported,
+            PPMDecodeErrors::LargeDimensions(..) => ErrorKind::LimitExceeded,
+            PPMDecodeErrors::IoErrors(err) => io_error_kind(err),
+            _ => ErrorKind::Corrupt
+        };
+        DecodeError::new(ImageFormat::PPM, kind, format!("{from:?}"))
+            .set_source(from)
+            .into()
     }
 }
 
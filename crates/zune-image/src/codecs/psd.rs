@@ -16,12 +16,13 @@
 use zune_core::bytestream::ZByteReaderTrait;
 use zune_core::colorspace::ColorSpace;
 use zune_core::result::DecodingResult;
+use zune_psd::errors::PSDDecodeErrors;
 pub use zune_psd::*;
 
-use crate::codecs::ImageFormat;
-use crate::errors::ImageErrors;
+use crate::codecs::{io_error_kind, ImageFormat};
+use crate::errors::{DecodeError, ErrorKind, ImageErrors};
 use crate::image::Image;
-use crate::metadata::ImageMetadata;
+use crate::metadata::{ImageMetadata, PixelLayout};
 use crate::traits::DecoderTrait;
 
 impl<T> DecoderTrait for PSDDecoder<T>
@@ -29,15 +30,29 @@ where
     T: ZByteReaderTrait
 {
     fn decode(&mut self) -> Result<Image, ImageErrors> {
+        self.decode_with_layout(PixelLayout::Planar)
+    }
+
+    fn decode_with_layout(&mut self, layout: PixelLayout) -> Result<Image, ImageErrors> {
         let pixels = self.decode()?;
 
         let depth = self.bit_depth().unwrap();
         let (width, height) = self.dimensions().unwrap();
         let colorspace = self.colorspace().unwrap();
 
-        let mut image = match pixels {
-            DecodingResult::U8(data) => Image::from_u8(&data, width, height, colorspace),
-            DecodingResult::U16(data) => Image::from_u16(&data, width, height, colorspace),
+        let mut image = match (pixels, layout) {
+            (DecodingResult::U8(data), PixelLayout::Planar) => {
+                Image::from_u8(&data, width, height, colorspace)
+            }
+            (DecodingResult::U16(data), PixelLayout::Planar) => {
+                Image::from_u16(&data, width, height, colorspace)
+            }
+            (DecodingResult::U8(data), PixelLayout::Interleaved) => {
+                Image::from_interleaved(&data, width, height, colorspace)
+            }
+            (DecodingResult::U16(data), PixelLayout::Interleaved) => {
+                Image::from_interleaved(&data, width, height, colorspace)
+            }
             _ => unreachable!()
         };
         // set metadata details
@@ -82,10 +97,20 @@ where
     }
 }
 
-impl From<zune_psd::errors::PSDDecodeErrors> for ImageErrors {
-    fn from(error: zune_psd::errors::PSDDecodeErrors) -> Self {
-        let err = format!("psd: {error:?}");
-
-        ImageErrors::ImageDecodeErrors(err)
+impl From<PSDDecodeErrors> for ImageErrors {
+    fn from(error: PSDDecodeErrors) -> Self {
+        let kind = match &error {
+            PSDDecodeErrors::UnsupportedFileType(_)
+            | PSDDecodeErrors::UnsupportedChannelCount(_)
+            | PSDDecodeErrors::UnsupportedBitDepth(_)
+            | PSDDecodeErrors::UnsupportedColorFormat(_)
+            | PSDDecodeErrors::UnknownCompression => ErrorKind::Unsupported,
+            PSDDecodeErrors::LargeDimensions(..) => ErrorKind::LimitExceeded,
+            PSDDecodeErrors::IoErrors(err) => io_error_kind(err),
+            _ => ErrorKind::Corrupt
+        };
+        DecodeError::new(ImageFormat::PSD, kind, format!("{error:?}"))
+            .set_source(error)
+            .into()
     }
 }
@@ -18,10 +18,10 @@ use zune_core::colorspace::ColorSpace;
 use zune_core::options::EncoderOptions;
 pub use zune_qoi::*;
 
-use crate::codecs::{create_options_for_encoder, ImageFormat};
-use crate::errors::{ImageErrors, ImgEncodeErrors};
+use crate::codecs::{create_options_for_encoder, io_error_kind, ImageFormat};
+use crate::errors::{DecodeError, ErrorKind, ImageErrors, ImgEncodeErrors};
 use crate::image::Image;
-use crate::metadata::ImageMetadata;
+use crate::metadata::{ImageMetadata, PixelLayout};
 use crate::traits::{DecodeInto, DecoderTrait, EncoderTrait};
 
 impl<T> DecoderTrait for QoiDecoder<T>
@@ -29,6 +29,10 @@ where
     T: ZByteReaderTrait
 {
     fn decode(&mut self) -> Result<Image, ImageErrors> {
+        self.decode_with_layout(PixelLayout::Planar)
+    }
+
+    fn decode_with_layout(&mut self, layout: PixelLayout) -> Result<Image, ImageErrors> {
         let pixels = self.decode()?;
         // safe because these are none when we haven't decoded.
         let colorspace = self.colorspace().unwrap();
@@ -36,7 +40,10 @@ where
 
         let depth = self.bit_depth();
 
-        let mut image = Image::from_u8(&pixels, width, height, colorspace);
+        let mut image = match layout {
+            PixelLayout::Planar => Image::from_u8(&pixels, width, height, colorspace),
+            PixelLayout::Interleaved => Image::from_interleaved(&pixels, width, height, colorspace)
+        };
 
         // set metadata details
         image.metadata.format = Some(ImageFormat::QOI);
@@ -148,11 +155,16 @@ impl EncoderTrait for QoiEncoder {
     }
 }
 
-impl From<zune_qoi::QoiErrors> for ImageErrors {
-    fn from(error: zune_qoi::QoiErrors) -> Self {
-        let err = format!("qoi: {error:?}");
-
-        ImageErrors::ImageDecodeErrors(err)
+impl From<QoiErrors> for ImageErrors {
+    fn from(error: QoiErrors) -> Self {
+        let kind = match &error {
+            QoiErrors::TooSmallOutput(..) => ErrorKind::InvalidInput,
+            QoiErrors::IoErrors(err) => io_error_kind(err),
+            _ => ErrorKind::Corrupt
+        };
+        DecodeError::new(ImageFormat::QOI, kind, format!("{error:?}"))
+            .set_source(error)
+            .into()
     }
 }
 
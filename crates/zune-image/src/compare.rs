@@ -0,0 +1,217 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Fuzzy image comparison
+//!
+//! [`Image::compare`] tells how far two images are from each other, which is what golden
+//! image tests need, since lossy codecs and platform specific floating point make exact
+//! comparisons too strict.
+//!
+//! # Harmonization
+//! Images are compared in the colorspace of the image `compare` is called on, the other
+//! image is converted to it if they differ. Both images are then converted to
+//! [`BitDepth::Float32`] so deltas go from 0.0 to 1.0 whatever the bit depth of the images.
+//!
+//! All frames are compared, both images need the same dimensions and number of frames.
+//!
+//! See `zune_imageprocs::metrics` for perceptual metrics like PSNR and SSIM.
+//!
+//! # Example
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//!
+//! let golden = Image::fill(100_u8, ColorSpace::RGB, 8, 8);
+//! let mut output = golden.clone();
+//! output.modify_pixels_mut(|_, _, pix: [&mut u8; 4]| *pix[0] += 1).unwrap();
+//!
+//! // one step out of 255 in the red channel
+//! let diff = golden.compare(&output, 2.0 / 255.0).unwrap();
+//! assert!(diff.is_within_tolerance());
+//! assert!(diff.max_delta[0] > 0.0 && diff.max_delta[1] == 0.0);
+//!
+//! let diff = golden.compare(&output, 0.0).unwrap();
+//! assert_eq!(diff.differing_pixels, 64);
+//! assert!(diff.difference.is_some());
+//! ```
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use zune_core::bit_depth::BitDepth;
+
+use crate::channel::Channel;
+use crate::errors::ImageErrors;
+use crate::image::Image;
+use crate::metadata::PixelLayout;
+
+/// The result of comparing two images
+#[derive(Clone)]
+pub struct ImageDiff {
+    /// Largest absolute difference of each channel, from 0.0 to 1.0
+    pub max_delta:        Vec<f32>,
+    /// Mean absolute difference of each channel, from 0.0 to 1.0
+    pub mean_delta:       Vec<f32>,
+    /// Number of pixels where a channel differs by more than the tolerance,
+    /// counted over all frames
+    pub differing_pixels: usize,
+    /// Number of pixels compared, counted over all frames
+    pub total_pixels:     usize,
+    /// The absolute difference of every channel, in the colorspace and depth of
+    /// the image `compare` was called on
+    ///
+    /// This is `None` if all pixels are within the tolerance
+    pub difference:       Option<Image>
+}
+
+impl ImageDiff {
+    /// Return true if no pixel differs by more than the tolerance
+    pub const fn is_within_tolerance(&self) -> bool {
+        self.differing_pixels == 0
+    }
+    /// Largest absolute difference over all channels, from 0.0 to 1.0
+    pub fn max(&self) -> f32 {
+        self.max_delta.iter().copied().fold(0.0, f32::max)
+    }
+}
+
+impl Image {
+    /// Compare this image against another image
+    ///
+    /// See the [module docs](crate::compare) for how images of different
+    /// depths and colorspaces are compared
+    ///
+    /// # Arguments
+    /// - other: The image to compare against
+    /// - tolerance: How much a channel can differ before a pixel counts as different,
+    ///   from 0.0 to 1.0 of the range of the channel
+    ///
+    /// # Errors
+    /// If the dimensions or number of frames differ, or the other image can't be
+    /// converted to the colorspace of this image
+    pub fn compare(&self, other: &Image, tolerance: f32) -> Result<ImageDiff, ImageErrors> {
+        if self.dimensions() != other.dimensions() || self.frames_len() != other.frames_len() {
+            return Err(ImageErrors::GenericString(format!(
+                "Cannot compare a {:?} image with {} frames to a {:?} image with {} frames",
+                self.dimensions(),
+                self.frames_len(),
+                other.dimensions(),
+                other.frames_len()
+            )));
+        }
+        let harmonize = |image: &Image| -> Result<Image, ImageErrors> {
+            let mut image = image.clone();
+
+            if image.colorspace() != self.colorspace() {
+                image.convert_color(self.colorspace())?;
+            }
+            image.convert_depth(BitDepth::Float32)?;
+            image.convert_layout(PixelLayout::Planar)?;
+            Ok(image)
+        };
+        let (reference, other) = (harmonize(self)?, harmonize(other)?);
+
+        let components = self.colorspace().num_components();
+        let mut max_delta = vec![0.0_f32; components];
+        let mut sum_delta = vec![0.0_f64; components];
+
+        let mut differing_pixels = 0;
+        let mut total_pixels = 0;
+        let mut frames = vec![];
+
+        for (frame, other_frame) in reference.frames_ref().iter().zip(other.frames_ref()) {
+            let mut channels = vec![];
+
+            for (channel, other_channel) in frame
+                .channels_vec_ref()
+                .iter()
+                .zip(other_frame.channels_vec_ref())
+                .take(components)
+            {
+                let (a, b) = (
+                    channel.reinterpret_as::<f32>()?,
+                    other_channel.reinterpret_as::<f32>()?
+                );
+                let mut delta = Channel::new_with_length::<f32>(size_of_val(a));
+                for ((out, a), b) in delta.reinterpret_as_mut::<f32>()?.iter_mut().zip(a).zip(b) {
+                    *out = (a - b).abs();
+                }
+                channels.push(delta);
+            }
+            let deltas = channels
+                .iter()
+                .map(|x| x.reinterpret_as::<f32>())
+                .collect::<Result<Vec<_>, _>>()?;
+            let pixels = deltas.first().map_or(0, |x| x.len());
+
+            for (i, delta) in deltas.iter().enumerate() {
+                max_delta[i] = delta.iter().copied().fold(max_delta[i], f32::max);
+                sum_delta[i] += delta.iter().map(|x| f64::from(*x)).sum::<f64>();
+            }
+            differing_pixels += (0..pixels)
+                .filter(|x| deltas.iter().any(|delta| delta[*x] > tolerance))
+                .count();
+            total_pixels += pixels;
+
+            frames.push(frame.with_channels(channels));
+        }
+        let mean_delta = sum_delta
+            .iter()
+            .map(|x| (x / total_pixels.max(1) as f64) as f32)
+            .collect();
+
+        let mut difference = None;
+
+        if differing_pixels > 0 {
+            let (width, height) = self.dimensions();
+            let mut image =
+                Image::new_frames(frames, BitDepth::Float32, width, height, self.colorspace());
+            image.convert_depth(self.depth())?;
+            difference = Some(image);
+        }
+        Ok(ImageDiff {
+            max_delta,
+            mean_delta,
+            differing_pixels,
+            total_pixels,
+            difference
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::bit_depth::BitDepth;
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::image::Image;
+
+    #[test]
+    fn test_compare_harmonizes() {
+        let a = Image::fill(255_u8, ColorSpace::RGB, 4, 4);
+        let mut b = Image::fill(65535_u16, ColorSpace::RGBA, 4, 4);
+        b.modify_pixels_mut(|y, x, pix: [&mut u16; 4]| {
+            if (x, y) == (1, 2) {
+                *pix[2] = 0;
+            }
+        })
+        .unwrap();
+
+        let diff = a.compare(&b, 0.5).unwrap();
+        assert_eq!(diff.max_delta, [0.0, 0.0, 1.0]);
+        assert_eq!(diff.mean_delta[2], 1.0 / 16.0);
+        assert_eq!((diff.differing_pixels, diff.total_pixels), (1, 16));
+
+        let difference = diff.difference.unwrap();
+        assert_eq!(difference.depth(), BitDepth::Eight);
+        assert_eq!(difference.pixel_at::<u8>(1, 2).unwrap(), [0, 0, 255, 0]);
+        assert_eq!(difference.pixel_at::<u8>(0, 0).unwrap(), [0, 0, 0, 0]);
+
+        assert!(a
+            .compare(&Image::fill(0_u8, ColorSpace::RGB, 4, 5), 0.0)
+            .is_err());
+    }
+}
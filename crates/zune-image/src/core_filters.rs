@@ -9,5 +9,8 @@
 //!
 //! Core filters are filters needed for simple
 //! running of images
+pub(crate) mod alpha;
 pub mod colorspace;
 pub mod depth;
+pub(crate) mod layout;
+pub(crate) mod orientation;
@@ -0,0 +1,66 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Alpha state conversions used by core operations
+//!
+//! Colorspace conversions and encoders expect straight alpha, these routines
+//! convert images tagged as premultiplied before handing them over.
+//! `zune-imageprocs` has a faster premultiply operation for general use.
+use zune_core::bit_depth::BitDepth;
+
+use crate::core_filters::depth::{Depth, Dither};
+use crate::core_filters::layout::convert_layout;
+use crate::errors::ImageErrors;
+use crate::image::Image;
+use crate::metadata::{AlphaState, PixelLayout};
+use crate::traits::OperationsTrait;
+
+/// Convert the image to the alpha state `to`, updating its metadata
+///
+/// Does nothing if the image has no alpha channel or already is in that state
+pub(crate) fn convert_alpha_state(image: &mut Image, to: AlphaState) -> Result<(), ImageErrors> {
+    let colorspace = image.colorspace();
+
+    if !colorspace.has_alpha() || image.metadata.alpha() == to {
+        image.metadata.set_alpha(to);
+        return Ok(());
+    }
+    // premultiplying needs the alpha channel on its own
+    convert_layout(image, PixelLayout::Planar)?;
+
+    let depth = image.depth();
+
+    if depth != BitDepth::Float32 {
+        image.convert_depth(BitDepth::Float32)?;
+    }
+    for frame in image.frames_mut() {
+        let (colors, alpha) = frame
+            .separate_color_and_alpha_mut(colorspace)
+            .ok_or(ImageErrors::GenericStr("Could not separate alpha channel"))?;
+        let alpha = alpha.reinterpret_as::<f32>()?;
+
+        for channel in colors {
+            let channel = channel.reinterpret_as_mut::<f32>()?;
+
+            for (color, a) in channel.iter_mut().zip(alpha) {
+                *color = match to {
+                    AlphaState::PreMultiplied => *color * a,
+                    AlphaState::NonPreMultiplied if *a > 0.0 => (*color / a).min(1.0),
+                    AlphaState::NonPreMultiplied => 0.0
+                };
+            }
+        }
+    }
+    if depth != BitDepth::Float32 {
+        Depth::new(depth)
+            .set_dither(Dither::Round)
+            .execute_impl(image)?;
+    }
+    image.metadata.set_alpha(to);
+
+    Ok(())
+}
@@ -17,27 +17,58 @@
 //! the library converts the image to an intermediate colorspace(most of the time this is RGB or RGBA)
 //! and then from the intermediate color converts it to the desired colorspace
 //!
+//! ## YCCK
+//! Adobe YCCK images are converted to CMYK first, undoing the inversion of the YCC
+//! channels, and from CMYK to the desired colorspace
+//!
+//! ## Multi-band images
+//! [`ColorSpace::MultiBand`] images are converted with functions registered in
+//! [`registry`], chained with the conversions above when needed. The registry needs the
+//! `std` feature
+//!
+use alloc::format;
+
 use zune_core::bit_depth::BitType;
-use zune_core::colorspace::{ColorSpace, ALL_COLORSPACES};
+use zune_core::colorspace::{
+    ChromaticAdaptation, ColorSpace, LumaCoefficients, WhitePoint, ALL_COLORSPACES
+};
 
+use crate::core_filters::alpha::convert_alpha_state;
 use crate::core_filters::colorspace::conversion_functions::{
-    convert_adding_opaque_alpha, convert_cmyk_to_rgb, convert_hsl_to_rgb, convert_hsv_to_rgb,
-    convert_luma_to_rgb, convert_rgb_bgr, convert_rgb_to_argb, convert_rgb_to_cmyk,
-    convert_rgb_to_grayscale, convert_rgb_to_hsl, convert_rgb_to_hsv,
-    convert_rgba_to_argb_or_vice_versa, pop_channel
+    convert_adding_opaque_alpha, convert_cmyk_to_rgb, convert_cmyk_to_ycck, convert_hsl_to_rgb,
+    convert_hsv_to_rgb, convert_lab_to_rgb, convert_lch_to_rgb, convert_luma_to_rgb,
+    convert_oklab_to_rgb, convert_oklch_to_rgb, convert_rgb_bgr, convert_rgb_to_argb,
+    convert_rgb_to_cmyk, convert_rgb_to_grayscale, convert_rgb_to_hsl, convert_rgb_to_hsv,
+    convert_rgb_to_lab, convert_rgb_to_lch, convert_rgb_to_oklab, convert_rgb_to_oklch,
+    convert_rgb_to_xyz, convert_rgb_to_ycbcr, convert_rgba_to_argb_or_vice_versa,
+    convert_xyz_to_rgb, convert_xyz_white_point, convert_ycbcr_to_rgb, convert_ycck_to_cmyk,
+    pop_channel
+};
+#[cfg(feature = "std")]
+use crate::core_filters::colorspace::registry::{
+    find_conversion_path, run_registered, ConversionStep
 };
 use crate::errors::ImageErrors;
 use crate::image::Image;
+use crate::metadata::AlphaState;
 use crate::traits::OperationsTrait;
 
 mod grayscale;
 //mod rgb_to_hsl;
 mod rgb_to_xyb;
 
+pub mod adaptation;
 mod conversion_functions;
+pub mod oklab;
+pub mod primaries;
+#[cfg(feature = "std")]
+pub mod registry;
 mod rgb_to_cmyk;
 mod rgb_to_hsl;
 mod rgb_to_hsv;
+mod rgb_to_lab;
+mod rgb_to_xyz;
+mod rgb_to_ycbcr;
 mod tests;
 
 /// Colorspace conversion filter
@@ -48,13 +79,123 @@ mod tests;
 ///
 /// This filter can also be accessed via
 /// [`image.convert_color()`](crate::image::Image::convert_color)
+#[derive(Copy, Clone)]
 pub struct ColorspaceConv {
-    to: ColorSpace
+    to:                ColorSpace,
+    white_point:       Option<WhitePoint>,
+    luma:              LumaCoefficients,
+    linear_light_luma: bool,
+    adaptation:        ChromaticAdaptation
 }
 
 impl ColorspaceConv {
     pub fn new(to: ColorSpace) -> ColorspaceConv {
-        ColorspaceConv { to }
+        ColorspaceConv {
+            to,
+            white_point: None,
+            luma: LumaCoefficients::default(),
+            linear_light_luma: false,
+            adaptation: ChromaticAdaptation::default()
+        }
+    }
+    /// Set the coefficients used when converting RGB to [`ColorSpace::Luma`]
+    ///
+    /// The default is [`LumaCoefficients::BT601`]
+    ///
+    /// # Example
+    /// ```
+    /// use zune_core::colorspace::{ColorSpace, LumaCoefficients};
+    /// use zune_image::core_filters::colorspace::ColorspaceConv;
+    /// use zune_image::image::Image;
+    /// use zune_image::traits::OperationsTrait;
+    ///
+    /// let mut image = Image::fill(128_u8, ColorSpace::RGB, 10, 10);
+    ///
+    /// ColorspaceConv::new(ColorSpace::Luma)
+    ///     .set_luma_coefficients(LumaCoefficients::BT709)
+    ///     .execute(&mut image)
+    ///     .unwrap();
+    /// ```
+    #[must_use]
+    pub fn set_luma_coefficients(mut self, luma: LumaCoefficients) -> Self {
+        self.luma = luma;
+        self
+    }
+    /// Weight the channels in linear light when converting RGB to [`ColorSpace::Luma`]
+    ///
+    /// sRGB encoded channels are linearized before weighting and the luma is encoded
+    /// again, this gives the true relative luminance of a pixel, the default weights the
+    /// encoded values directly.
+    #[must_use]
+    pub fn set_linear_light_luma(mut self, linear_light: bool) -> Self {
+        self.linear_light_luma = linear_light;
+        self
+    }
+    /// Set the reference white used when converting to [`ColorSpace::XYZ`]
+    ///
+    /// By default the reference white of the image is used, converting an XYZ image
+    /// to XYZ with a different white point adapts it to the new white point.
+    ///
+    /// # Example
+    /// ```
+    /// use zune_core::colorspace::{ColorSpace, WhitePoint};
+    /// use zune_image::core_filters::colorspace::ColorspaceConv;
+    /// use zune_image::image::Image;
+    /// use zune_image::traits::OperationsTrait;
+    ///
+    /// let mut image = Image::fill(128_u8, ColorSpace::RGB, 10, 10);
+    ///
+    /// ColorspaceConv::new(ColorSpace::XYZ)
+    ///     .set_white_point(WhitePoint::D50)
+    ///     .execute(&mut image)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(image.metadata().white_point(), WhitePoint::D50);
+    /// ```
+    #[must_use]
+    pub fn set_white_point(mut self, white_point: WhitePoint) -> Self {
+        self.white_point = Some(white_point);
+        self
+    }
+    /// Set the chromatic adaptation transform used when converting between
+    /// white points
+    ///
+    /// The default is [`ChromaticAdaptation::Bradford`]
+    #[must_use]
+    pub fn set_adaptation(mut self, adaptation: ChromaticAdaptation) -> Self {
+        self.adaptation = adaptation;
+        self
+    }
+    /// Convert from or to a [`ColorSpace::MultiBand`] colorspace with the
+    /// registered conversions
+    #[cfg(feature = "std")]
+    fn convert_registered(&self, image: &mut Image, from: ColorSpace) -> Result<(), ImageErrors> {
+        let Some(path) = find_conversion_path(from, self.to) else {
+            let msg = format!(
+                "No conversion from {from:?} to {:?}, multi-band images do not have a concrete colorspace, register a conversion for them or reinterpret the image yourself",
+                self.to
+            );
+            return Err(ImageErrors::GenericString(msg));
+        };
+        for step in path {
+            match step {
+                ConversionStep::Registered(from, to) => {
+                    run_registered(image, from, to)?;
+                }
+                ConversionStep::Builtin(_, to) => {
+                    ColorspaceConv { to, ..*self }.execute_impl(image)?;
+                }
+            }
+        }
+        Ok(())
+    }
+    #[cfg(not(feature = "std"))]
+    fn convert_registered(&self, _: &mut Image, from: ColorSpace) -> Result<(), ImageErrors> {
+        let msg = format!(
+            "No conversion from {from:?} to {:?}, registering conversions for multi-band images needs the `std` feature",
+            self.to
+        );
+        Err(ImageErrors::GenericString(msg))
     }
 }
 impl OperationsTrait for ColorspaceConv {
@@ -65,22 +206,59 @@ impl OperationsTrait for ColorspaceConv {
     fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
         let from = image.colorspace();
 
+        let white_point = self.white_point.unwrap_or(image.metadata.white_point());
+
         // colorspace matches
         if from == self.to {
+            if from == ColorSpace::XYZ {
+                convert_xyz_white_point(image, white_point, self.adaptation)?;
+            }
             return Ok(());
         }
+        if matches!(from, ColorSpace::MultiBand(_)) || matches!(self.to, ColorSpace::MultiBand(_)) {
+            return self.convert_registered(image, from);
+        }
+        // conversions work on straight colors
+        let premultiplied = from.has_alpha() && image.metadata.is_premultiplied_alpha();
+
+        if premultiplied {
+            convert_alpha_state(image, AlphaState::NonPreMultiplied)?;
+        }
 
         match from {
             ColorSpace::RGB => match self.to {
                 ColorSpace::RGBA => convert_adding_opaque_alpha(image)?,
-                ColorSpace::Luma => convert_rgb_to_grayscale(image, self.to, self.to.has_alpha())?,
-                ColorSpace::LumaA => convert_rgb_to_grayscale(image, self.to, self.to.has_alpha())?,
+                ColorSpace::Luma => convert_rgb_to_grayscale(
+                    image,
+                    self.to,
+                    self.to.has_alpha(),
+                    self.luma,
+                    self.linear_light_luma
+                )?,
+                ColorSpace::LumaA => convert_rgb_to_grayscale(
+                    image,
+                    self.to,
+                    self.to.has_alpha(),
+                    self.luma,
+                    self.linear_light_luma
+                )?,
                 ColorSpace::CMYK => convert_rgb_to_cmyk(image)?,
+                ColorSpace::YCCK => {
+                    convert_rgb_to_cmyk(image)?;
+                    image.set_colorspace(ColorSpace::CMYK);
+                    convert_cmyk_to_ycck(image)?;
+                }
                 ColorSpace::BGR => convert_rgb_bgr(from, self.to, image)?,
                 ColorSpace::BGRA => convert_rgb_bgr(from, self.to, image)?,
                 ColorSpace::ARGB => convert_rgb_to_argb(image)?,
                 ColorSpace::HSL => convert_rgb_to_hsl(image)?,
                 ColorSpace::HSV => convert_rgb_to_hsv(image)?,
+                ColorSpace::Lab => convert_rgb_to_lab(image)?,
+                ColorSpace::LCh => convert_rgb_to_lch(image)?,
+                ColorSpace::OKLab => convert_rgb_to_oklab(image)?,
+                ColorSpace::OKLCh => convert_rgb_to_oklch(image)?,
+                ColorSpace::XYZ => convert_rgb_to_xyz(image, white_point, self.adaptation)?,
+                ColorSpace::YCbCr => convert_rgb_to_ycbcr(image)?,
                 color => {
                     let msg = format!("Unsupported/unknown mapping from RGB to {color:?}");
                     return Err(ImageErrors::GenericString(msg));
@@ -91,15 +269,39 @@ impl OperationsTrait for ColorspaceConv {
                 ColorSpace::BGR => convert_rgb_bgr(from, self.to, image)?,
                 ColorSpace::BGRA => convert_rgb_bgr(from, self.to, image)?,
                 ColorSpace::ARGB => convert_rgba_to_argb_or_vice_versa(image)?,
-                ColorSpace::LumaA => convert_rgb_to_grayscale(image, self.to, self.to.has_alpha())?,
-                ColorSpace::Luma => convert_rgb_to_grayscale(image, self.to, self.to.has_alpha())?,
+                ColorSpace::LumaA => convert_rgb_to_grayscale(
+                    image,
+                    self.to,
+                    self.to.has_alpha(),
+                    self.luma,
+                    self.linear_light_luma
+                )?,
+                ColorSpace::Luma => convert_rgb_to_grayscale(
+                    image,
+                    self.to,
+                    self.to.has_alpha(),
+                    self.luma,
+                    self.linear_light_luma
+                )?,
                 ColorSpace::HSV => convert_rgb_to_hsv(image)?,
                 ColorSpace::HSL => convert_rgb_to_hsl(image)?,
+                ColorSpace::Lab => convert_rgb_to_lab(image)?,
+                ColorSpace::LCh => convert_rgb_to_lch(image)?,
+                ColorSpace::OKLab => convert_rgb_to_oklab(image)?,
+                ColorSpace::OKLCh => convert_rgb_to_oklch(image)?,
+                ColorSpace::XYZ => convert_rgb_to_xyz(image, white_point, self.adaptation)?,
+                ColorSpace::YCbCr => convert_rgb_to_ycbcr(image)?,
                 ColorSpace::CMYK => {
                     // drop alpha
                     pop_channel(image);
                     convert_rgb_to_cmyk(image)?;
                 }
+                ColorSpace::YCCK => {
+                    pop_channel(image);
+                    convert_rgb_to_cmyk(image)?;
+                    image.set_colorspace(ColorSpace::CMYK);
+                    convert_cmyk_to_ycck(image)?;
+                }
                 color => {
                     let msg = format!("Unsupported/unknown mapping from RGBA to {color:?}");
                     return Err(ImageErrors::GenericString(msg));
@@ -108,11 +310,11 @@ impl OperationsTrait for ColorspaceConv {
             ColorSpace::Luma => match self.to {
                 ColorSpace::RGB => convert_luma_to_rgb(image, self.to)?,
                 ColorSpace::LumaA => convert_adding_opaque_alpha(image)?,
-                color => {
+                _ => {
                     // convert to rgb
                     convert_luma_to_rgb(image, ColorSpace::RGB)?;
                     image.set_colorspace(ColorSpace::RGB);
-                    image.convert_color(color)?;
+                    self.execute_impl(image)?;
                 }
             },
 
@@ -120,26 +322,33 @@ impl OperationsTrait for ColorspaceConv {
                 ColorSpace::RGB => convert_luma_to_rgb(image, self.to)?,
                 ColorSpace::RGBA => convert_luma_to_rgb(image, self.to)?,
                 ColorSpace::Luma => pop_channel(image),
-                color => {
+                _ => {
                     // convert to rgba
                     convert_luma_to_rgb(image, ColorSpace::RGBA)?;
                     image.set_colorspace(ColorSpace::RGBA);
-                    image.convert_color(color)?;
+                    self.execute_impl(image)?;
                 }
             },
+            ColorSpace::CMYK if self.to == ColorSpace::YCCK => convert_cmyk_to_ycck(image)?,
             ColorSpace::CMYK => {
                 // convert to RGB first
                 convert_cmyk_to_rgb(image, ColorSpace::RGB)?;
                 image.set_colorspace(ColorSpace::RGB);
                 // convert to desired colorspace
-                image.convert_color(self.to)?;
+                self.execute_impl(image)?;
+            }
+            ColorSpace::YCCK => {
+                // convert to CMYK first
+                convert_ycck_to_cmyk(image)?;
+                image.set_colorspace(ColorSpace::CMYK);
+                self.execute_impl(image)?;
             }
             ColorSpace::BGR => {
                 // first convert to rgb
                 convert_rgb_bgr(from, ColorSpace::RGB, image)?;
                 // then convert to desired color
                 image.set_colorspace(ColorSpace::RGB);
-                image.convert_color(self.to)?;
+                self.execute_impl(image)?;
             }
             ColorSpace::BGRA => {
                 // BGRA and RGBA are similar with difference being only the R and B are swapped
@@ -148,35 +357,59 @@ impl OperationsTrait for ColorspaceConv {
 
                 // then use RGBA conversions
                 image.set_colorspace(ColorSpace::RGBA);
-                image.convert_color(self.to)?;
+                self.execute_impl(image)?;
             }
 
             ColorSpace::ARGB => {
                 // convert to RGBA
                 convert_rgba_to_argb_or_vice_versa(image)?;
                 image.set_colorspace(ColorSpace::RGBA);
-                image.convert_color(self.to)?;
+                self.execute_impl(image)?;
             }
             ColorSpace::HSL => {
                 // convert to rgb
                 convert_hsl_to_rgb(image)?;
                 image.set_colorspace(ColorSpace::RGB);
                 // convert to desired colorspace
-                image.convert_color(self.to)?;
+                self.execute_impl(image)?;
             }
             ColorSpace::HSV => {
                 // convert to rgb
                 convert_hsv_to_rgb(image)?;
                 image.set_colorspace(ColorSpace::RGB);
                 // convert to desired colorspace
-                image.convert_color(self.to)?;
+                self.execute_impl(image)?;
             }
-            ColorSpace::MultiBand(_) => {
-                // handle multi-band images
-                let msg = "Multi-band images do not have a concrete colorspace either create a new image with a concrete channel or reinterpret the image yourself";
-                return Err(ImageErrors::GenericStr(msg));
+            ColorSpace::Lab => {
+                convert_lab_to_rgb(image)?;
+                image.set_colorspace(ColorSpace::RGB);
+                self.execute_impl(image)?;
+            }
+            ColorSpace::LCh => {
+                convert_lch_to_rgb(image)?;
+                image.set_colorspace(ColorSpace::RGB);
+                self.execute_impl(image)?;
+            }
+            ColorSpace::OKLab => {
+                convert_oklab_to_rgb(image)?;
+                image.set_colorspace(ColorSpace::RGB);
+                self.execute_impl(image)?;
+            }
+            ColorSpace::OKLCh => {
+                convert_oklch_to_rgb(image)?;
+                image.set_colorspace(ColorSpace::RGB);
+                self.execute_impl(image)?;
+            }
+            ColorSpace::XYZ => {
+                convert_xyz_to_rgb(image, self.adaptation)?;
+                image.set_colorspace(ColorSpace::RGB);
+                self.execute_impl(image)?;
+            }
+            ColorSpace::YCbCr => {
+                convert_ycbcr_to_rgb(image)?;
+                image.set_colorspace(ColorSpace::RGB);
+                self.execute_impl(image)?;
             }
-
             color => {
                 let msg = format!("Unsupported colorspace  {color:?}");
                 return Err(ImageErrors::GenericString(msg));
@@ -185,6 +418,10 @@ impl OperationsTrait for ColorspaceConv {
         // set it to the new colorspace
         image.set_colorspace(self.to);
 
+        if premultiplied && self.to.has_alpha() {
+            convert_alpha_state(image, AlphaState::PreMultiplied)?;
+        }
+
         Ok(())
     }
 
@@ -0,0 +1,115 @@
+//! Chromatic adaptation transforms
+//!
+//! Chromatic adaptation converts XYZ colors seen under one white point to the colors
+//! that look the same under another white point. The transforms convert XYZ to a cone
+//! response like space, scale each response by the ratio of the white points and
+//! convert back, see [Bruce Lindbloom's chromatic adaptation](http://www.brucelindbloom.com/index.html?Eqn_ChromAdapt.html).
+use zune_core::colorspace::{ChromaticAdaptation, RgbPrimaries, WhitePoint};
+
+use crate::core_filters::colorspace::primaries::{invert, rgb_to_xyz_matrix};
+use crate::core_filters::colorspace::rgb_to_xyz::{mul, mul_matrices};
+
+/// XYZ to the cone response domain of the Bradford transform
+const BRADFORD: [[f32; 3]; 3] = [
+    [0.895_1, 0.266_4, -0.161_4],
+    [-0.750_2, 1.713_5, 0.036_7],
+    [0.038_9, -0.068_5, 1.029_6]
+];
+
+/// XYZ to the cone response domain of CIECAM02
+const CAT02: [[f32; 3]; 3] = [
+    [0.732_8, 0.429_6, -0.162_4],
+    [-0.703_6, 1.697_5, 0.006_1],
+    [0.003_0, 0.013_6, 0.983_4]
+];
+
+/// XYZ to LMS cone responses, the Hunt-Pointer-Estevez matrix
+const VON_KRIES: [[f32; 3]; 3] = [
+    [0.400_24, 0.707_6, -0.080_81],
+    [-0.226_3, 1.165_32, 0.045_7],
+    [0.0, 0.0, 0.918_22]
+];
+
+const IDENTITY: [[f32; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+const fn cone_response_matrix(method: ChromaticAdaptation) -> [[f32; 3]; 3] {
+    match method {
+        ChromaticAdaptation::Bradford => BRADFORD,
+        ChromaticAdaptation::CAT02 => CAT02,
+        ChromaticAdaptation::VonKries => VON_KRIES,
+        ChromaticAdaptation::XyzScaling => IDENTITY
+    }
+}
+
+/// Return the matrix adapting XYZ colors seen under `from` to colors seen under `to`
+///
+/// XYZ values are absolute, i.e. not relative to the white point.
+pub fn adaptation_matrix(
+    from: WhitePoint, to: WhitePoint, method: ChromaticAdaptation
+) -> [[f32; 3]; 3] {
+    let cone = cone_response_matrix(method);
+
+    let source = mul(&cone, from.to_xyz());
+    let destination = mul(&cone, to.to_xyz());
+
+    let mut scale = [[0.0; 3]; 3];
+    for i in 0..3 {
+        scale[i][i] = destination[i] / source[i];
+    }
+    mul_matrices(&invert(&cone), &mul_matrices(&scale, &cone))
+}
+
+/// Return the matrix adapting linear RGB colors seen under `from` to colors seen under `to`
+///
+/// The colors are converted to XYZ with the primaries, adapted and converted back,
+/// e.g. adapting from a warm white to D65 makes colors lit by the warm white look
+/// like they were lit by daylight.
+///
+/// # Example
+/// Adapt a linear sRGB color of a photo taken under D50 light to D65
+/// ```
+/// use zune_core::colorspace::{ChromaticAdaptation, RgbPrimaries, WhitePoint};
+/// use zune_image::core_filters::colorspace::adaptation::rgb_adaptation_matrix;
+///
+/// let matrix = rgb_adaptation_matrix(
+///     RgbPrimaries::sRGB,
+///     WhitePoint::D50,
+///     WhitePoint::D65,
+///     ChromaticAdaptation::CAT02
+/// );
+/// // D50 is warmer than D65, adapting it increases blue
+/// assert!(matrix[2].iter().sum::<f32>() > 1.0);
+/// ```
+pub fn rgb_adaptation_matrix(
+    primaries: RgbPrimaries, from: WhitePoint, to: WhitePoint, method: ChromaticAdaptation
+) -> [[f32; 3]; 3] {
+    let to_xyz = rgb_to_xyz_matrix(primaries);
+    let adapted = mul_matrices(&adaptation_matrix(from, to, method), &to_xyz);
+
+    mul_matrices(&invert(&to_xyz), &adapted)
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::{ChromaticAdaptation, WhitePoint};
+
+    use crate::core_filters::colorspace::adaptation::adaptation_matrix;
+    use crate::core_filters::colorspace::rgb_to_xyz::mul;
+
+    #[test]
+    fn test_adaptation_maps_white_to_white() {
+        for method in [
+            ChromaticAdaptation::Bradford,
+            ChromaticAdaptation::CAT02,
+            ChromaticAdaptation::VonKries,
+            ChromaticAdaptation::XyzScaling
+        ] {
+            let matrix = adaptation_matrix(WhitePoint::D65, WhitePoint::D50, method);
+            let adapted = mul(&matrix, WhitePoint::D65.to_xyz());
+
+            for (a, e) in adapted.iter().zip(WhitePoint::D50.to_xyz()) {
+                assert!((a - e).abs() < 1e-4, "{method:?} {adapted:?}");
+            }
+        }
+    }
+}
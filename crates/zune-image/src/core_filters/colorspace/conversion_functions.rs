@@ -1,14 +1,29 @@
+use alloc::borrow::ToOwned;
+use alloc::{format, vec};
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
 use zune_core::bit_depth::{BitDepth, BitType};
-use zune_core::colorspace::ColorSpace;
+use zune_core::colorspace::{
+    ChromaticAdaptation, ColorCharacteristics, ColorSpace, LumaCoefficients, WhitePoint
+};
 use zune_core::log::warn;
 
 use crate::channel::Channel;
 use crate::core_filters::colorspace::grayscale::{
-    rgb_to_grayscale_f32, rgb_to_grayscale_u16, rgb_to_grayscale_u8
+    rgb_to_grayscale_f32, rgb_to_grayscale_u16, rgb_to_grayscale_u8, rgb_to_grayscale_weighted
+};
+use crate::core_filters::colorspace::oklab::{
+    oklab_to_rgb, oklch_to_rgb, rgb_to_oklab, rgb_to_oklch
 };
 use crate::core_filters::colorspace::rgb_to_cmyk;
 use crate::core_filters::colorspace::rgb_to_hsl::{hsl_to_rgb, rgb_to_hsl};
 use crate::core_filters::colorspace::rgb_to_hsv::{hsv_to_rgb, rgb_to_hsv};
+use crate::core_filters::colorspace::rgb_to_lab::{lab_to_rgb, lch_to_rgb, rgb_to_lab, rgb_to_lch};
+use crate::core_filters::colorspace::rgb_to_xyz::{adapt_xyz, rgb_to_xyz, xyz_to_rgb};
+use crate::core_filters::colorspace::rgb_to_ycbcr::{
+    cmyk_to_ycck, rgb_to_ycbcr, ycbcr_to_rgb, ycck_to_cmyk
+};
 use crate::errors::ImageErrors;
 use crate::image::Image;
 
@@ -53,8 +68,10 @@ pub fn convert_adding_opaque_alpha(image: &mut Image) -> Result<(), ImageErrors>
     Ok(())
 }
 
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
 pub fn convert_rgb_to_grayscale(
-    image: &mut Image, to: ColorSpace, preserve_alpha: bool
+    image: &mut Image, to: ColorSpace, preserve_alpha: bool, luma: LumaCoefficients,
+    linear_light: bool
 ) -> Result<(), ImageErrors> {
     let im_colorspace = image.colorspace();
 
@@ -62,6 +79,12 @@ pub fn convert_rgb_to_grayscale(
         warn!("Image already in grayscale skipping this operation");
         return Ok(());
     }
+    // linear images are weighted as is
+    let linear_light =
+        linear_light && image.metadata.color_trc() != Some(ColorCharacteristics::Linear);
+    // the default uses the optimized routines
+    let fast_path = luma == LumaCoefficients::BT601 && !linear_light;
+    let coefficients = luma.coefficients();
 
     let (width, height) = image.dimensions();
     let size = width * height * image.depth().size_of();
@@ -82,13 +105,27 @@ pub fn convert_rgb_to_grayscale(
                 let b = channel[2].reinterpret_as::<u8>().unwrap();
                 let mut out = Channel::new_with_length::<u8>(size);
 
-                rgb_to_grayscale_u8(
-                    r,
-                    g,
-                    b,
-                    out.reinterpret_as_mut::<u8>().unwrap(),
-                    max_value as u8
-                );
+                if fast_path {
+                    rgb_to_grayscale_u8(
+                        r,
+                        g,
+                        b,
+                        out.reinterpret_as_mut::<u8>().unwrap(),
+                        max_value as u8
+                    );
+                } else {
+                    rgb_to_grayscale_weighted(
+                        r,
+                        g,
+                        b,
+                        out.reinterpret_as_mut::<u8>().unwrap(),
+                        coefficients,
+                        linear_light,
+                        f32::from(max_value),
+                        f32::from,
+                        |x| x.round() as u8
+                    );
+                }
 
                 if preserve_alpha && colorspace.has_alpha() {
                     frame.set_channels(vec![out, channel[3].clone()]);
@@ -110,7 +147,27 @@ pub fn convert_rgb_to_grayscale(
                 let b = channel[2].reinterpret_as::<u16>().unwrap();
                 let mut out = Channel::new_with_length::<u16>(size);
 
-                rgb_to_grayscale_u16(r, g, b, out.reinterpret_as_mut::<u16>().unwrap(), max_value);
+                if fast_path {
+                    rgb_to_grayscale_u16(
+                        r,
+                        g,
+                        b,
+                        out.reinterpret_as_mut::<u16>().unwrap(),
+                        max_value
+                    );
+                } else {
+                    rgb_to_grayscale_weighted(
+                        r,
+                        g,
+                        b,
+                        out.reinterpret_as_mut::<u16>().unwrap(),
+                        coefficients,
+                        linear_light,
+                        f32::from(max_value),
+                        f32::from,
+                        |x| x.round() as u16
+                    );
+                }
 
                 if preserve_alpha && colorspace.has_alpha() {
                     frame.set_channels(vec![out, channel[3].clone()]);
@@ -135,13 +192,27 @@ pub fn convert_rgb_to_grayscale(
                 let b = channel[2].reinterpret_as::<f32>().unwrap();
                 let mut out = Channel::new_with_length::<f32>(size);
 
-                rgb_to_grayscale_f32(
-                    r,
-                    g,
-                    b,
-                    out.reinterpret_as_mut::<f32>().unwrap(),
-                    max_value as f32
-                );
+                if fast_path {
+                    rgb_to_grayscale_f32(
+                        r,
+                        g,
+                        b,
+                        out.reinterpret_as_mut::<f32>().unwrap(),
+                        max_value as f32
+                    );
+                } else {
+                    rgb_to_grayscale_weighted(
+                        r,
+                        g,
+                        b,
+                        out.reinterpret_as_mut::<f32>().unwrap(),
+                        coefficients,
+                        linear_light,
+                        1.0,
+                        |x| x,
+                        |x| x
+                    );
+                }
 
                 if preserve_alpha && colorspace.has_alpha() {
                     frame.set_channels(vec![out, channel[3].clone()]);
@@ -393,6 +464,124 @@ pub fn convert_hsv_to_rgb(image: &mut Image) -> Result<(), ImageErrors> {
 
     Ok(())
 }
+/// Run a conversion on the first three channels of an image in floating point,
+/// preserving the bit depth
+fn convert_three_channels_f32(
+    image: &mut Image, func: impl Fn(&mut [f32], &mut [f32], &mut [f32])
+) -> Result<(), ImageErrors> {
+    // preserve original depth
+    let orig_depth = image.depth();
+    image.convert_depth(BitDepth::Float32)?;
+
+    for frame in image.frames_mut() {
+        let channels = frame.channels_vec();
+        let (c1, rest) = channels.split_at_mut(1);
+        let (c2, c3) = rest.split_at_mut(1);
+
+        func(
+            c1[0].reinterpret_as_mut()?,
+            c2[0].reinterpret_as_mut()?,
+            c3[0].reinterpret_as_mut()?
+        );
+    }
+    // restore original bit depth
+    image.convert_depth(orig_depth)?;
+    Ok(())
+}
+
+pub fn convert_rgb_to_lab(image: &mut Image) -> Result<(), ImageErrors> {
+    image.convert_color(ColorSpace::RGB)?;
+    convert_three_channels_f32(image, rgb_to_lab)
+}
+
+pub fn convert_lab_to_rgb(image: &mut Image) -> Result<(), ImageErrors> {
+    assert_eq!(image.colorspace(), ColorSpace::Lab);
+    convert_three_channels_f32(image, lab_to_rgb)
+}
+
+pub fn convert_rgb_to_lch(image: &mut Image) -> Result<(), ImageErrors> {
+    image.convert_color(ColorSpace::RGB)?;
+    convert_three_channels_f32(image, rgb_to_lch)
+}
+
+pub fn convert_lch_to_rgb(image: &mut Image) -> Result<(), ImageErrors> {
+    assert_eq!(image.colorspace(), ColorSpace::LCh);
+    convert_three_channels_f32(image, lch_to_rgb)
+}
+
+pub fn convert_rgb_to_ycbcr(image: &mut Image) -> Result<(), ImageErrors> {
+    image.convert_color(ColorSpace::RGB)?;
+    convert_three_channels_f32(image, rgb_to_ycbcr)
+}
+
+pub fn convert_ycbcr_to_rgb(image: &mut Image) -> Result<(), ImageErrors> {
+    assert_eq!(image.colorspace(), ColorSpace::YCbCr);
+    convert_three_channels_f32(image, ycbcr_to_rgb)
+}
+
+/// Convert Adobe YCCK to CMYK, the K channel is kept as is
+pub fn convert_ycck_to_cmyk(image: &mut Image) -> Result<(), ImageErrors> {
+    assert_eq!(image.colorspace(), ColorSpace::YCCK);
+    convert_three_channels_f32(image, ycck_to_cmyk)
+}
+
+/// Convert CMYK to Adobe YCCK, the K channel is kept as is
+pub fn convert_cmyk_to_ycck(image: &mut Image) -> Result<(), ImageErrors> {
+    assert_eq!(image.colorspace(), ColorSpace::CMYK);
+    convert_three_channels_f32(image, cmyk_to_ycck)
+}
+
+pub fn convert_rgb_to_oklab(image: &mut Image) -> Result<(), ImageErrors> {
+    image.convert_color(ColorSpace::RGB)?;
+    convert_three_channels_f32(image, rgb_to_oklab)
+}
+
+pub fn convert_oklab_to_rgb(image: &mut Image) -> Result<(), ImageErrors> {
+    assert_eq!(image.colorspace(), ColorSpace::OKLab);
+    convert_three_channels_f32(image, oklab_to_rgb)
+}
+
+pub fn convert_rgb_to_oklch(image: &mut Image) -> Result<(), ImageErrors> {
+    image.convert_color(ColorSpace::RGB)?;
+    convert_three_channels_f32(image, rgb_to_oklch)
+}
+
+pub fn convert_oklch_to_rgb(image: &mut Image) -> Result<(), ImageErrors> {
+    assert_eq!(image.colorspace(), ColorSpace::OKLCh);
+    convert_three_channels_f32(image, oklch_to_rgb)
+}
+
+pub fn convert_rgb_to_xyz(
+    image: &mut Image, white: WhitePoint, method: ChromaticAdaptation
+) -> Result<(), ImageErrors> {
+    image.convert_color(ColorSpace::RGB)?;
+    convert_three_channels_f32(image, |x, y, z| rgb_to_xyz(x, y, z, white, method))?;
+    image.metadata.set_white_point(white);
+    Ok(())
+}
+
+pub fn convert_xyz_to_rgb(
+    image: &mut Image, method: ChromaticAdaptation
+) -> Result<(), ImageErrors> {
+    assert_eq!(image.colorspace(), ColorSpace::XYZ);
+    let white = image.metadata.white_point();
+    convert_three_channels_f32(image, |x, y, z| xyz_to_rgb(x, y, z, white, method))
+}
+
+/// Adapt an XYZ image to a new reference white
+pub fn convert_xyz_white_point(
+    image: &mut Image, white: WhitePoint, method: ChromaticAdaptation
+) -> Result<(), ImageErrors> {
+    assert_eq!(image.colorspace(), ColorSpace::XYZ);
+    let from = image.metadata.white_point();
+
+    if from != white {
+        convert_three_channels_f32(image, |x, y, z| adapt_xyz(x, y, z, from, white, method))?;
+        image.metadata.set_white_point(white);
+    }
+    Ok(())
+}
+
 pub fn pop_channel(image: &mut Image) {
     // contain the alpha channel
     for frame in image.frames_mut() {
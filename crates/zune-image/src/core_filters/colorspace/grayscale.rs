@@ -6,12 +6,17 @@
  * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
  */
 
+use zune_core::simd::{Dispatcher, Isa};
+
 use crate::core_filters::colorspace::grayscale::scalar::{
     convert_rgb_to_grayscale_scalar, convert_rgb_to_grayscale_scalar_f32,
     convert_rgb_to_grayscale_scalar_u16
 };
+use crate::core_filters::colorspace::rgb_to_xyz::{linear_to_srgb, srgb_to_linear};
 
 mod avx2;
+mod avx512;
+mod neon;
 mod scalar;
 mod sse41;
 
@@ -19,29 +24,35 @@ pub fn rgb_to_grayscale_u16(r: &[u16], g: &[u16], b: &[u16], out: &mut [u16], ma
     convert_rgb_to_grayscale_scalar_u16(r, g, b, out, max_value);
 }
 
-pub fn rgb_to_grayscale_u8(r: &[u8], g: &[u8], b: &[u8], out: &mut [u8], max_value: u8) {
-    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-    {
-        #[cfg(feature = "avx2")]
-        {
-            use crate::core_filters::colorspace::grayscale::avx2::convert_rgb_to_grayscale_u8_avx2;
-
-            if is_x86_feature_detected!("avx2") {
-                unsafe {
-                    return convert_rgb_to_grayscale_u8_avx2(r, g, b, out);
-                }
-            }
-        }
-
-        #[cfg(feature = "sse41")]
-        {
-            use crate::core_filters::colorspace::grayscale::sse41::convert_rgb_to_grayscale_u8_sse41;
+/// A grayscale kernel for u8's with a maximum value of 255, `(r, g, b, out)`
+type GrayscaleFn = unsafe fn(&[u8], &[u8], &[u8], &mut [u8]);
+
+// SAFETY: every kernel only uses instructions of its instruction set
+static RGB_TO_GRAYSCALE_U8: Dispatcher<GrayscaleFn> = unsafe {
+    Dispatcher::new(
+        "rgb_to_grayscale_u8",
+        &[
+            #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+            (Isa::Avx512Bw, avx512::convert_rgb_to_grayscale_u8_avx512),
+            #[cfg(all(feature = "avx2", any(target_arch = "x86", target_arch = "x86_64")))]
+            (Isa::Avx2, avx2::convert_rgb_to_grayscale_u8_avx2),
+            #[cfg(all(feature = "sse41", any(target_arch = "x86", target_arch = "x86_64")))]
+            (Isa::Sse41, sse41::convert_rgb_to_grayscale_u8_sse41),
+            #[cfg(all(feature = "neon", target_arch = "aarch64", target_feature = "neon"))]
+            (Isa::Neon, neon::convert_rgb_to_grayscale_u8_neon),
+            (Isa::Scalar, |r, g, b, out| {
+                convert_rgb_to_grayscale_scalar(r, g, b, out, u8::MAX)
+            })
+        ]
+    )
+};
 
-            if is_x86_feature_detected!("sse4.1") {
-                unsafe {
-                    return convert_rgb_to_grayscale_u8_sse41(r, g, b, out);
-                }
-            }
+pub fn rgb_to_grayscale_u8(r: &[u8], g: &[u8], b: &[u8], out: &mut [u8], max_value: u8) {
+    // the SIMD versions assume 255 as the maximum value
+    if max_value == u8::MAX {
+        // SAFETY: the dispatcher only returns kernels the cpu supports
+        unsafe {
+            return RGB_TO_GRAYSCALE_U8.get()(r, g, b, out);
         }
     }
     convert_rgb_to_grayscale_scalar(r, g, b, out, max_value);
@@ -51,6 +62,36 @@ pub fn rgb_to_grayscale_f32(r: &[f32], g: &[f32], b: &[f32], out: &mut [f32], ma
     convert_rgb_to_grayscale_scalar_f32(r, g, b, out, max_value);
 }
 
+/// Convert RGB to grayscale by weighting the channels with `coefficients`
+///
+/// Values are scaled to 0..1 by `max_value`, if `linear_light` is set the
+/// channels are linearized before weighting and the result is sRGB encoded again
+#[allow(clippy::too_many_arguments)]
+pub fn rgb_to_grayscale_weighted<T: Copy>(
+    r: &[T], g: &[T], b: &[T], out: &mut [T], coefficients: [f32; 3], linear_light: bool,
+    max_value: f32, to_f32: impl Fn(T) -> f32, from_f32: impl Fn(f32) -> T
+) {
+    let [r_coef, g_coef, b_coef] = coefficients;
+    let max_inv = 1.0 / max_value;
+
+    let decode = |x: T| {
+        let x = to_f32(x) * max_inv;
+        if linear_light {
+            srgb_to_linear(x.clamp(0.0, 1.0))
+        } else {
+            x
+        }
+    };
+    for (((r_v, g_v), b_v), g_out) in r.iter().zip(g.iter()).zip(b.iter()).zip(out.iter_mut()) {
+        let mut gray = r_coef * decode(*r_v) + g_coef * decode(*g_v) + b_coef * decode(*b_v);
+
+        if linear_light {
+            gray = linear_to_srgb(gray.clamp(0.0, 1.0));
+        }
+        *g_out = from_f32(gray * max_value);
+    }
+}
+
 #[cfg(feature = "benchmarks")]
 #[cfg(test)]
 mod benchmarks {
@@ -114,3 +155,37 @@ mod benchmarks {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nanorand::Rng;
+
+    use crate::core_filters::colorspace::grayscale::scalar::convert_rgb_to_grayscale_scalar;
+    use crate::core_filters::colorspace::grayscale::RGB_TO_GRAYSCALE_U8;
+
+    #[test]
+    fn test_grayscale_kernels_match_scalar() {
+        let mut rng = nanorand::WyRand::new();
+
+        // lengths around every chunk size, for the remainders
+        for length in [0, 1, 7, 8, 15, 16, 31, 32, 33, 100, 1000] {
+            let [r, g, b] = [0; 3].map(|_| {
+                (0..length)
+                    .map(|_| rng.generate::<u8>())
+                    .collect::<Vec<u8>>()
+            });
+            let mut expected = vec![0; length];
+            convert_rgb_to_grayscale_scalar(&r, &g, &b, &mut expected, u8::MAX);
+
+            for (isa, kernel) in RGB_TO_GRAYSCALE_U8.kernels() {
+                if !isa.is_supported() {
+                    continue;
+                }
+                let mut out = vec![0; length];
+                unsafe { kernel(&r, &g, &b, &mut out) };
+
+                assert_eq!(out, expected, "{isa:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+#![cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#![cfg(feature = "avx512")]
+//! The AVX2 routine on 512 bit registers, 32 pixels at a time
+
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+use crate::core_filters::colorspace::grayscale::scalar::convert_rgb_to_grayscale_scalar;
+
+#[target_feature(enable = "avx512f,avx512bw")]
+#[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+pub(crate) unsafe fn convert_rgb_to_grayscale_u8_avx512(
+    r: &[u8], g: &[u8], b: &[u8], gr: &mut [u8]
+) {
+    const CHUNK_SIZE: usize = 32;
+    // Each coefficient is expanded by 2^15, and rounded to int16 (add 0.5 for rounding).
+    let r_coef = _mm512_set1_epi16((0.2989 * 32768.0 + 0.5) as i16);
+    let g_coef = _mm512_set1_epi16((0.5870 * 32768.0 + 0.5) as i16);
+    let b_coef = _mm512_set1_epi16((0.1140 * 32768.0 + 0.5) as i16);
+
+    for (((r_chunk, g_chunk), b_chunk), out) in r
+        .chunks_exact(CHUNK_SIZE)
+        .zip(g.chunks_exact(CHUNK_SIZE))
+        .zip(b.chunks_exact(CHUNK_SIZE))
+        .zip(gr.chunks_exact_mut(CHUNK_SIZE))
+    {
+        // Multiply input elements by 64 for improved accuracy.
+        let r_c = _mm512_slli_epi16::<6>(_mm512_cvtepu8_epi16(_mm256_loadu_si256(
+            r_chunk.as_ptr().cast()
+        )));
+        let g_c = _mm512_slli_epi16::<6>(_mm512_cvtepu8_epi16(_mm256_loadu_si256(
+            g_chunk.as_ptr().cast()
+        )));
+        let b_c = _mm512_slli_epi16::<6>(_mm512_cvtepu8_epi16(_mm256_loadu_si256(
+            b_chunk.as_ptr().cast()
+        )));
+
+        // round((x * coef) >> 15) for every channel, i.e
+        // Y = 0.2989*R + 0.5870*G + 0.1140*B in fixed point
+        let g_out = _mm512_add_epi16(
+            _mm512_add_epi16(
+                _mm512_mulhrs_epi16(r_c, r_coef),
+                _mm512_mulhrs_epi16(g_c, g_coef)
+            ),
+            _mm512_mulhrs_epi16(b_c, b_coef)
+        );
+
+        // Undo the multiplication, and narrow to 8 bits with saturation,
+        // which unlike packing keeps values in order
+        let g_out = _mm512_cvtusepi16_epi8(_mm512_srli_epi16::<6>(g_out));
+
+        _mm256_storeu_si256(out.as_mut_ptr().cast(), g_out);
+    }
+    // remainders
+    let start = r.len() - r.len() % CHUNK_SIZE;
+
+    convert_rgb_to_grayscale_scalar(&r[start..], &g[start..], &b[start..], &mut gr[start..], 255);
+}
@@ -0,0 +1,88 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+#![cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+#![cfg(feature = "neon")]
+
+use core::arch::aarch64::*;
+
+use crate::core_filters::colorspace::grayscale::scalar::convert_rgb_to_grayscale_scalar;
+
+/// The NEON version of the SSE4.1 conversion, `vqrdmulhq_s16` rounds like
+/// `_mm_mulhrs_epi16` so the results are the same
+#[allow(
+    clippy::cast_sign_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap
+)]
+pub(crate) unsafe fn convert_rgb_to_grayscale_u8_neon(r: &[u8], g: &[u8], b: &[u8], gr: &mut [u8]) {
+    const CHUNK_SIZE: usize = 8;
+    // Each coefficient is expanded by 2^15, and rounded to int16 (add 0.5 for rounding).
+    let r_coef = vdupq_n_s16((0.2989 * 32768.0 + 0.5) as i16);
+    let g_coef = vdupq_n_s16((0.5870 * 32768.0 + 0.5) as i16);
+    let b_coef = vdupq_n_s16((0.1140 * 32768.0 + 0.5) as i16);
+
+    for (((r_chunk, g_chunk), b_chunk), out) in r
+        .chunks_exact(CHUNK_SIZE)
+        .zip(g.chunks_exact(CHUNK_SIZE))
+        .zip(b.chunks_exact(CHUNK_SIZE))
+        .zip(gr.chunks_exact_mut(CHUNK_SIZE))
+    {
+        // zero extend to u16's and multiply by 64 for improved accuracy
+        let r_c = vreinterpretq_s16_u16(vshll_n_u8::<6>(vld1_u8(r_chunk.as_ptr())));
+        let g_c = vreinterpretq_s16_u16(vshll_n_u8::<6>(vld1_u8(g_chunk.as_ptr())));
+        let b_c = vreinterpretq_s16_u16(vshll_n_u8::<6>(vld1_u8(b_chunk.as_ptr())));
+
+        // round(x * coef / 2^15)
+        let g_out = vaddq_s16(
+            vaddq_s16(vqrdmulhq_s16(r_c, r_coef), vqrdmulhq_s16(g_c, g_coef)),
+            vqrdmulhq_s16(b_c, b_coef)
+        );
+        // divide by 64 and narrow to u8's
+        let g_out = vshrq_n_u16::<6>(vreinterpretq_u16_s16(g_out));
+
+        vst1_u8(out.as_mut_ptr(), vqmovn_u16(g_out));
+    }
+
+    // remainders
+    if r.len() % CHUNK_SIZE != 0 {
+        let rem = r.len() % CHUNK_SIZE;
+        let start = r.len() - rem;
+
+        convert_rgb_to_grayscale_scalar(
+            &r[start..],
+            &g[start..],
+            &b[start..],
+            &mut gr[start..],
+            255
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core_filters::colorspace::grayscale::neon::convert_rgb_to_grayscale_u8_neon;
+    use crate::core_filters::colorspace::grayscale::scalar::convert_rgb_to_grayscale_scalar;
+
+    #[test]
+    fn test_grayscale_neon_matches_scalar() {
+        let r: Vec<u8> = (0..=255).collect();
+        let g: Vec<u8> = r.iter().rev().copied().collect();
+        let b: Vec<u8> = r.iter().map(|x| x.wrapping_mul(31)).collect();
+
+        let mut neon = vec![0; r.len() - 3];
+        let mut scalar = vec![0; r.len() - 3];
+        let len = neon.len();
+
+        unsafe {
+            convert_rgb_to_grayscale_u8_neon(&r[..len], &g[..len], &b[..len], &mut neon);
+        }
+        convert_rgb_to_grayscale_scalar(&r[..len], &g[..len], &b[..len], &mut scalar, 255);
+        assert_eq!(neon, scalar);
+    }
+}
@@ -0,0 +1,147 @@
+//! sRGB to OKLab and OKLCh conversions
+//!
+//! The conversion is described in [Björn Ottosson's post](https://bottosson.github.io/posts/oklab/)
+//! introducing OKLab.
+//!
+//! The single color functions work on real OKLab values, the slice functions on the normalized
+//! encoding described in [`ColorSpace::OKLab`] and [`ColorSpace::OKLCh`]. RGB is sRGB in 0..1
+//!
+//! [`ColorSpace::OKLab`]: zune_core::colorspace::ColorSpace::OKLab
+//! [`ColorSpace::OKLCh`]: zune_core::colorspace::ColorSpace::OKLCh
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+use crate::core_filters::colorspace::rgb_to_xyz::{linear_to_srgb, mul, srgb_to_linear};
+
+/// Linear sRGB to LMS cone responses
+const RGB_TO_LMS: [[f32; 3]; 3] = [
+    [0.412_221_46, 0.536_332_55, 0.051_445_995],
+    [0.211_903_5, 0.680_699_5, 0.107_396_96],
+    [0.088_302_46, 0.281_718_85, 0.629_978_7]
+];
+
+/// Non-linear LMS to OKLab
+const LMS_TO_LAB: [[f32; 3]; 3] = [
+    [0.210_454_26, 0.793_617_8, -0.004_072_047],
+    [1.977_998_5, -2.428_592_2, 0.450_593_7],
+    [0.025_904_037, 0.782_771_77, -0.808_675_77]
+];
+
+/// OKLab to non-linear LMS, the inverse of [`LMS_TO_LAB`]
+const LAB_TO_LMS: [[f32; 3]; 3] = [
+    [1.0, 0.396_337_78, 0.215_803_76],
+    [1.0, -0.105_561_346, -0.063_854_17],
+    [1.0, -0.089_484_18, -1.291_485_5]
+];
+
+/// LMS to linear sRGB, the inverse of [`RGB_TO_LMS`]
+const LMS_TO_RGB: [[f32; 3]; 3] = [
+    [4.076_741_7, -3.307_711_6, 0.230_969_94],
+    [-1.268_438, 2.609_757_4, -0.341_319_38],
+    [-0.004_196_086_3, -0.703_418_6, 1.707_614_7]
+];
+
+/// Convert an sRGB color in 0..1 to OKLab
+#[inline]
+pub fn srgb_to_oklab(rgb: [f32; 3]) -> [f32; 3] {
+    let linear = rgb.map(|x| srgb_to_linear(x.clamp(0.0, 1.0)));
+    let lms = mul(&RGB_TO_LMS, linear).map(f32::cbrt);
+
+    mul(&LMS_TO_LAB, lms)
+}
+
+/// Convert an OKLab color to sRGB in 0..1
+///
+/// Colors outside the sRGB gamut are clipped
+#[inline]
+pub fn oklab_to_srgb(lab: [f32; 3]) -> [f32; 3] {
+    let lms = mul(&LAB_TO_LMS, lab).map(|x| x * x * x);
+
+    mul(&LMS_TO_RGB, lms).map(|x| linear_to_srgb(x.clamp(0.0, 1.0)))
+}
+
+#[inline(always)]
+fn encode_lab(lab: [f32; 3]) -> [f32; 3] {
+    [lab[0], lab[1] + 0.5, lab[2] + 0.5]
+}
+
+#[inline(always)]
+fn decode_lab(lab: [f32; 3]) -> [f32; 3] {
+    [lab[0], lab[1] - 0.5, lab[2] - 0.5]
+}
+
+#[inline(always)]
+fn encode_lch(lab: [f32; 3]) -> [f32; 3] {
+    let chroma = lab[1].hypot(lab[2]);
+    let hue = lab[2].atan2(lab[1]).to_degrees();
+    // atan2 is in -180..=180 degrees
+    let hue = if hue < 0.0 { hue + 360.0 } else { hue };
+
+    [lab[0], chroma * 2.0, hue / 360.0]
+}
+
+#[inline(always)]
+fn decode_lch(lch: [f32; 3]) -> [f32; 3] {
+    let chroma = lch[1] / 2.0;
+    let (sin, cos) = (lch[2] * 360.0).to_radians().sin_cos();
+
+    [lch[0], chroma * cos, chroma * sin]
+}
+
+fn map_pixels(c1: &mut [f32], c2: &mut [f32], c3: &mut [f32], func: impl Fn([f32; 3]) -> [f32; 3]) {
+    for ((x, y), z) in c1.iter_mut().zip(c2.iter_mut()).zip(c3.iter_mut()) {
+        [*x, *y, *z] = func([*x, *y, *z]);
+    }
+}
+
+pub(crate) fn rgb_to_oklab(r_l: &mut [f32], g_a: &mut [f32], b_b: &mut [f32]) {
+    map_pixels(r_l, g_a, b_b, |rgb| encode_lab(srgb_to_oklab(rgb)));
+}
+
+pub(crate) fn oklab_to_rgb(l_r: &mut [f32], a_g: &mut [f32], b_b: &mut [f32]) {
+    map_pixels(l_r, a_g, b_b, |lab| oklab_to_srgb(decode_lab(lab)));
+}
+
+pub(crate) fn rgb_to_oklch(r_l: &mut [f32], g_c: &mut [f32], b_h: &mut [f32]) {
+    map_pixels(r_l, g_c, b_h, |rgb| encode_lch(srgb_to_oklab(rgb)));
+}
+
+pub(crate) fn oklch_to_rgb(l_r: &mut [f32], c_g: &mut [f32], h_b: &mut [f32]) {
+    map_pixels(l_r, c_g, h_b, |lch| oklab_to_srgb(decode_lch(lch)));
+}
+
+#[cfg(test)]
+mod tests {
+    use nanorand::Rng;
+
+    use crate::core_filters::colorspace::oklab::{
+        decode_lch, encode_lch, oklab_to_srgb, srgb_to_oklab
+    };
+
+    #[test]
+    fn test_known_values() {
+        let white = srgb_to_oklab([1.0; 3]);
+        assert!((white[0] - 1.0).abs() < 1e-3);
+        assert!(white[1].abs() < 1e-3 && white[2].abs() < 1e-3);
+
+        // reference value from the OKLab post
+        let red = srgb_to_oklab([1.0, 0.0, 0.0]);
+        for (r, e) in red.iter().zip([0.627_955, 0.224_863, 0.125_846]) {
+            assert!((r - e).abs() < 1e-3, "{red:?}");
+        }
+    }
+
+    #[test]
+    fn test_round_trip_random() {
+        let mut rand = nanorand::WyRand::new();
+
+        for _ in 0..100 {
+            let rgb: [f32; 3] = [rand.generate(), rand.generate(), rand.generate()];
+            let result = oklab_to_srgb(decode_lch(encode_lch(srgb_to_oklab(rgb))));
+
+            rgb.iter()
+                .zip(result.iter())
+                .for_each(|(e, f)| assert!((e - f).abs() <= 0.001, "{}!={}", e, f));
+        }
+    }
+}
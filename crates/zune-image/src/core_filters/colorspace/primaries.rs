@@ -0,0 +1,103 @@
+//! Conversion matrices between RGB primaries
+//!
+//! The matrices work on linear RGB values, the matrix from primaries to XYZ is derived
+//! from the chromaticities of the primaries and their white point as described in
+//! [Bruce Lindbloom's RGB/XYZ matrices](http://www.brucelindbloom.com/index.html?Eqn_RGB_XYZ_Matrix.html).
+//!
+//! Colors converted to smaller gamuts may be outside of 0..1, use the `GamutMap` operation
+//! of `zune-imageprocs` to convert images and bring such colors into the gamut.
+use zune_core::colorspace::{ChromaticAdaptation, RgbPrimaries};
+
+use crate::core_filters::colorspace::adaptation::adaptation_matrix;
+use crate::core_filters::colorspace::rgb_to_xyz::{mul, mul_matrices};
+
+#[allow(clippy::many_single_char_names)]
+pub(crate) fn invert(m: &[[f32; 3]; 3]) -> [[f32; 3]; 3] {
+    let [[a, b, c], [d, e, f], [g, h, i]] = *m;
+
+    let det = a * (e * i - f * h) - b * (d * i - f * g) + c * (d * h - e * g);
+
+    [
+        [e * i - f * h, c * h - b * i, b * f - c * e],
+        [f * g - d * i, a * i - c * g, c * d - a * f],
+        [d * h - e * g, b * g - a * h, a * e - b * d]
+    ]
+    .map(|row| row.map(|x| x / det))
+}
+
+/// Return the matrix converting linear RGB with the given primaries to XYZ
+///
+/// XYZ is relative to the white point of the primaries, with `Y` of white being 1
+pub fn rgb_to_xyz_matrix(primaries: RgbPrimaries) -> [[f32; 3]; 3] {
+    // columns are the XYZ values of the primaries
+    let columns = primaries
+        .chromaticities()
+        .map(|(x, y)| [x / y, 1.0, (1.0 - x - y) / y]);
+    let matrix = [0, 1, 2].map(|i| columns.map(|column| column[i]));
+    // scale the primaries so that they add up to white
+    let scale = mul(&invert(&matrix), primaries.white_point().to_xyz());
+
+    matrix.map(|row| [row[0] * scale[0], row[1] * scale[1], row[2] * scale[2]])
+}
+
+/// Return the matrix converting linear RGB with the primaries `from` to linear
+/// RGB with the primaries `to`
+///
+/// Primaries with different white points are adapted with the Bradford transform
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::RgbPrimaries;
+/// use zune_image::core_filters::colorspace::primaries::conversion_matrix;
+///
+/// let matrix = conversion_matrix(RgbPrimaries::DisplayP3, RgbPrimaries::sRGB);
+/// // pure P3 red is more saturated than sRGB red
+/// assert!(matrix[0][0] > 1.0);
+/// ```
+pub fn conversion_matrix(from: RgbPrimaries, to: RgbPrimaries) -> [[f32; 3]; 3] {
+    let to_xyz = mul_matrices(
+        &adaptation_matrix(
+            from.white_point(),
+            to.white_point(),
+            ChromaticAdaptation::Bradford
+        ),
+        &rgb_to_xyz_matrix(from)
+    );
+    mul_matrices(&invert(&rgb_to_xyz_matrix(to)), &to_xyz)
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::RgbPrimaries;
+
+    use crate::core_filters::colorspace::primaries::{conversion_matrix, rgb_to_xyz_matrix};
+    use crate::core_filters::colorspace::rgb_to_xyz::RGB_TO_XYZ;
+
+    #[test]
+    fn test_srgb_matrix_matches_reference() {
+        let matrix = rgb_to_xyz_matrix(RgbPrimaries::sRGB);
+
+        for (a, e) in matrix.iter().flatten().zip(RGB_TO_XYZ.iter().flatten()) {
+            assert!((a - e).abs() < 1e-3, "{matrix:?}");
+        }
+    }
+
+    #[test]
+    fn test_round_trip_and_white() {
+        let forward = conversion_matrix(RgbPrimaries::Rec2020, RgbPrimaries::DisplayP3);
+        let backward = conversion_matrix(RgbPrimaries::DisplayP3, RgbPrimaries::Rec2020);
+
+        // white stays white
+        for row in forward {
+            assert!((row.iter().sum::<f32>() - 1.0).abs() < 1e-4);
+        }
+        let color = [0.2, 0.7, 0.4];
+        let mul =
+            |m: &[[f32; 3]; 3], v: [f32; 3]| m.map(|r| r[0] * v[0] + r[1] * v[1] + r[2] * v[2]);
+        let result = mul(&backward, mul(&forward, color));
+
+        for (a, e) in result.iter().zip(color) {
+            assert!((a - e).abs() < 1e-4, "{result:?}");
+        }
+    }
+}
@@ -0,0 +1,197 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Custom colorspace conversions
+//!
+//! [`ColorSpace::MultiBand`] images have no fixed interpretation, e.g. the bands of a
+//! multispectral capture or a stack of masks. The library can't convert them by itself,
+//! instead downstream crates register conversion functions with [`register_conversion`]
+//! and [`ColorspaceConv`] uses them.
+//!
+//! When no conversion between two colorspaces is registered, the shortest chain of
+//! registered conversions and the library's own conversions between concrete colorspaces is
+//! used, e.g. registering `MultiBand(6)` to RGB also allows converting `MultiBand(6)` to HSV
+//! or Luma.
+//!
+//! # Example
+//! Build a false color composite from the bands of a multispectral image
+//! ```
+//! use core::num::NonZeroU32;
+//!
+//! use zune_core::colorspace::{ColorSpace, ALL_COLORSPACES};
+//! use zune_image::core_filters::colorspace::registry::register_conversion;
+//! use zune_image::core_filters::colorspace::ColorspaceConv;
+//! use zune_image::errors::ImageErrors;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//!
+//! let bands = ColorSpace::MultiBand(NonZeroU32::new(5).unwrap());
+//!
+//! fn composite(image: &mut Image) -> Result<(), ImageErrors> {
+//!     for frame in image.frames_mut() {
+//!         // near infrared, red and green bands become red, green and blue
+//!         let mut channels = frame.channels_vec().to_vec();
+//!         channels.truncate(4);
+//!         channels.remove(0);
+//!         frame.set_channels(channels);
+//!     }
+//!     Ok(())
+//! }
+//! register_conversion(bands, ColorSpace::RGB, composite);
+//!
+//! let mut image = Image::fill(10_u8, bands, 20, 20);
+//! ColorspaceConv::new(ColorSpace::Luma).execute(&mut image).unwrap();
+//!
+//! assert_eq!(image.colorspace(), ColorSpace::Luma);
+//! ```
+//!
+//! [`ColorspaceConv`]: crate::core_filters::colorspace::ColorspaceConv
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+use std::sync::RwLock;
+
+use zune_core::colorspace::{ColorSpace, ALL_COLORSPACES};
+
+use crate::errors::ImageErrors;
+use crate::image::Image;
+
+/// A function converting an image between two colorspaces
+///
+/// The function replaces the channels of every frame with the channels of the new colorspace,
+/// the colorspace of the image is updated after it returns.
+pub type ConversionFn = fn(&mut Image) -> Result<(), ImageErrors>;
+
+/// A registered conversion
+#[derive(Copy, Clone)]
+struct Conversion {
+    from:     ColorSpace,
+    to:       ColorSpace,
+    function: ConversionFn
+}
+
+static CONVERSIONS: RwLock<Vec<Conversion>> = RwLock::new(Vec::new());
+
+/// A single conversion in a chain found by [`find_conversion_path`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ConversionStep {
+    /// A conversion registered with [`register_conversion`]
+    Registered(ColorSpace, ColorSpace),
+    /// A conversion built into the library
+    Builtin(ColorSpace, ColorSpace)
+}
+
+/// Register a function converting images from `from` to `to`
+///
+/// A conversion registered earlier for the same colorspaces is replaced.
+/// Conversions are global, they are used by every [`ColorspaceConv`](crate::core_filters::colorspace::ColorspaceConv)
+/// involving a [`ColorSpace::MultiBand`] colorspace.
+pub fn register_conversion(from: ColorSpace, to: ColorSpace, function: ConversionFn) {
+    let mut conversions = CONVERSIONS.write().unwrap_or_else(|e| e.into_inner());
+
+    conversions.retain(|x| !(x.from == from && x.to == to));
+    conversions.push(Conversion { from, to, function });
+}
+
+/// Remove the conversion from `from` to `to`, returning true if one was registered
+pub fn unregister_conversion(from: ColorSpace, to: ColorSpace) -> bool {
+    let mut conversions = CONVERSIONS.write().unwrap_or_else(|e| e.into_inner());
+    let length = conversions.len();
+
+    conversions.retain(|x| !(x.from == from && x.to == to));
+    length != conversions.len()
+}
+
+/// Whether the library itself can convert between the colorspaces
+fn is_builtin(from: ColorSpace, to: ColorSpace) -> bool {
+    ALL_COLORSPACES.contains(&from) && ALL_COLORSPACES.contains(&to)
+}
+
+/// Find the shortest chain of conversions converting `from` to `to`
+///
+/// Returns `None` if there is no chain, an empty chain if both colorspaces are the same
+pub fn find_conversion_path(from: ColorSpace, to: ColorSpace) -> Option<Vec<ConversionStep>> {
+    let conversions = CONVERSIONS.read().unwrap_or_else(|e| e.into_inner());
+
+    // every colorspace that can take part in a chain
+    let mut nodes = vec![from, to];
+    for conversion in conversions.iter() {
+        for colorspace in [conversion.from, conversion.to] {
+            if !nodes.contains(&colorspace) {
+                nodes.push(colorspace);
+            }
+        }
+    }
+    // breadth first search, storing the step reaching every node
+    let mut reached: Vec<Option<ConversionStep>> = vec![None; nodes.len()];
+    let mut queue = VecDeque::from([0]);
+    let mut visited = vec![false; nodes.len()];
+    visited[0] = true;
+
+    while let Some(current) = queue.pop_front() {
+        let node = nodes[current];
+
+        if node == to {
+            break;
+        }
+        for (next, colorspace) in nodes.iter().enumerate() {
+            if visited[next] {
+                continue;
+            }
+            let step = if conversions
+                .iter()
+                .any(|x| x.from == node && x.to == *colorspace)
+            {
+                ConversionStep::Registered(node, *colorspace)
+            } else if is_builtin(node, *colorspace) {
+                ConversionStep::Builtin(node, *colorspace)
+            } else {
+                continue;
+            };
+            visited[next] = true;
+            reached[next] = Some(step);
+            queue.push_back(next);
+        }
+    }
+    if from == to {
+        return Some(vec![]);
+    }
+    let mut path = vec![];
+    let mut current = 1;
+
+    while let Some(step) = reached[current] {
+        path.push(step);
+        let (ConversionStep::Registered(previous, _) | ConversionStep::Builtin(previous, _)) = step;
+        current = nodes.iter().position(|x| *x == previous)?;
+    }
+    if current != 0 {
+        return None;
+    }
+    path.reverse();
+    Some(path)
+}
+
+/// Run a registered conversion, returning false if there is none
+pub(crate) fn run_registered(
+    image: &mut Image, from: ColorSpace, to: ColorSpace
+) -> Result<bool, ImageErrors> {
+    let function = CONVERSIONS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .find(|x| x.from == from && x.to == to)
+        .map(|x| x.function);
+
+    match function {
+        Some(function) => {
+            function(image)?;
+            image.set_colorspace(to);
+            Ok(true)
+        }
+        None => Ok(false)
+    }
+}
@@ -5,6 +5,9 @@
 //!
 //! This contains a mapping from RGB to HSL and back in floating points, values are expected to be between 0 and 1
 //!
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
 const ONE_THIRD: f32 = 1.0 / 3.0;
 const ONE_SIXTH: f32 = 1.0 / 6.0;
 const TWO_THIRD: f32 = 2.0 / 3.0;
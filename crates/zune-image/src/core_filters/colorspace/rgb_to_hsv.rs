@@ -1,3 +1,6 @@
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
 fn python_mod(n: f32, base: f32) -> f32 {
     n - (n / base).floor() * base
 }
@@ -0,0 +1,186 @@
+//! sRGB to CIE L*a*b* and LCh conversions
+//!
+//! Values are in the normalized encoding described in [`ColorSpace::Lab`] and
+//! [`ColorSpace::LCh`], RGB is sRGB in 0..1
+//!
+//! [`ColorSpace::Lab`]: zune_core::colorspace::ColorSpace::Lab
+//! [`ColorSpace::LCh`]: zune_core::colorspace::ColorSpace::LCh
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+use crate::core_filters::colorspace::rgb_to_xyz::{
+    linear_to_srgb, mul, srgb_to_linear, RGB_TO_XYZ, XYZ_TO_RGB
+};
+
+/// D65 reference white, with `Y` normalized to 1
+const D65_WHITE: [f32; 3] = [0.950_47, 1.0, 1.088_83];
+
+/// Chroma of the most saturated color the a and b channels can encode, `128*sqrt(2)`
+const MAX_CHROMA: f32 = 181.019_33;
+
+const EPSILON: f32 = 216.0 / 24389.0;
+const KAPPA: f32 = 24389.0 / 27.0;
+
+/// Convert an sRGB color in 0..1 to L\*a\*b\*, with L in 0..100
+#[inline(always)]
+pub fn rgb_to_lab_inner(r: f32, g: f32, b: f32) -> [f32; 3] {
+    let linear = [r, g, b].map(|x| srgb_to_linear(x.clamp(0.0, 1.0)));
+    let xyz = mul(&RGB_TO_XYZ, linear);
+
+    let [fx, fy, fz] = [0, 1, 2].map(|i| {
+        let t = xyz[i] / D65_WHITE[i];
+
+        if t > EPSILON {
+            t.cbrt()
+        } else {
+            (KAPPA * t + 16.0) / 116.0
+        }
+    });
+
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+/// Convert a L\*a\*b\* color, with L in 0..100, to sRGB in 0..1
+///
+/// Colors outside the sRGB gamut are clipped
+#[inline(always)]
+pub fn lab_to_rgb_inner(l: f32, a: f32, b: f32) -> [f32; 3] {
+    let fy = (l + 16.0) / 116.0;
+    let fx = fy + a / 500.0;
+    let fz = fy - b / 200.0;
+
+    let inverse = |f: f32| {
+        let cube = f * f * f;
+
+        if cube > EPSILON {
+            cube
+        } else {
+            (116.0 * f - 16.0) / KAPPA
+        }
+    };
+    let xyz = [
+        inverse(fx) * D65_WHITE[0],
+        // use L directly for the dark range, it is more accurate than going via fy
+        if l > KAPPA * EPSILON { fy * fy * fy } else { l / KAPPA },
+        inverse(fz) * D65_WHITE[2]
+    ];
+
+    mul(&XYZ_TO_RGB, xyz).map(|x| linear_to_srgb(x.clamp(0.0, 1.0)))
+}
+
+#[inline(always)]
+fn encode_lab(lab: [f32; 3]) -> [f32; 3] {
+    [
+        lab[0] / 100.0,
+        (lab[1] + 128.0) / 255.0,
+        (lab[2] + 128.0) / 255.0
+    ]
+}
+
+#[inline(always)]
+fn decode_lab(lab: [f32; 3]) -> [f32; 3] {
+    [
+        lab[0] * 100.0,
+        lab[1] * 255.0 - 128.0,
+        lab[2] * 255.0 - 128.0
+    ]
+}
+
+/// Convert an encoded Lab color to an encoded LCh color
+#[inline(always)]
+fn lab_to_lch_inner(lab: [f32; 3]) -> [f32; 3] {
+    let [l, a, b] = decode_lab(lab);
+    let chroma = a.hypot(b);
+    let hue = b.atan2(a).to_degrees();
+    // atan2 is in -180..=180 degrees
+    let hue = if hue < 0.0 { hue + 360.0 } else { hue };
+
+    [l / 100.0, chroma / MAX_CHROMA, hue / 360.0]
+}
+
+/// Convert an encoded LCh color to an encoded Lab color
+#[inline(always)]
+fn lch_to_lab_inner(lch: [f32; 3]) -> [f32; 3] {
+    let chroma = lch[1] * MAX_CHROMA;
+    let (sin, cos) = (lch[2] * 360.0).to_radians().sin_cos();
+
+    encode_lab([lch[0] * 100.0, chroma * cos, chroma * sin])
+}
+
+fn map_pixels(c1: &mut [f32], c2: &mut [f32], c3: &mut [f32], func: impl Fn([f32; 3]) -> [f32; 3]) {
+    for ((x, y), z) in c1.iter_mut().zip(c2.iter_mut()).zip(c3.iter_mut()) {
+        [*x, *y, *z] = func([*x, *y, *z]);
+    }
+}
+
+pub fn rgb_to_lab(r_l: &mut [f32], g_a: &mut [f32], b_b: &mut [f32]) {
+    map_pixels(r_l, g_a, b_b, |[r, g, b]| {
+        encode_lab(rgb_to_lab_inner(r, g, b))
+    });
+}
+
+pub fn lab_to_rgb(l_r: &mut [f32], a_g: &mut [f32], b_b: &mut [f32]) {
+    map_pixels(l_r, a_g, b_b, |lab| {
+        let [l, a, b] = decode_lab(lab);
+        lab_to_rgb_inner(l, a, b)
+    });
+}
+
+pub fn rgb_to_lch(r_l: &mut [f32], g_c: &mut [f32], b_h: &mut [f32]) {
+    map_pixels(r_l, g_c, b_h, |[r, g, b]| {
+        lab_to_lch_inner(encode_lab(rgb_to_lab_inner(r, g, b)))
+    });
+}
+
+pub fn lch_to_rgb(l_r: &mut [f32], c_g: &mut [f32], h_b: &mut [f32]) {
+    map_pixels(l_r, c_g, h_b, |lch| {
+        let [l, a, b] = decode_lab(lch_to_lab_inner(lch));
+        lab_to_rgb_inner(l, a, b)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use nanorand::Rng;
+
+    use crate::core_filters::colorspace::rgb_to_lab::{
+        lab_to_lch_inner, lab_to_rgb_inner, lch_to_lab_inner, rgb_to_lab_inner
+    };
+
+    #[test]
+    fn test_known_values() {
+        let white = rgb_to_lab_inner(1.0, 1.0, 1.0);
+        assert!((white[0] - 100.0).abs() < 0.01);
+        assert!(white[1].abs() < 0.01 && white[2].abs() < 0.01);
+
+        let red = rgb_to_lab_inner(1.0, 0.0, 0.0);
+        let expected = [53.24, 80.09, 67.20];
+        for (r, e) in red.iter().zip(expected) {
+            assert!((r - e).abs() < 0.05, "{red:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn test_round_trip_random() {
+        const T_EPSILON: f32 = 0.001;
+        let mut rand = nanorand::WyRand::new();
+
+        for _ in 0..100 {
+            let rgb: [f32; 3] = [rand.generate(), rand.generate(), rand.generate()];
+            let [l, a, b] = rgb_to_lab_inner(rgb[0], rgb[1], rgb[2]);
+
+            let encoded = [l / 100.0, (a + 128.0) / 255.0, (b + 128.0) / 255.0];
+            let lab = lch_to_lab_inner(lab_to_lch_inner(encoded));
+            let [l, a, b] = [
+                lab[0] * 100.0,
+                lab[1] * 255.0 - 128.0,
+                lab[2] * 255.0 - 128.0
+            ];
+
+            let result = lab_to_rgb_inner(l, a, b);
+            rgb.iter()
+                .zip(result.iter())
+                .for_each(|(e, f)| assert!((e - f).abs() <= T_EPSILON, "{}!={}", e, f));
+        }
+    }
+}
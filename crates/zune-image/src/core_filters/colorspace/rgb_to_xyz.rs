@@ -0,0 +1,149 @@
+//! sRGB to CIE XYZ conversions
+//!
+//! XYZ values are relative to a reference white as described in [`ColorSpace::XYZ`],
+//! colors are adapted from the D65 white of sRGB to the reference white with a
+//! [chromatic adaptation transform](crate::core_filters::colorspace::adaptation).
+//!
+//! [`ColorSpace::XYZ`]: zune_core::colorspace::ColorSpace::XYZ
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use zune_core::colorspace::{ChromaticAdaptation, WhitePoint};
+
+use crate::core_filters::colorspace::adaptation::adaptation_matrix;
+
+/// Linear sRGB to XYZ, rows are X, Y and Z
+pub(crate) const RGB_TO_XYZ: [[f32; 3]; 3] = [
+    [0.412_456_4, 0.357_576_1, 0.180_437_5],
+    [0.212_672_9, 0.715_152_2, 0.072_175],
+    [0.019_333_9, 0.119_192, 0.950_304_1]
+];
+
+/// XYZ to linear sRGB, the inverse of [`RGB_TO_XYZ`]
+pub(crate) const XYZ_TO_RGB: [[f32; 3]; 3] = [
+    [3.240_454_2, -1.537_138_5, -0.498_531_4],
+    [-0.969_266, 1.876_010_8, 0.041_556],
+    [0.055_643_4, -0.204_025_9, 1.057_225_2]
+];
+
+#[inline(always)]
+pub(crate) fn srgb_to_linear(x: f32) -> f32 {
+    if x <= 0.040_45 {
+        x / 12.92
+    } else {
+        ((x + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+#[inline(always)]
+pub(crate) fn linear_to_srgb(x: f32) -> f32 {
+    if x <= 0.003_130_8 {
+        x * 12.92
+    } else {
+        1.055 * x.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+#[inline(always)]
+pub(crate) fn mul(matrix: &[[f32; 3]; 3], v: [f32; 3]) -> [f32; 3] {
+    matrix.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+}
+
+pub(crate) fn mul_matrices(a: &[[f32; 3]; 3], b: &[[f32; 3]; 3]) -> [[f32; 3]; 3] {
+    a.map(|row| [0, 1, 2].map(|j| row[0] * b[0][j] + row[1] * b[1][j] + row[2] * b[2][j]))
+}
+
+/// Convert channels relative to one reference white to channels relative to another one
+pub fn adapt_xyz(
+    x: &mut [f32], y: &mut [f32], z: &mut [f32], from: WhitePoint, to: WhitePoint,
+    method: ChromaticAdaptation
+) {
+    let (from_white, to_white) = (from.to_xyz(), to.to_xyz());
+    let mut matrix = adaptation_matrix(from, to, method);
+    // fold the normalization into the matrix
+    for row in &mut matrix {
+        for (value, white) in row.iter_mut().zip(from_white) {
+            *value *= white;
+        }
+    }
+    for (row, white) in matrix.iter_mut().zip(to_white) {
+        row.iter_mut().for_each(|value| *value /= white);
+    }
+    for ((x, y), z) in x.iter_mut().zip(y.iter_mut()).zip(z.iter_mut()) {
+        [*x, *y, *z] = mul(&matrix, [*x, *y, *z]);
+    }
+}
+
+pub fn rgb_to_xyz(
+    r_x: &mut [f32], g_y: &mut [f32], b_z: &mut [f32], white: WhitePoint,
+    method: ChromaticAdaptation
+) {
+    let matrix = mul_matrices(
+        &adaptation_matrix(WhitePoint::D65, white, method),
+        &RGB_TO_XYZ
+    );
+    let white = white.to_xyz();
+
+    for ((r, g), b) in r_x.iter_mut().zip(g_y.iter_mut()).zip(b_z.iter_mut()) {
+        let linear = [*r, *g, *b].map(|x| srgb_to_linear(x.clamp(0.0, 1.0)));
+        let xyz = mul(&matrix, linear);
+
+        *r = xyz[0] / white[0];
+        *g = xyz[1] / white[1];
+        *b = xyz[2] / white[2];
+    }
+}
+
+/// Convert XYZ to sRGB, colors outside the sRGB gamut are clipped
+pub fn xyz_to_rgb(
+    x_r: &mut [f32], y_g: &mut [f32], z_b: &mut [f32], white: WhitePoint,
+    method: ChromaticAdaptation
+) {
+    let matrix = mul_matrices(
+        &XYZ_TO_RGB,
+        &adaptation_matrix(white, WhitePoint::D65, method)
+    );
+    let white = white.to_xyz();
+
+    for ((x, y), z) in x_r.iter_mut().zip(y_g.iter_mut()).zip(z_b.iter_mut()) {
+        let xyz = [*x * white[0], *y * white[1], *z * white[2]];
+        let rgb = mul(&matrix, xyz).map(|x| linear_to_srgb(x.clamp(0.0, 1.0)));
+
+        [*x, *y, *z] = rgb;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::{ChromaticAdaptation, WhitePoint};
+
+    use crate::core_filters::colorspace::rgb_to_xyz::{adapt_xyz, rgb_to_xyz, xyz_to_rgb};
+
+    #[test]
+    fn test_round_trip_between_white_points() {
+        let (mut r, mut g, mut b) = ([0.1, 0.9, 1.0], [0.5, 0.2, 1.0], [0.7, 0.0, 1.0]);
+        let expected = (r, g, b);
+
+        let method = ChromaticAdaptation::CAT02;
+        rgb_to_xyz(&mut r, &mut g, &mut b, WhitePoint::D50, method);
+        // white is 1 relative to the reference white
+        assert!((r[2] - 1.0).abs() < 1e-3 && (b[2] - 1.0).abs() < 1e-3);
+
+        adapt_xyz(
+            &mut r,
+            &mut g,
+            &mut b,
+            WhitePoint::D50,
+            WhitePoint::E,
+            method
+        );
+        xyz_to_rgb(&mut r, &mut g, &mut b, WhitePoint::E, method);
+
+        for (a, e) in [r, g, b]
+            .iter()
+            .flatten()
+            .zip([expected.0, expected.1, expected.2].iter().flatten())
+        {
+            assert!((a - e).abs() < 1e-3, "{a} {e}");
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! RGB to YCbCr conversions
+//!
+//! This uses the full range BT.601 coefficients as used by JPEG (JFIF), values are in 0..1
+//! with Cb and Cr centered around 0.5
+//!
+//! Adobe YCCK stores the CMY channels of CMYK as YCbCr. The colors decoded from YCC are
+//! the inverse of the inverted CMY channels Adobe CMYK stores, i.e. `C = 1 - R`,
+//! matching libjpeg and the JPEG decoder
+#[inline(always)]
+fn rgb_to_ycbcr_inner(r: f32, g: f32, b: f32) -> [f32; 3] {
+    let y = 0.299 * r + 0.587 * g + 0.114 * b;
+    let cb = -0.168_736 * r - 0.331_264 * g + 0.5 * b + 0.5;
+    let cr = 0.5 * r - 0.418_688 * g - 0.081_312 * b + 0.5;
+
+    [y, cb, cr]
+}
+
+#[inline(always)]
+fn ycbcr_to_rgb_inner(y: f32, cb: f32, cr: f32) -> [f32; 3] {
+    let (cb, cr) = (cb - 0.5, cr - 0.5);
+
+    let r = y + 1.402 * cr;
+    let g = y - 0.344_136 * cb - 0.714_136 * cr;
+    let b = y + 1.772 * cb;
+
+    [r, g, b].map(|x| x.clamp(0.0, 1.0))
+}
+
+pub fn rgb_to_ycbcr(r_y: &mut [f32], g_cb: &mut [f32], b_cr: &mut [f32]) {
+    for ((r, g), b) in r_y.iter_mut().zip(g_cb.iter_mut()).zip(b_cr.iter_mut()) {
+        [*r, *g, *b] = rgb_to_ycbcr_inner(*r, *g, *b);
+    }
+}
+
+pub fn ycbcr_to_rgb(y_r: &mut [f32], cb_g: &mut [f32], cr_b: &mut [f32]) {
+    for ((y, cb), cr) in y_r.iter_mut().zip(cb_g.iter_mut()).zip(cr_b.iter_mut()) {
+        [*y, *cb, *cr] = ycbcr_to_rgb_inner(*y, *cb, *cr);
+    }
+}
+
+pub fn ycck_to_cmyk(y_c: &mut [f32], cb_m: &mut [f32], cr_y: &mut [f32]) {
+    for ((y, cb), cr) in y_c.iter_mut().zip(cb_m.iter_mut()).zip(cr_y.iter_mut()) {
+        [*y, *cb, *cr] = ycbcr_to_rgb_inner(*y, *cb, *cr).map(|x| 1.0 - x);
+    }
+}
+
+pub fn cmyk_to_ycck(c_y: &mut [f32], m_cb: &mut [f32], y_cr: &mut [f32]) {
+    for ((c, m), y) in c_y.iter_mut().zip(m_cb.iter_mut()).zip(y_cr.iter_mut()) {
+        [*c, *m, *y] = rgb_to_ycbcr_inner(1.0 - *c, 1.0 - *m, 1.0 - *y);
+    }
+}
@@ -53,6 +53,68 @@ fn test_real_time_rgb_to_cmyk() {
     assert!(new_img == im, "RGB to CYMK failed or diverged");
 }
 
+#[test]
+fn test_real_time_ycbcr_to_rgb() {
+    use zune_core::options::DecoderOptions;
+    use zune_jpeg::JpegDecoder;
+
+    use crate::traits::DecoderTrait;
+
+    let mut file = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    file.pop();
+    file.pop();
+
+    let decode_ycbcr = |name: &str| {
+        let data = std::fs::read(file.join("test-images/jpeg").join(name)).unwrap();
+        let opts = DecoderOptions::new_fast().jpeg_set_out_colorspace(ColorSpace::YCbCr);
+        let mut decoder: Box<dyn DecoderTrait> =
+            Box::new(JpegDecoder::new_with_options(ZCursor::new(data), opts));
+        decoder.decode().unwrap()
+    };
+
+    let mut im = decode_ycbcr("sampling_factors.jpg");
+    assert_eq!(im.colorspace(), ColorSpace::YCbCr);
+    im.convert_color(ColorSpace::RGB).unwrap();
+
+    let new_img = Image::open(file.join("test-images/jpeg/sampling_factors.jpg")).unwrap();
+    // the jpeg decoder uses fixed point conversions
+    for (a, e) in im
+        .flatten_frames::<u8>()
+        .iter()
+        .flatten()
+        .zip(new_img.flatten_frames::<u8>().iter().flatten())
+    {
+        assert!(a.abs_diff(*e) <= 2, "{a} {e}");
+    }
+    // CMYK images can't be returned as YCbCr
+    assert_eq!(decode_ycbcr("cymk.jpg").colorspace(), ColorSpace::RGB);
+}
+
+#[test]
+fn test_luma_coefficients() {
+    use zune_core::colorspace::LumaCoefficients;
+
+    let to_luma = |luma: LumaCoefficients, linear_light: bool| {
+        let mut image = Image::from_u8(&[0, 255, 0, 255, 0, 0], 2, 1, ColorSpace::RGB);
+        ColorspaceConv::new(ColorSpace::Luma)
+            .set_luma_coefficients(luma)
+            .set_linear_light_luma(linear_light)
+            .execute(&mut image)
+            .unwrap();
+        image.flatten_frames::<u8>()[0].clone()
+    };
+    assert_eq!(to_luma(LumaCoefficients::BT709, false), [182, 54]);
+    assert_eq!(to_luma(LumaCoefficients::Equal, false), [85, 85]);
+    // the default keeps using the optimized routines
+    let bt601 = to_luma(LumaCoefficients::BT601, false);
+    assert!(
+        bt601[0].abs_diff(150) <= 1 && bt601[1].abs_diff(76) <= 1,
+        "{bt601:?}"
+    );
+    // a luminance of 0.2126 is encoded to about half of the maximum value
+    assert_eq!(to_luma(LumaCoefficients::BT709, true)[1], 127);
+}
+
 fn test_helper(im1: &Image, im2: &Image, im3: &Image, color: ColorSpace) {
     let filter = ColorspaceConv::new(color);
     filter
@@ -86,6 +148,11 @@ fn single_tests(u8_im: &Image, u16_im: &Image, f32_im: &Image) {
     test_helper(u8_im, u16_im, f32_im, ColorSpace::ARGB);
     test_helper(u8_im, u16_im, f32_im, ColorSpace::HSL);
     test_helper(u8_im, u16_im, f32_im, ColorSpace::HSV);
+    test_helper(u8_im, u16_im, f32_im, ColorSpace::Lab);
+    test_helper(u8_im, u16_im, f32_im, ColorSpace::LCh);
+    test_helper(u8_im, u16_im, f32_im, ColorSpace::OKLab);
+    test_helper(u8_im, u16_im, f32_im, ColorSpace::OKLCh);
+    test_helper(u8_im, u16_im, f32_im, ColorSpace::XYZ);
 }
 #[test]
 fn test_rgb_to_other_colors() {
@@ -120,6 +187,57 @@ fn test_hsl_to_other_colors() {
     single_tests(&u8_im, &u16_im, &f32_im);
 }
 #[test]
+fn test_lab_to_other_colors() {
+    let [u8_im, u16_im, f32_im] = create_image(ColorSpace::Lab);
+    single_tests(&u8_im, &u16_im, &f32_im);
+}
+
+#[test]
+fn test_lab_round_trip_u16() {
+    let pixels = [65535, 0, 0, 1000, 30000, 60000];
+    let mut image = Image::from_u16(&pixels, 2, 1, ColorSpace::RGB);
+
+    image.convert_color(ColorSpace::LCh).unwrap();
+    image.convert_color(ColorSpace::Lab).unwrap();
+    image.convert_color(ColorSpace::RGB).unwrap();
+
+    let result = image.flatten_frames::<u16>().remove(0);
+    // storing Lab in 16 bits loses a bit of precision in dark channels
+    for (a, b) in pixels.iter().zip(result) {
+        assert!(a.abs_diff(b) < 64, "{a} {b}");
+    }
+}
+#[test]
+fn test_xyz_white_point_round_trip() {
+    use zune_core::colorspace::WhitePoint;
+
+    let pixels = [250, 10, 30, 40, 200, 90];
+    let mut image = Image::from_u8(&pixels, 2, 1, ColorSpace::RGB);
+    image
+        .convert_depth(zune_core::bit_depth::BitDepth::Float32)
+        .unwrap();
+
+    ColorspaceConv::new(ColorSpace::XYZ)
+        .set_white_point(WhitePoint::D50)
+        .execute(&mut image)
+        .unwrap();
+    ColorspaceConv::new(ColorSpace::XYZ)
+        .set_white_point(WhitePoint::Custom(0.3, 0.3))
+        .execute(&mut image)
+        .unwrap();
+    assert_eq!(image.metadata().white_point(), WhitePoint::Custom(0.3, 0.3));
+
+    image.convert_color(ColorSpace::RGB).unwrap();
+    image
+        .convert_depth(zune_core::bit_depth::BitDepth::Eight)
+        .unwrap();
+
+    let result = image.flatten_frames::<u8>().remove(0);
+    for (a, b) in pixels.iter().zip(result) {
+        assert!(a.abs_diff(b) <= 1, "{a} {b}");
+    }
+}
+#[test]
 fn test_luma_to_other_colors() {
     let [u8_im, u16_im, f32_im] = create_image(ColorSpace::Luma);
     single_tests(&u8_im, &u16_im, &f32_im);
@@ -130,3 +248,73 @@ fn test_luma_a_to_other_colors() {
     let [u8_im, u16_im, f32_im] = create_image(ColorSpace::LumaA);
     single_tests(&u8_im, &u16_im, &f32_im);
 }
+
+#[test]
+fn test_registered_multiband_conversion() {
+    use core::num::NonZeroU32;
+
+    use crate::core_filters::colorspace::registry::{
+        find_conversion_path, register_conversion, ConversionStep
+    };
+    use crate::errors::ImageErrors;
+
+    let bands = ColorSpace::MultiBand(NonZeroU32::new(7).unwrap());
+
+    fn first_three(image: &mut Image) -> Result<(), ImageErrors> {
+        for frame in image.frames_mut() {
+            frame.channels_vec().truncate(3);
+        }
+        Ok(())
+    }
+    let mut image = Image::fill(100_u8, bands, 10, 10);
+    assert!(image.convert_color(ColorSpace::HSV).is_err());
+
+    register_conversion(bands, ColorSpace::RGB, first_three);
+    assert_eq!(
+        find_conversion_path(bands, ColorSpace::HSV),
+        Some(vec![
+            ConversionStep::Registered(bands, ColorSpace::RGB),
+            ConversionStep::Builtin(ColorSpace::RGB, ColorSpace::HSV)
+        ])
+    );
+    image.convert_color(ColorSpace::HSV).unwrap();
+    assert_eq!(image.colorspace(), ColorSpace::HSV);
+    assert_eq!(
+        image.frames_ref()[0]
+            .channels_ref(ColorSpace::HSV, false)
+            .len(),
+        3
+    );
+    // no conversion back was registered
+    assert!(image.convert_color(bands).is_err());
+}
+
+#[test]
+fn test_ycck_to_rgb() {
+    // gray YCC with some black, the jpeg decoder gives (255 - 100) * 200 / 255
+    let mut image = Image::from_u8(&[100, 128, 128, 200], 1, 1, ColorSpace::YCCK);
+    image.convert_color(ColorSpace::RGB).unwrap();
+
+    for value in &image.flatten_frames::<u8>()[0] {
+        assert!(value.abs_diff(122) <= 2, "{value}");
+    }
+}
+
+#[test]
+fn test_ycck_cmyk_round_trip() {
+    let pixels: Vec<u8> = (0..400).map(|x| (x * 7 % 256) as u8).collect();
+    let mut image = Image::from_u8(&pixels, 10, 10, ColorSpace::CMYK);
+    let expected = {
+        let mut rgb = image.clone();
+        rgb.convert_color(ColorSpace::RGB).unwrap();
+        rgb.flatten_frames::<u8>()[0].clone()
+    };
+
+    image.convert_color(ColorSpace::YCCK).unwrap();
+    assert_eq!(image.colorspace(), ColorSpace::YCCK);
+    image.convert_color(ColorSpace::RGB).unwrap();
+
+    for (a, e) in image.flatten_frames::<u8>()[0].iter().zip(&expected) {
+        assert!(a.abs_diff(*e) <= 3, "{a} {e}");
+    }
+}
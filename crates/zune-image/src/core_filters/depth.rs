@@ -17,6 +17,7 @@
 //!|BitDepth::Eight  | [`u8`]        |0   - 255  |
 //!|BitDepth::Sixteen| [`u16`]       |0   - 65535|
 //!|BitDepth::F32    | [`f32`]       |0.0 - 1.0  |
+//!|BitDepth::F16    | [`F16`]       |0.0 - 1.0  |
 //!  
 //!
 //! Conversions are supported from any depth to another, both
@@ -26,13 +27,29 @@
 //! when moving from `BitDepth::Eight` to `BitDepth::F32`, the library will automatically
 //! divide all pixels by `255.0` after converting them to f32's
 //!
+//! Half float images are converted to and from other depths through `f32`, since
+//! operations don't work on half floats, convert them to [`BitDepth::Float32`] first.
+//!
+//! [`F16`]: crate::half::F16
+//! # Rounding and dithering
+//! By default conversions to a lower precision truncate, which darkens the image slightly
+//! and produces visible bands in smooth gradients. [`Depth::set_dither`] selects how
+//! values are quantized, see [`Dither`] for the options.
+//!
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
 use zune_core::bit_depth::{BitDepth, BitType};
 use zune_core::log::trace;
 
 use crate::channel::Channel;
 use crate::errors::ImageErrors;
+use crate::half::F16;
 use crate::image::Image;
-use crate::traits::OperationsTrait;
+use crate::metadata::PixelLayout;
+use crate::traits::{OperationsTrait, Parameter, ParameterKind, ParameterValue};
 
 /// Convert an image depth from u16 to u8
 ///
@@ -88,16 +105,143 @@ pub(crate) fn depth_u8_to_u16(from: &[u8], to: &mut [u16], max_value: u16) {
     }
 }
 
+/// How pixels are quantized when converting to a depth with less precision
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Default)]
+pub enum Dither {
+    /// Drop the fractional part, this is the fastest but darkens the image
+    /// slightly
+    #[default]
+    Truncate,
+    /// Round to the nearest value
+    Round,
+    /// Add an 8x8 Bayer threshold pattern before rounding
+    ///
+    /// This hides banding in gradients with a regular pattern and works independently
+    /// on every pixel
+    Ordered,
+    /// Floyd-Steinberg error diffusion
+    ///
+    /// The quantization error of every pixel is spread to its neighbours, hiding banding
+    /// with less visible patterns than [`Dither::Ordered`]
+    FloydSteinberg
+}
+
+/// 8x8 Bayer matrix for ordered dithering
+const BAYER_8X8: [[u8; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21]
+];
+
+/// Quantize values in 0..1 to integers in 0..=max_value
+///
+/// # Arguments
+/// - `from`: Normalized pixels of one channel
+/// - `to`: Output pixels
+/// - `width`: Image width, needed for dithering patterns
+/// - `max_value`: Maximum value of the output
+/// - `dither`: How values are quantized
+/// - `convert`: Convert an integral float in 0..=max_value to the output type
+#[allow(clippy::cast_precision_loss)]
+fn quantize<T: Copy>(
+    from: &[f32], to: &mut [T], width: usize, max_value: f32, dither: Dither, convert: fn(f32) -> T
+) {
+    let width = width.max(1);
+
+    match dither {
+        Dither::Truncate => {
+            for (old, new) in from.iter().zip(to.iter_mut()) {
+                *new = convert((old * max_value).clamp(0.0, max_value).trunc());
+            }
+        }
+        Dither::Round => {
+            for (old, new) in from.iter().zip(to.iter_mut()) {
+                *new = convert((old * max_value).round().clamp(0.0, max_value));
+            }
+        }
+        Dither::Ordered => {
+            for (y, (old_row, new_row)) in from.chunks(width).zip(to.chunks_mut(width)).enumerate()
+            {
+                let bayer_row = &BAYER_8X8[y % 8];
+
+                for (x, (old, new)) in old_row.iter().zip(new_row.iter_mut()).enumerate() {
+                    let threshold = (f32::from(bayer_row[x % 8]) + 0.5) / 64.0 - 0.5;
+                    *new = convert((old * max_value + threshold).round().clamp(0.0, max_value));
+                }
+            }
+        }
+        Dither::FloydSteinberg => {
+            // errors for the current and the next row, padded by one on each side
+            let mut current = vec![0.0_f32; width + 2];
+            let mut next = vec![0.0_f32; width + 2];
+
+            for (old_row, new_row) in from.chunks(width).zip(to.chunks_mut(width)) {
+                for (x, (old, new)) in old_row.iter().zip(new_row.iter_mut()).enumerate() {
+                    let value = old * max_value + current[x + 1];
+                    let quantized = value.round().clamp(0.0, max_value);
+                    let error = value - quantized;
+
+                    current[x + 2] += error * (7.0 / 16.0);
+                    next[x] += error * (3.0 / 16.0);
+                    next[x + 1] += error * (5.0 / 16.0);
+                    next[x + 2] += error * (1.0 / 16.0);
+
+                    *new = convert(quantized);
+                }
+                core::mem::swap(&mut current, &mut next);
+                next.fill(0.0);
+            }
+        }
+    }
+}
+
 /// Change the image's bit depth from it's initial
 /// value to the one specified by this operation.
+///
+/// # Example
+/// Convert a 16 bit image to 8 bits with error diffusion
+/// ```
+/// use zune_core::bit_depth::BitDepth;
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::core_filters::depth::{Depth, Dither};
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+///
+/// let mut image = Image::fill(30000_u16, ColorSpace::RGB, 10, 10);
+///
+/// Depth::new(BitDepth::Eight)
+///     .set_dither(Dither::FloydSteinberg)
+///     .execute(&mut image)
+///     .unwrap();
+///
+/// assert_eq!(image.depth(), BitDepth::Eight);
+/// ```
 #[derive(Copy, Clone)]
 pub struct Depth {
-    depth: BitDepth
+    depth:  BitDepth,
+    dither: Dither
 }
 
 impl Depth {
     pub fn new(depth: BitDepth) -> Depth {
-        Depth { depth }
+        Depth {
+            depth,
+            dither: Dither::default()
+        }
+    }
+    /// Set how values are quantized, the default is [`Dither::Truncate`]
+    ///
+    /// Conversions to a depth with more precision round for all options except
+    /// [`Dither::Truncate`], they never need dithering
+    #[must_use]
+    pub fn set_dither(mut self, dither: Dither) -> Self {
+        self.dither = dither;
+        self
     }
 }
 
@@ -106,6 +250,15 @@ impl OperationsTrait for Depth {
         "Depth"
     }
 
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            name:        "depth",
+            description: "Bit depth of the image",
+            kind:        ParameterKind::Choice(&["u8", "u16", "f32"]),
+            default:     ParameterValue::Choice("u8")
+        }]
+    }
+
     fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
         let image_depth = image.depth();
 
@@ -113,6 +266,19 @@ impl OperationsTrait for Depth {
             trace!("Image depth already matches requested, no-op");
             return Ok(());
         }
+        let half = image_depth == BitDepth::Float16 || self.depth == BitDepth::Float16;
+
+        if self.dither != Dither::Truncate || half {
+            let width = image.dimensions().0;
+
+            for channel in image.channels_mut(false) {
+                *channel = convert_channel(channel, width, image_depth, self.depth, self.dither)?;
+            }
+            trace!("Image depth changed to {:?}", self.depth);
+            image.set_depth(self.depth);
+
+            return Ok(());
+        }
 
         for channel in image.channels_mut(false) {
             match (image_depth, self.depth) {
@@ -210,6 +376,193 @@ impl OperationsTrait for Depth {
         Ok(())
     }
     fn supported_types(&self) -> &'static [BitType] {
-        &[BitType::U8, BitType::U16, BitType::F32]
+        &[BitType::U8, BitType::U16, BitType::F32, BitType::F16]
+    }
+
+    fn supported_layouts(&self) -> &'static [PixelLayout] {
+        match self.dither {
+            // every sample is converted on its own
+            Dither::Truncate | Dither::Round => &[PixelLayout::Planar, PixelLayout::Interleaved],
+            // dither patterns and error diffusion depend on the pixel position
+            Dither::Ordered | Dither::FloydSteinberg => &[PixelLayout::Planar]
+        }
+    }
+}
+
+/// Convert a channel between depths, quantizing with `dither`
+#[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+fn convert_channel(
+    channel: &Channel, width: usize, from: BitDepth, to: BitDepth, dither: Dither
+) -> Result<Channel, ImageErrors> {
+    let normalized: Vec<f32> = match from.bit_type() {
+        BitType::U8 => {
+            let recip = 1.0 / 255.0;
+            channel
+                .reinterpret_as::<u8>()?
+                .iter()
+                .map(|x| f32::from(*x) * recip)
+                .collect()
+        }
+        BitType::U16 => {
+            let recip = 1.0 / f32::from(from.max_value());
+            channel
+                .reinterpret_as::<u16>()?
+                .iter()
+                .map(|x| f32::from(*x) * recip)
+                .collect()
+        }
+        BitType::F32 => channel.reinterpret_as::<f32>()?.to_vec(),
+        BitType::F16 => channel
+            .reinterpret_as::<F16>()?
+            .iter()
+            .map(|x| x.to_f32())
+            .collect(),
+        _ => {
+            return Err(ImageErrors::ImageOperationNotImplemented(
+                "Depth",
+                from.bit_type()
+            ))
+        }
+    };
+    // increasing precision is lossless, only rounding is needed
+    let dither = if from.size_of() < to.size_of() { Dither::Round } else { dither };
+
+    match to.bit_type() {
+        BitType::U8 => {
+            let mut new_channel = Channel::new_with_length::<u8>(normalized.len());
+            let pixels = new_channel.reinterpret_as_mut::<u8>()?;
+            quantize(&normalized, pixels, width, 255.0, dither, |x| x as u8);
+            Ok(new_channel)
+        }
+        BitType::U16 => {
+            let max_value = f32::from(to.max_value());
+            let mut new_channel = Channel::new_with_length::<u16>(normalized.len() * 2);
+            let pixels = new_channel.reinterpret_as_mut::<u16>()?;
+            quantize(&normalized, pixels, width, max_value, dither, |x| x as u16);
+            Ok(new_channel)
+        }
+        BitType::F32 => {
+            let mut new_channel = Channel::new_with_length::<f32>(normalized.len() * 4);
+            new_channel
+                .reinterpret_as_mut::<f32>()?
+                .copy_from_slice(&normalized);
+            Ok(new_channel)
+        }
+        BitType::F16 => {
+            let mut new_channel = Channel::new_with_length::<F16>(normalized.len() * 2);
+
+            for (pixel, value) in new_channel
+                .reinterpret_as_mut::<F16>()?
+                .iter_mut()
+                .zip(&normalized)
+            {
+                *pixel = F16::from_f32(*value);
+            }
+            Ok(new_channel)
+        }
+        _ => Err(ImageErrors::ImageOperationNotImplemented(
+            "Depth",
+            to.bit_type()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::bit_depth::BitDepth;
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::core_filters::depth::{Depth, Dither};
+    use crate::image::Image;
+    use crate::traits::OperationsTrait;
+
+    fn gradient_to_u8(dither: Dither) -> Vec<u8> {
+        // a gradient spanning just two 8 bit values
+        let pixels: Vec<u16> = (0..64 * 64).map(|x| 25700 + (x % 64) * 4).collect();
+        let mut image = Image::from_u16(&pixels, 64, 64, ColorSpace::Luma);
+
+        Depth::new(BitDepth::Eight)
+            .set_dither(dither)
+            .execute(&mut image)
+            .unwrap();
+        image.flatten_frames::<u8>()[0].clone()
+    }
+
+    #[test]
+    fn test_round_and_truncate() {
+        let mut image = Image::from_f32(
+            &[0.0, 0.499 / 255.0, 0.6 / 255.0, 1.0],
+            4,
+            1,
+            ColorSpace::Luma
+        );
+        let mut rounded = image.clone();
+
+        image.convert_depth(BitDepth::Eight).unwrap();
+        assert_eq!(image.flatten_frames::<u8>()[0], [0, 0, 0, 255]);
+
+        Depth::new(BitDepth::Eight)
+            .set_dither(Dither::Round)
+            .execute(&mut rounded)
+            .unwrap();
+        assert_eq!(rounded.flatten_frames::<u8>()[0], [0, 0, 1, 255]);
+    }
+
+    #[test]
+    fn test_dithering_preserves_mean() {
+        // the exact mean of the gradient in 8 bits
+        let expected = (0..64).map(|x| (25700 + x * 4) as f32 / 257.0).sum::<f32>() / 64.0;
+
+        for dither in [Dither::Ordered, Dither::FloydSteinberg] {
+            let pixels = gradient_to_u8(dither);
+            let mean = pixels.iter().map(|x| f32::from(*x)).sum::<f32>() / pixels.len() as f32;
+
+            assert!(
+                (mean - expected).abs() < 0.05,
+                "{dither:?} {mean} {expected}"
+            );
+            // dithering mixes both values instead of producing a hard edge
+            assert!(pixels.contains(&100) && pixels.contains(&101));
+        }
+        let truncated = gradient_to_u8(Dither::Truncate);
+        assert!(truncated.iter().all(|x| *x == 100));
+    }
+
+    #[test]
+    fn test_u8_to_u16_is_exact() {
+        let pixels: Vec<u8> = (0..=255).collect();
+        let mut image = Image::from_u8(&pixels, 16, 16, ColorSpace::Luma);
+
+        Depth::new(BitDepth::Sixteen)
+            .set_dither(Dither::Round)
+            .execute(&mut image)
+            .unwrap();
+
+        for (a, e) in image.flatten_frames::<u16>()[0].iter().zip(&pixels) {
+            assert_eq!(*a, u16::from(*e) * 257);
+        }
+    }
+
+    #[test]
+    fn test_half_float_conversions() {
+        use crate::half::F16;
+
+        // every 8 bit value survives a trip through half floats
+        let pixels: Vec<u8> = (0..=255).collect();
+        let mut image = Image::from_u8(&pixels, 16, 16, ColorSpace::Luma);
+
+        Depth::new(BitDepth::Float16).execute(&mut image).unwrap();
+        assert_eq!(image.depth(), BitDepth::Float16);
+        assert_eq!(image.channels_ref(false)[0].len(), 256 * 2);
+
+        Depth::new(BitDepth::Eight)
+            .set_dither(Dither::Round)
+            .execute(&mut image)
+            .unwrap();
+        assert_eq!(image.flatten_frames::<u8>()[0], pixels);
+
+        let mut image = Image::fill(F16::from_f32(0.25), ColorSpace::RGB, 8, 8);
+        Depth::new(BitDepth::Float32).execute(&mut image).unwrap();
+        assert!(image.flatten_frames::<f32>()[0].iter().all(|x| *x == 0.25));
     }
 }
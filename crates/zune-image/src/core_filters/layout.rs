@@ -0,0 +1,66 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Conversions between planar and interleaved storage
+//!
+//! Operations are run on the layout they declare support for, these routines
+//! convert the channels of all frames and update the image metadata.
+use alloc::vec;
+
+use bytemuck::{Pod, Zeroable};
+use zune_core::bit_depth::BitType;
+
+use crate::channel::Channel;
+use crate::deinterleave::{deinterleave_f32, deinterleave_u16, deinterleave_u8};
+use crate::errors::ImageErrors;
+use crate::frame::Frame;
+use crate::image::Image;
+use crate::metadata::PixelLayout;
+
+/// Convert the image to the layout `to`, updating its metadata
+///
+/// Does nothing if the image already is in that layout
+pub(crate) fn convert_layout(image: &mut Image, to: PixelLayout) -> Result<(), ImageErrors> {
+    if image.layout() == to {
+        return Ok(());
+    }
+    let colorspace = image.colorspace();
+    let bit_type = image.depth().bit_type();
+
+    for frame in image.frames_mut() {
+        let channels = match to {
+            PixelLayout::Interleaved => match bit_type {
+                BitType::U8 => vec![interleave::<u8>(frame)],
+                BitType::U16 => vec![interleave::<u16>(frame)],
+                BitType::F32 => vec![interleave::<f32>(frame)],
+                d => return Err(ImageErrors::ImageOperationNotImplemented("layout", d))
+            },
+            PixelLayout::Planar => {
+                let channel = &frame.channels_vec_ref()[0];
+
+                match bit_type {
+                    BitType::U8 => deinterleave_u8(channel.reinterpret_as()?, colorspace)?,
+                    BitType::U16 => deinterleave_u16(channel.reinterpret_as()?, colorspace)?,
+                    BitType::F32 => deinterleave_f32(channel.reinterpret_as()?, colorspace)?,
+                    d => return Err(ImageErrors::ImageOperationNotImplemented("layout", d))
+                }
+            }
+        };
+        frame.set_channels(channels);
+    }
+    image.metadata.set_layout(to);
+
+    Ok(())
+}
+
+fn interleave<T: Default + Copy + 'static + Pod + Zeroable>(frame: &Frame) -> Channel {
+    let pixels = frame.flatten::<T>();
+    let mut channel = Channel::new_with_capacity::<T>(pixels.len() * size_of::<T>());
+    channel.extend(&pixels);
+
+    channel
+}
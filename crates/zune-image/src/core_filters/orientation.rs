@@ -0,0 +1,139 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Apply exif orientations to images
+//!
+//! The transforms live here instead of using the flip, rotate and transpose operations
+//! of `zune-imageprocs` so that decoders can orient images while reading them.
+use bytemuck::Pod;
+use zune_core::bit_depth::BitType;
+
+use crate::channel::Channel;
+use crate::errors::ImageErrors;
+use crate::image::Image;
+use crate::metadata::{ExifOrientation, PixelLayout};
+
+impl Image {
+    /// Flip and rotate the image so that an image stored with `orientation`
+    /// is upright
+    ///
+    /// The metadata of the image is not changed, see [`auto_orient`](Self::auto_orient)
+    /// to apply and reset the exif orientation
+    ///
+    /// # Errors
+    /// If the image has an unsupported depth
+    pub fn apply_orientation(&mut self, orientation: ExifOrientation) -> Result<(), ImageErrors> {
+        if orientation == ExifOrientation::Normal {
+            return Ok(());
+        }
+        let layout = self.layout();
+        self.convert_layout(PixelLayout::Planar)?;
+
+        let (width, height) = self.dimensions();
+        let bit_type = self.depth().bit_type();
+
+        for frame in self.frames_mut() {
+            for channel in frame.channels_vec() {
+                *channel = match bit_type {
+                    BitType::U8 => orient::<u8>(channel, width, height, orientation)?,
+                    BitType::U16 => orient::<u16>(channel, width, height, orientation)?,
+                    BitType::F32 => orient::<f32>(channel, width, height, orientation)?,
+                    d => return Err(ImageErrors::ImageOperationNotImplemented("orient", d))
+                };
+            }
+        }
+        if orientation.swaps_dimensions() {
+            self.set_dimensions(height, width);
+
+            if let Some(resolution) = self.metadata.resolution() {
+                self.metadata.set_resolution(resolution.transposed());
+            }
+        }
+        self.convert_layout(layout)
+    }
+
+    /// Apply the exif orientation of the image and reset it
+    ///
+    /// Images without an orientation are not changed. This requires the
+    /// `metadata` feature, decoders do it when
+    /// [`DecoderOptions::set_auto_orient`](zune_core::options::DecoderOptions::set_auto_orient)
+    /// is set
+    ///
+    /// # Errors
+    /// If the image has an unsupported depth
+    #[cfg(feature = "metadata")]
+    #[cfg_attr(feature = "docs", doc(cfg(feature = "metadata")))]
+    pub fn auto_orient(&mut self) -> Result<(), ImageErrors> {
+        if let Some(orientation) = self.metadata.orientation() {
+            self.apply_orientation(orientation)?;
+            self.metadata.set_orientation(ExifOrientation::Normal);
+        }
+        Ok(())
+    }
+}
+
+fn orient<T: Pod + Default + 'static>(
+    channel: &Channel, width: usize, height: usize, orientation: ExifOrientation
+) -> Result<Channel, ImageErrors> {
+    let input = channel.reinterpret_as::<T>()?;
+    let mut out = Channel::new_with_length::<T>(size_of_val(input));
+    let output = out.reinterpret_as_mut::<T>()?;
+
+    let out_width = if orientation.swaps_dimensions() { height } else { width };
+
+    for (y, row) in input.chunks_exact(width).enumerate() {
+        for (x, pixel) in row.iter().enumerate() {
+            // where the pixel ends up once the image is upright
+            let (out_x, out_y) = match orientation {
+                ExifOrientation::Normal => (x, y),
+                ExifOrientation::FlipHorizontal => (width - 1 - x, y),
+                ExifOrientation::Rotate180 => (width - 1 - x, height - 1 - y),
+                ExifOrientation::FlipVertical => (x, height - 1 - y),
+                ExifOrientation::Transpose => (y, x),
+                ExifOrientation::Rotate90 => (height - 1 - y, x),
+                ExifOrientation::Transverse => (height - 1 - y, width - 1 - x),
+                ExifOrientation::Rotate270 => (y, width - 1 - x)
+            };
+            output[out_y * out_width + out_x] = *pixel;
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::image::Image;
+    use crate::metadata::ExifOrientation;
+
+    #[test]
+    fn test_orientations() {
+        // 3x2 image
+        // 0 1 2
+        // 3 4 5
+        let pixels = [0_u8, 1, 2, 3, 4, 5];
+        let expected: [(ExifOrientation, &[u8]); 8] = [
+            (ExifOrientation::Normal, &[0, 1, 2, 3, 4, 5]),
+            (ExifOrientation::FlipHorizontal, &[2, 1, 0, 5, 4, 3]),
+            (ExifOrientation::Rotate180, &[5, 4, 3, 2, 1, 0]),
+            (ExifOrientation::FlipVertical, &[3, 4, 5, 0, 1, 2]),
+            (ExifOrientation::Transpose, &[0, 3, 1, 4, 2, 5]),
+            (ExifOrientation::Rotate90, &[3, 0, 4, 1, 5, 2]),
+            (ExifOrientation::Transverse, &[5, 2, 4, 1, 3, 0]),
+            (ExifOrientation::Rotate270, &[2, 5, 1, 4, 0, 3])
+        ];
+        for (orientation, result) in expected {
+            let mut image = Image::from_u8(&pixels, 3, 2, ColorSpace::Luma);
+            image.apply_orientation(orientation).unwrap();
+
+            let dimensions = if orientation.swaps_dimensions() { (2, 3) } else { (3, 2) };
+            assert_eq!(image.dimensions(), dimensions);
+            assert_eq!(image.flatten_frames::<u8>()[0], result, "{orientation:?}");
+        }
+    }
+}
@@ -18,6 +18,9 @@
 //!The latter representation makes it easier for processing and it allows multi-threaded
 //! post-processing for scenarios where processing is slow.
 
+use alloc::vec;
+use alloc::vec::Vec;
+
 use bytemuck::{Pod, Zeroable};
 use zune_core::colorspace::ColorSpace;
 
@@ -277,6 +280,90 @@ pub fn deinterleave_f32(
     deinterleave_generic(interleaved_pixels, colorspace)
 }
 
+/// Separates image u8's into channels that already exist, reusing their memory
+///
+/// Every channel must hold as many u8's as there are pixels
+pub(crate) fn deinterleave_u8_into(
+    interleaved_pixels: &[u8], channels: &mut [Channel]
+) -> Result<(), ImageErrors> {
+    deinterleave_into(
+        interleaved_pixels,
+        channels,
+        de_interleave_three_channels_u8,
+        deinterleave_four_channels_u8
+    )
+}
+
+/// Separates u16's into channels that already exist, reusing their memory
+///
+/// Every channel must hold as many u16's as there are pixels
+pub(crate) fn deinterleave_u16_into(
+    interleaved_pixels: &[u16], channels: &mut [Channel]
+) -> Result<(), ImageErrors> {
+    deinterleave_into(
+        interleaved_pixels,
+        channels,
+        de_interleave_three_channels_u16,
+        de_interleave_four_channels_u16
+    )
+}
+
+#[allow(clippy::type_complexity)]
+fn deinterleave_into<T: Copy + 'static + Pod>(
+    interleaved_pixels: &[T], channels: &mut [Channel],
+    three: fn(&[T], &mut [T], &mut [T], &mut [T]),
+    four: fn(&[T], &mut [T], &mut [T], &mut [T], &mut [T])
+) -> Result<(), ImageErrors> {
+    let components = channels.len();
+
+    if components == 0 || !interleaved_pixels.len().is_multiple_of(components) {
+        return Err(ImageErrors::OperationsError(
+            ImageOperationsErrors::InvalidChannelLayout("Extra pixels in the colorspace")
+        ));
+    }
+    let size = interleaved_pixels.len() / components;
+
+    if channels
+        .iter()
+        .any(|c| c.len() != size * core::mem::size_of::<T>())
+    {
+        return Err(ImageErrors::GenericStr(
+            "Channel length does not match the pixel count"
+        ));
+    }
+    match channels {
+        [c1] => c1
+            .reinterpret_as_mut::<T>()?
+            .copy_from_slice(interleaved_pixels),
+        [c1, c2, c3] => three(
+            interleaved_pixels,
+            c1.reinterpret_as_mut()?,
+            c2.reinterpret_as_mut()?,
+            c3.reinterpret_as_mut()?
+        ),
+        [c1, c2, c3, c4] => four(
+            interleaved_pixels,
+            c1.reinterpret_as_mut()?,
+            c2.reinterpret_as_mut()?,
+            c3.reinterpret_as_mut()?,
+            c4.reinterpret_as_mut()?
+        ),
+        _ => {
+            for (pos, channel) in channels.iter_mut().enumerate() {
+                let channel = channel.reinterpret_as_mut::<T>()?;
+
+                for (pix, chunk) in channel
+                    .iter_mut()
+                    .zip(interleaved_pixels.chunks_exact(components))
+                {
+                    *pix = chunk[pos];
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use std::array;
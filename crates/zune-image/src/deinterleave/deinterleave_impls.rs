@@ -6,62 +6,93 @@
  * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
  */
 
+use zune_core::simd::{Dispatcher, Isa};
+
 use crate::deinterleave::scalar;
+#[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+use crate::deinterleave::{avx2, sse2, sse41};
+
+/// A three channel kernel, `(source, c1, c2, c3)`
+type ThreeChannelsFn<T> = unsafe fn(&[T], &mut [T], &mut [T], &mut [T]);
+/// A four channel kernel, `(source, c1, c2, c3, c4)`
+type FourChannelsFn<T> = unsafe fn(&[T], &mut [T], &mut [T], &mut [T], &mut [T]);
+
+// SAFETY: every kernel only uses instructions of its instruction set
+static THREE_CHANNELS_U8: Dispatcher<ThreeChannelsFn<u8>> = unsafe {
+    Dispatcher::new(
+        "de_interleave_three_channels_u8",
+        &[
+            #[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+            (Isa::Avx2, avx2::de_interleave_three_channels_avx2),
+            #[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+            (Isa::Sse41, sse41::de_interleave_three_channels_sse3_u8),
+            #[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+            (Isa::Sse2, sse2::de_interleave_three_channels_sse2),
+            (Isa::Scalar, scalar::de_interleave_three_channels_scalar)
+        ]
+    )
+};
+
+// SAFETY: every kernel only uses instructions of its instruction set
+static THREE_CHANNELS_U16: Dispatcher<ThreeChannelsFn<u16>> = unsafe {
+    Dispatcher::new(
+        "de_interleave_three_channels_u16",
+        &[
+            #[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+            (Isa::Avx2, avx2::de_interleave_three_channels_avx2),
+            (Isa::Scalar, scalar::de_interleave_three_channels_scalar)
+        ]
+    )
+};
+
+// SAFETY: every kernel only uses instructions of its instruction set
+static THREE_CHANNELS_F32: Dispatcher<ThreeChannelsFn<f32>> = unsafe {
+    Dispatcher::new(
+        "de_interleave_three_channels_f32",
+        &[
+            #[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+            (Isa::Avx2, avx2::de_interleave_three_channels_avx2),
+            (Isa::Scalar, scalar::de_interleave_three_channels_scalar)
+        ]
+    )
+};
+
+// SAFETY: every kernel only uses instructions of its instruction set
+static FOUR_CHANNELS_U8: Dispatcher<FourChannelsFn<u8>> = unsafe {
+    Dispatcher::new(
+        "deinterleave_four_channels_u8",
+        &[
+            #[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+            (Isa::Avx2, avx2::de_interleave_four_channels_avx2),
+            #[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+            (Isa::Sse41, sse41::de_interleave_four_channels_sse41),
+            (Isa::Scalar, scalar::de_interleave_four_channels_scalar)
+        ]
+    )
+};
+
+// SAFETY: every kernel only uses instructions of its instruction set
+static FOUR_CHANNELS_F32: Dispatcher<FourChannelsFn<f32>> = unsafe {
+    Dispatcher::new(
+        "de_interleave_four_channels_f32",
+        &[
+            #[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+            (Isa::Avx2, avx2::de_interleave_four_channels_avx2),
+            (Isa::Scalar, scalar::de_interleave_four_channels_scalar)
+        ]
+    )
+};
 
 pub fn de_interleave_three_channels_u8(source: &[u8], c1: &mut [u8], c2: &mut [u8], c3: &mut [u8]) {
-    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-    {
-        #[cfg(feature = "simd")]
-        {
-            use crate::deinterleave::avx2::de_interleave_three_channels_avx2;
-
-            if is_x86_feature_detected!("avx2") {
-                unsafe {
-                    return de_interleave_three_channels_avx2(source, c1, c2, c3);
-                }
-            }
-        }
-        #[cfg(feature = "simd")]
-        {
-            use crate::deinterleave::sse41::de_interleave_three_channels_sse3_u8;
-
-            if is_x86_feature_detected!("sse3") {
-                unsafe {
-                    return de_interleave_three_channels_sse3_u8(source, c1, c2, c3);
-                }
-            }
-        }
-        #[cfg(feature = "simd")]
-        {
-            use crate::deinterleave::sse2::de_interleave_three_channels_sse2;
-
-            if is_x86_feature_detected!("sse2") {
-                unsafe {
-                    return de_interleave_three_channels_sse2(source, c1, c2, c3);
-                }
-            }
-        }
-    }
-    crate::deinterleave::scalar::de_interleave_three_channels_scalar(source, c1, c2, c3);
+    // SAFETY: the dispatcher only returns kernels the cpu supports
+    unsafe { THREE_CHANNELS_U8.get()(source, c1, c2, c3) }
 }
 
 pub fn de_interleave_three_channels_u16(
     source: &[u16], c1: &mut [u16], c2: &mut [u16], c3: &mut [u16]
 ) {
-    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-    {
-        #[cfg(feature = "simd")]
-        {
-            use crate::deinterleave::avx2::de_interleave_three_channels_avx2;
-
-            if is_x86_feature_detected!("avx2") {
-                unsafe {
-                    return de_interleave_three_channels_avx2(source, c1, c2, c3);
-                }
-            }
-        }
-    }
-    scalar::de_interleave_three_channels_scalar(source, c1, c2, c3);
+    // SAFETY: the dispatcher only returns kernels the cpu supports
+    unsafe { THREE_CHANNELS_U16.get()(source, c1, c2, c3) }
 }
 
 pub fn de_interleave_four_channels_u16(
@@ -73,29 +104,8 @@ pub fn de_interleave_four_channels_u16(
 pub fn deinterleave_four_channels_u8(
     source: &[u8], c1: &mut [u8], c2: &mut [u8], c3: &mut [u8], c4: &mut [u8]
 ) {
-    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-    {
-        #[cfg(feature = "simd")]
-        {
-            use crate::deinterleave::avx2::de_interleave_four_channels_avx2;
-            if is_x86_feature_detected!("sse4.1") {
-                unsafe {
-                    return de_interleave_four_channels_avx2(source, c1, c2, c3, c4);
-                }
-            }
-        }
-        #[cfg(feature = "simd")]
-        {
-            use crate::deinterleave::sse41::de_interleave_four_channels_sse41;
-            if is_x86_feature_detected!("sse4.1") {
-                unsafe {
-                    return de_interleave_four_channels_sse41(source, c1, c2, c3, c4);
-                }
-            }
-        }
-    }
-
-    scalar::de_interleave_four_channels_scalar(source, c1, c2, c3, c4);
+    // SAFETY: the dispatcher only returns kernels the cpu supports
+    unsafe { FOUR_CHANNELS_U8.get()(source, c1, c2, c3, c4) }
 }
 
 /// De interleave interleaved images in float to separate color components
@@ -111,39 +121,15 @@ pub fn deinterleave_four_channels_u8(
 pub fn de_interleave_three_channels_f32(
     source: &[f32], c1: &mut [f32], c2: &mut [f32], c3: &mut [f32]
 ) {
-    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-    {
-        #[cfg(feature = "avx2")]
-        {
-            use crate::deinterleave::avx2::de_interleave_three_channels_avx2;
-
-            if is_x86_feature_detected!("avx2") {
-                unsafe {
-                    return de_interleave_three_channels_avx2(source, c1, c2, c3);
-                }
-            }
-        }
-    }
-    scalar::de_interleave_three_channels_scalar(source, c1, c2, c3);
+    // SAFETY: the dispatcher only returns kernels the cpu supports
+    unsafe { THREE_CHANNELS_F32.get()(source, c1, c2, c3) }
 }
 
 pub fn de_interleave_four_channels_f32(
     source: &[f32], c1: &mut [f32], c2: &mut [f32], c3: &mut [f32], c4: &mut [f32]
 ) {
-    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-    {
-        #[cfg(feature = "avx2")]
-        {
-            use crate::deinterleave::avx2::de_interleave_four_channels_avx2;
-
-            if is_x86_feature_detected!("avx2") {
-                unsafe {
-                    return de_interleave_four_channels_avx2(source, c1, c2, c3, c4);
-                }
-            }
-        }
-    }
-    scalar::de_interleave_four_channels_scalar(source, c1, c2, c3, c4);
+    // SAFETY: the dispatcher only returns kernels the cpu supports
+    unsafe { FOUR_CHANNELS_F32.get()(source, c1, c2, c3, c4) }
 }
 
 #[cfg(feature = "benchmarks")]
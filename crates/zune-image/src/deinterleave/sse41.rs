@@ -10,9 +10,9 @@
 #![cfg(feature = "simd")]
 
 #[cfg(target_arch = "x86")]
-use std::arch::x86::*;
+use core::arch::x86::*;
 #[cfg(target_arch = "x86_64")]
-use std::arch::x86_64::*;
+use core::arch::x86_64::*;
 
 use crate::deinterleave::scalar::{
     de_interleave_four_channels_scalar, de_interleave_three_channels_scalar
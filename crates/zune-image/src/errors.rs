@@ -7,12 +7,42 @@
  */
 
 //! Errors possible during image processing
-use std::any::TypeId;
-use std::fmt::{Debug, Display, Formatter};
-use std::io::Error;
+//!
+//! Every error has an [`ErrorKind`], telling e.g. a corrupt file apart from one using a
+//! feature that isn't supported or one exceeding a limit, with a [code](ErrorKind::code)
+//! that stays the same across releases.
+//!
+//! Errors from decoders are [`DecodeError`]s which carry the format and, when decoding
+//! through [`Image::read`](crate::image::Image::read), the byte offset the decoder had
+//! reached. Errors from operations are wrapped in [`ImageErrors::OperationFailed`] with the
+//! name of the operation, the original errors are available through
+//! [`source`](core::error::Error::source).
+//!
+//! # Example
+//! ```
+//! use zune_core::bytestream::ZCursor;
+//! use zune_core::options::DecoderOptions;
+//! use zune_image::codecs::ImageFormat;
+//! use zune_image::errors::ErrorKind;
+//! use zune_image::image::Image;
+//!
+//! // a PPM header promising more pixels than there are
+//! let result = Image::read(ZCursor::new(b"P5 4 4 255 12"), DecoderOptions::default());
+//! let Err(err) = result else { panic!("decoding succeeded") };
+//!
+//! assert_eq!(err.kind(), ErrorKind::Corrupt);
+//! assert_eq!(err.format(), Some(ImageFormat::PPM));
+//! assert!(err.offset().is_some());
+//! ```
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::any::TypeId;
+use core::fmt::{Debug, Display, Formatter};
 
 use zune_core::bit_depth::BitType;
 use zune_core::colorspace::ColorSpace;
+#[cfg(feature = "std")]
+use zune_core::icc::IccError;
 
 use crate::channel::ChannelErrors;
 use crate::codecs::ImageFormat;
@@ -36,8 +66,239 @@ pub enum ImageErrors {
     ChannelErrors(ChannelErrors),
     ImageDecoderNotIncluded(ImageFormat),
     ImageDecoderNotImplemented(ImageFormat),
+    #[cfg(feature = "std")]
     IoError(std::io::Error),
-    ImageOperationNotImplemented(&'static str, BitType)
+    ImageOperationNotImplemented(&'static str, BitType),
+    #[cfg(feature = "std")]
+    IccError(IccError),
+    /// The work was cancelled through a [`Progress`](crate::progress::Progress)
+    Cancelled,
+    /// A limit set in [`DecoderOptions`](zune_core::options::DecoderOptions) was exceeded,
+    /// the name of the limit, the value found and the limit
+    LimitsExceeded(&'static str, usize, usize),
+    /// A decoder failed
+    DecodeError(DecodeError),
+    /// The named operation failed with the error
+    OperationFailed(&'static str, Box<ImageErrors>)
+}
+
+/// The category of an error
+///
+/// Lets applications react to errors, e.g. reporting a corrupt upload to the user but
+/// logging an unsupported feature, without matching on every error variant
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The encoded data is corrupt or truncated
+    Corrupt,
+    /// The data uses a feature, format or colorspace that isn't supported
+    Unsupported,
+    /// A limit set in [`DecoderOptions`](zune_core::options::DecoderOptions) was exceeded
+    LimitExceeded,
+    /// The decoder or encoder for a format isn't included in this build
+    MissingCodec,
+    /// The image or the arguments don't fit the operation, e.g. mismatched dimensions
+    InvalidInput,
+    /// Reading or writing failed
+    Io,
+    /// The work was cancelled through a [`Progress`](crate::progress::Progress)
+    Cancelled,
+    /// Anything else
+    Other
+}
+
+impl ErrorKind {
+    /// A short code identifying the kind, stable across releases
+    ///
+    /// ```
+    /// use zune_image::errors::ErrorKind;
+    ///
+    /// assert_eq!(ErrorKind::LimitExceeded.code(), "limit_exceeded");
+    /// ```
+    pub const fn code(self) -> &'static str {
+        match self {
+            ErrorKind::Corrupt => "corrupt",
+            ErrorKind::Unsupported => "unsupported",
+            ErrorKind::LimitExceeded => "limit_exceeded",
+            ErrorKind::MissingCodec => "missing_codec",
+            ErrorKind::InvalidInput => "invalid_input",
+            ErrorKind::Io => "io",
+            ErrorKind::Cancelled => "cancelled",
+            ErrorKind::Other => "other"
+        }
+    }
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// An error from a decoder
+pub struct DecodeError {
+    format:  ImageFormat,
+    kind:    ErrorKind,
+    offset:  Option<u64>,
+    message: String,
+    source:  Option<Box<dyn core::error::Error + Send + Sync>>
+}
+
+impl DecodeError {
+    /// Create an error from the decoder of `format`
+    pub fn new(format: ImageFormat, kind: ErrorKind, message: impl Into<String>) -> DecodeError {
+        DecodeError {
+            format,
+            kind,
+            offset: None,
+            message: message.into(),
+            source: None
+        }
+    }
+    /// Set the byte offset in the encoded data where decoding failed
+    #[must_use]
+    pub fn set_offset(mut self, offset: u64) -> DecodeError {
+        self.offset = Some(offset);
+        self
+    }
+    /// Set the error of the underlying decoder
+    #[must_use]
+    pub fn set_source(mut self, source: impl core::error::Error + Send + Sync + 'static) -> Self {
+        self.source = Some(Box::new(source));
+        self
+    }
+    /// The format being decoded
+    pub const fn format(&self) -> ImageFormat {
+        self.format
+    }
+    /// The category of this error
+    pub const fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+    /// The byte offset in the encoded data the decoder had reached when it failed, if known
+    ///
+    /// Decoders read ahead, so the offset is where the failure was detected, which
+    /// may be past the bytes that caused it
+    pub const fn offset(&self) -> Option<u64> {
+        self.offset
+    }
+    /// A description of what went wrong
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Debug for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?} decoding failed", self.format)?;
+
+        if let Some(offset) = self.offset {
+            write!(f, " at byte {offset}")?;
+        }
+        write!(f, ": {}", self.message.trim_end())
+    }
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
+impl core::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|x| x as &(dyn core::error::Error + 'static))
+    }
+}
+
+impl ImageErrors {
+    /// The category of this error
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ImageErrors::ImageDecodeErrors(_) => ErrorKind::Corrupt,
+            #[cfg(feature = "std")]
+            ImageErrors::IccError(_) => ErrorKind::Corrupt,
+            ImageErrors::UnsupportedColorspace(..)
+            | ImageErrors::ImageDecoderNotImplemented(_)
+            | ImageErrors::ImageOperationNotImplemented(..) => ErrorKind::Unsupported,
+            ImageErrors::LimitsExceeded(..) => ErrorKind::LimitExceeded,
+            ImageErrors::ImageDecoderNotIncluded(_) => ErrorKind::MissingCodec,
+            ImageErrors::DimensionsMisMatch(..)
+            | ImageErrors::NoImageForOperations
+            | ImageErrors::NoImageForEncoding
+            | ImageErrors::NoImageBuffer
+            | ImageErrors::WrongTypeId(..)
+            | ImageErrors::ChannelErrors(_) => ErrorKind::InvalidInput,
+            #[cfg(feature = "std")]
+            ImageErrors::IoError(_) => ErrorKind::Io,
+            ImageErrors::Cancelled => ErrorKind::Cancelled,
+            ImageErrors::OperationsError(err) => match err {
+                ImageOperationsErrors::UnsupportedType(..) => ErrorKind::Unsupported,
+                ImageOperationsErrors::WrongColorspace(..)
+                | ImageOperationsErrors::WrongComponents(..)
+                | ImageOperationsErrors::InvalidChannelLayout(_) => ErrorKind::InvalidInput,
+                ImageOperationsErrors::Generic(_) | ImageOperationsErrors::GenericString(_) => {
+                    ErrorKind::Other
+                }
+            },
+            ImageErrors::EncodeErrors(err) => match err {
+                ImgEncodeErrors::UnsupportedColorspace(..) => ErrorKind::Unsupported,
+                ImgEncodeErrors::NoEncoderForFormat(_) => ErrorKind::MissingCodec,
+                _ => ErrorKind::Other
+            },
+            ImageErrors::GenericString(_) | ImageErrors::GenericStr(_) => ErrorKind::Other,
+            ImageErrors::DecodeError(err) => err.kind(),
+            ImageErrors::OperationFailed(_, err) => err.kind()
+        }
+    }
+    /// The stable code of the [kind](Self::kind) of this error
+    pub fn code(&self) -> &'static str {
+        self.kind().code()
+    }
+    /// The format whose decoder failed, if this is a decoding error
+    pub fn format(&self) -> Option<ImageFormat> {
+        match self {
+            ImageErrors::DecodeError(err) => Some(err.format()),
+            ImageErrors::OperationFailed(_, err) => err.format(),
+            _ => None
+        }
+    }
+    /// The byte offset where decoding failed, if known
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            ImageErrors::DecodeError(err) => err.offset(),
+            ImageErrors::OperationFailed(_, err) => err.offset(),
+            _ => None
+        }
+    }
+    /// The name of the operation that failed, if this error came from one
+    pub fn operation(&self) -> Option<&'static str> {
+        match self {
+            ImageErrors::OperationFailed(name, _) => Some(name),
+            _ => None
+        }
+    }
+    /// Record the offset of a decoding error that doesn't have one yet
+    pub(crate) fn at_offset(self, offset: u64) -> ImageErrors {
+        match self {
+            ImageErrors::DecodeError(err) if err.offset.is_none() => {
+                ImageErrors::DecodeError(err.set_offset(offset))
+            }
+            err => err
+        }
+    }
+    /// Wrap the error with the name of the operation it came from
+    ///
+    /// Cancellation isn't a failure of the operation and errors of nested operations
+    /// keep the innermost name
+    pub(crate) fn in_operation(self, name: &'static str) -> ImageErrors {
+        match self {
+            ImageErrors::Cancelled | ImageErrors::OperationFailed(..) => self,
+            err => ImageErrors::OperationFailed(name, Box::new(err))
+        }
+    }
 }
 
 /// Errors that may occur during image operations
@@ -68,7 +329,7 @@ pub enum ImgEncodeErrors {
 }
 
 impl Debug for ImageErrors {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::ImageDecodeErrors(err) => {
                 writeln!(f, "{err}")
@@ -107,6 +368,7 @@ impl Debug for ImageErrors {
                     "Expected type with ID of {expected:?} but found {found:?}"
                 )
             }
+            #[cfg(feature = "std")]
             ImageErrors::IoError(reason) => {
                 writeln!(f, "IO error, {:?}", reason)
             }
@@ -132,12 +394,26 @@ impl Debug for ImageErrors {
                     op_type, depth
                 )
             }
+            #[cfg(feature = "std")]
+            ImageErrors::IccError(err) => {
+                writeln!(f, "ICC error : {:?}", err)
+            }
+            ImageErrors::Cancelled => writeln!(f, "The work was cancelled"),
+            ImageErrors::DecodeError(err) => writeln!(f, "{err:?}"),
+            ImageErrors::OperationFailed(name, err) => write!(f, "{name} failed: {err:?}"),
+            ImageErrors::LimitsExceeded(limit, found, max) => {
+                writeln!(
+                    f,
+                    "Image exceeds the {limit} limit, found {found} but the limit is {max}"
+                )
+            }
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl From<std::io::Error> for ImageErrors {
-    fn from(value: Error) -> Self {
+    fn from(value: std::io::Error) -> Self {
         Self::IoError(value)
     }
 }
@@ -155,7 +431,7 @@ impl From<ImgEncodeErrors> for ImageErrors {
 }
 
 impl Debug for ImageOperationsErrors {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::UnsupportedType(operation, depth) => {
                 writeln!(
@@ -194,6 +470,19 @@ impl From<&'static str> for ImageErrors {
     }
 }
 
+#[cfg(feature = "std")]
+impl From<IccError> for ImageErrors {
+    fn from(value: IccError) -> Self {
+        ImageErrors::IccError(value)
+    }
+}
+
+impl From<DecodeError> for ImageErrors {
+    fn from(value: DecodeError) -> Self {
+        ImageErrors::DecodeError(value)
+    }
+}
+
 impl From<ChannelErrors> for ImageErrors {
     fn from(value: ChannelErrors) -> Self {
         ImageErrors::ChannelErrors(value)
@@ -201,7 +490,7 @@ impl From<ChannelErrors> for ImageErrors {
 }
 
 impl Debug for ImgEncodeErrors {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::Generic(ref string) => writeln!(f, "{string}"),
             Self::GenericStatic(ref string) => writeln!(f, "{string}"),
@@ -222,9 +511,19 @@ impl Debug for ImgEncodeErrors {
 }
 
 impl Display for ImageErrors {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         writeln!(f, "{:?}", self)
     }
 }
 
-impl std::error::Error for ImageErrors {}
+impl core::error::Error for ImageErrors {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            ImageErrors::DecodeError(err) => err.source.as_deref().map(|x| x as _),
+            ImageErrors::OperationFailed(_, err) => Some(&**err),
+            #[cfg(feature = "std")]
+            ImageErrors::IoError(err) => Some(err),
+            _ => None
+        }
+    }
+}
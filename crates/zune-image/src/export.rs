@@ -0,0 +1,456 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Export pixels in the layout other libraries expect
+//!
+//! Machine learning frameworks, GPU uploads and video encoders all want pixels slightly
+//! differently, planar or interleaved, `BGR` instead of `RGB`, floats from 0.0 to 1.0 or
+//! from 0.0 to 255.0. The `to_*` methods of [`Image`] produce these layouts from any
+//! image, converting depth and layout as needed, with [`ExportOptions`] choosing the
+//! order of the channels, range of floats and the mean and standard deviation
+//! models were trained with.
+//!
+//! [`Image::to_tensor`] and [`Tensor::from_images`] export floats with the shape of
+//! a batch, `NCHW` or `NHWC`, which the `candle-interop` and `tch-interop` features
+//! turn into tensors of those frameworks, see [`interop`](crate::interop).
+//!
+//! # Example
+//! Export an RGB image as planar `BGR` floats from 0.0 to 255.0
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::export::{ExportOptions, Normalization};
+//! use zune_image::image::Image;
+//!
+//! let image = Image::from_interleaved(&[255_u8, 0, 0, 0, 255, 0], 2, 1, ColorSpace::RGB);
+//!
+//! let options = ExportOptions::new()
+//!     .set_channel_order(&[2, 1, 0])
+//!     .set_normalization(Normalization::ZeroTo255);
+//!
+//! let tensor = image.to_f32_planar(&options).unwrap();
+//! assert_eq!(tensor, [0.0, 0.0, 0.0, 255.0, 255.0, 0.0]);
+//! ```
+//!
+//! Export an image as an `NCHW` tensor normalized with the ImageNet mean and
+//! standard deviation
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::export::{ExportOptions, TensorLayout};
+//! use zune_image::image::Image;
+//!
+//! let image = Image::fill(128_u8, ColorSpace::RGB, 224, 224);
+//!
+//! let options =
+//!     ExportOptions::new().set_mean_std(&[0.485, 0.456, 0.406], &[0.229, 0.224, 0.225]);
+//!
+//! let tensor = image.to_tensor(TensorLayout::Nchw, &options).unwrap();
+//! assert_eq!(tensor.shape(), [1, 3, 224, 224]);
+//! ```
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use bytemuck::Pod;
+use zune_core::bit_depth::BitDepth;
+
+use crate::errors::ImageErrors;
+use crate::image::Image;
+use crate::metadata::PixelLayout;
+
+/// Range of exported floats
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Normalization {
+    /// Values go from 0.0 to 1.0, the range zune-image uses for floats
+    #[default]
+    ZeroToOne,
+    /// Values go from 0.0 to 255.0, the range of 8 bit images
+    ZeroTo255
+}
+
+/// Order of the dimensions of exported tensors
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum TensorLayout {
+    /// Batch, channels, height, width, each channel is stored after the other,
+    /// used by PyTorch and most ONNX models
+    #[default]
+    Nchw,
+    /// Batch, height, width, channels, channels of a pixel are stored together,
+    /// used by TensorFlow
+    Nhwc
+}
+
+/// Options for exporting pixels
+///
+/// By default all channels of the first frame are exported in the order of the
+/// colorspace, with floats from 0.0 to 1.0
+#[derive(Clone, Debug, Default)]
+pub struct ExportOptions {
+    channel_order: Option<Vec<usize>>,
+    normalization: Normalization,
+    mean_std:      Option<(Vec<f32>, Vec<f32>)>,
+    frame:         usize
+}
+
+impl ExportOptions {
+    /// Create options exporting all channels of the first frame
+    pub fn new() -> ExportOptions {
+        ExportOptions::default()
+    }
+
+    /// Choose which channels are exported and in which order
+    ///
+    /// Each entry is the index of a channel in the colorspace of the image, e.g `[2, 1, 0]`
+    /// exports RGB images as BGR and `[0, 1, 2]` drops the alpha of RGBA images.
+    /// Channels can be repeated
+    pub fn set_channel_order(mut self, order: &[usize]) -> Self {
+        self.channel_order = Some(order.to_vec());
+        self
+    }
+
+    /// Set the range of exported floats
+    ///
+    /// This doesn't affect integer exports, which use the full range of their type
+    pub const fn set_normalization(mut self, normalization: Normalization) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
+    /// Subtract `mean` from exported floats and divide them by `std`
+    ///
+    /// There is a value for each exported channel, in the order they are exported. This
+    /// is done after the floats are in the range of the [normalization](Self::set_normalization),
+    /// so values for the default 0.0 to 1.0 range look like `[0.485, 0.456, 0.406]`.
+    ///
+    /// This doesn't affect integer exports
+    pub fn set_mean_std(mut self, mean: &[f32], std: &[f32]) -> Self {
+        self.mean_std = Some((mean.to_vec(), std.to_vec()));
+        self
+    }
+
+    /// Set the index of the frame to export
+    pub const fn set_frame(mut self, frame: usize) -> Self {
+        self.frame = frame;
+        self
+    }
+
+    /// The exported channels, `None` if all channels are exported in colorspace order
+    pub fn channel_order(&self) -> Option<&[usize]> {
+        self.channel_order.as_deref()
+    }
+
+    /// The range of exported floats
+    pub const fn normalization(&self) -> Normalization {
+        self.normalization
+    }
+
+    /// The mean and standard deviation of each exported channel, if set
+    pub fn mean_std(&self) -> Option<(&[f32], &[f32])> {
+        self.mean_std
+            .as_ref()
+            .map(|(mean, std)| (mean.as_slice(), std.as_slice()))
+    }
+
+    /// The index of the exported frame
+    pub const fn frame(&self) -> usize {
+        self.frame
+    }
+}
+
+impl Image {
+    /// Export pixels as interleaved floats, e.g `[R,G,B,R,G,B]`
+    ///
+    /// # Errors
+    /// See [`to_f32_planar`](Self::to_f32_planar)
+    pub fn to_f32_interleaved(&self, options: &ExportOptions) -> Result<Vec<f32>, ImageErrors> {
+        let mut pixels = self.export::<f32>(options, BitDepth::Float32, true)?;
+        normalize(&mut pixels, options, true);
+        Ok(pixels)
+    }
+
+    /// Export pixels as planar floats, all values of one channel followed by the next
+    /// channel, e.g `[R,R,G,G,B,B]`
+    ///
+    /// # Errors
+    /// If the frame doesn't exist, a channel index is outside the colorspace,
+    /// there isn't a mean and standard deviation for each exported channel,
+    /// or the image can't be converted to floats
+    pub fn to_f32_planar(&self, options: &ExportOptions) -> Result<Vec<f32>, ImageErrors> {
+        let mut pixels = self.export::<f32>(options, BitDepth::Float32, false)?;
+        normalize(&mut pixels, options, false);
+        Ok(pixels)
+    }
+
+    /// Export pixels as a batch of one image, with the dimensions in `layout` order
+    ///
+    /// # Errors
+    /// See [`to_f32_planar`](Self::to_f32_planar)
+    pub fn to_tensor(
+        &self, layout: TensorLayout, options: &ExportOptions
+    ) -> Result<Tensor, ImageErrors> {
+        Tensor::from_images(core::slice::from_ref(self), layout, options)
+    }
+
+    /// Export pixels as interleaved bytes
+    ///
+    /// # Errors
+    /// See [`to_f32_planar`](Self::to_f32_planar)
+    pub fn to_u8_interleaved(&self, options: &ExportOptions) -> Result<Vec<u8>, ImageErrors> {
+        self.export::<u8>(options, BitDepth::Eight, true)
+    }
+
+    /// Export pixels as planar bytes
+    ///
+    /// # Errors
+    /// See [`to_f32_planar`](Self::to_f32_planar)
+    pub fn to_u8_planar(&self, options: &ExportOptions) -> Result<Vec<u8>, ImageErrors> {
+        self.export::<u8>(options, BitDepth::Eight, false)
+    }
+
+    /// Export pixels as interleaved 16 bit values
+    ///
+    /// # Errors
+    /// See [`to_f32_planar`](Self::to_f32_planar)
+    pub fn to_u16_interleaved(&self, options: &ExportOptions) -> Result<Vec<u16>, ImageErrors> {
+        self.export::<u16>(options, BitDepth::Sixteen, true)
+    }
+
+    /// Export pixels as planar 16 bit values
+    ///
+    /// # Errors
+    /// See [`to_f32_planar`](Self::to_f32_planar)
+    pub fn to_u16_planar(&self, options: &ExportOptions) -> Result<Vec<u16>, ImageErrors> {
+        self.export::<u16>(options, BitDepth::Sixteen, false)
+    }
+
+    fn export<T: Pod + Default + 'static>(
+        &self, options: &ExportOptions, depth: BitDepth, interleave: bool
+    ) -> Result<Vec<T>, ImageErrors> {
+        let components = self.colorspace().num_components();
+
+        let Some(frame) = self.frames_ref().get(options.frame) else {
+            return Err(ImageErrors::GenericString(format!(
+                "Cannot export frame {} of an image with {} frames",
+                options.frame,
+                self.frames_len()
+            )));
+        };
+        let all_channels: Vec<usize> = (0..components).collect();
+        let order = options.channel_order().unwrap_or(&all_channels);
+
+        if let Some(index) = order.iter().find(|x| **x >= components) {
+            return Err(ImageErrors::GenericString(format!(
+                "Channel {index} is outside the {:?} colorspace",
+                self.colorspace()
+            )));
+        }
+        if let Some((mean, std)) = options.mean_std() {
+            if mean.len() != order.len() || std.len() != order.len() {
+                return Err(ImageErrors::GenericString(format!(
+                    "Expected a mean and standard deviation for each of the {} exported channels but found {} and {}",
+                    order.len(),
+                    mean.len(),
+                    std.len()
+                )));
+            }
+            if std.contains(&0.0) {
+                return Err(ImageErrors::GenericStr(
+                    "Standard deviations of zero would divide by zero"
+                ));
+            }
+        }
+        // convert only the exported frame, clones share channels so this is cheap
+        let mut image = self.clone();
+        image.frames = vec![frame.clone()];
+        image.convert_layout(PixelLayout::Planar)?;
+        image.convert_depth(depth)?;
+
+        let channels = image.frames_ref()[0]
+            .channels_vec_ref()
+            .iter()
+            .map(|x| x.reinterpret_as::<T>())
+            .collect::<Result<Vec<_>, _>>()?;
+        let (width, height) = self.dimensions();
+        let mut out = Vec::with_capacity(width * height * order.len());
+
+        if interleave {
+            let channels = &channels;
+            out.extend(
+                (0..width * height).flat_map(|i| order.iter().map(move |x| channels[*x][i]))
+            );
+        } else {
+            for index in order {
+                out.extend_from_slice(channels[*index]);
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Floats exported with their shape, created by [`Image::to_tensor`] and [`Tensor::from_images`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tensor {
+    data:   Vec<f32>,
+    shape:  [usize; 4],
+    layout: TensorLayout
+}
+
+impl Tensor {
+    /// Export images as a batch, with the dimensions in `layout` order
+    ///
+    /// All images must have the same dimensions and colorspace, `options` apply to
+    /// each of them.
+    ///
+    /// # Errors
+    /// If there are no images, they differ in dimensions or colorspace, or the errors
+    /// of [`Image::to_f32_planar`]
+    pub fn from_images(
+        images: &[Image], layout: TensorLayout, options: &ExportOptions
+    ) -> Result<Tensor, ImageErrors> {
+        let Some(first) = images.first() else {
+            return Err(ImageErrors::GenericStr("Cannot export an empty batch"));
+        };
+        let (width, height) = first.dimensions();
+        let channels = options
+            .channel_order()
+            .map_or(first.colorspace().num_components(), <[usize]>::len);
+
+        let mut data = Vec::with_capacity(images.len() * width * height * channels);
+
+        for image in images {
+            let matches = image.dimensions() == first.dimensions()
+                && image.colorspace() == first.colorspace();
+
+            if !matches {
+                return Err(ImageErrors::GenericString(format!(
+                    "Images of a batch must match, found a {:?} {:?} image and a {:?} {:?} image",
+                    first.dimensions(),
+                    first.colorspace(),
+                    image.dimensions(),
+                    image.colorspace()
+                )));
+            }
+            data.extend(match layout {
+                TensorLayout::Nchw => image.to_f32_planar(options)?,
+                TensorLayout::Nhwc => image.to_f32_interleaved(options)?
+            });
+        }
+        let shape = match layout {
+            TensorLayout::Nchw => [images.len(), channels, height, width],
+            TensorLayout::Nhwc => [images.len(), height, width, channels]
+        };
+
+        Ok(Tensor {
+            data,
+            shape,
+            layout
+        })
+    }
+
+    /// The size of each dimension, in the order of [`layout`](Self::layout)
+    pub const fn shape(&self) -> [usize; 4] {
+        self.shape
+    }
+
+    /// The order of the dimensions
+    pub const fn layout(&self) -> TensorLayout {
+        self.layout
+    }
+
+    /// The exported floats
+    pub fn data(&self) -> &[f32] {
+        &self.data
+    }
+
+    /// Return the exported floats, without the shape
+    pub fn into_data(self) -> Vec<f32> {
+        self.data
+    }
+}
+
+/// Bring exported floats into the range and distribution the options ask for
+fn normalize(pixels: &mut [f32], options: &ExportOptions, interleaved: bool) {
+    if options.normalization == Normalization::ZeroTo255 {
+        pixels.iter_mut().for_each(|x| *x *= 255.0);
+    }
+    // lengths were checked when exporting
+    if let Some((mean, std)) = options.mean_std() {
+        if interleaved {
+            for pixel in pixels.chunks_exact_mut(mean.len()) {
+                for ((x, mean), std) in pixel.iter_mut().zip(mean).zip(std) {
+                    *x = (*x - mean) / std;
+                }
+            }
+        } else {
+            let plane = pixels.len() / mean.len();
+
+            for ((channel, mean), std) in pixels.chunks_exact_mut(plane).zip(mean).zip(std) {
+                channel.iter_mut().for_each(|x| *x = (*x - mean) / std);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::export::{ExportOptions, Normalization, Tensor, TensorLayout};
+    use crate::image::Image;
+
+    #[test]
+    fn test_export_layouts() {
+        let image = Image::from_interleaved(&[0_u8, 51, 255, 102, 0, 255], 1, 3, ColorSpace::LumaA);
+        let options = ExportOptions::new();
+
+        assert_eq!(
+            image.to_u8_planar(&options).unwrap(),
+            [0, 255, 0, 51, 102, 255]
+        );
+        let floats = image.to_f32_interleaved(&options).unwrap();
+        assert!(floats[0] == 0.0 && (floats[1] - 0.2).abs() < 1e-6);
+        assert_eq!(image.to_u16_interleaved(&options).unwrap()[..2], [0, 13107]);
+
+        // alpha only, repeated
+        let options = options.set_channel_order(&[1, 1]);
+        assert_eq!(
+            image.to_u8_interleaved(&options).unwrap(),
+            [51, 51, 102, 102, 255, 255]
+        );
+
+        assert!(image
+            .to_u8_planar(&ExportOptions::new().set_channel_order(&[2]))
+            .is_err());
+        assert!(image
+            .to_u8_planar(&ExportOptions::new().set_frame(1))
+            .is_err());
+    }
+
+    #[test]
+    fn test_tensor_export() {
+        let image = Image::from_interleaved(&[0_u8, 255, 255, 0], 2, 1, ColorSpace::LumaA);
+        let options = ExportOptions::new()
+            .set_normalization(Normalization::ZeroTo255)
+            .set_mean_std(&[127.5, 0.0], &[127.5, 255.0]);
+
+        let tensor = image.to_tensor(TensorLayout::Nchw, &options).unwrap();
+        assert_eq!(tensor.shape(), [1, 2, 1, 2]);
+        assert_eq!(tensor.data(), [-1.0, 1.0, 1.0, 0.0]);
+
+        let batch = [image.clone(), image.clone()];
+        let tensor = Tensor::from_images(&batch, TensorLayout::Nhwc, &options).unwrap();
+        assert_eq!(tensor.shape(), [2, 1, 2, 2]);
+        assert_eq!(tensor.data()[..4], [-1.0, 1.0, 1.0, 0.0]);
+
+        // a mean for each exported channel is needed
+        let options = ExportOptions::new().set_mean_std(&[0.5], &[0.5]);
+        assert!(image.to_tensor(TensorLayout::Nchw, &options).is_err());
+
+        let other = Image::fill(0_u8, ColorSpace::LumaA, 1, 2);
+        let options = ExportOptions::new();
+        assert!(Tensor::from_images(&[image, other], TensorLayout::Nchw, &options).is_err());
+        assert!(Tensor::from_images(&[], TensorLayout::Nchw, &options).is_err());
+    }
+}
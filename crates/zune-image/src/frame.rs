@@ -11,7 +11,9 @@
 
 #![allow(dead_code)]
 
-use std::any::TypeId;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::any::TypeId;
 
 use bytemuck::Pod;
 use zune_core::colorspace::ColorSpace;
@@ -20,6 +22,28 @@ use crate::channel::{Channel, ChannelErrors};
 use crate::deinterleave::{deinterleave_f32, deinterleave_u16, deinterleave_u8};
 use crate::utils::swizzle_channels;
 
+/// How the area of a frame is treated before the next frame is shown
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum FrameDisposal {
+    /// Leave the frame as is, the next frame is drawn over it
+    #[default]
+    None,
+    /// Clear the area of the frame to transparent black
+    Background,
+    /// Restore the area of the frame to what it was before the frame was drawn
+    Previous
+}
+
+/// How a frame is drawn over the previous contents of the canvas
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum FrameBlend {
+    /// Replace the contents, including alpha
+    #[default]
+    Source,
+    /// Composite over the contents using the alpha of the frame
+    Over
+}
+
 /// A single image frame
 ///
 /// This represents a simple image frame which contains a group
@@ -29,12 +53,20 @@ use crate::utils::swizzle_channels;
 /// Each channel should have the same size
 ///
 /// Each frame also contains a duration or delay, for animated images,
-/// this is how long this particular frame should be shown
+/// this is how long this particular frame should be shown, and how the
+/// frame is disposed of and blended, to allow animations to be written back
+/// the way they were read.
+///
+/// Decoders compose frames onto the full canvas, so decoded frames have no offset
+/// and replace the previous frame.
 #[derive(Eq, PartialEq)]
 pub struct Frame {
     pub(crate) channels:    Vec<Channel>,
     pub(crate) numerator:   usize,
-    pub(crate) denominator: usize
+    pub(crate) denominator: usize,
+    pub(crate) disposal:    FrameDisposal,
+    pub(crate) blend:       FrameBlend,
+    pub(crate) offset:      (usize, usize)
 }
 
 impl Clone for Frame {
@@ -55,16 +87,20 @@ impl Clone for Frame {
             // to copy huge images
             let channels = &self.channels;
             // safeguards to ensure that we justify starting threads
-            if channels.len() > 1 && unsafe { channels[0].alias().len() > 1000 } {
+            if channels.len() > 1 && channels[0].len() >= crate::threads::MIN_PARALLEL_PIXELS {
                 let bit_type = channels[0].type_id();
 
                 // create new channels
                 let mut new_channels =
                     vec![Channel::new_with_capacity_and_type(1, bit_type); channels.len()];
 
-                std::thread::scope(|c| {
-                    for (old, new) in channels.iter().zip(new_channels.iter_mut()) {
-                        c.spawn(|| {
+                crate::threads::install(|| {
+                    use rayon::prelude::*;
+
+                    channels
+                        .par_iter()
+                        .zip(new_channels.par_iter_mut())
+                        .for_each(|(old, new)| {
                             let old_alias = unsafe { old.alias() };
                             // make enough space to fit the new array
                             unsafe {
@@ -81,20 +117,11 @@ impl Clone for Frame {
                             // now copy
                             new_alias.copy_from_slice(old_alias);
                         });
-                    }
                 });
-                return Frame {
-                    channels:    new_channels,
-                    numerator:   self.numerator,
-                    denominator: self.denominator
-                };
+                return self.with_channels(new_channels);
             }
         }
-        Frame {
-            channels:    self.channels.clone(),
-            numerator:   self.numerator,
-            denominator: self.denominator
-        }
+        self.with_channels(self.channels.clone())
     }
 }
 impl Frame {
@@ -117,11 +144,7 @@ impl Frame {
     ///
     /// ```
     pub fn new(channels: Vec<Channel>) -> Frame {
-        Frame {
-            channels,
-            numerator: 1,
-            denominator: 1
-        }
+        Frame::new_with_duration(channels, 1, 1)
     }
     /// Create a new frame from a slice of f32 pixels
     ///
@@ -140,11 +163,7 @@ impl Frame {
     ) -> Frame {
         let channels = deinterleave_f32(pixels, colorspace).unwrap();
 
-        Frame {
-            channels,
-            numerator,
-            denominator
-        }
+        Frame::new_with_duration(channels, numerator, denominator)
     }
     /// Create a new frame from a slice of u16 pixels
     ///
@@ -163,11 +182,7 @@ impl Frame {
         pixels: &[u16], colorspace: ColorSpace, numerator: usize, denominator: usize
     ) -> Frame {
         let channels = deinterleave_u16(pixels, colorspace).unwrap();
-        Frame {
-            channels,
-            numerator,
-            denominator
-        }
+        Frame::new_with_duration(channels, numerator, denominator)
     }
 
     /// Create a new frame from a slice of u8 pixels
@@ -187,11 +202,7 @@ impl Frame {
         pixels: &[u8], colorspace: ColorSpace, numerator: usize, denominator: usize
     ) -> Frame {
         let channels = deinterleave_u8(pixels, colorspace).unwrap();
-        Frame {
-            channels,
-            numerator,
-            denominator
-        }
+        Frame::new_with_duration(channels, numerator, denominator)
     }
 
     /// Return a mutable reference to the vector of
@@ -243,10 +254,72 @@ impl Frame {
         Frame {
             channels,
             numerator,
-            denominator
+            denominator,
+            disposal: FrameDisposal::default(),
+            blend: FrameBlend::default(),
+            offset: (0, 0)
         }
     }
 
+    /// Create a frame with new channels, keeping the duration, disposal,
+    /// blend and offset of this frame
+    ///
+    /// Operations producing new channels for a frame should use this to keep
+    /// the timing of animations
+    pub fn with_channels(&self, channels: Vec<Channel>) -> Frame {
+        Frame {
+            channels,
+            numerator: self.numerator,
+            denominator: self.denominator,
+            disposal: self.disposal,
+            blend: self.blend,
+            offset: self.offset
+        }
+    }
+
+    /// Return the duration of the frame as a fraction of seconds,
+    /// `(numerator, denominator)`
+    pub const fn duration(&self) -> (usize, usize) {
+        (self.numerator, self.denominator)
+    }
+    /// Return the duration of the frame in seconds
+    ///
+    /// A zero denominator is treated as 100, like APNG does
+    pub fn duration_secs(&self) -> f64 {
+        let denominator = if self.denominator == 0 { 100 } else { self.denominator };
+
+        self.numerator as f64 / denominator as f64
+    }
+    /// Set the duration of the frame as a fraction of seconds
+    pub fn set_duration(&mut self, numerator: usize, denominator: usize) {
+        self.numerator = numerator;
+        self.denominator = denominator;
+    }
+    /// Return how the frame is disposed of before the next frame
+    pub const fn disposal(&self) -> FrameDisposal {
+        self.disposal
+    }
+    /// Set how the frame is disposed of before the next frame
+    pub fn set_disposal(&mut self, disposal: FrameDisposal) {
+        self.disposal = disposal;
+    }
+    /// Return how the frame is drawn over the canvas
+    pub const fn blend(&self) -> FrameBlend {
+        self.blend
+    }
+    /// Set how the frame is drawn over the canvas
+    pub fn set_blend(&mut self, blend: FrameBlend) {
+        self.blend = blend;
+    }
+    /// Return the position of the top left corner of the frame on the canvas
+    pub const fn offset(&self) -> (usize, usize) {
+        self.offset
+    }
+    /// Set the position of the top left corner of the frame on the canvas
+    pub fn set_offset(&mut self, x: usize, y: usize) {
+        self.offset = (x, y);
+    }
+
     /// Returns a reference to the channels in this frame
     ///
     /// # Arguments
@@ -616,15 +689,14 @@ impl Frame {
         if color_space.num_components() != self.channels.len() {
             return None;
         }
-        let mut position = color_space.alpha_position().expect("No way!!");
-        if position == 0 {
-            position = 1;
-        }
+        let alpha_position = color_space.alpha_position().expect("No way!!");
+        // alpha first (ARGB) splits after the alpha, otherwise split at the alpha
+        let position = alpha_position.max(1);
         let (src_c1, src_c2) = self.channels.split_at(position);
 
         let src_alpha_channel;
         let src_color_channels;
-        if position == 1 {
+        if alpha_position == 0 {
             // argb
             src_alpha_channel = &src_c1[0];
             src_color_channels = src_c2;
@@ -660,15 +732,14 @@ impl Frame {
         if color_space.num_components() != self.channels.len() {
             return None;
         }
-        let mut position = color_space.alpha_position().expect("No way!!");
-        if position == 0 {
-            position = 1;
-        }
+        let alpha_position = color_space.alpha_position().expect("No way!!");
+        // alpha first (ARGB) splits after the alpha, otherwise split at the alpha
+        let position = alpha_position.max(1);
         let (src_c1, src_c2) = self.channels.split_at_mut(position);
 
         let src_alpha_channel;
         let src_color_channels;
-        if position == 1 {
+        if alpha_position == 0 {
             // argb
             src_alpha_channel = &mut src_c1[0];
             src_color_channels = src_c2;
@@ -0,0 +1,293 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Procedural image generators
+//!
+//! Constructors for solid colors, gradients, checkerboards and test charts, useful
+//! for tests and as a base for procedural content. See [`Image::from_fn`] to build images
+//! from a function of the pixel position.
+//!
+//! Colors are given as floats between 0.0 and 1.0, one value per colorspace component,
+//! components without a value are zero. Images are generated in float and converted to the
+//! requested depth by rounding.
+//!
+//! Generators support colorspaces with up to [`MAX_CHANNELS`] components.
+//!
+//! # Example
+//! ```
+//! use zune_core::bit_depth::BitDepth;
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//!
+//! let image = Image::checkerboard(
+//!     64,
+//!     64,
+//!     ColorSpace::RGB,
+//!     BitDepth::Eight,
+//!     8,
+//!     &[1.0, 1.0, 1.0],
+//!     &[0.0, 0.0, 0.0]
+//! )
+//! .unwrap();
+//!
+//! assert_eq!(image.pixel_at::<u8>(8, 0).unwrap(), [0, 0, 0, 0]);
+//! ```
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use zune_core::bit_depth::BitDepth;
+use zune_core::colorspace::ColorSpace;
+
+use crate::core_filters::depth::{Depth, Dither};
+use crate::errors::ImageErrors;
+use crate::image::{Image, MAX_CHANNELS};
+use crate::traits::OperationsTrait;
+
+/// SMPTE color bars in 8 bit studio range, black is 16 and white 235
+mod smpte {
+    pub const GRAY: [u8; 3] = [180, 180, 180];
+    pub const YELLOW: [u8; 3] = [180, 180, 16];
+    pub const CYAN: [u8; 3] = [16, 180, 180];
+    pub const GREEN: [u8; 3] = [16, 180, 16];
+    pub const MAGENTA: [u8; 3] = [180, 16, 180];
+    pub const RED: [u8; 3] = [180, 16, 16];
+    pub const BLUE: [u8; 3] = [16, 16, 180];
+    pub const BLACK: [u8; 3] = [16, 16, 16];
+    pub const WHITE: [u8; 3] = [235, 235, 235];
+    pub const MINUS_I: [u8; 3] = [16, 44, 81];
+    pub const PLUS_Q: [u8; 3] = [59, 16, 107];
+    pub const SUPER_BLACK: [u8; 3] = [7, 7, 7];
+    pub const LIGHT_BLACK: [u8; 3] = [25, 25, 25];
+
+    pub const TOP: [[u8; 3]; 7] = [GRAY, YELLOW, CYAN, GREEN, MAGENTA, RED, BLUE];
+    pub const MIDDLE: [[u8; 3]; 7] = [BLUE, BLACK, MAGENTA, BLACK, CYAN, BLACK, GRAY];
+}
+
+/// Expand a color to all channels, missing components are zero
+fn expand(color: &[f32]) -> [f32; MAX_CHANNELS] {
+    let mut pixel = [0.0; MAX_CHANNELS];
+
+    for (out, value) in pixel.iter_mut().zip(color) {
+        *out = *value;
+    }
+    pixel
+}
+
+fn mix(start: &[f32; MAX_CHANNELS], end: &[f32; MAX_CHANNELS], t: f32) -> [f32; MAX_CHANNELS] {
+    let t = t.clamp(0.0, 1.0);
+
+    core::array::from_fn(|i| start[i] + (end[i] - start[i]) * t)
+}
+
+impl Image {
+    /// Generate an image of the given depth from a function returning float pixels
+    ///
+    /// The function receives the x and y position of every pixel
+    fn generate<F>(
+        width: usize, height: usize, colorspace: ColorSpace, depth: BitDepth, func: F
+    ) -> Result<Image, ImageErrors>
+    where
+        F: Fn(usize, usize) -> [f32; MAX_CHANNELS]
+    {
+        if !(1..=MAX_CHANNELS).contains(&colorspace.num_components()) {
+            return Err(ImageErrors::GenericStr(
+                "Generators support colorspaces with one to four components"
+            ));
+        }
+        let mut image = Image::from_fn::<f32, _>(width, height, colorspace, |y, x, pixel| {
+            *pixel = func(x, y);
+        });
+        Depth::new(depth)
+            .set_dither(Dither::Round)
+            .execute(&mut image)?;
+
+        Ok(image)
+    }
+
+    /// Create an image filled with a single color
+    ///
+    /// Unlike [`fill`](Self::fill), every channel can have its own value
+    ///
+    /// # Errors
+    /// If the depth is not 8 or 16 bit or float, or the colorspace has more than four components
+    pub fn solid(
+        width: usize, height: usize, colorspace: ColorSpace, depth: BitDepth, color: &[f32]
+    ) -> Result<Image, ImageErrors> {
+        let color = expand(color);
+
+        Image::generate(width, height, colorspace, depth, |_, _| color)
+    }
+
+    /// Create a linear gradient from `start` to `end`
+    ///
+    /// # Arguments
+    /// - angle: Direction of the gradient in degrees, 0 goes from left to right,
+    ///   90 from top to bottom. The gradient spans the whole image in that direction
+    ///
+    /// # Errors
+    /// If the depth is not 8 or 16 bit or float, or the colorspace has more than four components
+    pub fn linear_gradient(
+        width: usize, height: usize, colorspace: ColorSpace, depth: BitDepth, start: &[f32],
+        end: &[f32], angle: f32
+    ) -> Result<Image, ImageErrors> {
+        let (start, end) = (expand(start), expand(end));
+        let (dy, dx) = angle.to_radians().sin_cos();
+
+        let (center_x, center_y) = (width as f32 / 2.0, height as f32 / 2.0);
+        // length of the image projected onto the direction
+        let length = (width as f32 * dx).abs() + (height as f32 * dy).abs();
+
+        Image::generate(width, height, colorspace, depth, |x, y| {
+            let px = x as f32 + 0.5 - center_x;
+            let py = y as f32 + 0.5 - center_y;
+
+            mix(&start, &end, (px * dx + py * dy) / length.max(1.0) + 0.5)
+        })
+    }
+
+    /// Create a radial gradient from `inner` at the center of the image to `outer`
+    /// at the corners
+    ///
+    /// # Errors
+    /// If the depth is not 8 or 16 bit or float, or the colorspace has more than four components
+    pub fn radial_gradient(
+        width: usize, height: usize, colorspace: ColorSpace, depth: BitDepth, inner: &[f32],
+        outer: &[f32]
+    ) -> Result<Image, ImageErrors> {
+        let (inner, outer) = (expand(inner), expand(outer));
+
+        let (center_x, center_y) = (width as f32 / 2.0, height as f32 / 2.0);
+        let radius = center_x.hypot(center_y).max(f32::EPSILON);
+
+        Image::generate(width, height, colorspace, depth, |x, y| {
+            let distance = (x as f32 + 0.5 - center_x).hypot(y as f32 + 0.5 - center_y);
+
+            mix(&inner, &outer, distance / radius)
+        })
+    }
+
+    /// Create a checkerboard of `size` by `size` squares, starting with `first` at the
+    /// top left corner
+    ///
+    /// # Errors
+    /// If the depth is not 8 or 16 bit or float, or the colorspace has more than four components
+    pub fn checkerboard(
+        width: usize, height: usize, colorspace: ColorSpace, depth: BitDepth, size: usize,
+        first: &[f32], second: &[f32]
+    ) -> Result<Image, ImageErrors> {
+        let (first, second) = (expand(first), expand(second));
+        let size = size.max(1);
+
+        Image::generate(width, height, colorspace, depth, |x, y| {
+            if (x / size + y / size).is_multiple_of(2) {
+                first
+            } else {
+                second
+            }
+        })
+    }
+
+    /// Create SMPTE color bars
+    ///
+    /// The chart has the seven 75% bars on top, the reversed blue bars below them and
+    /// the -I, white, +Q and PLUGE patches at the bottom. Levels follow 8 bit studio range,
+    /// black is 16 and white 235, so the PLUGE bars below and above black are visible.
+    ///
+    /// The chart is generated in RGB and converted to `colorspace`
+    ///
+    /// # Errors
+    /// If the depth is not 8 or 16 bit or float, or RGB can't be converted to the colorspace
+    pub fn smpte_bars(
+        width: usize, height: usize, colorspace: ColorSpace, depth: BitDepth
+    ) -> Result<Image, ImageErrors> {
+        let bar_width = width as f32 / 7.0;
+
+        let mut image = Image::generate(width, height, ColorSpace::RGB, depth, |x, y| {
+            // position in bars
+            let bar = (x as f32 + 0.5) / bar_width;
+            let index = (bar as usize).min(6);
+
+            let color = if y < height * 2 / 3 {
+                smpte::TOP[index]
+            } else if y < height * 3 / 4 {
+                smpte::MIDDLE[index]
+            } else {
+                match bar {
+                    b if b < 1.25 => smpte::MINUS_I,
+                    b if b < 2.5 => smpte::WHITE,
+                    b if b < 3.75 => smpte::PLUS_Q,
+                    b if b < 5.0 => smpte::BLACK,
+                    b if b < 5.0 + 1.0 / 3.0 => smpte::SUPER_BLACK,
+                    b if b < 5.0 + 2.0 / 3.0 => smpte::BLACK,
+                    b if b < 6.0 => smpte::LIGHT_BLACK,
+                    _ => smpte::BLACK
+                }
+            };
+            expand(&color.map(|c| f32::from(c) / 255.0))
+        })?;
+
+        if colorspace != ColorSpace::RGB {
+            image.convert_color(colorspace)?;
+        }
+        Ok(image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::bit_depth::BitDepth;
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::image::Image;
+
+    #[test]
+    fn test_gradients() {
+        let image = Image::linear_gradient(
+            100,
+            10,
+            ColorSpace::Luma,
+            BitDepth::Eight,
+            &[0.0],
+            &[1.0],
+            0.0
+        )
+        .unwrap();
+        // increasing from left to right, constant down
+        assert!(image.pixel_at::<u8>(0, 0).unwrap()[0] < 5);
+        assert!(image.pixel_at::<u8>(99, 0).unwrap()[0] > 250);
+        assert_eq!(
+            image.pixel_at::<u8>(40, 0).unwrap(),
+            image.pixel_at::<u8>(40, 9).unwrap()
+        );
+
+        let image = Image::radial_gradient(
+            21,
+            21,
+            ColorSpace::RGB,
+            BitDepth::Sixteen,
+            &[1.0, 0.0, 0.0],
+            &[0.0, 0.0, 1.0]
+        )
+        .unwrap();
+        let center = image.pixel_at::<u16>(10, 10).unwrap();
+        let corner = image.pixel_at::<u16>(0, 0).unwrap();
+        assert!(center[0] > 60000 && center[2] < 5000);
+        assert!(corner[0] < 5000 && corner[2] > 60000);
+    }
+
+    #[test]
+    fn test_smpte_bars() {
+        let image = Image::smpte_bars(70, 40, ColorSpace::RGB, BitDepth::Eight).unwrap();
+
+        // yellow, then black below the green bar and white at the bottom
+        assert_eq!(image.pixel_at::<u8>(15, 0).unwrap(), [180, 180, 16, 0]);
+        assert_eq!(image.pixel_at::<u8>(35, 28).unwrap(), [16, 16, 16, 0]);
+        assert_eq!(image.pixel_at::<u8>(15, 39).unwrap(), [235, 235, 235, 0]);
+
+        let solid = Image::solid(4, 4, ColorSpace::RGBA, BitDepth::Eight, &[1.0, 0.5]).unwrap();
+        assert_eq!(solid.pixel_at::<u8>(3, 3).unwrap(), [255, 128, 0, 0]);
+    }
+}
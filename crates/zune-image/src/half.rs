@@ -0,0 +1,199 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Half precision floats
+//!
+//! Images of [`BitDepth::Float16`] store their values as [`F16`], the bits of an
+//! IEEE 754 half float, since Rust has no stable `f16` type. Half floats take half
+//! the memory of [`f32`]s, while keeping enough precision and range for HDR images,
+//! which is why HDR formats often store them.
+//!
+//! Operations usually work on [`f32`]s, so convert half float images back and forth with
+//! [`Depth`](crate::core_filters::depth::Depth), or convert single values with
+//! [`F16::from_f32`] and [`F16::to_f32`].
+//!
+//! # Example
+//! ```
+//! use zune_core::bit_depth::BitDepth;
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::core_filters::depth::Depth;
+//! use zune_image::half::F16;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//!
+//! let mut image = Image::fill(F16::from_f32(0.5), ColorSpace::RGB, 100, 100);
+//!
+//! Depth::new(BitDepth::Float32).execute(&mut image).unwrap();
+//! assert_eq!(image.flatten_frames::<f32>()[0][0], 0.5);
+//! ```
+use bytemuck::{Pod, Zeroable};
+use zune_core::bit_depth::BitDepth;
+
+use crate::traits::ZuneInts;
+
+/// An IEEE 754 half precision float, stored as its bits
+///
+/// Conversions from [`f32`] round to the nearest half float, ties to even, values
+/// too large for a half float become infinities.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct F16(u16);
+
+// Safety: F16 is a transparent wrapper over u16, every bit pattern is a valid half float
+unsafe impl Zeroable for F16 {}
+
+unsafe impl Pod for F16 {}
+
+impl F16 {
+    /// Zero
+    pub const ZERO: F16 = F16(0);
+    /// One
+    pub const ONE: F16 = F16(0x3c00);
+
+    /// Create a half float from its bits
+    pub const fn from_bits(bits: u16) -> F16 {
+        F16(bits)
+    }
+    /// Return the bits of the half float
+    pub const fn to_bits(self) -> u16 {
+        self.0
+    }
+    /// Convert a float to the nearest half float
+    ///
+    /// # Example
+    /// ```
+    /// use zune_image::half::F16;
+    ///
+    /// assert_eq!(F16::from_f32(1.0), F16::ONE);
+    /// assert_eq!(F16::from_f32(0.1).to_f32(), 0.099975586);
+    /// assert!(F16::from_f32(1e6).to_f32().is_infinite());
+    /// ```
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub const fn from_f32(value: f32) -> F16 {
+        let bits = value.to_bits();
+        let sign = (bits >> 16) & 0x8000;
+        let exponent = ((bits >> 23) & 0xff) as i32;
+        let mantissa = bits & 0x7f_ffff;
+
+        // infinities, and NaNs, which stay NaNs
+        if exponent == 0xff {
+            let nan = if mantissa == 0 { 0 } else { 0x200 | (mantissa >> 13) };
+            return F16((sign | 0x7c00 | nan) as u16);
+        }
+        let exponent = exponent - 127 + 15;
+
+        if exponent >= 0x1f {
+            return F16((sign | 0x7c00) as u16);
+        }
+        if exponent <= 0 {
+            // a subnormal half float, or zero
+            if exponent < -10 {
+                return F16(sign as u16);
+            }
+            let mantissa = mantissa | 0x80_0000;
+            let shift = (14 - exponent) as u32;
+
+            let half = 1 << (shift - 1);
+            let rest = mantissa & ((1 << shift) - 1);
+            let mut value = mantissa >> shift;
+
+            if rest > half || (rest == half && value & 1 == 1) {
+                value += 1;
+            }
+            return F16((sign | value) as u16);
+        }
+        let mut value = ((exponent as u32) << 10) | (mantissa >> 13);
+        let rest = mantissa & 0x1fff;
+
+        // rounding up may carry into the exponent, up to infinity, which is correct
+        if rest > 0x1000 || (rest == 0x1000 && value & 1 == 1) {
+            value += 1;
+        }
+        F16((sign | value) as u16)
+    }
+    /// Convert a half float to a float, this is exact
+    pub const fn to_f32(self) -> f32 {
+        let bits = self.0 as u32;
+        let sign = (bits & 0x8000) << 16;
+        let exponent = (bits >> 10) & 0x1f;
+        let mantissa = bits & 0x3ff;
+
+        let bits = match exponent {
+            0 if mantissa == 0 => sign,
+            0 => {
+                // subnormal half floats are normal floats, move the highest bit
+                // of the mantissa to the implicit one
+                let shift = mantissa.leading_zeros() - 21;
+                let mantissa = (mantissa << shift) & 0x3ff;
+
+                sign | ((113 - shift) << 23) | (mantissa << 13)
+            }
+            0x1f => sign | 0x7f80_0000 | (mantissa << 13),
+            _ => sign | ((exponent + 127 - 15) << 23) | (mantissa << 13)
+        };
+        f32::from_bits(bits)
+    }
+}
+
+impl From<F16> for f32 {
+    fn from(value: F16) -> f32 {
+        value.to_f32()
+    }
+}
+
+impl core::fmt::Debug for F16 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}", self.to_f32())
+    }
+}
+
+impl ZuneInts<F16> for F16 {
+    #[inline(always)]
+    fn depth() -> BitDepth {
+        BitDepth::Float16
+    }
+    #[inline(always)]
+    fn max_value() -> F16 {
+        F16::ONE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::half::F16;
+
+    #[test]
+    fn test_f16_round_trips() {
+        // every half float converts to a float and back to itself
+        for bits in 0..=u16::MAX {
+            let value = F16::from_bits(bits);
+
+            if value.to_f32().is_nan() {
+                assert!(F16::from_f32(value.to_f32()).to_f32().is_nan());
+                continue;
+            }
+            assert_eq!(F16::from_f32(value.to_f32()), value, "{bits:#x}");
+        }
+    }
+
+    #[test]
+    fn test_f16_rounding() {
+        // halfway between 1.0 and the next half float rounds to even, 1.0
+        assert_eq!(F16::from_f32(1.0 + 2.0_f32.powi(-11)), F16::ONE);
+        assert_eq!(
+            F16::from_f32(1.0 + 2.0_f32.powi(-11) * 1.01).to_bits(),
+            0x3c01
+        );
+        // largest half float, and overflow
+        assert_eq!(F16::from_f32(65504.0).to_bits(), 0x7bff);
+        assert_eq!(F16::from_f32(65520.0).to_bits(), 0x7c00);
+        // smallest subnormal, and underflow
+        assert_eq!(F16::from_f32(2.0_f32.powi(-24)).to_bits(), 1);
+        assert_eq!(F16::from_f32(2.0_f32.powi(-25)).to_bits(), 0);
+        assert_eq!(F16::from_f32(-0.0).to_bits(), 0x8000);
+    }
+}
@@ -10,8 +10,10 @@
 //! Fully supported bit depths are 8 and 16 and float 32 which are expected to be in the range between 0.0 and 1.0,
 //! see [channel](crate::channel) documentation for how that happens
 //!
-use std::fmt::Debug;
-use std::mem::size_of;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::fmt::Debug;
+use core::mem::size_of;
 
 use bytemuck::{Pod, Zeroable};
 use zune_core::bit_depth::BitDepth;
@@ -20,16 +22,20 @@ use zune_core::colorspace::ColorSpace;
 use crate::channel::{Channel, ChannelErrors};
 use crate::core_filters::colorspace::ColorspaceConv;
 use crate::core_filters::depth::Depth;
+use crate::core_filters::layout::convert_layout;
 use crate::deinterleave::{deinterleave_f32, deinterleave_u16, deinterleave_u8};
 use crate::errors::ImageErrors;
 use crate::frame::Frame;
-use crate::metadata::ImageMetadata;
+use crate::metadata::{ImageMetadata, PixelLayout};
 use crate::traits::{OperationsTrait, ZuneInts};
 
 /// Maximum supported color channels
 pub const MAX_CHANNELS: usize = 4;
 
 /// Represents a single image
+///
+/// Cloning an image doesn't copy its pixels, the clones share them until either
+/// is modified, see [channel](crate::channel#copy-on-write)
 #[derive(Clone)]
 pub struct Image {
     pub(crate) frames:   Vec<Frame>,
@@ -110,6 +116,11 @@ impl Image {
         self.metadata.set_depth(depth)
     }
 
+    /// Get how the channels of this image are stored
+    pub const fn layout(&self) -> PixelLayout {
+        self.metadata.layout()
+    }
+
     /// Return an immutable reference to the metadata of the image
     pub const fn metadata(&self) -> &ImageMetadata {
         &self.metadata
@@ -123,6 +134,14 @@ impl Image {
         &mut self.metadata
     }
 
+    /// Remove metadata that may identify where, when and with what the image was captured
+    ///
+    /// See [`ImageMetadata::strip_metadata`], use [`ImageMetadata::strip_metadata_keeping`]
+    /// to keep some fields
+    pub fn strip_metadata(&mut self) {
+        self.metadata.strip_metadata();
+    }
+
     /// Return an immutable reference to all image frames
     ///
     /// # Returns
@@ -139,9 +158,19 @@ impl Image {
         &mut self.frames
     }
     /// Return a reference to the underlying channels
+    ///
+    /// Interleaved images have one channel per frame holding all components,
+    /// `ignore_alpha` has no effect on them
     pub fn channels_ref(&self, ignore_alpha: bool) -> Vec<&Channel> {
         let colorspace = self.colorspace();
 
+        if self.layout() == PixelLayout::Interleaved {
+            return self
+                .frames_ref()
+                .iter()
+                .flat_map(Frame::channels_vec_ref)
+                .collect();
+        }
         self.frames_ref()
             .iter()
             .flat_map(|x| x.channels_ref(colorspace, ignore_alpha))
@@ -152,9 +181,19 @@ impl Image {
     ///
     /// This gives mutable access to the chanel data allowing
     /// single or multithreaded manipulation of images
+    ///
+    /// Interleaved images have one channel per frame holding all components,
+    /// `ignore_alpha` has no effect on them
     pub fn channels_mut(&mut self, ignore_alpha: bool) -> Vec<&mut Channel> {
         let colorspace = self.colorspace();
 
+        if self.layout() == PixelLayout::Interleaved {
+            return self
+                .frames_mut()
+                .iter_mut()
+                .flat_map(|x| x.channels_vec().iter_mut())
+                .collect();
+        }
         self.frames_mut()
             .iter_mut()
             .flat_map(|x| x.channels_mut(colorspace, ignore_alpha))
@@ -206,7 +245,11 @@ impl Image {
     }
     #[allow(dead_code)]
     pub(crate) fn to_u8_be(&self) -> Vec<Vec<u8>> {
-        let colorspace = self.colorspace();
+        let colorspace = match self.layout() {
+            PixelLayout::Planar => self.colorspace(),
+            // the single channel already holds every sample in order
+            PixelLayout::Interleaved => ColorSpace::Luma
+        };
         if self.metadata.depth() == BitDepth::Eight {
             self.flatten_frames::<u8>()
         } else if self.metadata.depth() == BitDepth::Sixteen {
@@ -475,6 +518,94 @@ impl Image {
 
         Image::new(pixels, BitDepth::Float32, width, height, colorspace)
     }
+    /// Create an interleaved image from raw pixels, without separating the channels
+    ///
+    /// Pixels are expected to be interleaved according to number of components in the colorspace,
+    /// the bit depth is derived from `T`, see [`fill`](Self::fill)
+    ///
+    /// The image keeps the pixels in [`PixelLayout::Interleaved`] layout, which saves a
+    /// deinterleave and interleave when the pixels are only passed on to an encoder or display.
+    /// Operations that need planar channels convert the image when executed.
+    ///
+    /// # Panics
+    /// - If calculating image dimensions will overflow [`usize`]
+    ///
+    /// - If pixels length is not equal to expected length
+    ///
+    /// # Example
+    /// ```
+    /// use zune_core::colorspace::ColorSpace;
+    /// use zune_image::image::Image;
+    /// use zune_image::metadata::PixelLayout;
+    ///
+    /// let image = Image::from_interleaved(&[1_u8, 2, 3, 4, 5, 6], 2, 1, ColorSpace::RGB);
+    ///
+    /// assert_eq!(image.layout(), PixelLayout::Interleaved);
+    /// assert_eq!(image.flatten_frames::<u8>()[0], [1, 2, 3, 4, 5, 6]);
+    /// ```
+    pub fn from_interleaved<T>(
+        pixels: &[T], width: usize, height: usize, colorspace: ColorSpace
+    ) -> Image
+    where
+        T: Copy + 'static + ZuneInts<T> + Zeroable + Pod
+    {
+        let expected_len = checked_mul(width, height, 1, colorspace.num_components());
+        assert_eq!(
+            pixels.len(),
+            expected_len,
+            "Length mismatch, expected {expected_len} but found {} ",
+            pixels.len()
+        );
+        let mut channel = Channel::new_with_capacity::<T>(size_of_val(pixels));
+        channel.extend(pixels);
+
+        let mut image = Image::new(vec![channel], T::depth(), width, height, colorspace);
+        image.metadata.set_layout(PixelLayout::Interleaved);
+
+        image
+    }
+    /// Create an interleaved image from a vector of pixels, taking over its memory
+    /// without copying it
+    ///
+    /// This is [`from_interleaved`](Self::from_interleaved) for pixels that are already owned,
+    /// e.g. frames handed over by a camera or another library, see [`Channel::from_vec`]
+    ///
+    /// # Panics
+    /// - If calculating image dimensions will overflow [`usize`]
+    ///
+    /// - If pixels length is not equal to expected length
+    ///
+    /// # Example
+    /// ```
+    /// use zune_core::colorspace::ColorSpace;
+    /// use zune_image::image::Image;
+    ///
+    /// let pixels = vec![0_u16; 4 * 4 * 3];
+    /// let address = pixels.as_ptr();
+    ///
+    /// let image = Image::from_interleaved_vec(pixels, 4, 4, ColorSpace::RGB);
+    /// assert_eq!(image.view::<u16>().unwrap().pixels().as_ptr(), address);
+    /// ```
+    pub fn from_interleaved_vec<T>(
+        pixels: Vec<T>, width: usize, height: usize, colorspace: ColorSpace
+    ) -> Image
+    where
+        T: Copy + 'static + ZuneInts<T> + Zeroable + Pod
+    {
+        let expected_len = checked_mul(width, height, 1, colorspace.num_components());
+        assert_eq!(
+            pixels.len(),
+            expected_len,
+            "Length mismatch, expected {expected_len} but found {} ",
+            pixels.len()
+        );
+        let channel = Channel::from_vec(pixels);
+
+        let mut image = Image::new(vec![channel], T::depth(), width, height, colorspace);
+        image.metadata.set_layout(PixelLayout::Interleaved);
+
+        image
+    }
     pub fn frames_len(&self) -> usize {
         self.frames.len()
     }
@@ -532,6 +663,27 @@ impl Image {
 
         let (width, height) = self.dimensions();
 
+        if self.layout() == PixelLayout::Interleaved {
+            let components = colorspace.num_components();
+
+            for frame in self.frames.iter_mut() {
+                let data = frame.channels_vec()[0].reinterpret_as_mut::<T>()?;
+
+                for (position, pixel) in data.chunks_exact_mut(components).enumerate() {
+                    let mut output: [&mut T; MAX_CHANNELS] = [
+                        &mut T::default(),
+                        &mut T::default(),
+                        &mut T::default(),
+                        &mut T::default()
+                    ];
+                    for (i, j) in pixel.iter_mut().zip(output.iter_mut()) {
+                        *j = i;
+                    }
+                    (func)(position / width, position % width, output);
+                }
+            }
+            return Ok(());
+        }
         for frame in self.frames.iter_mut() {
             let mut pixel_muts: Vec<&mut [T]> = vec![];
 
@@ -563,6 +715,291 @@ impl Image {
         }
         Ok(())
     }
+    /// Get the pixel at `(x, y)` of the first frame
+    ///
+    /// Returns the channels of the pixel in colorspace order, channels past the
+    /// components of the colorspace are zero. Only the first [`MAX_CHANNELS`] bands
+    /// of multi-band images are returned.
+    ///
+    /// # Errors
+    /// - If `T` is not the type of the image depth, e.g. `u16` for an 8 bit image
+    /// - If the coordinates are outside the image
+    ///
+    /// # Example
+    /// ```
+    /// use zune_core::colorspace::ColorSpace;
+    /// use zune_image::image::Image;
+    ///
+    /// let mut image = Image::fill(10_u8, ColorSpace::RGB, 20, 20);
+    /// image.set_pixel(3, 4, &[255_u8, 0, 0]).unwrap();
+    ///
+    /// assert_eq!(image.pixel_at::<u8>(3, 4).unwrap(), [255, 0, 0, 0]);
+    /// assert_eq!(image.pixel_at::<u8>(0, 0).unwrap(), [10, 10, 10, 0]);
+    /// assert!(image.pixel_at::<u8>(20, 0).is_err());
+    /// ```
+    pub fn pixel_at<T>(&self, x: usize, y: usize) -> Result<[T; MAX_CHANNELS], ImageErrors>
+    where
+        T: Default + Copy + 'static + Pod
+    {
+        let position = self.pixel_position(x, y)?;
+        let mut pixel = [T::default(); MAX_CHANNELS];
+        let channels = self.frames[0].channels_vec_ref();
+
+        if self.layout() == PixelLayout::Interleaved {
+            let components = self.colorspace().num_components();
+            let start = position * components;
+            let data = &channels[0].reinterpret_as::<T>()?[start..start + components];
+
+            for (value, sample) in pixel.iter_mut().zip(data) {
+                *value = *sample;
+            }
+            return Ok(pixel);
+        }
+        for (value, channel) in pixel.iter_mut().zip(channels) {
+            *value = channel.reinterpret_as::<T>()?[position];
+        }
+        Ok(pixel)
+    }
+
+    /// Get the pixel at `(x, y)` of the first frame without checking the coordinates
+    /// or the type, see [`pixel_at`](Self::pixel_at)
+    ///
+    /// # Safety
+    /// - `x` and `y` must be inside the image
+    /// - `T` must be the type of the image depth
+    pub unsafe fn pixel_at_unchecked<T>(&self, x: usize, y: usize) -> [T; MAX_CHANNELS]
+    where
+        T: Default + Copy + 'static + Pod
+    {
+        let position = y * self.dimensions().0 + x;
+        let mut pixel = [T::default(); MAX_CHANNELS];
+        let channels = self.frames[0].channels_vec_ref();
+
+        if self.layout() == PixelLayout::Interleaved {
+            let components = self.colorspace().num_components();
+            let data = bytemuck::cast_slice::<u8, T>(channels[0].alias());
+
+            for (i, value) in pixel.iter_mut().take(components).enumerate() {
+                *value = *data.get_unchecked(position * components + i);
+            }
+            return pixel;
+        }
+        for (value, channel) in pixel.iter_mut().zip(channels) {
+            *value = *bytemuck::cast_slice::<u8, T>(channel.alias()).get_unchecked(position);
+        }
+        pixel
+    }
+
+    /// Set the pixel at `(x, y)` of the first frame
+    ///
+    /// `pixel` holds the channels in colorspace order, values past the channels of the
+    /// image are ignored and channels without a value are left untouched
+    ///
+    /// # Errors
+    /// - If `T` is not the type of the image depth
+    /// - If the coordinates are outside the image
+    pub fn set_pixel<T>(&mut self, x: usize, y: usize, pixel: &[T]) -> Result<(), ImageErrors>
+    where
+        T: Default + Copy + 'static + Pod
+    {
+        let position = self.pixel_position(x, y)?;
+
+        if self.layout() == PixelLayout::Interleaved {
+            let components = self.colorspace().num_components();
+            let start = position * components;
+            let data = self.frames[0].channels_vec()[0].reinterpret_as_mut::<T>()?;
+
+            for (sample, value) in data[start..start + components].iter_mut().zip(pixel) {
+                *sample = *value;
+            }
+            return Ok(());
+        }
+        for (value, channel) in pixel.iter().zip(self.frames[0].channels_vec()) {
+            channel.reinterpret_as_mut::<T>()?[position] = *value;
+        }
+        Ok(())
+    }
+
+    /// Set the pixel at `(x, y)` of the first frame without checking the coordinates
+    /// or the type, see [`set_pixel`](Self::set_pixel)
+    ///
+    /// # Safety
+    /// - `x` and `y` must be inside the image
+    /// - `T` must be the type of the image depth
+    pub unsafe fn set_pixel_unchecked<T>(&mut self, x: usize, y: usize, pixel: &[T])
+    where
+        T: Default + Copy + 'static + Pod
+    {
+        let position = y * self.dimensions().0 + x;
+
+        if self.layout() == PixelLayout::Interleaved {
+            let components = self.colorspace().num_components();
+            let data =
+                bytemuck::cast_slice_mut::<u8, T>(self.frames[0].channels_vec()[0].alias_mut());
+
+            for (i, value) in pixel.iter().take(components).enumerate() {
+                *data.get_unchecked_mut(position * components + i) = *value;
+            }
+            return;
+        }
+        for (value, channel) in pixel.iter().zip(self.frames[0].channels_vec()) {
+            *bytemuck::cast_slice_mut::<u8, T>(channel.alias_mut()).get_unchecked_mut(position) =
+                *value;
+        }
+    }
+
+    fn pixel_position(&self, x: usize, y: usize) -> Result<usize, ImageErrors> {
+        let (width, height) = self.dimensions();
+
+        if x >= width || y >= height {
+            let msg = format!("Pixel ({x},{y}) is outside the {width}x{height} image");
+            return Err(ImageErrors::GenericString(msg));
+        }
+        Ok(y * width + x)
+    }
+}
+
+/// Frame manipulation methods
+impl Image {
+    /// Add a frame to the end of the image
+    ///
+    /// The frame must have the same dimensions, depth and layout as the image
+    ///
+    /// # Errors
+    /// If the channels of the frame don't match the image
+    pub fn push_frame(&mut self, frame: Frame) -> Result<(), ImageErrors> {
+        self.insert_frame(self.frames.len(), frame)
+    }
+
+    /// Insert a frame at `index`, shifting the frames after it
+    ///
+    /// The frame must have the same dimensions, depth and layout as the image
+    ///
+    /// # Errors
+    /// If the index is past the last frame or the channels of the frame don't match the image
+    pub fn insert_frame(&mut self, index: usize, frame: Frame) -> Result<(), ImageErrors> {
+        if index > self.frames.len() {
+            let msg = format!("Frame index {index} is past {} frames", self.frames.len());
+            return Err(ImageErrors::GenericString(msg));
+        }
+        self.check_frame(&frame)?;
+        self.frames.insert(index, frame);
+
+        Ok(())
+    }
+
+    /// Remove the frame at `index` and return it
+    ///
+    /// # Errors
+    /// If there is no such frame or it is the only frame of the image
+    pub fn remove_frame(&mut self, index: usize) -> Result<Frame, ImageErrors> {
+        if self.frames.len() == 1 {
+            return Err(ImageErrors::GenericStr(
+                "Cannot remove the only frame of an image"
+            ));
+        }
+        self.frame_index(index)?;
+
+        Ok(self.frames.remove(index))
+    }
+
+    /// Swap the frames at `a` and `b`
+    ///
+    /// # Errors
+    /// If there is no such frame
+    pub fn swap_frames(&mut self, a: usize, b: usize) -> Result<(), ImageErrors> {
+        self.frame_index(a)?;
+        self.frame_index(b)?;
+        self.frames.swap(a, b);
+
+        Ok(())
+    }
+
+    /// Reorder the frames, `order` lists the current index of every frame in its new position
+    ///
+    /// # Example
+    /// ```
+    /// use zune_core::colorspace::ColorSpace;
+    /// use zune_image::image::Image;
+    ///
+    /// let mut image = Image::fill(0_u8, ColorSpace::Luma, 2, 2);
+    /// for duration in [2, 3] {
+    ///     let mut frame = image.frames_ref()[0].clone();
+    ///     frame.set_duration(duration, 10);
+    ///     image.push_frame(frame).unwrap();
+    /// }
+    /// // play backwards
+    /// image.reorder_frames(&[2, 1, 0]).unwrap();
+    ///
+    /// assert_eq!(image.frames_ref()[0].duration(), (3, 10));
+    /// ```
+    ///
+    /// # Errors
+    /// If `order` isn't a permutation of the frame indices
+    pub fn reorder_frames(&mut self, order: &[usize]) -> Result<(), ImageErrors> {
+        let mut seen = vec![false; self.frames.len()];
+
+        for index in order {
+            if seen.get(*index) != Some(&false) {
+                return Err(ImageErrors::GenericStr(
+                    "Frame order must contain every frame index exactly once"
+                ));
+            }
+            seen[*index] = true;
+        }
+        if order.len() != self.frames.len() {
+            return Err(ImageErrors::GenericStr(
+                "Frame order must contain every frame index exactly once"
+            ));
+        }
+        let mut frames: Vec<Option<Frame>> = self.frames.drain(..).map(Some).collect();
+
+        self.frames = order.iter().filter_map(|x| frames[*x].take()).collect();
+
+        Ok(())
+    }
+
+    /// Return the total duration of all frames in seconds
+    pub fn total_duration_secs(&self) -> f64 {
+        self.frames.iter().map(Frame::duration_secs).sum()
+    }
+
+    fn frame_index(&self, index: usize) -> Result<(), ImageErrors> {
+        if index >= self.frames.len() {
+            let msg = format!("No frame {index}, image has {} frames", self.frames.len());
+            return Err(ImageErrors::GenericString(msg));
+        }
+        Ok(())
+    }
+
+    fn check_frame(&self, frame: &Frame) -> Result<(), ImageErrors> {
+        let (width, height) = self.dimensions();
+        let components = self.colorspace().num_components();
+
+        let (expected_channels, per_channel) = match self.layout() {
+            PixelLayout::Planar => (components, 1),
+            PixelLayout::Interleaved => (1, components)
+        };
+        let expected_length = width * height * per_channel * self.depth().size_of();
+        let channels = frame.channels_vec_ref();
+
+        if channels.len() != expected_channels {
+            let msg = format!(
+                "Frame has {} channels, expected {expected_channels}",
+                channels.len()
+            );
+            return Err(ImageErrors::GenericString(msg));
+        }
+        for channel in channels {
+            if channel.len() != expected_length {
+                return Err(ImageErrors::DimensionsMisMatch(
+                    expected_length,
+                    channel.len()
+                ));
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Image conversion routines
@@ -582,6 +1019,25 @@ impl Image {
     pub fn convert_depth(&mut self, to: BitDepth) -> Result<(), ImageErrors> {
         Depth::new(to).execute(self)
     }
+    /// Convert the image between planar and interleaved storage
+    ///
+    /// # Arguments
+    /// - to: The layout to store the channels in
+    ///
+    /// # Example
+    /// ```
+    /// use zune_core::colorspace::ColorSpace;
+    /// use zune_image::image::Image;
+    /// use zune_image::metadata::PixelLayout;
+    ///
+    /// let mut image = Image::fill(10_u8, ColorSpace::RGBA, 4, 4);
+    /// image.convert_layout(PixelLayout::Interleaved).unwrap();
+    ///
+    /// assert_eq!(image.frames_ref()[0].channels_vec_ref().len(), 1);
+    /// ```
+    pub fn convert_layout(&mut self, to: PixelLayout) -> Result<(), ImageErrors> {
+        convert_layout(self, to)
+    }
 }
 
 pub(crate) fn checked_mul(
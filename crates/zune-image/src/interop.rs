@@ -0,0 +1,29 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Conversions to and from the image types of other crates
+//!
+//! Each conversion is behind the feature of the crate it converts to, so none of them
+//! add dependencies unless asked for.
+//!
+//! | Feature           | Types                                                                               |
+//! |-------------------|-------------------------------------------------------------------------------------|
+//! | `image-interop`   | [`image::DynamicImage`], see [`dynamic_image`]                                      |
+//! | `ndarray-interop` | [`::ndarray::Array3`] and [`::ndarray::ArrayView3`], see [`ndarray`](self::ndarray) |
+//! | `rgb-interop`     | Slices of [`::rgb::RGB8`], [`::rgb::RGBA8`], [`::rgb::RGB16`] etc, see [`rgb`](self::rgb) |
+//! | `candle-interop`  | [`candle_core::Tensor`] from exported tensors, see [`candle`](self::candle)         |
+//! | `tch-interop`     | [`::tch::Tensor`] from exported tensors, see [`tch`](self::tch)                     |
+#[cfg(feature = "candle-interop")]
+pub mod candle;
+#[cfg(feature = "image-interop")]
+pub mod dynamic_image;
+#[cfg(feature = "ndarray-interop")]
+pub mod ndarray;
+#[cfg(feature = "rgb-interop")]
+pub mod rgb;
+#[cfg(feature = "tch-interop")]
+pub mod tch;
@@ -0,0 +1,60 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Conversions from [`Image`] to candle's [`Tensor`](candle_core::Tensor)
+//!
+//! Images are exported with [`Image::to_tensor`] first, so the layout, channel order
+//! and normalization come from [`TensorLayout`] and [`ExportOptions`].
+//!
+//! # Example
+//! ```
+//! use candle_core::Device;
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::export::{ExportOptions, TensorLayout};
+//! use zune_image::image::Image;
+//!
+//! let image = Image::fill(128_u8, ColorSpace::RGB, 32, 16);
+//!
+//! let options = ExportOptions::new().set_mean_std(&[0.5; 3], &[0.5; 3]);
+//! let tensor = image
+//!     .to_candle_tensor(TensorLayout::Nchw, &options, &Device::Cpu)
+//!     .unwrap();
+//!
+//! assert_eq!(tensor.dims(), [1, 3, 16, 32]);
+//! ```
+use alloc::format;
+
+use candle_core::Device;
+
+use crate::errors::ImageErrors;
+use crate::export::{ExportOptions, Tensor, TensorLayout};
+use crate::image::Image;
+
+impl Tensor {
+    /// Move the exported floats into a candle tensor of the same shape on `device`
+    ///
+    /// # Errors
+    /// If candle can't create the tensor on `device`
+    pub fn into_candle(self, device: &Device) -> Result<candle_core::Tensor, ImageErrors> {
+        let [d0, d1, d2, d3] = self.shape();
+
+        candle_core::Tensor::from_vec(self.into_data(), (d0, d1, d2, d3), device)
+            .map_err(|e| ImageErrors::GenericString(format!("Could not create tensor: {e}")))
+    }
+}
+
+impl Image {
+    /// Export the image as a candle tensor with a batch of one image on `device`
+    ///
+    /// # Errors
+    /// The errors of [`Image::to_tensor`] and [`Tensor::into_candle`]
+    pub fn to_candle_tensor(
+        &self, layout: TensorLayout, options: &ExportOptions, device: &Device
+    ) -> Result<candle_core::Tensor, ImageErrors> {
+        self.to_tensor(layout, options)?.into_candle(device)
+    }
+}
@@ -0,0 +1,230 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Conversions between [`Image`] and the image crate's [`DynamicImage`]
+//!
+//! Projects using the image crate can move one step at a time, converting to an [`Image`]
+//! where zune-image is used and back to a [`DynamicImage`] for the rest.
+//!
+//! Converting a [`DynamicImage`] is lossless, each of its variants has a matching
+//! colorspace and depth.
+//!
+//! Converting an [`Image`] keeps its depth and colorspace, when the image crate has them:
+//! - Luma, LumaA, RGB and RGBA images at 8, 16 and 32 bit float depths convert as they are,
+//!   except for float Luma and LumaA which become RGB and RGBA, the image crate has no
+//!   float gray images.
+//! - Half float images are converted to 32 bit floats.
+//! - Other colorspaces are converted to RGB, or RGBA if they have alpha.
+//!
+//! Only the first frame of animated images is converted, and metadata is not carried over.
+//!
+//! # Example
+//! ```
+//! use image::DynamicImage;
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//!
+//! let image = Image::fill(100_u8, ColorSpace::BGR, 20, 10);
+//!
+//! let dynamic = DynamicImage::try_from(&image).unwrap();
+//! assert_eq!(dynamic.as_rgb8().unwrap().get_pixel(0, 0).0, [100, 100, 100]);
+//!
+//! let back = Image::from(dynamic);
+//! assert_eq!(back.colorspace(), ColorSpace::RGB);
+//! ```
+use image::{DynamicImage, ImageBuffer, Pixel};
+use zune_core::bit_depth::{BitDepth, BitType};
+use zune_core::colorspace::ColorSpace;
+
+use crate::core_filters::colorspace::ColorspaceConv;
+use crate::core_filters::depth::Depth;
+use crate::errors::ImageErrors;
+use crate::image::Image;
+use crate::traits::OperationsTrait;
+
+impl TryFrom<&Image> for DynamicImage {
+    type Error = ImageErrors;
+
+    /// Convert the first frame of an image, see the [module docs](self)
+    fn try_from(image: &Image) -> Result<Self, Self::Error> {
+        let (width, height) = image.dimensions();
+
+        let (Ok(width), Ok(height)) = (u32::try_from(width), u32::try_from(height)) else {
+            return Err(ImageErrors::GenericStr(
+                "Image dimensions don't fit the image crate's u32 dimensions"
+            ));
+        };
+        let colorspace = image.colorspace();
+        let is_float = matches!(image.depth(), BitDepth::Float16 | BitDepth::Float32);
+
+        let target = match colorspace {
+            ColorSpace::RGB | ColorSpace::RGBA => colorspace,
+            ColorSpace::Luma | ColorSpace::LumaA if !is_float => colorspace,
+            c if c.has_alpha() => ColorSpace::RGBA,
+            _ => ColorSpace::RGB
+        };
+
+        let mut converted = None;
+
+        if target != colorspace || image.depth() == BitDepth::Float16 {
+            let mut copy = image.clone();
+
+            if image.depth() == BitDepth::Float16 {
+                Depth::new(BitDepth::Float32).execute(&mut copy)?;
+            }
+            ColorspaceConv::new(target).execute(&mut copy)?;
+            converted = Some(copy);
+        }
+        let image = converted.as_ref().unwrap_or(image);
+
+        let Some(frame) = image.frames_ref().first() else {
+            return Err(ImageErrors::GenericStr("Image has no frames to convert"));
+        };
+
+        let dynamic = match (image.depth().bit_type(), target) {
+            (BitType::U8, ColorSpace::Luma) => {
+                DynamicImage::ImageLuma8(buffer(width, height, frame.flatten())?)
+            }
+            (BitType::U8, ColorSpace::LumaA) => {
+                DynamicImage::ImageLumaA8(buffer(width, height, frame.flatten())?)
+            }
+            (BitType::U8, ColorSpace::RGB) => {
+                DynamicImage::ImageRgb8(buffer(width, height, frame.flatten())?)
+            }
+            (BitType::U8, ColorSpace::RGBA) => {
+                DynamicImage::ImageRgba8(buffer(width, height, frame.flatten())?)
+            }
+            (BitType::U16, ColorSpace::Luma) => {
+                DynamicImage::ImageLuma16(buffer(width, height, frame.flatten())?)
+            }
+            (BitType::U16, ColorSpace::LumaA) => {
+                DynamicImage::ImageLumaA16(buffer(width, height, frame.flatten())?)
+            }
+            (BitType::U16, ColorSpace::RGB) => {
+                DynamicImage::ImageRgb16(buffer(width, height, frame.flatten())?)
+            }
+            (BitType::U16, ColorSpace::RGBA) => {
+                DynamicImage::ImageRgba16(buffer(width, height, frame.flatten())?)
+            }
+            (BitType::F32, ColorSpace::RGB) => {
+                DynamicImage::ImageRgb32F(buffer(width, height, frame.flatten())?)
+            }
+            (BitType::F32, ColorSpace::RGBA) => {
+                DynamicImage::ImageRgba32F(buffer(width, height, frame.flatten())?)
+            }
+            (depth, _) => {
+                return Err(ImageErrors::GenericString(format!(
+                    "Images of depth {depth:?} can't be converted to a DynamicImage"
+                )))
+            }
+        };
+        Ok(dynamic)
+    }
+}
+
+impl TryFrom<Image> for DynamicImage {
+    type Error = ImageErrors;
+
+    /// Convert the first frame of an image, see the [module docs](self)
+    fn try_from(image: Image) -> Result<Self, Self::Error> {
+        DynamicImage::try_from(&image)
+    }
+}
+
+impl From<&DynamicImage> for Image {
+    /// Convert an image of the image crate, keeping its depth and colorspace
+    fn from(dynamic: &DynamicImage) -> Self {
+        let width = dynamic.width() as usize;
+        let height = dynamic.height() as usize;
+
+        match dynamic {
+            DynamicImage::ImageLuma8(x) => Image::from_u8(x, width, height, ColorSpace::Luma),
+            DynamicImage::ImageLumaA8(x) => Image::from_u8(x, width, height, ColorSpace::LumaA),
+            DynamicImage::ImageRgb8(x) => Image::from_u8(x, width, height, ColorSpace::RGB),
+            DynamicImage::ImageRgba8(x) => Image::from_u8(x, width, height, ColorSpace::RGBA),
+            DynamicImage::ImageLuma16(x) => Image::from_u16(x, width, height, ColorSpace::Luma),
+            DynamicImage::ImageLumaA16(x) => Image::from_u16(x, width, height, ColorSpace::LumaA),
+            DynamicImage::ImageRgb16(x) => Image::from_u16(x, width, height, ColorSpace::RGB),
+            DynamicImage::ImageRgba16(x) => Image::from_u16(x, width, height, ColorSpace::RGBA),
+            DynamicImage::ImageRgb32F(x) => Image::from_f32(x, width, height, ColorSpace::RGB),
+            DynamicImage::ImageRgba32F(x) => Image::from_f32(x, width, height, ColorSpace::RGBA),
+            // variants added after this was written
+            other => Image::from_f32(&other.to_rgba32f(), width, height, ColorSpace::RGBA)
+        }
+    }
+}
+
+impl From<DynamicImage> for Image {
+    /// Convert an image of the image crate, keeping its depth and colorspace
+    fn from(dynamic: DynamicImage) -> Self {
+        Image::from(&dynamic)
+    }
+}
+
+/// Wrap interleaved samples in an image buffer
+fn buffer<P: Pixel>(
+    width: u32, height: u32, samples: Vec<P::Subpixel>
+) -> Result<ImageBuffer<P, Vec<P::Subpixel>>, ImageErrors> {
+    ImageBuffer::from_raw(width, height, samples).ok_or(ImageErrors::GenericStr(
+        "Image samples don't match its dimensions"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use image::DynamicImage;
+    use zune_core::bit_depth::BitDepth;
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::half::F16;
+    use crate::image::Image;
+
+    #[test]
+    fn test_round_trips() {
+        let pixels: Vec<u16> = (0..4 * 6 * 5).map(|x| x * 500).collect();
+
+        for colorspace in [
+            ColorSpace::Luma,
+            ColorSpace::LumaA,
+            ColorSpace::RGB,
+            ColorSpace::RGBA
+        ] {
+            let samples = &pixels[..6 * 5 * colorspace.num_components()];
+            let bytes: Vec<u8> = samples.iter().map(|x| (*x >> 8) as u8).collect();
+            let floats: Vec<f32> = samples.iter().map(|x| f32::from(*x) / 65535.0).collect();
+
+            let images = [
+                Image::from_u8(&bytes, 6, 5, colorspace),
+                Image::from_u16(samples, 6, 5, colorspace)
+            ];
+            for image in images {
+                let back = Image::from(DynamicImage::try_from(&image).unwrap());
+                assert_eq!(back.colorspace(), colorspace);
+                assert_eq!(back.depth(), image.depth());
+                assert!(back == image);
+            }
+            if matches!(colorspace, ColorSpace::RGB | ColorSpace::RGBA) {
+                let image = Image::from_f32(&floats, 6, 5, colorspace);
+                let back = Image::from(DynamicImage::try_from(&image).unwrap());
+                assert!(back == image);
+            }
+        }
+    }
+
+    #[test]
+    fn test_converted_colorspaces() {
+        // float gray becomes float RGB, half floats become floats
+        let image = Image::fill(F16::from_f32(0.5), ColorSpace::LumaA, 4, 4);
+        let dynamic = DynamicImage::try_from(&image).unwrap();
+        assert_eq!(dynamic.as_rgba32f().unwrap().get_pixel(1, 1).0, [0.5; 4]);
+
+        let image = Image::fill(7_u16, ColorSpace::BGRA, 4, 4);
+        let back = Image::from(DynamicImage::try_from(&image).unwrap());
+        assert_eq!(back.colorspace(), ColorSpace::RGBA);
+        assert_eq!(back.depth(), BitDepth::Sixteen);
+    }
+}
@@ -0,0 +1,244 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Conversions between [`Image`] and ndarray's [`Array3`] and [`ArrayView3`]
+//!
+//! Arrays have the shape `(height, width, components)`, the same order pixels of an
+//! interleaved image are stored in, so images can go through ndarray math and come back
+//! to be saved with any encoder.
+//!
+//! - [`Image::as_ndarray`] and [`Image::as_ndarray_mut`] borrow the pixels of interleaved
+//!   images, and of single component images, without a copy. Other images have a channel
+//!   per component, convert them with [`Image::convert_layout`] first.
+//! - [`Image::to_ndarray`] copies the pixels of any image, interleaving them if needed.
+//! - [`Image::from_ndarray`] takes over the memory of arrays in standard layout, and
+//!   copies other ones. [`Image::from_ndarray_view`] always copies.
+//!
+//! Only the first frame of animated images is converted, the element type of the array
+//! must be the type of the image's pixels, i.e. `u8`, `u16` or `f32`.
+//!
+//! # Example
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//!
+//! let pixels = ndarray::Array3::<f32>::from_elem((10, 20, 3), 0.5);
+//! let mut image = Image::from_ndarray(pixels, ColorSpace::RGB).unwrap();
+//!
+//! // brighten the red channel in place
+//! let mut array = image.as_ndarray_mut::<f32>().unwrap();
+//! array.slice_mut(ndarray::s![.., .., 0]).mapv_inplace(|x| x * 2.0);
+//!
+//! assert_eq!(image.to_ndarray::<f32>().unwrap()[[5, 5, 0]], 1.0);
+//! ```
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use bytemuck::{Pod, Zeroable};
+use ndarray::{Array3, ArrayView3, ArrayViewMut3};
+use zune_core::colorspace::ColorSpace;
+
+use crate::errors::ImageErrors;
+use crate::image::Image;
+use crate::metadata::PixelLayout;
+use crate::traits::ZuneInts;
+
+impl Image {
+    /// Borrow the pixels of the first frame as an array of shape `(height, width, components)`
+    ///
+    /// # Errors
+    /// If the image has a channel per component or `T` isn't the type of its pixels
+    pub fn as_ndarray<T>(&self) -> Result<ArrayView3<'_, T>, ImageErrors>
+    where
+        T: Copy + Default + 'static + ZuneInts<T> + Zeroable + Pod
+    {
+        let shape = self.ndarray_shape::<T>()?;
+        let pixels = self.frames_ref()[0].channels_vec_ref()[0].reinterpret_as::<T>()?;
+
+        ArrayView3::from_shape(shape, pixels)
+            .map_err(|e| ImageErrors::GenericString(format!("Could not create array: {e}")))
+    }
+
+    /// Mutably borrow the pixels of the first frame as an array of shape
+    /// `(height, width, components)`
+    ///
+    /// # Errors
+    /// If the image has a channel per component or `T` isn't the type of its pixels
+    pub fn as_ndarray_mut<T>(&mut self) -> Result<ArrayViewMut3<'_, T>, ImageErrors>
+    where
+        T: Copy + Default + 'static + ZuneInts<T> + Zeroable + Pod
+    {
+        let shape = self.ndarray_shape::<T>()?;
+        let pixels = self.frames_mut()[0].channels_vec()[0].reinterpret_as_mut::<T>()?;
+
+        ArrayViewMut3::from_shape(shape, pixels)
+            .map_err(|e| ImageErrors::GenericString(format!("Could not create array: {e}")))
+    }
+
+    /// Copy the pixels of the first frame into an array of shape `(height, width, components)`
+    ///
+    /// # Errors
+    /// If `T` isn't the type of the image's pixels
+    pub fn to_ndarray<T>(&self) -> Result<Array3<T>, ImageErrors>
+    where
+        T: Copy + Default + 'static + ZuneInts<T> + Zeroable + Pod
+    {
+        check_depth::<T>(self)?;
+
+        let (width, height) = self.dimensions();
+        let components = self.colorspace().num_components();
+        let frame = &self.frames_ref()[0];
+
+        let pixels = match self.layout() {
+            PixelLayout::Interleaved => frame.channels_vec_ref()[0].reinterpret_as::<T>()?.to_vec(),
+            PixelLayout::Planar => {
+                let mut pixels = vec![T::default(); width * height * components];
+                frame.flatten_into(&mut pixels)?;
+                pixels
+            }
+        };
+        Array3::from_shape_vec((height, width, components), pixels)
+            .map_err(|e| ImageErrors::GenericString(format!("Could not create array: {e}")))
+    }
+
+    /// Create an interleaved image from an array of shape `(height, width, components)`
+    ///
+    /// Arrays in standard layout, i.e. rows one after the other with interleaved
+    /// components, are taken over without copying the pixels, other ones are copied.
+    ///
+    /// # Errors
+    /// If the last axis doesn't have as many elements as the colorspace has components
+    pub fn from_ndarray<T>(array: Array3<T>, colorspace: ColorSpace) -> Result<Image, ImageErrors>
+    where
+        T: Copy + Default + 'static + ZuneInts<T> + Zeroable + Pod
+    {
+        if !array.is_standard_layout() || array.is_empty() {
+            return Image::from_ndarray_view(array.view(), colorspace);
+        }
+        let (height, width, components) = array.dim();
+        check_components(components, colorspace)?;
+
+        let start = array.as_ptr();
+        let mut pixels = array.into_raw_vec();
+
+        // a sliced array starts inside its memory, move its pixels to the front
+        let offset = (start as usize - pixels.as_ptr() as usize) / size_of::<T>();
+        pixels.drain(..offset);
+        pixels.truncate(width * height * components);
+
+        Ok(Image::from_interleaved_vec(
+            pixels, width, height, colorspace
+        ))
+    }
+
+    /// Create an interleaved image from a copy of an array of shape `(height, width, components)`
+    ///
+    /// # Errors
+    /// If the last axis doesn't have as many elements as the colorspace has components
+    pub fn from_ndarray_view<T>(
+        array: ArrayView3<'_, T>, colorspace: ColorSpace
+    ) -> Result<Image, ImageErrors>
+    where
+        T: Copy + Default + 'static + ZuneInts<T> + Zeroable + Pod
+    {
+        let (height, width, components) = array.dim();
+        check_components(components, colorspace)?;
+
+        // iterating goes in logical order, whatever the strides
+        let pixels = array.iter().copied().collect::<Vec<T>>();
+
+        Ok(Image::from_interleaved_vec(
+            pixels, width, height, colorspace
+        ))
+    }
+
+    fn ndarray_shape<T: ZuneInts<T>>(&self) -> Result<(usize, usize, usize), ImageErrors> {
+        let components = self.colorspace().num_components();
+
+        if self.layout() != PixelLayout::Interleaved && components != 1 {
+            return Err(ImageErrors::GenericStr(
+                "Only interleaved images can be borrowed as arrays, convert the layout first"
+            ));
+        }
+        check_depth::<T>(self)?;
+        let (width, height) = self.dimensions();
+
+        Ok((height, width, components))
+    }
+}
+
+fn check_depth<T: ZuneInts<T>>(image: &Image) -> Result<(), ImageErrors> {
+    if image.depth() != T::depth() {
+        return Err(ImageErrors::GenericString(format!(
+            "Image depth {:?} doesn't match the array type's depth {:?}",
+            image.depth(),
+            T::depth()
+        )));
+    }
+    Ok(())
+}
+
+fn check_components(components: usize, colorspace: ColorSpace) -> Result<(), ImageErrors> {
+    if components != colorspace.num_components() {
+        return Err(ImageErrors::GenericString(format!(
+            "Array has {components} components per pixel but colorspace {colorspace:?} has {}",
+            colorspace.num_components()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{s, Array3};
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::image::Image;
+    use crate::metadata::PixelLayout;
+
+    #[test]
+    fn test_ndarray_round_trip() {
+        let array = Array3::from_shape_fn((3, 4, 3), |(y, x, c)| (y * 100 + x * 10 + c) as u16);
+        let copy = array.clone();
+        let address = copy.as_ptr();
+
+        let image = Image::from_ndarray(copy, ColorSpace::RGB).unwrap();
+        assert_eq!(image.dimensions(), (4, 3));
+        assert_eq!(image.as_ndarray::<u16>().unwrap().as_ptr(), address);
+        assert_eq!(image.to_ndarray::<u16>().unwrap(), array);
+
+        // planar images are copied, not borrowed
+        let mut planar = image.clone();
+        planar.convert_layout(PixelLayout::Planar).unwrap();
+        assert!(planar.as_ndarray::<u16>().is_err());
+        assert_eq!(planar.to_ndarray::<u16>().unwrap(), array);
+
+        assert!(image.as_ndarray::<u8>().is_err());
+        assert!(Image::from_ndarray(array.clone(), ColorSpace::RGBA).is_err());
+    }
+
+    #[test]
+    fn test_ndarray_non_standard_layouts() {
+        let array = Array3::from_shape_fn((4, 6, 1), |(y, x, _)| (y * 6 + x) as u8);
+
+        let sliced = array.slice(s![1..3, 2..5, ..]).to_owned();
+        let image =
+            Image::from_ndarray(array.clone().slice_move(s![1.., .., ..]), ColorSpace::Luma);
+        assert_eq!(
+            image.unwrap().to_ndarray::<u8>().unwrap(),
+            array.slice(s![1.., .., ..])
+        );
+
+        let image = Image::from_ndarray_view(array.slice(s![1..3, 2..5, ..]), ColorSpace::Luma);
+        assert_eq!(image.unwrap().to_ndarray::<u8>().unwrap(), sliced);
+
+        let transposed = array.clone().permuted_axes([1, 0, 2]);
+        let image = Image::from_ndarray(transposed.clone(), ColorSpace::Luma).unwrap();
+        assert_eq!(image.dimensions(), (4, 6));
+        assert_eq!(image.to_ndarray::<u8>().unwrap(), transposed);
+    }
+}
@@ -0,0 +1,244 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Conversions between [`Image`] and slices of the rgb crate's pixel types
+//!
+//! Many crates take or return pixels as [`RGB8`](rgb::RGB8), [`RGBA8`](rgb::RGBA8),
+//! [`RGB16`](rgb::RGB16) and friends, these conversions move images between them and
+//! zune-image without going through bytes.
+//!
+//! Each pixel type has a colorspace and a depth, see [`RgbPixel`]:
+//!
+//! | Pixel                     | Colorspace | Component types     |
+//! |---------------------------|------------|---------------------|
+//! | [`RGB`]                   | RGB        | `u8`, `u16`, `f32`  |
+//! | [`RGBA`]                  | RGBA       | `u8`, `u16`, `f32`  |
+//! | [`BGR`]                   | BGR        | `u8`, `u16`, `f32`  |
+//! | [`BGRA`]                  | BGRA       | `u8`, `u16`, `f32`  |
+//! | [`Gray`]                  | Luma       | `u8`, `u16`, `f32`  |
+//! | [`GrayAlpha`]             | LumaA      | `u8`, `u16`, `f32`  |
+//!
+//! - [`Image::as_rgb_pixels`] and [`Image::as_rgb_pixels_mut`] borrow the pixels of
+//!   interleaved images whose colorspace and depth match the pixel type.
+//! - [`Image::to_rgb_pixels`] copies the pixels of any image, converting its colorspace,
+//!   depth and layout when they don't match.
+//! - [`Image::from_rgb_pixels`] copies pixels into a new interleaved image.
+//!
+//! Only the first frame of animated images is converted.
+//!
+//! # Example
+//! ```
+//! use rgb::{RGB8, RGBA16};
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//!
+//! let pixels = vec![RGB8::new(255, 0, 0); 20 * 10];
+//! let image = Image::from_rgb_pixels(&pixels, 20, 10).unwrap();
+//! assert_eq!(image.colorspace(), ColorSpace::RGB);
+//!
+//! // borrowed since the image matches the pixel type
+//! assert_eq!(image.as_rgb_pixels::<RGB8>().unwrap()[0], RGB8::new(255, 0, 0));
+//!
+//! // converted since it doesn't
+//! let converted = image.to_rgb_pixels::<RGBA16>().unwrap();
+//! assert_eq!(converted[0], RGBA16::new(65535, 0, 0, 65535));
+//! ```
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use bytemuck::{Pod, Zeroable};
+use rgb::alt::{Gray, GrayAlpha, BGR, BGRA};
+use rgb::{RGB, RGBA};
+use zune_core::colorspace::ColorSpace;
+
+use crate::core_filters::colorspace::ColorspaceConv;
+use crate::core_filters::depth::Depth;
+use crate::errors::ImageErrors;
+use crate::image::Image;
+use crate::metadata::PixelLayout;
+use crate::traits::{OperationsTrait, ZuneInts};
+
+/// A pixel type of the rgb crate that images can be converted to and from
+///
+/// This is implemented for the pixel types listed in the [module docs](self),
+/// with `u8`, `u16` and `f32` components.
+pub trait RgbPixel: Copy + Pod {
+    /// The type of each component of the pixel
+    type Component: Copy + Default + 'static + ZuneInts<Self::Component> + Zeroable + Pod;
+    /// The colorspace of images holding these pixels
+    const COLORSPACE: ColorSpace;
+}
+
+macro_rules! rgb_pixel {
+    ($pixel:ident, $colorspace:expr) => {
+        rgb_pixel!($pixel, $colorspace, u8);
+        rgb_pixel!($pixel, $colorspace, u16);
+        rgb_pixel!($pixel, $colorspace, f32);
+    };
+    ($pixel:ident, $colorspace:expr, $component:ty) => {
+        impl RgbPixel for $pixel<$component> {
+            type Component = $component;
+            const COLORSPACE: ColorSpace = $colorspace;
+        }
+    };
+}
+
+rgb_pixel!(RGB, ColorSpace::RGB);
+rgb_pixel!(RGBA, ColorSpace::RGBA);
+rgb_pixel!(BGR, ColorSpace::BGR);
+rgb_pixel!(BGRA, ColorSpace::BGRA);
+rgb_pixel!(Gray, ColorSpace::Luma);
+rgb_pixel!(GrayAlpha, ColorSpace::LumaA);
+
+impl Image {
+    /// Borrow the pixels of the first frame as rgb pixels, in row order
+    ///
+    /// # Errors
+    /// If the image has a channel per component, or its colorspace or depth isn't
+    /// the one of `P`, see [`Image::to_rgb_pixels`] to convert them
+    pub fn as_rgb_pixels<P: RgbPixel>(&self) -> Result<&[P], ImageErrors> {
+        self.check_rgb_pixels::<P>()?;
+        let pixels = self.frames_ref()[0].channels_vec_ref()[0].reinterpret_as::<P::Component>()?;
+
+        Ok(bytemuck::cast_slice(pixels))
+    }
+
+    /// Mutably borrow the pixels of the first frame as rgb pixels, in row order
+    ///
+    /// # Errors
+    /// If the image has a channel per component, or its colorspace or depth isn't
+    /// the one of `P`
+    pub fn as_rgb_pixels_mut<P: RgbPixel>(&mut self) -> Result<&mut [P], ImageErrors> {
+        self.check_rgb_pixels::<P>()?;
+        let pixels =
+            self.frames_mut()[0].channels_vec()[0].reinterpret_as_mut::<P::Component>()?;
+
+        Ok(bytemuck::cast_slice_mut(pixels))
+    }
+
+    /// Copy the pixels of the first frame into rgb pixels, in row order
+    ///
+    /// Images with a different colorspace or depth than `P` are converted first,
+    /// the image itself is left as it is.
+    ///
+    /// # Errors
+    /// If the colorspace of the image can't be converted to the one of `P`
+    pub fn to_rgb_pixels<P: RgbPixel>(&self) -> Result<Vec<P>, ImageErrors> {
+        if self.colorspace() != P::COLORSPACE || self.depth() != P::Component::depth() {
+            let mut copy = self.clone();
+
+            Depth::new(P::Component::depth()).execute(&mut copy)?;
+            ColorspaceConv::new(P::COLORSPACE).execute(&mut copy)?;
+
+            return copy.to_rgb_pixels();
+        }
+        let (width, height) = self.dimensions();
+        let frame = &self.frames_ref()[0];
+
+        if self.layout() == PixelLayout::Interleaved || P::COLORSPACE.num_components() == 1 {
+            let pixels = frame.channels_vec_ref()[0].reinterpret_as::<P::Component>()?;
+            return Ok(bytemuck::cast_slice(pixels).to_vec());
+        }
+        let mut pixels = vec![P::zeroed(); width * height];
+        frame.flatten_into::<P::Component>(bytemuck::cast_slice_mut(&mut pixels))?;
+
+        Ok(pixels)
+    }
+
+    /// Create an interleaved image from a copy of rgb pixels, in row order
+    ///
+    /// The colorspace and depth of the image are the ones of `P`.
+    ///
+    /// # Errors
+    /// If there aren't `width * height` pixels
+    pub fn from_rgb_pixels<P: RgbPixel>(
+        pixels: &[P], width: usize, height: usize
+    ) -> Result<Image, ImageErrors> {
+        if Some(pixels.len()) != width.checked_mul(height) {
+            return Err(ImageErrors::GenericString(format!(
+                "Expected {width}x{height} pixels but found {}",
+                pixels.len()
+            )));
+        }
+        let components: &[P::Component] = bytemuck::cast_slice(pixels);
+
+        Ok(Image::from_interleaved_vec(
+            components.to_vec(),
+            width,
+            height,
+            P::COLORSPACE
+        ))
+    }
+
+    fn check_rgb_pixels<P: RgbPixel>(&self) -> Result<(), ImageErrors> {
+        if self.colorspace() != P::COLORSPACE || self.depth() != P::Component::depth() {
+            return Err(ImageErrors::GenericString(format!(
+                "Image has colorspace {:?} and depth {:?} but the pixels are {:?} with depth {:?}",
+                self.colorspace(),
+                self.depth(),
+                P::COLORSPACE,
+                P::Component::depth()
+            )));
+        }
+        if self.layout() != PixelLayout::Interleaved && P::COLORSPACE.num_components() != 1 {
+            return Err(ImageErrors::GenericStr(
+                "Only interleaved images can be borrowed as pixels, convert the layout first"
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rgb::alt::{Gray, BGRA};
+    use rgb::{RGB16, RGB8, RGBA8};
+    use zune_core::bit_depth::BitDepth;
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::image::Image;
+    use crate::metadata::PixelLayout;
+
+    #[test]
+    fn test_rgb_round_trip() {
+        let pixels = (0..12)
+            .map(|x| RGB16::new(x, x * 2, x * 3))
+            .collect::<Vec<_>>();
+
+        let mut image = Image::from_rgb_pixels(&pixels, 4, 3).unwrap();
+        assert_eq!(image.depth(), BitDepth::Sixteen);
+        assert_eq!(image.as_rgb_pixels::<RGB16>().unwrap(), &pixels[..]);
+
+        image.as_rgb_pixels_mut::<RGB16>().unwrap()[0] = RGB16::new(7, 8, 9);
+        assert_eq!(image.to_rgb_pixels::<RGB16>().unwrap()[0], RGB16::new(7, 8, 9));
+
+        // planar images are copied, not borrowed
+        image.convert_layout(PixelLayout::Planar).unwrap();
+        assert!(image.as_rgb_pixels::<RGB16>().is_err());
+        assert_eq!(image.to_rgb_pixels::<RGB16>().unwrap()[1..], pixels[1..]);
+
+        assert!(Image::from_rgb_pixels(&pixels, 4, 4).is_err());
+    }
+
+    #[test]
+    fn test_rgb_conversions() {
+        let image = Image::fill(200_u8, ColorSpace::RGB, 5, 5);
+
+        assert!(image.as_rgb_pixels::<RGBA8>().is_err());
+        assert!(image.as_rgb_pixels::<RGB16>().is_err());
+
+        let rgba = image.to_rgb_pixels::<RGBA8>().unwrap();
+        assert_eq!(rgba[0], RGBA8::new(200, 200, 200, 255));
+
+        let bgra = image.to_rgb_pixels::<BGRA<u8>>().unwrap();
+        assert_eq!(bgra.len(), 25);
+
+        let gray = Image::fill(10_u8, ColorSpace::Luma, 5, 5);
+        assert_eq!(gray.as_rgb_pixels::<Gray<u8>>().unwrap()[0], Gray(10));
+        assert_eq!(gray.to_rgb_pixels::<RGB8>().unwrap()[0], RGB8::new(10, 10, 10));
+    }
+}
@@ -0,0 +1,60 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Conversions from [`Image`] to tch's [`Tensor`](tch::Tensor)
+//!
+//! Images are exported with [`Image::to_tensor`] first, so the layout, channel order
+//! and normalization come from [`TensorLayout`] and [`ExportOptions`]. Tensors are
+//! created on the CPU, move them with [`tch::Tensor::to_device`].
+//!
+//! This needs libtorch to build, see the tch crate for how to install it.
+//!
+//! # Example
+//! ```no_run
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::export::{ExportOptions, TensorLayout};
+//! use zune_image::image::Image;
+//!
+//! let image = Image::fill(128_u8, ColorSpace::RGB, 32, 16);
+//!
+//! let options = ExportOptions::new().set_mean_std(&[0.5; 3], &[0.5; 3]);
+//! let tensor = image.to_tch_tensor(TensorLayout::Nchw, &options).unwrap();
+//!
+//! assert_eq!(tensor.size(), [1, 3, 16, 32]);
+//! ```
+use alloc::format;
+
+use crate::errors::ImageErrors;
+use crate::export::{ExportOptions, Tensor, TensorLayout};
+use crate::image::Image;
+
+impl Tensor {
+    /// Copy the exported floats into a tch tensor of the same shape
+    ///
+    /// # Errors
+    /// If tch can't create the tensor
+    pub fn to_tch(&self) -> Result<tch::Tensor, ImageErrors> {
+        // dimensions fit, the floats are already allocated
+        let shape = self.shape().map(|x| x as i64);
+
+        tch::Tensor::f_from_slice(self.data())
+            .and_then(|x| x.f_reshape(shape.as_slice()))
+            .map_err(|e| ImageErrors::GenericString(format!("Could not create tensor: {e}")))
+    }
+}
+
+impl Image {
+    /// Export the image as a tch tensor with a batch of one image
+    ///
+    /// # Errors
+    /// The errors of [`Image::to_tensor`] and [`Tensor::to_tch`]
+    pub fn to_tch_tensor(
+        &self, layout: TensorLayout, options: &ExportOptions
+    ) -> Result<tch::Tensor, ImageErrors> {
+        self.to_tensor(layout, options)?.to_tch()
+    }
+}
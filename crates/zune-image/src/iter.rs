@@ -0,0 +1,333 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Typed row and pixel iterators
+//!
+//! Images store every channel in its own plane, these iterators give rows of those
+//! planes, either one channel at a time with [`Channel::rows`] or all channels of a row
+//! together with [`Image::rows`], and whole pixels with [`Image::pixels`], without
+//! reinterpreting channels by hand.
+//!
+//! The type parameter must match the image depth, e.g. `u8` for 8 bit images, `u16`
+//! for 16 bit images and `f32` for float images.
+//!
+//! Image iterators go through all frames of an image in order, like
+//! [`Image::modify_pixels_mut`]. They need planar images, interleaved images
+//! can be converted with [`Image::convert_layout`].
+//!
+//! # Example
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//!
+//! let mut image = Image::fill(0_u8, ColorSpace::RGB, 10, 10);
+//!
+//! // a horizontal gradient in the red channel
+//! for mut row in image.rows_mut::<u8>().unwrap() {
+//!     for (x, value) in row.channel_mut(0).iter_mut().enumerate() {
+//!         *value = (x * 25) as u8;
+//!     }
+//! }
+//! let brightest = image
+//!     .pixels::<u8>()
+//!     .unwrap()
+//!     .max_by_key(|(_, _, pixel)| pixel[0])
+//!     .unwrap();
+//!
+//! assert_eq!(brightest.0, 9);
+//! ```
+use alloc::vec;
+use alloc::vec::Vec;
+use core::slice::{ChunksExact, ChunksExactMut};
+
+use bytemuck::Pod;
+
+use crate::channel::{Channel, ChannelErrors};
+use crate::errors::ImageErrors;
+use crate::image::{Image, MAX_CHANNELS};
+use crate::metadata::PixelLayout;
+
+impl Channel {
+    /// Iterate over the rows of a channel of an image `width` pixels wide
+    ///
+    /// # Errors
+    /// If `T` is not the type stored in the channel
+    pub fn rows<T: Default + 'static>(
+        &self, width: usize
+    ) -> Result<ChunksExact<'_, T>, ChannelErrors> {
+        Ok(self.reinterpret_as::<T>()?.chunks_exact(width.max(1)))
+    }
+    /// Iterate mutably over the rows of a channel of an image `width` pixels wide
+    ///
+    /// # Errors
+    /// If `T` is not the type stored in the channel
+    pub fn rows_mut<T: 'static + Pod>(
+        &mut self, width: usize
+    ) -> Result<ChunksExactMut<'_, T>, ChannelErrors> {
+        Ok(self
+            .reinterpret_as_mut::<T>()?
+            .chunks_exact_mut(width.max(1)))
+    }
+}
+
+/// A row of all channels of an image
+pub struct Row<'a, T> {
+    frame:    usize,
+    y:        usize,
+    channels: Vec<&'a [T]>
+}
+
+impl<'a, T: Copy + Default> Row<'a, T> {
+    /// The frame this row is in
+    pub const fn frame(&self) -> usize {
+        self.frame
+    }
+    /// The position of this row in the frame
+    pub const fn y(&self) -> usize {
+        self.y
+    }
+    /// The row of channel `index`, in colorspace order
+    ///
+    /// # Panics
+    /// If the image has no such channel
+    pub fn channel(&self, index: usize) -> &'a [T] {
+        self.channels[index]
+    }
+    /// The rows of all the channels
+    pub fn channels(&self) -> &[&'a [T]] {
+        &self.channels
+    }
+    /// The pixel at `x`, channels past the colorspace components are zero
+    pub fn pixel(&self, x: usize) -> [T; MAX_CHANNELS] {
+        let mut pixel = [T::default(); MAX_CHANNELS];
+
+        for (value, channel) in pixel.iter_mut().zip(&self.channels) {
+            *value = channel[x];
+        }
+        pixel
+    }
+    /// Iterate over the pixels of the row with their position
+    pub fn pixels(&self) -> impl Iterator<Item = (usize, [T; MAX_CHANNELS])> + '_ {
+        let width = self.channels.first().map_or(0, |x| x.len());
+
+        (0..width).map(|x| (x, self.pixel(x)))
+    }
+}
+
+/// A mutable row of all channels of an image
+pub struct RowMut<'a, T> {
+    frame:    usize,
+    y:        usize,
+    channels: Vec<&'a mut [T]>
+}
+
+impl<'a, T: Copy + Default> RowMut<'a, T> {
+    /// The frame this row is in
+    pub const fn frame(&self) -> usize {
+        self.frame
+    }
+    /// The position of this row in the frame
+    pub const fn y(&self) -> usize {
+        self.y
+    }
+    /// The row of channel `index`, in colorspace order
+    ///
+    /// # Panics
+    /// If the image has no such channel
+    pub fn channel_mut(&mut self, index: usize) -> &mut [T] {
+        self.channels[index]
+    }
+    /// The rows of all the channels
+    pub fn channels_mut(&mut self) -> &mut [&'a mut [T]] {
+        &mut self.channels
+    }
+    /// The pixel at `x`, channels past the colorspace components are zero
+    pub fn pixel(&self, x: usize) -> [T; MAX_CHANNELS] {
+        let mut pixel = [T::default(); MAX_CHANNELS];
+
+        for (value, channel) in pixel.iter_mut().zip(&self.channels) {
+            *value = channel[x];
+        }
+        pixel
+    }
+    /// Set the pixel at `x`, values past the channels of the image are ignored
+    pub fn set_pixel(&mut self, x: usize, pixel: &[T]) {
+        for (value, channel) in pixel.iter().zip(self.channels.iter_mut()) {
+            channel[x] = *value;
+        }
+    }
+}
+
+impl Image {
+    /// Iterate over the rows of every frame, with all channels of a row together
+    ///
+    /// # Errors
+    /// If `T` is not the type of the image depth, or the image is interleaved
+    pub fn rows<T: Copy + Default + 'static>(
+        &self
+    ) -> Result<impl Iterator<Item = Row<'_, T>>, ImageErrors> {
+        self.require_planar()?;
+
+        let (width, _) = self.dimensions();
+        let mut rows = vec![];
+
+        for (frame, channels) in self.frames_ref().iter().enumerate() {
+            let mut planes = channels
+                .channels_vec_ref()
+                .iter()
+                .map(|x| x.rows::<T>(width))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            for y in 0.. {
+                let channels: Option<Vec<&[T]>> = planes.iter_mut().map(Iterator::next).collect();
+
+                match channels {
+                    Some(channels) if !channels.is_empty() => rows.push(Row { frame, y, channels }),
+                    _ => break
+                }
+            }
+        }
+        Ok(rows.into_iter())
+    }
+
+    /// Iterate mutably over the rows of every frame, with all channels of a row together
+    ///
+    /// # Errors
+    /// If `T` is not the type of the image depth, or the image is interleaved
+    pub fn rows_mut<T: Copy + Default + 'static + Pod>(
+        &mut self
+    ) -> Result<impl Iterator<Item = RowMut<'_, T>>, ImageErrors> {
+        Ok(self.collect_rows_mut()?.into_iter())
+    }
+
+    /// Iterate over the pixels of every frame as `(x, y, pixel)`
+    ///
+    /// Channels past the colorspace components are zero, only the first
+    /// [`MAX_CHANNELS`] bands of multi-band images are returned
+    ///
+    /// # Errors
+    /// If `T` is not the type of the image depth, or the image is interleaved
+    pub fn pixels<T: Copy + Default + 'static>(
+        &self
+    ) -> Result<impl Iterator<Item = (usize, usize, [T; MAX_CHANNELS])> + '_, ImageErrors> {
+        let (width, _) = self.dimensions();
+
+        Ok(self
+            .rows::<T>()?
+            .flat_map(move |row| (0..width).map(move |x| (x, row.y(), row.pixel(x)))))
+    }
+
+    /// Run a function on every row of every frame, using multiple threads
+    ///
+    /// Rows are split between threads of the [thread pool](crate::threads) when the
+    /// `threads` feature is enabled, otherwise, or for small images, they are processed
+    /// one after the other
+    ///
+    /// # Errors
+    /// If `T` is not the type of the image depth, or the image is interleaved
+    pub fn par_rows_mut<T, F>(&mut self, function: F) -> Result<(), ImageErrors>
+    where
+        T: Copy + Default + 'static + Pod + Send,
+        F: Fn(RowMut<'_, T>) + Send + Sync
+    {
+        #[cfg(feature = "threads")]
+        let (width, _) = self.dimensions();
+        let rows = self.collect_rows_mut::<T>()?;
+
+        #[cfg(feature = "threads")]
+        if rows.len() * width >= crate::threads::MIN_PARALLEL_PIXELS {
+            use rayon::prelude::*;
+
+            crate::threads::install(|| rows.into_par_iter().for_each(function));
+            return Ok(());
+        }
+        rows.into_iter().for_each(function);
+        Ok(())
+    }
+
+    fn collect_rows_mut<T: Copy + Default + 'static + Pod>(
+        &mut self
+    ) -> Result<Vec<RowMut<'_, T>>, ImageErrors> {
+        self.require_planar()?;
+
+        let (width, _) = self.dimensions();
+        let mut rows = vec![];
+
+        for (frame, channels) in self.frames_mut().iter_mut().enumerate() {
+            let mut planes = channels
+                .channels_vec()
+                .iter_mut()
+                .map(|x| x.rows_mut::<T>(width))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            for y in 0.. {
+                let channels: Option<Vec<&mut [T]>> =
+                    planes.iter_mut().map(Iterator::next).collect();
+
+                match channels {
+                    Some(channels) if !channels.is_empty() => {
+                        rows.push(RowMut { frame, y, channels });
+                    }
+                    _ => break
+                }
+            }
+        }
+        Ok(rows)
+    }
+
+    fn require_planar(&self) -> Result<(), ImageErrors> {
+        if self.layout() != PixelLayout::Planar {
+            return Err(ImageErrors::GenericStr(
+                "Row iterators need a planar image, convert the layout first"
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::image::Image;
+
+    #[test]
+    fn test_rows_and_pixels() {
+        let pixels: Vec<u16> = (0..4 * 3 * 2).collect();
+        let image = Image::from_u16(&pixels, 4, 3, ColorSpace::LumaA);
+
+        let rows: Vec<_> = image.rows::<u16>().unwrap().collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[1].y(), 1);
+        assert_eq!(rows[1].channel(0), [8, 10, 12, 14]);
+        assert_eq!(rows[1].channel(1), [9, 11, 13, 15]);
+
+        let all: Vec<_> = image.pixels::<u16>().unwrap().collect();
+        assert_eq!(all.len(), 12);
+        assert_eq!(all[5], (1, 1, [10, 11, 0, 0]));
+
+        assert!(image.rows::<u8>().is_err());
+    }
+
+    #[test]
+    fn test_par_rows_mut() {
+        let mut image = Image::fill(0.0_f32, ColorSpace::RGB, 31, 17);
+
+        image
+            .par_rows_mut::<f32, _>(|mut row| {
+                let y = row.y() as f32;
+                for x in 0..31 {
+                    row.set_pixel(x, &[x as f32, y, 1.0]);
+                }
+            })
+            .unwrap();
+
+        for (x, y, pixel) in image.pixels::<f32>().unwrap() {
+            assert_eq!(pixel, [x as f32, y as f32, 1.0, 0.0]);
+        }
+    }
+}
@@ -31,6 +31,15 @@
 //!| hdr          | zune-hdr      | zune-hdr       |
 //!
 //!
+//! ### `no_std`
+//!
+//! Without the default `std` feature the crate is `#[no_std]` and needs only `alloc`.
+//! [`Image`](image::Image), channels, frames, the traits and the core filters are
+//! available, float math comes from the `libm` feature which has to be enabled instead.
+//!
+//! Codecs, pipelines, batches, progress reporting, threads, runtime simd detection and metadata
+//! need `std`, each of their features enables it.
+//!
 //! ### Image filters
 //!
 //! Image filters are divided into two types,
@@ -77,7 +86,8 @@
 //! animated, each frame of an animated image should have the same color channels and length.
 //!
 //! You can iterate the frames via the `frames_` method ([`frames_ref`](image::Image::frames_ref)
-//! and [`frames_mut`](image::Image::frames_mut)
+//! and [`frames_mut`](image::Image::frames_mut), long animations can be decoded one frame at a time
+//! with [`FrameStream`](stream::FrameStream)
 //!
 //! ### Image and channels
 //!
@@ -95,19 +105,51 @@
 )]
 #![cfg_attr(feature = "benchmarks", feature(test))]
 #![cfg_attr(feature = "docs", feature(doc_cfg))]
+#![cfg_attr(not(feature = "std"), no_std)]
+extern crate alloc;
 extern crate core;
 
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("zune-image needs the `std` feature or the `libm` feature for float math");
+
+#[cfg(feature = "tokio")]
+pub mod asynchronous;
+#[cfg(feature = "std")]
+pub mod batch;
 pub mod channel;
 pub mod codecs;
+pub mod compare;
 pub mod core_filters;
 mod deinterleave;
 pub mod errors;
+pub mod export;
 pub mod frame;
+pub mod generators;
+pub mod half;
 pub mod image;
+pub mod interop;
+pub mod iter;
+mod limits;
 pub mod metadata;
+mod mmap;
 mod ops;
+pub mod palette;
+#[cfg(feature = "std")]
 pub mod pipelines;
+pub mod point_ops;
+mod position;
+#[cfg(feature = "preview")]
+pub mod preview;
+#[cfg(feature = "std")]
+pub mod progress;
+pub mod region;
 mod serde;
+pub mod stream;
 mod tests;
+pub mod threads;
+pub mod tiles;
 pub mod traits;
+pub mod typed;
 pub mod utils;
+pub mod view;
+pub mod yuv;
@@ -0,0 +1,49 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Enforcing the limits of [`DecoderOptions`]
+//!
+//! Decoders check the limits themselves while reading their headers, with their own
+//! errors. Images decoded through [`Image::read`](crate::image::Image::read) and
+//! [`FrameStream::from_source`](crate::stream::FrameStream::from_source) are checked
+//! here as well, for decoders without headers and for the frames of streams as they are
+//! decoded, failing with [`ImageErrors::LimitsExceeded`].
+use zune_core::options::DecoderOptions;
+
+use crate::errors::ImageErrors;
+use crate::metadata::ImageMetadata;
+
+/// Check the dimensions and the size of the first frame described by the headers
+pub(crate) fn check_headers(
+    metadata: &ImageMetadata, options: &DecoderOptions
+) -> Result<(), ImageErrors> {
+    let (width, height) = metadata.dimensions();
+
+    check("width", width, options.max_width())?;
+    check("height", height, options.max_height())?;
+    check_frames(metadata, 1, options)
+}
+
+/// Check the number of frames and the memory they take up
+pub(crate) fn check_frames(
+    metadata: &ImageMetadata, frames: usize, options: &DecoderOptions
+) -> Result<(), ImageErrors> {
+    let (width, height) = metadata.dimensions();
+    let pixel_bytes = metadata.colorspace().num_components() * metadata.depth().size_of();
+
+    match options.exceeded_limit(width, height, pixel_bytes, frames) {
+        Some((limit, found, max)) => Err(ImageErrors::LimitsExceeded(limit, found, max)),
+        None => Ok(())
+    }
+}
+
+fn check(limit: &'static str, found: usize, max: usize) -> Result<(), ImageErrors> {
+    if found > max {
+        return Err(ImageErrors::LimitsExceeded(limit, found, max));
+    }
+    Ok(())
+}
@@ -11,22 +11,220 @@
 //! This module provides the ability to store image metadata and transfer it
 //! from one image to another
 
+use alloc::string::String;
+use alloc::vec::Vec;
+
 use zune_core::bit_depth::BitDepth;
-use zune_core::colorspace::{ColorCharacteristics, ColorSpace};
+use zune_core::colorspace::{ColorCharacteristics, ColorSpace, RgbPrimaries, WhitePoint};
+#[cfg(any(feature = "png", feature = "jpeg"))]
+use zune_core::log::warn;
 
 use crate::codecs::ImageFormat;
 
 mod exif;
+mod iptc;
+
+#[cfg(feature = "metadata")]
+pub use self::exif::{ExifData, ExifExposure, ExifGps, ExifLens};
+#[cfg(feature = "jpeg")]
+pub(crate) use self::iptc::photoshop_resources;
+pub use self::iptc::{IptcData, IptcDataSet};
+
+/// How the pixels of an image are stored relative to how it should be displayed
+///
+/// Corresponds to the values of the exif orientation tag, the variant names
+/// describe the transformation needed to display the image upright
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Default)]
+pub enum ExifOrientation {
+    /// The image is upright
+    #[default]
+    Normal,
+    /// The image needs to be flipped horizontally
+    FlipHorizontal,
+    /// The image needs to be rotated by 180 degrees
+    Rotate180,
+    /// The image needs to be flipped vertically
+    FlipVertical,
+    /// The image needs to be transposed, mirrored along the top left to bottom right diagonal
+    Transpose,
+    /// The image needs to be rotated 90 degrees clockwise
+    Rotate90,
+    /// The image needs to be transversed, mirrored along the top right to bottom left diagonal
+    Transverse,
+    /// The image needs to be rotated 270 degrees clockwise
+    Rotate270
+}
+
+impl ExifOrientation {
+    /// Create an orientation from the value of the exif tag,
+    /// returns `None` for values outside 1 to 8
+    pub const fn from_exif(value: u32) -> Option<ExifOrientation> {
+        match value {
+            1 => Some(ExifOrientation::Normal),
+            2 => Some(ExifOrientation::FlipHorizontal),
+            3 => Some(ExifOrientation::Rotate180),
+            4 => Some(ExifOrientation::FlipVertical),
+            5 => Some(ExifOrientation::Transpose),
+            6 => Some(ExifOrientation::Rotate90),
+            7 => Some(ExifOrientation::Transverse),
+            8 => Some(ExifOrientation::Rotate270),
+            _ => None
+        }
+    }
+    /// The value of the exif tag for this orientation
+    pub const fn to_exif(self) -> u16 {
+        match self {
+            ExifOrientation::Normal => 1,
+            ExifOrientation::FlipHorizontal => 2,
+            ExifOrientation::Rotate180 => 3,
+            ExifOrientation::FlipVertical => 4,
+            ExifOrientation::Transpose => 5,
+            ExifOrientation::Rotate90 => 6,
+            ExifOrientation::Transverse => 7,
+            ExifOrientation::Rotate270 => 8
+        }
+    }
+    /// Return true if displaying the image swaps its width and height
+    pub const fn swaps_dimensions(self) -> bool {
+        matches!(
+            self,
+            ExifOrientation::Transpose
+                | ExifOrientation::Rotate90
+                | ExifOrientation::Transverse
+                | ExifOrientation::Rotate270
+        )
+    }
+}
+
+/// Metadata kept when stripping an image's metadata
+///
+/// See [`ImageMetadata::strip_metadata_keeping`]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct KeepMetadata {
+    /// Keep the exif orientation, so the image is still displayed upright
+    pub orientation: bool,
+    /// Keep the exif and IPTC copyright and artist
+    pub copyright:   bool
+}
+
+/// The unit of a [`Resolution`]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ResolutionUnit {
+    /// No unit, the resolution only gives the aspect ratio of a pixel
+    None,
+    /// Pixels per inch
+    Inch,
+    /// Pixels per centimeter
+    Centimeter
+}
+
+/// Physical resolution of an image, in pixels per unit
+///
+/// Printers and layout programs use this to size images, e.g. a 3000 pixel
+/// wide image at 300 pixels per inch prints 10 inches wide
+///
+/// # Example
+/// ```
+/// use zune_image::metadata::Resolution;
+///
+/// let resolution = Resolution::from_dpi(300.0);
+/// assert_eq!(resolution.physical_size(3000, 1500), Some((10.0, 5.0)));
+/// // halving the pixels keeps the printed size
+/// assert_eq!(resolution.scaled(0.5, 0.5).dpi(), Some((150.0, 150.0)));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Resolution {
+    /// Horizontal pixels per unit
+    pub x:    f32,
+    /// Vertical pixels per unit
+    pub y:    f32,
+    /// The unit of `x` and `y`
+    pub unit: ResolutionUnit
+}
+
+impl Resolution {
+    /// Create a resolution
+    pub const fn new(x: f32, y: f32, unit: ResolutionUnit) -> Resolution {
+        Resolution { x, y, unit }
+    }
+    /// Create a resolution with the same pixels per inch in both directions
+    pub const fn from_dpi(dpi: f32) -> Resolution {
+        Resolution::new(dpi, dpi, ResolutionUnit::Inch)
+    }
+    /// Horizontal and vertical pixels per inch
+    ///
+    /// Returns `None` if the resolution has no unit
+    pub fn dpi(self) -> Option<(f32, f32)> {
+        match self.unit {
+            ResolutionUnit::None => None,
+            ResolutionUnit::Inch => Some((self.x, self.y)),
+            ResolutionUnit::Centimeter => Some((self.x * 2.54, self.y * 2.54))
+        }
+    }
+    /// Horizontal and vertical pixels per meter
+    ///
+    /// Returns `None` if the resolution has no unit
+    pub fn pixels_per_meter(self) -> Option<(f32, f32)> {
+        self.dpi().map(|(x, y)| (x / 0.0254, y / 0.0254))
+    }
+    /// The printed width and height in inches of an image with these dimensions
+    ///
+    /// Returns `None` if the resolution has no unit or is zero
+    pub fn physical_size(self, width: usize, height: usize) -> Option<(f32, f32)> {
+        let (x, y) = self.dpi().filter(|(x, y)| *x > 0.0 && *y > 0.0)?;
+
+        Some((width as f32 / x, height as f32 / y))
+    }
+    /// The resolution after scaling the image by `x_scale` and `y_scale`,
+    /// so that it keeps its physical size
+    #[must_use]
+    pub fn scaled(self, x_scale: f32, y_scale: f32) -> Resolution {
+        Resolution::new(self.x * x_scale, self.y * y_scale, self.unit)
+    }
+    /// The resolution with the horizontal and vertical values swapped,
+    /// for images rotated by 90 or 270 degrees
+    #[must_use]
+    pub const fn transposed(self) -> Resolution {
+        Resolution::new(self.y, self.x, self.unit)
+    }
+}
 
 /// Contains information about whether the image
 /// is pre multiplied with it's alpha
 /// or it's not
+///
+/// Operations consult this before running, see [`OperationsTrait::alpha_state`],
+/// so that images aren't premultiplied twice
+///
+/// [`OperationsTrait::alpha_state`]: crate::traits::OperationsTrait::alpha_state
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum AlphaState {
+    /// Color channels are multiplied by the alpha
     PreMultiplied,
+    /// Color channels are independent of the alpha, also known as straight alpha
     NonPreMultiplied
 }
 
+/// How the channels of an image are stored in its frames
+///
+/// Planar images keep every channel in its own [`Channel`], interleaved images keep all
+/// channels of a frame together in a single channel, e.g. `R,G,B,R,G,B`, which is what most
+/// decoders produce and what encoders and displays consume.
+///
+/// Operations declare the layouts they can work on, see [`OperationsTrait::supported_layouts`],
+/// images in other layouts are converted before running them.
+///
+/// [`Channel`]: crate::channel::Channel
+/// [`OperationsTrait::supported_layouts`]: crate::traits::OperationsTrait::supported_layouts
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum PixelLayout {
+    /// One channel per colorspace component
+    #[default]
+    Planar,
+    /// All components of a pixel next to each other in one channel per frame
+    Interleaved
+}
+
 /// Image metadata
 ///
 /// Each image type has this information present
@@ -44,9 +242,16 @@ pub struct ImageMetadata {
     pub(crate) depth:         BitDepth,
     pub(crate) format:        Option<ImageFormat>,
     pub(crate) alpha:         AlphaState,
+    pub(crate) layout:        PixelLayout,
+    pub(crate) loop_count:    u32,
+    pub(crate) white_point:   WhitePoint,
+    pub(crate) primaries:     RgbPrimaries,
     #[cfg(feature = "metadata")]
     pub(crate) exif:          Option<Vec<::exif::Field>>,
-    pub(crate) icc_chunk:     Option<Vec<u8>>
+    pub(crate) icc_chunk:     Option<Vec<u8>>,
+    pub(crate) xmp:           Option<String>,
+    pub(crate) iptc:          Option<IptcData>,
+    pub(crate) resolution:    Option<Resolution>
 }
 
 impl Default for ImageMetadata {
@@ -60,10 +265,17 @@ impl Default for ImageMetadata {
             depth: BitDepth::default(),
             format: None,
             alpha: AlphaState::NonPreMultiplied,
+            layout: PixelLayout::Planar,
+            loop_count: 0,
+            white_point: WhitePoint::D65,
+            primaries: RgbPrimaries::sRGB,
             #[cfg(feature = "metadata")]
             exif: None,
 
-            icc_chunk: None
+            icc_chunk:  None,
+            xmp:        None,
+            iptc:       None,
+            resolution: None
         }
     }
 }
@@ -88,6 +300,36 @@ impl ImageMetadata {
     pub fn exif_mut(&mut self) -> Option<&mut Vec<::exif::Field>> {
         return self.exif.as_mut();
     }
+
+    /// Remove metadata that may identify where, when and with what the image was
+    /// captured, e.g. exif fields with the camera and location
+    ///
+    /// The colorspace, gamma, ICC profile and resolution are kept since they are needed to
+    /// display the image correctly. The XMP packet is always removed
+    pub fn strip_metadata(&mut self) {
+        self.strip_metadata_keeping(KeepMetadata::default());
+    }
+
+    /// Remove metadata like [`strip_metadata`](Self::strip_metadata), keeping
+    /// the fields selected by `keep`
+    pub fn strip_metadata_keeping(&mut self, keep: KeepMetadata) {
+        #[cfg(feature = "metadata")]
+        {
+            self.strip_exif(keep);
+        }
+        self.xmp = None;
+
+        if let Some(iptc) = &mut self.iptc {
+            iptc.retain(|x| {
+                keep.copyright
+                    && x.record == IptcData::APPLICATION_RECORD
+                    && matches!(x.dataset, IptcData::COPYRIGHT | IptcData::BYLINE)
+            });
+            if iptc.is_empty() {
+                self.iptc = None;
+            }
+        }
+    }
     /// Get image dimensions as a tuple of width and height
     ///  
     /// # Example
@@ -132,6 +374,8 @@ impl ImageMetadata {
     /// Color transfer characteristics tell us more about how
     /// the colorspace values are represented
     /// whether they are linear or gamma encoded
+    ///
+    /// Images without transfer characteristics are assumed to be sRGB encoded
     pub const fn color_trc(&self) -> Option<ColorCharacteristics> {
         self.color_trc
     }
@@ -139,6 +383,34 @@ impl ImageMetadata {
     pub fn set_color_trc(&mut self, trc: ColorCharacteristics) {
         self.color_trc = Some(trc);
     }
+    /// Get the reference white of the image
+    ///
+    /// This is used by colorspaces relative to a white point, e.g. [`ColorSpace::XYZ`],
+    /// the default is [`WhitePoint::D65`], the white point of sRGB
+    pub const fn white_point(&self) -> WhitePoint {
+        self.white_point
+    }
+    /// Set the reference white of the image
+    ///
+    /// This does not modify pixels, use [`ColorspaceConv::set_white_point`](crate::core_filters::colorspace::ColorspaceConv::set_white_point)
+    /// to adapt pixels to a new white point
+    pub fn set_white_point(&mut self, white_point: WhitePoint) {
+        self.white_point = white_point;
+    }
+    /// Get the RGB primaries of the image
+    ///
+    /// The default is [`RgbPrimaries::sRGB`], colorspace conversions assume
+    /// sRGB primaries
+    pub const fn primaries(&self) -> RgbPrimaries {
+        self.primaries
+    }
+    /// Set the RGB primaries of the image
+    ///
+    /// This does not modify pixels, use the `GamutMap` operation of `zune-imageprocs`
+    /// to convert pixels to other primaries
+    pub fn set_primaries(&mut self, primaries: RgbPrimaries) {
+        self.primaries = primaries;
+    }
     /// Get the image bit depth
     ///
     /// Default value is [`BitDepth::Unknown`]
@@ -186,6 +458,25 @@ impl ImageMetadata {
         self.alpha = alpha_state;
     }
 
+    /// Return how the channels of the image are stored
+    pub const fn layout(&self) -> PixelLayout {
+        self.layout
+    }
+    /// Return how many times an animated image plays, zero means forever
+    pub const fn loop_count(&self) -> u32 {
+        self.loop_count
+    }
+    /// Set how many times an animated image plays, zero means forever
+    pub fn set_loop_count(&mut self, loop_count: u32) {
+        self.loop_count = loop_count;
+    }
+    /// Set the layout without touching the channels
+    ///
+    /// Use [`Image::convert_layout`](crate::image::Image::convert_layout) to change it
+    pub(crate) fn set_layout(&mut self, layout: PixelLayout) {
+        self.layout = layout;
+    }
+
     pub fn set_icc_chunk(&mut self, icc_chunk: Vec<u8>) {
         self.icc_chunk = Some(icc_chunk);
     }
@@ -193,4 +484,95 @@ impl ImageMetadata {
     pub fn icc_chunk(&self) -> Option<&Vec<u8>> {
         self.icc_chunk.as_ref()
     }
+    /// Remove the icc chunk of the image, returning it
+    ///
+    /// Images without an icc chunk are assumed to be sRGB
+    pub fn remove_icc_chunk(&mut self) -> Option<Vec<u8>> {
+        self.icc_chunk.take()
+    }
+    /// Return the icc chunk if it describes pixels of `colorspace`
+    ///
+    /// Encoders embed the profile through this, so an image converted to a
+    /// colorspace its profile doesn't describe, e.g. an RGB image converted to
+    /// grayscale, is written without it
+    #[cfg(any(feature = "png", feature = "jpeg"))]
+    pub(crate) fn icc_chunk_for(&self, colorspace: ColorSpace) -> Option<&[u8]> {
+        let icc = self.icc_chunk.as_deref()?;
+
+        // the data colorspace is stored at offset 16 of the profile header
+        let matches = match icc.get(16..20)? {
+            b"GRAY" => colorspace.is_grayscale(),
+            b"RGB " => matches!(
+                colorspace,
+                ColorSpace::RGB
+                    | ColorSpace::RGBA
+                    | ColorSpace::BGR
+                    | ColorSpace::BGRA
+                    | ColorSpace::ARGB
+            ),
+            b"CMYK" => colorspace == ColorSpace::CMYK,
+            _ => false
+        };
+        if !matches {
+            warn!(
+                "Not embedding the ICC profile, it does not describe {:?} images",
+                colorspace
+            );
+            return None;
+        }
+        Some(icc)
+    }
+    /// Return the XMP packet of the image
+    ///
+    /// The packet is returned as the raw XML, encoders that support XMP
+    /// write it back unchanged
+    pub fn xmp(&self) -> Option<&str> {
+        self.xmp.as_deref()
+    }
+    /// Set the XMP packet of the image
+    pub fn set_xmp(&mut self, xmp: String) {
+        self.xmp = Some(xmp);
+    }
+    /// Remove the XMP packet of the image, returning it
+    pub fn remove_xmp(&mut self) -> Option<String> {
+        self.xmp.take()
+    }
+    /// Return the IPTC-IIM metadata of the image
+    pub const fn iptc(&self) -> Option<&IptcData> {
+        self.iptc.as_ref()
+    }
+    /// Return a mutable reference to the IPTC-IIM metadata of the image,
+    /// creating empty metadata if there is none
+    pub fn iptc_mut(&mut self) -> &mut IptcData {
+        self.iptc.get_or_insert_with(IptcData::new)
+    }
+    /// Set the IPTC-IIM metadata of the image
+    pub fn set_iptc(&mut self, iptc: IptcData) {
+        self.iptc = Some(iptc);
+    }
+    /// Remove the IPTC-IIM metadata of the image, returning it
+    pub fn remove_iptc(&mut self) -> Option<IptcData> {
+        self.iptc.take()
+    }
+    /// Return the physical resolution of the image
+    ///
+    /// This is read from the JFIF density of JPEG images and the pHYs chunk of
+    /// PNG images, and written back by their encoders
+    pub const fn resolution(&self) -> Option<Resolution> {
+        self.resolution
+    }
+    /// Set the physical resolution of the image
+    pub fn set_resolution(&mut self, resolution: Resolution) {
+        self.resolution = Some(resolution);
+    }
+    /// Remove the physical resolution of the image, returning it
+    pub fn remove_resolution(&mut self) -> Option<Resolution> {
+        self.resolution.take()
+    }
+    /// The printed width and height of the image in inches
+    ///
+    /// Returns `None` if the image has no resolution with a unit
+    pub fn physical_size(&self) -> Option<(f32, f32)> {
+        self.resolution?.physical_size(self.width, self.height)
+    }
 }
@@ -8,9 +8,184 @@
 
 #![cfg(feature = "metadata")]
 
+use exif::{Field, In, Tag, Value};
 use zune_core::log::{error, trace};
 
-use crate::metadata::ImageMetadata;
+use crate::metadata::{ExifOrientation, ImageMetadata, KeepMetadata};
+
+/// Exposure settings the image was captured with
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExifExposure {
+    /// Exposure time in seconds
+    pub exposure_time:     Option<f64>,
+    /// The F number of the aperture
+    pub f_number:          Option<f64>,
+    /// ISO speed
+    pub iso:               Option<u32>,
+    /// Exposure bias in EV
+    pub exposure_bias:     Option<f64>,
+    /// Focal length of the lens in millimeters
+    pub focal_length:      Option<f64>,
+    /// Focal length in millimeters for a 35mm film camera
+    pub focal_length_35mm: Option<u32>,
+    /// Whether the flash fired
+    pub flash_fired:       Option<bool>
+}
+
+/// The lens the image was captured with
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExifLens {
+    /// Manufacturer of the lens
+    pub make:           Option<String>,
+    /// Model name of the lens
+    pub model:          Option<String>,
+    /// Minimum and maximum focal length in millimeters
+    pub focal_range:    Option<(f64, f64)>,
+    /// Minimum F number at the minimum and maximum focal length
+    pub f_number_range: Option<(f64, f64)>
+}
+
+/// Where the image was captured
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ExifGps {
+    /// Latitude in degrees, positive to the north
+    pub latitude:  f64,
+    /// Longitude in degrees, positive to the east
+    pub longitude: f64,
+    /// Altitude in meters, negative below sea level
+    pub altitude:  Option<f64>
+}
+
+/// Typed values of common exif fields
+///
+/// Created by [`ImageMetadata::exif_data`] from the fields of the primary image,
+/// thumbnail fields are ignored. Fields that are missing or malformed are `None`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExifData {
+    /// How the image should be oriented for display
+    pub orientation: Option<ExifOrientation>,
+    /// Manufacturer of the camera
+    pub make:        Option<String>,
+    /// Model name of the camera
+    pub model:       Option<String>,
+    /// Software that created the image
+    pub software:    Option<String>,
+    /// When the image was captured, as `YYYY:MM:DD HH:MM:SS`
+    pub date_time:   Option<String>,
+    /// Exposure settings
+    pub exposure:    ExifExposure,
+    /// The lens used
+    pub lens:        ExifLens,
+    /// Location the image was captured at
+    pub gps:         Option<ExifGps>,
+    /// The maker note, its format is specific to the camera manufacturer
+    /// so it is kept as raw bytes
+    pub maker_note:  Option<Vec<u8>>
+}
+
+impl ExifData {
+    /// Extract typed values from exif fields
+    pub fn from_fields(fields: &[Field]) -> ExifData {
+        let value = |tag: Tag| {
+            fields
+                .iter()
+                .find(|x| x.tag == tag && x.ifd_num == In::PRIMARY)
+                .map(|x| &x.value)
+        };
+        let uint = |tag: Tag| value(tag).and_then(|x| x.get_uint(0));
+        let float = |tag: Tag| value(tag).and_then(|x| rational(x, 0));
+        let string = |tag: Tag| value(tag).and_then(ascii);
+        let range = |tag: Tag, first: usize| {
+            let value = value(tag)?;
+            Some((rational(value, first)?, rational(value, first + 1)?))
+        };
+
+        let exposure = ExifExposure {
+            exposure_time:     float(Tag::ExposureTime),
+            f_number:          float(Tag::FNumber),
+            iso:               uint(Tag::PhotographicSensitivity),
+            exposure_bias:     float(Tag::ExposureBiasValue),
+            focal_length:      float(Tag::FocalLength),
+            focal_length_35mm: uint(Tag::FocalLengthIn35mmFilm),
+            flash_fired:       uint(Tag::Flash).map(|x| x & 1 == 1)
+        };
+        let lens = ExifLens {
+            make:           string(Tag::LensMake),
+            model:          string(Tag::LensModel),
+            focal_range:    range(Tag::LensSpecification, 0),
+            f_number_range: range(Tag::LensSpecification, 2)
+        };
+        let coordinate = |tag: Tag, reference: Tag, negative: &str| {
+            let value = value(tag)?;
+            let degrees =
+                rational(value, 0)? + rational(value, 1)? / 60.0 + rational(value, 2)? / 3600.0;
+
+            match string(reference) {
+                Some(r) if r.eq_ignore_ascii_case(negative) => Some(-degrees),
+                _ => Some(degrees)
+            }
+        };
+        let gps = match (
+            coordinate(Tag::GPSLatitude, Tag::GPSLatitudeRef, "S"),
+            coordinate(Tag::GPSLongitude, Tag::GPSLongitudeRef, "W")
+        ) {
+            (Some(latitude), Some(longitude)) => {
+                // a reference of 1 means below sea level
+                let below = value(Tag::GPSAltitudeRef).and_then(|x| x.get_uint(0)) == Some(1);
+                let altitude = float(Tag::GPSAltitude).map(|x| if below { -x } else { x });
+
+                Some(ExifGps {
+                    latitude,
+                    longitude,
+                    altitude
+                })
+            }
+            _ => None
+        };
+        let maker_note = match value(Tag::MakerNote) {
+            Some(Value::Undefined(data, _)) => Some(data.clone()),
+            _ => None
+        };
+
+        ExifData {
+            orientation: uint(Tag::Orientation).and_then(ExifOrientation::from_exif),
+            make: string(Tag::Make),
+            model: string(Tag::Model),
+            software: string(Tag::Software),
+            date_time: string(Tag::DateTimeOriginal).or_else(|| string(Tag::DateTime)),
+            exposure,
+            lens,
+            gps,
+            maker_note
+        }
+    }
+}
+
+/// Read a rational, signed rational or float at `index` as a float
+fn rational(value: &Value, index: usize) -> Option<f64> {
+    let value = match value {
+        Value::Rational(x) => x.get(index).map(|x| (f64::from(x.num), f64::from(x.denom))),
+        Value::SRational(x) => x.get(index).map(|x| (f64::from(x.num), f64::from(x.denom))),
+        Value::Float(x) => x.get(index).map(|x| (f64::from(*x), 1.0)),
+        Value::Double(x) => x.get(index).map(|x| (*x, 1.0)),
+        _ => None
+    };
+    // zero denominators mark unknown values
+    value.filter(|x| x.1 != 0.0).map(|(num, denom)| num / denom)
+}
+
+/// Read the first string of an ascii value, without trailing whitespace
+fn ascii(value: &Value) -> Option<String> {
+    match value {
+        Value::Ascii(x) => {
+            let string = String::from_utf8_lossy(x.first()?);
+            let string = string.trim_end();
+
+            (!string.is_empty()).then(|| string.to_string())
+        }
+        _ => None
+    }
+}
 
 impl ImageMetadata {
     /// Parse raw Exif and store it as a field in the data
@@ -32,4 +207,154 @@ impl ImageMetadata {
             }
         };
     }
+
+    /// Return typed values of common exif fields, or `None` if the image
+    /// has no exif metadata
+    ///
+    /// Use [`exif`](Self::exif) for other fields
+    ///
+    /// This requires the `metadata` feature to be activated
+    #[cfg_attr(feature = "docs", doc(cfg(feature = "metadata")))]
+    pub fn exif_data(&self) -> Option<ExifData> {
+        self.exif.as_deref().map(ExifData::from_fields)
+    }
+
+    /// Return the orientation from the exif metadata, if present
+    ///
+    /// This requires the `metadata` feature to be activated
+    #[cfg_attr(feature = "docs", doc(cfg(feature = "metadata")))]
+    pub fn orientation(&self) -> Option<ExifOrientation> {
+        self.exif_data()?.orientation
+    }
+
+    /// Set an exif field, replacing any field with the same tag in the same IFD
+    ///
+    /// Encoders that support exif write the fields when saving the image,
+    /// unless [`EncoderOptions::set_strip_metadata`] is set
+    ///
+    /// This requires the `metadata` feature to be activated
+    ///
+    /// [`EncoderOptions::set_strip_metadata`]: zune_core::options::EncoderOptions::set_strip_metadata
+    #[cfg_attr(feature = "docs", doc(cfg(feature = "metadata")))]
+    pub fn set_exif_field(&mut self, field: Field) {
+        let fields = self.exif.get_or_insert_with(Vec::new);
+
+        match fields
+            .iter_mut()
+            .find(|x| x.tag == field.tag && x.ifd_num == field.ifd_num)
+        {
+            Some(existing) => *existing = field,
+            None => fields.push(field)
+        }
+    }
+
+    /// Remove all exif fields with `tag`, returning true if any was present
+    ///
+    /// This requires the `metadata` feature to be activated
+    #[cfg_attr(feature = "docs", doc(cfg(feature = "metadata")))]
+    pub fn remove_exif_field(&mut self, tag: Tag) -> bool {
+        let Some(fields) = &mut self.exif else {
+            return false;
+        };
+        let length = fields.len();
+        fields.retain(|x| x.tag != tag);
+
+        fields.len() != length
+    }
+
+    /// Set the exif orientation of the image
+    ///
+    /// This requires the `metadata` feature to be activated
+    #[cfg_attr(feature = "docs", doc(cfg(feature = "metadata")))]
+    pub fn set_orientation(&mut self, orientation: ExifOrientation) {
+        self.set_exif_field(Field {
+            tag:     Tag::Orientation,
+            ifd_num: In::PRIMARY,
+            value:   Value::Short(vec![orientation.to_exif()])
+        });
+    }
+
+    /// Remove exif fields not selected by `keep`
+    pub(crate) fn strip_exif(&mut self, keep: KeepMetadata) {
+        if let Some(fields) = &mut self.exif {
+            fields.retain(|x| {
+                x.ifd_num == In::PRIMARY
+                    && ((keep.orientation && x.tag == Tag::Orientation)
+                        || (keep.copyright && matches!(x.tag, Tag::Copyright | Tag::Artist)))
+            });
+            if fields.is_empty() {
+                self.exif = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use exif::{Field, In, Rational, Tag, Value};
+
+    use crate::metadata::{ExifOrientation, ImageMetadata};
+
+    fn field(tag: Tag, value: Value) -> Field {
+        Field {
+            tag,
+            ifd_num: In::PRIMARY,
+            value
+        }
+    }
+
+    fn rationals(values: &[(u32, u32)]) -> Value {
+        Value::Rational(
+            values
+                .iter()
+                .map(|&(num, denom)| Rational { num, denom })
+                .collect()
+        )
+    }
+
+    #[test]
+    fn test_exif_data() {
+        let mut metadata = ImageMetadata::default();
+        assert!(metadata.exif_data().is_none());
+
+        metadata.exif = Some(vec![
+            field(Tag::Orientation, Value::Short(vec![6])),
+            field(Tag::Make, Value::Ascii(vec![b"Camera Co ".to_vec()])),
+            field(Tag::ExposureTime, rationals(&[(1, 250)])),
+            field(Tag::FNumber, rationals(&[(28, 10)])),
+            field(Tag::PhotographicSensitivity, Value::Short(vec![400])),
+            field(Tag::Flash, Value::Short(vec![0x19])),
+            field(
+                Tag::LensSpecification,
+                rationals(&[(24, 1), (70, 1), (28, 10), (0, 0)])
+            ),
+            field(Tag::GPSLatitude, rationals(&[(51, 1), (30, 1), (0, 1)])),
+            field(Tag::GPSLatitudeRef, Value::Ascii(vec![b"N".to_vec()])),
+            field(Tag::GPSLongitude, rationals(&[(0, 1), (7, 1), (48, 1)])),
+            field(Tag::GPSLongitudeRef, Value::Ascii(vec![b"W".to_vec()])),
+            field(Tag::MakerNote, Value::Undefined(vec![1, 2, 3], 0)),
+            // thumbnail fields are ignored
+            Field {
+                tag:     Tag::Model,
+                ifd_num: In::THUMBNAIL,
+                value:   Value::Ascii(vec![b"Thumbnail".to_vec()])
+            },
+        ]);
+        let exif = metadata.exif_data().unwrap();
+
+        assert_eq!(metadata.orientation(), Some(ExifOrientation::Rotate90));
+        assert_eq!(exif.make.as_deref(), Some("Camera Co"));
+        assert_eq!(exif.model, None);
+        assert_eq!(exif.exposure.exposure_time, Some(0.004));
+        assert_eq!(exif.exposure.iso, Some(400));
+        assert_eq!(exif.exposure.flash_fired, Some(true));
+        assert_eq!(exif.lens.focal_range, Some((24.0, 70.0)));
+        // unknown maximum aperture at the long end
+        assert_eq!(exif.lens.f_number_range, None);
+
+        let gps = exif.gps.unwrap();
+        assert_eq!(gps.latitude, 51.5);
+        assert!((gps.longitude + 0.13).abs() < 1e-9);
+        assert_eq!(exif.maker_note, Some(vec![1, 2, 3]));
+    }
 }
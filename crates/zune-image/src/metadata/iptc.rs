@@ -0,0 +1,292 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use zune_core::log::warn;
+
+/// Marks the start of every IPTC-IIM dataset
+const TAG_MARKER: u8 = 0x1C;
+
+/// Record of the envelope, which holds the character set
+const ENVELOPE_RECORD: u8 = 1;
+/// Dataset of the envelope record holding the character set
+const CODED_CHARACTER_SET: u8 = 90;
+/// Escape sequence marking text as UTF-8
+const UTF8_ESCAPE: &[u8] = b"\x1b%G";
+
+/// A dataset of IPTC-IIM metadata
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IptcDataSet {
+    /// The record number, editorial fields are in [`IptcData::APPLICATION_RECORD`]
+    pub record:  u8,
+    /// The dataset number within the record
+    pub dataset: u8,
+    /// The raw value of the dataset
+    pub data:    Vec<u8>
+}
+
+/// IPTC-IIM metadata, used by editorial workflows for captions, keywords and credits
+///
+/// Datasets are kept in the order they were read, fields that can repeat like
+/// keywords have one dataset per value. Text is read and written as UTF-8.
+///
+/// # Example
+/// ```
+/// use zune_image::metadata::IptcData;
+///
+/// let mut iptc = IptcData::new();
+/// iptc.set(IptcData::CAPTION, "A lighthouse at dusk");
+/// iptc.add(IptcData::KEYWORDS, "lighthouse");
+/// iptc.add(IptcData::KEYWORDS, "coast");
+///
+/// let parsed = IptcData::parse(&iptc.to_bytes());
+/// assert_eq!(parsed.caption().as_deref(), Some("A lighthouse at dusk"));
+/// assert_eq!(parsed.keywords(), ["lighthouse", "coast"]);
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct IptcData {
+    datasets: Vec<IptcDataSet>
+}
+
+impl IptcData {
+    /// The record holding editorial fields
+    pub const APPLICATION_RECORD: u8 = 2;
+
+    /// Title of the image
+    pub const OBJECT_NAME: u8 = 5;
+    /// Keywords, one dataset per keyword
+    pub const KEYWORDS: u8 = 25;
+    /// Name of the creator
+    pub const BYLINE: u8 = 80;
+    /// City the image was captured in
+    pub const CITY: u8 = 90;
+    /// Country the image was captured in
+    pub const COUNTRY: u8 = 101;
+    /// Headline
+    pub const HEADLINE: u8 = 105;
+    /// Who should be credited
+    pub const CREDIT: u8 = 110;
+    /// Original owner of the image
+    pub const SOURCE: u8 = 115;
+    /// Copyright notice
+    pub const COPYRIGHT: u8 = 116;
+    /// Description of the image
+    pub const CAPTION: u8 = 120;
+
+    /// Create empty metadata
+    pub const fn new() -> IptcData {
+        IptcData { datasets: vec![] }
+    }
+
+    /// Parse IPTC-IIM records
+    ///
+    /// Parsing stops at the first malformed dataset, keeping the datasets read until then
+    pub fn parse(data: &[u8]) -> IptcData {
+        let mut datasets = vec![];
+        let mut data = data;
+
+        while let [TAG_MARKER, record, dataset, a, b, rest @ ..] = data {
+            let mut length = usize::from(u16::from_be_bytes([*a, *b]));
+            let mut rest = rest;
+
+            if length & 0x8000 != 0 {
+                // extended datasets store the number of bytes of the length instead
+                let bytes = length & 0x7FFF;
+
+                if bytes > size_of::<usize>() || rest.len() < bytes {
+                    break;
+                }
+                length = rest[..bytes]
+                    .iter()
+                    .fold(0, |acc, x| (acc << 8) | usize::from(*x));
+                rest = &rest[bytes..];
+            }
+            if rest.len() < length {
+                warn!("Truncated IPTC dataset {}:{}", record, dataset);
+                break;
+            }
+            datasets.push(IptcDataSet {
+                record:  *record,
+                dataset: *dataset,
+                data:    rest[..length].to_vec()
+            });
+            data = &rest[length..];
+        }
+        IptcData { datasets }
+    }
+
+    /// Encode the datasets as IPTC-IIM records
+    ///
+    /// The character set is marked as UTF-8 if it isn't set
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = vec![];
+
+        let has_charset = self
+            .datasets
+            .iter()
+            .any(|x| x.record == ENVELOPE_RECORD && x.dataset == CODED_CHARACTER_SET);
+
+        if !has_charset && !self.datasets.is_empty() {
+            write_dataset(&mut out, ENVELOPE_RECORD, CODED_CHARACTER_SET, UTF8_ESCAPE);
+        }
+        // records have to be in increasing order
+        let mut datasets: Vec<&IptcDataSet> = self.datasets.iter().collect();
+        datasets.sort_by_key(|x| x.record);
+
+        for dataset in datasets {
+            write_dataset(&mut out, dataset.record, dataset.dataset, &dataset.data);
+        }
+        out
+    }
+
+    /// All datasets
+    pub fn datasets(&self) -> &[IptcDataSet] {
+        &self.datasets
+    }
+
+    /// Return true if there are no datasets
+    pub fn is_empty(&self) -> bool {
+        self.datasets.is_empty()
+    }
+
+    /// The first value of an editorial dataset as text
+    pub fn get(&self, dataset: u8) -> Option<String> {
+        self.get_all(dataset).into_iter().next()
+    }
+
+    /// All values of an editorial dataset as text
+    pub fn get_all(&self, dataset: u8) -> Vec<String> {
+        self.datasets
+            .iter()
+            .filter(|x| x.record == IptcData::APPLICATION_RECORD && x.dataset == dataset)
+            .map(|x| {
+                String::from_utf8_lossy(&x.data)
+                    .trim_end_matches('\0')
+                    .to_string()
+            })
+            .collect()
+    }
+
+    /// Set an editorial dataset, replacing all of its values
+    pub fn set(&mut self, dataset: u8, value: &str) {
+        self.remove(dataset);
+        self.add(dataset, value);
+    }
+
+    /// Add a value to an editorial dataset, keeping existing values
+    pub fn add(&mut self, dataset: u8, value: &str) {
+        self.datasets.push(IptcDataSet {
+            record: IptcData::APPLICATION_RECORD,
+            dataset,
+            data: value.as_bytes().to_vec()
+        });
+    }
+
+    /// Remove all values of an editorial dataset
+    pub fn remove(&mut self, dataset: u8) {
+        self.datasets
+            .retain(|x| x.record != IptcData::APPLICATION_RECORD || x.dataset != dataset);
+    }
+
+    /// Keep only the datasets for which `function` returns true
+    pub fn retain(&mut self, function: impl FnMut(&IptcDataSet) -> bool) {
+        self.datasets.retain(function);
+    }
+
+    /// The caption, or description, of the image
+    pub fn caption(&self) -> Option<String> {
+        self.get(IptcData::CAPTION)
+    }
+
+    /// The keywords of the image
+    pub fn keywords(&self) -> Vec<String> {
+        self.get_all(IptcData::KEYWORDS)
+    }
+
+    /// Who should be credited for the image
+    pub fn credit(&self) -> Option<String> {
+        self.get(IptcData::CREDIT)
+    }
+
+    /// The copyright notice of the image
+    pub fn copyright(&self) -> Option<String> {
+        self.get(IptcData::COPYRIGHT)
+    }
+}
+
+fn write_dataset(out: &mut Vec<u8>, record: u8, dataset: u8, data: &[u8]) {
+    out.extend_from_slice(&[TAG_MARKER, record, dataset]);
+
+    if data.len() < 0x8000 {
+        out.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    } else {
+        // extended dataset, the length is stored in the next four bytes
+        out.extend_from_slice(&0x8004_u16.to_be_bytes());
+        out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    }
+    out.extend_from_slice(data);
+}
+
+/// Wrap IPTC-IIM records in Photoshop image resources, as stored in
+/// JPEG APP13 segments
+#[cfg(feature = "jpeg")]
+pub(crate) fn photoshop_resources(iptc: &[u8]) -> Vec<u8> {
+    let mut out = b"Photoshop 3.0\x00".to_vec();
+
+    out.extend_from_slice(b"8BIM");
+    // resource id, followed by an empty name padded to an even length
+    out.extend_from_slice(&[0x04, 0x04, 0, 0]);
+    out.extend_from_slice(&(iptc.len() as u32).to_be_bytes());
+    out.extend_from_slice(iptc);
+
+    if iptc.len() % 2 == 1 {
+        out.push(0);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::metadata::{IptcData, IptcDataSet};
+
+    #[test]
+    fn test_iptc_round_trip() {
+        let mut iptc = IptcData::new();
+        iptc.add(IptcData::KEYWORDS, "first");
+        iptc.add(IptcData::KEYWORDS, "second");
+        iptc.set(IptcData::CREDIT, "Agency");
+        iptc.set(IptcData::CREDIT, "Another agency");
+        // long enough to need an extended dataset
+        let caption = "ä".repeat(20000);
+        iptc.set(IptcData::CAPTION, &caption);
+
+        let parsed = IptcData::parse(&iptc.to_bytes());
+        // the character set is added in front
+        assert_eq!(parsed.datasets()[0].record, 1);
+        assert_eq!(parsed.datasets().len(), 5);
+        assert_eq!(parsed.keywords(), ["first", "second"]);
+        assert_eq!(parsed.credit().as_deref(), Some("Another agency"));
+        assert_eq!(parsed.caption(), Some(caption));
+
+        // truncated data keeps the complete datasets
+        let bytes = iptc.to_bytes();
+        let truncated = IptcData::parse(&bytes[..bytes.len() - 1]);
+        assert_eq!(
+            truncated.datasets()[1],
+            IptcDataSet {
+                record:  2,
+                dataset: IptcData::KEYWORDS,
+                data:    b"first".to_vec()
+            }
+        );
+        assert!(truncated.caption().is_none());
+    }
+}
@@ -0,0 +1,76 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Read only memory maps of files
+//!
+//! Decoding from a mapping lets the decoder borrow the file contents straight from the
+//! page cache instead of copying them through a read buffer, and the kernel can drop the
+//! pages again under memory pressure.
+#![cfg(all(feature = "mmap", unix))]
+
+use std::fs::File;
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::ptr::NonNull;
+
+/// A read only mapping of a whole file
+pub(crate) struct Mmap {
+    ptr: NonNull<u8>,
+    len: usize
+}
+
+impl Mmap {
+    /// Map `file` into memory
+    ///
+    /// # Safety
+    /// The file must be a regular, non empty file and must not be truncated or modified
+    /// while the mapping lives, doing so changes the bytes behind the returned slice
+    /// or makes reading them fault
+    pub(crate) unsafe fn map(file: &File) -> io::Result<Mmap> {
+        let len = usize::try_from(file.metadata()?.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "File too large to map"))?;
+
+        if len == 0 {
+            // mmap rejects empty mappings, callers are expected to read those instead
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Cannot map an empty file"));
+        }
+        let ptr = libc::mmap(
+            std::ptr::null_mut(),
+            len,
+            libc::PROT_READ,
+            libc::MAP_PRIVATE,
+            file.as_raw_fd(),
+            0
+        );
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        // decoders read front to back
+        libc::madvise(ptr, len, libc::MADV_SEQUENTIAL);
+
+        Ok(Mmap {
+            ptr: NonNull::new_unchecked(ptr.cast()),
+            len
+        })
+    }
+}
+
+impl AsRef<[u8]> for Mmap {
+    fn as_ref(&self) -> &[u8] {
+        // SAFETY: the mapping is readable for len bytes until dropped
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        // SAFETY: ptr and len are the values returned by and passed to mmap
+        unsafe {
+            libc::munmap(self.ptr.as_ptr().cast(), self.len);
+        }
+    }
+}
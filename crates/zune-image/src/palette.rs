@@ -0,0 +1,516 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Indexed (paletted) images
+//!
+//! An [`IndexedImage`] stores one byte per pixel indexing into a [`Palette`] of at most
+//! 256 RGBA colors, the way PNG, GIF and BMP store paletted images.
+//!
+//! [`Image`] always stores expanded colors, converting an indexed asset to an image and
+//! back requires quantizing it again which may change the palette. Keeping the indexed
+//! representation allows editing the palette and the indices directly and writing the
+//! image out with the same palette.
+//!
+//! # Decoding
+//! - PNG: [`IndexedImage::decode_png`], requires the `png` feature
+//! - BMP: [`IndexedImage::decode_bmp`], requires the `bmp` feature
+//!
+//! The decoders are asked to return the palette indices as stored in the file, so
+//! duplicate palette entries and unused entries are kept.
+//!
+//! GIF isn't supported yet since its decoder isn't available through this crate.
+//!
+//! # Encoding
+//! - PNG: [`IndexedImage::encode_png`], requires the `png` feature
+use alloc::format;
+use alloc::vec::Vec;
+
+use zune_core::bit_depth::BitDepth;
+use zune_core::colorspace::ColorSpace;
+
+use crate::errors::ImageErrors;
+use crate::image::Image;
+
+/// Maximum number of entries in a palette
+pub const MAX_PALETTE_ENTRIES: usize = 256;
+
+/// A table of up to 256 RGBA colors
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Palette {
+    entries: Vec<[u8; 4]>
+}
+
+impl Palette {
+    /// Create a new palette from RGBA entries
+    ///
+    /// # Errors
+    /// If there are more than [`MAX_PALETTE_ENTRIES`] entries
+    pub fn new(entries: Vec<[u8; 4]>) -> Result<Palette, ImageErrors> {
+        if entries.len() > MAX_PALETTE_ENTRIES {
+            return Err(ImageErrors::GenericString(format!(
+                "Palettes can have at most {MAX_PALETTE_ENTRIES} entries, found {}",
+                entries.len()
+            )));
+        }
+        Ok(Palette { entries })
+    }
+    /// Return the palette entries
+    pub fn entries(&self) -> &[[u8; 4]] {
+        &self.entries
+    }
+    /// Return the palette entries for modification
+    ///
+    /// Changing an entry changes the color of every pixel indexing it
+    pub fn entries_mut(&mut self) -> &mut [[u8; 4]] {
+        &mut self.entries
+    }
+    /// Return the number of entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    /// Return true if the palette has no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /// Return true if all entries are fully opaque
+    pub fn is_opaque(&self) -> bool {
+        self.entries.iter().all(|x| x[3] == 255)
+    }
+    /// Return the index of the first entry exactly matching `color`
+    pub fn position(&self, color: [u8; 4]) -> Option<u8> {
+        self.entries
+            .iter()
+            .position(|x| *x == color)
+            .map(|x| x as u8)
+    }
+    /// Return the index of the entry closest to `color`
+    ///
+    /// Distance is the squared euclidean distance of the RGBA values
+    ///
+    /// # Panics
+    /// If the palette is empty
+    pub fn nearest(&self, color: [u8; 4]) -> u8 {
+        let distance = |entry: &[u8; 4]| {
+            entry
+                .iter()
+                .zip(color.iter())
+                .map(|(a, b)| {
+                    let diff = i32::from(*a) - i32::from(*b);
+                    diff * diff
+                })
+                .sum::<i32>()
+        };
+        self.entries
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, entry)| distance(entry))
+            .map(|(i, _)| i as u8)
+            .expect("Palette is empty")
+    }
+    /// Add an entry to the palette returning its index
+    ///
+    /// # Errors
+    /// If the palette is full
+    pub fn push(&mut self, color: [u8; 4]) -> Result<u8, ImageErrors> {
+        if self.entries.len() >= MAX_PALETTE_ENTRIES {
+            return Err(ImageErrors::GenericStr(
+                "Palette is full, an image can't have more than 256 colors"
+            ));
+        }
+        self.entries.push(color);
+        Ok((self.entries.len() - 1) as u8)
+    }
+}
+
+/// An image storing palette indices
+///
+/// # Example
+/// Edit the palette of an indexed image
+/// ```
+/// use zune_image::palette::{IndexedImage, Palette};
+///
+/// let palette = Palette::new(vec![[0, 0, 0, 255], [255, 255, 255, 255]]).unwrap();
+/// let mut image = IndexedImage::new(vec![0, 1, 1, 0], palette, 2, 2).unwrap();
+///
+/// // turn white into red, without touching the pixels
+/// image.palette_mut().entries_mut()[1] = [255, 0, 0, 255];
+///
+/// assert_eq!(image.to_image().flatten_to_u8()[0][3..6], [255, 0, 0]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct IndexedImage {
+    indices: Vec<u8>,
+    palette: Palette,
+    width:   usize,
+    height:  usize
+}
+
+impl IndexedImage {
+    /// Create a new indexed image
+    ///
+    /// # Arguments
+    /// - indices: One palette index per pixel, in row major order
+    /// - palette: The palette of the image
+    /// - width, height: Image dimensions
+    ///
+    /// # Errors
+    /// If the number of indices doesn't match the dimensions or an index is out
+    /// of the palette
+    pub fn new(
+        indices: Vec<u8>, palette: Palette, width: usize, height: usize
+    ) -> Result<IndexedImage, ImageErrors> {
+        if indices.len() != width * height {
+            return Err(ImageErrors::DimensionsMisMatch(
+                width * height,
+                indices.len()
+            ));
+        }
+        if let Some(index) = indices.iter().find(|x| usize::from(**x) >= palette.len()) {
+            return Err(ImageErrors::GenericString(format!(
+                "Index {index} is out of the palette with {} entries",
+                palette.len()
+            )));
+        }
+        Ok(IndexedImage {
+            indices,
+            palette,
+            width,
+            height
+        })
+    }
+
+    /// Create an indexed image from the first frame of an image with at most 256 colors
+    ///
+    /// Colors are added to the palette in the order they appear.
+    ///
+    /// # Errors
+    /// - If the image isn't an 8 bit RGB, RGBA, Luma or LumaA image
+    /// - If the image has more than 256 colors, quantize it first
+    pub fn from_image(image: &Image) -> Result<IndexedImage, ImageErrors> {
+        IndexedImage::from_image_with_palette(image, Palette::default())
+    }
+
+    /// Create an indexed image from the first frame of an image, using the indices
+    /// of `palette` for the colors it contains
+    ///
+    /// Colors missing from the palette are added to it
+    ///
+    /// # Errors
+    /// - If the image isn't an 8 bit RGB, RGBA, Luma or LumaA image
+    /// - If the palette can't fit all colors of the image
+    pub fn from_image_with_palette(
+        image: &Image, mut palette: Palette
+    ) -> Result<IndexedImage, ImageErrors> {
+        let colorspace = image.colorspace();
+
+        if image.depth() != BitDepth::Eight {
+            return Err(ImageErrors::GenericStr(
+                "Indexed images can only be created from 8 bit images"
+            ));
+        }
+        let to_rgba: fn(&[u8]) -> [u8; 4] = match colorspace {
+            ColorSpace::RGB => |x| [x[0], x[1], x[2], 255],
+            ColorSpace::RGBA => |x| [x[0], x[1], x[2], x[3]],
+            ColorSpace::Luma => |x| [x[0], x[0], x[0], 255],
+            ColorSpace::LumaA => |x| [x[0], x[0], x[0], x[1]],
+            _ => {
+                return Err(ImageErrors::UnsupportedColorspace(
+                    colorspace,
+                    "Indexed image",
+                    &[
+                        ColorSpace::RGB,
+                        ColorSpace::RGBA,
+                        ColorSpace::Luma,
+                        ColorSpace::LumaA
+                    ]
+                ))
+            }
+        };
+        let (width, height) = image.dimensions();
+        let pixels = &image.flatten_frames::<u8>()[0];
+
+        let mut lookup = alloc::collections::BTreeMap::new();
+        for (i, entry) in palette.entries().iter().enumerate() {
+            lookup.entry(*entry).or_insert(i as u8);
+        }
+        let mut indices = Vec::with_capacity(width * height);
+
+        for pixel in pixels.chunks_exact(colorspace.num_components()) {
+            let color = to_rgba(pixel);
+
+            let index = match lookup.get(&color) {
+                Some(index) => *index,
+                None => {
+                    let index = palette.push(color)?;
+                    lookup.insert(color, index);
+                    index
+                }
+            };
+            indices.push(index);
+        }
+        IndexedImage::new(indices, palette, width, height)
+    }
+
+    /// Expand the image to RGB, or RGBA if the palette has transparent entries
+    pub fn to_image(&self) -> Image {
+        let opaque = self.palette.is_opaque();
+        let components = if opaque { 3 } else { 4 };
+        let entries = self.palette.entries();
+
+        let mut pixels = Vec::with_capacity(self.indices.len() * components);
+
+        for index in &self.indices {
+            pixels.extend_from_slice(&entries[usize::from(*index)][..components]);
+        }
+        let colorspace = if opaque { ColorSpace::RGB } else { ColorSpace::RGBA };
+        Image::from_u8(&pixels, self.width, self.height, colorspace)
+    }
+
+    /// Return the palette indices, one per pixel
+    pub fn indices(&self) -> &[u8] {
+        &self.indices
+    }
+    /// Return the palette indices for modification
+    ///
+    /// Indices must stay smaller than the palette length, other operations
+    /// may panic otherwise
+    pub fn indices_mut(&mut self) -> &mut [u8] {
+        &mut self.indices
+    }
+    /// Return the image palette
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+    /// Return the image palette for modification
+    ///
+    /// Entries can be changed freely, removing entries used by the image
+    /// isn't possible through this, see [`compact`](Self::compact)
+    pub fn palette_mut(&mut self) -> &mut Palette {
+        &mut self.palette
+    }
+    /// Return the image dimensions as a tuple of width and height
+    pub const fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Apply a color operation to the palette
+    ///
+    /// This is equivalent to applying the operation to every pixel but only
+    /// touches the palette entries
+    pub fn map_palette(&mut self, mut function: impl FnMut([u8; 4]) -> [u8; 4]) {
+        for entry in self.palette.entries_mut() {
+            *entry = function(*entry);
+        }
+    }
+
+    /// Use a new palette, mapping every pixel to the closest color of `palette`
+    ///
+    /// # Errors
+    /// If the new palette is empty
+    pub fn remap(&mut self, palette: Palette) -> Result<(), ImageErrors> {
+        if palette.is_empty() {
+            return Err(ImageErrors::GenericStr("Can't remap to an empty palette"));
+        }
+        let mapping: Vec<u8> = self
+            .palette
+            .entries()
+            .iter()
+            .map(|x| palette.nearest(*x))
+            .collect();
+
+        for index in &mut self.indices {
+            *index = mapping[usize::from(*index)];
+        }
+        self.palette = palette;
+
+        Ok(())
+    }
+
+    /// Remove palette entries that no pixel uses, keeping the order of
+    /// the remaining entries
+    pub fn compact(&mut self) {
+        let mut used = [false; MAX_PALETTE_ENTRIES];
+        for index in &self.indices {
+            used[usize::from(*index)] = true;
+        }
+        let mut mapping = [0_u8; MAX_PALETTE_ENTRIES];
+        let mut entries = Vec::with_capacity(self.palette.len());
+
+        for (i, entry) in self.palette.entries().iter().enumerate() {
+            if used[i] {
+                mapping[i] = entries.len() as u8;
+                entries.push(*entry);
+            }
+        }
+        for index in &mut self.indices {
+            *index = mapping[usize::from(*index)];
+        }
+        self.palette = Palette { entries };
+    }
+
+    /// Decode a paletted PNG keeping its palette
+    ///
+    /// # Errors
+    /// If decoding fails or the image isn't paletted
+    #[cfg(feature = "png")]
+    pub fn decode_png(data: &[u8]) -> Result<IndexedImage, ImageErrors> {
+        use zune_core::bytestream::ZCursor;
+        use zune_core::options::DecoderOptions;
+
+        let options = crate::codecs::limit_decoder_threads(
+            DecoderOptions::default().png_set_keep_indices(true)
+        );
+        let mut decoder = zune_png::PngDecoder::new_with_options(ZCursor::new(data), options);
+        decoder.decode_headers()?;
+
+        let palette = decoder
+            .palette()
+            .ok_or(ImageErrors::GenericStr("PNG image is not paletted"))?;
+        let indices = decoder.decode_raw()?;
+        let (width, height) = decoder.dimensions().unwrap();
+
+        IndexedImage::new(indices, Palette::new(palette)?, width, height)
+    }
+
+    /// Decode a paletted BMP keeping its palette
+    ///
+    /// # Errors
+    /// If decoding fails or the image isn't paletted
+    #[cfg(feature = "bmp")]
+    pub fn decode_bmp(data: &[u8]) -> Result<IndexedImage, ImageErrors> {
+        use zune_core::bytestream::ZCursor;
+
+        let mut decoder = zune_bmp::BmpDecoder::new(ZCursor::new(data));
+        decoder.keep_indices(true);
+        decoder.decode_headers()?;
+
+        let palette = decoder
+            .palette()
+            .ok_or(ImageErrors::GenericStr("BMP image is not paletted"))?;
+        let indices = decoder.decode()?;
+        let (width, height) = decoder.dimensions().unwrap();
+
+        IndexedImage::new(indices, Palette::new(palette)?, width, height)
+    }
+
+    /// Encode the image as a paletted PNG
+    ///
+    /// # Errors
+    /// If the palette is empty or encoding fails
+    #[cfg(feature = "png")]
+    pub fn encode_png(&self) -> Result<Vec<u8>, ImageErrors> {
+        use zune_core::options::EncoderOptions;
+
+        use crate::errors::ImgEncodeErrors;
+
+        let options = EncoderOptions::default()
+            .set_width(self.width)
+            .set_height(self.height)
+            .set_colorspace(ColorSpace::Luma)
+            .set_depth(BitDepth::Eight);
+
+        let mut encoder = zune_png::PngEncoder::new(&self.indices, options);
+        encoder.set_palette(self.palette.entries());
+
+        let mut sink = vec![];
+        encoder.encode(&mut sink).map_err(|e| {
+            ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!("{e:?}")))
+        })?;
+        Ok(sink)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::image::Image;
+    use crate::palette::{IndexedImage, Palette};
+
+    fn test_image() -> IndexedImage {
+        let palette = Palette::new(vec![
+            [255, 0, 0, 255],
+            [0, 255, 0, 255],
+            [0, 0, 255, 128],
+            [9, 9, 9, 255],
+        ])
+        .unwrap();
+        let indices = (0..64).map(|x| (x % 3) as u8).collect();
+
+        IndexedImage::new(indices, palette, 8, 8).unwrap()
+    }
+
+    #[test]
+    fn test_image_round_trip() {
+        let image = test_image();
+        let expanded = image.to_image();
+        assert_eq!(expanded.colorspace(), ColorSpace::RGBA);
+
+        let indexed =
+            IndexedImage::from_image_with_palette(&expanded, image.palette().clone()).unwrap();
+        assert_eq!(indexed.indices(), image.indices());
+        assert_eq!(indexed.palette(), image.palette());
+    }
+
+    #[test]
+    fn test_too_many_colors() {
+        let pixels: Vec<u16> = (0..300).collect();
+        let pixels: Vec<u8> = pixels.iter().flat_map(|x| x.to_le_bytes()).collect();
+        let image = Image::from_u8(&pixels, 300, 1, ColorSpace::LumaA);
+
+        assert!(IndexedImage::from_image(&image).is_err());
+    }
+
+    #[test]
+    fn test_remap_and_compact() {
+        let mut image = test_image();
+
+        image.compact();
+        assert_eq!(image.palette().len(), 3);
+
+        let gray = Palette::new(vec![[0, 0, 0, 255], [250, 10, 10, 255]]).unwrap();
+        image.remap(gray).unwrap();
+        // red maps to the reddish entry, everything else to black
+        assert_eq!(&image.indices()[..3], &[1, 0, 0]);
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_png_round_trip() {
+        let image = test_image();
+        let encoded = image.encode_png().unwrap();
+
+        let decoded = IndexedImage::decode_png(&encoded).unwrap();
+        assert_eq!(decoded.indices(), image.indices());
+        assert_eq!(decoded.palette(), image.palette());
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_png_duplicate_entries() {
+        // the same color twice, pixels using either entry keep their index
+        let palette = Palette::new(vec![[7, 7, 7, 255], [7, 7, 7, 255], [1, 2, 3, 255]]).unwrap();
+        let image = IndexedImage::new(vec![1, 0, 2, 1, 1, 0], palette, 3, 2).unwrap();
+
+        let decoded = IndexedImage::decode_png(&image.encode_png().unwrap()).unwrap();
+        assert_eq!(decoded.indices(), image.indices());
+        assert_eq!(decoded.palette(), image.palette());
+    }
+
+    #[cfg(feature = "bmp")]
+    #[test]
+    fn test_bmp_indices() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../test-images/bmp/pal4rle.bmp");
+        let data = std::fs::read(path).unwrap();
+
+        let decoded = IndexedImage::decode_bmp(&data).unwrap();
+        let expanded = Image::read(zune_core::bytestream::ZCursor::new(&data), Default::default())
+            .unwrap();
+        assert!(decoded.to_image().flatten_frames::<u8>() == expanded.flatten_frames::<u8>());
+    }
+}
@@ -6,14 +6,20 @@
 //! Pipelines, Batch image processing support
 //!
 #![allow(unused_variables)]
+use std::sync::Arc;
 use std::time::Instant;
 
+#[cfg(feature = "threads")]
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
 use zune_core::log::Level::Trace;
 use zune_core::log::{log_enabled, trace};
 
+use crate::channel::{with_allocator, ScratchPool};
 use crate::codecs::ImageFormat;
 use crate::errors::ImageErrors;
 use crate::image::Image;
+use crate::point_ops::execute_fused;
 use crate::traits::{IntoImage, OperationsTrait};
 
 #[derive(Copy, Clone, Debug)]
@@ -72,11 +78,23 @@ impl EncodeResult {
 /// it has to own the image for the duration of it's lifetime, but can return references to it
 /// via  [`images`](crate::pipelines::Pipeline::images) and
 ///  [`images_mut`](crate::pipelines::Pipeline::images_mut)
+///
+/// # Fusing point operations
+/// Consecutive operations that are [point operations](OperationsTrait::is_point_op), e.g.
+/// invert, brighten and gamma, are fused like [`PointOps`](crate::point_ops::PointOps) does.
+/// On 8 and 16 bit images the operations run once on every possible value of each channel,
+/// producing a lookup table per channel, which is then applied to the image in a single pass,
+/// float images are run through the operations in chunks. The result is the same as running
+/// the operations one after the other.
 pub struct Pipeline {
     state:      Option<PipelineState>,
     decode:     Option<Box<dyn IntoImage>>,
     image:      Vec<Image>,
-    operations: Vec<Box<dyn OperationsTrait>>
+    operations: Vec<Box<dyn OperationsTrait>>,
+    /// The pool set by [`set_threads`](Self::set_threads), or why it couldn't be built
+    #[cfg(feature = "threads")]
+    pool:       Option<Result<Arc<ThreadPool>, String>>,
+    scratch:    Option<Arc<ScratchPool>>
 }
 
 impl Pipeline {
@@ -87,7 +105,10 @@ impl Pipeline {
             image:      vec![],
             state:      Some(PipelineState::Initialized),
             decode:     None,
-            operations: vec![]
+            operations: vec![],
+            #[cfg(feature = "threads")]
+            pool:       None,
+            scratch:    None
         }
     }
 
@@ -135,6 +156,80 @@ impl Pipeline {
         self.operations.push(operations);
         self
     }
+    /// Set the number of threads operations run on
+    ///
+    /// With the `threads` feature, operations split their work, e.g. channels or bands
+    /// of rows, between threads of a thread pool. A value of `0`, the default, uses
+    /// the pool set in [`threads`](crate::threads), any other value creates a pool of
+    /// that many threads for running this pipeline's operations.
+    ///
+    /// The pool is created here and kept for every run of the pipeline, if that fails
+    /// running the pipeline returns the error.
+    ///
+    /// Without the `threads` feature operations run on the calling thread and
+    /// this does nothing
+    pub fn set_threads(&mut self, threads: usize) -> &mut Pipeline {
+        #[cfg(feature = "threads")]
+        {
+            self.pool = match threads {
+                0 => None,
+                threads => Some(
+                    ThreadPoolBuilder::new()
+                        .num_threads(threads)
+                        .build()
+                        .map(Arc::new)
+                        .map_err(|e| format!("Could not create a thread pool: {e}"))
+                )
+            };
+        }
+        self
+    }
+    /// Run this pipeline's operations on `pool`
+    ///
+    /// Like [`set_threads`](Self::set_threads), but sharing a pool, e.g. between
+    /// pipelines run one after the other
+    #[cfg(feature = "threads")]
+    pub fn set_thread_pool(&mut self, pool: Arc<ThreadPool>) -> &mut Pipeline {
+        self.pool = Some(Ok(pool));
+        self
+    }
+    /// Allocate the channels created by operations from `pool`
+    ///
+    /// Sharing one pool between the pipelines of a loop lets the operations of an image
+    /// reuse the memory of the previous images, instead of allocating and freeing
+    /// their outputs and temporaries for every image, see [`ScratchPool`].
+    ///
+    /// Operations splitting their work between threads allocate what they create on
+    /// those threads from the system allocator.
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use zune_core::colorspace::ColorSpace;
+    /// use zune_image::channel::ScratchPool;
+    /// use zune_image::core_filters::colorspace::ColorspaceConv;
+    /// use zune_image::image::Image;
+    /// use zune_image::pipelines::Pipeline;
+    ///
+    /// let pool = Arc::new(ScratchPool::new());
+    ///
+    /// for _ in 0..10 {
+    ///     let mut pipeline = Pipeline::new();
+    ///     pipeline.chain_image(Image::fill(10_u8, ColorSpace::RGB, 100, 100));
+    ///     pipeline
+    ///         .chain_operations(Box::new(ColorspaceConv::new(ColorSpace::Luma)))
+    ///         .set_scratch_pool(pool.clone())
+    ///         .advance_to_end()
+    ///         .unwrap();
+    /// }
+    /// // the luma channel of the first image is reused by the others
+    /// assert_eq!(pool.system_allocations(), 1);
+    /// ```
+    pub fn set_scratch_pool(&mut self, pool: Arc<ScratchPool>) -> &mut Pipeline {
+        self.scratch = Some(pool);
+        self
+    }
     pub fn images(&self) -> &[Image] {
         self.image.as_ref()
     }
@@ -160,7 +255,7 @@ impl Pipeline {
                     // do the actual decode
                     if self.decode.is_none() {
                         // we have an image, no need to decode a new one
-                        if self.image.is_empty() {
+                        if !self.image.is_empty() {
                             trace!("Image already present, no need to decode");
                             // move to the next state
                             self.state = state.next();
@@ -197,25 +292,12 @@ impl Pipeline {
                         trace!("Current state: {:?}\n", state);
                     }
 
-                    for image in self.image.iter_mut() {
-                        for operation in &self.operations {
-                            let operation_name = operation.name();
-
-                            trace!("Running {}", operation_name);
+                    let mut images = core::mem::take(&mut self.image);
+                    let result = self.apply(&mut images);
+                    self.image = images;
 
-                            let start = Instant::now();
-
-                            operation.execute(image)?;
-
-                            let stop = Instant::now();
-
-                            trace!(
-                                "Finished running `{operation_name}` in {} ms",
-                                (stop - start).as_millis()
-                            );
-                        }
-                        self.state = state.next();
-                    }
+                    result?;
+                    self.state = state.next();
                 }
                 PipelineState::Finished => {
                     trace!("Finished operations for this workflow");
@@ -230,6 +312,26 @@ impl Pipeline {
         }
         Ok(())
     }
+    /// Run the operations of this pipeline on `images`, without touching its state
+    /// or its images
+    ///
+    /// Unlike [`advance`](Self::advance), this can be called any number of times, so
+    /// one pipeline can process image after image. Point operations are fused and
+    /// the [threads](Self::set_threads) and [scratch pool](Self::set_scratch_pool)
+    /// are used the same way.
+    pub fn apply(&self, images: &mut [Image]) -> Result<(), ImageErrors> {
+        #[cfg(feature = "threads")]
+        if let Some(pool) = &self.pool {
+            let pool = pool
+                .as_ref()
+                .map_err(|e| ImageErrors::GenericString(e.clone()))?;
+            // the decoder isn't Sync, so only the operations go to the pool
+            let (operations, scratch) = (&self.operations, &self.scratch);
+
+            return crate::threads::install_on(pool, || run_operations(images, operations, scratch));
+        }
+        run_operations(images, &self.operations, &self.scratch)
+    }
     /// Advance the operations in this workflow up until
     /// we finish.
     ///
@@ -244,3 +346,165 @@ impl Pipeline {
         Ok(())
     }
 }
+
+/// Run `operations` on every image, in order, allocating from `scratch` if it's set
+pub(crate) fn run_operations(
+    images: &mut [Image], operations: &[Box<dyn OperationsTrait>],
+    scratch: &Option<Arc<ScratchPool>>
+) -> Result<(), ImageErrors> {
+    if let Some(pool) = scratch {
+        return with_allocator(pool.clone(), || run_operations(images, operations, &None));
+    }
+    for image in images {
+        let mut operations = operations;
+
+        while let Some(operation) = operations.first() {
+            let fused = operations.iter().take_while(|x| x.is_point_op()).count();
+            let ran = &operations[..fused.max(1)];
+
+            let start = Instant::now();
+
+            if fused > 1 {
+                trace!("Fusing {fused} point operations");
+
+                execute_fused(image, &|image| ran.iter().try_for_each(|x| x.execute(image)))?;
+            } else {
+                trace!("Running {}", operation.name());
+
+                operation.execute(image)?;
+            }
+            operations = &operations[ran.len()..];
+
+            let stop = Instant::now();
+
+            if log_enabled!(Trace) {
+                let names: Vec<&str> = ran.iter().map(|x| x.name()).collect();
+
+                trace!(
+                    "Finished running `{}` in {} ms",
+                    names.join("`, `"),
+                    (stop - start).as_millis()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::bit_depth::BitType;
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::errors::ImageErrors;
+    use crate::image::Image;
+    use crate::pipelines::Pipeline;
+    use crate::traits::OperationsTrait;
+
+    #[test]
+    fn test_chained_image_skips_decoding() {
+        let mut pipeline = Pipeline::new();
+        pipeline.chain_image(Image::fill(0_u8, ColorSpace::RGB, 8, 8));
+        pipeline.advance_to_end().unwrap();
+        assert_eq!(pipeline.images().len(), 1);
+
+        // without an image or a decoder there is nothing to run on
+        let mut pipeline = Pipeline::new();
+        assert!(matches!(
+            pipeline.advance_to_end(),
+            Err(ImageErrors::NoImageForOperations)
+        ));
+    }
+
+    /// Adds a value to color channels, saturating
+    struct Add(u8);
+
+    impl OperationsTrait for Add {
+        fn name(&self) -> &'static str {
+            "Add"
+        }
+        fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+            for channel in image.channels_mut(true) {
+                for x in channel.reinterpret_as_mut::<u8>()? {
+                    *x = x.saturating_add(self.0);
+                }
+            }
+            Ok(())
+        }
+        fn supported_types(&self) -> &'static [BitType] {
+            &[BitType::U8]
+        }
+        fn is_point_op(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_fused_point_ops() {
+        let image = Image::from_fn(16, 16, ColorSpace::RGBA, |y, x, px| {
+            px.fill((y * 16 + x) as u8);
+        });
+        let mut expected = image.clone();
+        Add(20).execute(&mut expected).unwrap();
+        Add(240).execute(&mut expected).unwrap();
+
+        let mut pipeline = Pipeline::new();
+        pipeline.chain_image(image.clone());
+        pipeline
+            .chain_operations(Box::new(Add(20)))
+            .chain_operations(Box::new(Add(240)));
+        pipeline.advance_to_end().unwrap();
+
+        let fused = &pipeline.images()[0];
+        assert!(fused == &expected);
+        // alpha is untouched, so it shares memory with the original
+        assert!(fused.frames_ref()[0].channels_vec_ref()[3].is_shared());
+
+        // the same operations run again on other images
+        let mut images = [image.clone(), image];
+        pipeline.apply(&mut images).unwrap();
+        assert!(images.iter().all(|x| x == &expected));
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn test_pipeline_threads() {
+        /// Fails unless run on a pool of three threads
+        struct CheckThreads;
+
+        impl OperationsTrait for CheckThreads {
+            fn name(&self) -> &'static str {
+                "Check threads"
+            }
+            fn execute_impl(&self, _: &mut Image) -> Result<(), ImageErrors> {
+                match rayon::current_num_threads() {
+                    3 => Ok(()),
+                    _ => Err(ImageErrors::GenericStr("Not on the pipeline's pool"))
+                }
+            }
+            fn supported_types(&self) -> &'static [BitType] {
+                &[BitType::U8]
+            }
+        }
+        let mut pipeline = Pipeline::new();
+        pipeline.chain_image(Image::fill(0_u8, ColorSpace::RGB, 8, 8));
+        pipeline
+            .chain_operations(Box::new(CheckThreads))
+            .set_threads(3);
+        pipeline.advance_to_end().unwrap();
+
+        // the pool is kept for later runs, and can be shared
+        let mut images = [Image::fill(0_u8, ColorSpace::RGB, 8, 8)];
+        pipeline.apply(&mut images).unwrap();
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(3)
+            .build()
+            .unwrap();
+        let mut other = Pipeline::new();
+        other
+            .chain_operations(Box::new(CheckThreads))
+            .set_thread_pool(std::sync::Arc::new(pool));
+        other.apply(&mut images).unwrap();
+    }
+}
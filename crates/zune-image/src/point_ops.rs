@@ -0,0 +1,404 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Point operations run in a single pass over the image
+//!
+//! A point operation maps every value of a channel to a new value depending only on
+//! that value, see [`is_point_op`](OperationsTrait::is_point_op). Running several
+//! of them one after the other goes over the image once per operation, [`PointOps`]
+//! composes them and goes over the image once.
+//!
+//! - 8 and 16 bit images: the operations run on a table of every possible value of each
+//!   channel, 256 or 65536 entries, which is then looked up for every value of the image.
+//! - Float images: the operations run on chunks of the image small enough to stay in cache,
+//!   one chunk after the other.
+//!
+//! [`Pipeline`](crate::pipelines::Pipeline) fuses consecutive point operations this way.
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+use bytemuck::Pod;
+use zune_core::bit_depth::BitType;
+use zune_core::log::warn;
+
+use crate::channel::Channel;
+use crate::errors::ImageErrors;
+use crate::frame::Frame;
+use crate::image::Image;
+use crate::metadata::PixelLayout;
+use crate::traits::OperationsTrait;
+
+/// Values per channel of float images run through the operations at a time
+const CHUNK_SIZE: usize = 1 << 14;
+
+enum PointFn {
+    Operation(Box<dyn OperationsTrait>),
+    Function(Box<dyn Fn(f32) -> f32 + Send + Sync>)
+}
+
+/// Point operations and functions applied to an image in one pass
+///
+/// See the [module docs](self) for how they are combined.
+///
+/// # Example
+/// Square every value then invert it
+///```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::point_ops::PointOps;
+/// use zune_image::traits::OperationsTrait;
+///
+/// let mut image = Image::fill(128_u8, ColorSpace::RGB, 100, 100);
+///
+/// PointOps::new()
+///     .chain_function(|x| x * x)
+///     .chain_function(|x| 1.0 - x)
+///     .execute(&mut image)?;
+///
+/// assert!(image.flatten_to_u8()[0].iter().all(|x| *x == 191));
+/// # Ok::<(), zune_image::errors::ImageErrors>(())
+///```
+#[derive(Default)]
+pub struct PointOps {
+    functions: Vec<PointFn>
+}
+
+impl PointOps {
+    /// Create an empty set of point operations, which leaves images as they are
+    #[must_use]
+    pub fn new() -> PointOps {
+        PointOps::default()
+    }
+
+    /// Add an operation to run after the ones already added
+    ///
+    /// # Panics
+    /// If the operation isn't a [point operation](OperationsTrait::is_point_op)
+    #[must_use]
+    pub fn chain_operation(mut self, operation: Box<dyn OperationsTrait>) -> Self {
+        assert!(
+            operation.is_point_op(),
+            "{} is not a point operation",
+            operation.name()
+        );
+        self.functions.push(PointFn::Operation(operation));
+        self
+    }
+
+    /// Add a function to run after the operations already added
+    ///
+    /// The function is called with a value of a color channel scaled to `0.0..=1.0`,
+    /// i.e. divided by 255 for 8 bit images or 65535 for 16 bit images, and returns
+    /// the new value in the same range. Values are clamped to the range and rounded
+    /// for integer images, float values are passed and stored as they are.
+    ///
+    /// Alpha channels are left alone
+    #[must_use]
+    pub fn chain_function<F>(mut self, function: F) -> Self
+    where
+        F: Fn(f32) -> f32 + Send + Sync + 'static
+    {
+        self.functions.push(PointFn::Function(Box::new(function)));
+        self
+    }
+
+    fn run(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        for function in &self.functions {
+            match function {
+                PointFn::Operation(operation) => operation.execute(image)?,
+                PointFn::Function(function) => apply_function(image, function)?
+            }
+        }
+        Ok(())
+    }
+}
+
+impl OperationsTrait for PointOps {
+    fn name(&self) -> &'static str {
+        "Point operations"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        if self.functions.is_empty() {
+            return Ok(());
+        }
+        execute_fused(image, &|image| self.run(image))
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+
+    fn is_point_op(&self) -> bool {
+        true
+    }
+}
+
+/// Apply `function` to every color channel of the image
+fn apply_function(image: &mut Image, function: &dyn Fn(f32) -> f32) -> Result<(), ImageErrors> {
+    let depth = image.depth().bit_type();
+
+    // integer values are scaled to 0..=1, and rounded back after clamping
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let to_u8 =
+        |x: &mut u8| *x = (function(f32::from(*x) / 255.0).clamp(0.0, 1.0) * 255.0 + 0.5) as u8;
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let to_u16 = |x: &mut u16| {
+        *x = (function(f32::from(*x) / 65535.0).clamp(0.0, 1.0) * 65535.0 + 0.5) as u16;
+    };
+
+    for channel in image.channels_mut(true) {
+        match depth {
+            BitType::U8 => map_values(channel, to_u8)?,
+            BitType::U16 => map_values(channel, to_u16)?,
+            BitType::F32 => map_values(channel, |x: &mut f32| *x = function(*x))?,
+            d => {
+                return Err(ImageErrors::ImageOperationNotImplemented(
+                    "point function",
+                    d
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Run `function` on every value of `channel`, or once if the channel is
+/// one value, see [`Channel::constant`]
+fn map_values<T: 'static + Pod>(
+    channel: &mut Channel, function: impl Fn(&mut T)
+) -> Result<(), ImageErrors> {
+    match channel.constant::<T>() {
+        Some(mut value) => {
+            function(&mut value);
+            channel.fill(value)?;
+        }
+        None => channel
+            .reinterpret_as_mut::<T>()?
+            .iter_mut()
+            .for_each(function)
+    }
+    Ok(())
+}
+
+/// Run `run`, which must only do point operations, in one pass over the image
+///
+/// Falls back to running it on the whole image if it changes anything besides the values,
+/// e.g. the depth or colorspace, and runs it on small images directly, since building
+/// a table or copying chunks takes longer than going over a few pixels more than once
+pub(crate) fn execute_fused(
+    image: &mut Image, run: &dyn Fn(&mut Image) -> Result<(), ImageErrors>
+) -> Result<(), ImageErrors> {
+    let (width, height) = image.dimensions();
+    let values = width * height * image.frames_ref().len();
+
+    match image.depth().bit_type() {
+        BitType::U8 => fuse_point_ops::<u8>(image, run, (0..=u8::MAX).collect()),
+        BitType::U16 if values > usize::from(u16::MAX) => {
+            fuse_point_ops::<u16>(image, run, (0..=u16::MAX).collect())
+        }
+        BitType::F32 if values > CHUNK_SIZE => run_in_chunks(image, run),
+        _ => run(image)
+    }
+}
+
+/// Return true and warn if `run` changed more than the values of `image` to get `result`
+fn changed_image(image: &Image, result: &Image) -> bool {
+    let changed = result.depth() != image.depth() || result.colorspace() != image.colorspace();
+
+    if changed {
+        warn!("Point operations changed the image, running them on the whole image");
+    }
+    changed
+}
+
+/// Build a lookup table per channel by running `run` on `ramp`, every value
+/// of `T`, and apply it to the image
+fn fuse_point_ops<T>(
+    image: &mut Image, run: &dyn Fn(&mut Image) -> Result<(), ImageErrors>, ramp: Vec<T>
+) -> Result<(), ImageErrors>
+where
+    T: Pod + Default + Into<usize>
+{
+    let components = image.colorspace().num_components();
+    let channel = Channel::from_vec(ramp);
+
+    // same metadata so that operations treat the table like the image
+    let mut table = image.clone();
+    table.frames = vec![Frame::new(vec![channel; components])];
+    table.set_dimensions(channel_len::<T>(&table), 1);
+    table.metadata.set_layout(PixelLayout::Planar);
+
+    run(&mut table)?;
+    table.convert_layout(PixelLayout::Planar)?;
+
+    if changed_image(image, &table) {
+        return run(image);
+    }
+    let tables = table.frames_ref()[0]
+        .channels_vec_ref()
+        .iter()
+        .map(|x| x.reinterpret_as::<T>())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    image.convert_layout(PixelLayout::Planar)?;
+
+    for frame in image.frames_mut() {
+        for (channel, table) in frame.channels_vec().iter_mut().zip(&tables) {
+            // leave channels the operations don't touch, e.g. alpha, shared
+            if table.iter().enumerate().all(|(i, x)| (*x).into() == i) {
+                continue;
+            }
+            // e.g. letterbox bars or canvases, one lookup does
+            if let Some(value) = channel.constant::<T>() {
+                channel.fill(table[value.into()])?;
+                continue;
+            }
+            for value in channel.reinterpret_as_mut::<T>()? {
+                *value = table[(*value).into()];
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Run `run` on chunks of every channel of a float image, one chunk after the other
+fn run_in_chunks(
+    image: &mut Image, run: &dyn Fn(&mut Image) -> Result<(), ImageErrors>
+) -> Result<(), ImageErrors> {
+    image.convert_layout(PixelLayout::Planar)?;
+
+    let components = image.colorspace().num_components();
+
+    // same metadata so that operations treat chunks like the image, try
+    // a single pixel first so that nothing is written if it runs on the whole image
+    let mut chunk = image.clone();
+    chunk.frames = vec![Frame::new(vec![
+        Channel::from_vec(vec![0.5_f32]);
+        components
+    ])];
+    chunk.set_dimensions(1, 1);
+
+    let metadata = chunk.metadata.clone();
+
+    run(&mut chunk)?;
+
+    if changed_image(image, &chunk) {
+        return run(image);
+    }
+
+    for frame in &mut image.frames {
+        let channels = frame.channels_vec();
+        let length = channels.first().map_or(0, |x| x.len() / size_of::<f32>());
+
+        for start in (0..length).step_by(CHUNK_SIZE) {
+            let end = (start + CHUNK_SIZE).min(length);
+
+            let values = channels
+                .iter()
+                .map(|x| {
+                    Ok(Channel::from_vec(
+                        x.reinterpret_as::<f32>()?[start..end].to_vec()
+                    ))
+                })
+                .collect::<Result<Vec<_>, ImageErrors>>()?;
+
+            chunk.metadata = metadata.clone();
+            chunk.frames = vec![Frame::new(values)];
+            chunk.set_dimensions(end - start, 1);
+
+            run(&mut chunk)?;
+            chunk.convert_layout(PixelLayout::Planar)?;
+
+            for (channel, values) in channels.iter_mut().zip(chunk.frames[0].channels_vec_ref()) {
+                channel.reinterpret_as_mut::<f32>()?[start..end]
+                    .copy_from_slice(values.reinterpret_as::<f32>()?);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn channel_len<T>(image: &Image) -> usize {
+    image.frames_ref()[0].channels_vec_ref()[0].len() / size_of::<T>()
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::bit_depth::BitType;
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::errors::ImageErrors;
+    use crate::image::Image;
+    use crate::point_ops::PointOps;
+    use crate::traits::OperationsTrait;
+
+    /// Squares color channels of float images
+    struct Square;
+
+    impl OperationsTrait for Square {
+        fn name(&self) -> &'static str {
+            "Square"
+        }
+        fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+            for channel in image.channels_mut(true) {
+                for x in channel.reinterpret_as_mut::<f32>()? {
+                    *x *= *x;
+                }
+            }
+            Ok(())
+        }
+        fn supported_types(&self) -> &'static [BitType] {
+            &[BitType::F32]
+        }
+        fn is_point_op(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_point_ops_float_chunks() {
+        // more than a chunk, and not a multiple of it
+        let image = Image::from_fn(301, 97, ColorSpace::RGBA, |y, x, px| {
+            px.fill((y * 301 + x) as f32 / 30000.0);
+        });
+        let mut expected = image.clone();
+        Square.execute(&mut expected).unwrap();
+        for channel in expected.channels_mut(true) {
+            for x in channel.reinterpret_as_mut::<f32>().unwrap() {
+                *x = 1.0 - *x;
+            }
+        }
+        let mut image = image;
+        PointOps::new()
+            .chain_operation(Box::new(Square))
+            .chain_function(|x| 1.0 - x)
+            .execute(&mut image)
+            .unwrap();
+
+        assert!(image == expected);
+    }
+
+    #[test]
+    fn test_point_ops_u16_function() {
+        // large enough to go through a table
+        let mut image = Image::fill(65535_u16, ColorSpace::Luma, 260, 260);
+
+        PointOps::new()
+            .chain_function(|x| x * 0.5)
+            .execute(&mut image)
+            .unwrap();
+
+        let values = image.frames_ref()[0].channels_vec_ref()[0]
+            .reinterpret_as::<u16>()
+            .unwrap();
+        assert!(values.iter().all(|x| *x == 32768));
+    }
+}
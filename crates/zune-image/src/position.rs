@@ -0,0 +1,101 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Tracking how far a decoder has read
+//!
+//! Decoders own their reader and their errors don't say where they failed, wrapping the
+//! reader in a [`Tracked`] keeps its position readable from outside the decoder so
+//! [`DecodeError`](crate::errors::DecodeError)s can carry the offset.
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::Cell;
+
+use zune_core::bytestream::{ZByteIoError, ZByteReaderTrait, ZSeekFrom};
+
+/// A reader recording its position in a shared cell
+pub(crate) struct Tracked<T> {
+    inner:    T,
+    position: Rc<Cell<u64>>
+}
+
+impl<T: ZByteReaderTrait> Tracked<T> {
+    /// Wrap `inner`, returning the cell holding its position
+    pub(crate) fn new(mut inner: T) -> (Tracked<T>, Rc<Cell<u64>>) {
+        let position = Rc::new(Cell::new(inner.z_position().unwrap_or(0)));
+        let tracked = Tracked {
+            inner,
+            position: position.clone()
+        };
+        (tracked, position)
+    }
+
+    fn advance(&self, bytes: usize) {
+        self.position.set(self.position.get() + bytes as u64);
+    }
+}
+
+impl<T: ZByteReaderTrait> ZByteReaderTrait for Tracked<T> {
+    #[inline(always)]
+    fn read_byte_no_error(&mut self) -> u8 {
+        // counts reads past the end too, the error is close to it either way
+        self.advance(1);
+        self.inner.read_byte_no_error()
+    }
+
+    fn read_exact_bytes(&mut self, buf: &mut [u8]) -> Result<(), ZByteIoError> {
+        self.inner.read_exact_bytes(buf)?;
+        self.advance(buf.len());
+        Ok(())
+    }
+
+    fn read_const_bytes<const N: usize>(&mut self, buf: &mut [u8; N]) -> Result<(), ZByteIoError> {
+        self.inner.read_const_bytes(buf)?;
+        self.advance(N);
+        Ok(())
+    }
+
+    fn read_const_bytes_no_error<const N: usize>(&mut self, buf: &mut [u8; N]) {
+        self.advance(N);
+        self.inner.read_const_bytes_no_error(buf);
+    }
+
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<usize, ZByteIoError> {
+        let bytes = self.inner.read_bytes(buf)?;
+        self.advance(bytes);
+        Ok(bytes)
+    }
+
+    fn peek_bytes(&mut self, buf: &mut [u8]) -> Result<usize, ZByteIoError> {
+        self.inner.peek_bytes(buf)
+    }
+
+    fn peek_exact_bytes(&mut self, buf: &mut [u8]) -> Result<(), ZByteIoError> {
+        self.inner.peek_exact_bytes(buf)
+    }
+
+    fn z_seek(&mut self, from: ZSeekFrom) -> Result<u64, ZByteIoError> {
+        let position = self.inner.z_seek(from)?;
+        self.position.set(position);
+        Ok(position)
+    }
+
+    fn is_eof(&mut self) -> Result<bool, ZByteIoError> {
+        self.inner.is_eof()
+    }
+
+    fn z_position(&mut self) -> Result<u64, ZByteIoError> {
+        let position = self.inner.z_position()?;
+        self.position.set(position);
+        Ok(position)
+    }
+
+    fn read_remaining(&mut self, sink: &mut Vec<u8>) -> Result<usize, ZByteIoError> {
+        let bytes = self.inner.read_remaining(sink)?;
+        self.advance(bytes);
+        Ok(bytes)
+    }
+}
@@ -0,0 +1,197 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! A window showing an image, for debugging
+//!
+//! [`Image::show`] opens a window with the image and blocks until it's closed, which is
+//! quicker than saving images and opening them somewhere else when looking at what
+//! each step of a pipeline does.
+//!
+//! Images of every colorspace and depth can be shown, they are converted to 8 bit RGBA
+//! and drawn over a checkerboard so transparent areas are visible. The window can be
+//! resized, the image keeps its aspect ratio.
+//!
+//! | Key         | Action                                           |
+//! |-------------|--------------------------------------------------|
+//! | Space       | Play or pause animations                         |
+//! | Left, Right | Show the previous or next frame, pausing playing |
+//! | Escape      | Close the window                                 |
+//!
+//! Animations start playing, using the duration of each frame.
+//!
+//! This requires the `preview` feature, and a display to open windows on.
+//!
+//! # Example
+//! ```no_run
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//!
+//! let image = Image::fill(100_u8, ColorSpace::RGB, 640, 480);
+//! image.show().unwrap();
+//! ```
+use std::time::{Duration, Instant};
+
+use minifb::{Key, KeyRepeat, ScaleMode, Window, WindowOptions};
+use zune_core::bit_depth::BitDepth;
+use zune_core::colorspace::ColorSpace;
+
+use crate::core_filters::colorspace::ColorspaceConv;
+use crate::core_filters::depth::Depth;
+use crate::errors::ImageErrors;
+use crate::image::Image;
+use crate::traits::OperationsTrait;
+
+/// How long frames without a duration are shown when playing
+const DEFAULT_FRAME_DURATION: Duration = Duration::from_millis(100);
+
+/// Size of the squares of the checkerboard behind transparent pixels
+const CHECKER_SIZE: usize = 8;
+
+impl Image {
+    /// Show the image in a window, returning when the window is closed
+    ///
+    /// See the [module docs](crate::preview) for the keys the window responds to
+    ///
+    /// # Errors
+    /// If the image is empty or the window couldn't be opened
+    pub fn show(&self) -> Result<(), ImageErrors> {
+        self.show_with_title("zune-image")
+    }
+
+    /// Show the image in a window titled `title`, returning when the window is closed
+    ///
+    /// This helps telling windows apart when showing images from different steps
+    ///
+    /// # Errors
+    /// If the image is empty or the window couldn't be opened
+    pub fn show_with_title(&self, title: &str) -> Result<(), ImageErrors> {
+        let (width, height) = self.dimensions();
+
+        if width == 0 || height == 0 {
+            return Err(ImageErrors::GenericStr("Cannot show an empty image"));
+        }
+        let frames = window_frames(self)?;
+        let durations = self
+            .frames_ref()
+            .iter()
+            .map(|frame| match frame.duration_secs() {
+                secs if secs > 0.0 => Duration::from_secs_f64(secs),
+                _ => DEFAULT_FRAME_DURATION
+            })
+            .collect::<Vec<_>>();
+
+        let options = WindowOptions {
+            resize: true,
+            scale_mode: ScaleMode::AspectRatioStretch,
+            ..WindowOptions::default()
+        };
+        let mut window = Window::new(title, width, height, options).map_err(preview_error)?;
+        window.set_target_fps(60);
+
+        let mut current = 0;
+        let mut playing = frames.len() > 1;
+        let mut shown_at = Instant::now();
+
+        if frames.len() > 1 {
+            window.set_title(&frame_title(title, current, frames.len(), playing));
+        }
+
+        while window.is_open() && !window.is_key_down(Key::Escape) {
+            let previous = (current, playing);
+
+            if window.is_key_pressed(Key::Space, KeyRepeat::No) {
+                playing = !playing;
+            }
+            if window.is_key_pressed(Key::Right, KeyRepeat::Yes) {
+                current = (current + 1) % frames.len();
+                playing = false;
+            }
+            if window.is_key_pressed(Key::Left, KeyRepeat::Yes) {
+                current = (current + frames.len() - 1) % frames.len();
+                playing = false;
+            }
+            if playing && shown_at.elapsed() >= durations[current] {
+                current = (current + 1) % frames.len();
+            }
+            if (current, playing) != previous {
+                shown_at = Instant::now();
+                window.set_title(&frame_title(title, current, frames.len(), playing));
+            }
+            window
+                .update_with_buffer(&frames[current], width, height)
+                .map_err(preview_error)?;
+        }
+        Ok(())
+    }
+}
+
+fn frame_title(title: &str, current: usize, frames: usize, playing: bool) -> String {
+    let state = if playing { "playing" } else { "paused" };
+    format!("{title} - frame {}/{frames} ({state})", current + 1)
+}
+
+fn preview_error(error: minifb::Error) -> ImageErrors {
+    ImageErrors::GenericString(format!("Could not show the image: {error}"))
+}
+
+/// Convert every frame to the `0RGB` pixels windows show, drawing
+/// transparent pixels over a checkerboard
+fn window_frames(image: &Image) -> Result<Vec<Vec<u32>>, ImageErrors> {
+    let mut image = image.clone();
+
+    Depth::new(BitDepth::Eight).execute(&mut image)?;
+    ColorspaceConv::new(ColorSpace::RGBA).execute(&mut image)?;
+
+    let (width, _) = image.dimensions();
+
+    let frames = image
+        .flatten_frames::<u8>()
+        .iter()
+        .map(|pixels| {
+            pixels
+                .chunks_exact(4)
+                .enumerate()
+                .map(|(i, pixel)| {
+                    let (x, y) = (i % width, i / width);
+                    let checker = if (x / CHECKER_SIZE + y / CHECKER_SIZE) % 2 == 0 {
+                        0xFF
+                    } else {
+                        0xCC
+                    };
+                    let alpha = u32::from(pixel[3]);
+                    let [r, g, b] = [pixel[0], pixel[1], pixel[2]]
+                        .map(|c| (u32::from(c) * alpha + checker * (255 - alpha) + 127) / 255);
+
+                    (r << 16) | (g << 8) | b
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::image::Image;
+    use crate::preview::window_frames;
+
+    #[test]
+    fn test_window_frames() {
+        let image = Image::fill(200_u8, ColorSpace::Luma, 16, 2);
+        let frames = window_frames(&image).unwrap();
+        assert!(frames[0].iter().all(|x| *x == 0x00C8_C8C8));
+
+        // transparent pixels show the checkerboard
+        let image = Image::fill(0_u8, ColorSpace::RGBA, 16, 2);
+        let frames = window_frames(&image).unwrap();
+        assert_eq!(frames[0][0], 0x00FF_FFFF);
+        assert_eq!(frames[0][8], 0x00CC_CCCC);
+    }
+}
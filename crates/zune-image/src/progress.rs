@@ -0,0 +1,218 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Progress reporting and cancellation
+//!
+//! Decoding a large image or blurring it can take seconds, interactive applications
+//! want to show a progress bar while that happens and stop the work when the user
+//! asks them to.
+//!
+//! A [`Progress`] holds an optional callback receiving the name of the current stage and
+//! how far along it is, and a flag that can be set from any thread with
+//! [`Progress::cancel`]. Work run inside [`with_progress`] reports to it, and returns
+//! [`ImageErrors::Cancelled`] at the next check once it's cancelled.
+//!
+//! Reports come from decoding with [`Image::read`](crate::image::Image::read),
+//! between frames of a [`FrameStream`](crate::stream::FrameStream) and before and
+//! after every operation, operations splitting their work between threads check
+//! for cancellation between channels.
+//!
+//! # Example
+//! ```
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//! use std::sync::Arc;
+//!
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::core_filters::depth::Depth;
+//! use zune_image::errors::ImageErrors;
+//! use zune_image::image::Image;
+//! use zune_image::progress::{with_progress, Progress};
+//! use zune_image::traits::OperationsTrait;
+//! use zune_core::bit_depth::BitDepth;
+//!
+//! let reports = Arc::new(AtomicUsize::new(0));
+//! let counter = reports.clone();
+//!
+//! let progress = Progress::new().set_callback(move |_stage, _fraction| {
+//!     counter.fetch_add(1, Ordering::Relaxed);
+//! });
+//! let mut image = Image::fill(0_u8, ColorSpace::RGB, 10, 10);
+//!
+//! with_progress(progress.clone(), || Depth::new(BitDepth::Sixteen).execute(&mut image)).unwrap();
+//! assert!(reports.load(Ordering::Relaxed) > 0);
+//!
+//! // e.g. from a cancel button on another thread
+//! progress.cancel();
+//!
+//! let result = with_progress(progress, || Depth::new(BitDepth::Eight).execute(&mut image));
+//! assert!(matches!(result, Err(ImageErrors::Cancelled)));
+//! ```
+use std::cell::RefCell;
+use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::errors::ImageErrors;
+
+type Callback = Arc<dyn Fn(&str, f32) + Send + Sync>;
+
+/// Receives progress reports and carries a cancellation flag
+///
+/// Clones share the callback and the flag, so a clone kept by the user interface
+/// can cancel work running elsewhere
+#[derive(Clone, Default)]
+pub struct Progress {
+    callback:  Option<Callback>,
+    cancelled: Arc<AtomicBool>
+}
+
+impl Progress {
+    /// Create a progress without a callback that isn't cancelled
+    pub fn new() -> Progress {
+        Progress::default()
+    }
+
+    /// Call `callback` with the name of the stage and a fraction from 0.0 to 1.0
+    /// of how much of it is done
+    ///
+    /// Stages are `"decode"` for decoding and the [name](crate::traits::OperationsTrait::name)
+    /// of operations. The callback is called on the thread doing the work, and should
+    /// return quickly
+    pub fn set_callback(mut self, callback: impl Fn(&str, f32) + Send + Sync + 'static) -> Self {
+        self.callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Ask work using this progress, or a clone of it, to stop
+    ///
+    /// Work stops at its next check, which returns [`ImageErrors::Cancelled`]
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Return true if [`cancel`](Self::cancel) has been called
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+impl Debug for Progress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Progress")
+            .field("callback", &self.callback.is_some())
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Progress>> = const { RefCell::new(None) };
+}
+
+/// Report the progress of all work on this thread done by `function` to `progress`
+///
+/// Work split between threads has to pass [`current_progress`] on to them,
+/// e.g. by calling `with_progress` on each thread.
+///
+/// Calls can be nested, the previous progress is restored when `function` returns
+pub fn with_progress<R>(progress: Progress, function: impl FnOnce() -> R) -> R {
+    /// Restores the previous progress, even if `function` panics
+    struct Restore(Option<Progress>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            CURRENT.with(|x| *x.borrow_mut() = previous);
+        }
+    }
+    let _restore = Restore(CURRENT.with(|x| x.borrow_mut().replace(progress)));
+
+    function()
+}
+
+/// The progress set by [`with_progress`] on this thread, if any
+pub fn current_progress() -> Option<Progress> {
+    CURRENT.with(|x| x.borrow().clone())
+}
+
+/// Report that `fraction` of `stage` is done
+///
+/// Does nothing outside [`with_progress`]
+///
+/// # Errors
+/// [`ImageErrors::Cancelled`] if the progress was cancelled
+pub fn report(stage: &str, fraction: f32) -> Result<(), ImageErrors> {
+    CURRENT.with(|x| match &*x.borrow() {
+        Some(progress) => {
+            if let Some(callback) = &progress.callback {
+                callback(stage, fraction.clamp(0.0, 1.0));
+            }
+            check(progress)
+        }
+        None => Ok(())
+    })
+}
+
+/// Check whether the work on this thread was cancelled, without reporting progress
+///
+/// # Errors
+/// [`ImageErrors::Cancelled`] if the progress was cancelled
+pub fn check_cancelled() -> Result<(), ImageErrors> {
+    CURRENT.with(|x| match &*x.borrow() {
+        Some(progress) => check(progress),
+        None => Ok(())
+    })
+}
+
+fn check(progress: &Progress) -> Result<(), ImageErrors> {
+    if progress.is_cancelled() {
+        return Err(ImageErrors::Cancelled);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use zune_core::bytestream::ZCursor;
+    use zune_core::options::DecoderOptions;
+
+    use crate::errors::ImageErrors;
+    use crate::image::Image;
+    use crate::progress::{check_cancelled, with_progress, Progress};
+
+    #[test]
+    fn test_decode_reports_progress() {
+        let stages = Arc::new(Mutex::new(vec![]));
+        let seen = stages.clone();
+
+        let progress = Progress::new().set_callback(move |stage, fraction| {
+            seen.lock().unwrap().push((stage.to_string(), fraction));
+        });
+        let source = || ZCursor::new(&b"P5 1 1 255 1"[..]);
+
+        with_progress(progress.clone(), || {
+            Image::read(source(), DecoderOptions::default())
+        })
+        .unwrap();
+        assert_eq!(
+            *stages.lock().unwrap(),
+            [("decode".to_string(), 0.0), ("decode".to_string(), 1.0)]
+        );
+
+        progress.cancel();
+        let result = with_progress(progress.clone(), || {
+            Image::read(source(), DecoderOptions::default())
+        });
+        assert!(matches!(result, Err(ImageErrors::Cancelled)));
+
+        // outside the scope nothing is cancelled
+        assert!(check_cancelled().is_ok());
+        assert!(with_progress(progress, check_cancelled).is_err());
+    }
+}
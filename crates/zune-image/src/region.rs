@@ -0,0 +1,511 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Regions of interest
+//!
+//! A region is a rectangle of an image that can be modified, leaving the rest of
+//! the image untouched, e.g. to blur a face or sharpen a detail
+//!
+//! # Views
+//! The pixels of a region can be read and written in place, without copying, through
+//! [`ImageRegion::channels_mut`], which returns strided views of the rows of the region,
+//! or [`ImageRegion::modify_pixels_mut`] which calls a function on every pixel.
+//!
+//! # Running operations
+//! Operations work on whole images, so [`ImageRegion::execute_copy`] and
+//! [`ImageRegion::modify_copy`] copy the rectangle into a temporary image, run on it and
+//! paste the result back. Only the pixels of the rectangle are copied, never the rest of
+//! the image.
+//!
+//! Operations that sample neighbouring pixels see the edge of the region as the edge of the
+//! image.
+//!
+//! # Example
+//! Make the top left corner of an image red, in place
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//!
+//! let mut image = Image::fill(10_u8, ColorSpace::RGB, 100, 100);
+//!
+//! image
+//!     .region_mut(0, 0, 50, 50)
+//!     .unwrap()
+//!     .modify_pixels_mut(|_, _, pix: [&mut u8; 4]| *pix[0] = 255)
+//!     .unwrap();
+//!
+//! let pixels = &image.flatten_frames::<u8>()[0];
+//! assert_eq!(pixels[0], 255);
+//! assert_eq!(pixels[3 * 99], 10);
+//! ```
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use bytemuck::Pod;
+use zune_core::bit_depth::BitType;
+
+use crate::channel::Channel;
+use crate::errors::ImageErrors;
+use crate::image::{Image, MAX_CHANNELS};
+use crate::metadata::PixelLayout;
+use crate::traits::OperationsTrait;
+
+/// A mutable rectangle of an image
+///
+/// Created by [`Image::region_mut`]
+pub struct ImageRegion<'a> {
+    image:  &'a mut Image,
+    x:      usize,
+    y:      usize,
+    width:  usize,
+    height: usize
+}
+
+/// The rows of one channel of a region, borrowed from the image
+///
+/// Created by [`ImageRegion::channels_mut`]. For interleaved images a row holds every
+/// sample of the pixels, i.e. `width * components` values
+pub struct RegionRows<'a, T> {
+    data:   &'a mut [T],
+    stride: usize,
+    width:  usize,
+    height: usize
+}
+
+impl<T> RegionRows<'_, T> {
+    /// The number of values in a row and the number of rows
+    pub const fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+    /// Get row `y` of the region
+    ///
+    /// # Panics
+    /// If `y` is not less than the region height
+    pub fn row(&self, y: usize) -> &[T] {
+        assert!(y < self.height, "Row {y} is outside the region");
+        &self.data[y * self.stride..y * self.stride + self.width]
+    }
+    /// Get row `y` of the region for modification
+    ///
+    /// # Panics
+    /// If `y` is not less than the region height
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        assert!(y < self.height, "Row {y} is outside the region");
+        &mut self.data[y * self.stride..y * self.stride + self.width]
+    }
+    /// Iterate over the rows of the region, top to bottom
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [T]> {
+        let width = self.width;
+
+        self.data
+            .chunks_mut(self.stride)
+            .take(self.height)
+            .map(move |row| &mut row[..width])
+    }
+}
+
+impl Image {
+    /// Get a mutable region of the image
+    ///
+    /// # Arguments
+    /// - x, y: The top left corner of the region
+    /// - width, height: The dimensions of the region
+    ///
+    /// # Errors
+    /// If the region is empty or doesn't fit inside the image
+    pub fn region_mut(
+        &mut self, x: usize, y: usize, width: usize, height: usize
+    ) -> Result<ImageRegion<'_>, ImageErrors> {
+        let (image_width, image_height) = self.dimensions();
+
+        let fits = x.checked_add(width).is_some_and(|e| e <= image_width)
+            && y.checked_add(height).is_some_and(|e| e <= image_height);
+
+        if width == 0 || height == 0 || !fits {
+            let msg = format!(
+                "Region {width}x{height} at ({x},{y}) is outside the {image_width}x{image_height} image"
+            );
+            return Err(ImageErrors::GenericString(msg));
+        }
+        Ok(ImageRegion {
+            image: self,
+            x,
+            y,
+            width,
+            height
+        })
+    }
+}
+
+impl ImageRegion<'_> {
+    /// The top left corner of the region in the image
+    pub const fn position(&self) -> (usize, usize) {
+        (self.x, self.y)
+    }
+    /// The width and height of the region
+    pub const fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Copy the region into a new image
+    ///
+    /// The new image keeps the metadata of the image, with the dimensions of the region
+    ///
+    /// # Errors
+    /// If the image has an unsupported bit depth
+    pub fn to_image(&self) -> Result<Image, ImageErrors> {
+        let (image_width, _) = self.image.dimensions();
+        let bit_type = self.image.depth().bit_type();
+        let samples = self.samples_per_pixel();
+
+        let mut frames = Vec::with_capacity(self.image.frames_len());
+
+        for frame in self.image.frames_ref() {
+            let mut channels = vec![];
+
+            for channel in frame.channels_vec_ref() {
+                let copied = match bit_type {
+                    BitType::U8 => self.copy_out::<u8>(channel, image_width, samples)?,
+                    BitType::U16 => self.copy_out::<u16>(channel, image_width, samples)?,
+                    BitType::F32 => self.copy_out::<f32>(channel, image_width, samples)?,
+                    depth => return Err(ImageErrors::ImageOperationNotImplemented("region", depth))
+                };
+                channels.push(copied);
+            }
+            frames.push(frame.with_channels(channels));
+        }
+        let mut image = Image::new_frames(
+            frames,
+            self.image.depth(),
+            self.width,
+            self.height,
+            self.image.colorspace()
+        );
+        image.metadata = self.image.metadata.clone();
+        image.metadata.set_dimensions(self.width, self.height);
+
+        Ok(image)
+    }
+
+    /// Write an image over the region
+    ///
+    /// The image is converted to the colorspace and bit depth of the image the region
+    /// belongs to if needed
+    ///
+    /// # Errors
+    /// If the dimensions or the number of frames don't match the region
+    pub fn paste(&mut self, image: &Image) -> Result<(), ImageErrors> {
+        if image.dimensions() != (self.width, self.height)
+            || image.frames_len() != self.image.frames_len()
+        {
+            let msg = format!(
+                "Cannot paste a {:?} image with {} frames over a {}x{} region with {} frames",
+                image.dimensions(),
+                image.frames_len(),
+                self.width,
+                self.height,
+                self.image.frames_len()
+            );
+            return Err(ImageErrors::GenericString(msg));
+        }
+        let mut converted;
+        let mut image = image;
+
+        if image.colorspace() != self.image.colorspace()
+            || image.depth() != self.image.depth()
+            || image.layout() != self.image.layout()
+        {
+            converted = image.clone();
+            converted.convert_color(self.image.colorspace())?;
+            converted.convert_depth(self.image.depth())?;
+            converted.convert_layout(self.image.layout())?;
+            image = &converted;
+        }
+        // interleaved rows hold every sample of a pixel
+        let samples = self.samples_per_pixel();
+        let image_width = self.image.dimensions().0 * samples;
+        let bit_type = self.image.depth().bit_type();
+        let (x, y, width, height) = (self.x * samples, self.y, self.width * samples, self.height);
+
+        for (frame, source) in self.image.frames_mut().iter_mut().zip(image.frames_ref()) {
+            for (channel, source) in frame
+                .channels_vec()
+                .iter_mut()
+                .zip(source.channels_vec_ref())
+            {
+                match bit_type {
+                    BitType::U8 => {
+                        copy_in::<u8>(channel, source, image_width, x, y, width, height)?
+                    }
+                    BitType::U16 => {
+                        copy_in::<u16>(channel, source, image_width, x, y, width, height)?;
+                    }
+                    BitType::F32 => {
+                        copy_in::<f32>(channel, source, image_width, x, y, width, height)?;
+                    }
+                    depth => return Err(ImageErrors::ImageOperationNotImplemented("region", depth))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Get strided views of the channels of a frame of the region
+    ///
+    /// The views borrow the pixels of the image, writes to them change the image
+    /// directly. Planar images return one view per channel in colorspace order,
+    /// interleaved images a single view holding all samples.
+    ///
+    /// # Errors
+    /// If `frame` doesn't exist or `T` isn't the type of the image depth
+    pub fn channels_mut<T: Pod + 'static>(
+        &mut self, frame: usize
+    ) -> Result<Vec<RegionRows<'_, T>>, ImageErrors> {
+        let samples = self.samples_per_pixel();
+        let stride = self.image.dimensions().0 * samples;
+        let (x, y, width, height) = (self.x * samples, self.y, self.width * samples, self.height);
+
+        let frames_len = self.image.frames_len();
+        let frame = self.image.frames_mut().get_mut(frame).ok_or_else(|| {
+            ImageErrors::GenericString(format!(
+                "Frame {frame} is outside the image with {frames_len} frames"
+            ))
+        })?;
+
+        let mut views = vec![];
+
+        for channel in frame.channels_vec() {
+            let data = channel.reinterpret_as_mut::<T>()?;
+            let start = y * stride + x;
+            let end = start + (height - 1) * stride + width;
+
+            views.push(RegionRows {
+                data: &mut data[start..end],
+                stride,
+                width,
+                height
+            });
+        }
+        Ok(views)
+    }
+
+    /// Modify the pixels of the region in place using function `func`
+    ///
+    /// This works like [`Image::modify_pixels_mut`] on every frame, without copying
+    /// the region, `y` and `x` are relative to the top left corner of the region
+    ///
+    /// # Errors
+    /// If `T` isn't the type of the image depth
+    pub fn modify_pixels_mut<T, F>(&mut self, func: F) -> Result<(), ImageErrors>
+    where
+        T: Pod + Default + 'static,
+        F: Fn(usize, usize, [&mut T; MAX_CHANNELS])
+    {
+        let samples = self.samples_per_pixel();
+        let interleaved = self.image.layout() == PixelLayout::Interleaved;
+        let (width, height) = self.dimensions();
+
+        for frame in 0..self.image.frames_len() {
+            let mut channels = self.channels_mut::<T>(frame)?;
+
+            for y in 0..height {
+                let mut rows: Vec<&mut [T]> = channels.iter_mut().map(|c| c.row_mut(y)).collect();
+
+                if interleaved {
+                    for (x, pixel) in rows[0].chunks_exact_mut(samples).enumerate() {
+                        let mut output: [&mut T; MAX_CHANNELS] = [
+                            &mut T::default(),
+                            &mut T::default(),
+                            &mut T::default(),
+                            &mut T::default()
+                        ];
+                        for (i, j) in pixel.iter_mut().zip(output.iter_mut()) {
+                            *j = i;
+                        }
+                        (func)(y, x, output);
+                    }
+                    continue;
+                }
+                for x in 0..width {
+                    let mut output: [&mut T; MAX_CHANNELS] = [
+                        &mut T::default(),
+                        &mut T::default(),
+                        &mut T::default(),
+                        &mut T::default()
+                    ];
+                    for (i, j) in rows.iter_mut().zip(output.iter_mut()) {
+                        *j = &mut i[x];
+                    }
+                    (func)(y, x, output);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Execute an operation on a copy of the region and paste the result back
+    ///
+    /// See [running operations](self#running-operations). Operations that change the
+    /// dimensions of the image can't be used, operations that change its colorspace or
+    /// depth are converted back
+    ///
+    /// # Errors
+    /// Errors from the operation, or if it changed the dimensions
+    pub fn execute_copy(&mut self, operation: &dyn OperationsTrait) -> Result<(), ImageErrors> {
+        self.modify_copy(|image| operation.execute(image))
+    }
+
+    /// Run a function on a copy of the region and paste the result back
+    ///
+    /// To modify the pixels without copying, see [`modify_pixels_mut`](Self::modify_pixels_mut)
+    ///
+    /// # Errors
+    /// Errors from the function, or if it changed the dimensions
+    pub fn modify_copy<E>(
+        &mut self, function: impl FnOnce(&mut Image) -> Result<(), E>
+    ) -> Result<(), ImageErrors>
+    where
+        ImageErrors: From<E>
+    {
+        let mut region = self.to_image()?;
+        function(&mut region)?;
+        self.paste(&region)
+    }
+
+    /// Number of samples a pixel takes in a channel
+    fn samples_per_pixel(&self) -> usize {
+        match self.image.layout() {
+            PixelLayout::Planar => 1,
+            PixelLayout::Interleaved => self.image.colorspace().num_components()
+        }
+    }
+
+    fn copy_out<T: Pod + Default + 'static>(
+        &self, channel: &Channel, image_width: usize, samples: usize
+    ) -> Result<Channel, ImageErrors> {
+        let data = channel.reinterpret_as::<T>()?;
+        let (x, width) = (self.x * samples, self.width * samples);
+        let mut out = Channel::new_with_capacity::<T>(width * self.height * size_of::<T>());
+
+        for row in data
+            .chunks_exact(image_width * samples)
+            .skip(self.y)
+            .take(self.height)
+        {
+            out.extend(&row[x..x + width]);
+        }
+        Ok(out)
+    }
+}
+
+fn copy_in<T: Pod + Default + 'static>(
+    channel: &mut Channel, source: &Channel, image_width: usize, x: usize, y: usize, width: usize,
+    height: usize
+) -> Result<(), ImageErrors> {
+    let source = source.reinterpret_as::<T>()?;
+    let data = channel.reinterpret_as_mut::<T>()?;
+
+    for (row, source) in data
+        .chunks_exact_mut(image_width)
+        .skip(y)
+        .take(height)
+        .zip(source.chunks_exact(width))
+    {
+        row[x..x + width].copy_from_slice(source);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::image::Image;
+    use crate::metadata::PixelLayout;
+
+    #[test]
+    fn test_region_round_trip() {
+        let pixels: Vec<u16> = (0..8 * 6 * 3).collect();
+        let mut image = Image::from_u16(&pixels, 8, 6, ColorSpace::RGB);
+
+        let mut region = image.region_mut(2, 1, 3, 4).unwrap();
+        let copy = region.to_image().unwrap();
+        assert_eq!(copy.dimensions(), (3, 4));
+        // top left pixel of the region
+        assert_eq!(copy.flatten_frames::<u16>()[0][..3], [30, 31, 32]);
+
+        region.paste(&copy).unwrap();
+        assert_eq!(image.flatten_frames::<u16>()[0], pixels);
+    }
+
+    #[test]
+    fn test_only_region_is_modified() {
+        let mut image = Image::fill(0_u8, ColorSpace::Luma, 6, 6);
+
+        image
+            .region_mut(1, 2, 2, 3)
+            .unwrap()
+            .modify_copy(|region| region.modify_pixels_mut(|_, _, pix: [&mut u8; 4]| *pix[0] = 1))
+            .unwrap();
+
+        let pixels = &image.flatten_frames::<u8>()[0];
+        for y in 0..6 {
+            for x in 0..6 {
+                let inside = (1..3).contains(&x) && (2..5).contains(&y);
+                assert_eq!(pixels[y * 6 + x], u8::from(inside));
+            }
+        }
+        assert!(image.region_mut(4, 4, 3, 1).is_err());
+    }
+
+    #[test]
+    fn test_views_modify_in_place() {
+        let pixels: Vec<u8> = (0..5 * 4 * 3).map(|x| x as u8).collect();
+        let mut planar = Image::from_u8(&pixels, 5, 4, ColorSpace::RGB);
+        planar.convert_layout(PixelLayout::Planar).unwrap();
+        let mut interleaved = planar.clone();
+        interleaved.convert_layout(PixelLayout::Interleaved).unwrap();
+
+        for image in [&mut interleaved, &mut planar] {
+            let mut region = image.region_mut(1, 2, 3, 2).unwrap();
+
+            region
+                .modify_pixels_mut(|y, x, pix: [&mut u8; 4]| *pix[1] = (10 * y + x) as u8)
+                .unwrap();
+            assert!(region.channels_mut::<u16>(0).is_err());
+            assert!(region.channels_mut::<u8>(1).is_err());
+
+            let image = region.to_image().unwrap();
+            let copied = image.flatten_frames::<u8>();
+            // green is set, the other channels keep their values
+            assert_eq!(copied[0][..6], [33, 0, 35, 36, 1, 38]);
+            assert_eq!(copied[0][9..12], [48, 10, 50]);
+        }
+        assert!(interleaved.flatten_frames::<u8>() == planar.flatten_frames::<u8>());
+
+        let mut region = interleaved.region_mut(4, 3, 1, 1).unwrap();
+        let mut channels = region.channels_mut::<u8>(0).unwrap();
+        assert_eq!(channels[0].dimensions(), (3, 1));
+        channels[0].row_mut(0).copy_from_slice(&[1, 2, 3]);
+
+        assert_eq!(interleaved.flatten_frames::<u8>()[0][57..], [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_converted_results_are_pasted() {
+        let mut image = Image::fill(100_u8, ColorSpace::RGB, 4, 4);
+
+        image
+            .region_mut(0, 0, 2, 2)
+            .unwrap()
+            .modify_copy(|region| region.convert_color(ColorSpace::Luma))
+            .unwrap();
+
+        assert_eq!(image.colorspace(), ColorSpace::RGB);
+        assert_eq!(image.flatten_frames::<u8>()[0][..3], [100, 100, 100]);
+    }
+}
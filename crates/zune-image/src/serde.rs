@@ -7,21 +7,28 @@
  */
 
 #![cfg(feature = "serde-support")]
+//! Serde support for image metadata
+//!
+//! Metadata is serialized with exif and iptc as text, which is good for
+//! displaying them but can't be turned back into the original records, so deserializing
+//! ignores them. The other fields round trip.
 
 use std::collections::BTreeMap;
+use std::fmt;
 
+use serde::de::{self, MapAccess, Visitor};
 use serde::ser::SerializeStruct;
-use serde::{Serialize, Serializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::codecs::ImageFormat;
-use crate::metadata::ImageMetadata;
+use crate::metadata::{ImageMetadata, Resolution, ResolutionUnit};
 
 impl Serialize for ImageMetadata {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer
     {
-        const STRUCT_FIELDS: usize = 7;
+        const STRUCT_FIELDS: usize = 13;
         let mut state = serializer.serialize_struct("Metadata", STRUCT_FIELDS)?;
 
         state.serialize_field("width", &self.width)?;
@@ -31,6 +38,25 @@ impl Serialize for ImageMetadata {
         state.serialize_field("format", &self.format)?;
         state.serialize_field("color_transfer_characteristics", &self.color_trc)?;
         state.serialize_field("gamma_value", &self.default_gamma)?;
+        state.serialize_field("white_point", &self.white_point)?;
+        state.serialize_field("primaries", &self.primaries)?;
+        state.serialize_field("loop_count", &self.loop_count)?;
+        state.serialize_field("xmp", &self.xmp)?;
+        state.serialize_field("dpi", &self.resolution.and_then(Resolution::dpi))?;
+
+        // iptc datasets as text, keyed by record and dataset number
+        let iptc = self.iptc.as_ref().map(|iptc| {
+            let mut fields: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+            for dataset in iptc.datasets() {
+                fields
+                    .entry(format!("{}:{}", dataset.record, dataset.dataset))
+                    .or_default()
+                    .push(String::from_utf8_lossy(&dataset.data).into_owned());
+            }
+            fields
+        });
+        state.serialize_field("iptc", &iptc)?;
 
         #[cfg(feature = "metadata")]
         {
@@ -75,3 +101,113 @@ impl Serialize for ImageFormat {
         serializer.serialize_str(&format!("{:?}", self))
     }
 }
+
+impl<'de> Deserialize<'de> for ImageFormat {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>
+    {
+        const VARIANTS: &[&str] = &[
+            "JPEG", "PNG", "PPM", "PSD", "Farbfeld", "QOI", "JPEG_XL", "HDR", "BMP", "Unknown"
+        ];
+
+        struct FormatVisitor;
+
+        impl<'de> Visitor<'de> for FormatVisitor {
+            type Value = ImageFormat;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an image format name")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error
+            {
+                match v {
+                    "JPEG" => Ok(ImageFormat::JPEG),
+                    "PNG" => Ok(ImageFormat::PNG),
+                    "PPM" => Ok(ImageFormat::PPM),
+                    "PSD" => Ok(ImageFormat::PSD),
+                    "Farbfeld" => Ok(ImageFormat::Farbfeld),
+                    "QOI" => Ok(ImageFormat::QOI),
+                    "JPEG_XL" => Ok(ImageFormat::JPEG_XL),
+                    "HDR" => Ok(ImageFormat::HDR),
+                    "BMP" => Ok(ImageFormat::BMP),
+                    "Unknown" => Ok(ImageFormat::Unknown),
+                    _ => Err(E::unknown_variant(v, VARIANTS))
+                }
+            }
+        }
+        deserializer.deserialize_str(FormatVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for ImageMetadata {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>
+    {
+        const FIELDS: &[&str] = &[
+            "width",
+            "height",
+            "colorspace",
+            "depth",
+            "format",
+            "color_transfer_characteristics",
+            "gamma_value",
+            "white_point",
+            "primaries",
+            "loop_count",
+            "xmp",
+            "dpi",
+            "iptc",
+            "exif"
+        ];
+
+        struct MetadataVisitor;
+
+        impl<'de> Visitor<'de> for MetadataVisitor {
+            type Value = ImageMetadata;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("image metadata")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>
+            {
+                let mut metadata = ImageMetadata::default();
+
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "width" => metadata.width = map.next_value()?,
+                        "height" => metadata.height = map.next_value()?,
+                        "colorspace" => metadata.colorspace = map.next_value()?,
+                        "depth" => metadata.depth = map.next_value()?,
+                        "format" => metadata.format = map.next_value()?,
+                        "color_transfer_characteristics" => metadata.color_trc = map.next_value()?,
+                        "gamma_value" => metadata.default_gamma = map.next_value()?,
+                        "white_point" => metadata.white_point = map.next_value()?,
+                        "primaries" => metadata.primaries = map.next_value()?,
+                        "loop_count" => metadata.loop_count = map.next_value()?,
+                        "xmp" => metadata.xmp = map.next_value()?,
+                        "dpi" => {
+                            let dpi: Option<(f32, f32)> = map.next_value()?;
+                            metadata.resolution =
+                                dpi.map(|(x, y)| Resolution::new(x, y, ResolutionUnit::Inch));
+                        }
+                        // stored as text, which can't be turned back into records
+                        "iptc" | "exif" => {
+                            map.next_value::<de::IgnoredAny>()?;
+                        }
+                        _ => return Err(de::Error::unknown_field(&key, FIELDS))
+                    }
+                }
+                Ok(metadata)
+            }
+        }
+        deserializer.deserialize_struct("Metadata", FIELDS, MetadataVisitor)
+    }
+}
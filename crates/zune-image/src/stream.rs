@@ -0,0 +1,229 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Frame by frame decoding
+//!
+//! Decoding an animated image with [`Image::read`] keeps every frame in memory, which
+//! for long animations quickly adds up. [`FrameStream`] instead yields frames one at a time,
+//! so an animation can be processed or transcoded with only the frame being worked on and
+//! the state the decoder needs to compose the next one resident.
+//!
+//! Decoders that can't decode frames one at a time, see
+//! [`DecoderTrait::supports_frame_streaming`], decode the whole image on the first call
+//! and their frames are returned from memory.
+//!
+//! # Example
+//! ```no_run
+//! use zune_core::bytestream::ZCursor;
+//! use zune_core::options::DecoderOptions;
+//! use zune_image::stream::FrameStream;
+//!
+//! let data = std::fs::read("animation.png").unwrap();
+//! let mut stream = FrameStream::from_source(ZCursor::new(data), DecoderOptions::default()).unwrap();
+//!
+//! let (width, height) = stream.metadata().dimensions();
+//!
+//! for frame in stream {
+//!     let frame = frame.unwrap();
+//!     // process the frame, only it is kept in memory
+//! }
+//! ```
+//!
+//! [`Image::read`]: crate::image::Image::read
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::vec;
+use core::cell::Cell;
+
+use zune_core::bytestream::ZByteReaderTrait;
+use zune_core::options::DecoderOptions;
+
+use crate::codecs::ImageFormat;
+use crate::errors::ImageErrors;
+use crate::frame::Frame;
+use crate::image::Image;
+use crate::limits;
+use crate::metadata::{ImageMetadata, PixelLayout};
+use crate::position::Tracked;
+#[cfg(feature = "std")]
+use crate::progress::check_cancelled;
+use crate::traits::DecoderTrait;
+
+/// An iterator decoding the frames of an image one at a time
+///
+/// Created by [`FrameStream::new`] from a decoder or [`FrameStream::from_source`],
+/// frames are planar
+pub struct FrameStream<'a> {
+    decoder:   Box<dyn DecoderTrait + 'a>,
+    metadata:  ImageMetadata,
+    streaming: bool,
+    /// Frames of a decoder that decoded the whole image
+    decoded:   Option<alloc::vec::IntoIter<Frame>>,
+    finished:  bool,
+    /// Limits to enforce and the number of frames returned so far
+    limits:    Option<DecoderOptions>,
+    frames:    usize,
+    /// Position of the decoder in its source, for errors of streams from a source
+    position:  Option<Rc<Cell<u64>>>
+}
+
+impl<'a> FrameStream<'a> {
+    /// Create a stream of the frames of an image from a decoder
+    ///
+    /// The headers are decoded immediately
+    ///
+    /// # Errors
+    /// Errors from decoding the headers
+    pub fn new(mut decoder: Box<dyn DecoderTrait + 'a>) -> Result<FrameStream<'a>, ImageErrors> {
+        let metadata = match decoder.read_headers()? {
+            Some(metadata) => metadata,
+            None => {
+                let (width, height) = decoder.dimensions().unwrap_or((0, 0));
+                let mut metadata = ImageMetadata::default();
+
+                metadata.set_dimensions(width, height);
+                metadata.set_colorspace(decoder.out_colorspace());
+                metadata
+            }
+        };
+        let streaming = decoder.supports_frame_streaming();
+
+        Ok(FrameStream {
+            decoder,
+            metadata,
+            streaming,
+            decoded: None,
+            finished: false,
+            limits: None,
+            frames: 0,
+            position: None
+        })
+    }
+
+    /// Create a stream of the frames of an image in memory, guessing the format
+    /// from its contents
+    ///
+    /// The limits of `options` are enforced for every frame
+    ///
+    /// # Errors
+    /// If the format isn't known or has no decoder, errors from decoding the headers
+    /// and [`ImageErrors::LimitsExceeded`] if the image is too large
+    pub fn from_source<T>(src: T, options: DecoderOptions) -> Result<FrameStream<'a>, ImageErrors>
+    where
+        T: ZByteReaderTrait + 'a
+    {
+        match ImageFormat::guess_format(src) {
+            Some((format, src)) => {
+                let (src, position) = Tracked::new(src);
+                let at_position = |e: ImageErrors| e.at_offset(position.get());
+
+                let decoder = format
+                    .decoder_with_options(src, options)
+                    .map_err(at_position)?;
+                let mut stream = FrameStream::new(decoder).map_err(at_position)?;
+                stream.metadata.format = Some(format);
+                stream.position = Some(position.clone());
+
+                limits::check_headers(&stream.metadata, &options)?;
+                stream.limits = Some(options);
+
+                Ok(stream)
+            }
+            None => Err(ImageErrors::ImageDecoderNotImplemented(
+                ImageFormat::Unknown
+            ))
+        }
+    }
+
+    /// The metadata of the image, frames have its dimensions, colorspace and depth
+    ///
+    /// For decoders that can't decode frames one at a time this is updated
+    /// with the metadata of the decoded image once the first frame is returned
+    pub const fn metadata(&self) -> &ImageMetadata {
+        &self.metadata
+    }
+
+    /// Return true if frames are decoded one at a time, false if the
+    /// decoder decodes the whole image on the first frame
+    pub const fn is_streaming(&self) -> bool {
+        self.streaming
+    }
+
+    /// Decode the remaining frames into an image
+    ///
+    /// # Errors
+    /// Errors from decoding the frames, or if no frames are left
+    pub fn collect_image(mut self) -> Result<Image, ImageErrors> {
+        let mut frames = vec![];
+
+        while let Some(frame) = self.next_frame()? {
+            frames.push(frame);
+        }
+        if frames.is_empty() {
+            return Err(ImageErrors::GenericStr("No frames left in the stream"));
+        }
+        let mut image = Image::new_frames(
+            frames,
+            self.metadata.depth(),
+            self.metadata.width,
+            self.metadata.height,
+            self.metadata.colorspace()
+        );
+        image.metadata = self.metadata;
+
+        Ok(image)
+    }
+
+    fn next_frame(&mut self) -> Result<Option<Frame>, ImageErrors> {
+        if self.finished {
+            return Ok(None);
+        }
+        #[cfg(feature = "std")]
+        check_cancelled()?;
+
+        let frame = self.decode_frame().map_err(|e| match &self.position {
+            Some(position) => e.at_offset(position.get()),
+            None => e
+        })?;
+
+        if frame.is_some() {
+            self.frames += 1;
+
+            if let Some(options) = &self.limits {
+                limits::check_frames(&self.metadata, self.frames, options)?;
+            }
+        }
+        Ok(frame)
+    }
+
+    fn decode_frame(&mut self) -> Result<Option<Frame>, ImageErrors> {
+        if self.streaming {
+            return self.decoder.next_frame();
+        }
+        if self.decoded.is_none() {
+            let format = self.metadata.format;
+            let mut image = self.decoder.decode_with_layout(PixelLayout::Planar)?;
+
+            image.metadata.format = image.metadata.format.or(format);
+            self.metadata = image.metadata;
+            self.decoded = Some(image.frames.into_iter());
+        }
+        Ok(self.decoded.as_mut().and_then(Iterator::next))
+    }
+}
+
+impl Iterator for FrameStream<'_> {
+    type Item = Result<Frame, ImageErrors>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let frame = self.next_frame();
+        // don't decode further after the last frame or an error
+        self.finished = !matches!(frame, Ok(Some(_)));
+
+        frame.transpose()
+    }
+}
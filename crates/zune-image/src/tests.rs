@@ -44,5 +44,653 @@ fn test_fractal() {
             *px[1] = i as u8;
         })
         .unwrap();
-    image.save_to("a.ppm", ImageFormat::PPM).unwrap()
+    image
+        .save_to(std::env::temp_dir().join("a.ppm"), ImageFormat::PPM)
+        .unwrap()
+}
+
+#[test]
+fn test_pixel_accessors() {
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::image::Image;
+
+    let mut image = Image::fill(1000_u16, ColorSpace::LumaA, 5, 3);
+
+    image.set_pixel(4, 2, &[7_u16, 8]).unwrap();
+    assert_eq!(image.pixel_at::<u16>(4, 2).unwrap(), [7, 8, 0, 0]);
+    // wrong type and out of bounds
+    assert!(image.pixel_at::<u8>(4, 2).is_err());
+    assert!(image.set_pixel(5, 0, &[1_u16]).is_err());
+
+    unsafe {
+        image.set_pixel_unchecked(0, 1, &[9_u16]);
+        assert_eq!(image.pixel_at_unchecked::<u16>(0, 1), [9, 1000, 0, 0]);
+    }
+}
+
+#[test]
+#[cfg(feature = "ppm")]
+fn test_interleaved_layout() {
+    use zune_core::bit_depth::BitDepth;
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::DecoderOptions;
+
+    use crate::image::Image;
+    use crate::metadata::PixelLayout;
+
+    let pixels: Vec<u8> = (0..4 * 3 * 3).map(|x| x * 7).collect();
+    let planar = Image::from_u8(&pixels, 4, 3, ColorSpace::RGB);
+    let encoded = planar.write_to_vec(ImageFormat::PPM).unwrap();
+
+    let mut image = Image::read_with_layout(
+        ZCursor::new(&encoded),
+        DecoderOptions::default(),
+        PixelLayout::Interleaved
+    )
+    .unwrap();
+    assert_eq!(image.layout(), PixelLayout::Interleaved);
+    assert_eq!(image.frames_ref()[0].channels_vec_ref().len(), 1);
+    assert_eq!(image.flatten_frames::<u8>()[0], pixels);
+    assert_eq!(image.write_to_vec(ImageFormat::PPM).unwrap(), encoded);
+
+    image.set_pixel(1, 2, &[1_u8, 2, 3]).unwrap();
+    assert_eq!(image.pixel_at::<u8>(1, 2).unwrap(), [1, 2, 3, 0]);
+
+    let region = image.region_mut(1, 1, 2, 2).unwrap().to_image().unwrap();
+    assert_eq!(region.flatten_frames::<u8>()[0][6..9], [1, 2, 3]);
+
+    // rounding depth conversions work per sample and keep the layout
+    image.convert_depth(BitDepth::Sixteen).unwrap();
+    assert_eq!(image.layout(), PixelLayout::Interleaved);
+
+    // colorspace conversion needs planar channels
+    image.convert_color(ColorSpace::Luma).unwrap();
+    assert_eq!(image.layout(), PixelLayout::Planar);
+
+    let mut back = planar.clone();
+    back.convert_layout(PixelLayout::Interleaved).unwrap();
+    back.convert_layout(PixelLayout::Planar).unwrap();
+    assert!(back == planar);
+}
+
+#[test]
+fn test_frame_api() {
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::frame::{FrameBlend, FrameDisposal};
+    use crate::image::Image;
+
+    let mut image = Image::fill(10_u8, ColorSpace::RGB, 4, 4);
+    image.metadata_mut().set_loop_count(3);
+
+    for (duration, disposal) in [(2, FrameDisposal::Background), (5, FrameDisposal::Previous)] {
+        let mut frame = image.frames_ref()[0].clone();
+        frame.set_duration(duration, 10);
+        frame.set_disposal(disposal);
+        frame.set_blend(FrameBlend::Over);
+        image.push_frame(frame).unwrap();
+    }
+    image.swap_frames(0, 2).unwrap();
+    image.reorder_frames(&[1, 0, 2]).unwrap();
+    assert!(image.reorder_frames(&[0, 0, 1]).is_err());
+
+    let durations: Vec<_> = image.frames_ref().iter().map(|x| x.duration()).collect();
+    assert_eq!(durations, [(2, 10), (5, 10), (1, 1)]);
+    assert!((image.total_duration_secs() - 1.7).abs() < 1e-9);
+
+    // frames keep their timing through operations
+    image.convert_color(ColorSpace::Luma).unwrap();
+    let frame = &image.frames_ref()[1];
+    assert_eq!(frame.duration(), (5, 10));
+    assert_eq!(frame.disposal(), FrameDisposal::Previous);
+    assert_eq!(frame.blend(), FrameBlend::Over);
+    assert_eq!(image.metadata().loop_count(), 3);
+
+    // frames must match the image
+    let rgb = Image::fill(10_u8, ColorSpace::RGB, 4, 4);
+    assert!(image.insert_frame(0, rgb.frames_ref()[0].clone()).is_err());
+
+    let removed = image.remove_frame(0).unwrap();
+    assert_eq!(removed.duration(), (2, 10));
+    assert_eq!(image.frames_len(), 2);
+}
+
+#[test]
+#[cfg(all(feature = "png", feature = "ppm"))]
+fn test_frame_stream() {
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::DecoderOptions;
+
+    use crate::image::Image;
+    use crate::stream::FrameStream;
+
+    let data = include_bytes!("../../zune-png/tests/random/animated_ball.png");
+
+    let image = Image::read(ZCursor::new(data), DecoderOptions::default()).unwrap();
+    let stream = FrameStream::from_source(ZCursor::new(data), DecoderOptions::default()).unwrap();
+    assert!(stream.is_streaming());
+    assert_eq!(stream.metadata().dimensions(), image.dimensions());
+
+    let frames: Vec<_> = stream.map(Result::unwrap).collect();
+    assert!(frames.len() > 1);
+    assert!(frames.iter().eq(image.frames_ref()));
+
+    // decoders without streaming support return their frames after decoding
+    let still = Image::fill(3_u8, ColorSpace::RGB, 5, 5);
+    let encoded = still.write_to_vec(ImageFormat::PPM).unwrap();
+    let mut stream =
+        FrameStream::from_source(ZCursor::new(&encoded), DecoderOptions::default()).unwrap();
+    assert!(!stream.is_streaming());
+    assert!(stream.next().unwrap().unwrap() == still.frames_ref()[0]);
+    assert!(stream.next().is_none());
+}
+
+#[test]
+#[cfg(all(feature = "jpeg", feature = "metadata"))]
+fn test_exif_round_trip() {
+    use exif::{Field, In, Tag, Value};
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::DecoderOptions;
+
+    use crate::image::Image;
+    use crate::metadata::{ExifOrientation, KeepMetadata};
+
+    let mut image = Image::fill(128_u8, ColorSpace::RGB, 16, 16);
+    let metadata = image.metadata_mut();
+    metadata.set_orientation(ExifOrientation::Rotate270);
+    for (tag, text) in [(Tag::Model, "Camera"), (Tag::Copyright, "Someone")] {
+        metadata.set_exif_field(Field {
+            tag,
+            ifd_num: In::PRIMARY,
+            value: Value::Ascii(vec![text.as_bytes().to_vec()])
+        });
+    }
+
+    let encoded = image.write_to_vec(ImageFormat::JPEG).unwrap();
+    let decoded = Image::read(ZCursor::new(&encoded), DecoderOptions::default()).unwrap();
+    let exif = decoded.metadata().exif_data().unwrap();
+    assert_eq!(exif.orientation, Some(ExifOrientation::Rotate270));
+    assert_eq!(exif.model.as_deref(), Some("Camera"));
+
+    let mut stripped = decoded.clone();
+    stripped
+        .metadata_mut()
+        .strip_metadata_keeping(KeepMetadata {
+            orientation: true,
+            copyright:   false
+        });
+    let exif = stripped.metadata().exif_data().unwrap();
+    assert_eq!(exif.orientation, Some(ExifOrientation::Rotate270));
+    assert_eq!(exif.model, None);
+
+    stripped.strip_metadata();
+    let encoded = stripped.write_to_vec(ImageFormat::JPEG).unwrap();
+    let decoded = Image::read(ZCursor::new(&encoded), DecoderOptions::default()).unwrap();
+    assert!(decoded.metadata().exif().is_none());
+}
+
+#[test]
+#[cfg(all(feature = "png", feature = "metadata"))]
+fn test_auto_orient_on_decode() {
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::DecoderOptions;
+
+    use crate::image::Image;
+    use crate::metadata::ExifOrientation;
+
+    // 3x2 image stored rotated, the top left pixel should end up in the top right
+    let mut image = Image::from_u8(&[9, 0, 0, 0, 0, 0], 3, 2, ColorSpace::Luma);
+    image
+        .metadata_mut()
+        .set_orientation(ExifOrientation::Rotate90);
+    let encoded = image.write_to_vec(ImageFormat::PNG).unwrap();
+
+    let decoded = Image::read(ZCursor::new(&encoded), DecoderOptions::default()).unwrap();
+    assert_eq!(decoded.dimensions(), (3, 2));
+
+    let options = DecoderOptions::default().set_auto_orient(true);
+    let decoded = Image::read(ZCursor::new(&encoded), options).unwrap();
+    assert_eq!(decoded.dimensions(), (2, 3));
+    assert_eq!(decoded.flatten_frames::<u8>()[0], [0, 9, 0, 0, 0, 0]);
+    assert_eq!(
+        decoded.metadata().orientation(),
+        Some(ExifOrientation::Normal)
+    );
+}
+
+#[test]
+#[cfg(all(feature = "jpeg", feature = "png"))]
+fn test_xmp_iptc_round_trip() {
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::DecoderOptions;
+
+    use crate::image::Image;
+    use crate::metadata::{IptcData, KeepMetadata};
+
+    let xmp = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF/></x:xmpmeta>"#;
+
+    let mut image = Image::fill(20_u8, ColorSpace::RGB, 8, 8);
+    image.metadata_mut().set_xmp(xmp.to_string());
+    let iptc = image.metadata_mut().iptc_mut();
+    iptc.set(IptcData::CAPTION, "Caption");
+    iptc.add(IptcData::KEYWORDS, "one");
+    iptc.add(IptcData::KEYWORDS, "two");
+    iptc.set(IptcData::COPYRIGHT, "Someone");
+
+    let encoded = image.write_to_vec(ImageFormat::JPEG).unwrap();
+    let decoded = Image::read(ZCursor::new(&encoded), DecoderOptions::default()).unwrap();
+    assert_eq!(decoded.metadata().xmp(), Some(xmp));
+    let iptc = decoded.metadata().iptc().unwrap();
+    assert_eq!(iptc.caption().as_deref(), Some("Caption"));
+    assert_eq!(iptc.keywords(), ["one", "two"]);
+
+    // png only carries xmp
+    let encoded = image.write_to_vec(ImageFormat::PNG).unwrap();
+    let decoded = Image::read(ZCursor::new(&encoded), DecoderOptions::default()).unwrap();
+    assert_eq!(decoded.metadata().xmp(), Some(xmp));
+
+    image.metadata_mut().strip_metadata_keeping(KeepMetadata {
+        orientation: false,
+        copyright:   true
+    });
+    assert!(image.metadata().xmp().is_none());
+    let iptc = image.metadata().iptc().unwrap();
+    assert_eq!(iptc.copyright().as_deref(), Some("Someone"));
+    assert!(iptc.caption().is_none());
+}
+
+#[test]
+#[cfg(all(feature = "jpeg", feature = "png"))]
+fn test_icc_profile_round_trip() {
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::DecoderOptions;
+
+    use crate::image::Image;
+
+    // only the header is looked at, a real profile would follow it
+    let mut profile = vec![0_u8; 128];
+    profile[16..20].copy_from_slice(b"RGB ");
+    profile.extend((0..70000).map(|x| (x % 251) as u8));
+
+    let mut image = Image::fill(128_u8, ColorSpace::RGB, 16, 16);
+    image.metadata_mut().set_icc_chunk(profile.clone());
+
+    // large profiles are split over multiple jpeg segments
+    for format in [ImageFormat::JPEG, ImageFormat::PNG] {
+        let encoded = image.write_to_vec(format).unwrap();
+        let decoded = Image::read(ZCursor::new(&encoded), DecoderOptions::default()).unwrap();
+        assert_eq!(decoded.metadata().icc_chunk(), Some(&profile), "{format:?}");
+    }
+
+    // the profile doesn't describe grayscale images
+    image.convert_color(ColorSpace::Luma).unwrap();
+    let encoded = image.write_to_vec(ImageFormat::PNG).unwrap();
+    let decoded = Image::read(ZCursor::new(&encoded), DecoderOptions::default()).unwrap();
+    assert!(decoded.metadata().icc_chunk().is_none());
+}
+
+#[test]
+#[cfg(all(feature = "jpeg", feature = "png"))]
+fn test_resolution_round_trip() {
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::DecoderOptions;
+
+    use crate::image::Image;
+    use crate::metadata::{Resolution, ResolutionUnit};
+
+    let mut image = Image::fill(128_u8, ColorSpace::RGB, 16, 16);
+    image
+        .metadata_mut()
+        .set_resolution(Resolution::from_dpi(300.0));
+
+    let encoded = image.write_to_vec(ImageFormat::JPEG).unwrap();
+    let decoded = Image::read(ZCursor::new(&encoded), DecoderOptions::default()).unwrap();
+    assert_eq!(
+        decoded.metadata().resolution(),
+        Some(Resolution::from_dpi(300.0))
+    );
+
+    // png stores pixels per meter
+    let encoded = image.write_to_vec(ImageFormat::PNG).unwrap();
+    let decoded = Image::read(ZCursor::new(&encoded), DecoderOptions::default()).unwrap();
+    let resolution = decoded.metadata().resolution().unwrap();
+    assert_eq!(resolution.unit, ResolutionUnit::Centimeter);
+    let (x, y) = resolution.dpi().unwrap();
+    assert!((x - 300.0).abs() < 0.1 && (y - 300.0).abs() < 0.1);
+
+    // images without a density don't get one
+    let encoded = Image::fill(0_u8, ColorSpace::RGB, 8, 8)
+        .write_to_vec(ImageFormat::JPEG)
+        .unwrap();
+    let decoded = Image::read(ZCursor::new(&encoded), DecoderOptions::default()).unwrap();
+    assert!(decoded.metadata().resolution().is_none());
+}
+
+#[test]
+fn test_apply_shares_untouched_channels() {
+    use zune_core::bit_depth::BitType;
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::errors::ImageErrors;
+    use crate::image::Image;
+    use crate::traits::OperationsTrait;
+
+    /// Sets the first channel to 255
+    struct FirstChannel;
+
+    impl OperationsTrait for FirstChannel {
+        fn name(&self) -> &'static str {
+            "First channel"
+        }
+        fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+            for frame in image.frames_mut() {
+                frame.channels_vec()[0].fill(255_u8)?;
+            }
+            Ok(())
+        }
+        fn supported_types(&self) -> &'static [BitType] {
+            &[BitType::U8]
+        }
+    }
+    let image = Image::from_u8(&[1_u8, 2, 3, 4, 5, 6], 2, 1, ColorSpace::RGB);
+    let output = FirstChannel.apply(&image).unwrap();
+
+    assert_eq!(image.flatten_frames::<u8>()[0], [1, 2, 3, 4, 5, 6]);
+    assert_eq!(output.flatten_frames::<u8>()[0], [255, 2, 3, 255, 5, 6]);
+
+    let channels = output.frames_ref()[0].channels_vec_ref();
+    assert!(!channels[0].is_shared() && channels[1].is_shared());
+}
+
+#[test]
+#[cfg(all(
+    feature = "png",
+    feature = "jpeg",
+    feature = "ppm",
+    feature = "qoi",
+    feature = "farbfeld"
+))]
+fn test_decode_from_stream() {
+    use std::io::Read;
+
+    use zune_core::bytestream::{ZCursor, ZStreamReader};
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::DecoderOptions;
+
+    use crate::image::Image;
+
+    /// A source that isn't `Seek` and returns a few bytes at a time, like a socket
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let bytes = buf.len().min(self.0.len()).min(3);
+            buf[..bytes].copy_from_slice(&self.0[..bytes]);
+            self.0 = &self.0[bytes..];
+            Ok(bytes)
+        }
+    }
+    let image = Image::from_fn(37, 23, ColorSpace::RGBA, |y, x, px| {
+        *px = [(x * 5) as u16, (y * 7) as u16, 40, 255];
+    });
+
+    for format in [
+        ImageFormat::PPM,
+        ImageFormat::QOI,
+        ImageFormat::PNG,
+        ImageFormat::JPEG,
+        ImageFormat::Farbfeld
+    ] {
+        let mut image = image.clone();
+        if format == ImageFormat::JPEG || format == ImageFormat::PPM {
+            image.convert_color(ColorSpace::RGB).unwrap();
+        }
+        let encoded = image.write_to_vec(format).unwrap();
+
+        let expected = Image::read(ZCursor::new(&encoded), DecoderOptions::default()).unwrap();
+        let decoded = Image::read(
+            ZStreamReader::with_history(Trickle(&encoded), 16),
+            DecoderOptions::default()
+        )
+        .unwrap();
+        assert!(decoded == expected, "{format:?}");
+    }
+}
+
+#[test]
+#[cfg(feature = "ppm")]
+fn test_open_file() {
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::image::Image;
+
+    let path = std::env::temp_dir().join(format!("zune-open-{}.ppm", std::process::id()));
+    let image = Image::fill(90_u8, ColorSpace::RGB, 6, 4);
+    image.save_to(&path, ImageFormat::PPM).unwrap();
+
+    let opened = Image::open(&path).unwrap();
+    assert!(opened.flatten_frames::<u8>() == image.flatten_frames::<u8>());
+
+    std::fs::write(&path, []).unwrap();
+    assert!(Image::open(&path).is_err());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+#[cfg(all(feature = "ppm", feature = "mmap", unix))]
+fn test_open_mmap() {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::DecoderOptions;
+
+    use crate::image::Image;
+
+    let dir = std::env::temp_dir();
+    let path = dir.join(format!("zune-mmap-{}.ppm", std::process::id()));
+    let image = Image::fill(90_u8, ColorSpace::RGB, 6, 4);
+    image.save_to(&path, ImageFormat::PPM).unwrap();
+    let encoded = std::fs::read(&path).unwrap();
+
+    // SAFETY: nothing else touches the file during the test
+    let opened = unsafe { Image::open_mmap(&path, DecoderOptions::default()).unwrap() };
+    assert!(opened.flatten_frames::<u8>() == image.flatten_frames::<u8>());
+
+    // empty files can't be mapped, they still have to fail cleanly
+    std::fs::write(&path, []).unwrap();
+    assert!(unsafe { Image::open_mmap(&path, DecoderOptions::default()) }.is_err());
+    std::fs::remove_file(&path).unwrap();
+
+    // pipes report a length of zero but still have to be read fully
+    let fifo = dir.join(format!("zune-mmap-{}.fifo", std::process::id()));
+    let c_path = CString::new(fifo.as_os_str().as_bytes()).unwrap();
+    assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+
+    let writer_path = fifo.clone();
+    let writer = std::thread::spawn(move || std::fs::write(writer_path, encoded).unwrap());
+    let opened = unsafe { Image::open_mmap(&fifo, DecoderOptions::default()).unwrap() };
+    writer.join().unwrap();
+    assert!(opened.flatten_frames::<u8>() == image.flatten_frames::<u8>());
+
+    std::fs::remove_file(&fifo).unwrap();
+}
+
+#[test]
+#[cfg(all(feature = "png", feature = "ppm"))]
+fn test_decoder_limits() {
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::DecoderOptions;
+
+    use crate::errors::ErrorKind;
+    use crate::image::Image;
+    use crate::stream::FrameStream;
+
+    let encoded = Image::fill(90_u8, ColorSpace::RGB, 20, 10)
+        .write_to_vec(ImageFormat::PPM)
+        .unwrap();
+    let read = |options| Image::read(ZCursor::new(&encoded), options);
+
+    assert!(read(DecoderOptions::default().set_max_pixels(200)).is_ok());
+
+    for options in [
+        DecoderOptions::default().set_max_width(19),
+        DecoderOptions::default().set_max_pixels(199),
+        DecoderOptions::default().set_max_memory(599)
+    ] {
+        match read(options) {
+            Err(err) => assert_eq!(err.kind(), ErrorKind::LimitExceeded),
+            Ok(_) => panic!("expected {options:?} to be exceeded")
+        }
+    }
+
+    // animated images are checked before any frame is decoded
+    let data = include_bytes!("../../zune-png/tests/random/animated_ball.png");
+    let options = DecoderOptions::default().set_max_frames(19);
+    assert!(Image::read(ZCursor::new(data), options).is_err());
+    assert!(FrameStream::from_source(ZCursor::new(data), options).is_err());
+
+    let options = DecoderOptions::default().set_max_frames(20);
+    assert_eq!(
+        Image::read(ZCursor::new(data), options)
+            .unwrap()
+            .frames_len(),
+        20
+    );
+}
+
+#[test]
+#[cfg(feature = "ppm")]
+fn test_structured_errors() {
+    use core::error::Error;
+
+    use zune_core::bit_depth::BitType;
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::DecoderOptions;
+
+    use crate::errors::{ErrorKind, ImageErrors};
+    use crate::image::Image;
+    use crate::traits::OperationsTrait;
+
+    // header of 11 bytes, then one of the 16 pixels
+    let Err(err) = Image::read(ZCursor::new(b"P5 4 4 255 9"), DecoderOptions::default()) else {
+        panic!("decoding a truncated image succeeded");
+    };
+    assert_eq!(err.kind(), ErrorKind::Corrupt);
+    assert_eq!(err.code(), "corrupt");
+    assert_eq!(err.format(), Some(ImageFormat::PPM));
+    assert!(err.offset().is_some_and(|x| x >= 11));
+    assert!(err.source().is_some());
+
+    let options = DecoderOptions::default().set_max_width(2);
+    let Err(err) = Image::read(ZCursor::new(b"P5 4 4 255 9"), options) else {
+        panic!("decoding past the limits succeeded");
+    };
+    assert_eq!(err.kind(), ErrorKind::LimitExceeded);
+
+    /// Always fails
+    struct Failing;
+
+    impl OperationsTrait for Failing {
+        fn name(&self) -> &'static str {
+            "Failing"
+        }
+        fn execute_impl(&self, _: &mut Image) -> Result<(), ImageErrors> {
+            Err(ImageErrors::GenericStr("failed"))
+        }
+        fn supported_types(&self) -> &'static [BitType] {
+            &[BitType::U8]
+        }
+    }
+    let mut image = Image::fill(0_u8, ColorSpace::Luma, 2, 2);
+    let err = Failing.execute(&mut image).unwrap_err();
+
+    assert_eq!(err.operation(), Some("Failing"));
+    assert_eq!(err.kind(), ErrorKind::Other);
+    assert!(matches!(
+        err.source().and_then(|x| x.downcast_ref::<ImageErrors>()),
+        Some(ImageErrors::GenericStr("failed"))
+    ));
+}
+
+#[test]
+#[cfg(all(feature = "png", feature = "jpeg"))]
+fn test_decode_into_image_reuses_channels() {
+    use zune_core::bit_depth::BitDepth;
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+    use zune_jpeg::JpegDecoder;
+    use zune_png::PngDecoder;
+
+    use crate::image::Image;
+    use crate::metadata::PixelLayout;
+    use crate::traits::DecoderTrait;
+
+    fn addresses(image: &Image) -> Vec<*const u8> {
+        let channels = image.frames_ref()[0].channels_vec_ref();
+        // Safety: only the address is used
+        channels
+            .iter()
+            .map(|x| unsafe { x.alias() }.as_ptr())
+            .collect()
+    }
+    let frame = |seed: u16| {
+        let pixels: Vec<u16> = (0..16 * 9 * 3).map(|x| x * 131 + seed).collect();
+        Image::from_u16(&pixels, 16, 9, ColorSpace::RGB)
+    };
+    let (first, second) = (frame(0), frame(7));
+
+    for layout in [PixelLayout::Planar, PixelLayout::Interleaved] {
+        for depth in [BitDepth::Eight, BitDepth::Sixteen] {
+            let (mut first, mut second) = (first.clone(), second.clone());
+            first.convert_depth(depth).unwrap();
+            second.convert_depth(depth).unwrap();
+
+            let first = first.write_to_vec(ImageFormat::PNG).unwrap();
+            let second = second.write_to_vec(ImageFormat::PNG).unwrap();
+
+            let mut image = PngDecoder::new(ZCursor::new(&first))
+                .decode_with_layout(layout)
+                .unwrap();
+            let before = addresses(&image);
+
+            PngDecoder::new(ZCursor::new(&second))
+                .decode_into_image(&mut image)
+                .unwrap();
+            let expected = PngDecoder::new(ZCursor::new(&second))
+                .decode_with_layout(layout)
+                .unwrap();
+
+            assert_eq!(addresses(&image), before);
+            assert_eq!(image.layout(), layout);
+            assert_eq!(image.depth(), depth);
+            assert!(image == expected);
+        }
+    }
+    // a different size can't reuse the channels
+    let jpeg = first.write_to_vec(ImageFormat::JPEG).unwrap();
+    let mut image = Image::fill(0_u8, ColorSpace::RGB, 3, 3);
+
+    JpegDecoder::new(ZCursor::new(&jpeg))
+        .decode_into_image(&mut image)
+        .unwrap();
+    let before = addresses(&image);
+    assert_eq!(image.dimensions(), (16, 9));
+
+    JpegDecoder::new(ZCursor::new(&jpeg))
+        .decode_into_image(&mut image)
+        .unwrap();
+    assert_eq!(addresses(&image), before);
+    assert!(image == DecoderTrait::decode(&mut JpegDecoder::new(ZCursor::new(&jpeg))).unwrap());
 }
@@ -0,0 +1,112 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! The thread pool parallel decoding, encoding and image operations run on
+//!
+//! By default operations split their work between the threads of rayon's global
+//! thread pool, which starts a thread per cpu core.
+//!
+//! Applications that already keep every core busy, e.g. a server decoding an image per
+//! request on its own threads, can limit the threads used with [`set_max_threads`],
+//! or share a pool of their own with [`set_thread_pool`]. The setting applies to
+//! every image, except for pipelines with their own thread count,
+//! see [`Pipeline::set_threads`](crate::pipelines::Pipeline::set_threads).
+//!
+//! Decoders and encoders starting threads of their own, e.g. the JPEG, PNG and JPEG-XL ones,
+//! start at most [`max_threads`] of them. A [`DecoderOptions::max_threads`] of `0`, the
+//! default, lets decoders pick, which zune-image replaces with [`max_threads`], other
+//! values are lowered to it. Without the `threads` feature decoders and encoders stay
+//! on the calling thread. [Batches](crate::batch::Batch) start no more workers than
+//! [`max_threads`] either.
+//!
+//! [`DecoderOptions::max_threads`]: zune_core::options::DecoderOptions::max_threads
+//!
+//! Small images, below 64K pixels, are processed on the calling thread since
+//! handing them out to threads takes longer than processing them.
+//!
+//! # Example
+//! ```
+//! use zune_image::threads;
+//!
+//! // use at most two threads for all images
+//! threads::set_max_threads(2).unwrap();
+//! assert_eq!(threads::max_threads(), 2);
+//!
+//! // go back to rayon's global pool
+//! threads::set_max_threads(0).unwrap();
+//! ```
+#![cfg(feature = "threads")]
+
+use std::sync::{Arc, PoisonError, RwLock};
+
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+use crate::errors::ImageErrors;
+use crate::progress::{current_progress, with_progress};
+
+static POOL: RwLock<Option<Arc<ThreadPool>>> = RwLock::new(None);
+
+/// Images with less pixels than this, e.g. thumbnails, take less time to process
+/// than to hand out to threads, so they are processed on the calling thread
+pub(crate) const MIN_PARALLEL_PIXELS: usize = 1 << 16;
+
+/// Run parallel work on `pool`, or on rayon's global pool if `None`
+pub fn set_thread_pool(pool: Option<Arc<ThreadPool>>) {
+    *POOL.write().unwrap_or_else(PoisonError::into_inner) = pool;
+}
+
+/// Run parallel work on a new pool of `threads` threads
+///
+/// A value of `0` goes back to rayon's global pool
+///
+/// # Errors
+/// If the thread pool couldn't be created
+pub fn set_max_threads(threads: usize) -> Result<(), ImageErrors> {
+    if threads == 0 {
+        set_thread_pool(None);
+        return Ok(());
+    }
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .map_err(|e| ImageErrors::GenericString(format!("Could not create a thread pool: {e}")))?;
+
+    set_thread_pool(Some(Arc::new(pool)));
+    Ok(())
+}
+
+/// Return the pool set by [`set_thread_pool`] or [`set_max_threads`], if any
+pub fn thread_pool() -> Option<Arc<ThreadPool>> {
+    POOL.read().unwrap_or_else(PoisonError::into_inner).clone()
+}
+
+/// Return the number of threads parallel work runs on
+pub fn max_threads() -> usize {
+    thread_pool().map_or_else(rayon::current_num_threads, |x| x.current_num_threads())
+}
+
+/// Run `function` on the configured pool
+///
+/// Work already running on a rayon pool, e.g. an operation running other operations or
+/// a pipeline with its own pool, stays on it
+pub(crate) fn install<R: Send>(function: impl FnOnce() -> R + Send) -> R {
+    match thread_pool() {
+        Some(pool) if rayon::current_thread_index().is_none() => install_on(&pool, function),
+        _ => function()
+    }
+}
+
+/// Run `function` on `pool`, keeping the progress of the calling thread
+pub(crate) fn install_on<R: Send>(pool: &ThreadPool, function: impl FnOnce() -> R + Send) -> R {
+    // progress is per thread, so pass it on to the pool
+    let progress = current_progress();
+
+    pool.install(|| match progress {
+        Some(progress) => with_progress(progress, function),
+        None => function()
+    })
+}
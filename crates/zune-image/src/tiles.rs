@@ -0,0 +1,332 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Tiled processing
+//!
+//! Splits an image into tiles and runs an operation on every tile instead of the whole
+//! image, so that the memory operations need for their intermediate buffers is bounded by
+//! the tile size, e.g. for gigapixel images.
+//!
+//! Operations that look at neighbouring pixels, like blurs, would show seams where
+//! tiles meet. To avoid this every tile is extended by a halo of pixels from its neighbours,
+//! processed, and only its core is written back. A halo at least as large as the radius of the
+//! operation gives the same result as processing the whole image.
+//!
+//! Cores are written back to the image as soon as their tile is done, tiles still read the
+//! original pixels where their halo overlaps tiles that were already processed. Those are
+//! kept aside, the columns left of the next tile and the rows above the next row of tiles,
+//! so besides the image processing needs memory for a tile and a halo high strip of rows.
+//!
+//! # Example
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::tiles::TiledProcessing;
+//!
+//! let mut image = Image::fill(100_u8, ColorSpace::RGB, 1000, 800);
+//!
+//! TiledProcessing::new(256, 256)
+//!     .set_halo(8)
+//!     .execute_with(&mut image, |tile, _| {
+//!         tile.modify_pixels_mut(|_, _, pix: [&mut u8; 4]| *pix[1] = 200)
+//!     })
+//!     .unwrap();
+//!
+//! assert_eq!(image.flatten_frames::<u8>()[0][..3], [100, 200, 100]);
+//! ```
+use crate::errors::ImageErrors;
+use crate::image::Image;
+use crate::traits::OperationsTrait;
+
+/// A tile of an image
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Tile {
+    core: (usize, usize, usize, usize),
+    halo: (usize, usize, usize, usize)
+}
+
+impl Tile {
+    /// The part of the image this tile produces, as `(x, y, width, height)`
+    ///
+    /// The cores of all tiles cover the image without overlapping
+    pub const fn core(&self) -> (usize, usize, usize, usize) {
+        self.core
+    }
+    /// The part of the image this tile reads, the core extended by the halo and
+    /// clipped to the image, as `(x, y, width, height)`
+    pub const fn with_halo(&self) -> (usize, usize, usize, usize) {
+        self.halo
+    }
+}
+
+/// Run operations on an image tile by tile
+#[derive(Copy, Clone, Debug)]
+pub struct TiledProcessing {
+    tile_width:  usize,
+    tile_height: usize,
+    halo:        usize
+}
+
+impl TiledProcessing {
+    /// Create a new tiled processor with tiles of the given size and no halo
+    ///
+    /// Tiles at the right and bottom edges may be smaller
+    #[must_use]
+    pub fn new(tile_width: usize, tile_height: usize) -> TiledProcessing {
+        TiledProcessing {
+            tile_width:  tile_width.max(1),
+            tile_height: tile_height.max(1),
+            halo:        0
+        }
+    }
+    /// Set the number of pixels every tile is extended by on each side
+    #[must_use]
+    pub fn set_halo(mut self, halo: usize) -> Self {
+        self.halo = halo;
+        self
+    }
+
+    /// Iterate over the tiles of an image of the given dimensions, row by row
+    pub fn tiles(&self, width: usize, height: usize) -> Tiles {
+        Tiles {
+            layout: *self,
+            width,
+            height,
+            x: 0,
+            y: 0
+        }
+    }
+
+    /// Execute an operation on every tile of the image
+    ///
+    /// The operation must keep the dimensions of the image, tiles it converts to
+    /// another colorspace or depth are converted back
+    ///
+    /// # Errors
+    /// Errors from the operation
+    pub fn execute(
+        &self, image: &mut Image, operation: &dyn OperationsTrait
+    ) -> Result<(), ImageErrors> {
+        self.execute_with(image, |tile, _| operation.execute(tile))
+    }
+
+    /// Run a function on every tile of the image
+    ///
+    /// The function gets the tile including its halo and where it lies in the image,
+    /// it must keep the dimensions of the tile
+    ///
+    /// # Errors
+    /// Errors from the function, or if it changed the dimensions of a tile
+    pub fn execute_with<E>(
+        &self, image: &mut Image, mut function: impl FnMut(&mut Image, &Tile) -> Result<(), E>
+    ) -> Result<(), ImageErrors>
+    where
+        ImageErrors: From<E>
+    {
+        let (width, height) = image.dimensions();
+        // original pixels of the rows above the current row of tiles, and of the
+        // columns left of the current tile, which were overwritten by the cores there
+        let mut above: Option<Image> = None;
+        let mut next_above: Option<Image> = None;
+        let mut left: Option<Image> = None;
+
+        for tile in self.tiles(width, height) {
+            let (x, y, w, h) = tile.with_halo();
+            let (core_x, core_y, core_w, core_h) = tile.core();
+            let (next_x, next_y) = (core_x + core_w, core_y + core_h);
+
+            if core_x == 0 {
+                // a new row of tiles, every tile of it fills its columns in
+                above = next_above.take();
+                left = None;
+
+                if self.halo > 0 && next_y < height {
+                    let above_y = next_y.saturating_sub(self.halo);
+                    let strip = image.region_mut(0, above_y, width, next_y - above_y)?;
+                    next_above = Some(strip.to_image()?);
+                }
+            }
+            let mut processed = image.region_mut(x, y, w, h)?.to_image()?;
+
+            if let Some(above) = &mut above {
+                copy_rect(above, (x, 0, w, core_y - y), &mut processed, (0, 0))?;
+            }
+            if let Some(left) = &mut left {
+                copy_rect(left, (0, 0, core_x - x, core_h), &mut processed, (0, core_y - y))?;
+            }
+            // keep what the next tiles read of this one before it's processed
+            if let Some(next_above) = &mut next_above {
+                let above_y = next_y.saturating_sub(self.halo);
+                let rect = (core_x - x, above_y - y, core_w, next_y - above_y);
+
+                copy_rect(&mut processed, rect, next_above, (core_x, 0))?;
+            }
+            left = None;
+
+            if self.halo > 0 && next_x < width {
+                let left_x = next_x.saturating_sub(self.halo);
+                let strip = processed.region_mut(left_x - x, core_y - y, next_x - left_x, core_h)?;
+                left = Some(strip.to_image()?);
+            }
+
+            function(&mut processed, &tile)?;
+
+            let core = processed
+                .region_mut(core_x - x, core_y - y, core_w, core_h)?
+                .to_image()?;
+
+            image
+                .region_mut(core_x, core_y, core_w, core_h)?
+                .paste(&core)?;
+        }
+        Ok(())
+    }
+}
+
+/// Copy the `(x, y, width, height)` rectangle of `from` to `position` in `to`,
+/// empty rectangles copy nothing
+fn copy_rect(
+    from: &mut Image, rect: (usize, usize, usize, usize), to: &mut Image, position: (usize, usize)
+) -> Result<(), ImageErrors> {
+    let (x, y, width, height) = rect;
+
+    if width == 0 || height == 0 {
+        return Ok(());
+    }
+    let pixels = from.region_mut(x, y, width, height)?.to_image()?;
+
+    to.region_mut(position.0, position.1, width, height)?
+        .paste(&pixels)
+}
+
+/// Iterator over the tiles of an image
+///
+/// Created by [`TiledProcessing::tiles`]
+pub struct Tiles {
+    layout: TiledProcessing,
+    width:  usize,
+    height: usize,
+    x:      usize,
+    y:      usize
+}
+
+impl Iterator for Tiles {
+    type Item = Tile;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.y >= self.height || self.width == 0 {
+            return None;
+        }
+        let layout = self.layout;
+        let width = layout.tile_width.min(self.width - self.x);
+        let height = layout.tile_height.min(self.height - self.y);
+
+        let halo_x = self.x.saturating_sub(layout.halo);
+        let halo_y = self.y.saturating_sub(layout.halo);
+        let halo_end_x = (self.x + width + layout.halo).min(self.width);
+        let halo_end_y = (self.y + height + layout.halo).min(self.height);
+
+        let tile = Tile {
+            core: (self.x, self.y, width, height),
+            halo: (halo_x, halo_y, halo_end_x - halo_x, halo_end_y - halo_y)
+        };
+
+        self.x += width;
+        if self.x >= self.width {
+            self.x = 0;
+            self.y += height;
+        }
+        Some(tile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::errors::ImageErrors;
+    use crate::image::Image;
+    use crate::tiles::TiledProcessing;
+
+    /// Replace every pixel of a grayscale image with the largest of its 3x3 neighbourhood
+    fn dilate(image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let pixels = image.flatten_frames::<u8>()[0].clone();
+        let mut output = pixels.clone();
+
+        for y in 0..height {
+            for x in 0..width {
+                let mut max = 0;
+                for ny in y.saturating_sub(1)..(y + 2).min(height) {
+                    for nx in x.saturating_sub(1)..(x + 2).min(width) {
+                        max = max.max(pixels[ny * width + nx]);
+                    }
+                }
+                output[y * width + x] = max;
+            }
+        }
+        *image = Image::from_u8(&output, width, height, ColorSpace::Luma);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tiles_cover_image() {
+        let tiles: Vec<_> = TiledProcessing::new(4, 3)
+            .set_halo(2)
+            .tiles(10, 7)
+            .collect();
+        assert_eq!(tiles.len(), 9);
+
+        let mut covered = [0; 70];
+        for tile in &tiles {
+            let (x, y, w, h) = tile.core();
+            let (hx, hy, hw, hh) = tile.with_halo();
+            assert!(hx <= x && hy <= y && hx + hw >= x + w && hy + hh >= y + h);
+            assert!(hx + hw <= 10 && hy + hh <= 7);
+
+            for j in y..y + h {
+                for i in x..x + w {
+                    covered[j * 10 + i] += 1;
+                }
+            }
+        }
+        assert!(covered.iter().all(|x| *x == 1));
+    }
+
+    #[test]
+    fn test_halo_matches_whole_image() {
+        let pixels: Vec<u8> = (0..37 * 23).map(|x| (x * 37 % 251) as u8).collect();
+        let mut expected = Image::from_u8(&pixels, 37, 23, ColorSpace::Luma);
+        dilate(&mut expected).unwrap();
+
+        let mut image = Image::from_u8(&pixels, 37, 23, ColorSpace::Luma);
+        TiledProcessing::new(8, 5)
+            .set_halo(1)
+            .execute_with(&mut image, |tile, _| dilate(tile))
+            .unwrap();
+
+        assert!(image == expected);
+    }
+
+    #[test]
+    fn test_halo_larger_than_tiles() {
+        let dilate_3 = |image: &mut Image| (0..3).try_for_each(|_| dilate(image));
+
+        let pixels: Vec<u8> = (0..29 * 17).map(|x| (x * 53 % 241) as u8).collect();
+        let mut expected = Image::from_u8(&pixels, 29, 17, ColorSpace::Luma);
+        dilate_3(&mut expected).unwrap();
+
+        // tiles read pixels of tiles a few rows and columns of tiles away
+        let mut image = Image::from_u8(&pixels, 29, 17, ColorSpace::Luma);
+        TiledProcessing::new(2, 3)
+            .set_halo(4)
+            .execute_with(&mut image, |tile, _| dilate_3(tile))
+            .unwrap();
+
+        assert!(image == expected);
+    }
+}
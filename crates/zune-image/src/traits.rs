@@ -14,6 +14,8 @@
 //! - encoding: `EncoderTrait`: Implementing this means the image can be saved to a certain format
 //!
 #![allow(unused_variables)]
+use alloc::format;
+
 use zune_core::bit_depth::{BitDepth, BitType};
 use zune_core::bytestream::ZByteWriterTrait;
 use zune_core::colorspace::{ColorSpace, ALL_COLORSPACES};
@@ -21,13 +23,19 @@ use zune_core::log::{trace, warn};
 use zune_core::options::EncoderOptions;
 
 use crate::codecs::ImageFormat;
+use crate::core_filters::alpha::convert_alpha_state;
 use crate::core_filters::colorspace::ColorspaceConv;
-use crate::core_filters::depth::Depth;
+use crate::core_filters::depth::{Depth, Dither};
+use crate::core_filters::layout::convert_layout;
 use crate::errors::{ImageErrors, ImageOperationsErrors};
+use crate::frame::Frame;
 use crate::image::Image;
 use crate::metadata::AlphaState::NonPreMultiplied;
-use crate::metadata::{AlphaState, ImageMetadata};
+use crate::metadata::{AlphaState, ImageMetadata, PixelLayout};
+#[cfg(feature = "std")]
 use crate::pipelines::EncodeResult;
+#[cfg(feature = "std")]
+use crate::progress::report;
 
 /// Encapsulates an image decoder.
 ///
@@ -88,6 +96,146 @@ pub trait DecoderTrait {
     fn read_headers(&mut self) -> Result<Option<ImageMetadata>, crate::errors::ImageErrors> {
         Ok(None)
     }
+    /// Decode a buffer already in memory, storing the channels in `layout`
+    ///
+    /// Decoders producing interleaved pixels can skip separating them into planes
+    /// when [`PixelLayout::Interleaved`] is requested, images that are only decoded to be
+    /// encoded again or displayed never pay for deinterleaving.
+    ///
+    /// The default implementation decodes with [`decode`](Self::decode) and converts
+    /// the result to `layout`
+    ///
+    /// # Errors
+    ///  - Any image decoding errors will be propagated to the caller.
+    fn decode_with_layout(
+        &mut self, layout: PixelLayout
+    ) -> Result<Image, crate::errors::ImageErrors> {
+        let mut image = self.decode()?;
+        image.convert_layout(layout)?;
+
+        Ok(image)
+    }
+    /// Decode the image into `image`, replacing its pixels and metadata
+    ///
+    /// Decoders that support it reuse the channels of `image` when it already holds a
+    /// single frame with the same dimensions, colorspace and depth, so decoding many
+    /// same-sized images, e.g. the frames of a video, doesn't allocate new channels for
+    /// each. The image keeps its layout, interleaved images are decoded into their
+    /// channel directly while planar ones go through a temporary buffer.
+    ///
+    /// The default implementation decodes a new image with
+    /// [`decode_with_layout`](Self::decode_with_layout) and replaces `image` with it
+    ///
+    /// # Errors
+    ///  - Any image decoding errors will be propagated to the caller, the
+    ///    pixels of `image` are unspecified in that case
+    ///
+    /// # Example
+    /// ```no_run
+    /// use zune_core::bytestream::ZCursor;
+    /// #[cfg(feature = "jpeg")]
+    /// {
+    ///     use zune_image::metadata::PixelLayout;
+    ///     use zune_image::traits::DecoderTrait;
+    ///     use zune_jpeg::JpegDecoder;
+    ///
+    ///     let frames: Vec<Vec<u8>> = vec![];
+    ///     let mut image = JpegDecoder::new(ZCursor::new(&frames[0]))
+    ///         .decode_with_layout(PixelLayout::Interleaved)
+    ///         .unwrap();
+    ///
+    ///     for frame in &frames[1..] {
+    ///         JpegDecoder::new(ZCursor::new(frame))
+    ///             .decode_into_image(&mut image)
+    ///             .unwrap();
+    ///     }
+    /// }
+    /// #[cfg(not(feature="jpeg"))]
+    /// ()
+    /// ```
+    fn decode_into_image(&mut self, image: &mut Image) -> Result<(), crate::errors::ImageErrors> {
+        *image = self.decode_with_layout(image.layout())?;
+
+        Ok(())
+    }
+    /// Decode the next frame of the image
+    ///
+    /// Decoders of animated formats decode one frame per call and keep only what they
+    /// need to compose the next one, so long animations can be processed or transcoded
+    /// without holding every frame in memory. Frames are planar, with the dimensions,
+    /// colorspace and depth returned by [`read_headers`](Self::read_headers)
+    ///
+    /// Returns `Ok(None)` after the last frame.
+    ///
+    /// Only decoders where [`supports_frame_streaming`](Self::supports_frame_streaming)
+    /// returns true implement this, the default implementation returns an error.
+    /// [`FrameStream`](crate::stream::FrameStream) works with every decoder
+    ///
+    /// # Errors
+    ///  - Any image decoding errors will be propagated to the caller.
+    fn next_frame(&mut self) -> Result<Option<Frame>, crate::errors::ImageErrors> {
+        Err(ImageErrors::GenericStr(
+            "Decoder does not support decoding frames one at a time"
+        ))
+    }
+    /// Return true if the decoder can decode the image one frame at a time
+    /// with [`next_frame`](Self::next_frame)
+    ///
+    /// Decoders may only support it for some images, e.g. animated ones, this
+    /// should be called after [`read_headers`](Self::read_headers)
+    fn supports_frame_streaming(&self) -> bool {
+        false
+    }
+}
+
+/// The type of an operation parameter and the values it takes
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ParameterKind {
+    /// A number, usually from `min` to `max`
+    Float { min: f32, max: f32 },
+    /// A whole number, usually from `min` to `max`
+    Integer { min: i64, max: i64 },
+    /// A flag
+    Bool,
+    /// One of a fixed set of names
+    Choice(&'static [&'static str])
+}
+
+/// The value of an operation parameter
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ParameterValue {
+    Float(f32),
+    Integer(i64),
+    Bool(bool),
+    Choice(&'static str)
+}
+
+/// Describes a parameter an operation is created with
+///
+/// The range of a parameter is the range of useful values, e.g. for a slider,
+/// operations may accept values outside it.
+///
+/// # Example
+/// List the parameters of an operation
+/// ```
+/// use zune_image::core_filters::depth::Depth;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_core::bit_depth::BitDepth;
+///
+/// for parameter in Depth::new(BitDepth::Eight).parameters() {
+///     println!("--{} {:?}: {}", parameter.name, parameter.kind, parameter.description);
+/// }
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Parameter {
+    /// Name of the parameter, in snake case
+    pub name:        &'static str,
+    /// What the parameter does
+    pub description: &'static str,
+    /// Type and range of the parameter
+    pub kind:        ParameterKind,
+    /// The value that leaves the image unchanged, or is a good starting point
+    pub default:     ParameterValue
 }
 
 /// This encapsulates an image operation.
@@ -132,87 +280,201 @@ pub trait OperationsTrait: Send + Sync {
     /// it checks that image dimensions match array length and that this operation
     /// supports the image colorspace, before calling [`execute_impl`]
     ///
+    /// With the `threads` feature [`execute_impl`] runs on the thread pool
+    /// set in [`threads`](crate::threads)
+    ///
     /// # Arguments
     /// - image: A mutable reference to an image which
     /// this operation will manipulate
     ///
     ///
     /// # Errors
-    /// Any operations error will be propagated to the caller
+    /// Any operations error will be propagated to the caller, wrapped in
+    /// [`ImageErrors::OperationFailed`] with the name of this operation
     ///
     ///
     /// [`execute_impl`]: Self::execute_impl
     fn execute(&self, image: &mut Image) -> Result<(), ImageErrors> {
-        // Confirm colorspace
-        let colorspace = image.colorspace();
+        #[cfg(feature = "std")]
+        report(self.name(), 0.0)?;
+        let in_operation = |e: ImageErrors| e.in_operation(self.name());
+
+        prepare_image(self, image).map_err(in_operation)?;
+        #[cfg(feature = "threads")]
+        crate::threads::install(|| self.execute_impl(image)).map_err(in_operation)?;
+        #[cfg(not(feature = "threads"))]
+        self.execute_impl(image).map_err(in_operation)?;
+        confirm_invariants(image).map_err(in_operation)?;
+
+        #[cfg(feature = "std")]
+        report(self.name(), 1.0)?;
+        Ok(())
+    }
+    /// Alpha state for which the image operation works in
+    ///
+    /// Images with an alpha channel are converted into this state before
+    /// carrying out the operation and stay in it afterwards, the state is tracked by
+    /// [`ImageMetadata::alpha`]. Operations that mix neighbouring pixels, e.g. resampling,
+    /// work on premultiplied alpha to avoid color fringes around transparent areas,
+    /// compositing works on straight alpha.
+    ///
+    /// Default is `None`, the operation works in either state
+    fn alpha_state(&self) -> Option<AlphaState> {
+        None
+    }
 
-        let supported = self
-            .supported_colorspaces()
-            .iter()
-            .any(|x| *x == colorspace);
-
-        if !supported {
-            match colorspace {
-                // for multi-band images, we want to ignore them
-                // since it is a lot of work to match them. so we assume they are images
-                // with color channels that lacks an alpha channel.
-                // This means it kinda behaves like an RGB image
-                ColorSpace::MultiBand(_) => {
-                    warn!("Multi-band image encountered");
-                    warn!("The image will be treated as a n-channel image with no alpha channel to allow operations to run on it");
-                }
-                _ => {
-                    return Err(ImageErrors::UnsupportedColorspace(
-                        colorspace,
-                        self.name(),
-                        self.supported_colorspaces()
-                    ));
-                }
-            }
-        }
-        // if image.metadata.alpha != self.alpha_state()
-        // {
-        //     PremultiplyAlpha::new(self.alpha_state());
-        // }
-        // check we support the bit depth
-        let bit_type = image.metadata.depth().bit_type();
-
-        let supported = self.supported_types().iter().any(|x| *x == bit_type);
-
-        if !supported {
-            return Err(ImageErrors::OperationsError(
-                ImageOperationsErrors::UnsupportedType(self.name(), bit_type)
-            ));
-        }
+    /// Storage layouts this operation works on
+    ///
+    /// Images in another layout are converted to the first layout in the list before
+    /// carrying out the operation and stay in it afterwards, see [`PixelLayout`].
+    /// Operations treating every sample the same, regardless of the channel it belongs to,
+    /// can work on interleaved images directly.
+    ///
+    /// Default is planar only
+    fn supported_layouts(&self) -> &'static [PixelLayout] {
+        &[PixelLayout::Planar]
+    }
 
-        confirm_invariants(image)?;
+    /// Return true if this is a point operation
+    ///
+    /// A point operation maps every value to a new value that only depends on that value
+    /// and the channel it's in, e.g. inverting or brightening, without changing the
+    /// dimensions, colorspace or depth of the image.
+    ///
+    /// [`Pipeline`](crate::pipelines::Pipeline) fuses consecutive point operations,
+    /// and [`PointOps`](crate::point_ops::PointOps) combines them, going over the image
+    /// once instead of once per operation.
+    ///
+    /// Default is false
+    fn is_point_op(&self) -> bool {
+        false
+    }
+
+    /// Describe the parameters this operation is created with
+    ///
+    /// This lets generic user interfaces and command line tools offer every operation
+    /// without bindings written for each one, see [`Parameter`]
+    ///
+    /// Default is no parameters
+    fn parameters(&self) -> &'static [Parameter] {
+        &[]
+    }
+
+    /// Execute the operation, returning a new image and leaving `image` untouched
+    ///
+    /// This does the same checks and conversions as [`execute`] before calling
+    /// [`apply_impl`]. Channels the operation doesn't write to are shared
+    /// between both images instead of being copied, see [channel](crate::channel#copy-on-write)
+    ///
+    /// # Errors
+    /// Any operations error will be propagated to the caller, wrapped in
+    /// [`ImageErrors::OperationFailed`] with the name of this operation
+    ///
+    /// [`execute`]: Self::execute
+    /// [`apply_impl`]: Self::apply_impl
+    fn apply(&self, image: &Image) -> Result<Image, ImageErrors> {
+        #[cfg(feature = "std")]
+        report(self.name(), 0.0)?;
+        let in_operation = |e: ImageErrors| e.in_operation(self.name());
 
-        self.execute_impl(image)
-            .map_err(<ImageErrors as Into<ImageErrors>>::into)?;
+        let mut input = image.clone();
+        prepare_image(self, &mut input).map_err(in_operation)?;
 
-        confirm_invariants(image)?;
+        let output = self.apply_impl(&input).map_err(in_operation)?;
+        confirm_invariants(&output).map_err(in_operation)?;
 
-        Ok(())
+        #[cfg(feature = "std")]
+        report(self.name(), 1.0)?;
+        Ok(output)
     }
-    /// Alpha state for which the image operation works in
+
+    /// Produce the result of the operation as a new image
+    ///
+    /// The default clones `image` and calls [`execute_impl`] on the clone. Operations
+    /// that replace every value of a channel can write straight into new channels
+    /// instead, skipping the copy made when a shared channel is written to.
     ///
-    /// Most image expect a premultiplied alpha state to work correctly
-    /// this allows one to override the alpha state the image will
-    /// be converted into before carrying out an operation
-    fn alpha_state(&self) -> AlphaState {
-        AlphaState::PreMultiplied
+    /// A caller should call [`apply`], which does some error checking
+    /// before calling this method
+    ///
+    /// [`execute_impl`]: Self::execute_impl
+    /// [`apply`]: Self::apply
+    fn apply_impl(&self, image: &Image) -> Result<Image, ImageErrors> {
+        let mut output = image.clone();
+        self.execute_impl(&mut output)?;
+        Ok(output)
     }
 
     /// Clone the image and execute the operation on it, returning
     /// a new image instead of modifying the existing one
     ///
     /// This is provided as a convenience function for when one
-    /// doesn't want to modify the existing image
+    /// doesn't want to modify the existing image, it's the same as [`apply`](Self::apply)
     fn clone_and_execute(&self, image: &Image) -> Result<Image, ImageErrors> {
-        let mut c_img = image.clone();
-        self.execute(&mut c_img)?;
-        Ok(c_img)
+        self.apply(image)
+    }
+}
+
+/// Check that `operation` supports the image and convert it to the layout
+/// and alpha state the operation works in
+fn prepare_image<O: OperationsTrait + ?Sized>(
+    operation: &O, image: &mut Image
+) -> Result<(), ImageErrors> {
+    // Confirm colorspace
+    let colorspace = image.colorspace();
+
+    let supported = operation.supported_colorspaces().contains(&colorspace);
+
+    if !supported {
+        match colorspace {
+            // for multi-band images, we want to ignore them
+            // since it is a lot of work to match them. so we assume they are images
+            // with color channels that lacks an alpha channel.
+            // This means it kinda behaves like an RGB image
+            ColorSpace::MultiBand(_) => {
+                warn!("Multi-band image encountered");
+                warn!("The image will be treated as a n-channel image with no alpha channel to allow operations to run on it");
+            }
+            _ => {
+                return Err(ImageErrors::UnsupportedColorspace(
+                    colorspace,
+                    operation.name(),
+                    operation.supported_colorspaces()
+                ));
+            }
+        }
+    }
+    // check we support the bit depth
+    let bit_type = image.metadata.depth().bit_type();
+
+    let supported = operation.supported_types().contains(&bit_type);
+
+    if !supported {
+        return Err(ImageErrors::OperationsError(
+            ImageOperationsErrors::UnsupportedType(operation.name(), bit_type)
+        ));
+    }
+
+    confirm_invariants(image)?;
+
+    let layout = image.layout();
+    let layouts = operation.supported_layouts();
+
+    if !layouts.contains(&layout) {
+        let to = layouts.first().copied().unwrap_or_default();
+
+        trace!(
+            "Image is in {layout:?} layout, converting it to {to:?} for {}",
+            operation.name()
+        );
+        convert_layout(image, to)?;
     }
+
+    if let Some(alpha_state) = operation.alpha_state() {
+        convert_alpha_state(image, alpha_state)?;
+    }
+
+    Ok(())
 }
 
 /// Confirm that image invariants have been respected across image
@@ -220,13 +482,22 @@ pub trait OperationsTrait: Send + Sync {
 fn confirm_invariants(image: &Image) -> Result<(), ImageErrors> {
     // Ensure dimensions are correct
 
+    let components = image.colorspace().num_components();
+
+    // interleaved images keep all components in one channel
+    let (expected_channels, per_channel) = match image.layout() {
+        PixelLayout::Planar => (components, 1),
+        PixelLayout::Interleaved => (1, components)
+    };
+
     for frame in image.frames_ref() {
-        if frame.channels.len() != image.colorspace().num_components() {
+        if frame.channels.len() != expected_channels {
             {
                 return Err(ImageErrors::GenericString(format!(
-                    "Components mismatch, expected {} channels since image format is {:?}, but found {}",
-                    image.colorspace().num_components(),
+                    "Components mismatch, expected {} channels since image format is {:?} in {:?} layout, but found {}",
+                    expected_channels,
                     image.colorspace(),
+                    image.layout(),
                     frame.channels.len()
                 )));
             }
@@ -236,7 +507,7 @@ fn confirm_invariants(image: &Image) -> Result<(), ImageErrors> {
     let (width, height) = image.dimensions();
     // check the number of channels match the length
 
-    let expected_length = image.depth().size_of() * width * height;
+    let expected_length = image.depth().size_of() * width * height * per_channel;
 
     for channel in image.channels_ref(true) {
         if channel.len() != expected_length {
@@ -349,11 +620,15 @@ pub trait EncoderTrait {
                     self.default_depth(image_depth)
                 );
 
-                let depth = Depth::new(self.default_depth(image_depth));
+                let depth =
+                    Depth::new(self.default_depth(image_depth)).set_dither(self.depth_dither());
 
                 depth.execute(&mut image_clone)?;
             }
 
+            // encoders expect straight alpha
+            convert_alpha_state(&mut image_clone, NonPreMultiplied)?;
+
             // confirm again we didn't mess up
             confirm_invariants(&image_clone)?;
 
@@ -380,6 +655,7 @@ pub trait EncoderTrait {
 
     /// Call `encode` and then store the image
     /// and format in `EncodeResult`
+    #[cfg(feature = "std")]
     fn encode_to_result(&mut self, image: &Image) -> Result<EncodeResult, ImageErrors> {
         let mut sink = vec![];
         let data = self.encode(image, &mut sink)?;
@@ -404,6 +680,16 @@ pub trait EncoderTrait {
     /// [`supported_bit_depth`]:EncoderTrait::supported_bit_depth
     fn default_depth(&self, depth: BitDepth) -> BitDepth;
 
+    /// Returns how pixels are quantized when the image is converted
+    /// to the [`default_depth`] of the encoder
+    ///
+    /// Default is [`Dither::Round`]
+    ///
+    /// [`default_depth`]:EncoderTrait::default_depth
+    fn depth_dither(&self) -> Dither {
+        Dither::Round
+    }
+
     /// Returns the default colorspace to use when the image
     /// contains a different colorspace
     ///
@@ -0,0 +1,223 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Images with a depth known at compile time
+//!
+//! [`Image`] can hold any depth, so code touching its pixels has to match on
+//! [`BitType`](zune_core::bit_depth::BitType) and call
+//! [`reinterpret_as`](crate::channel::Channel::reinterpret_as) for every channel.
+//! A [`TypedImage<T>`] checks the depth once when it's created, after which its
+//! channels are plain `&[T]` slices.
+//!
+//! Typed images are always planar, with one channel per component.
+//!
+//! # Example
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::typed::TypedImage;
+//!
+//! let image = Image::fill(100_u8, ColorSpace::RGB, 4, 4);
+//!
+//! let mut typed = TypedImage::<f32>::convert(image).unwrap();
+//! typed.map(|x| x * 0.5);
+//!
+//! assert!(typed.channels().iter().all(|c| c.iter().all(|x| *x < 0.2)));
+//!
+//! let image = typed.into_image();
+//! ```
+use alloc::format;
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use core::marker::PhantomData;
+
+use bytemuck::{Pod, Zeroable};
+use zune_core::colorspace::ColorSpace;
+
+use crate::errors::ImageErrors;
+use crate::image::{Image, MAX_CHANNELS};
+use crate::metadata::PixelLayout;
+use crate::traits::{OperationsTrait, ZuneInts};
+
+/// An image whose pixels are stored as `T`
+///
+/// `T` is [`u8`], [`u16`] or [`f32`]
+#[derive(Clone)]
+pub struct TypedImage<T> {
+    image:   Image,
+    _marker: PhantomData<T>
+}
+
+impl<T> TypedImage<T>
+where
+    T: ZuneInts<T> + Copy + Default + Debug + 'static + Zeroable + Pod
+{
+    /// Wrap an image stored as `T`
+    ///
+    /// Interleaved images are converted to planar
+    ///
+    /// # Errors
+    /// If the pixels of the image aren't stored as `T`, see [`convert`](Self::convert)
+    pub fn new(mut image: Image) -> Result<TypedImage<T>, ImageErrors> {
+        if image.depth().bit_type() != T::depth().bit_type() {
+            return Err(ImageErrors::GenericString(format!(
+                "Image depth {:?} is not stored as {:?}",
+                image.depth(),
+                T::depth().bit_type()
+            )));
+        }
+        image.convert_layout(PixelLayout::Planar)?;
+
+        Ok(TypedImage {
+            image,
+            _marker: PhantomData
+        })
+    }
+
+    /// Wrap an image, converting its depth to the depth of `T`
+    ///
+    /// # Errors
+    /// If the depth or layout of the image can't be converted
+    pub fn convert(mut image: Image) -> Result<TypedImage<T>, ImageErrors> {
+        if image.depth().bit_type() != T::depth().bit_type() {
+            image.convert_depth(T::depth())?;
+        }
+        TypedImage::new(image)
+    }
+
+    /// Create an image with every pixel set to `pixel`
+    pub fn fill(pixel: T, colorspace: ColorSpace, width: usize, height: usize) -> TypedImage<T> {
+        TypedImage {
+            image:   Image::fill(pixel, colorspace, width, height),
+            _marker: PhantomData
+        }
+    }
+
+    /// Create an image by calling `function` for every pixel
+    ///
+    /// See [`Image::from_fn`]
+    pub fn from_fn(
+        width: usize, height: usize, colorspace: ColorSpace,
+        function: impl Fn(usize, usize, &mut [T; MAX_CHANNELS])
+    ) -> TypedImage<T> {
+        TypedImage {
+            image:   Image::from_fn(width, height, colorspace, function),
+            _marker: PhantomData
+        }
+    }
+
+    /// The wrapped image
+    pub const fn image(&self) -> &Image {
+        &self.image
+    }
+
+    /// Unwrap the image
+    pub fn into_image(self) -> Image {
+        self.image
+    }
+
+    /// Width and height of the image
+    pub fn dimensions(&self) -> (usize, usize) {
+        self.image.dimensions()
+    }
+
+    /// Colorspace of the image
+    pub const fn colorspace(&self) -> ColorSpace {
+        self.image.colorspace()
+    }
+
+    /// The channels of all frames, one after the other
+    pub fn channels(&self) -> Vec<&[T]> {
+        self.image
+            .frames_ref()
+            .iter()
+            .flat_map(|x| x.channels_vec_ref())
+            .map(|x| x.reinterpret_as::<T>().expect("typed image holds T"))
+            .collect()
+    }
+
+    /// The channels of all frames, one after the other, mutably
+    ///
+    /// Channels shared with clones are copied when this is called
+    pub fn channels_mut(&mut self) -> Vec<&mut [T]> {
+        self.image
+            .frames_mut()
+            .iter_mut()
+            .flat_map(|x| x.channels_vec().iter_mut())
+            .map(|x| x.reinterpret_as_mut::<T>().expect("typed image holds T"))
+            .collect()
+    }
+
+    /// The components of the pixel at `x`, `y` of the first frame, or `None` if
+    /// it's outside the image
+    pub fn pixel(&self, x: usize, y: usize) -> Option<[T; MAX_CHANNELS]> {
+        self.image.pixel_at(x, y).ok()
+    }
+
+    /// Replace every value of every channel with `function(value)`
+    pub fn map(&mut self, function: impl Fn(T) -> T) {
+        for channel in self.channels_mut() {
+            channel.iter_mut().for_each(|x| *x = function(*x));
+        }
+    }
+
+    /// Modify every pixel with `function`, called with the row, column and
+    /// components of the pixel
+    ///
+    /// See [`Image::modify_pixels_mut`]
+    pub fn modify_pixels(&mut self, function: impl Fn(usize, usize, [&mut T; MAX_CHANNELS])) {
+        self.image
+            .modify_pixels_mut(function)
+            .expect("typed image holds T");
+    }
+
+    /// Execute an operation on the image
+    ///
+    /// Operations that change the depth or layout are converted back
+    ///
+    /// # Errors
+    /// Errors from the operation or converting its result
+    pub fn execute(&mut self, operation: &dyn OperationsTrait) -> Result<(), ImageErrors> {
+        operation.execute(&mut self.image)?;
+
+        if self.image.depth().bit_type() != T::depth().bit_type() {
+            self.image.convert_depth(T::depth())?;
+        }
+        self.image.convert_layout(PixelLayout::Planar)
+    }
+}
+
+impl<T> From<TypedImage<T>> for Image {
+    fn from(value: TypedImage<T>) -> Image {
+        value.image
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::bit_depth::BitDepth;
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::image::Image;
+    use crate::typed::TypedImage;
+
+    #[test]
+    fn test_typed_image() {
+        let image = Image::from_interleaved(&[1_u16, 2, 3, 4, 5, 6], 2, 1, ColorSpace::RGB);
+        assert!(TypedImage::<u8>::new(image.clone()).is_err());
+
+        let mut typed = TypedImage::<u16>::new(image).unwrap();
+        assert_eq!(typed.channels(), [&[1, 4], &[2, 5], &[3, 6]]);
+
+        typed.modify_pixels(|_, x, pix| *pix[0] = x as u16 + 10);
+        assert_eq!(typed.pixel(1, 0), Some([11, 5, 6, 0]));
+        assert_eq!(typed.pixel(2, 0), None);
+
+        let typed = TypedImage::<u8>::convert(typed.into_image()).unwrap();
+        assert_eq!(typed.image().depth(), BitDepth::Eight);
+    }
+}
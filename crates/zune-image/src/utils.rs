@@ -1,5 +1,7 @@
 //! A set of miscellaneous functions that are good to have
-use std::cmp::min;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::min;
 
 use zune_core::bytestream::ZByteReaderTrait;
 
@@ -8,7 +10,7 @@ use crate::metadata::ImageMetadata;
 
 /// Swizzle three channels optionally using simd intrinsics where possible
 fn swizzle_three_channels<T: Copy + Default>(r: &[&[T]], y: &mut [T]) {
-    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    #[cfg(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64")))]
     {
         // Note that this `unsafe` block is safe because we're testing
         // that the `avx2` feature is indeed available on our CPU.
@@ -19,7 +21,7 @@ fn swizzle_three_channels<T: Copy + Default>(r: &[&[T]], y: &mut [T]) {
     swizzle_three_channels_fallback(r, y);
 }
 
-#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[cfg(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64")))]
 #[target_feature(enable = "avx2")]
 unsafe fn swizzle_three_channels_avx<T: Copy + Default>(r: &[&[T]], y: &mut [T]) {
     swizzle_three_channels_fallback(r, y); // the function below is inlined here
@@ -43,7 +45,7 @@ fn swizzle_three_channels_fallback<T: Copy + Default>(r: &[&[T]], y: &mut [T]) {
 }
 
 fn swizzle_four_channels<T: Copy + Default>(r: &[&[T]], y: &mut [T]) {
-    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    #[cfg(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64")))]
     {
         // Note that this `unsafe` block is safe because we're testing
         // that the `avx2` feature is indeed available on our CPU.
@@ -54,7 +56,7 @@ fn swizzle_four_channels<T: Copy + Default>(r: &[&[T]], y: &mut [T]) {
     swizzle_four_channels_fallback(r, y);
 }
 
-#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[cfg(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64")))]
 #[target_feature(enable = "avx2")]
 unsafe fn swizzle_four_channels_avx<T: Copy + Default>(r: &[&[T]], y: &mut [T]) {
     swizzle_four_channels_fallback(r, y); // the function below is inlined here
@@ -0,0 +1,242 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Borrowed views of interleaved pixels
+//!
+//! An [`ImageView`] reads pixels that live somewhere else, e.g. a frame buffer owned by a
+//! camera driver or another library, without copying them. Rows may be padded, as is common
+//! for hardware buffers.
+//!
+//! Views are read only, [`ImageView::to_image`] copies the pixels into an [`Image`] that
+//! operations can run on. Pixels that can be handed over by value enter an image without a
+//! copy with [`Image::from_interleaved_vec`].
+//!
+//! # Example
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::view::ImageView;
+//!
+//! // a 2x2 RGB frame, each row padded to 8 bytes
+//! let buffer = [1_u8, 2, 3, 4, 5, 6, 0, 0, 7, 8, 9, 10, 11, 12, 0, 0];
+//! let view = ImageView::with_stride(&buffer, 2, 2, 8, ColorSpace::RGB).unwrap();
+//!
+//! assert_eq!(view.row(1), [7, 8, 9, 10, 11, 12]);
+//! assert_eq!(view.pixel(1, 0), [4, 5, 6]);
+//!
+//! let image = view.to_image();
+//! assert_eq!(image.flatten_frames::<u8>()[0], [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]);
+//! ```
+use alloc::{format, vec};
+
+use bytemuck::{Pod, Zeroable};
+use zune_core::bit_depth::BitDepth;
+use zune_core::colorspace::ColorSpace;
+
+use crate::channel::Channel;
+use crate::errors::ImageErrors;
+use crate::image::Image;
+use crate::metadata::PixelLayout;
+use crate::traits::ZuneInts;
+
+/// A borrowed, read only view of interleaved pixels
+///
+/// Pixels are interleaved according to the colorspace, i.e `[R,G,B,R,G,B]` for RGB
+#[derive(Copy, Clone, Debug)]
+pub struct ImageView<'a, T> {
+    pixels:     &'a [T],
+    width:      usize,
+    height:     usize,
+    stride:     usize,
+    colorspace: ColorSpace
+}
+
+impl<'a, T> ImageView<'a, T>
+where
+    T: Copy + Default + 'static + ZuneInts<T> + Zeroable + Pod
+{
+    /// Create a view of tightly packed pixels
+    ///
+    /// # Errors
+    /// If `pixels` doesn't hold exactly `width * height` pixels
+    pub fn new(
+        pixels: &'a [T], width: usize, height: usize, colorspace: ColorSpace
+    ) -> Result<ImageView<'a, T>, ImageErrors> {
+        let stride = width.saturating_mul(colorspace.num_components());
+
+        if Some(pixels.len()) != stride.checked_mul(height) {
+            return Err(ImageErrors::GenericString(format!(
+                "Expected {width}x{height} {colorspace:?} pixels but found {} values",
+                pixels.len()
+            )));
+        }
+        Ok(ImageView {
+            pixels,
+            width,
+            height,
+            stride,
+            colorspace
+        })
+    }
+
+    /// Create a view of pixels whose rows start `stride` values apart
+    ///
+    /// # Arguments
+    /// - stride: Number of values, not pixels, from the start of one row to the next
+    ///
+    /// # Errors
+    /// If the stride is smaller than a row or `pixels` is too small for `height` rows
+    pub fn with_stride(
+        pixels: &'a [T], width: usize, height: usize, stride: usize, colorspace: ColorSpace
+    ) -> Result<ImageView<'a, T>, ImageErrors> {
+        let row = width.saturating_mul(colorspace.num_components());
+        // the last row doesn't need padding
+        let needed = stride
+            .checked_mul(height.saturating_sub(1))
+            .and_then(|x| x.checked_add(row));
+
+        if stride < row || needed.is_none_or(|x| pixels.len() < x) {
+            return Err(ImageErrors::GenericString(format!(
+                "{} values with a stride of {stride} can't hold {width}x{height} {colorspace:?} pixels",
+                pixels.len()
+            )));
+        }
+        Ok(ImageView {
+            pixels,
+            width,
+            height,
+            stride,
+            colorspace
+        })
+    }
+
+    /// Width and height of the view
+    pub const fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Colorspace of the pixels
+    pub const fn colorspace(&self) -> ColorSpace {
+        self.colorspace
+    }
+
+    /// Bit depth of the pixels, derived from `T`
+    pub fn depth(&self) -> BitDepth {
+        T::depth()
+    }
+
+    /// Number of values from the start of one row to the next
+    pub const fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// The borrowed values, including padding between rows
+    pub const fn pixels(&self) -> &'a [T] {
+        self.pixels
+    }
+
+    /// The pixels of row `y`, without padding
+    ///
+    /// # Panics
+    /// If `y` is outside the view
+    pub fn row(&self, y: usize) -> &'a [T] {
+        assert!(
+            y < self.height,
+            "Row {y} is outside a view of height {}",
+            self.height
+        );
+
+        let start = y * self.stride;
+        &self.pixels[start..start + self.width * self.colorspace.num_components()]
+    }
+
+    /// Iterate over the rows of the view, without padding
+    pub fn rows(&self) -> impl Iterator<Item = &'a [T]> + '_ {
+        (0..self.height).map(|y| self.row(y))
+    }
+
+    /// The components of the pixel at `x`, `y`
+    ///
+    /// # Panics
+    /// If the pixel is outside the view
+    pub fn pixel(&self, x: usize, y: usize) -> &'a [T] {
+        assert!(
+            x < self.width,
+            "Column {x} is outside a view of width {}",
+            self.width
+        );
+
+        let components = self.colorspace.num_components();
+        &self.row(y)[x * components..(x + 1) * components]
+    }
+
+    /// Copy the pixels into an interleaved image, dropping padding between rows
+    pub fn to_image(&self) -> Image {
+        let row = self.width * self.colorspace.num_components();
+        let mut channel = Channel::new_with_capacity::<T>(row * self.height * size_of::<T>());
+
+        for row in self.rows() {
+            channel.extend(row);
+        }
+        let mut image = Image::new(
+            vec![channel],
+            T::depth(),
+            self.width,
+            self.height,
+            self.colorspace
+        );
+        image.metadata.set_layout(PixelLayout::Interleaved);
+
+        image
+    }
+}
+
+impl Image {
+    /// Borrow the pixels of the first frame of an interleaved image
+    ///
+    /// # Errors
+    /// If the image isn't interleaved or `T` isn't the type of its pixels
+    pub fn view<T>(&self) -> Result<ImageView<'_, T>, ImageErrors>
+    where
+        T: Copy + Default + 'static + ZuneInts<T> + Zeroable + Pod
+    {
+        if self.layout() != PixelLayout::Interleaved {
+            return Err(ImageErrors::GenericStr(
+                "Only interleaved images can be viewed, convert the layout first"
+            ));
+        }
+        let (width, height) = self.dimensions();
+        let pixels = self.frames_ref()[0].channels_vec_ref()[0].reinterpret_as::<T>()?;
+
+        ImageView::new(pixels, width, height, self.colorspace())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::image::Image;
+    use crate::view::ImageView;
+
+    #[test]
+    fn test_view_round_trip() {
+        let image = Image::from_interleaved(&[1_u16, 2, 3, 4], 2, 1, ColorSpace::LumaA);
+        let view = image.view::<u16>().unwrap();
+        assert_eq!(view.pixel(1, 0), [3, 4]);
+        assert!(view.to_image() == image);
+
+        assert!(image.view::<u8>().is_err());
+        assert!(Image::fill(0_u8, ColorSpace::RGB, 2, 2)
+            .view::<u8>()
+            .is_err());
+
+        assert!(ImageView::new(&[0_u8; 5], 2, 1, ColorSpace::RGB).is_err());
+        assert!(ImageView::with_stride(&[0_u8; 9], 2, 2, 1, ColorSpace::Luma).is_err());
+        assert!(ImageView::with_stride(&[0_u8; 5], 2, 2, 4, ColorSpace::Luma).is_err());
+        assert!(ImageView::with_stride(&[0_u8; 6], 2, 2, 4, ColorSpace::Luma).is_ok());
+    }
+}
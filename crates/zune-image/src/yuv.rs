@@ -0,0 +1,404 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Importing raw YUV frames from video decoders and cameras
+//!
+//! Video decoders, e.g. ffmpeg, and camera APIs hand out frames as 8 bit YUV planes with
+//! subsampled chroma, each row possibly followed by padding. The constructors here copy
+//! them into planar [`YCbCr`](ColorSpace::YCbCr) images, upsampling chroma by repeating
+//! each value, so frames can go through every operation and encoder.
+//!
+//! | Constructor              | Layout                                                          |
+//! |--------------------------|-----------------------------------------------------------------|
+//! | [`Image::from_yuv420p`]  | Y, U and V planes, chroma at half width and half height (I420)  |
+//! | [`Image::from_nv12`]     | Y plane and interleaved UV plane at half width and half height  |
+//! | [`Image::from_yuv422p`]  | Y, U and V planes, chroma at half width and full height         |
+//!
+//! Video YUV rarely uses the full range BT.601 coefficients of JPEG that
+//! [`ColorspaceConv`](crate::core_filters::colorspace::ColorspaceConv) assumes,
+//! convert frames with [`YuvToRgb`] and the matrix and range the video uses.
+//!
+//! # Example
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_image::yuv::{YuvMatrix, YuvPlane, YuvRange, YuvToRgb};
+//!
+//! // a 4x2 frame, the luma rows are padded to 8 bytes
+//! let y = [16; 16];
+//! let uv = [128; 4];
+//!
+//! let mut image = Image::from_nv12(YuvPlane::new(&y, 8), YuvPlane::new(&uv, 4), 4, 2).unwrap();
+//! YuvToRgb::new(YuvMatrix::Bt709, YuvRange::Limited).execute(&mut image).unwrap();
+//!
+//! assert_eq!(image.colorspace(), ColorSpace::RGB);
+//! assert_eq!(image.flatten_to_u8()[0][..3], [0, 0, 0]);
+//! ```
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use zune_core::bit_depth::{BitDepth, BitType};
+use zune_core::colorspace::ColorSpace;
+
+use crate::channel::Channel;
+use crate::errors::ImageErrors;
+use crate::image::Image;
+use crate::traits::OperationsTrait;
+
+/// One plane of a YUV frame
+#[derive(Copy, Clone, Debug)]
+pub struct YuvPlane<'a> {
+    data:   &'a [u8],
+    stride: usize
+}
+
+impl<'a> YuvPlane<'a> {
+    /// Create a plane whose rows start every `stride` bytes of `data`
+    ///
+    /// The stride is also called the pitch or line size, it's at least the width of
+    /// the plane in bytes, more if rows are padded.
+    pub const fn new(data: &'a [u8], stride: usize) -> YuvPlane<'a> {
+        YuvPlane { data, stride }
+    }
+
+    /// Return the row `y`, of `width` bytes
+    fn row(&self, y: usize, width: usize) -> &'a [u8] {
+        &self.data[y * self.stride..][..width]
+    }
+
+    fn check(&self, name: &str, width: usize, height: usize) -> Result<(), ImageErrors> {
+        if self.stride < width {
+            return Err(ImageErrors::GenericString(format!(
+                "{name} plane stride {} is less than its width {width}",
+                self.stride
+            )));
+        }
+        // the last row may not be padded
+        let expected = height.saturating_sub(1) * self.stride + width;
+
+        if height > 0 && self.data.len() < expected {
+            return Err(ImageErrors::GenericString(format!(
+                "{name} plane has {} bytes, expected at least {expected}",
+                self.data.len()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// How chroma is sampled from a plane
+struct Chroma {
+    /// Distance between samples of the same channel in a row
+    step:    usize,
+    /// Position of the first sample in a row
+    offset:  usize,
+    /// Chroma rows cover `1 << y_shift` luma rows
+    y_shift: usize
+}
+
+impl Image {
+    /// Create an image from planar YUV 4:2:0 (I420), chroma planes have half
+    /// the width and height of the luma plane, rounded up
+    ///
+    /// # Errors
+    /// If a plane is too small for the dimensions
+    pub fn from_yuv420p(
+        y: YuvPlane, u: YuvPlane, v: YuvPlane, width: usize, height: usize
+    ) -> Result<Image, ImageErrors> {
+        let planar = Chroma {
+            step:    1,
+            offset:  0,
+            y_shift: 1
+        };
+        yuv_image(y, [(u, &planar), (v, &planar)], width, height)
+    }
+
+    /// Create an image from semi-planar YUV 4:2:0 (NV12), the chroma plane stores U and V
+    /// interleaved, with half the width and height of the luma plane, rounded up
+    ///
+    /// # Errors
+    /// If a plane is too small for the dimensions
+    pub fn from_nv12(
+        y: YuvPlane, uv: YuvPlane, width: usize, height: usize
+    ) -> Result<Image, ImageErrors> {
+        let u = Chroma {
+            step:    2,
+            offset:  0,
+            y_shift: 1
+        };
+        let v = Chroma { offset: 1, ..u };
+        yuv_image(y, [(uv, &u), (uv, &v)], width, height)
+    }
+
+    /// Create an image from planar YUV 4:2:2, chroma planes have half the width of the
+    /// luma plane, rounded up, and the same height
+    ///
+    /// # Errors
+    /// If a plane is too small for the dimensions
+    pub fn from_yuv422p(
+        y: YuvPlane, u: YuvPlane, v: YuvPlane, width: usize, height: usize
+    ) -> Result<Image, ImageErrors> {
+        let planar = Chroma {
+            step:    1,
+            offset:  0,
+            y_shift: 0
+        };
+        yuv_image(y, [(u, &planar), (v, &planar)], width, height)
+    }
+}
+
+fn yuv_image(
+    y: YuvPlane, chroma: [(YuvPlane, &Chroma); 2], width: usize, height: usize
+) -> Result<Image, ImageErrors> {
+    y.check("Y", width, height)?;
+
+    let mut luma = Vec::with_capacity(width * height);
+    for row in 0..height {
+        luma.extend_from_slice(y.row(row, width));
+    }
+    let mut channels = vec![Channel::from_vec(luma)];
+
+    for ((plane, sampling), name) in chroma.into_iter().zip(["U", "V"]) {
+        // chroma has half the width, rounded up
+        let chroma_width = width.div_ceil(2);
+        let chroma_height = height.div_ceil(1 << sampling.y_shift);
+        let row_width = (chroma_width - 1) * sampling.step + sampling.offset + 1;
+
+        if width > 0 {
+            plane.check(name, row_width, chroma_height)?;
+        }
+        let mut values = Vec::with_capacity(width * height);
+
+        for row in 0..height {
+            let row = plane.row(row >> sampling.y_shift, row_width);
+            values.extend((0..width).map(|x| row[(x / 2) * sampling.step + sampling.offset]));
+        }
+        channels.push(Channel::from_vec(values));
+    }
+    Ok(Image::new(
+        channels,
+        BitDepth::Eight,
+        width,
+        height,
+        ColorSpace::YCbCr
+    ))
+}
+
+/// The coefficients used to go from RGB to YUV
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum YuvMatrix {
+    /// ITU-R BT.601, standard definition video and JPEG
+    Bt601,
+    /// ITU-R BT.709, high definition video
+    #[default]
+    Bt709,
+    /// ITU-R BT.2020, ultra high definition video
+    Bt2020
+}
+
+impl YuvMatrix {
+    /// The red and blue luma coefficients, `Kr` and `Kb`
+    const fn coefficients(self) -> (f32, f32) {
+        match self {
+            YuvMatrix::Bt601 => (0.299, 0.114),
+            YuvMatrix::Bt709 => (0.2126, 0.0722),
+            YuvMatrix::Bt2020 => (0.2627, 0.0593)
+        }
+    }
+}
+
+/// The range of YUV values
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum YuvRange {
+    /// Luma is in 16..=235 and chroma in 16..=240, scaled for deeper depths,
+    /// also called TV or MPEG range, used by most video
+    #[default]
+    Limited,
+    /// Every value is used, also called PC or JPEG range
+    Full
+}
+
+/// Convert a YCbCr image holding video YUV to RGB
+///
+/// Unlike converting the colorspace, which assumes the full range BT.601
+/// YCbCr of JPEG, this uses the matrix and range the video was encoded with,
+/// usually BT.709 limited range for HD video and BT.601 limited range for SD video.
+///
+/// Values are clamped to the RGB range and the depth is kept.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct YuvToRgb {
+    matrix: YuvMatrix,
+    range:  YuvRange
+}
+
+impl YuvToRgb {
+    /// Create a new conversion for YUV encoded with `matrix` and `range`
+    #[must_use]
+    pub fn new(matrix: YuvMatrix, range: YuvRange) -> YuvToRgb {
+        YuvToRgb { matrix, range }
+    }
+}
+
+impl OperationsTrait for YuvToRgb {
+    fn name(&self) -> &'static str {
+        "YUV to RGB"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (kr, kb) = self.matrix.coefficients();
+        let kg = 1.0 - kr - kb;
+
+        // offsets and scales of values in 0..=1, from 8 bit values
+        let (y_offset, y_scale, c_scale) = match self.range {
+            YuvRange::Limited => (16.0 / 255.0, 255.0 / 219.0, 255.0 / 224.0),
+            YuvRange::Full => (0.0, 1.0, 1.0)
+        };
+        let c_offset = 128.0 / 255.0;
+
+        let convert = |y: f32, u: f32, v: f32| {
+            let y = (y - y_offset) * y_scale;
+            let (u, v) = ((u - c_offset) * c_scale, (v - c_offset) * c_scale);
+
+            let r = y + 2.0 * (1.0 - kr) * v;
+            let b = y + 2.0 * (1.0 - kb) * u;
+            let g = (y - kr * r - kb * b) / kg;
+
+            [r, g, b].map(|x| x.clamp(0.0, 1.0))
+        };
+
+        // preserve original depth
+        let depth = image.depth();
+        image.convert_depth(BitDepth::Float32)?;
+
+        for frame in image.frames_mut() {
+            let channels = frame.channels_vec();
+            let (c1, rest) = channels.split_at_mut(1);
+            let (c2, c3) = rest.split_at_mut(1);
+
+            let y = c1[0].reinterpret_as_mut::<f32>()?;
+            let u = c2[0].reinterpret_as_mut::<f32>()?;
+            let v = c3[0].reinterpret_as_mut::<f32>()?;
+
+            for ((y, u), v) in y.iter_mut().zip(u.iter_mut()).zip(v.iter_mut()) {
+                [*y, *u, *v] = convert(*y, *u, *v);
+            }
+        }
+        image.set_colorspace(ColorSpace::RGB);
+        image.convert_depth(depth)?;
+
+        Ok(())
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[ColorSpace::YCbCr]
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::image::Image;
+    use crate::traits::OperationsTrait;
+    use crate::yuv::{YuvMatrix, YuvPlane, YuvRange, YuvToRgb};
+
+    #[test]
+    fn test_yuv420p_odd_dimensions() {
+        // 3x3 luma, 2x2 chroma, luma rows padded to 4 bytes
+        let y = [1, 2, 3, 0, 4, 5, 6, 0, 7, 8, 9];
+        let u = [10, 11, 12, 13];
+        let v = [20, 21, 22, 23];
+
+        let image = Image::from_yuv420p(
+            YuvPlane::new(&y, 4),
+            YuvPlane::new(&u, 2),
+            YuvPlane::new(&v, 2),
+            3,
+            3
+        )
+        .unwrap();
+        let channels = image.flatten_to_u8();
+
+        assert_eq!(image.colorspace(), ColorSpace::YCbCr);
+        assert_eq!(channels[0][..3], [1, 10, 20]);
+        assert_eq!(
+            image.frames_ref()[0].channels_vec_ref()[1]
+                .reinterpret_as::<u8>()
+                .unwrap(),
+            &[10, 10, 11, 10, 10, 11, 12, 12, 13]
+        );
+        assert!(Image::from_yuv420p(
+            YuvPlane::new(&y, 4),
+            YuvPlane::new(&u[..3], 2),
+            YuvPlane::new(&v, 2),
+            3,
+            3
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_nv12_and_yuv422p() {
+        let y = [50; 8];
+        let uv = [1, 2, 3, 4];
+        let image = Image::from_nv12(YuvPlane::new(&y, 4), YuvPlane::new(&uv, 4), 4, 2).unwrap();
+        let channels = image.frames_ref()[0].channels_vec_ref();
+
+        assert_eq!(channels[1].reinterpret_as::<u8>().unwrap(), &[1, 1, 3, 3, 1, 1, 3, 3]);
+        assert_eq!(channels[2].reinterpret_as::<u8>().unwrap(), &[2, 2, 4, 4, 2, 2, 4, 4]);
+
+        let u = [1, 2, 3, 4];
+        let v = [5, 6, 7, 8];
+        let image = Image::from_yuv422p(
+            YuvPlane::new(&y, 4),
+            YuvPlane::new(&u, 2),
+            YuvPlane::new(&v, 2),
+            4,
+            2
+        )
+        .unwrap();
+        let channels = image.frames_ref()[0].channels_vec_ref();
+        assert_eq!(channels[1].reinterpret_as::<u8>().unwrap(), &[1, 1, 2, 2, 3, 3, 4, 4]);
+    }
+
+    #[test]
+    fn test_yuv_to_rgb() {
+        // limited range white and black
+        for (luma, expected) in [(235, 255), (16, 0)] {
+            let y = [luma; 4];
+            let uv = [128; 2];
+
+            let mut image =
+                Image::from_nv12(YuvPlane::new(&y, 2), YuvPlane::new(&uv, 2), 2, 2).unwrap();
+            YuvToRgb::new(YuvMatrix::Bt601, YuvRange::Limited)
+                .execute(&mut image)
+                .unwrap();
+
+            assert_eq!(image.colorspace(), ColorSpace::RGB);
+            assert!(image.flatten_to_u8()[0].iter().all(|x| *x == expected));
+        }
+        // full range red in BT.709
+        let mut image = Image::from_yuv420p(
+            YuvPlane::new(&[54], 1),
+            YuvPlane::new(&[99], 1),
+            YuvPlane::new(&[255], 1),
+            1,
+            1
+        )
+        .unwrap();
+        YuvToRgb::new(YuvMatrix::Bt709, YuvRange::Full)
+            .execute(&mut image)
+            .unwrap();
+        let rgb = &image.flatten_to_u8()[0];
+        assert!(rgb[0] > 250 && rgb[1] < 5 && rgb[2] < 5, "{rgb:?}");
+    }
+}
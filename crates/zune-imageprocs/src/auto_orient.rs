@@ -9,11 +9,13 @@
 //! Perform auto orientation of the image
 //!
 //! This uses the exif orientation tag of an image if it has
-//! it requires the `metadata` feature in order to read exif tags, otherwise
+//! it requires the `exif` feature in order to read exif tags, otherwise
 //! this is a no-op.
+//!
+//! Images can also be oriented while decoding with
+//! [`DecoderOptions::set_auto_orient`](zune_core::options::DecoderOptions::set_auto_orient)
 #![allow(unused_variables, unused_imports)]
 use zune_core::bit_depth::BitType;
-use zune_core::log::warn;
 use zune_image::errors::ImageErrors;
 use zune_image::image::Image;
 use zune_image::traits::OperationsTrait;
@@ -45,69 +47,40 @@ impl OperationsTrait for AutoOrient {
         // to be this orientation
         #[cfg(feature = "exif")]
         {
-            use exif::{Tag, Value};
-
-            if let Some(data) = image.metadata().clone().exif() {
-                for field in data {
-                    // look for the orientation tag
-                    if field.tag == Tag::Orientation {
-                        match &field.value {
-                            Value::Short(bytes) => {
-                                if bytes.is_empty() {
-                                    warn!("The exif value is empty, cannot orient");
-                                    return Ok(());
-                                }
-                                let byte = bytes[0];
-                                match byte {
-                                    1 => (), // orientation is okay
-                                    2 => {
-                                        Flip::new(FlipDirection::Horizontal).execute(image)?;
-                                    }
-
-                                    3 => {
-                                        Rotate::new(180.0).execute(image)?;
-                                    }
-                                    4 => {
-                                        Flip::new(FlipDirection::Vertical).execute(image)?;
-                                    }
-                                    5 => {
-                                        Transpose::new().execute_impl(image)?;
-                                    }
-                                    6 => {
-                                        Rotate::new(90.0).execute(image)?;
-                                    }
-                                    7 => {
-                                        Rotate::new(270.0).execute(image)?;
-                                        Flip::new(FlipDirection::Horizontal).execute(image)?;
-                                    }
-                                    8 => {
-                                        Rotate::new(270.0).execute(image)?;
-                                    }
+            use zune_image::metadata::ExifOrientation;
 
-                                    _ => {
-                                        warn!(
-                                            "Unknown exif orientation tag {:?}, ignoring it",
-                                            &field.value
-                                        );
-                                    }
-                                }
-                            }
-                            _ => {
-                                warn!("Invalid exif orientation type, ignoring it");
-                            }
-                        }
-                    }
+            let Some(orientation) = image.metadata().orientation() else {
+                return Ok(());
+            };
+            match orientation {
+                ExifOrientation::Normal => (),
+                ExifOrientation::FlipHorizontal => {
+                    Flip::new(FlipDirection::Horizontal).execute(image)?;
                 }
-            }
-            // update exif
-            if let Some(data) = image.metadata_mut().exif_mut() {
-                for field in data {
-                    // set orientation to do nothing
-                    if field.tag == Tag::Orientation {
-                        field.value = Value::Byte(vec![1]);
-                    }
+                ExifOrientation::Rotate180 => {
+                    Rotate::new(180.0).execute(image)?;
+                }
+                ExifOrientation::FlipVertical => {
+                    Flip::new(FlipDirection::Vertical).execute(image)?;
+                }
+                ExifOrientation::Transpose => {
+                    Transpose::new().execute_impl(image)?;
+                }
+                ExifOrientation::Rotate90 => {
+                    Rotate::new(90.0).execute(image)?;
+                }
+                ExifOrientation::Transverse => {
+                    Rotate::new(270.0).execute(image)?;
+                    Flip::new(FlipDirection::Horizontal).execute(image)?;
+                }
+                ExifOrientation::Rotate270 => {
+                    Rotate::new(270.0).execute(image)?;
                 }
             }
+            // update exif so the orientation isn't applied twice
+            image
+                .metadata_mut()
+                .set_orientation(ExifOrientation::Normal);
         }
         Ok(())
     }
@@ -10,6 +10,11 @@
 //!
 //!  A description can be found [here](https://homepages.inf.ed.ac.uk/rbf/CVonline/LOCAL_COPIES/MANDUCHI1/Bilateral_Filtering.html)
 //!
+use alloc::vec;
+use alloc::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
 use zune_core::bit_depth::BitType;
 use zune_image::channel::Channel;
 use zune_image::errors::ImageErrors;
@@ -44,7 +49,8 @@ use crate::utils::execute_on;
 pub struct BilateralFilter {
     d:           i32,
     sigma_color: f32,
-    sigma_space: f32
+    sigma_space: f32,
+    pad_method:  PadMethod
 }
 
 impl BilateralFilter {
@@ -65,9 +71,16 @@ impl BilateralFilter {
         BilateralFilter {
             d,
             sigma_color,
-            sigma_space
+            sigma_space,
+            pad_method: PadMethod::Replicate
         }
     }
+    /// Set how pixels outside the image are computed, defaults to [PadMethod::Replicate]
+    #[must_use]
+    pub fn set_pad_method(mut self, pad_method: PadMethod) -> Self {
+        self.pad_method = pad_method;
+        self
+    }
 }
 
 impl OperationsTrait for BilateralFilter {
@@ -99,14 +112,16 @@ impl OperationsTrait for BilateralFilter {
                     new_channel.reinterpret_as_mut()?,
                     w,
                     h,
-                    &coeffs
+                    &coeffs,
+                    self.pad_method
                 ),
                 BitType::U16 => bilateral_filter_int::<u16>(
                     channel.reinterpret_as()?,
                     new_channel.reinterpret_as_mut()?,
                     w,
                     h,
-                    &coeffs
+                    &coeffs,
+                    self.pad_method
                 ),
 
                 d => {
@@ -178,15 +193,16 @@ fn init_bilateral(
 }
 
 fn bilateral_filter_int<T>(
-    src: &[T], dest: &mut [T], width: usize, height: usize, coeffs: &BilateralCoeffs
+    src: &[T], dest: &mut [T], width: usize, height: usize, coeffs: &BilateralCoeffs,
+    pad_method: PadMethod
 ) where
-    T: Copy + NumOps<T> + Default,
-    i32: std::convert::From<T>
+    T: Copy + NumOps<T> + Default + Send + Sync,
+    i32: core::convert::From<T>
 {
     let radius = coeffs.radius;
 
     //pad here
-    let padded_input = pad(src, width, height, radius, radius, PadMethod::Replicate);
+    let padded_input = pad(src, width, height, radius, radius, pad_method);
 
     // use an inner lambda to implement the bilateral loop as it allows us to borrow
     // surrounding variables
@@ -11,12 +11,18 @@
 //! ```
 //! `src_alpha` is expected to be between 0.0 and 1.0
 //!
+//! RGB images can optionally be blended in [OKLab](ColorSpace::OKLab), which gives perceptually
+//! even transitions between colors
+//!
 use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::core_filters::colorspace::oklab::{oklab_to_srgb, srgb_to_oklab};
 use zune_image::errors::ImageErrors;
 use zune_image::image::Image;
 use zune_image::traits::OperationsTrait;
 
 use crate::traits::NumOps;
+use crate::utils::round_to_depth;
 
 /// Create a blend image filter which
 /// can blend two images based on a configurable alpha
@@ -51,7 +57,8 @@ use crate::traits::NumOps;
 ///
 pub struct Blend<'src> {
     image: &'src Image,
-    alpha: f32
+    alpha: f32,
+    oklab: bool
 }
 
 impl<'src> Blend<'src> {
@@ -65,8 +72,81 @@ impl<'src> Blend<'src> {
     pub fn new(image: &'src Image, src_alpha: f32) -> Blend<'src> {
         Blend {
             image,
-            alpha: src_alpha
+            alpha: src_alpha,
+            oklab: false
+        }
+    }
+    /// Blend colors in OKLab instead of blending each channel separately
+    ///
+    /// This requires RGB or RGBA images, default is false
+    #[must_use]
+    pub fn set_oklab(mut self, oklab: bool) -> Self {
+        self.oklab = oklab;
+        self
+    }
+}
+
+impl Blend<'_> {
+    fn blend_oklab(&self, image: &mut Image, b_type: BitType) -> Result<(), ImageErrors> {
+        let colorspace = image.colorspace();
+
+        if !matches!(colorspace, ColorSpace::RGB | ColorSpace::RGBA) {
+            return Err(ImageErrors::GenericStr(
+                "OKLab blending requires RGB or RGBA images"
+            ));
+        }
+        for (src_frame, d_frame) in self.image.frames_ref().iter().zip(image.frames_mut()) {
+            let src = src_frame.channels_ref(colorspace, true);
+            let dest = d_frame.channels_mut(colorspace, true);
+            let (r, rest) = dest.split_at_mut(1);
+            let (g, b) = rest.split_at_mut(1);
+
+            match b_type {
+                BitType::U8 => blend_oklab::<u8>(
+                    [
+                        src[0].reinterpret_as()?,
+                        src[1].reinterpret_as()?,
+                        src[2].reinterpret_as()?
+                    ],
+                    [
+                        r[0].reinterpret_as_mut()?,
+                        g[0].reinterpret_as_mut()?,
+                        b[0].reinterpret_as_mut()?
+                    ],
+                    self.alpha
+                ),
+                BitType::U16 => blend_oklab::<u16>(
+                    [
+                        src[0].reinterpret_as()?,
+                        src[1].reinterpret_as()?,
+                        src[2].reinterpret_as()?
+                    ],
+                    [
+                        r[0].reinterpret_as_mut()?,
+                        g[0].reinterpret_as_mut()?,
+                        b[0].reinterpret_as_mut()?
+                    ],
+                    self.alpha
+                ),
+                BitType::F32 => blend_oklab::<f32>(
+                    [
+                        src[0].reinterpret_as()?,
+                        src[1].reinterpret_as()?,
+                        src[2].reinterpret_as()?
+                    ],
+                    [
+                        r[0].reinterpret_as_mut()?,
+                        g[0].reinterpret_as_mut()?,
+                        b[0].reinterpret_as_mut()?
+                    ],
+                    self.alpha
+                ),
+                d => {
+                    return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d));
+                }
+            }
         }
+        Ok(())
     }
 }
 
@@ -99,6 +179,10 @@ impl<'src> OperationsTrait for Blend<'src> {
 
         let b_type = image.depth().bit_type();
 
+        if self.oklab {
+            return self.blend_oklab(image, b_type);
+        }
+
         for (src_chan, d_chan) in self
             .image
             .channels_ref(true)
@@ -137,7 +221,7 @@ impl<'src> OperationsTrait for Blend<'src> {
 
 pub fn blend_single_channel<T>(src: &[T], dest: &mut [T], src_alpha: f32)
 where
-    f32: std::convert::From<T>,
+    f32: core::convert::From<T>,
     T: Copy + NumOps<T>
 {
     if src_alpha <= 0.0 {
@@ -155,3 +239,57 @@ where
         *dest = T::from_f32((src_alpha * f32::from(*src)) + (dest_alpha * f32::from(*dest)));
     }
 }
+
+/// Blend the RGB channels of two images in OKLab
+///
+/// # Arguments
+/// - src: The R, G and B channels of the source image
+/// - dest: The R, G and B channels of the destination image, modified in place
+/// - src_alpha: The opacity of the source, between 0 and 1
+pub fn blend_oklab<T: Copy + NumOps<T>>(src: [&[T]; 3], dest: [&mut [T]; 3], src_alpha: f32) {
+    let src_alpha = src_alpha.clamp(0.0, 1.0);
+    let max = T::MAX_VAL.to_f32();
+    let [sr, sg, sb] = src;
+    let [dr, dg, db] = dest;
+
+    for (((sr, sg), sb), ((dr, dg), db)) in sr
+        .iter()
+        .zip(sg)
+        .zip(sb)
+        .zip(dr.iter_mut().zip(dg.iter_mut()).zip(db.iter_mut()))
+    {
+        let src_lab = srgb_to_oklab([sr.to_f32() / max, sg.to_f32() / max, sb.to_f32() / max]);
+        let dest_lab = srgb_to_oklab([dr.to_f32() / max, dg.to_f32() / max, db.to_f32() / max]);
+
+        let mixed = [0, 1, 2].map(|i| src_alpha * src_lab[i] + (1.0 - src_alpha) * dest_lab[i]);
+        [*dr, *dg, *db] = oklab_to_srgb(mixed).map(|x| round_to_depth(x * max));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::blend::Blend;
+
+    #[test]
+    fn test_blend_oklab_endpoints() {
+        let src = Image::from_u8(&[255, 0, 0], 1, 1, ColorSpace::RGB);
+        let mut dest = Image::from_u8(&[0, 0, 255], 1, 1, ColorSpace::RGB);
+
+        Blend::new(&src, 1.0)
+            .set_oklab(true)
+            .execute(&mut dest)
+            .unwrap();
+        assert_eq!(dest.flatten_frames::<u8>()[0], [255, 0, 0]);
+
+        let mut luma = Image::fill(0_u8, ColorSpace::Luma, 1, 1);
+        let src = Image::fill(0_u8, ColorSpace::Luma, 1, 1);
+        assert!(Blend::new(&src, 0.5)
+            .set_oklab(true)
+            .execute(&mut luma)
+            .is_err());
+    }
+}
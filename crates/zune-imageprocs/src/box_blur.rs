@@ -11,17 +11,23 @@
 /// in it's window and divides it by the window size
 ///
 /// This approximates a `mean` of the window  and sets it as the output
-use std::f32;
+use alloc::vec;
+use core::f32;
 
 use zune_core::bit_depth::BitType;
 use zune_core::log::{trace, warn};
 use zune_image::errors::ImageErrors;
 use zune_image::image::Image;
-use zune_image::traits::OperationsTrait;
+use zune_image::metadata::AlphaState;
+use zune_image::traits::{OperationsTrait, Parameter, ParameterKind, ParameterValue};
 
 use crate::mathops::{compute_mod_u32, fastdiv_u32};
+use crate::pad::{filter_with_border, PadMethod};
 use crate::traits::NumOps;
 use crate::transpose;
+use crate::utils::{execute_on, for_each_row_band};
+
+pub(crate) mod neon;
 
 /// Perform a box blur
 ///
@@ -32,8 +38,10 @@ use crate::transpose;
 ///
 /// This operation is multithreaded capable
 #[derive(Default)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct BoxBlur {
-    radius: usize
+    radius:     usize,
+    pad_method: PadMethod
 }
 
 impl BoxBlur {
@@ -43,7 +51,16 @@ impl BoxBlur {
     /// - radius: The radius of the blur, larger the value the more pronounced the blur
     #[must_use]
     pub fn new(radius: usize) -> BoxBlur {
-        BoxBlur { radius }
+        BoxBlur {
+            radius,
+            pad_method: PadMethod::Replicate
+        }
+    }
+    /// Set how pixels outside the image are computed, defaults to [PadMethod::Replicate]
+    #[must_use]
+    pub fn set_pad_method(mut self, pad_method: PadMethod) -> Self {
+        self.pad_method = pad_method;
+        self
     }
 }
 
@@ -52,87 +69,91 @@ impl OperationsTrait for BoxBlur {
         "Box blur"
     }
 
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            name:        "radius",
+            description: "Radius of the blur",
+            kind:        ParameterKind::Integer { min: 0, max: 100 },
+            default:     ParameterValue::Integer(1)
+        }]
+    }
+
     fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
         let (width, height) = image.dimensions();
 
         let depth = image.depth();
 
-        #[cfg(feature = "threads")]
-        {
-            trace!("Running box blur in multithreaded mode");
-            std::thread::scope(|s| {
-                let mut errors = vec![];
-                // blur each channel on a separate thread
-                for channel in image.channels_mut(false) {
-                    let result = s.spawn(|| match depth.bit_type() {
-                        BitType::U16 => {
-                            let mut scratch_space = vec![0; width * height];
-                            let data = channel.reinterpret_as_mut::<u16>()?;
-                            box_blur_u16(data, &mut scratch_space, width, height, self.radius);
-                            Ok(())
-                        }
-                        BitType::U8 => {
-                            let mut scratch_space = vec![0; width * height];
-                            let data = channel.reinterpret_as_mut::<u8>()?;
-                            box_blur_u8(data, &mut scratch_space, width, height, self.radius);
-                            Ok(())
-                        }
-
-                        BitType::F32 => {
-                            let mut scratch_space = vec![0.0; width * height];
-                            let data = channel.reinterpret_as_mut::<f32>()?;
-                            box_blur_f32(data, &mut scratch_space, width, height, self.radius);
-                            Ok(())
-                        }
-                        d => return Err(ImageErrors::ImageOperationNotImplemented("box_blur", d))
-                    });
-                    errors.push(result);
-                }
-                errors
-                    .into_iter()
-                    .map(|x| x.join().unwrap())
-                    .collect::<Result<Vec<()>, ImageErrors>>()
-            })?;
-        }
-        #[cfg(not(feature = "threads"))]
-        {
-            trace!("Running box blur in single threaded mode");
+        trace!("Running box blur");
 
-            match depth.bit_type() {
-                BitType::U16 => {
-                    let mut scratch_space = vec![0; width * height];
+        // even radii are bumped to the next odd one, and the window
+        // of a pixel reaches one pixel further on the right
+        let reach = self.radius + 2;
 
-                    for channel in image.channels_mut(false) {
+        // channels are blurred in parallel with the threads feature,
+        // so each one gets its own scratch space
+        execute_on(
+            |channel| {
+                match depth.bit_type() {
+                    BitType::U16 => {
+                        let mut scratch_space = vec![0; width * height];
                         let data = channel.reinterpret_as_mut::<u16>()?;
-                        box_blur_u16(data, &mut scratch_space, width, height, self.radius);
+                        filter_with_border(
+                            data,
+                            &mut scratch_space,
+                            width,
+                            height,
+                            reach,
+                            self.pad_method,
+                            |data, scratch, width, height| {
+                                box_blur_u16(data, scratch, width, height, self.radius);
+                            }
+                        );
                     }
-                }
-                BitType::U8 => {
-                    let mut scratch_space = vec![0; width * height];
-
-                    for channel in image.channels_mut(false) {
+                    BitType::U8 => {
+                        let mut scratch_space = vec![0; width * height];
                         let data = channel.reinterpret_as_mut::<u8>()?;
-                        box_blur_u8(data, &mut scratch_space, width, height, self.radius);
+                        filter_with_border(
+                            data,
+                            &mut scratch_space,
+                            width,
+                            height,
+                            reach,
+                            self.pad_method,
+                            |data, scratch, width, height| {
+                                box_blur_u8(data, scratch, width, height, self.radius);
+                            }
+                        );
                     }
-                }
-
-                BitType::F32 => {
-                    let mut scratch_space = vec![0.0; width * height];
-
-                    for channel in image.channels_mut(false) {
+                    BitType::F32 => {
+                        let mut scratch_space = vec![0.0; width * height];
                         let data = channel.reinterpret_as_mut::<f32>()?;
-                        box_blur_f32(data, &mut scratch_space, width, height, self.radius);
+                        filter_with_border(
+                            data,
+                            &mut scratch_space,
+                            width,
+                            height,
+                            reach,
+                            self.pad_method,
+                            |data, scratch, width, height| {
+                                box_blur_f32(data, scratch, width, height, self.radius);
+                            }
+                        );
                     }
+                    d => return Err(ImageErrors::ImageOperationNotImplemented("box_blur", d))
                 }
-                d => return Err(ImageErrors::ImageOperationNotImplemented("box_blur", d))
-            }
-        }
-
-        Ok(())
+                Ok(())
+            },
+            image,
+            false
+        )
     }
     fn supported_types(&self) -> &'static [BitType] {
         &[BitType::U8, BitType::U16, BitType::F32]
     }
+
+    fn alpha_state(&self) -> Option<AlphaState> {
+        Some(AlphaState::PreMultiplied)
+    }
 }
 
 pub fn box_blur_u16(
@@ -147,6 +168,20 @@ pub fn box_blur_u16(
         radius += 1;
     }
     box_blur_inner(in_out_image, scratch_space, width, radius);
+
+    #[cfg(all(feature = "neon", target_arch = "aarch64", target_feature = "neon"))]
+    if height > radius * 2 {
+        // blur the columns in place of transposing
+        unsafe {
+            return neon::box_blur_vertical_neon_u16(
+                scratch_space,
+                in_out_image,
+                width,
+                height,
+                radius
+            );
+        }
+    }
     transpose::transpose_u16(scratch_space, in_out_image, width, height);
     box_blur_inner(in_out_image, scratch_space, height, radius);
     transpose::transpose_u16(scratch_space, in_out_image, height, width);
@@ -165,6 +200,20 @@ pub fn box_blur_u8(
         radius += 1;
     }
     box_blur_inner(in_out_image, scratch_space, width, radius);
+
+    #[cfg(all(feature = "neon", target_arch = "aarch64", target_feature = "neon"))]
+    if height > radius * 2 {
+        // blur the columns in place of transposing
+        unsafe {
+            return neon::box_blur_vertical_neon_u8(
+                scratch_space,
+                in_out_image,
+                width,
+                height,
+                radius
+            );
+        }
+    }
     transpose::transpose_u8(scratch_space, in_out_image, width, height);
     box_blur_inner(in_out_image, scratch_space, height, radius);
     transpose::transpose_u8(scratch_space, in_out_image, height, width);
@@ -190,8 +239,8 @@ pub fn box_blur_f32(
 #[allow(clippy::cast_possible_truncation, clippy::too_many_lines)]
 pub(crate) fn box_blur_inner<T>(in_image: &[T], out_image: &mut [T], width: usize, radius: usize)
 where
-    T: Copy + NumOps<T>,
-    u32: std::convert::From<T>
+    T: Copy + NumOps<T> + Send + Sync,
+    u32: core::convert::From<T>
 {
     let diameter = (radius * 2) + 1;
     // 1D-Box blurs can be seen as the average of radius pixels iterating
@@ -226,74 +275,77 @@ where
     let diameter = diameter.min(width);
     let m_radius = compute_mod_u32(diameter as u64);
 
-    for (stride_in, stride_out) in in_image
-        .chunks_exact(width)
-        .zip(out_image.chunks_exact_mut(width))
-    {
-        let half_radius = (diameter + 1) / 2;
-
-        let mut accumulator: u32 = stride_in[..half_radius].iter().map(|x| u32::from(*x)).sum();
+    // rows are independent, so bands of them can be blurred in parallel
+    for_each_row_band(out_image, width, |first_row, out_band| {
+        for (stride_in, stride_out) in in_image[first_row * width..]
+            .chunks_exact(width)
+            .zip(out_band.chunks_exact_mut(width))
+        {
+            let half_radius = diameter.div_ceil(2);
 
-        accumulator += ((half_radius - 1) as u32) * u32::from(stride_in[0]);
+            let mut accumulator: u32 = stride_in[..half_radius].iter().map(|x| u32::from(*x)).sum();
 
-        for (data_in, data_out) in stride_in[half_radius..]
-            .iter()
-            .zip(stride_out.iter_mut())
-            .take(half_radius - 1)
-        {
-            accumulator += u32::from(*data_in);
-            accumulator -= u32::from(stride_in[0]);
+            accumulator += ((half_radius - 1) as u32) * u32::from(stride_in[0]);
 
-            *data_out = T::from_u32(fastdiv_u32(accumulator, m_radius));
-        }
+            for (data_in, data_out) in stride_in[half_radius..]
+                .iter()
+                .zip(stride_out.iter_mut())
+                .take(half_radius - 1)
+            {
+                accumulator += u32::from(*data_in);
+                accumulator -= u32::from(stride_in[0]);
 
-        // testing purposes
-        //
-        // assert_eq!(
-        //     accumulator,
-        //     stride_in[..diameter]
-        //         .iter()
-        //         .map(|x| u32::from(*x))
-        //         .sum::<u32>()
-        // );
-
-        let mut window_slide = u32::from(stride_in[0]);
-
-        for (window_in, data_out) in stride_in[1..]
-            .windows(diameter)
-            .zip(stride_out[half_radius - 1..].iter_mut())
-        {
-            accumulator -= window_slide;
-            accumulator += u32::from(*window_in.last().unwrap());
+                *data_out = T::from_u32(fastdiv_u32(accumulator, m_radius));
+            }
 
             // testing purposes
             //
             // assert_eq!(
             //     accumulator,
-            //     window_in.iter().map(|x| u32::from(*x)).sum::<u32>()
+            //     stride_in[..diameter]
+            //         .iter()
+            //         .map(|x| u32::from(*x))
+            //         .sum::<u32>()
             // );
 
-            window_slide = u32::from(window_in[0]);
+            let mut window_slide = u32::from(stride_in[0]);
 
-            *data_out = T::from_u32(fastdiv_u32(accumulator, m_radius));
-        }
+            for (window_in, data_out) in stride_in[1..]
+                .windows(diameter)
+                .zip(stride_out[half_radius - 1..].iter_mut())
+            {
+                accumulator -= window_slide;
+                accumulator += u32::from(*window_in.last().unwrap());
 
-        let edge_len = stride_out.len() - half_radius;
+                // testing purposes
+                //
+                // assert_eq!(
+                //     accumulator,
+                //     window_in.iter().map(|x| u32::from(*x)).sum::<u32>()
+                // );
 
-        let end_stride = &mut stride_out[edge_len..];
-        let last_item = u32::from(*stride_in.last().unwrap());
+                window_slide = u32::from(window_in[0]);
 
-        for (data_in, data_out) in stride_in[edge_len..]
-            .iter()
-            .zip(end_stride)
-            .take(half_radius)
-        {
-            accumulator = accumulator.wrapping_sub(u32::from(*data_in));
-            accumulator = accumulator.wrapping_add(last_item);
+                *data_out = T::from_u32(fastdiv_u32(accumulator, m_radius));
+            }
+
+            let edge_len = stride_out.len() - half_radius;
 
-            *data_out = T::from_u32(fastdiv_u32(accumulator, m_radius));
+            let end_stride = &mut stride_out[edge_len..];
+            let last_item = u32::from(*stride_in.last().unwrap());
+
+            for (data_in, data_out) in stride_in[edge_len..]
+                .iter()
+                .zip(end_stride)
+                .take(half_radius)
+            {
+                accumulator = accumulator.wrapping_sub(u32::from(*data_in));
+                accumulator = accumulator.wrapping_add(last_item);
+
+                *data_out = T::from_u32(fastdiv_u32(accumulator, m_radius));
+            }
         }
-    }
+    });
 }
 #[allow(
     clippy::cast_possible_truncation,
@@ -312,59 +364,63 @@ pub(crate) fn box_blur_f32_inner(
     let diameter = diameter.min(width);
     let recip = 1.0 / diameter as f32;
 
-    for (stride_in, stride_out) in in_image
-        .chunks_exact(width)
-        .zip(out_image.chunks_exact_mut(width))
-    {
-        let half_radius = (diameter + 1) / 2;
+    // rows are independent, so bands of them can be blurred in parallel
+    for_each_row_band(out_image, width, |first_row, out_band| {
+        for (stride_in, stride_out) in in_image[first_row * width..]
+            .chunks_exact(width)
+            .zip(out_band.chunks_exact_mut(width))
+        {
+            let half_radius = diameter.div_ceil(2);
 
-        let mut accumulator: f32 = stride_in[..half_radius].iter().copied().sum();
+            let mut accumulator: f32 = stride_in[..half_radius].iter().copied().sum();
 
-        accumulator += (half_radius - 1) as f32 * stride_in[0];
+            accumulator += (half_radius - 1) as f32 * stride_in[0];
 
-        for (data_in, data_out) in stride_in[half_radius..]
-            .iter()
-            .zip(stride_out.iter_mut())
-            .take(half_radius - 1)
-        {
-            accumulator += *data_in;
-            accumulator -= stride_in[0];
+            for (data_in, data_out) in stride_in[half_radius..]
+                .iter()
+                .zip(stride_out.iter_mut())
+                .take(half_radius - 1)
+            {
+                accumulator += *data_in;
+                accumulator -= stride_in[0];
 
-            *data_out = accumulator * recip;
-        }
+                *data_out = accumulator * recip;
+            }
 
-        let mut window_slide = stride_in[0];
+            let mut window_slide = stride_in[0];
 
-        for (window_in, data_out) in stride_in[1..]
-            .windows(diameter)
-            .zip(stride_out[half_radius - 1..].iter_mut())
-        {
-            accumulator -= window_slide;
-            accumulator += *window_in.last().unwrap();
+            for (window_in, data_out) in stride_in[1..]
+                .windows(diameter)
+                .zip(stride_out[half_radius - 1..].iter_mut())
+            {
+                accumulator -= window_slide;
+                accumulator += *window_in.last().unwrap();
 
-            window_slide = window_in[0];
+                window_slide = window_in[0];
 
-            *data_out = accumulator * recip;
-        }
+                *data_out = accumulator * recip;
+            }
 
-        let edge_len = stride_out.len() - half_radius;
+            let edge_len = stride_out.len() - half_radius;
 
-        let end_stride = &mut stride_out[edge_len..];
-        let last_item = *stride_in.last().unwrap();
+            let end_stride = &mut stride_out[edge_len..];
+            let last_item = *stride_in.last().unwrap();
 
-        for (data_in, data_out) in stride_in[edge_len..]
-            .iter()
-            .zip(end_stride)
-            .take(half_radius)
-        {
-            accumulator -= *data_in;
-            accumulator += last_item;
+            for (data_in, data_out) in stride_in[edge_len..]
+                .iter()
+                .zip(end_stride)
+                .take(half_radius)
+            {
+                accumulator -= *data_in;
+                accumulator += last_item;
 
-            *data_out = accumulator * recip;
+                *data_out = accumulator * recip;
+            }
         }
-    }
+    });
 }
 
+
 #[cfg(feature = "benchmarks")]
 #[cfg(test)]
 mod benchmarks {
@@ -0,0 +1,227 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+#![cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+#![cfg(feature = "neon")]
+//! NEON vertical box blur
+//!
+//! The scalar blurs run [`box_blur_inner`] over rows, transpose, run it over the
+//! rows of the transposed image (the columns) and transpose back.
+//!
+//! Here the column pass is done directly on the untransposed image, the same
+//! sliding window is run down eight columns at once, one row of eight values
+//! per step, with the running sums kept in two `u32x4` registers.
+//! The division by the window size uses the same fastdiv constant as the scalar code,
+//! split into its low and high 32 bits, so results are the same as the transposing version.
+use alloc::vec;
+use core::arch::aarch64::*;
+
+use crate::box_blur::box_blur_inner;
+use crate::mathops::compute_mod_u32;
+use crate::traits::NumOps;
+
+/// Columns blurred per step
+const COLUMNS: usize = 8;
+
+/// `(m * a) >> 64` per lane, with `m` split into `m_low` and `m_high`
+#[inline(always)]
+unsafe fn fastdiv(a: uint32x4_t, m_low: uint32x4_t, m_high: uint32x4_t) -> uint32x4_t {
+    let low = vshrq_n_u64::<32>(vmull_u32(vget_low_u32(a), vget_low_u32(m_low)));
+    let low = vmlal_u32(low, vget_low_u32(a), vget_low_u32(m_high));
+
+    let high = vshrq_n_u64::<32>(vmull_high_u32(a, m_low));
+    let high = vmlal_high_u32(high, a, m_high);
+
+    vcombine_u32(vshrn_n_u64::<32>(low), vshrn_n_u64::<32>(high))
+}
+
+#[inline(always)]
+unsafe fn add(a: [uint32x4_t; 2], b: [uint32x4_t; 2]) -> [uint32x4_t; 2] {
+    [vaddq_u32(a[0], b[0]), vaddq_u32(a[1], b[1])]
+}
+
+#[inline(always)]
+unsafe fn sub(a: [uint32x4_t; 2], b: [uint32x4_t; 2]) -> [uint32x4_t; 2] {
+    [vsubq_u32(a[0], b[0]), vsubq_u32(a[1], b[1])]
+}
+
+/// Box blur the columns of `in_image` into `out_image`, giving the same result
+/// as blurring the rows of the transposed image with [`box_blur_inner`]
+///
+/// `load` reads eight values as u32's and `store` narrows and writes them back.
+///
+/// The caller must ensure the window fits the column, `2 * radius + 1 <= height`
+#[allow(clippy::cast_possible_truncation)]
+unsafe fn box_blur_vertical<T>(
+    in_image: &[T], out_image: &mut [T], width: usize, height: usize, radius: usize,
+    load: impl Fn(*const T) -> [uint32x4_t; 2], store: impl Fn(*mut T, [uint32x4_t; 2])
+) where
+    T: Copy + Default + NumOps<T> + Send + Sync,
+    u32: From<T>
+{
+    let diameter = (radius * 2) + 1;
+
+    if height <= 1 || diameter <= 1 {
+        // same as box_blur_inner
+        return;
+    }
+    assert!(diameter <= height);
+    // ensure the reads and writes are in bounds
+    assert!(in_image.len() >= width * height);
+    assert!(out_image.len() >= width * height);
+
+    let half_radius = diameter.div_ceil(2);
+    let m_radius = compute_mod_u32(diameter as u64);
+    let m_low = vdupq_n_u32(m_radius as u32);
+    let m_high = vdupq_n_u32((m_radius >> 32) as u32);
+
+    let mut column = 0;
+
+    while column + COLUMNS <= width {
+        let row = |y: usize| load(in_image.as_ptr().add(y * width + column));
+        let mut write = |y: usize, accumulator: [uint32x4_t; 2]| {
+            let values = [
+                fastdiv(accumulator[0], m_low, m_high),
+                fastdiv(accumulator[1], m_low, m_high)
+            ];
+            store(out_image.as_mut_ptr().add(y * width + column), values);
+        };
+
+        let first = row(0);
+        // the window hangs over the top edge, repeat the first row for it
+        let mut accumulator = [
+            vmulq_n_u32(first[0], (half_radius - 1) as u32),
+            vmulq_n_u32(first[1], (half_radius - 1) as u32)
+        ];
+        for y in 0..half_radius {
+            accumulator = add(accumulator, row(y));
+        }
+        for y in 0..half_radius - 1 {
+            accumulator = sub(add(accumulator, row(half_radius + y)), first);
+            write(y, accumulator);
+        }
+        for y in 0..height - diameter {
+            accumulator = add(sub(accumulator, row(y)), row(y + diameter));
+            write(half_radius - 1 + y, accumulator);
+        }
+        // and the bottom edge, repeat the last row
+        let last = row(height - 1);
+
+        for y in height - half_radius..height {
+            accumulator = add(sub(accumulator, row(y)), last);
+            write(y, accumulator);
+        }
+        column += COLUMNS;
+    }
+    if column < width {
+        // leftover columns, blur each one on its own
+        let mut column_in = vec![T::default(); height];
+        let mut column_out = vec![T::default(); height];
+
+        for x in column..width {
+            for (y, value) in column_in.iter_mut().enumerate() {
+                *value = in_image[y * width + x];
+            }
+            box_blur_inner(&column_in, &mut column_out, height, radius);
+
+            for (y, value) in column_out.iter().enumerate() {
+                out_image[y * width + x] = *value;
+            }
+        }
+    }
+}
+
+/// Vertical pass of [`box_blur_u8`](super::box_blur_u8), see [`box_blur_vertical`]
+pub(crate) unsafe fn box_blur_vertical_neon_u8(
+    in_image: &[u8], out_image: &mut [u8], width: usize, height: usize, radius: usize
+) {
+    box_blur_vertical(
+        in_image,
+        out_image,
+        width,
+        height,
+        radius,
+        |ptr| {
+            let values = vmovl_u8(vld1_u8(ptr));
+            [vmovl_u16(vget_low_u16(values)), vmovl_high_u16(values)]
+        },
+        |ptr, values| {
+            let values = vcombine_u16(vmovn_u32(values[0]), vmovn_u32(values[1]));
+            vst1_u8(ptr, vmovn_u16(values));
+        }
+    );
+}
+
+/// Vertical pass of [`box_blur_u16`](super::box_blur_u16), see [`box_blur_vertical`]
+pub(crate) unsafe fn box_blur_vertical_neon_u16(
+    in_image: &[u16], out_image: &mut [u16], width: usize, height: usize, radius: usize
+) {
+    box_blur_vertical(
+        in_image,
+        out_image,
+        width,
+        height,
+        radius,
+        |ptr| {
+            let values = vld1q_u16(ptr);
+            [vmovl_u16(vget_low_u16(values)), vmovl_high_u16(values)]
+        },
+        |ptr, values| {
+            vst1q_u16(
+                ptr,
+                vcombine_u16(vmovn_u32(values[0]), vmovn_u32(values[1]))
+            );
+        }
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use nanorand::Rng;
+
+    use crate::box_blur::box_blur_inner;
+    use crate::box_blur::neon::{box_blur_vertical_neon_u16, box_blur_vertical_neon_u8};
+    use crate::transpose::scalar::transpose_scalar;
+
+    #[test]
+    fn test_box_blur_vertical_neon_matches_transposed() {
+        let mut rng = nanorand::WyRand::new();
+
+        for (width, height, radius) in [(16, 16, 1), (21, 40, 3), (7, 9, 4), (100, 37, 18)] {
+            let mut in_u8 = vec![0_u8; width * height];
+            let mut in_u16 = vec![0_u16; width * height];
+            rng.fill(&mut in_u8);
+            rng.fill(&mut in_u16);
+
+            let mut neon_u8 = vec![0; width * height];
+            let mut neon_u16 = vec![0; width * height];
+            unsafe {
+                box_blur_vertical_neon_u8(&in_u8, &mut neon_u8, width, height, radius);
+                box_blur_vertical_neon_u16(&in_u16, &mut neon_u16, width, height, radius);
+            }
+
+            // transpose, blur the rows and transpose back
+            let mut transposed_u8 = vec![0; width * height];
+            let mut blurred_u8 = vec![0; width * height];
+            let mut scalar_u8 = vec![0; width * height];
+            transpose_scalar(&in_u8, &mut transposed_u8, width, height);
+            box_blur_inner(&transposed_u8, &mut blurred_u8, height, radius);
+            transpose_scalar(&blurred_u8, &mut scalar_u8, height, width);
+
+            let mut transposed_u16 = vec![0; width * height];
+            let mut blurred_u16 = vec![0; width * height];
+            let mut scalar_u16 = vec![0; width * height];
+            transpose_scalar(&in_u16, &mut transposed_u16, width, height);
+            box_blur_inner(&transposed_u16, &mut blurred_u16, height, radius);
+            transpose_scalar(&blurred_u16, &mut scalar_u16, height, width);
+
+            assert_eq!(neon_u8, scalar_u8, "u8 {width}x{height} radius {radius}");
+            assert_eq!(neon_u16, scalar_u16, "u16 {width}x{height} radius {radius}");
+        }
+    }
+}
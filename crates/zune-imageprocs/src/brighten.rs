@@ -28,11 +28,13 @@ use zune_core::colorspace::ColorSpace;
 use zune_image::channel::Channel;
 use zune_image::errors::ImageErrors;
 use zune_image::image::Image;
-use zune_image::traits::OperationsTrait;
+use zune_image::traits::{OperationsTrait, Parameter, ParameterKind, ParameterValue};
 
 use crate::traits::NumOps;
 use crate::utils::execute_on;
 
+pub(crate) mod neon;
+
 /// Brighten struct
 ///
 ///
@@ -53,6 +55,7 @@ use crate::utils::execute_on;
 /// # Ok::<(),ImageErrors>(())
 /// ```
 #[derive(Default)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Brighten {
     value: f32
 }
@@ -74,6 +77,18 @@ impl OperationsTrait for Brighten {
         "Brighten"
     }
 
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            name:        "value",
+            description: "Amount to brighten by, negative values darken",
+            kind:        ParameterKind::Float {
+                min: -1.0,
+                max: 1.0
+            },
+            default:     ParameterValue::Float(0.0)
+        }]
+    }
+
     #[allow(
         clippy::cast_sign_loss,
         clippy::cast_precision_loss,
@@ -85,12 +100,8 @@ impl OperationsTrait for Brighten {
 
         let brighten_fn = |channel: &mut Channel| -> Result<(), ImageErrors> {
             match depth.bit_type() {
-                BitType::U8 => brighten(
-                    channel.reinterpret_as_mut::<u8>()?,
-                    self.value,
-                    u8::try_from(max_val.clamp(0, 255)).unwrap()
-                ),
-                BitType::U16 => brighten(channel.reinterpret_as_mut::<u16>()?, self.value, max_val),
+                BitType::U8 => brighten_u8(channel.reinterpret_as_mut::<u8>()?, self.value),
+                BitType::U16 => brighten_u16(channel.reinterpret_as_mut::<u16>()?, self.value),
                 BitType::F32 => brighten_f32(
                     channel.reinterpret_as_mut::<f32>()?,
                     self.value,
@@ -114,6 +125,9 @@ impl OperationsTrait for Brighten {
     fn supported_types(&self) -> &'static [BitType] {
         &[BitType::U8, BitType::U16, BitType::F32]
     }
+    fn is_point_op(&self) -> bool {
+        true
+    }
 }
 /// Brighten operation
 ///
@@ -138,6 +152,26 @@ pub fn brighten<T: Copy + PartialOrd + NumOps<T> + Default>(
         .for_each(|x| *x = T::from_f32((x.to_f32() + scale_v).zclamp(t_min, t_max)));
 }
 
+/// [`brighten`] for u8's, with NEON on aarch64
+fn brighten_u8(channel: &mut [u8], value: f32) {
+    #[cfg(all(feature = "neon", target_arch = "aarch64", target_feature = "neon"))]
+    unsafe {
+        neon::brighten_neon_u8(channel, value);
+    }
+    #[cfg(not(all(feature = "neon", target_arch = "aarch64", target_feature = "neon")))]
+    brighten(channel, value, u8::MAX);
+}
+
+/// [`brighten`] for u16's, with NEON on aarch64
+fn brighten_u16(channel: &mut [u16], value: f32) {
+    #[cfg(all(feature = "neon", target_arch = "aarch64", target_feature = "neon"))]
+    unsafe {
+        neon::brighten_neon_u16(channel, value);
+    }
+    #[cfg(not(all(feature = "neon", target_arch = "aarch64", target_feature = "neon")))]
+    brighten(channel, value, u16::MAX);
+}
+
 /// Brighten operation
 ///
 /// # Arguments
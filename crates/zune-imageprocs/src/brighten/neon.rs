@@ -0,0 +1,119 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+#![cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+#![cfg(feature = "neon")]
+//! NEON point operations on integer channels
+//!
+//! Values are converted to f32's four at a time, mapped and converted back truncating
+//! like an `as` cast, so the results are the same as the scalar versions doing
+//! the same float operations on each value.
+use core::arch::aarch64::*;
+
+/// Replace every value with `map` of it, done on f32 lanes, values left over
+/// after the last full register are passed to `remainder`
+pub(crate) unsafe fn map_u8_as_f32(
+    values: &mut [u8], map: impl Fn(float32x4_t) -> float32x4_t, remainder: impl FnOnce(&mut [u8])
+) {
+    let mut chunks = values.chunks_exact_mut(16);
+
+    for chunk in &mut chunks {
+        let bytes = vld1q_u8(chunk.as_ptr());
+        let low = vmovl_u8(vget_low_u8(bytes));
+        let high = vmovl_u8(vget_high_u8(bytes));
+
+        let lanes = [
+            vmovl_u16(vget_low_u16(low)),
+            vmovl_u16(vget_high_u16(low)),
+            vmovl_u16(vget_low_u16(high)),
+            vmovl_u16(vget_high_u16(high))
+        ]
+        .map(|x| vcvtq_u32_f32(map(vcvtq_f32_u32(x))));
+
+        let low = vcombine_u16(vmovn_u32(lanes[0]), vmovn_u32(lanes[1]));
+        let high = vcombine_u16(vmovn_u32(lanes[2]), vmovn_u32(lanes[3]));
+
+        vst1q_u8(
+            chunk.as_mut_ptr(),
+            vcombine_u8(vmovn_u16(low), vmovn_u16(high))
+        );
+    }
+    remainder(chunks.into_remainder());
+}
+
+/// Replace every value with `map` of it, done on f32 lanes, values left over
+/// after the last full register are passed to `remainder`
+pub(crate) unsafe fn map_u16_as_f32(
+    values: &mut [u16], map: impl Fn(float32x4_t) -> float32x4_t,
+    remainder: impl FnOnce(&mut [u16])
+) {
+    let mut chunks = values.chunks_exact_mut(8);
+
+    for chunk in &mut chunks {
+        let words = vld1q_u16(chunk.as_ptr());
+
+        let low = vcvtq_u32_f32(map(vcvtq_f32_u32(vmovl_u16(vget_low_u16(words)))));
+        let high = vcvtq_u32_f32(map(vcvtq_f32_u32(vmovl_u16(vget_high_u16(words)))));
+
+        vst1q_u16(
+            chunk.as_mut_ptr(),
+            vcombine_u16(vmovn_u32(low), vmovn_u32(high))
+        );
+    }
+    remainder(chunks.into_remainder());
+}
+
+/// [`brighten`](super::brighten) on u8's
+pub(crate) unsafe fn brighten_neon_u8(channel: &mut [u8], value: f32) {
+    let scale_v = vdupq_n_f32(value.clamp(-1.0, 1.0) * 255.0);
+    let max = vdupq_n_f32(255.0);
+
+    map_u8_as_f32(
+        channel,
+        |x| vminq_f32(vmaxq_f32(vaddq_f32(x, scale_v), vdupq_n_f32(0.0)), max),
+        |rest| super::brighten(rest, value, u8::MAX)
+    );
+}
+
+/// [`brighten`](super::brighten) on u16's
+pub(crate) unsafe fn brighten_neon_u16(channel: &mut [u16], value: f32) {
+    let scale_v = vdupq_n_f32(value.clamp(-1.0, 1.0) * 65535.0);
+    let max = vdupq_n_f32(65535.0);
+
+    map_u16_as_f32(
+        channel,
+        |x| vminq_f32(vmaxq_f32(vaddq_f32(x, scale_v), vdupq_n_f32(0.0)), max),
+        |rest| super::brighten(rest, value, u16::MAX)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::brighten::brighten;
+    use crate::brighten::neon::{brighten_neon_u16, brighten_neon_u8};
+
+    #[test]
+    fn test_brighten_neon_matches_scalar() {
+        for value in [-1.5, -0.3, 0.0, 0.25, 0.7] {
+            let mut neon_u8: Vec<u8> = (0..=255).chain(0..=20).collect();
+            let mut scalar_u8 = neon_u8.clone();
+            let mut neon_u16: Vec<u16> = (0..1003).map(|x| x * 65).collect();
+            let mut scalar_u16 = neon_u16.clone();
+
+            unsafe {
+                brighten_neon_u8(&mut neon_u8, value);
+                brighten_neon_u16(&mut neon_u16, value);
+            }
+            brighten(&mut scalar_u8, value, u8::MAX);
+            brighten(&mut scalar_u16, value, u16::MAX);
+
+            assert_eq!(neon_u8, scalar_u8);
+            assert_eq!(neon_u16, scalar_u16);
+        }
+    }
+}
@@ -0,0 +1,198 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Adapt an image to another white point
+//!
+//! Images may be tagged with a white point other than D65, e.g. the media white point
+//! of an ICC profile or the white point of a DNG file, see
+//! [`ImageMetadata::white_point`]. This operation adapts the colors so that they look
+//! the same under a new white point, by default D65, the white point of sRGB which the
+//! other colorspace conversions assume.
+//!
+//! # Algorithm
+//! RGB pixels are linearized, converted to XYZ with the primaries of the image, adapted with
+//! a [chromatic adaptation transform](ChromaticAdaptation) and converted back. Adapted colors
+//! outside of the gamut are clipped.
+//!
+//! XYZ images are adapted directly, like converting them with
+//! [`ColorspaceConv::set_white_point`].
+//!
+//! [`ImageMetadata::white_point`]: zune_image::metadata::ImageMetadata::white_point
+//! [`ColorspaceConv::set_white_point`]: zune_image::core_filters::colorspace::ColorspaceConv::set_white_point
+use zune_core::bit_depth::{BitDepth, BitType};
+use zune_core::colorspace::{ChromaticAdaptation, ColorCharacteristics, ColorSpace, WhitePoint};
+use zune_image::core_filters::colorspace::adaptation::rgb_adaptation_matrix;
+use zune_image::core_filters::colorspace::ColorspaceConv;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::gamut_map::{gamut_map, GamutMapping};
+
+/// Adapt an image to a new white point
+///
+/// # Example
+/// Adapt an image tagged with the white point of an ICC profile to D65
+/// ```
+/// use zune_core::colorspace::{ChromaticAdaptation, ColorSpace, WhitePoint};
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::chromatic_adapt::ChromaticAdapt;
+///
+/// let mut image = Image::fill(200_u8, ColorSpace::RGB, 10, 10);
+/// // e.g. the wtpt tag of the profile
+/// let media_white = [0.964_2, 1.0, 0.824_9];
+/// image.metadata_mut().set_white_point(WhitePoint::from_xyz(media_white));
+///
+/// ChromaticAdapt::new(WhitePoint::D65)
+///     .set_adaptation(ChromaticAdaptation::CAT02)
+///     .execute(&mut image)
+///     .unwrap();
+///
+/// assert_eq!(image.metadata().white_point(), WhitePoint::D65);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct ChromaticAdapt {
+    source:      Option<WhitePoint>,
+    destination: WhitePoint,
+    adaptation:  ChromaticAdaptation
+}
+
+impl ChromaticAdapt {
+    /// Create a new operation adapting the image to `destination`
+    ///
+    /// The source is the white point of the image
+    #[must_use]
+    pub fn new(destination: WhitePoint) -> ChromaticAdapt {
+        ChromaticAdapt {
+            source: None,
+            destination,
+            adaptation: ChromaticAdaptation::default()
+        }
+    }
+    /// Set the white point the image colors are seen under, overriding the
+    /// white point of the image
+    #[must_use]
+    pub fn set_source(mut self, source: WhitePoint) -> Self {
+        self.source = Some(source);
+        self
+    }
+    /// Set the chromatic adaptation transform, the default is [`ChromaticAdaptation::Bradford`]
+    #[must_use]
+    pub fn set_adaptation(mut self, adaptation: ChromaticAdaptation) -> Self {
+        self.adaptation = adaptation;
+        self
+    }
+}
+
+impl OperationsTrait for ChromaticAdapt {
+    fn name(&self) -> &'static str {
+        "Chromatic Adaptation"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        if let Some(source) = self.source {
+            image.metadata_mut().set_white_point(source);
+        }
+        if image.colorspace() == ColorSpace::XYZ {
+            return ColorspaceConv::new(ColorSpace::XYZ)
+                .set_white_point(self.destination)
+                .set_adaptation(self.adaptation)
+                .execute_impl(image);
+        }
+        let source = image.metadata().white_point();
+
+        if source != self.destination {
+            let primaries = image.metadata().primaries();
+            let matrix =
+                rgb_adaptation_matrix(primaries, source, self.destination, self.adaptation);
+            let linear = image.metadata().color_trc() == Some(ColorCharacteristics::Linear);
+
+            let depth = image.depth();
+            image.convert_depth(BitDepth::Float32)?;
+
+            let colorspace = image.colorspace();
+            for frame in image.frames_mut() {
+                let channels = frame.channels_mut(colorspace, true);
+                let (r, rest) = channels.split_at_mut(1);
+                let (g, b) = rest.split_at_mut(1);
+
+                gamut_map(
+                    [
+                        r[0].reinterpret_as_mut()?,
+                        g[0].reinterpret_as_mut()?,
+                        b[0].reinterpret_as_mut()?
+                    ],
+                    &matrix,
+                    // luminance is only used by compression
+                    [0.0; 3],
+                    GamutMapping::Clip,
+                    linear
+                );
+            }
+            image.convert_depth(depth)?;
+        }
+        image.metadata_mut().set_white_point(self.destination);
+
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[ColorSpace::RGB, ColorSpace::RGBA, ColorSpace::XYZ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::{ChromaticAdaptation, ColorSpace, WhitePoint};
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::chromatic_adapt::ChromaticAdapt;
+
+    #[test]
+    fn test_warm_white_becomes_cooler() {
+        for adaptation in [ChromaticAdaptation::Bradford, ChromaticAdaptation::CAT02] {
+            let mut image = Image::fill(0.8_f32, ColorSpace::RGB, 2, 2);
+
+            ChromaticAdapt::new(WhitePoint::D65)
+                .set_source(WhitePoint::D50)
+                .set_adaptation(adaptation)
+                .execute(&mut image)
+                .unwrap();
+
+            let pixels = &image.flatten_frames::<f32>()[0];
+            // adapting from the warmer D50 adds blue and removes red
+            assert!(
+                pixels[0] < 0.8 && pixels[2] > 0.8,
+                "{adaptation:?} {pixels:?}"
+            );
+            assert_eq!(image.metadata().white_point(), WhitePoint::D65);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let pixels: Vec<u16> = (0..300).map(|x| x * 100 + 10000).collect();
+        let mut image = Image::from_u16(&pixels, 10, 10, ColorSpace::RGB);
+
+        ChromaticAdapt::new(WhitePoint::D55)
+            .execute(&mut image)
+            .unwrap();
+        ChromaticAdapt::new(WhitePoint::D65)
+            .execute(&mut image)
+            .unwrap();
+
+        for (a, e) in image.flatten_frames::<u16>().iter().flatten().zip(&pixels) {
+            assert!(a.abs_diff(*e) <= 8, "{a} {e}");
+        }
+    }
+}
@@ -0,0 +1,343 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Simulate color vision deficiencies and daltonize images
+//!
+//! People with dichromacy lack one of the three cone types of the eye, colors that only
+//! differ in the response of the missing cone look the same to them. The simulation shows
+//! an image as seen with such a deficiency, which helps auditing charts, maps and interfaces
+//! for colors that can't be told apart.
+//!
+//! Daltonization shifts the color information lost to the deficiency into channels that
+//! are still seen, so that such colors become distinguishable again.
+//!
+//! # Algorithm
+//! Pixels are linearized and converted to LMS cone responses, the response of the
+//! missing cone is rebuilt from the other two as in *"Digital video colourmaps for checking
+//! the legibility of displays by dichromats"* (Viénot, Brettel, Mollon 1999) and the result is
+//! converted back.
+//!
+//! Daltonization adds the difference between the image and its simulation to the
+//! channels the deficiency keeps, as done by Fidaner, Lin and Ozguven.
+//!
+//! Images with [`Linear`](ColorCharacteristics::Linear) transfer characteristics are not
+//! linearized, any other image is treated as sRGB.
+use zune_core::bit_depth::{BitDepth, BitType};
+use zune_core::colorspace::{ColorCharacteristics, ColorSpace};
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::linearize::{linear_to_srgb, srgb_to_linear};
+
+/// Linear sRGB to LMS cone responses
+const RGB_TO_LMS: [[f32; 3]; 3] = [
+    [17.882_4, 43.516_1, 4.119_35],
+    [3.455_65, 27.155_4, 3.867_14],
+    [0.029_956_6, 0.184_309, 1.467_09]
+];
+
+/// LMS cone responses to linear sRGB
+const LMS_TO_RGB: [[f32; 3]; 3] = [
+    [0.080_944_45, -0.130_504_4, 0.116_721_07],
+    [-0.010_248_534, 0.054_019_33, -0.113_614_71],
+    [-0.000_365_296_94, -0.004_121_614_7, 0.693_511_4]
+];
+
+/// Spreads the lost color information over the channels that are still seen
+const ERROR_SHIFT: [[f32; 3]; 3] = [[0.0, 0.0, 0.0], [0.7, 1.0, 0.0], [0.7, 0.0, 1.0]];
+
+/// A color vision deficiency
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ColorBlindness {
+    /// Missing long wavelength (red) cones
+    Protanopia,
+    /// Missing medium wavelength (green) cones
+    Deuteranopia,
+    /// Missing short wavelength (blue) cones
+    Tritanopia
+}
+
+impl ColorBlindness {
+    /// The matrix rebuilding the missing cone response from the others
+    const fn lms_matrix(self) -> [[f32; 3]; 3] {
+        match self {
+            ColorBlindness::Protanopia => {
+                [[0.0, 2.023_44, -2.525_81], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]
+            }
+            ColorBlindness::Deuteranopia => {
+                [[1.0, 0.0, 0.0], [0.494_207, 0.0, 1.248_27], [0.0, 0.0, 1.0]]
+            }
+            ColorBlindness::Tritanopia => [
+                [1.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [-0.395_913, 0.801_109, 0.0]
+            ]
+        }
+    }
+
+    /// Linear RGB matrix simulating the deficiency, `severity` blends between
+    /// normal vision at 0 and dichromacy at 1
+    fn rgb_matrix(self, severity: f32) -> [[f32; 3]; 3] {
+        let dichromat = mul(&LMS_TO_RGB, &mul(&self.lms_matrix(), &RGB_TO_LMS));
+        let mut matrix = [[0.0; 3]; 3];
+
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                let identity = if i == j { 1.0 } else { 0.0 };
+                *value = identity + (dichromat[i][j] - identity) * severity;
+            }
+        }
+        matrix
+    }
+}
+
+/// Simulate how an image looks with a color vision deficiency
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::color_blindness::{ColorBlindness, SimulateColorBlindness};
+///
+/// let mut image = Image::fill(200_u8, ColorSpace::RGB, 10, 10);
+///
+/// SimulateColorBlindness::new(ColorBlindness::Deuteranopia)
+///     .execute(&mut image)
+///     .unwrap();
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct SimulateColorBlindness {
+    deficiency: ColorBlindness,
+    severity:   f32
+}
+
+impl SimulateColorBlindness {
+    /// Create a new simulation of full dichromacy
+    #[must_use]
+    pub fn new(deficiency: ColorBlindness) -> SimulateColorBlindness {
+        SimulateColorBlindness {
+            deficiency,
+            severity: 1.0
+        }
+    }
+    /// Set the severity of the deficiency, from 0 for normal vision to 1 for
+    /// dichromacy, values in between approximate anomalous trichromacy
+    #[must_use]
+    pub fn set_severity(mut self, severity: f32) -> Self {
+        self.severity = severity.clamp(0.0, 1.0);
+        self
+    }
+}
+
+impl OperationsTrait for SimulateColorBlindness {
+    fn name(&self) -> &'static str {
+        "Simulate Color Blindness"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let matrix = self.deficiency.rgb_matrix(self.severity);
+
+        transform_pixels(image, |rgb| mul_vector(&matrix, rgb))
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[ColorSpace::RGB, ColorSpace::RGBA]
+    }
+}
+
+/// Re-map colors so that people with a color vision deficiency can tell them apart
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::color_blindness::{ColorBlindness, Daltonize};
+///
+/// let mut image = Image::fill(200_u8, ColorSpace::RGB, 10, 10);
+///
+/// Daltonize::new(ColorBlindness::Protanopia)
+///     .execute(&mut image)
+///     .unwrap();
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct Daltonize {
+    deficiency: ColorBlindness,
+    strength:   f32
+}
+
+impl Daltonize {
+    /// Create a new daltonization for a deficiency
+    #[must_use]
+    pub fn new(deficiency: ColorBlindness) -> Daltonize {
+        Daltonize {
+            deficiency,
+            strength: 1.0
+        }
+    }
+    /// Set how much of the lost color information is shifted into visible
+    /// channels, the default is 1
+    #[must_use]
+    pub fn set_strength(mut self, strength: f32) -> Self {
+        self.strength = strength;
+        self
+    }
+}
+
+impl OperationsTrait for Daltonize {
+    fn name(&self) -> &'static str {
+        "Daltonize"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let matrix = self.deficiency.rgb_matrix(1.0);
+        let strength = self.strength;
+
+        transform_pixels(image, |rgb| {
+            let simulated = mul_vector(&matrix, rgb);
+            let error = [0, 1, 2].map(|i| rgb[i] - simulated[i]);
+            let shift = mul_vector(&ERROR_SHIFT, error);
+
+            [0, 1, 2].map(|i| rgb[i] + shift[i] * strength)
+        })
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[ColorSpace::RGB, ColorSpace::RGBA]
+    }
+}
+
+fn mul(a: &[[f32; 3]; 3], b: &[[f32; 3]; 3]) -> [[f32; 3]; 3] {
+    let mut out = [[0.0; 3]; 3];
+
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    out
+}
+
+#[inline]
+fn mul_vector(matrix: &[[f32; 3]; 3], rgb: [f32; 3]) -> [f32; 3] {
+    matrix.map(|row| row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2])
+}
+
+/// Run `function` on the linear RGB values of every pixel, clipping the result
+fn transform_pixels(
+    image: &mut Image, function: impl Fn([f32; 3]) -> [f32; 3]
+) -> Result<(), ImageErrors> {
+    let linear = image.metadata().color_trc() == Some(ColorCharacteristics::Linear);
+    let depth = image.depth();
+
+    image.convert_depth(BitDepth::Float32)?;
+
+    let colorspace = image.colorspace();
+    for frame in image.frames_mut() {
+        let channels = frame.channels_mut(colorspace, true);
+        let (r, rest) = channels.split_at_mut(1);
+        let (g, b) = rest.split_at_mut(1);
+
+        let r = r[0].reinterpret_as_mut::<f32>()?;
+        let g = g[0].reinterpret_as_mut::<f32>()?;
+        let b = b[0].reinterpret_as_mut::<f32>()?;
+
+        for ((r, g), b) in r.iter_mut().zip(g.iter_mut()).zip(b.iter_mut()) {
+            let mut rgb = [*r, *g, *b].map(|x| x.clamp(0.0, 1.0));
+            if !linear {
+                rgb = rgb.map(srgb_to_linear);
+            }
+            let mut result = function(rgb).map(|x| x.clamp(0.0, 1.0));
+            if !linear {
+                result = result.map(linear_to_srgb);
+            }
+            [*r, *g, *b] = result;
+        }
+    }
+    image.convert_depth(depth)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::color_blindness::{ColorBlindness, Daltonize, SimulateColorBlindness};
+
+    const DEFICIENCIES: [ColorBlindness; 3] = [
+        ColorBlindness::Protanopia,
+        ColorBlindness::Deuteranopia,
+        ColorBlindness::Tritanopia
+    ];
+
+    fn simulate(pixels: &[f32], deficiency: ColorBlindness) -> Vec<f32> {
+        let mut image = Image::from_f32(pixels, pixels.len() / 3, 1, ColorSpace::RGB);
+        SimulateColorBlindness::new(deficiency)
+            .execute(&mut image)
+            .unwrap();
+        image.flatten_frames::<f32>()[0].clone()
+    }
+
+    fn distance(a: &[f32], b: &[f32]) -> f32 {
+        a.iter()
+            .zip(b)
+            .map(|(x, y)| (x - y) * (x - y))
+            .sum::<f32>()
+            .sqrt()
+    }
+
+    #[test]
+    fn test_grays_are_kept() {
+        for deficiency in DEFICIENCIES {
+            let pixels = [0.0, 0.0, 0.0, 0.5, 0.5, 0.5, 1.0, 1.0, 1.0];
+            let result = simulate(&pixels, deficiency);
+
+            assert!(
+                distance(&result, &pixels) < 0.02,
+                "{deficiency:?} {result:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_red_and_green_are_confused() {
+        let red_green = [0.8, 0.3, 0.2, 0.45, 0.55, 0.2];
+
+        for deficiency in [ColorBlindness::Protanopia, ColorBlindness::Deuteranopia] {
+            let simulated = simulate(&red_green, deficiency);
+            assert!(
+                distance(&simulated[..3], &simulated[3..])
+                    < distance(&red_green[..3], &red_green[3..]) / 2.0,
+                "{deficiency:?} {simulated:?}"
+            );
+
+            // daltonized colors are easier to tell apart with the deficiency
+            let mut image = Image::from_f32(&red_green, 2, 1, ColorSpace::RGB);
+            Daltonize::new(deficiency).execute(&mut image).unwrap();
+            let daltonized = simulate(&image.flatten_frames::<f32>()[0], deficiency);
+
+            assert!(
+                distance(&daltonized[..3], &daltonized[3..])
+                    > distance(&simulated[..3], &simulated[3..]),
+                "{deficiency:?} {daltonized:?}"
+            );
+        }
+    }
+}
@@ -1,8 +1,12 @@
+use alloc::format;
+
 use zune_core::bit_depth::BitType;
 use zune_image::errors::ImageErrors;
 use zune_image::image::Image;
+use zune_image::metadata::AlphaState;
 use zune_image::traits::OperationsTrait;
 
+use crate::premul_alpha::PremultiplyAlpha;
 use crate::traits::NumOps;
 use crate::utils::{calculate_gravity, Gravity};
 
@@ -99,26 +103,36 @@ impl<'a> OperationsTrait for Composite<'a> {
 
     #[allow(clippy::too_many_lines)]
     fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
-        let dims = if self.gravity.is_some() {
-            calculate_gravity(self.src_image, image, self.gravity.unwrap())
-        } else if self.geometry.is_some() {
-            self.geometry.unwrap()
+        // compositing works on straight alpha, the destination is converted by `execute`
+        let straight_src;
+        let src_image = if self.src_image.metadata().is_premultiplied_alpha() {
+            let mut src = self.src_image.clone();
+            PremultiplyAlpha::new(AlphaState::NonPreMultiplied).execute_impl(&mut src)?;
+            straight_src = src;
+            &straight_src
+        } else {
+            self.src_image
+        };
+        let dims = if let Some(gravity) = self.gravity {
+            calculate_gravity(src_image, image, gravity)
+        } else if let Some(geometry) = self.geometry {
+            geometry
         } else {
             unreachable!()
         };
-        let (src_width, _) = self.src_image.dimensions();
+        let (src_width, _) = src_image.dimensions();
         let (dst_width, _) = image.dimensions();
         // confirm compatibility
-        if image.depth() != self.src_image.depth() {
+        if image.depth() != src_image.depth() {
             return Err(ImageErrors::GenericStr(
                 "Image depths do not match for composite"
             ));
         }
 
-        if image.colorspace() != self.src_image.colorspace() {
+        if image.colorspace() != src_image.colorspace() {
             return Err(ImageErrors::GenericString(format!(
                 "Image colorspace does not match for composite src image = {:?}, dst_image = {:?}",
-                self.src_image.colorspace(),
+                src_image.colorspace(),
                 image.colorspace()
             )));
         }
@@ -129,7 +143,7 @@ impl<'a> OperationsTrait for Composite<'a> {
                 let colorspace = image.colorspace();
                 if colorspace.has_alpha() {
                     for (src_frame, dst_frame) in
-                        self.src_image.frames_ref().iter().zip(image.frames_mut())
+                        src_image.frames_ref().iter().zip(image.frames_mut())
                     {
                         let (src_color_channels, src_alpha_channel) =
                             src_frame.separate_color_and_alpha_ref(colorspace).unwrap();
@@ -277,6 +291,10 @@ impl<'a> OperationsTrait for Composite<'a> {
     fn supported_types(&self) -> &'static [BitType] {
         &[BitType::U8, BitType::U16, BitType::F32]
     }
+
+    fn alpha_state(&self) -> Option<AlphaState> {
+        Some(AlphaState::NonPreMultiplied)
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -0,0 +1,373 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Connected components labeling
+//!
+//! This finds groups of connected foreground pixels (blobs) in a binary image
+//! and assigns each group a unique label, along with some statistics about the group.
+//!
+//! A pixel is considered foreground if it's non-zero, so images should be
+//! binarized beforehand, e.g. via [Threshold](crate::threshold::Threshold)
+//!
+//! # Algorithm
+//! This uses the classical two pass algorithm with a union-find structure
+//! to resolve label equivalences.
+//!
+//! - The first pass assigns provisional labels to pixels from their already visited neighbours,
+//!   recording when two labels touch
+//! - The second pass replaces each provisional label with its final, compact label and
+//!   gathers statistics
+use alloc::vec;
+use alloc::vec::Vec;
+use std::sync::Mutex;
+
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::traits::NumOps;
+
+/// Pixel neighbourhood used to decide if two pixels are connected
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Connectivity {
+    /// Pixels are connected if they share an edge (left, right, top, bottom)
+    Four,
+    /// Pixels are connected if they share an edge or a corner
+    Eight
+}
+
+/// Statistics for a single connected component
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ComponentStats {
+    /// The label for this component, as stored in the label map
+    pub label:    u32,
+    /// Number of pixels in this component
+    pub area:     usize,
+    /// Leftmost x coordinate of the component
+    pub min_x:    usize,
+    /// Topmost y coordinate of the component
+    pub min_y:    usize,
+    /// Rightmost x coordinate of the component (inclusive)
+    pub max_x:    usize,
+    /// Bottommost y coordinate of the component (inclusive)
+    pub max_y:    usize,
+    /// Center of mass of the component as `(x,y)`
+    pub centroid: (f64, f64)
+}
+
+impl ComponentStats {
+    /// Return the bounding box of the component as `(x,y,width,height)`
+    #[must_use]
+    pub const fn bounding_box(&self) -> (usize, usize, usize, usize) {
+        (
+            self.min_x,
+            self.min_y,
+            self.max_x - self.min_x + 1,
+            self.max_y - self.min_y + 1
+        )
+    }
+}
+
+/// The result of connected components labeling
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComponentLabels {
+    /// A `width*height` label map, `0` is background, components are labeled from `1`
+    pub labels: Vec<u32>,
+    /// Width of the label map
+    pub width:  usize,
+    /// Height of the label map
+    pub height: usize,
+    /// Statistics for each component, `stats[i]` describes label `i+1`
+    pub stats:  Vec<ComponentStats>
+}
+
+impl ComponentLabels {
+    /// Number of components found, excluding background
+    #[must_use]
+    pub fn num_components(&self) -> usize {
+        self.stats.len()
+    }
+}
+
+/// Connected components labeling
+///
+/// This does not modify the image, labels can be fetched via [`components`](Self::components)
+/// after calling `execute`.
+///
+/// Only the first frame of the image is analyzed, and for images with alpha,
+/// the alpha channel is ignored
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::connected_components::{ConnectedComponents, Connectivity};
+///
+/// let mut image = Image::from_fn::<u8, _>(10, 10, ColorSpace::Luma, |y, x, pix| {
+///     // two squares
+///     let inside = (x < 3 && y < 3) || (x > 5 && y > 5);
+///     pix[0] = if inside { 255 } else { 0 };
+/// });
+/// let cc = ConnectedComponents::new(Connectivity::Eight);
+/// cc.execute(&mut image).unwrap();
+///
+/// let components = cc.components().unwrap();
+/// assert_eq!(components.num_components(), 2);
+/// assert_eq!(components.stats[0].area, 9);
+/// ```
+pub struct ConnectedComponents {
+    connectivity: Connectivity,
+    result:       Mutex<Option<ComponentLabels>>
+}
+
+impl ConnectedComponents {
+    /// Create a new connected components operation
+    ///
+    /// # Arguments
+    /// - connectivity: Neighbourhood used to decide if two pixels are connected
+    #[must_use]
+    pub fn new(connectivity: Connectivity) -> ConnectedComponents {
+        ConnectedComponents {
+            connectivity,
+            result: Mutex::new(None)
+        }
+    }
+    /// Return the labels from the last execution or `None` if the operation
+    /// hasn't been executed
+    pub fn components(&self) -> Option<ComponentLabels> {
+        self.result.lock().ok()?.clone()
+    }
+}
+
+impl OperationsTrait for ConnectedComponents {
+    fn name(&self) -> &'static str {
+        "Connected Components"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let depth = image.depth().bit_type();
+
+        let channel = image.channels_ref(true)[0];
+
+        let result = match depth {
+            BitType::U8 => connected_components(
+                channel.reinterpret_as::<u8>()?,
+                width,
+                height,
+                self.connectivity
+            ),
+            BitType::U16 => connected_components(
+                channel.reinterpret_as::<u16>()?,
+                width,
+                height,
+                self.connectivity
+            ),
+            BitType::F32 => connected_components(
+                channel.reinterpret_as::<f32>()?,
+                width,
+                height,
+                self.connectivity
+            ),
+            d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+        };
+
+        *self
+            .result
+            .lock()
+            .map_err(|_| ImageErrors::GenericStr("Connected components result is poisoned"))? =
+            Some(result);
+
+        Ok(())
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[ColorSpace::Luma, ColorSpace::LumaA]
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Find the root of a label, compressing the path on the way
+fn find(parents: &mut [u32], mut label: u32) -> u32 {
+    while parents[label as usize] != label {
+        let parent = parents[label as usize];
+        parents[label as usize] = parents[parent as usize];
+        label = parent;
+    }
+    label
+}
+
+fn union(parents: &mut [u32], a: u32, b: u32) {
+    let (a, b) = (find(parents, a), find(parents, b));
+    // keep the smaller label as the root, so labels are ordered by first appearance
+    if a < b {
+        parents[b as usize] = a;
+    } else if b < a {
+        parents[a as usize] = b;
+    }
+}
+
+/// Label connected components of non-zero pixels in a single channel
+///
+/// # Arguments
+/// - pixels: The image channel, should have `width*height` elements
+/// - width,height: Image dimensions
+/// - connectivity: Neighbourhood used to decide if two pixels are connected
+///
+/// # Returns
+/// The label map and statistics, labels are assigned in raster order of
+/// each component's first pixel
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+pub fn connected_components<T>(
+    pixels: &[T], width: usize, height: usize, connectivity: Connectivity
+) -> ComponentLabels
+where
+    T: NumOps<T> + Copy + PartialOrd
+{
+    let mut labels = vec![0_u32; width * height];
+    // parents[0] is background and is never unioned
+    let mut parents: Vec<u32> = vec![0];
+
+    let zero = T::from_u8(0);
+
+    // first pass, provisional labels
+    for y in 0..height {
+        for x in 0..width {
+            let pos = y * width + x;
+
+            if pixels[pos] <= zero {
+                continue;
+            }
+            // already visited neighbours
+            let mut neighbours = [0_u32; 4];
+
+            if x > 0 {
+                neighbours[0] = labels[pos - 1];
+            }
+            if y > 0 {
+                neighbours[1] = labels[pos - width];
+
+                if connectivity == Connectivity::Eight {
+                    if x > 0 {
+                        neighbours[2] = labels[pos - width - 1];
+                    }
+                    if x + 1 < width {
+                        neighbours[3] = labels[pos - width + 1];
+                    }
+                }
+            }
+            let smallest = neighbours.iter().filter(|x| **x != 0).min().copied();
+
+            if let Some(label) = smallest {
+                labels[pos] = label;
+
+                for neighbour in neighbours.iter().filter(|x| **x != 0) {
+                    union(&mut parents, label, *neighbour);
+                }
+            } else {
+                let label = parents.len() as u32;
+                parents.push(label);
+                labels[pos] = label;
+            }
+        }
+    }
+    // map roots to compact labels
+    let mut compact = vec![0_u32; parents.len()];
+    let mut stats: Vec<ComponentStats> = vec![];
+    let mut sums: Vec<(f64, f64)> = vec![];
+
+    for y in 0..height {
+        for x in 0..width {
+            let pos = y * width + x;
+
+            if labels[pos] == 0 {
+                continue;
+            }
+            let root = find(&mut parents, labels[pos]) as usize;
+
+            if compact[root] == 0 {
+                stats.push(ComponentStats {
+                    label:    stats.len() as u32 + 1,
+                    area:     0,
+                    min_x:    x,
+                    min_y:    y,
+                    max_x:    x,
+                    max_y:    y,
+                    centroid: (0.0, 0.0)
+                });
+                sums.push((0.0, 0.0));
+                compact[root] = stats.len() as u32;
+            }
+            let label = compact[root];
+            labels[pos] = label;
+
+            let stat = &mut stats[label as usize - 1];
+            stat.area += 1;
+            stat.min_x = stat.min_x.min(x);
+            stat.max_x = stat.max_x.max(x);
+            stat.max_y = stat.max_y.max(y);
+
+            let sum = &mut sums[label as usize - 1];
+            sum.0 += x as f64;
+            sum.1 += y as f64;
+        }
+    }
+    for (stat, sum) in stats.iter_mut().zip(sums) {
+        stat.centroid = (sum.0 / stat.area as f64, sum.1 / stat.area as f64);
+    }
+
+    ComponentLabels {
+        labels,
+        width,
+        height,
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::connected_components::{connected_components, Connectivity};
+
+    #[test]
+    fn test_diagonal_connectivity() {
+        // a diagonal line, connected only with eight connectivity
+        #[rustfmt::skip]
+        let pixels: [u8; 9] = [
+            1, 0, 0,
+            0, 1, 0,
+            0, 0, 1
+        ];
+        let four = connected_components(&pixels, 3, 3, Connectivity::Four);
+        assert_eq!(four.num_components(), 3);
+
+        let eight = connected_components(&pixels, 3, 3, Connectivity::Eight);
+        assert_eq!(eight.num_components(), 1);
+        assert_eq!(eight.stats[0].area, 3);
+        assert_eq!(eight.stats[0].bounding_box(), (0, 0, 3, 3));
+        assert_eq!(eight.stats[0].centroid, (1.0, 1.0));
+    }
+
+    #[test]
+    fn test_u_shape_merges_labels() {
+        // the two arms get different provisional labels which must be merged
+        #[rustfmt::skip]
+        let pixels: [u8; 9] = [
+            1, 0, 1,
+            1, 0, 1,
+            1, 1, 1
+        ];
+        let result = connected_components(&pixels, 3, 3, Connectivity::Four);
+        assert_eq!(result.num_components(), 1);
+        assert!(result.labels.iter().all(|x| *x <= 1));
+    }
+}
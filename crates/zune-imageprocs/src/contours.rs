@@ -0,0 +1,362 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Contour extraction
+//!
+//! This traces the borders of foreground regions in a binary image and returns them as
+//! polygons, along with the nesting relationship between them (e.g. a hole inside a blob).
+//!
+//! As with [connected components](crate::connected_components), a pixel is considered foreground
+//! if it's non-zero.
+//!
+//! # Algorithm
+//! This implements the border following algorithm from
+//! Suzuki, S. and Abe, K., *Topological Structural Analysis of Digitized Binary Images by Border Following*,
+//! CVGIP 30 1, pp 32-46 (1985).
+//!
+//! The image is scanned in raster order, and whenever a pixel is found that starts an unvisited
+//! outer border or hole border, the border is followed (with 8-connectivity) until we return to
+//! the start, labelling pixels on the way so that each border is only traced once.
+//!
+//! The last border met on the current row tells us the parent of the new border.
+use alloc::vec;
+use alloc::vec::Vec;
+use std::sync::Mutex;
+
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::traits::NumOps;
+
+/// The kind of border a contour represents
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum BorderType {
+    /// The outer border of a foreground region
+    Outer,
+    /// The border of a hole inside a foreground region
+    Hole
+}
+
+/// A single traced contour
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Contour {
+    /// Points of the contour as `(x,y)` in the order they were traced
+    pub points:      Vec<(usize, usize)>,
+    /// Whether this is an outer border or a hole border
+    pub border_type: BorderType,
+    /// Index of the contour directly enclosing this one, `None` for outermost contours
+    pub parent:      Option<usize>
+}
+
+/// Find contours in an image
+///
+/// This does not modify the image, contours can be fetched via [`contours`](Self::contours)
+/// after calling `execute`.
+///
+/// Only the first frame of the image is analyzed, and for images with alpha,
+/// the alpha channel is ignored
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::contours::{BorderType, FindContours};
+///
+/// // a 4x4 square in a 10x10 image
+/// let mut image = Image::from_fn::<u8, _>(10, 10, ColorSpace::Luma, |y, x, pix| {
+///     let inside = (3..7).contains(&x) && (3..7).contains(&y);
+///     pix[0] = if inside { 255 } else { 0 };
+/// });
+/// let finder = FindContours::new();
+/// finder.execute(&mut image).unwrap();
+///
+/// let contours = finder.contours().unwrap();
+/// assert_eq!(contours.len(), 1);
+/// assert_eq!(contours[0].border_type, BorderType::Outer);
+/// ```
+#[derive(Default)]
+pub struct FindContours {
+    result: Mutex<Option<Vec<Contour>>>
+}
+
+impl FindContours {
+    /// Create a new contour finding operation
+    #[must_use]
+    pub fn new() -> FindContours {
+        FindContours::default()
+    }
+    /// Return the contours from the last execution or `None` if the operation
+    /// hasn't been executed
+    pub fn contours(&self) -> Option<Vec<Contour>> {
+        self.result.lock().ok()?.clone()
+    }
+}
+
+impl OperationsTrait for FindContours {
+    fn name(&self) -> &'static str {
+        "Find Contours"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let depth = image.depth().bit_type();
+
+        let channel = image.channels_ref(true)[0];
+
+        let result = match depth {
+            BitType::U8 => find_contours(channel.reinterpret_as::<u8>()?, width, height),
+            BitType::U16 => find_contours(channel.reinterpret_as::<u16>()?, width, height),
+            BitType::F32 => find_contours(channel.reinterpret_as::<f32>()?, width, height),
+            d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+        };
+
+        *self
+            .result
+            .lock()
+            .map_err(|_| ImageErrors::GenericStr("Find contours result is poisoned"))? =
+            Some(result);
+
+        Ok(())
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[ColorSpace::Luma, ColorSpace::LumaA]
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Neighbour offsets as `(dx,dy)` in clockwise order, starting from the east
+const DIRECTIONS: [(isize, isize); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1)
+];
+
+#[allow(clippy::cast_possible_wrap)]
+fn direction_of(from: (usize, usize), to: (usize, usize)) -> usize {
+    let dx = to.0 as isize - from.0 as isize;
+    let dy = to.1 as isize - from.1 as isize;
+
+    DIRECTIONS.iter().position(|d| *d == (dx, dy)).unwrap()
+}
+
+fn step(pos: (usize, usize), direction: usize) -> (usize, usize) {
+    let (dx, dy) = DIRECTIONS[direction];
+    // The image is padded with a zero border and we never examine pixels on it
+    // so this never underflows
+    (pos.0.wrapping_add_signed(dx), pos.1.wrapping_add_signed(dy))
+}
+
+/// Find contours of non-zero regions in a single channel
+///
+/// # Arguments
+/// - pixels: The image channel, should have `width*height` elements
+/// - width,height: Image dimensions
+///
+/// # Returns
+/// All outer and hole borders, in the order they are met in a raster scan
+#[allow(
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss,
+    clippy::cast_possible_truncation
+)]
+pub fn find_contours<T>(pixels: &[T], width: usize, height: usize) -> Vec<Contour>
+where
+    T: NumOps<T> + Copy + PartialOrd
+{
+    // pad the image by one pixel on each side, so that borders touching the image
+    // edges can be followed without bounds checks
+    let padded_w = width + 2;
+    let padded_h = height + 2;
+
+    let zero = T::from_u8(0);
+    let mut f = vec![0_i32; padded_w * padded_h];
+
+    for (out, row) in f
+        .chunks_exact_mut(padded_w)
+        .skip(1)
+        .zip(pixels.chunks_exact(width.max(1)).take(height))
+    {
+        for (o, p) in out[1..].iter_mut().zip(row) {
+            *o = i32::from(*p > zero);
+        }
+    }
+
+    let at = |f: &[i32], pos: (usize, usize)| f[pos.1 * padded_w + pos.0];
+
+    let mut contours: Vec<Contour> = vec![];
+    // NBD 1 is the frame (the padding), which behaves like a hole border
+    // contour for NBD n is stored at contours[n-2]
+    let mut nbd: i32 = 1;
+
+    for y in 1..padded_h - 1 {
+        let mut lnbd: i32 = 1;
+
+        for x in 1..padded_w - 1 {
+            let pos = (x, y);
+            let value = at(&f, pos);
+
+            let start = if value == 1 && at(&f, (x - 1, y)) == 0 {
+                Some((BorderType::Outer, (x - 1, y)))
+            } else if value >= 1 && at(&f, (x + 1, y)) == 0 {
+                if value > 1 {
+                    lnbd = value;
+                }
+                Some((BorderType::Hole, (x + 1, y)))
+            } else {
+                None
+            };
+
+            if let Some((border_type, from)) = start {
+                nbd += 1;
+
+                // the type of the last border met decides the parent
+                let (lnbd_type, lnbd_parent) = if lnbd == 1 {
+                    (BorderType::Hole, None)
+                } else {
+                    let c = &contours[lnbd as usize - 2];
+                    (c.border_type, c.parent)
+                };
+                let lnbd_index = (lnbd >= 2).then(|| lnbd as usize - 2);
+
+                let parent = if border_type == lnbd_type { lnbd_parent } else { lnbd_index };
+
+                let points = follow_border(&mut f, padded_w, pos, from, nbd);
+
+                contours.push(Contour {
+                    points,
+                    border_type,
+                    parent
+                });
+            }
+
+            let value = at(&f, pos);
+
+            if value != 1 && value != 0 {
+                lnbd = value.abs();
+            }
+        }
+    }
+    contours
+}
+
+/// Follow a single border starting at `start`, where `from` is the zero pixel
+/// that told us this is a border.
+///
+/// Returns the border points, with the padding removed.
+fn follow_border(
+    f: &mut [i32], padded_w: usize, start: (usize, usize), from: (usize, usize), nbd: i32
+) -> Vec<(usize, usize)> {
+    let at = |f: &[i32], pos: (usize, usize)| f[pos.1 * padded_w + pos.0];
+    let unpad = |pos: (usize, usize)| (pos.0 - 1, pos.1 - 1);
+
+    // search clockwise from `from` for a non-zero pixel
+    let from_dir = direction_of(start, from);
+
+    let first = (0..8)
+        .map(|i| step(start, (from_dir + i) % 8))
+        .find(|p| at(f, *p) != 0);
+
+    let Some(first) = first else {
+        // single isolated pixel
+        f[start.1 * padded_w + start.0] = -nbd;
+        return vec![unpad(start)];
+    };
+
+    let mut points = vec![];
+    let mut previous = first;
+    let mut current = start;
+
+    loop {
+        // search counter-clockwise, starting after the previous pixel
+        let prev_dir = direction_of(current, previous);
+        let mut east_examined_zero = false;
+        let mut next = current;
+
+        for i in 1..=8 {
+            let dir = (prev_dir + 8 - i) % 8;
+            let candidate = step(current, dir);
+
+            if at(f, candidate) != 0 {
+                next = candidate;
+                break;
+            }
+            if dir == 0 {
+                east_examined_zero = true;
+            }
+        }
+        let index = current.1 * padded_w + current.0;
+
+        if east_examined_zero {
+            f[index] = -nbd;
+        } else if f[index] == 1 {
+            f[index] = nbd;
+        }
+        points.push(unpad(current));
+
+        if next == start && current == first {
+            break;
+        }
+        previous = current;
+        current = next;
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::contours::{find_contours, BorderType};
+
+    #[test]
+    fn test_ring_has_hole() {
+        #[rustfmt::skip]
+        let pixels: [u8; 25] = [
+            0, 0, 0, 0, 0,
+            0, 1, 1, 1, 0,
+            0, 1, 0, 1, 0,
+            0, 1, 1, 1, 0,
+            0, 0, 0, 0, 0
+        ];
+        let contours = find_contours(&pixels, 5, 5);
+
+        assert_eq!(contours.len(), 2);
+        assert_eq!(contours[0].border_type, BorderType::Outer);
+        assert_eq!(contours[0].parent, None);
+        assert_eq!(contours[0].points.len(), 8);
+
+        assert_eq!(contours[1].border_type, BorderType::Hole);
+        assert_eq!(contours[1].parent, Some(0));
+    }
+
+    #[test]
+    fn test_touching_edges_and_single_pixel() {
+        #[rustfmt::skip]
+        let pixels: [u8; 9] = [
+            1, 1, 0,
+            1, 1, 0,
+            0, 0, 1
+        ];
+        let contours = find_contours(&pixels, 3, 3);
+
+        // the square and the pixel are 8-connected so they form one region
+        assert_eq!(contours.len(), 1);
+        assert!(contours[0].points.contains(&(2, 2)));
+        assert!(contours[0].points.contains(&(0, 0)));
+    }
+}
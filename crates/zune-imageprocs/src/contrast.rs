@@ -31,7 +31,9 @@ use zune_core::bit_depth::BitType;
 use zune_core::colorspace::ColorSpace;
 use zune_image::errors::ImageErrors;
 use zune_image::image::Image;
-use zune_image::traits::OperationsTrait;
+use zune_image::traits::{OperationsTrait, Parameter, ParameterKind, ParameterValue};
+
+mod neon;
 
 /// Adjust the contrast of an image
 ///
@@ -54,6 +56,7 @@ use zune_image::traits::OperationsTrait;
 ///
 /// ```
 #[derive(Default)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Contrast {
     contrast: f32
 }
@@ -70,6 +73,18 @@ impl OperationsTrait for Contrast {
         "contrast"
     }
 
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            name:        "contrast",
+            description: "Contrast adjustment, negative values decrease contrast",
+            kind:        ParameterKind::Float {
+                min: -255.0,
+                max: 255.0
+            },
+            default:     ParameterValue::Float(0.0)
+        }]
+    }
+
     fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
         let depth = image.depth();
 
@@ -92,6 +107,9 @@ impl OperationsTrait for Contrast {
     fn supported_types(&self) -> &'static [BitType] {
         &[BitType::U8]
     }
+    fn is_point_op(&self) -> bool {
+        true
+    }
 }
 
 /// Calculate the contrast of an image
@@ -99,12 +117,21 @@ impl OperationsTrait for Contrast {
 /// # Arguments
 /// - channel: Input channel , modified in place
 /// - contrast: The contrast to adjust the channel with
-#[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
 pub fn contrast_u8(channel: &mut [u8], contrast: f32) {
     // calculate correlation factor
     // These constants may not work for u16
     let factor = (259.0 * (contrast + 255.0)) / (255.0 * (259.0 - contrast));
 
+    #[cfg(all(feature = "neon", target_arch = "aarch64", target_feature = "neon"))]
+    unsafe {
+        neon::contrast_neon_u8(channel, factor);
+    }
+    #[cfg(not(all(feature = "neon", target_arch = "aarch64", target_feature = "neon")))]
+    contrast_u8_scalar(channel, factor);
+}
+
+#[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+fn contrast_u8_scalar(channel: &mut [u8], factor: f32) {
     for pix in channel {
         let float_pix = f32::from(*pix);
         let new_val = ((factor * (float_pix - 128.0)) + 128.0).clamp(0.0, 255.0);
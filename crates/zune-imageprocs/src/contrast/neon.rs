@@ -0,0 +1,53 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+#![cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+#![cfg(feature = "neon")]
+
+use core::arch::aarch64::*;
+
+use crate::brighten::neon::map_u8_as_f32;
+
+/// [`contrast_u8`](super::contrast_u8) with an already calculated correlation factor
+pub(crate) unsafe fn contrast_neon_u8(channel: &mut [u8], factor: f32) {
+    let factor_v = vdupq_n_f32(factor);
+    let half = vdupq_n_f32(128.0);
+    let max = vdupq_n_f32(255.0);
+
+    map_u8_as_f32(
+        channel,
+        |x| {
+            // multiply then add instead of fusing so rounding matches the scalar version
+            let new_val = vaddq_f32(vmulq_f32(factor_v, vsubq_f32(x, half)), half);
+            vminq_f32(vmaxq_f32(new_val, vdupq_n_f32(0.0)), max)
+        },
+        |rest| super::contrast_u8_scalar(rest, factor)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::contrast::contrast_u8_scalar;
+    use crate::contrast::neon::contrast_neon_u8;
+
+    #[test]
+    fn test_contrast_neon_matches_scalar() {
+        for contrast in [-200.0, -10.0, 0.0, 35.5, 254.0] {
+            let factor = (259.0 * (contrast + 255.0)) / (255.0 * (259.0 - contrast));
+
+            let mut neon: Vec<u8> = (0..=255).chain(0..=20).collect();
+            let mut scalar = neon.clone();
+
+            unsafe {
+                contrast_neon_u8(&mut neon, factor);
+            }
+            contrast_u8_scalar(&mut scalar, factor);
+            assert_eq!(neon, scalar);
+        }
+    }
+}
@@ -14,6 +14,8 @@
 //! The intermediate calculations are carried in `f32`
 //!
 
+use alloc::vec::Vec;
+
 use zune_core::bit_depth::BitType;
 use zune_image::channel::Channel;
 use zune_image::errors::ImageErrors;
@@ -22,7 +24,7 @@ use zune_image::traits::OperationsTrait;
 
 use crate::pad::{pad, PadMethod};
 use crate::traits::NumOps;
-use crate::utils::{execute_on, z_prefetch};
+use crate::utils::{execute_on, for_each_padded_row_band, z_prefetch};
 
 /// Convolve an image
 ///
@@ -58,8 +60,9 @@ use crate::utils::{execute_on, z_prefetch};
 /// ```
 #[derive(Default)]
 pub struct Convolve {
-    weights: Vec<f32>,
-    scale:   f32
+    weights:    Vec<f32>,
+    scale:      f32,
+    pad_method: PadMethod
 }
 
 impl Convolve {
@@ -68,7 +71,17 @@ impl Convolve {
     /// The operation will return an error if the weights length isn't 9(3x3),25(5x5) or 49(7x7)
     #[must_use]
     pub fn new(weights: Vec<f32>, scale: f32) -> Convolve {
-        Convolve { weights, scale }
+        Convolve {
+            weights,
+            scale,
+            pad_method: PadMethod::Replicate
+        }
+    }
+    /// Set how pixels outside the image are computed, defaults to [PadMethod::Replicate]
+    #[must_use]
+    pub fn set_pad_method(mut self, pad_method: PadMethod) -> Self {
+        self.pad_method = pad_method;
+        self
     }
 }
 
@@ -86,33 +99,36 @@ impl OperationsTrait for Convolve {
 
             match depth.bit_type() {
                 BitType::U8 => {
-                    convolve(
+                    convolve_with_border(
                         channel.reinterpret_as::<u8>()?,
                         out_channel.reinterpret_as_mut::<u8>()?,
                         width,
                         height,
                         &self.weights,
-                        self.scale
+                        self.scale,
+                        self.pad_method
                     )?;
                 }
                 BitType::U16 => {
-                    convolve(
+                    convolve_with_border(
                         channel.reinterpret_as::<u16>()?,
                         out_channel.reinterpret_as_mut::<u16>()?,
                         width,
                         height,
                         &self.weights,
-                        self.scale
+                        self.scale,
+                        self.pad_method
                     )?;
                 }
                 BitType::F32 => {
-                    convolve(
+                    convolve_with_border(
                         channel.reinterpret_as::<f32>()?,
                         out_channel.reinterpret_as_mut::<f32>()?,
                         width,
                         height,
                         &self.weights,
-                        self.scale
+                        self.scale,
+                        self.pad_method
                     )?;
                 }
                 d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
@@ -130,7 +146,7 @@ impl OperationsTrait for Convolve {
 
 fn convolve_3x3_inner<T>(in_array: &[T; 9], weights: &[f32; 9], scale: f32) -> T
 where
-    T: NumOps<T> + Copy + Default,
+    T: NumOps<T> + Copy + Default + Send + Sync,
     f32: From<T>
 {
     T::from_f32(
@@ -146,7 +162,7 @@ where
 
 fn convolve_5x5_inner<T>(in_array: &[T; 25], weights: &[f32; 25], scale: f32) -> T
 where
-    T: NumOps<T> + Copy + Default,
+    T: NumOps<T> + Copy + Default + Send + Sync,
     f32: From<T>
 {
     T::from_f32(
@@ -162,7 +178,7 @@ where
 
 fn convolve_7x7_inner<T>(in_array: &[T; 49], weights: &[f32; 49], scale: f32) -> T
 where
-    T: NumOps<T> + Copy + Default,
+    T: NumOps<T> + Copy + Default + Send + Sync,
     f32: From<T>
 {
     T::from_f32(
@@ -181,12 +197,31 @@ pub fn convolve_3x3<T>(
     in_channel: &[T], out_channel: &mut [T], width: usize, height: usize, weights: &[f32; 9],
     scale: f32
 ) where
-    T: NumOps<T> + Copy + Default,
+    T: NumOps<T> + Copy + Default + Send + Sync,
+    f32: From<T>
+{
+    convolve_3x3_with_border(
+        in_channel,
+        out_channel,
+        width,
+        height,
+        weights,
+        scale,
+        PadMethod::Replicate
+    )
+}
+
+/// [`convolve_3x3`] with pixels outside the image computed by `pad_method`
+pub fn convolve_3x3_with_border<T>(
+    in_channel: &[T], out_channel: &mut [T], width: usize, height: usize, weights: &[f32; 9],
+    scale: f32, pad_method: PadMethod
+) where
+    T: NumOps<T> + Copy + Default + Send + Sync,
     f32: From<T>
 {
     // pad input
     //pad here
-    let padded_input = pad(in_channel, width, height, 1, 1, PadMethod::Replicate);
+    let padded_input = pad(in_channel, width, height, 1, 1, pad_method);
 
     spatial_NxN::<T, _, 1, 9>(
         &padded_input,
@@ -203,12 +238,31 @@ pub fn convolve_5x5<T>(
     in_channel: &[T], out_channel: &mut [T], width: usize, height: usize, weights: &[f32; 25],
     scale: f32
 ) where
-    T: NumOps<T> + Copy + Default,
+    T: NumOps<T> + Copy + Default + Send + Sync,
+    f32: From<T>
+{
+    convolve_5x5_with_border(
+        in_channel,
+        out_channel,
+        width,
+        height,
+        weights,
+        scale,
+        PadMethod::Replicate
+    )
+}
+
+/// [`convolve_5x5`] with pixels outside the image computed by `pad_method`
+pub fn convolve_5x5_with_border<T>(
+    in_channel: &[T], out_channel: &mut [T], width: usize, height: usize, weights: &[f32; 25],
+    scale: f32, pad_method: PadMethod
+) where
+    T: NumOps<T> + Copy + Default + Send + Sync,
     f32: From<T>
 {
     // pad input
     //pad here
-    let padded_input = pad(in_channel, width, height, 2, 2, PadMethod::Replicate);
+    let padded_input = pad(in_channel, width, height, 2, 2, pad_method);
 
     spatial_NxN::<T, _, 2, 25>(
         &padded_input,
@@ -225,12 +279,31 @@ pub fn convolve_7x7<T>(
     in_channel: &[T], out_channel: &mut [T], width: usize, height: usize, weights: &[f32; 49],
     scale: f32
 ) where
-    T: NumOps<T> + Copy + Default,
+    T: NumOps<T> + Copy + Default + Send + Sync,
+    f32: From<T>
+{
+    convolve_7x7_with_border(
+        in_channel,
+        out_channel,
+        width,
+        height,
+        weights,
+        scale,
+        PadMethod::Replicate
+    )
+}
+
+/// [`convolve_7x7`] with pixels outside the image computed by `pad_method`
+pub fn convolve_7x7_with_border<T>(
+    in_channel: &[T], out_channel: &mut [T], width: usize, height: usize, weights: &[f32; 49],
+    scale: f32, pad_method: PadMethod
+) where
+    T: NumOps<T> + Copy + Default + Send + Sync,
     f32: From<T>
 {
     // pad input
     //pad here
-    let padded_input = pad(in_channel, width, height, 3, 3, PadMethod::Replicate);
+    let padded_input = pad(in_channel, width, height, 3, 3, pad_method);
 
     spatial_NxN::<T, _, 3, 49>(
         &padded_input,
@@ -249,35 +322,58 @@ pub fn convolve<T>(
     scale: f32
 ) -> Result<(), &'static str>
 where
-    T: NumOps<T> + Copy + Default,
-    f32: std::convert::From<T>
+    T: NumOps<T> + Copy + Default + Send + Sync,
+    f32: core::convert::From<T>
+{
+    convolve_with_border(
+        in_channel,
+        out_channel,
+        width,
+        height,
+        weights,
+        scale,
+        PadMethod::Replicate
+    )
+}
+
+/// [`convolve`] with pixels outside the image computed by `pad_method`
+pub fn convolve_with_border<T>(
+    in_channel: &[T], out_channel: &mut [T], width: usize, height: usize, weights: &[f32],
+    scale: f32, pad_method: PadMethod
+) -> Result<(), &'static str>
+where
+    T: NumOps<T> + Copy + Default + Send + Sync,
+    f32: core::convert::From<T>
 {
     if weights.len() == 9 {
-        convolve_3x3::<T>(
+        convolve_3x3_with_border::<T>(
             in_channel,
             out_channel,
             width,
             height,
             weights.try_into().unwrap(),
-            scale
+            scale,
+            pad_method
         );
     } else if weights.len() == 25 {
-        convolve_5x5::<T>(
+        convolve_5x5_with_border::<T>(
             in_channel,
             out_channel,
             width,
             height,
             weights.try_into().unwrap(),
-            scale
+            scale,
+            pad_method
         );
     } else if weights.len() == 49 {
-        convolve_7x7::<T>(
+        convolve_7x7_with_border::<T>(
             in_channel,
             out_channel,
             width,
             height,
             weights.try_into().unwrap(),
-            scale
+            scale,
+            pad_method
         );
     } else {
         return Err("Not implemented, only works for 3x3, 5x5 and 7x7 arrays");
@@ -291,6 +387,39 @@ where
 fn spatial_NxN<T, F, const RADIUS: usize, const OUT_SIZE: usize>(
     in_channel: &[T], out_channel: &mut [T], width: usize, height: usize, function: F,
     values: &[f32; OUT_SIZE], scale: f32
+) where
+    T: Default + Copy + Send + Sync,
+    F: Fn(&[T; OUT_SIZE], &[f32; OUT_SIZE], f32) -> T + Send + Sync
+{
+    assert_eq!(
+        (height + RADIUS * 2) * (width + RADIUS * 2),
+        in_channel.len()
+    );
+    // windows of a row only read the rows around it, so bands of rows run in parallel
+    for_each_padded_row_band(
+        in_channel,
+        out_channel,
+        width,
+        height,
+        RADIUS,
+        |in_band, out_band, band_height| {
+            spatial_NxN_band::<T, _, RADIUS, OUT_SIZE>(
+                in_band,
+                out_band,
+                width,
+                band_height,
+                &function,
+                values,
+                scale
+            );
+        }
+    );
+}
+
+#[allow(non_snake_case)]
+fn spatial_NxN_band<T, F, const RADIUS: usize, const OUT_SIZE: usize>(
+    in_channel: &[T], out_channel: &mut [T], width: usize, height: usize, function: F,
+    values: &[f32; OUT_SIZE], scale: f32
 ) where
     T: Default + Copy,
     F: Fn(&[T; OUT_SIZE], &[f32; OUT_SIZE], f32) -> T
@@ -50,18 +50,26 @@
 //!
 //!
 
+use alloc::vec::Vec;
+
 use zune_core::bit_depth::BitType;
 use zune_image::channel::Channel;
 use zune_image::errors::ImageErrors;
-use zune_image::image::Image;
-use zune_image::traits::OperationsTrait;
+use zune_image::image::{Image, MAX_CHANNELS};
+use zune_image::traits::{OperationsTrait, Parameter, ParameterKind, ParameterValue};
 
+use crate::pad::{border_index, PadMethod};
+use crate::traits::NumOps;
 use crate::utils::execute_on;
+use crate::DepthColor;
 
 /// Crop out a part of an image
 ///
 /// This creates a smaller image from a bigger image
 ///
+/// The crop rectangle may also extend outside the image, see [Crop::new_with_border], in which case
+/// the uncovered area is filled according to a [PadMethod]
+///
 /// # Example
 /// Create a smaller 100x100 from a larger 1000x1000 image based on the left edge
 /// ```
@@ -91,11 +99,14 @@ use crate::utils::execute_on;
 ///      Ok(())
 /// }
 /// ```
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Crop {
-    x:      usize,
-    y:      usize,
+    x:      isize,
+    y:      isize,
     width:  usize,
-    height: usize
+    height: usize,
+    border: Option<PadMethod>,
+    fill:   DepthColor
 }
 
 impl Crop {
@@ -109,14 +120,72 @@ impl Crop {
     ///
     /// Origin is defined as the image top left corner.
     #[must_use]
+    #[allow(clippy::cast_possible_wrap)]
     pub fn new(width: usize, height: usize, x: usize, y: usize) -> Crop {
+        Crop {
+            x: x as isize,
+            y: y as isize,
+            width,
+            height,
+            border: None,
+            fill: [0.0; MAX_CHANNELS]
+        }
+    }
+    /// Create a new crop operation where the rectangle may be partially or fully
+    /// outside the image
+    ///
+    /// # Arguments
+    /// - width: The width of the new cropped out image
+    /// - height: The height of the new cropped out image.
+    /// - x: How far from the x origin the image should start from, may be negative
+    /// - y: How far from the y origin the image should start from, may be negative
+    /// - border: How to fill the area of the rectangle that is outside the image,
+    ///   for [PadMethod::Constant], the value can be set via [set_fill_color](Self::set_fill_color)
+    ///
+    /// # Example
+    /// Extend a 100x100 image by 10 pixels on each side, mirroring the edges
+    /// ```
+    /// use zune_core::colorspace::ColorSpace;
+    /// use zune_image::image::Image;
+    /// use zune_image::traits::OperationsTrait;
+    /// use zune_imageprocs::crop::Crop;
+    /// use zune_imageprocs::pad::PadMethod;
+    ///
+    /// let mut image = Image::fill(255_u8, ColorSpace::RGB, 100, 100);
+    /// Crop::new_with_border(120, 120, -10, -10, PadMethod::Reflect)
+    ///     .execute(&mut image)
+    ///     .unwrap();
+    /// assert_eq!(image.dimensions(), (120, 120));
+    /// ```
+    #[must_use]
+    pub fn new_with_border(
+        width: usize, height: usize, x: isize, y: isize, border: PadMethod
+    ) -> Crop {
         Crop {
             x,
             y,
             width,
-            height
+            height,
+            border: Some(border),
+            fill: [0.0; MAX_CHANNELS]
         }
     }
+    /// Set the color used to fill the area outside the image when the border
+    /// method is [PadMethod::Constant], see [DepthColor] for the range of the values
+    #[must_use]
+    pub fn set_fill_color(mut self, fill: DepthColor) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    /// Whether the crop rectangle lies completely inside an image of these dimensions
+    #[allow(clippy::cast_sign_loss)]
+    fn is_inside(&self, width: usize, height: usize) -> bool {
+        self.x >= 0
+            && self.y >= 0
+            && (self.x as usize).saturating_add(self.width) <= width
+            && (self.y as usize).saturating_add(self.height) <= height
+    }
 }
 
 impl OperationsTrait for Crop {
@@ -124,11 +193,90 @@ impl OperationsTrait for Crop {
         "Crop"
     }
 
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                name:        "width",
+                description: "Width of the crop",
+                kind:        ParameterKind::Integer { min: 1, max: 65535 },
+                default:     ParameterValue::Integer(1)
+            },
+            Parameter {
+                name:        "height",
+                description: "Height of the crop",
+                kind:        ParameterKind::Integer { min: 1, max: 65535 },
+                default:     ParameterValue::Integer(1)
+            },
+            Parameter {
+                name:        "x",
+                description: "Left edge of the crop",
+                kind:        ParameterKind::Integer { min: 0, max: 65535 },
+                default:     ParameterValue::Integer(0)
+            },
+            Parameter {
+                name:        "y",
+                description: "Top edge of the crop",
+                kind:        ParameterKind::Integer { min: 0, max: 65535 },
+                default:     ParameterValue::Integer(0)
+            }
+        ]
+    }
+
+    #[allow(clippy::cast_sign_loss)]
     fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
         let new_dims = self.width * self.height * image.depth().size_of();
-        let (old_width, _) = image.dimensions();
+        let (old_width, old_height) = image.dimensions();
         let depth = image.depth().bit_type();
 
+        if let Some(border) = self.border.filter(|_| !self.is_inside(old_width, old_height)) {
+            let colorspace = image.colorspace();
+
+            for frame in image.frames_mut() {
+                for (channel, fill) in frame
+                    .channels_mut(colorspace, false)
+                    .iter_mut()
+                    .zip(self.fill)
+                {
+                    let mut new_vec =
+                        Channel::new_with_length_and_type(new_dims, channel.type_id());
+
+                    match depth {
+                        BitType::U8 => self.crop_with_border::<u8>(
+                            channel.reinterpret_as()?,
+                            old_width,
+                            old_height,
+                            new_vec.reinterpret_as_mut()?,
+                            border,
+                            fill
+                        ),
+                        BitType::U16 => self.crop_with_border::<u16>(
+                            channel.reinterpret_as()?,
+                            old_width,
+                            old_height,
+                            new_vec.reinterpret_as_mut()?,
+                            border,
+                            fill
+                        ),
+                        BitType::F32 => self.crop_with_border::<f32>(
+                            channel.reinterpret_as()?,
+                            old_width,
+                            old_height,
+                            new_vec.reinterpret_as_mut()?,
+                            border,
+                            fill
+                        ),
+                        d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+                    }
+                    *channel = new_vec;
+                }
+            }
+            image.set_dimensions(self.width, self.height);
+
+            return Ok(());
+        }
+        // inside the image or a plain crop, neither has negative offsets
+        let (x, y) = (self.x as usize, self.y as usize);
+
         let crop_fn = |channel: &mut Channel| -> Result<(), ImageErrors> {
             let mut new_vec = Channel::new_with_length_and_type(new_dims, channel.type_id());
 
@@ -142,8 +290,8 @@ impl OperationsTrait for Crop {
                         new_vec.reinterpret_as_mut()?,
                         self.width,
                         self.height,
-                        self.x,
-                        self.y
+                        x,
+                        y
                     );
                 }
                 BitType::U16 => {
@@ -153,8 +301,8 @@ impl OperationsTrait for Crop {
                         new_vec.reinterpret_as_mut()?,
                         self.width,
                         self.height,
-                        self.x,
-                        self.y
+                        x,
+                        y
                     );
                 }
                 BitType::F32 => {
@@ -164,8 +312,8 @@ impl OperationsTrait for Crop {
                         new_vec.reinterpret_as_mut()?,
                         self.width,
                         self.height,
-                        self.x,
-                        self.y
+                        x,
+                        y
                     );
                 }
                 d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
@@ -186,6 +334,27 @@ impl OperationsTrait for Crop {
     }
 }
 
+impl Crop {
+    fn crop_with_border<T: Copy + NumOps<T>>(
+        &self, in_image: &[T], in_width: usize, in_height: usize, out_image: &mut [T],
+        border: PadMethod, fill: f32
+    ) {
+        let fill = T::from_f32(fill.clamp(T::MIN_VAL.to_f32(), T::MAX_VAL.to_f32()));
+
+        crop_with_border(
+            in_image,
+            in_width,
+            in_height,
+            out_image,
+            self.width,
+            self.height,
+            (self.x, self.y),
+            border,
+            fill
+        );
+    }
+}
+
 /// Crop an image channel
 ///
 /// # Arguments
@@ -231,6 +400,93 @@ pub fn crop<T: Copy>(
     }
 }
 
+/// Crop an image channel where the crop rectangle may extend outside the channel
+///
+/// # Arguments
+///
+/// * `in_image`:   Input image/image channel
+/// * `in_width`, `in_height`:   Input dimensions
+/// * `out_image`:  Output image/image channel, should have `out_width*out_height` elements
+/// * `out_width`, `out_height`: Output dimensions
+/// * `origin`:  `(x,y)` offset of the rectangle from the input top left corner, may be negative
+/// * `method`: How to fill pixels outside the input
+/// * `fill`: Value used for pixels outside the input when `method` is [PadMethod::Constant]
+#[allow(clippy::too_many_arguments, clippy::cast_possible_wrap)]
+pub fn crop_with_border<T: Copy>(
+    in_image: &[T], in_width: usize, in_height: usize, out_image: &mut [T], out_width: usize,
+    out_height: usize, origin: (isize, isize), method: PadMethod, fill: T
+) {
+    if out_width == 0 {
+        return;
+    }
+    // source columns are the same for every row
+    let columns: Vec<Option<usize>> = (0..out_width)
+        .map(|x| border_index(origin.0 + x as isize, in_width, method))
+        .collect();
+
+    for (y, out) in out_image
+        .chunks_exact_mut(out_width)
+        .take(out_height)
+        .enumerate()
+    {
+        let Some(src_y) = border_index(origin.1 + y as isize, in_height, method) else {
+            out.fill(fill);
+            continue;
+        };
+        let in_row = &in_image[src_y * in_width..(src_y + 1) * in_width];
+
+        for (pix, x) in out.iter_mut().zip(&columns) {
+            *pix = x.map_or(fill, |x| in_row[x]);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::cast_possible_truncation)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::crop::Crop;
+    use crate::pad::PadMethod;
+
+    #[test]
+    fn test_crop_outside_constant() {
+        let mut image = Image::from_fn::<u8, _>(4, 4, ColorSpace::Luma, |y, x, pix| {
+            pix[0] = (y * 4 + x) as u8;
+        });
+        Crop::new_with_border(3, 3, -1, 2, PadMethod::Constant)
+            .set_fill_color([99.0; 4])
+            .execute(&mut image)
+            .unwrap();
+
+        let pixels = image.channels_ref(false)[0].reinterpret_as::<u8>().unwrap();
+        #[rustfmt::skip]
+        let expected = [
+            99, 8, 9,
+            99, 12, 13,
+            99, 99, 99
+        ];
+        assert_eq!(pixels, expected);
+    }
+
+    #[test]
+    fn test_crop_outside_wrap() {
+        let mut image = Image::from_fn::<u16, _>(3, 1, ColorSpace::Luma, |_, x, pix| {
+            pix[0] = x as u16;
+        });
+        Crop::new_with_border(5, 1, -1, 0, PadMethod::Wrap)
+            .execute(&mut image)
+            .unwrap();
+
+        let pixels = image.channels_ref(false)[0]
+            .reinterpret_as::<u16>()
+            .unwrap();
+        assert_eq!(pixels, [2, 0, 1, 2, 0]);
+    }
+}
+
 #[cfg(feature = "benchmarks")]
 #[cfg(test)]
 mod benchmarks {
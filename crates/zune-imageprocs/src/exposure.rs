@@ -22,7 +22,7 @@ use zune_core::bit_depth::BitType;
 use zune_image::channel::Channel;
 use zune_image::errors::ImageErrors;
 use zune_image::image::Image;
-use zune_image::traits::OperationsTrait;
+use zune_image::traits::{OperationsTrait, Parameter, ParameterKind, ParameterValue};
 
 use crate::utils::execute_on;
 
@@ -51,6 +51,7 @@ use crate::utils::execute_on;
 ///# Ok::<(),ImageErrors>(())
 /// ```
 ///
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Exposure {
     exposure: f32,
     black:    f32
@@ -79,6 +80,23 @@ impl OperationsTrait for Exposure {
         "Exposure"
     }
 
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                name:        "exposure",
+                description: "Exposure multiplier",
+                kind:        ParameterKind::Float { min: 0.0, max: 4.0 },
+                default:     ParameterValue::Float(1.0)
+            },
+            Parameter {
+                name:        "black",
+                description: "Black level correction",
+                kind:        ParameterKind::Float { min: 0.0, max: 1.0 },
+                default:     ParameterValue::Float(0.0)
+            }
+        ]
+    }
+
     #[allow(
         clippy::cast_sign_loss,
         clippy::cast_lossless,
@@ -120,4 +138,7 @@ impl OperationsTrait for Exposure {
     fn supported_types(&self) -> &'static [BitType] {
         &[BitType::U8, BitType::U16, BitType::F32]
     }
+    fn is_point_op(&self) -> bool {
+        true
+    }
 }
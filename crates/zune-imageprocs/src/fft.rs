@@ -0,0 +1,556 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Fast fourier transforms and frequency domain filtering
+//!
+//! This contains a 2D real-to-complex FFT for single image channels
+//! and an operation to filter images in the frequency domain, useful for
+//! descreening and removing periodic noise.
+//!
+//! # Algorithm
+//! 1D transforms use an iterative radix-2 Cooley-Tukey FFT for power of two lengths,
+//! other lengths are handled via Bluestein's algorithm, which expresses the transform as
+//! a convolution that can be computed with a power of two FFT.
+//!
+//! The 2D transform is separable, rows are transformed first, keeping only the non-redundant
+//! half of the spectrum (real input has a hermitian symmetric spectrum), then columns.
+//!
+//! ## Spectrum layout
+//! A [Spectrum] of a `width x height` channel has `width/2+1` columns and `height` rows.
+//! The DC component is at `(0,0)`, column `u` holds horizontal frequency `u/width`, row `v` holds
+//! vertical frequency `v/height` for `v <= height/2` and `(v-height)/height` otherwise.
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f64::consts::PI;
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use zune_core::bit_depth::BitType;
+use zune_image::channel::Channel;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::utils::execute_on;
+
+/// A complex number
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Complex {
+    pub re: f32,
+    pub im: f32
+}
+
+impl Complex {
+    /// Create a new complex number
+    #[must_use]
+    pub const fn new(re: f32, im: f32) -> Complex {
+        Complex { re, im }
+    }
+    /// Magnitude (absolute value) of the complex number
+    #[must_use]
+    pub fn norm(self) -> f32 {
+        self.re.hypot(self.im)
+    }
+    /// Phase angle of the complex number in radians
+    #[must_use]
+    pub fn arg(self) -> f32 {
+        self.im.atan2(self.re)
+    }
+}
+
+/// Internal double precision complex number, used during transforms
+#[derive(Copy, Clone, Debug, Default)]
+struct C64 {
+    re: f64,
+    im: f64
+}
+
+impl C64 {
+    const fn new(re: f64, im: f64) -> C64 {
+        C64 { re, im }
+    }
+    fn add(self, other: C64) -> C64 {
+        C64::new(self.re + other.re, self.im + other.im)
+    }
+    fn sub(self, other: C64) -> C64 {
+        C64::new(self.re - other.re, self.im - other.im)
+    }
+    fn mul(self, other: C64) -> C64 {
+        C64::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re
+        )
+    }
+    fn conj(self) -> C64 {
+        C64::new(self.re, -self.im)
+    }
+    fn scale(self, s: f64) -> C64 {
+        C64::new(self.re * s, self.im * s)
+    }
+}
+
+/// A precomputed 1D transform of a fixed length
+enum Plan {
+    Radix2 {
+        twiddles: Vec<C64>,
+        reversed: Vec<usize>
+    },
+    Bluestein {
+        len:         usize,
+        chirp:       Vec<C64>,
+        kernel_fft:  Vec<C64>,
+        inner:       Box<Plan>,
+        padded_size: usize
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+impl Plan {
+    fn new(len: usize) -> Plan {
+        if len.is_power_of_two() {
+            let bits = len.trailing_zeros();
+            let reversed = (0..len)
+                .map(
+                    |i| {
+                        if bits == 0 {
+                            0
+                        } else {
+                            i.reverse_bits() >> (usize::BITS - bits)
+                        }
+                    }
+                )
+                .collect();
+            let twiddles = (0..len / 2)
+                .map(|i| {
+                    let angle = -2.0 * PI * i as f64 / len as f64;
+                    C64::new(angle.cos(), angle.sin())
+                })
+                .collect();
+
+            Plan::Radix2 { twiddles, reversed }
+        } else {
+            // chirp[k] = exp(-i*pi*k^2/n)
+            let chirp: Vec<C64> = (0..len)
+                .map(|k| {
+                    // reduce k^2 mod 2n to keep the angle precise for large k
+                    let k2 = (k * k) % (2 * len);
+                    let angle = -PI * k2 as f64 / len as f64;
+                    C64::new(angle.cos(), angle.sin())
+                })
+                .collect();
+            let padded_size = (2 * len - 1).next_power_of_two();
+            let inner = Box::new(Plan::new(padded_size));
+
+            let mut kernel = vec![C64::default(); padded_size];
+            kernel[0] = chirp[0].conj();
+            for k in 1..len {
+                kernel[k] = chirp[k].conj();
+                kernel[padded_size - k] = chirp[k].conj();
+            }
+            inner.forward(&mut kernel);
+
+            Plan::Bluestein {
+                len,
+                chirp,
+                kernel_fft: kernel,
+                inner,
+                padded_size
+            }
+        }
+    }
+
+    /// Forward transform, in place and unnormalized
+    fn forward(&self, data: &mut [C64]) {
+        match self {
+            Plan::Radix2 { twiddles, reversed } => {
+                let n = data.len();
+
+                for (i, r) in reversed.iter().enumerate() {
+                    if i < *r {
+                        data.swap(i, *r);
+                    }
+                }
+                let mut size = 2;
+
+                while size <= n {
+                    let half = size / 2;
+                    let stride = n / size;
+
+                    for chunk in data.chunks_exact_mut(size) {
+                        for j in 0..half {
+                            let t = chunk[j + half].mul(twiddles[j * stride]);
+                            let u = chunk[j];
+                            chunk[j] = u.add(t);
+                            chunk[j + half] = u.sub(t);
+                        }
+                    }
+                    size *= 2;
+                }
+            }
+            Plan::Bluestein {
+                len,
+                chirp,
+                kernel_fft,
+                inner,
+                padded_size
+            } => {
+                let mut scratch = vec![C64::default(); *padded_size];
+
+                for ((s, d), c) in scratch.iter_mut().zip(data.iter()).zip(chirp) {
+                    *s = d.mul(*c);
+                }
+                inner.forward(&mut scratch);
+
+                for (s, k) in scratch.iter_mut().zip(kernel_fft) {
+                    *s = s.mul(*k);
+                }
+                inner.inverse(&mut scratch);
+
+                let scale = 1.0 / *padded_size as f64;
+
+                for ((d, s), c) in data.iter_mut().zip(&scratch).zip(chirp).take(*len) {
+                    *d = s.mul(*c).scale(scale);
+                }
+            }
+        }
+    }
+    /// Inverse transform, in place and unnormalized
+    fn inverse(&self, data: &mut [C64]) {
+        // ifft(x) = conj(fft(conj(x)))
+        for d in data.iter_mut() {
+            *d = d.conj();
+        }
+        self.forward(data);
+
+        for d in data.iter_mut() {
+            *d = d.conj();
+        }
+    }
+}
+
+/// The frequency spectrum of a single image channel
+///
+/// See the [module docs](self) for the layout
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spectrum {
+    /// Spectrum values, in row major order
+    pub data:   Vec<Complex>,
+    /// Width of the spatial channel this spectrum was computed from
+    pub width:  usize,
+    /// Height of the spatial channel this spectrum was computed from
+    pub height: usize
+}
+
+impl Spectrum {
+    /// Number of columns stored in the spectrum, this is `width/2+1`
+    #[must_use]
+    pub const fn columns(&self) -> usize {
+        self.width / 2 + 1
+    }
+    /// Return the normalized frequency of a spectrum position as `(fx,fy)`
+    ///
+    /// Frequencies are in cycles per pixel, in the range `-0.5..=0.5`
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn frequency(&self, column: usize, row: usize) -> (f32, f32) {
+        let fx = column as f32 / self.width as f32;
+        let fy = if row <= self.height / 2 {
+            row as f32 / self.height as f32
+        } else {
+            -((self.height - row) as f32) / self.height as f32
+        };
+        (fx, fy)
+    }
+    /// Multiply each spectrum value by the result of `func`
+    ///
+    /// `func` receives the normalized frequency `(fx,fy)` of each value,
+    /// see [frequency](Self::frequency)
+    pub fn apply_mask<F: Fn(f32, f32) -> f32>(&mut self, func: F) {
+        let columns = self.columns();
+
+        for row in 0..self.height {
+            for column in 0..columns {
+                let (fx, fy) = self.frequency(column, row);
+                let weight = func(fx, fy);
+
+                let value = &mut self.data[row * columns + column];
+                value.re *= weight;
+                value.im *= weight;
+            }
+        }
+    }
+}
+
+/// Compute the 2D forward transform of a single channel
+///
+/// # Arguments
+/// - pixels: The channel, should have `width*height` elements
+/// - width,height: Channel dimensions
+///
+/// # Panics
+/// If `pixels` is smaller than `width*height`
+#[must_use]
+#[allow(clippy::cast_possible_truncation)]
+pub fn fft_2d(pixels: &[f32], width: usize, height: usize) -> Spectrum {
+    assert!(pixels.len() >= width * height, "Pixels too small");
+
+    if width == 0 || height == 0 {
+        return Spectrum {
+            data: vec![],
+            width,
+            height
+        };
+    }
+    let columns = width / 2 + 1;
+    let mut data = vec![C64::default(); columns * height];
+
+    // rows
+    let row_plan = Plan::new(width);
+    let mut row = vec![C64::default(); width];
+
+    for (in_row, out_row) in pixels
+        .chunks_exact(width)
+        .zip(data.chunks_exact_mut(columns))
+    {
+        for (r, p) in row.iter_mut().zip(in_row) {
+            *r = C64::new(f64::from(*p), 0.0);
+        }
+        row_plan.forward(&mut row);
+        out_row.copy_from_slice(&row[..columns]);
+    }
+    // columns
+    let column_plan = Plan::new(height);
+    let mut column = vec![C64::default(); height];
+
+    for x in 0..columns {
+        for (y, c) in column.iter_mut().enumerate() {
+            *c = data[y * columns + x];
+        }
+        column_plan.forward(&mut column);
+
+        for (y, c) in column.iter().enumerate() {
+            data[y * columns + x] = *c;
+        }
+    }
+
+    Spectrum {
+        data: data
+            .iter()
+            .map(|c| Complex::new(c.re as f32, c.im as f32))
+            .collect(),
+        width,
+        height
+    }
+}
+
+/// Compute the 2D inverse transform of a spectrum, writing pixels to `pixels`
+///
+/// This is normalized, so `ifft_2d(fft_2d(x)) == x` save for rounding errors.
+///
+/// # Panics
+/// If `pixels` is smaller than `width*height` of the spectrum or the spectrum
+/// has the wrong length
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+pub fn ifft_2d(spectrum: &Spectrum, pixels: &mut [f32]) {
+    let (width, height) = (spectrum.width, spectrum.height);
+    let columns = spectrum.columns();
+
+    assert!(pixels.len() >= width * height, "Pixels too small");
+
+    if width == 0 || height == 0 {
+        return;
+    }
+    assert_eq!(spectrum.data.len(), columns * height, "Invalid spectrum");
+
+    let mut data: Vec<C64> = spectrum
+        .data
+        .iter()
+        .map(|c| C64::new(f64::from(c.re), f64::from(c.im)))
+        .collect();
+
+    // columns
+    let column_plan = Plan::new(height);
+    let mut column = vec![C64::default(); height];
+
+    for x in 0..columns {
+        for (y, c) in column.iter_mut().enumerate() {
+            *c = data[y * columns + x];
+        }
+        column_plan.inverse(&mut column);
+
+        for (y, c) in column.iter().enumerate() {
+            data[y * columns + x] = *c;
+        }
+    }
+    // rows, reconstructing the redundant half from hermitian symmetry
+    let row_plan = Plan::new(width);
+    let mut row = vec![C64::default(); width];
+    let scale = 1.0 / (width * height) as f64;
+
+    for (in_row, out_row) in data
+        .chunks_exact(columns)
+        .zip(pixels.chunks_exact_mut(width))
+    {
+        row[..columns].copy_from_slice(in_row);
+
+        for x in columns..width {
+            row[x] = in_row[width - x].conj();
+        }
+        row_plan.inverse(&mut row);
+
+        for (o, r) in out_row.iter_mut().zip(&row) {
+            *o = (r.re * scale) as f32;
+        }
+    }
+}
+
+/// Frequency domain filters
+///
+/// Cutoffs are normalized frequencies in cycles per pixel, in the range `0..=0.5`, where
+/// `0.5` is the highest frequency an image can represent (the Nyquist frequency).
+///
+/// Filters use gaussian transitions to avoid ringing artifacts that sharp cutoffs cause
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FrequencyFilterType {
+    /// Keep frequencies below the cutoff, blurring the image
+    LowPass(f32),
+    /// Keep frequencies above the cutoff, keeping edges and fine detail
+    HighPass(f32),
+    /// Keep frequencies between the low and high cutoffs
+    BandPass(f32, f32),
+    /// Remove frequencies between the low and high cutoffs, useful for removing
+    /// periodic noise and halftone screens
+    BandStop(f32, f32)
+}
+
+impl FrequencyFilterType {
+    /// Return the weight of a frequency for this filter, in the range `0..=1`
+    #[must_use]
+    pub fn weight(self, fx: f32, fy: f32) -> f32 {
+        let distance_sq = fx * fx + fy * fy;
+
+        let low_pass = |cutoff: f32| -> f32 {
+            if cutoff <= 0.0 {
+                return 0.0;
+            }
+            (-distance_sq / (2.0 * cutoff * cutoff)).exp()
+        };
+
+        match self {
+            FrequencyFilterType::LowPass(cutoff) => low_pass(cutoff),
+            FrequencyFilterType::HighPass(cutoff) => 1.0 - low_pass(cutoff),
+            FrequencyFilterType::BandPass(low, high) => low_pass(high) * (1.0 - low_pass(low)),
+            FrequencyFilterType::BandStop(low, high) => 1.0 - low_pass(high) * (1.0 - low_pass(low))
+        }
+    }
+}
+
+/// Filter an image in the frequency domain
+///
+/// Each channel is transformed, multiplied with the filter and transformed back.
+///
+/// This only works on float images, convert the image to [F32](zune_core::bit_depth::BitDepth::Float32)
+/// first. Values are not clamped afterwards since high pass filters produce negative values.
+///
+/// # Example
+/// ```
+/// use zune_core::bit_depth::BitDepth;
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::fft::{FrequencyFilter, FrequencyFilterType};
+///
+/// let mut image = Image::fill(128_u8, ColorSpace::RGB, 30, 20);
+/// image.convert_depth(BitDepth::Float32).unwrap();
+///
+/// FrequencyFilter::new(FrequencyFilterType::LowPass(0.1)).execute(&mut image).unwrap();
+/// ```
+pub struct FrequencyFilter {
+    filter: FrequencyFilterType
+}
+
+impl FrequencyFilter {
+    /// Create a new frequency domain filter
+    #[must_use]
+    pub fn new(filter: FrequencyFilterType) -> FrequencyFilter {
+        FrequencyFilter { filter }
+    }
+}
+
+impl OperationsTrait for FrequencyFilter {
+    fn name(&self) -> &'static str {
+        "Frequency Filter"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let depth = image.depth().bit_type();
+
+        let filter_fn = |channel: &mut Channel| -> Result<(), ImageErrors> {
+            match depth {
+                BitType::F32 => {
+                    let pixels = channel.reinterpret_as_mut::<f32>()?;
+
+                    let mut spectrum = fft_2d(pixels, width, height);
+                    spectrum.apply_mask(|fx, fy| self.filter.weight(fx, fy));
+                    ifft_2d(&spectrum, pixels);
+                }
+                d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+            }
+            Ok(())
+        };
+        execute_on(filter_fn, image, true)
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::F32]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fft::{fft_2d, ifft_2d, FrequencyFilterType};
+
+    #[allow(clippy::cast_precision_loss)]
+    fn roundtrip(width: usize, height: usize) {
+        let pixels: Vec<f32> = (0..width * height)
+            .map(|i| ((i * 7919) % 251) as f32 / 251.0)
+            .collect();
+
+        let spectrum = fft_2d(&pixels, width, height);
+        // DC component is the sum of all pixels
+        let sum: f32 = pixels.iter().sum();
+        assert!((spectrum.data[0].re - sum).abs() < 1e-2);
+
+        let mut output = vec![0.0; width * height];
+        ifft_2d(&spectrum, &mut output);
+
+        for (a, b) in pixels.iter().zip(&output) {
+            assert!((a - b).abs() < 1e-4, "{a} {b}");
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        // power of two, odd and mixed lengths
+        roundtrip(16, 8);
+        roundtrip(15, 7);
+        roundtrip(1, 9);
+        roundtrip(30, 20);
+    }
+
+    #[test]
+    fn test_filter_weights() {
+        let low = FrequencyFilterType::LowPass(0.1);
+        assert!((low.weight(0.0, 0.0) - 1.0).abs() < 1e-6);
+        assert!(low.weight(0.5, 0.5) < 1e-3);
+
+        let high = FrequencyFilterType::HighPass(0.1);
+        assert!(high.weight(0.0, 0.0).abs() < 1e-6);
+    }
+}
@@ -7,15 +7,21 @@
  */
 //! Flip filter: Flip an image by reflecting pixels around the x-axis.
 //!
+use alloc::vec;
+
 use zune_core::bit_depth::BitType;
+use zune_core::simd::{Dispatcher, Isa};
 use zune_image::channel::Channel;
 use zune_image::errors::ImageErrors;
 use zune_image::image::Image;
-use zune_image::traits::OperationsTrait;
+use zune_image::traits::{OperationsTrait, Parameter, ParameterKind, ParameterValue};
 
 use crate::utils::execute_on;
 
+mod avx2;
+
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub enum FlipDirection {
     /// Creates a horizontal mirror image by reflecting the pixels around the central y-axis
     ///```text
@@ -55,6 +61,7 @@ pub enum FlipDirection {
 }
 
 /// Flip an image to a certain direction
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Flip {
     flip_direction: FlipDirection
 }
@@ -72,6 +79,15 @@ impl OperationsTrait for Flip {
         "Flip"
     }
 
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            name:        "direction",
+            description: "Axis to flip around",
+            kind:        ParameterKind::Choice(&["horizontal", "vertical", "x_axis"]),
+            default:     ParameterValue::Choice("horizontal")
+        }]
+    }
+
     fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
         let depth = image.depth();
         let width = image.dimensions().0;
@@ -80,10 +96,14 @@ impl OperationsTrait for Flip {
             match self.flip_direction {
                 FlipDirection::Horizontal => match depth.bit_type() {
                     BitType::U8 => {
-                        flop(inp.reinterpret_as_mut::<u8>()?, width);
+                        inp.reinterpret_as_mut::<u8>()?
+                            .chunks_exact_mut(width)
+                            .for_each(reverse_u8);
                     }
                     BitType::U16 => {
-                        flop(inp.reinterpret_as_mut::<u16>()?, width);
+                        inp.reinterpret_as_mut::<u16>()?
+                            .chunks_exact_mut(width)
+                            .for_each(reverse_u16);
                     }
                     BitType::F32 => {
                         flop(inp.reinterpret_as_mut::<f32>()?, width);
@@ -104,10 +124,10 @@ impl OperationsTrait for Flip {
                 },
                 FlipDirection::MirrorXAxis => match depth.bit_type() {
                     BitType::U8 => {
-                        flip(inp.reinterpret_as_mut::<u8>()?);
+                        reverse_u8(inp.reinterpret_as_mut::<u8>()?);
                     }
                     BitType::U16 => {
-                        flip(inp.reinterpret_as_mut::<u16>()?);
+                        reverse_u16(inp.reinterpret_as_mut::<u16>()?);
                     }
                     BitType::F32 => {
                         flip(inp.reinterpret_as_mut::<f32>()?);
@@ -155,7 +175,7 @@ pub fn flip<T: Copy>(in_out_image: &mut [T]) {
     let (in_img_top, in_img_bottom) = in_out_image.split_at_mut(length);
 
     for (in_dim, out_dim) in in_img_top.iter_mut().zip(in_img_bottom.iter_mut().rev()) {
-        std::mem::swap(in_dim, out_dim);
+        core::mem::swap(in_dim, out_dim);
     }
 }
 
@@ -213,10 +233,85 @@ pub fn flop<T: Copy>(in_out_image: &mut [T], width: usize) {
 
         // iterate and swap
         for (ltr, rtl) in left_to_right.iter_mut().zip(right_to_left.iter_mut().rev()) {
-            std::mem::swap(ltr, rtl);
+            core::mem::swap(ltr, rtl);
+        }
+    }
+}
+// SAFETY: every kernel only uses instructions of its instruction set
+static REVERSE_U8: Dispatcher<unsafe fn(&mut [u8])> = unsafe {
+    Dispatcher::new(
+        "reverse_u8",
+        &[
+            #[cfg(all(feature = "avx2", any(target_arch = "x86", target_arch = "x86_64")))]
+            (Isa::Avx2, avx2::reverse_avx2_u8),
+            (Isa::Scalar, <[u8]>::reverse)
+        ]
+    )
+};
+
+// SAFETY: every kernel only uses instructions of its instruction set
+static REVERSE_U16: Dispatcher<unsafe fn(&mut [u16])> = unsafe {
+    Dispatcher::new(
+        "reverse_u16",
+        &[
+            #[cfg(all(feature = "avx2", any(target_arch = "x86", target_arch = "x86_64")))]
+            (Isa::Avx2, avx2::reverse_avx2_u16),
+            (Isa::Scalar, <[u16]>::reverse)
+        ]
+    )
+};
+
+/// Reverse the order of `values`, with AVX2 if the cpu supports it
+fn reverse_u8(values: &mut [u8]) {
+    // SAFETY: the dispatcher only returns kernels the cpu supports
+    unsafe { REVERSE_U8.get()(values) }
+}
+
+/// Reverse the order of `values`, with AVX2 if the cpu supports it
+fn reverse_u16(values: &mut [u16]) {
+    // SAFETY: the dispatcher only returns kernels the cpu supports
+    unsafe { REVERSE_U16.get()(values) }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::flip::{flip, flop, reverse_u8, REVERSE_U16, REVERSE_U8};
+
+    #[test]
+    fn test_reverse_matches_scalar() {
+        // lengths around multiples of the register sizes
+        for length in [0, 1, 15, 16, 31, 32, 63, 64, 65, 100, 257] {
+            let values: Vec<u16> = (0_u16..)
+                .take(length)
+                .map(|x| x.wrapping_mul(7919))
+                .collect();
+            let bytes: Vec<u8> = values.iter().map(|x| x.to_le_bytes()[1]).collect();
+
+            let mut expected = values.clone();
+            flip(&mut expected);
+            // every kernel this cpu can run, not just the one picked
+            for (isa, kernel) in REVERSE_U16.kernels().iter().filter(|k| k.0.is_supported()) {
+                let mut reversed = values.clone();
+                unsafe { kernel(&mut reversed) };
+                assert_eq!(reversed, expected, "{isa:?} u16 length {length}");
+            }
+
+            let mut expected = bytes.clone();
+            flip(&mut expected);
+            for (isa, kernel) in REVERSE_U8.kernels().iter().filter(|k| k.0.is_supported()) {
+                let mut reversed = bytes.clone();
+                unsafe { kernel(&mut reversed) };
+                assert_eq!(reversed, expected, "{isa:?} u8 length {length}");
+            }
         }
+        let mut rows: Vec<u8> = (0..=255).collect();
+        let mut expected = rows.clone();
+        flop(&mut expected, 64);
+        rows.chunks_exact_mut(64).for_each(reverse_u8);
+        assert_eq!(rows, expected);
     }
 }
+
 #[cfg(feature = "benchmarks")]
 #[cfg(test)]
 mod benchmarks {
@@ -0,0 +1,65 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+#![cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#![cfg(feature = "avx2")]
+//! AVX2 reversal, for flipping and flopping
+//!
+//! Registers are loaded from both ends at once, reversed and stored at the opposite end.
+//! Reversing a register shuffles the elements of each 128 bit lane and then swaps
+//! the lanes.
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+#[target_feature(enable = "avx2")]
+unsafe fn reverse_register(value: __m256i, mask: __m256i) -> __m256i {
+    _mm256_permute4x64_epi64::<0b01_00_11_10>(_mm256_shuffle_epi8(value, mask))
+}
+
+/// Reverse `values` in place, `mask` reverses the elements within each 128 bit lane
+#[target_feature(enable = "avx2")]
+unsafe fn reverse<T: Copy>(values: &mut [T], mask: __m256i) {
+    let lanes = 32 / core::mem::size_of::<T>();
+
+    let mut start = 0;
+    let mut end = values.len();
+
+    while end - start >= 2 * lanes {
+        let ptr = values.as_mut_ptr();
+        // SAFETY: start + lanes <= end - lanes <= values.len()
+        let front = _mm256_loadu_si256(ptr.add(start).cast());
+        let back = _mm256_loadu_si256(ptr.add(end - lanes).cast());
+
+        _mm256_storeu_si256(ptr.add(start).cast(), reverse_register(back, mask));
+        _mm256_storeu_si256(ptr.add(end - lanes).cast(), reverse_register(front, mask));
+
+        start += lanes;
+        end -= lanes;
+    }
+    values[start..end].reverse();
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn reverse_avx2_u8(values: &mut [u8]) {
+    let mask = _mm256_setr_epi8(
+        15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0, 15, 14, 13, 12, 11, 10, 9, 8, 7, 6,
+        5, 4, 3, 2, 1, 0
+    );
+    reverse(values, mask);
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn reverse_avx2_u16(values: &mut [u16]) {
+    let mask = _mm256_setr_epi8(
+        14, 15, 12, 13, 10, 11, 8, 9, 6, 7, 4, 5, 2, 3, 0, 1, 14, 15, 12, 13, 10, 11, 8, 9, 6, 7,
+        4, 5, 2, 3, 0, 1
+    );
+    reverse(values, mask);
+}
@@ -0,0 +1,299 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Flood fill: Fill a connected region of similar pixels
+//!
+//! # Algorithm
+//! This uses a scanline flood fill, starting from a seed point we expand left
+//! and right as far as the pixels match the seed pixel, mark that span and then
+//! look at the rows directly above and below the span for new spans to visit.
+//!
+//! A pixel matches the seed if every color component (alpha is ignored) is within
+//! `tolerance` of the seed pixel's component.
+//!
+//! The region is computed once per frame from the color channels and then
+//! applied to all channels, so the result is the same regardless of the order of channels.
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use zune_core::bit_depth::BitType;
+use zune_image::channel::Channel;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::traits::NumOps;
+use crate::DepthColor;
+
+/// What to do with pixels inside the filled region
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FloodFillMode {
+    /// Replace the pixels with the given color, see [DepthColor] for the range of the values
+    Color(DepthColor),
+    /// Make the pixels fully transparent by setting the alpha channel to zero.
+    ///
+    /// This requires the image to have an alpha channel
+    ToAlpha
+}
+
+/// Fill a connected region of an image starting from a seed point
+///
+/// This is useful for background removal and creating masks.
+///
+/// # Example
+/// Make a white background transparent
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::flood_fill::FloodFill;
+///
+/// let mut image = Image::fill(255_u8, ColorSpace::RGBA, 100, 100);
+/// FloodFill::new_to_alpha(0, 0, 10.0).execute(&mut image).unwrap();
+/// ```
+pub struct FloodFill {
+    x:         usize,
+    y:         usize,
+    tolerance: f32,
+    mode:      FloodFillMode
+}
+
+impl FloodFill {
+    /// Create a new flood fill operation which fills the region with a color
+    ///
+    /// # Arguments
+    /// - x,y: The seed point, the region grows from here
+    /// - tolerance: Maximum difference between a component and the seed pixel's component
+    ///   for a pixel to be considered part of the region, in the range of the image depth
+    /// - color: The color to write to pixels in the region, see [FloodFillMode::Color]
+    #[must_use]
+    pub fn new(x: usize, y: usize, tolerance: f32, color: DepthColor) -> FloodFill {
+        FloodFill {
+            x,
+            y,
+            tolerance,
+            mode: FloodFillMode::Color(color)
+        }
+    }
+    /// Create a new flood fill operation which makes the region transparent
+    ///
+    /// # Arguments
+    /// - x,y: The seed point, the region grows from here
+    /// - tolerance: Maximum difference between a component and the seed pixel's component
+    ///   for a pixel to be considered part of the region, in the range of the image depth
+    #[must_use]
+    pub fn new_to_alpha(x: usize, y: usize, tolerance: f32) -> FloodFill {
+        FloodFill {
+            x,
+            y,
+            tolerance,
+            mode: FloodFillMode::ToAlpha
+        }
+    }
+}
+
+impl OperationsTrait for FloodFill {
+    fn name(&self) -> &'static str {
+        "Flood Fill"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let colorspace = image.colorspace();
+
+        if self.x >= width || self.y >= height {
+            return Err(ImageErrors::GenericString(format!(
+                "Seed point ({},{}) is outside the image dimensions ({width},{height})",
+                self.x, self.y
+            )));
+        }
+        if self.mode == FloodFillMode::ToAlpha && !colorspace.has_alpha() {
+            return Err(ImageErrors::GenericString(format!(
+                "Flood fill to alpha requires an image with an alpha channel, but image is in {colorspace:?}"
+            )));
+        }
+        let depth = image.depth().bit_type();
+
+        for frame in image.frames_mut() {
+            // compute the region from the color channels
+            let mask = {
+                let channels = frame.channels_ref(colorspace, true);
+
+                match depth {
+                    BitType::U8 => {
+                        let c = channels
+                            .iter()
+                            .map(|x| x.reinterpret_as::<u8>())
+                            .collect::<Result<Vec<_>, _>>()?;
+                        flood_fill_mask(&c, width, height, self.x, self.y, self.tolerance)
+                    }
+                    BitType::U16 => {
+                        let c = channels
+                            .iter()
+                            .map(|x| x.reinterpret_as::<u16>())
+                            .collect::<Result<Vec<_>, _>>()?;
+                        flood_fill_mask(&c, width, height, self.x, self.y, self.tolerance)
+                    }
+                    BitType::F32 => {
+                        let c = channels
+                            .iter()
+                            .map(|x| x.reinterpret_as::<f32>())
+                            .collect::<Result<Vec<_>, _>>()?;
+                        flood_fill_mask(&c, width, height, self.x, self.y, self.tolerance)
+                    }
+                    d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+                }
+            };
+            // then apply it
+            let targets: Vec<(&mut Channel, f32)> = match self.mode {
+                FloodFillMode::Color(color) => frame
+                    .channels_mut(colorspace, false)
+                    .iter_mut()
+                    .zip(color)
+                    .collect(),
+                FloodFillMode::ToAlpha => {
+                    // checked above
+                    let (_, alpha) = frame.separate_color_and_alpha_mut(colorspace).unwrap();
+                    vec![(alpha, 0.0)]
+                }
+            };
+
+            for (channel, value) in targets {
+                match depth {
+                    BitType::U8 => fill_masked::<u8>(channel.reinterpret_as_mut()?, &mask, value),
+                    BitType::U16 => fill_masked::<u16>(channel.reinterpret_as_mut()?, &mask, value),
+                    BitType::F32 => fill_masked::<f32>(channel.reinterpret_as_mut()?, &mask, value),
+                    d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Compute the region a flood fill starting at `(x,y)` would cover
+///
+/// # Arguments
+/// - channels: The image channels used for comparison, each should have `width*height` elements
+/// - width,height: Image dimensions
+/// - x,y: The seed point. Must be inside the image
+/// - tolerance: Maximum absolute difference per channel for a pixel to match the seed pixel
+///
+/// # Returns
+/// A `width*height` mask, `true` indicating the pixel is part of the region
+///
+/// # Panics
+/// If the seed point is outside the image
+pub fn flood_fill_mask<T>(
+    channels: &[&[T]], width: usize, height: usize, x: usize, y: usize, tolerance: f32
+) -> Vec<bool>
+where
+    T: NumOps<T> + Copy
+{
+    assert!(x < width && y < height, "Seed point outside the image");
+
+    let mut mask = vec![false; width * height];
+
+    let seed_pos = y * width + x;
+    let seed: Vec<f32> = channels.iter().map(|c| c[seed_pos].to_f32()).collect();
+
+    let matches = |pos: usize| -> bool {
+        channels
+            .iter()
+            .zip(&seed)
+            .all(|(c, s)| (c[pos].to_f32() - s).abs() <= tolerance)
+    };
+
+    let mut stack = vec![(x, y)];
+
+    while let Some((x, y)) = stack.pop() {
+        let row = y * width;
+
+        if mask[row + x] {
+            continue;
+        }
+        // expand to the left and right as far as pixels match
+        let mut left = x;
+        while left > 0 && !mask[row + left - 1] && matches(row + left - 1) {
+            left -= 1;
+        }
+        let mut right = x;
+        while right + 1 < width && !mask[row + right + 1] && matches(row + right + 1) {
+            right += 1;
+        }
+        mask[row + left..=row + right].fill(true);
+
+        // look for new spans in the rows above and below
+        let rows = [y.checked_sub(1), Some(y + 1).filter(|ny| *ny < height)];
+
+        for new_y in rows.into_iter().flatten() {
+            let new_row = new_y * width;
+            let mut in_span = false;
+
+            for new_x in left..=right {
+                let pos = new_row + new_x;
+
+                if !mask[pos] && matches(pos) {
+                    if !in_span {
+                        stack.push((new_x, new_y));
+                        in_span = true;
+                    }
+                } else {
+                    in_span = false;
+                }
+            }
+        }
+    }
+    mask
+}
+
+fn fill_masked<T: NumOps<T> + Copy>(channel: &mut [T], mask: &[bool], value: f32) {
+    let value = T::from_f32(value.clamp(T::MIN_VAL.to_f32(), T::MAX_VAL.to_f32()));
+
+    for (pix, in_region) in channel.iter_mut().zip(mask) {
+        if *in_region {
+            *pix = value;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::flood_fill::{flood_fill_mask, FloodFill};
+
+    #[test]
+    fn test_flood_fill_stops_at_border() {
+        // a vertical wall at x == 2 separates the left and right halves
+        let pixels: Vec<u8> = (0..25).map(|i| if i % 5 == 2 { 255 } else { 0 }).collect();
+        let mask = flood_fill_mask(&[&pixels], 5, 5, 0, 0, 0.0);
+
+        for (i, m) in mask.iter().enumerate() {
+            assert_eq!(*m, i % 5 < 2);
+        }
+    }
+
+    #[test]
+    fn test_flood_fill_to_alpha() {
+        let mut image = Image::fill(200_u16, ColorSpace::LumaA, 10, 10);
+        FloodFill::new_to_alpha(5, 5, 0.0)
+            .execute(&mut image)
+            .unwrap();
+
+        let alpha = image.channels_ref(false)[1]
+            .reinterpret_as::<u16>()
+            .unwrap();
+        assert!(alpha.iter().all(|x| *x == 0));
+    }
+}
@@ -23,11 +23,15 @@
 //! - For `u8` and `u16` , we use lookup tables to improve speed
 //! - For `f32` naive execution is used
 //!
+use alloc::vec;
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
 use zune_core::bit_depth::BitType;
 use zune_image::channel::Channel;
 use zune_image::errors::ImageErrors;
 use zune_image::image::Image;
-use zune_image::traits::OperationsTrait;
+use zune_image::traits::{OperationsTrait, Parameter, ParameterKind, ParameterValue};
 
 use crate::traits::NumOps;
 use crate::utils::execute_on;
@@ -37,6 +41,7 @@ use crate::utils::execute_on;
 ///
 /// This operation is internally multithreaded, where supported
 #[derive(Default)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Gamma {
     value: f32
 }
@@ -57,6 +62,15 @@ impl OperationsTrait for Gamma {
         "Gamma Correction"
     }
 
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            name:        "value",
+            description: "Gamma exponent",
+            kind:        ParameterKind::Float { min: 0.1, max: 5.0 },
+            default:     ParameterValue::Float(1.0)
+        }]
+    }
+
     fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
         let max_value = image.depth().max_value();
 
@@ -89,6 +103,9 @@ impl OperationsTrait for Gamma {
     fn supported_types(&self) -> &'static [BitType] {
         &[BitType::U8, BitType::U16, BitType::F32]
     }
+    fn is_point_op(&self) -> bool {
+        true
+    }
 }
 
 #[allow(
@@ -0,0 +1,276 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Convert an image to other RGB primaries
+//!
+//! This converts pixels from the primaries recorded in the image metadata, see
+//! [`ImageMetadata::primaries`], to new primaries, e.g. from a Display P3 image to sRGB.
+//!
+//! Converting to a smaller gamut may produce colors that can't be represented,
+//! the [`GamutMapping`] decides how such colors are brought into the gamut.
+//!
+//! # Algorithm
+//! Pixels are linearized, multiplied by the conversion matrix and gamut mapped
+//! in linear light, then encoded again. Images with [`Linear`](ColorCharacteristics::Linear)
+//! transfer characteristics are not linearized.
+//!
+//! Compression desaturates colors towards a gray of the same luminance. The distance of
+//! a color from its gray relative to the gamut boundary is compressed with a soft knee,
+//! colors within [`COMPRESSION_THRESHOLD`] of the distance to the boundary are kept
+//! as is, colors beyond it are smoothly compressed so that no color ends up outside
+//! the gamut. This keeps gradients in saturated areas that clipping flattens.
+//!
+//! [`ImageMetadata::primaries`]: zune_image::metadata::ImageMetadata::primaries
+use zune_core::bit_depth::{BitDepth, BitType};
+use zune_core::colorspace::{ColorCharacteristics, ColorSpace, RgbPrimaries};
+use zune_image::core_filters::colorspace::primaries::{conversion_matrix, rgb_to_xyz_matrix};
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::linearize::{linear_to_srgb, srgb_to_linear};
+
+/// The fraction of the distance to the gamut boundary below which compression
+/// leaves colors untouched
+pub const COMPRESSION_THRESHOLD: f32 = 0.8;
+
+/// How to bring colors outside the destination gamut into it
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Default)]
+pub enum GamutMapping {
+    /// Clip every channel to the valid range
+    ///
+    /// Colors inside the gamut are preserved exactly, colors outside may change hue
+    /// and lose detail
+    Clip,
+    /// Compress saturated colors towards the gamut boundary
+    ///
+    /// Preserves luminance and gradients at the cost of slightly desaturating
+    /// colors close to the boundary
+    #[default]
+    Compress
+}
+
+/// Convert an image to different RGB primaries
+///
+/// # Example
+/// Convert a Display P3 image to sRGB
+/// ```
+/// use zune_core::colorspace::{ColorSpace, RgbPrimaries};
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::gamut_map::{GamutMap, GamutMapping};
+///
+/// let mut image = Image::fill(200_u8, ColorSpace::RGB, 10, 10);
+/// image.metadata_mut().set_primaries(RgbPrimaries::DisplayP3);
+///
+/// GamutMap::new(RgbPrimaries::sRGB)
+///     .set_mapping(GamutMapping::Clip)
+///     .execute(&mut image)
+///     .unwrap();
+///
+/// assert_eq!(image.metadata().primaries(), RgbPrimaries::sRGB);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct GamutMap {
+    primaries: RgbPrimaries,
+    mapping:   GamutMapping
+}
+
+impl GamutMap {
+    /// Create a new operation converting the image to `primaries`
+    ///
+    /// Out of gamut colors are compressed by default
+    #[must_use]
+    pub fn new(primaries: RgbPrimaries) -> GamutMap {
+        GamutMap {
+            primaries,
+            mapping: GamutMapping::default()
+        }
+    }
+    /// Set how out of gamut colors are handled
+    #[must_use]
+    pub fn set_mapping(mut self, mapping: GamutMapping) -> Self {
+        self.mapping = mapping;
+        self
+    }
+}
+
+impl OperationsTrait for GamutMap {
+    fn name(&self) -> &'static str {
+        "Gamut Map"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let from = image.metadata().primaries();
+
+        if from == self.primaries {
+            return Ok(());
+        }
+        let matrix = conversion_matrix(from, self.primaries);
+        let luminance = rgb_to_xyz_matrix(self.primaries)[1];
+        let linear = image.metadata().color_trc() == Some(ColorCharacteristics::Linear);
+
+        let depth = image.depth();
+        image.convert_depth(BitDepth::Float32)?;
+
+        let colorspace = image.colorspace();
+        for frame in image.frames_mut() {
+            let channels = frame.channels_mut(colorspace, true);
+            let (r, rest) = channels.split_at_mut(1);
+            let (g, b) = rest.split_at_mut(1);
+
+            gamut_map(
+                [
+                    r[0].reinterpret_as_mut()?,
+                    g[0].reinterpret_as_mut()?,
+                    b[0].reinterpret_as_mut()?
+                ],
+                &matrix,
+                luminance,
+                self.mapping,
+                linear
+            );
+        }
+        image.convert_depth(depth)?;
+        image.metadata_mut().set_primaries(self.primaries);
+
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[ColorSpace::RGB, ColorSpace::RGBA]
+    }
+}
+
+/// Compress a distance relative to the gamut boundary so that it never exceeds 1
+#[inline]
+fn compress_distance(distance: f32) -> f32 {
+    let knee = COMPRESSION_THRESHOLD;
+
+    if distance <= knee {
+        return distance;
+    }
+    let excess = (distance - knee) / (1.0 - knee);
+
+    knee + (1.0 - knee) * excess / (1.0 + excess)
+}
+
+/// Bring a linear RGB value into the 0..1 range
+fn map_into_gamut(rgb: [f32; 3], luminance: [f32; 3], mapping: GamutMapping) -> [f32; 3] {
+    if mapping == GamutMapping::Clip {
+        return rgb.map(|x| x.clamp(0.0, 1.0));
+    }
+    let y = (rgb[0] * luminance[0] + rgb[1] * luminance[1] + rgb[2] * luminance[2]).clamp(0.0, 1.0);
+
+    if y <= 0.0 || y >= 1.0 {
+        return [y; 3];
+    }
+    // how far the color is from its gray compared to the gamut boundary,
+    // values above 1 are outside of the gamut
+    let distance = rgb
+        .iter()
+        .map(|c| {
+            let delta = c - y;
+            if delta > 0.0 {
+                delta / (1.0 - y)
+            } else {
+                -delta / y
+            }
+        })
+        .fold(0.0_f32, f32::max);
+
+    if distance <= COMPRESSION_THRESHOLD {
+        return rgb;
+    }
+    let scale = compress_distance(distance) / distance;
+
+    rgb.map(|c| (y + (c - y) * scale).clamp(0.0, 1.0))
+}
+
+/// Convert RGB channels in 0..1 with a primaries conversion matrix and gamut map them
+///
+/// # Arguments
+/// - channels: The red, green and blue channels
+/// - matrix: The linear RGB conversion matrix, see [`conversion_matrix`]
+/// - luminance: Luminance coefficients of the destination primaries, the second row of
+///   their RGB to XYZ matrix
+/// - mapping: How to handle out of gamut colors
+/// - linear: Whether the channels are linear, otherwise they are treated as sRGB encoded
+pub fn gamut_map(
+    channels: [&mut [f32]; 3], matrix: &[[f32; 3]; 3], luminance: [f32; 3], mapping: GamutMapping,
+    linear: bool
+) {
+    let [r, g, b] = channels;
+
+    for ((r, g), b) in r.iter_mut().zip(g.iter_mut()).zip(b.iter_mut()) {
+        let mut rgb = [*r, *g, *b];
+        if !linear {
+            rgb = rgb.map(|x| srgb_to_linear(x.clamp(0.0, 1.0)));
+        }
+        let converted = matrix.map(|row| row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2]);
+        let mut mapped = map_into_gamut(converted, luminance, mapping);
+
+        if !linear {
+            mapped = mapped.map(linear_to_srgb);
+        }
+        [*r, *g, *b] = mapped;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::{ColorSpace, RgbPrimaries};
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::gamut_map::{GamutMap, GamutMapping};
+
+    fn p3_to_srgb(pixels: &[f32], mapping: GamutMapping) -> Vec<f32> {
+        let mut image = Image::from_f32(pixels, pixels.len() / 3, 1, ColorSpace::RGB);
+        image.metadata_mut().set_primaries(RgbPrimaries::DisplayP3);
+
+        GamutMap::new(RgbPrimaries::sRGB)
+            .set_mapping(mapping)
+            .execute(&mut image)
+            .unwrap();
+
+        assert_eq!(image.metadata().primaries(), RgbPrimaries::sRGB);
+        image.flatten_frames::<f32>()[0].clone()
+    }
+
+    #[test]
+    fn test_gray_is_unchanged() {
+        for mapping in [GamutMapping::Clip, GamutMapping::Compress] {
+            let result = p3_to_srgb(&[0.5, 0.5, 0.5, 1.0, 1.0, 1.0], mapping);
+
+            for (a, e) in result.iter().zip([0.5, 0.5, 0.5, 1.0, 1.0, 1.0]) {
+                assert!((a - e).abs() < 1e-3, "{result:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_compression_keeps_gradient() {
+        // two saturated P3 reds, both outside of sRGB
+        let pixels = [1.0, 0.0, 0.0, 0.9, 0.0, 0.0];
+
+        let clipped = p3_to_srgb(&pixels, GamutMapping::Clip);
+        // clipping maps the red channel of both to the maximum
+        assert!((clipped[0] - clipped[3]).abs() < 0.1);
+        assert!(clipped[1] == 0.0 && clipped[4] == 0.0);
+
+        let compressed = p3_to_srgb(&pixels, GamutMapping::Compress);
+        assert!(compressed.iter().all(|x| (0.0..=1.0).contains(x)));
+        // the darker red stays darker and both are desaturated
+        assert!(compressed[0] > compressed[3], "{compressed:?}");
+        assert!(compressed[1] > 0.0 && compressed[4] > 0.0, "{compressed:?}");
+    }
+}
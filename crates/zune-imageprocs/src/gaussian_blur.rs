@@ -16,18 +16,40 @@
 //! box blurs
 //!
 //! For the math behind it see <https://blog.ivank.net/fastest-gaussian-blur.html>
+//!
+//! Box blurs approximate the gaussian less well as sigma grows, so from a sigma of
+//! 10 a recursive (IIR) filter is used instead, see the `recursive` module.
+//! Both take the same time for any sigma.
+
+use alloc::vec;
 
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
 use zune_core::bit_depth::BitType;
 use zune_core::log::trace;
 use zune_image::errors::ImageErrors;
 use zune_image::image::Image;
-use zune_image::traits::OperationsTrait;
+use zune_image::metadata::AlphaState;
+use zune_image::traits::{OperationsTrait, Parameter, ParameterKind, ParameterValue};
 
+use crate::gaussian_blur::recursive::RecursiveGaussian;
+use crate::linearize::execute_in_linear_light;
+use crate::pad::{filter_with_border, PadMethod};
+use crate::traits::NumOps;
 use crate::transpose;
+use crate::utils::execute_on;
+
+mod recursive;
+
+/// Sigmas from which the recursive filter is used in place of box blurs
+const RECURSIVE_MIN_SIGMA: f32 = 10.0;
 
 #[derive(Default)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct GaussianBlur {
-    sigma: f32
+    sigma:        f32,
+    linear_light: bool,
+    pad_method:   PadMethod
 }
 
 impl GaussianBlur {
@@ -37,7 +59,31 @@ impl GaussianBlur {
     /// - sigma: How much to blur by.
     #[must_use]
     pub fn new(sigma: f32) -> GaussianBlur {
-        GaussianBlur { sigma }
+        GaussianBlur {
+            sigma,
+            linear_light: false,
+            pad_method: PadMethod::Replicate
+        }
+    }
+    /// Blur in linear light
+    ///
+    /// When set, gamma encoded sRGB images are linearized before blurring and
+    /// encoded back afterwards, so bright details don't get darker when blurred
+    #[must_use]
+    pub fn set_linear_light(mut self, linear_light: bool) -> Self {
+        self.linear_light = linear_light;
+        self
+    }
+    /// Set how pixels outside the image are computed, defaults to [PadMethod::Replicate]
+    ///
+    /// For sigmas of 10 and above the blur uses a recursive filter, which reads the
+    /// whole row, while methods other than [PadMethod::Replicate] only pad the image
+    /// by four sigmas, so their results are approximate, off by up to one level for
+    /// 8 bit images
+    #[must_use]
+    pub fn set_pad_method(mut self, pad_method: PadMethod) -> Self {
+        self.pad_method = pad_method;
+        self
     }
 }
 
@@ -46,118 +92,96 @@ impl OperationsTrait for GaussianBlur {
         "Gaussian blur"
     }
 
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            name:        "sigma",
+            description: "Standard deviation of the blur",
+            kind:        ParameterKind::Float {
+                min: 0.0,
+                max: 100.0
+            },
+            default:     ParameterValue::Float(1.0)
+        }]
+    }
+
     #[allow(clippy::too_many_lines)]
     fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        if self.linear_light {
+            let blur = GaussianBlur::new(self.sigma).set_pad_method(self.pad_method);
+            return execute_in_linear_light(image, |image| blur.execute_impl(image));
+        }
         let (width, height) = image.dimensions();
         let depth = image.depth();
 
-        #[cfg(not(feature = "threads"))]
-        {
-            trace!("Running gaussian blur in single threaded mode");
+        trace!("Running gaussian blur");
 
-            match depth.bit_type() {
-                BitType::U8 => {
-                    let mut temp = vec![0; width * height];
+        let reach = blur_reach(self.sigma);
 
-                    for channel in image.channels_mut(false) {
-                        gaussian_blur_u8(
-                            channel.reinterpret_as_mut::<u8>()?,
+        // channels are blurred in parallel with the threads feature,
+        // so each one gets its own scratch space
+        execute_on(
+            |channel| {
+                match depth.bit_type() {
+                    BitType::U8 => {
+                        let mut temp = vec![0; width * height];
+                        let data = channel.reinterpret_as_mut::<u8>()?;
+                        filter_with_border(
+                            data,
                             &mut temp,
                             width,
                             height,
-                            self.sigma
+                            reach,
+                            self.pad_method,
+                            |data, temp, width, height| {
+                                gaussian_blur_u8(data, temp, width, height, self.sigma);
+                            }
                         );
                     }
-                }
-                BitType::U16 => {
-                    let mut temp = vec![0; width * height];
-
-                    for channel in image.channels_mut(false) {
-                        gaussian_blur_u16(
-                            channel.reinterpret_as_mut::<u16>()?,
+                    BitType::U16 => {
+                        let mut temp = vec![0; width * height];
+                        let data = channel.reinterpret_as_mut::<u16>()?;
+                        filter_with_border(
+                            data,
                             &mut temp,
                             width,
                             height,
-                            self.sigma
+                            reach,
+                            self.pad_method,
+                            |data, temp, width, height| {
+                                gaussian_blur_u16(data, temp, width, height, self.sigma);
+                            }
                         );
                     }
-                }
-                BitType::F32 => {
-                    let mut temp = vec![0.0; width * height];
-                    for channel in image.channels_mut(false) {
-                        gaussian_blur_f32(
-                            channel.reinterpret_as_mut()?,
+                    BitType::F32 => {
+                        let mut temp = vec![0.0; width * height];
+                        let data = channel.reinterpret_as_mut::<f32>()?;
+                        filter_with_border(
+                            data,
                             &mut temp,
                             width,
                             height,
-                            self.sigma
+                            reach,
+                            self.pad_method,
+                            |data, temp, width, height| {
+                                gaussian_blur_f32(data, temp, width, height, self.sigma);
+                            }
                         );
                     }
+                    d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
                 }
-                d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
-            }
-        }
-
-        #[cfg(feature = "threads")]
-        {
-            trace!("Running gaussian blur in multithreaded mode");
-            std::thread::scope(|s| {
-                let mut errors = vec![];
-                // blur each channel on a separate thread
-                for channel in image.channels_mut(false) {
-                    let result = s.spawn(|| match depth.bit_type() {
-                        BitType::U8 => {
-                            let mut temp = vec![0; width * height];
-
-                            gaussian_blur_u8(
-                                channel.reinterpret_as_mut::<u8>()?,
-                                &mut temp,
-                                width,
-                                height,
-                                self.sigma
-                            );
-                            Ok(())
-                        }
-                        BitType::U16 => {
-                            let mut temp = vec![0; width * height];
-
-                            gaussian_blur_u16(
-                                channel.reinterpret_as_mut::<u16>()?,
-                                &mut temp,
-                                width,
-                                height,
-                                self.sigma
-                            );
-                            Ok(())
-                        }
-                        BitType::F32 => {
-                            let mut temp = vec![0.0; width * height];
-
-                            gaussian_blur_f32(
-                                channel.reinterpret_as_mut()?,
-                                &mut temp,
-                                width,
-                                height,
-                                self.sigma
-                            );
-                            Ok(())
-                        }
-                        d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
-                    });
-                    errors.push(result);
-                }
-                errors
-                    .into_iter()
-                    .map(|x| x.join().unwrap())
-                    .collect::<Result<Vec<()>, ImageErrors>>()
-            })?;
-        }
-
-        Ok(())
+                Ok(())
+            },
+            image,
+            false
+        )
     }
     fn supported_types(&self) -> &'static [BitType] {
         &[BitType::U8, BitType::U16]
     }
+
+    fn alpha_state(&self) -> Option<AlphaState> {
+        Some(AlphaState::PreMultiplied)
+    }
 }
 /// Create different box radius for each gaussian kernel function.
 #[allow(
@@ -203,6 +227,41 @@ fn create_box_gauss(sigma: f32) -> [usize; 3] {
     return radii.map(|c| if (c % 2) == 0 { c + 1 } else { c });
 }
 
+/// Return how many pixels past the image edges a blur of `sigma` reads
+///
+/// The recursive filter reads the whole row, but past four sigmas the gaussian
+/// has no visible weight left, so borders other than [PadMethod::Replicate] are
+/// only padded that far and are approximate for it
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub(crate) fn blur_reach(sigma: f32) -> usize {
+    if sigma >= RECURSIVE_MIN_SIGMA {
+        return (sigma * 4.0).ceil() as usize;
+    }
+    // three box blurs one after the other, each reaching a pixel past its radius
+    create_box_gauss(sigma).iter().map(|radius| radius + 1).sum()
+}
+
+/// Blur with the recursive filter, the columns are blurred as the rows of the
+/// transposed channel
+///
+/// `round` rounds the blurred samples to the nearest integer, for integer types
+fn recursive_blur<T: Copy + NumOps<T>>(
+    in_out_image: &mut [T], scratch_space: &mut [T], width: usize, height: usize, sigma: f32,
+    round: bool, transpose: fn(&[T], &mut [T], usize, usize)
+) {
+    let filter = RecursiveGaussian::new(sigma);
+    let bias = if round { 0.5 } else { 0.0 };
+
+    // conversion saturates, which clamps the slight overshoot of the filter
+    let store = |x: f64| T::from_f64(x + bias);
+
+    filter.blur_rows(in_out_image, width, T::to_f64, store);
+    transpose(in_out_image, scratch_space, width, height);
+
+    filter.blur_rows(scratch_space, height, T::to_f64, store);
+    transpose(scratch_space, in_out_image, height, width);
+}
+
 /// Carry out a gaussian blur on bytes that represent a single image channel
 ///
 ///
@@ -215,6 +274,18 @@ fn create_box_gauss(sigma: f32) -> [usize; 3] {
 pub fn gaussian_blur_u16(
     in_out_image: &mut [u16], scratch_space: &mut [u16], width: usize, height: usize, sigma: f32
 ) {
+    if sigma >= RECURSIVE_MIN_SIGMA {
+        let transpose = transpose::transpose_u16;
+        return recursive_blur(
+            in_out_image,
+            scratch_space,
+            width,
+            height,
+            sigma,
+            true,
+            transpose
+        );
+    }
     // use the box blur implementation
     let blur_radii = create_box_gauss(sigma);
 
@@ -228,6 +299,20 @@ pub fn gaussian_blur_u16(
             _ => unreachable!()
         };
     }
+    #[cfg(all(feature = "neon", target_arch = "aarch64", target_feature = "neon"))]
+    if blur_radii.iter().all(|radius| height > radius * 2) {
+        // blur the columns in place of transposing, the passes go from scratch_space
+        // to in_out_image and back so the last one ends up in in_out_image
+        use crate::box_blur::neon::box_blur_vertical_neon_u16;
+
+        let [first, second, third] = blur_radii;
+        unsafe {
+            box_blur_vertical_neon_u16(scratch_space, in_out_image, width, height, first);
+            box_blur_vertical_neon_u16(in_out_image, scratch_space, width, height, second);
+            box_blur_vertical_neon_u16(scratch_space, in_out_image, width, height, third);
+        }
+        return;
+    }
     // transpose
     // we do three iterations above, so when that is done, results will always be in
     // scratch_space, so wr transpose writing to in_out_image which is used below
@@ -248,6 +333,18 @@ pub fn gaussian_blur_u16(
 pub fn gaussian_blur_f32(
     in_out_image: &mut [f32], scratch_space: &mut [f32], width: usize, height: usize, sigma: f32
 ) {
+    if sigma >= RECURSIVE_MIN_SIGMA {
+        let transpose = transpose::transpose_generic;
+        return recursive_blur(
+            in_out_image,
+            scratch_space,
+            width,
+            height,
+            sigma,
+            false,
+            transpose
+        );
+    }
     // use the box blur implementation
     let blur_radii = create_box_gauss(sigma);
 
@@ -310,6 +407,18 @@ pub fn gaussian_blur_f32(
 pub fn gaussian_blur_u8(
     in_out_image: &mut [u8], scratch_space: &mut [u8], width: usize, height: usize, sigma: f32
 ) {
+    if sigma >= RECURSIVE_MIN_SIGMA {
+        let transpose = transpose::transpose_u8;
+        return recursive_blur(
+            in_out_image,
+            scratch_space,
+            width,
+            height,
+            sigma,
+            true,
+            transpose
+        );
+    }
     // use the box blur implementation
     let blur_radii = create_box_gauss(sigma);
 
@@ -348,6 +457,20 @@ pub fn gaussian_blur_u8(
             _ => unreachable!()
         };
     }
+    #[cfg(all(feature = "neon", target_arch = "aarch64", target_feature = "neon"))]
+    if blur_radii.iter().all(|radius| height > radius * 2) {
+        // blur the columns in place of transposing, the passes go from scratch_space
+        // to in_out_image and back so the last one ends up in in_out_image
+        use crate::box_blur::neon::box_blur_vertical_neon_u8;
+
+        let [first, second, third] = blur_radii;
+        unsafe {
+            box_blur_vertical_neon_u8(scratch_space, in_out_image, width, height, first);
+            box_blur_vertical_neon_u8(in_out_image, scratch_space, width, height, second);
+            box_blur_vertical_neon_u8(scratch_space, in_out_image, width, height, third);
+        }
+        return;
+    }
     // transpose
     // we do three iterations above, so when that is done, results will always be in
     // scratch_space, so wr transpose writing to in_out_image which is used below
@@ -0,0 +1,192 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! A recursive gaussian filter, whose cost does not depend on sigma
+//!
+//! This is the third order filter of Young and van Vliet, *Recursive implementation
+//! of the Gaussian filter* (1995), run forwards and then backwards over every row.
+//!
+//! The backward pass starts with the boundary conditions of Triggs and Sdika,
+//! *Boundary conditions for Young-van Vliet recursive filtering* (2006), so pixels
+//! past either edge behave as copies of the edge pixel, like in the box blur.
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+/// Coefficients of the filter for one sigma
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct RecursiveGaussian {
+    /// Gain of the input sample
+    b: f64,
+    /// Weights of the last three outputs
+    a: [f64; 3],
+    /// Maps the end of the forward pass to the start of the backward pass
+    m: [[f64; 3]; 3]
+}
+
+impl RecursiveGaussian {
+    pub(crate) fn new(sigma: f32) -> RecursiveGaussian {
+        let sigma = f64::from(sigma);
+
+        let q = if sigma >= 2.5 {
+            0.987_11 * sigma - 0.963_30
+        } else {
+            3.971_56 - 4.145_54 * (1.0 - 0.268_91 * sigma).sqrt()
+        };
+        let (q2, q3) = (q * q, q * q * q);
+
+        let b0 = 1.578_25 + 2.444_13 * q + 1.4281 * q2 + 0.422_205 * q3;
+        let a1 = (2.444_13 * q + 2.856_19 * q2 + 1.266_61 * q3) / b0;
+        let a2 = -(1.4281 * q2 + 1.266_61 * q3) / b0;
+        let a3 = (0.422_205 * q3) / b0;
+
+        let scale =
+            1.0 / ((1.0 + a1 - a2 + a3) * (1.0 - a1 - a2 - a3) * (1.0 + a2 + (a1 - a3) * a3));
+
+        let m = [
+            [
+                -a3 * a1 + 1.0 - a3 * a3 - a2,
+                (a3 + a1) * (a2 + a3 * a1),
+                a3 * (a1 + a3 * a2)
+            ],
+            [
+                a1 + a3 * a2,
+                -(a2 - 1.0) * (a2 + a3 * a1),
+                -a3 * (a3 * a1 + a3 * a3 + a2 - 1.0)
+            ],
+            [
+                a3 * a1 + a2 + a1 * a1 - a2 * a2,
+                a1 * a2 + a3 * a2 * a2 - a1 * a3 * a3 - a3 * a3 * a3 - a3 * a2 + a3,
+                a3 * (a1 + a3 * a2)
+            ]
+        ];
+        RecursiveGaussian {
+            b: 1.0 - (a1 + a2 + a3),
+            a: [a1, a2, a3],
+            m: m.map(|row| row.map(|x| x * scale))
+        }
+    }
+
+    /// Blur a single row in place
+    pub(crate) fn blur_row(&self, row: &mut [f64]) {
+        let [a1, a2, a3] = self.a;
+        let b = self.b;
+
+        let (Some(&first), Some(&last)) = (row.first(), row.last()) else {
+            return;
+        };
+        // pixels before the row are copies of the first, which the filter settles on
+        let (mut w1, mut w2, mut w3) = (first, first, first);
+
+        for x in row.iter_mut() {
+            let w = b * *x + a1 * w1 + a2 * w2 + a3 * w3;
+            (w3, w2, w1) = (w2, w1, w);
+            *x = w;
+        }
+        let n = row.len();
+        // the last three outputs of the forward pass, relative to what the pass settles on
+        // after the row, shorter rows repeat their first pixel
+        let deviation = [1, 2, 3].map(|i| row[n.saturating_sub(i)] - last);
+
+        let [y0, y1, y2] = self
+            .m
+            .map(|m| b * (m[0] * deviation[0] + m[1] * deviation[1] + m[2] * deviation[2]) + last);
+        row[n - 1] = y0;
+
+        let (mut y1, mut y2, mut y3) = (y0, y1, y2);
+
+        for x in row[..n - 1].iter_mut().rev() {
+            let y = b * *x + a1 * y1 + a2 * y2 + a3 * y3;
+            (y3, y2, y1) = (y2, y1, y);
+            *x = y;
+        }
+    }
+
+    /// Blur every `width` long row of `data` in place
+    ///
+    /// Samples are converted to `f64` with `load`, and back with `store`
+    pub(crate) fn blur_rows<T: Copy>(
+        &self, data: &mut [T], width: usize, load: impl Fn(T) -> f64, store: impl Fn(f64) -> T
+    ) {
+        let mut row = alloc::vec![0.0; width];
+
+        for samples in data.chunks_exact_mut(width) {
+            for (value, sample) in row.iter_mut().zip(samples.iter()) {
+                *value = load(*sample);
+            }
+            self.blur_row(&mut row);
+
+            for (sample, value) in samples.iter_mut().zip(row.iter()) {
+                *sample = store(*value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RecursiveGaussian;
+
+    /// Blur of `row` with a sampled gaussian, repeating the edges
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    fn exact_blur(row: &[f64], sigma: f64) -> Vec<f64> {
+        let radius = (sigma * 6.0) as isize;
+        let kernel: Vec<f64> = (-radius..=radius)
+            .map(|x| (-((x * x) as f64) / (2.0 * sigma * sigma)).exp())
+            .collect();
+        let sum: f64 = kernel.iter().sum();
+        let last = row.len() as isize - 1;
+
+        (0..=last)
+            .map(|x| {
+                let taps = kernel.iter().zip(x - radius..);
+                let value: f64 = taps.map(|(k, i)| k * row[i.clamp(0, last) as usize]).sum();
+                value / sum
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_recursive_gaussian_matches_convolution() {
+        for sigma in [10.0_f32, 25.0, 50.0] {
+            let filter = RecursiveGaussian::new(sigma);
+
+            // an impulse, a step and a ramp ending at the edges
+            let mut row: Vec<f64> = (0..600)
+                .map(|x| match x {
+                    300 => 1000.0,
+                    0..=99 => 100.0,
+                    500.. => f64::from(x - 500) * 2.0,
+                    _ => 0.0
+                })
+                .collect();
+            let expected = exact_blur(&row, f64::from(sigma));
+            filter.blur_row(&mut row);
+
+            for (x, (a, b)) in row.iter().zip(&expected).enumerate() {
+                assert!((a - b).abs() < 1.0, "sigma {sigma}, pixel {x}: {a} != {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_recursive_gaussian_keeps_constants() {
+        let filter = RecursiveGaussian::new(40.0);
+
+        for length in [1, 2, 3, 1000] {
+            let mut row = vec![200.0; length];
+            filter.blur_row(&mut row);
+
+            assert!(row.iter().all(|x| (x - 200.0).abs() < 1e-6), "{row:?}");
+        }
+    }
+}
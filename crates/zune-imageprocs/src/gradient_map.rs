@@ -0,0 +1,298 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Gradient map: Map luminance through a color gradient
+//!
+//! This replaces every pixel with a color picked from a gradient based on how bright
+//! the pixel is, dark pixels take colors from the start of the gradient and bright pixels
+//! from the end.
+//!
+//! With two stops this produces a duotone, with three a tritone, e.t.c
+//!
+//! # Algorithm
+//! - The luminance of each pixel is computed as `0.299*r + 0.587*g + 0.114*b` and normalized to 0..1
+//! - The two stops surrounding the luminance are found and their colors are linearly interpolated
+//! - Luminance values before the first stop or after the last stop take the color of that stop
+//!
+//! Colors can optionally be interpolated in [OKLab](ColorSpace::OKLab) instead of RGB, which
+//! avoids the dull and dark midpoints of RGB interpolation between saturated colors.
+//!
+//! The output image is always RGB, or RGBA if the input had an alpha channel, which is preserved.
+use alloc::vec::Vec;
+
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::core_filters::colorspace::oklab::{oklab_to_srgb, srgb_to_oklab};
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::traits::NumOps;
+use crate::utils::round_to_depth;
+
+/// A single color stop of a gradient
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GradientStop {
+    /// Position of the stop in the gradient, between 0 (darkest) and 1 (brightest)
+    pub position: f32,
+    /// Color of the stop as RGB, in the range of the image depth like a
+    /// [DepthColor](crate::DepthColor)
+    pub color:    [f32; 3]
+}
+
+impl GradientStop {
+    /// Create a new gradient stop
+    #[must_use]
+    pub fn new(position: f32, color: [f32; 3]) -> GradientStop {
+        GradientStop { position, color }
+    }
+}
+
+/// Map the luminance of an image through a color gradient
+///
+/// # Example
+/// Create a duotone going from dark blue to orange
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::gradient_map::GradientMap;
+///
+/// let mut image = Image::fill(128_u8, ColorSpace::Luma, 100, 100);
+///
+/// GradientMap::duotone([20.0, 20.0, 80.0], [255.0, 160.0, 40.0])
+///     .execute(&mut image)
+///     .unwrap();
+///
+/// assert_eq!(image.colorspace(), ColorSpace::RGB);
+/// ```
+pub struct GradientMap {
+    stops: Vec<GradientStop>,
+    oklab: bool
+}
+
+impl GradientMap {
+    /// Create a new gradient map from a list of stops
+    ///
+    /// The stops don't need to be sorted, at least two stops are required
+    /// otherwise executing the operation returns an error
+    #[must_use]
+    pub fn new(stops: &[GradientStop]) -> GradientMap {
+        let mut stops = stops.to_vec();
+        stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+
+        GradientMap {
+            stops,
+            oklab: false
+        }
+    }
+    /// Create a two color gradient map mapping black to `shadows` and white to `highlights`
+    #[must_use]
+    pub fn duotone(shadows: [f32; 3], highlights: [f32; 3]) -> GradientMap {
+        GradientMap::new(&[
+            GradientStop::new(0.0, shadows),
+            GradientStop::new(1.0, highlights)
+        ])
+    }
+    /// Create a three color gradient map mapping black to `shadows`, mid gray to `midtones`
+    /// and white to `highlights`
+    #[must_use]
+    pub fn tritone(shadows: [f32; 3], midtones: [f32; 3], highlights: [f32; 3]) -> GradientMap {
+        GradientMap::new(&[
+            GradientStop::new(0.0, shadows),
+            GradientStop::new(0.5, midtones),
+            GradientStop::new(1.0, highlights)
+        ])
+    }
+    /// Interpolate between stops in OKLab instead of RGB
+    ///
+    /// Default is false
+    #[must_use]
+    pub fn set_oklab(mut self, oklab: bool) -> Self {
+        self.oklab = oklab;
+        self
+    }
+}
+
+impl OperationsTrait for GradientMap {
+    fn name(&self) -> &'static str {
+        "Gradient Map"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        if self.stops.len() < 2 {
+            return Err(ImageErrors::GenericStr(
+                "Gradient map requires at least two stops"
+            ));
+        }
+        let target =
+            if image.colorspace().has_alpha() { ColorSpace::RGBA } else { ColorSpace::RGB };
+        if image.colorspace() != target {
+            image.convert_color(target)?;
+        }
+        let depth = image.depth().bit_type();
+
+        for frame in image.frames_mut() {
+            let channels = frame.channels_mut(target, true);
+            let (r, rest) = channels.split_at_mut(1);
+            let (g, b) = rest.split_at_mut(1);
+
+            match depth {
+                BitType::U8 => gradient_map::<u8>(
+                    r[0].reinterpret_as_mut()?,
+                    g[0].reinterpret_as_mut()?,
+                    b[0].reinterpret_as_mut()?,
+                    &self.stops,
+                    self.oklab
+                ),
+                BitType::U16 => gradient_map::<u16>(
+                    r[0].reinterpret_as_mut()?,
+                    g[0].reinterpret_as_mut()?,
+                    b[0].reinterpret_as_mut()?,
+                    &self.stops,
+                    self.oklab
+                ),
+                BitType::F32 => gradient_map::<f32>(
+                    r[0].reinterpret_as_mut()?,
+                    g[0].reinterpret_as_mut()?,
+                    b[0].reinterpret_as_mut()?,
+                    &self.stops,
+                    self.oklab
+                ),
+                d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+            }
+        }
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Return the gradient color at position `t`, `stops` must be sorted and non-empty
+fn sample(stops: &[GradientStop], t: f32) -> [f32; 3] {
+    let first = stops[0];
+    let last = stops[stops.len() - 1];
+
+    if t <= first.position {
+        return first.color;
+    }
+    if t >= last.position {
+        return last.color;
+    }
+    // first stop after t, there is always one since t < last.position
+    let end = stops.partition_point(|stop| stop.position <= t);
+    let (a, b) = (stops[end - 1], stops[end]);
+
+    let frac = (t - a.position) / (b.position - a.position);
+
+    [0, 1, 2].map(|i| a.color[i] + (b.color[i] - a.color[i]) * frac)
+}
+
+/// Map pixels of an RGB image through a gradient
+///
+/// # Arguments
+/// - r,g,b: The image channels, modified in place
+/// - stops: Gradient stops, sorted by position, must not be empty
+/// - oklab: Whether to interpolate colors in OKLab instead of RGB
+pub fn gradient_map<T: Copy + NumOps<T>>(
+    r: &mut [T], g: &mut [T], b: &mut [T], stops: &[GradientStop], oklab: bool
+) {
+    let max = T::MAX_VAL.to_f32();
+    let min = T::MIN_VAL.to_f32();
+
+    let oklab_stops = if oklab {
+        stops
+            .iter()
+            .map(|stop| {
+                GradientStop::new(stop.position, srgb_to_oklab(stop.color.map(|c| c / max)))
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    for ((r, g), b) in r.iter_mut().zip(g.iter_mut()).zip(b.iter_mut()) {
+        let luma = (0.299 * r.to_f32() + 0.587 * g.to_f32() + 0.114 * b.to_f32()) / max;
+
+        let [nr, ng, nb] = if oklab {
+            oklab_to_srgb(sample(&oklab_stops, luma)).map(|c| c * max)
+        } else {
+            sample(stops, luma)
+        };
+
+        *r = round_to_depth(nr.clamp(min, max));
+        *g = round_to_depth(ng.clamp(min, max));
+        *b = round_to_depth(nb.clamp(min, max));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::gradient_map::{GradientMap, GradientStop};
+
+    #[test]
+    fn test_gradient_map_stops() {
+        let map = GradientMap::new(&[
+            GradientStop::new(1.0, [0.0, 0.0, 1.0]),
+            GradientStop::new(0.0, [1.0, 0.0, 0.0]),
+            GradientStop::new(0.5, [0.0, 1.0, 0.0])
+        ]);
+
+        for (value, expected) in [
+            (0.0, [1.0, 0.0, 0.0]),
+            (0.25, [0.5, 0.5, 0.0]),
+            (0.5, [0.0, 1.0, 0.0]),
+            (1.0, [0.0, 0.0, 1.0])
+        ] {
+            let mut image = Image::fill(value, ColorSpace::Luma, 2, 2);
+            map.execute(&mut image).unwrap();
+
+            for (channel, expected) in image.channels_ref(false).iter().zip(expected) {
+                let pixels = channel.reinterpret_as::<f32>().unwrap();
+                assert!((pixels[0] - expected).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn test_gradient_map_keeps_alpha() {
+        let mut image = Image::fill(255_u8, ColorSpace::LumaA, 2, 2);
+        GradientMap::duotone([0.0; 3], [10.0, 20.0, 30.0])
+            .execute(&mut image)
+            .unwrap();
+
+        assert_eq!(image.colorspace(), ColorSpace::RGBA);
+        let channels = image.channels_ref(false);
+        assert_eq!(channels[0].reinterpret_as::<u8>().unwrap()[0], 10);
+        assert_eq!(channels[2].reinterpret_as::<u8>().unwrap()[0], 30);
+        assert_eq!(channels[3].reinterpret_as::<u8>().unwrap()[0], 255);
+
+        assert!(GradientMap::new(&[]).execute(&mut image).is_err());
+    }
+
+    #[test]
+    fn test_gradient_map_oklab_midpoint() {
+        let mut rgb = Image::fill(0.5_f32, ColorSpace::Luma, 1, 1);
+        let mut oklab = rgb.clone();
+        let map = GradientMap::duotone([0.0, 0.0, 1.0], [1.0, 1.0, 0.0]);
+
+        map.execute(&mut rgb).unwrap();
+        map.set_oklab(true).execute(&mut oklab).unwrap();
+
+        let rgb = rgb.flatten_frames::<f32>().remove(0);
+        let oklab = oklab.flatten_frames::<f32>().remove(0);
+        // blue to yellow passes through gray in RGB, OKLab keeps the midpoint brighter
+        assert!(rgb.iter().all(|x| (x - 0.5).abs() < 1e-3));
+        assert!(oklab.iter().sum::<f32>() > rgb.iter().sum::<f32>());
+    }
+}
@@ -15,13 +15,15 @@
 //! but the main gist is that instead of converting back and forth, use a simple matrix that allows such calculations
 //! this routine adapts that for use
 //!
-use std::f32::consts::PI;
+use core::f32::consts::PI;
 
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
 use zune_core::bit_depth::BitType;
 use zune_core::colorspace::ColorSpace;
 use zune_image::errors::ImageErrors;
 use zune_image::image::Image;
-use zune_image::traits::OperationsTrait;
+use zune_image::traits::{OperationsTrait, Parameter, ParameterKind, ParameterValue};
 
 use crate::traits::NumOps;
 
@@ -49,6 +51,7 @@ use crate::traits::NumOps;
 ///
 ///# Ok::<(),ImageErrors>(())
 /// ```
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct HsvAdjust {
     hue:        f32,
     saturation: f32,
@@ -82,6 +85,32 @@ impl OperationsTrait for HsvAdjust {
         "modulate"
     }
 
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                name:        "hue",
+                description: "Hue rotation in degrees",
+                kind:        ParameterKind::Float {
+                    min: 0.0,
+                    max: 360.0
+                },
+                default:     ParameterValue::Float(0.0)
+            },
+            Parameter {
+                name:        "saturation",
+                description: "Saturation scale, 0 is grayscale",
+                kind:        ParameterKind::Float { min: 0.0, max: 3.0 },
+                default:     ParameterValue::Float(1.0)
+            },
+            Parameter {
+                name:        "lightness",
+                description: "Lightness scale",
+                kind:        ParameterKind::Float { min: 0.0, max: 3.0 },
+                default:     ParameterValue::Float(1.0)
+            }
+        ]
+    }
+
     fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
         let orig_color = image.colorspace();
         // convert to RGBA, this preserves alpha when it exists
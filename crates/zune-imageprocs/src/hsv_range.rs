@@ -0,0 +1,237 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Select pixels by hue, saturation and value
+//!
+//! This creates a mask of the pixels whose HSV values lie within a range, similar to
+//! opencv's `inRange` on HSV images, e.g. to select all red-ish pixels of an image
+//!
+//! # Algorithm
+//! Every pixel is converted to HSV, with hue in degrees and saturation and value in 0..1,
+//! pixels inside all three ranges become the maximum value of the bit depth, the rest become zero.
+//!
+//! Hue ranges wrap around, so a range from 340 to 20 degrees selects reds on both sides of 0.
+//!
+//! The output image is a [`Luma`](ColorSpace::Luma) mask with the same dimensions and depth as
+//! the input.
+use alloc::vec;
+
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::channel::Channel;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::traits::NumOps;
+use crate::utils::rem_euclid;
+
+/// Create a mask of pixels within a range of hue, saturation and value
+///
+/// # Example
+/// Select saturated red pixels
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::hsv_range::HsvRange;
+///
+/// let mut image = Image::fill(128_u8, ColorSpace::RGB, 100, 100);
+///
+/// HsvRange::new()
+///     .set_hue(340.0, 20.0)
+///     .set_saturation(0.5, 1.0)
+///     .execute(&mut image)
+///     .unwrap();
+///
+/// assert_eq!(image.colorspace(), ColorSpace::Luma);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct HsvRange {
+    hue:        (f32, f32),
+    saturation: (f32, f32),
+    value:      (f32, f32)
+}
+
+impl Default for HsvRange {
+    fn default() -> Self {
+        HsvRange {
+            hue:        (0.0, 360.0),
+            saturation: (0.0, 1.0),
+            value:      (0.0, 1.0)
+        }
+    }
+}
+
+impl HsvRange {
+    /// Create a new range selecting every pixel
+    ///
+    /// Use the setters to narrow it down
+    #[must_use]
+    pub fn new() -> HsvRange {
+        HsvRange::default()
+    }
+    /// Set the hue range in degrees, both ends inclusive
+    ///
+    /// If `start` is greater than `end`, the range wraps around 0 degrees
+    #[must_use]
+    pub fn set_hue(mut self, start: f32, end: f32) -> Self {
+        self.hue = (rem_euclid(start, 360.0), rem_euclid(end, 360.0));
+        // keep the full circle when given 0..360
+        if end - start >= 360.0 {
+            self.hue = (0.0, 360.0);
+        }
+        self
+    }
+    /// Set the saturation range, between 0 and 1, both ends inclusive
+    #[must_use]
+    pub fn set_saturation(mut self, min: f32, max: f32) -> Self {
+        self.saturation = (min, max);
+        self
+    }
+    /// Set the value (brightness) range, between 0 and 1, both ends inclusive
+    #[must_use]
+    pub fn set_value(mut self, min: f32, max: f32) -> Self {
+        self.value = (min, max);
+        self
+    }
+
+    fn contains(&self, [h, s, v]: [f32; 3]) -> bool {
+        let (start, end) = self.hue;
+        // gray pixels have no hue, treat it as zero like the HSV conversion does
+        let hue = if start <= end { (start..=end).contains(&h) } else { h >= start || h <= end };
+
+        hue && (self.saturation.0..=self.saturation.1).contains(&s)
+            && (self.value.0..=self.value.1).contains(&v)
+    }
+}
+
+impl OperationsTrait for HsvRange {
+    fn name(&self) -> &'static str {
+        "HSV Range"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        image.convert_color(ColorSpace::RGB)?;
+
+        let depth = image.depth().bit_type();
+
+        for frame in image.frames_mut() {
+            let channels = frame.channels_ref(ColorSpace::RGB, true);
+            let mut mask = Channel::new_with_bit_type(channels[0].len(), depth);
+
+            match depth {
+                BitType::U8 => hsv_range::<u8>(
+                    channels[0].reinterpret_as()?,
+                    channels[1].reinterpret_as()?,
+                    channels[2].reinterpret_as()?,
+                    mask.reinterpret_as_mut()?,
+                    self
+                ),
+                BitType::U16 => hsv_range::<u16>(
+                    channels[0].reinterpret_as()?,
+                    channels[1].reinterpret_as()?,
+                    channels[2].reinterpret_as()?,
+                    mask.reinterpret_as_mut()?,
+                    self
+                ),
+                BitType::F32 => hsv_range::<f32>(
+                    channels[0].reinterpret_as()?,
+                    channels[1].reinterpret_as()?,
+                    channels[2].reinterpret_as()?,
+                    mask.reinterpret_as_mut()?,
+                    self
+                ),
+                d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+            }
+            frame.set_channels(vec![mask]);
+        }
+        image.metadata_mut().set_colorspace(ColorSpace::Luma);
+
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Convert RGB in 0..1 to hue in degrees, saturation and value
+#[allow(clippy::float_cmp)]
+fn rgb_to_hsv(r: f32, g: f32, b: f32) -> [f32; 3] {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+
+    if delta <= 0.0 {
+        return [0.0, 0.0, max];
+    }
+    let hue = if max == r {
+        (g - b) / delta
+    } else if max == g {
+        2.0 + (b - r) / delta
+    } else {
+        4.0 + (r - g) / delta
+    };
+    [rem_euclid(hue * 60.0, 360.0), delta / max, max]
+}
+
+/// Create a mask of RGB pixels inside an HSV range
+///
+/// # Arguments
+/// - r,g,b: The image channels
+/// - mask: The output mask, set to the maximum value of `T` for pixels inside the range and zero for the rest
+/// - range: The HSV range to select
+pub fn hsv_range<T: Copy + NumOps<T>>(r: &[T], g: &[T], b: &[T], mask: &mut [T], range: &HsvRange) {
+    let max = T::MAX_VAL.to_f32();
+
+    for (((r, g), b), out) in r.iter().zip(g).zip(b).zip(mask.iter_mut()) {
+        let hsv = rgb_to_hsv(r.to_f32() / max, g.to_f32() / max, b.to_f32() / max);
+
+        *out = if range.contains(hsv) { T::MAX_VAL } else { T::MIN_VAL };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::hsv_range::HsvRange;
+
+    #[test]
+    fn test_select_reds_wrapping_hue() {
+        // red, a pink-ish red, green and gray
+        let pixels = [255, 0, 0, 255, 0, 40, 0, 255, 0, 128, 128, 128];
+        let mut image = Image::from_u8(&pixels, 4, 1, ColorSpace::RGB);
+
+        HsvRange::new()
+            .set_hue(340.0, 20.0)
+            .set_saturation(0.5, 1.0)
+            .execute(&mut image)
+            .unwrap();
+
+        assert_eq!(image.colorspace(), ColorSpace::Luma);
+        assert_eq!(image.flatten_frames::<u8>()[0], [255, 255, 0, 0]);
+    }
+
+    #[test]
+    fn test_hsv_input_and_value_range() {
+        let mut image = Image::fill(0.25_f32, ColorSpace::RGB, 2, 2);
+        image.convert_color(ColorSpace::HSV).unwrap();
+
+        HsvRange::new()
+            .set_value(0.2, 0.3)
+            .execute(&mut image)
+            .unwrap();
+
+        assert!(image.flatten_frames::<f32>()[0]
+            .iter()
+            .all(|x| (*x - 1.0).abs() < f32::EPSILON));
+    }
+}
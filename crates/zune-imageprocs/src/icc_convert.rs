@@ -0,0 +1,434 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Color managed conversion using ICC profiles
+//!
+//! Decoders store the ICC profile embedded in an image in its metadata, this operation
+//! uses that profile to convert pixels to sRGB (or any other profile), so that images
+//! with wide gamut or otherwise unusual profiles display with correct colors.
+//!
+//! # Algorithm
+//! Every pixel is normalized to 0..1, converted to the D50 XYZ profile connection space using
+//! the source profile and then to device values of the destination profile, see
+//! [`zune_core::icc`] for the supported profile types.
+//!
+//! Images without an embedded profile are assumed to be sRGB.
+//!
+//! After conversion the image carries the destination profile, or no profile when converting to sRGB.
+use alloc::format;
+use alloc::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::{ColorSpace, RenderingIntent};
+use zune_core::icc::{IccColorSpace, IccProfile, IccTransform};
+use zune_image::channel::Channel;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::traits::NumOps;
+use crate::utils::round_to_depth;
+
+/// Convert an image between ICC profiles
+///
+/// # Example
+/// Convert an image with an embedded profile to sRGB
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::icc_convert::IccConvert;
+///
+/// // images without a profile are already sRGB, so this does nothing
+/// let mut image = Image::fill(128_u8, ColorSpace::RGB, 100, 100);
+///
+/// IccConvert::to_srgb().execute(&mut image).unwrap();
+/// ```
+pub struct IccConvert {
+    source:            Option<IccProfile>,
+    destination:       IccProfile,
+    destination_bytes: Option<Vec<u8>>,
+    intent:            RenderingIntent
+}
+
+impl IccConvert {
+    /// Convert images to sRGB, removing their embedded profile
+    #[must_use]
+    pub fn to_srgb() -> IccConvert {
+        IccConvert {
+            source:            None,
+            destination:       IccProfile::srgb(),
+            destination_bytes: None,
+            intent:            RenderingIntent::Perceptual
+        }
+    }
+    /// Convert images to the colors described by `profile`, embedding it in the image
+    ///
+    /// # Errors
+    /// If the profile can't be parsed
+    pub fn new(profile: &[u8]) -> Result<IccConvert, ImageErrors> {
+        Ok(IccConvert {
+            source:            None,
+            destination:       IccProfile::parse(profile)?,
+            destination_bytes: Some(profile.to_vec()),
+            intent:            RenderingIntent::Perceptual
+        })
+    }
+    /// Use `profile` as the source profile instead of the profile embedded in the image
+    #[must_use]
+    pub fn set_source(mut self, profile: IccProfile) -> Self {
+        self.source = Some(profile);
+        self
+    }
+    /// Set the rendering intent, the default is perceptual
+    #[must_use]
+    pub fn set_intent(mut self, intent: RenderingIntent) -> Self {
+        self.intent = intent;
+        self
+    }
+}
+
+/// The colorspace image channels are processed in for an ICC color space
+fn image_colorspace(color_space: IccColorSpace, alpha: bool) -> Result<ColorSpace, ImageErrors> {
+    match (color_space, alpha) {
+        (IccColorSpace::RGB, false) => Ok(ColorSpace::RGB),
+        (IccColorSpace::RGB, true) => Ok(ColorSpace::RGBA),
+        (IccColorSpace::Gray, false) => Ok(ColorSpace::Luma),
+        (IccColorSpace::Gray, true) => Ok(ColorSpace::LumaA),
+        (IccColorSpace::CMYK, _) => Ok(ColorSpace::CMYK),
+        (space, _) => Err(ImageErrors::GenericString(format!(
+            "ICC profile color space {space:?} has no matching image colorspace"
+        )))
+    }
+}
+
+/// Check that an image colorspace can be converted to the channels of a profile without changing colors
+fn check_compatible(
+    colorspace: ColorSpace, input_colorspace: ColorSpace, profile_space: IccColorSpace
+) -> Result<(), ImageErrors> {
+    let compatible = match profile_space {
+        IccColorSpace::RGB => !colorspace.is_grayscale() && colorspace != ColorSpace::CMYK,
+        IccColorSpace::Gray => colorspace.is_grayscale(),
+        _ => colorspace == input_colorspace
+    };
+    if !compatible {
+        return Err(ImageErrors::GenericString(format!(
+            "Image colorspace {colorspace:?} does not match ICC profile color space {profile_space:?}"
+        )));
+    }
+    Ok(())
+}
+
+impl OperationsTrait for IccConvert {
+    fn name(&self) -> &'static str {
+        "ICC Convert"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let embedded = match image.metadata().icc_chunk() {
+            Some(data) => Some(IccProfile::parse(data)?),
+            None => None
+        };
+        let source = match (&self.source, embedded) {
+            (Some(profile), _) => profile.clone(),
+            (None, Some(profile)) => profile,
+            // untagged images are sRGB
+            (None, None) => {
+                if self.destination_bytes.is_none() {
+                    // untagged CMYK has no known colors, only the naive conversion is possible
+                    if image.colorspace() == ColorSpace::CMYK {
+                        image.convert_color(ColorSpace::RGB)?;
+                    }
+                    // and already in the destination colors
+                    return Ok(());
+                }
+                IccProfile::srgb()
+            }
+        };
+        let transform = IccTransform::new(&source, &self.destination, self.intent)?;
+
+        let alpha = image.colorspace().has_alpha();
+        let input_colorspace = image_colorspace(source.color_space, alpha)?;
+        let output_colorspace = image_colorspace(self.destination.color_space, alpha)?;
+
+        check_compatible(image.colorspace(), input_colorspace, source.color_space)?;
+
+        if image.colorspace() != input_colorspace {
+            // reorders channels, e.g. BGR to RGB, without changing colors
+            image.convert_color(input_colorspace)?;
+        }
+        // image CMYK stores inverted ink amounts, profiles expect the ink amounts
+        let inverted = (
+            source.color_space == IccColorSpace::CMYK,
+            self.destination.color_space == IccColorSpace::CMYK
+        );
+        let (width, height) = image.dimensions();
+        let bit_depth = image.depth();
+        let depth = bit_depth.bit_type();
+        let length = width * height * bit_depth.size_of();
+
+        for frame in image.frames_mut() {
+            let inputs = frame.channels_ref(input_colorspace, true);
+            let mut outputs = (0..transform.output_channels())
+                .map(|_| Channel::new_with_bit_type(length, depth))
+                .collect::<Vec<_>>();
+
+            match depth {
+                BitType::U8 => icc_convert::<u8>(
+                    &inputs
+                        .iter()
+                        .map(Channel::reinterpret_as)
+                        .collect::<Result<Vec<_>, _>>()?,
+                    &mut outputs
+                        .iter_mut()
+                        .map(Channel::reinterpret_as_mut)
+                        .collect::<Result<Vec<_>, _>>()?,
+                    &transform,
+                    inverted
+                ),
+                BitType::U16 => icc_convert::<u16>(
+                    &inputs
+                        .iter()
+                        .map(Channel::reinterpret_as)
+                        .collect::<Result<Vec<_>, _>>()?,
+                    &mut outputs
+                        .iter_mut()
+                        .map(Channel::reinterpret_as_mut)
+                        .collect::<Result<Vec<_>, _>>()?,
+                    &transform,
+                    inverted
+                ),
+                BitType::F32 => icc_convert::<f32>(
+                    &inputs
+                        .iter()
+                        .map(Channel::reinterpret_as)
+                        .collect::<Result<Vec<_>, _>>()?,
+                    &mut outputs
+                        .iter_mut()
+                        .map(Channel::reinterpret_as_mut)
+                        .collect::<Result<Vec<_>, _>>()?,
+                    &transform,
+                    inverted
+                ),
+                d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+            }
+            if output_colorspace.has_alpha() {
+                let (_, alpha) = frame
+                    .separate_color_and_alpha_ref(input_colorspace)
+                    .expect("colorspace has alpha");
+                outputs.push(alpha.clone());
+            }
+            frame.set_channels(outputs);
+        }
+        image.metadata_mut().set_colorspace(output_colorspace);
+
+        match &self.destination_bytes {
+            Some(bytes) => image.metadata_mut().set_icc_chunk(bytes.clone()),
+            None => {
+                image.metadata_mut().remove_icc_chunk();
+            }
+        }
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Convert pixels of separate channels through an ICC transform
+///
+/// # Arguments
+/// - inputs: One slice per input channel of the transform
+/// - outputs: One slice per output channel of the transform, the same length as the inputs
+/// - transform: The transform to use
+/// - inverted: Whether the input and output values are inverted, as is the case for
+///   [`ColorSpace::CMYK`] images
+pub fn icc_convert<T: Copy + NumOps<T>>(
+    inputs: &[&[T]], outputs: &mut [&mut [T]], transform: &IccTransform, inverted: (bool, bool)
+) {
+    let max = T::MAX_VAL.to_f32();
+    let mut pixel_in = [0.0; 4];
+    let mut pixel_out = [0.0; 4];
+    let length = inputs.first().map_or(0, |x| x.len());
+
+    for i in 0..length {
+        for (value, input) in pixel_in.iter_mut().zip(inputs) {
+            *value = input[i].to_f32() / max;
+
+            if inverted.0 {
+                *value = 1.0 - *value;
+            }
+        }
+        transform.transform(&pixel_in, &mut pixel_out);
+
+        for (output, mut value) in outputs.iter_mut().zip(pixel_out) {
+            value = value.clamp(0.0, 1.0);
+
+            if inverted.1 {
+                value = 1.0 - value;
+            }
+            output[i] = round_to_depth(value * max);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::icc::{IccColorSpace, IccProfile};
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::icc_convert::IccConvert;
+
+    /// sRGB primaries adapted to D50, as columns
+    const PRIMARIES: [[f32; 3]; 3] = [
+        [0.436_074, 0.222_504, 0.013_932],
+        [0.385_065, 0.716_879, 0.097_104],
+        [0.143_080, 0.060_617, 0.714_173]
+    ];
+
+    fn s15_fixed16(value: f32) -> [u8; 4] {
+        ((value * 65536.0).round() as i32).to_be_bytes()
+    }
+
+    fn xyz_tag(xyz: [f32; 3]) -> Vec<u8> {
+        let mut tag = b"XYZ \0\0\0\0".to_vec();
+        for x in xyz {
+            tag.extend(s15_fixed16(x));
+        }
+        tag
+    }
+
+    /// Build a profile from its tags
+    fn build_profile(color_space: [u8; 4], tags: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let mut header = vec![0; 128];
+        header[8] = 4;
+        header[12..16].copy_from_slice(b"mntr");
+        header[16..20].copy_from_slice(&color_space);
+        header[20..24].copy_from_slice(b"XYZ ");
+        header[36..40].copy_from_slice(b"acsp");
+
+        let mut table = (tags.len() as u32).to_be_bytes().to_vec();
+        let mut data = Vec::new();
+        let data_start = 128 + 4 + tags.len() * 12;
+
+        for (signature, tag) in tags {
+            table.extend_from_slice(*signature);
+            table.extend(((data_start + data.len()) as u32).to_be_bytes());
+            table.extend((tag.len() as u32).to_be_bytes());
+            data.extend_from_slice(tag);
+            // tags are four byte aligned
+            data.resize((data.len() + 3) & !3, 0);
+        }
+        let mut profile = [header, table, data].concat();
+        let size = profile.len() as u32;
+        profile[..4].copy_from_slice(&size.to_be_bytes());
+        profile
+    }
+
+    #[test]
+    fn test_linear_matrix_profile_to_srgb() {
+        // a linear light profile with sRGB primaries
+        let gamma = [b"curv".as_slice(), &[0; 4], &1_u32.to_be_bytes(), &[1, 0]].concat();
+        let profile = build_profile(
+            *b"RGB ",
+            &[
+                (b"rXYZ", xyz_tag(PRIMARIES[0])),
+                (b"gXYZ", xyz_tag(PRIMARIES[1])),
+                (b"bXYZ", xyz_tag(PRIMARIES[2])),
+                (b"rTRC", gamma.clone()),
+                (b"gTRC", gamma.clone()),
+                (b"bTRC", gamma)
+            ]
+        );
+        let parsed = IccProfile::parse(&profile).unwrap();
+        assert_eq!(parsed.color_space, IccColorSpace::RGB);
+        assert!(parsed.is_matrix_shaper());
+
+        let mut image = Image::fill(128_u8, ColorSpace::BGRA, 4, 4);
+        image.metadata_mut().set_icc_chunk(profile);
+
+        IccConvert::to_srgb().execute(&mut image).unwrap();
+
+        assert_eq!(image.colorspace(), ColorSpace::RGBA);
+        assert!(image.metadata().icc_chunk().is_none());
+
+        let channels = image.channels_ref(false);
+        // linear 0.5 is 0.735 in sRGB
+        for channel in &channels[..3] {
+            let value = channel.reinterpret_as::<u8>().unwrap()[0];
+            assert!(value.abs_diff(188) <= 1, "{value}");
+        }
+        assert_eq!(channels[3].reinterpret_as::<u8>().unwrap()[0], 128);
+    }
+
+    #[test]
+    fn test_untagged_cmyk_to_srgb() {
+        // no ink except for half the black ink
+        let pixels = [255, 255, 255, 128].repeat(4);
+        let mut image = Image::from_u8(&pixels, 2, 2, ColorSpace::CMYK);
+        IccConvert::to_srgb().execute(&mut image).unwrap();
+
+        assert_eq!(image.colorspace(), ColorSpace::RGB);
+        for channel in image.channels_ref(false) {
+            assert_eq!(channel.reinterpret_as::<u8>().unwrap()[0], 128);
+        }
+    }
+
+    #[test]
+    fn test_lut_profile_to_srgb() {
+        // a lut16 mapping linear RGB to XYZ with a 2x2x2 CLUT, every curve is identity
+        let mut lut = b"mft2\0\0\0\0".to_vec();
+        lut.extend([3, 3, 2, 0]);
+        for i in 0..9 {
+            lut.extend(s15_fixed16(if i % 4 == 0 { 1.0 } else { 0.0 }));
+        }
+        lut.extend(2_u16.to_be_bytes());
+        lut.extend(2_u16.to_be_bytes());
+        let identity = [0_u16.to_be_bytes(), 65535_u16.to_be_bytes()].concat();
+        (0..3).for_each(|_| lut.extend(&identity));
+
+        for corner in 0..8 {
+            let rgb = [(corner >> 2) & 1, (corner >> 1) & 1, corner & 1].map(|x| x as f32);
+            for row in 0..3 {
+                let xyz = PRIMARIES
+                    .iter()
+                    .zip(rgb)
+                    .map(|(p, c)| p[row] * c)
+                    .sum::<f32>();
+                // XYZ is encoded with 1.0 as 0x8000
+                lut.extend(((xyz * 32768.0).round() as u16).to_be_bytes());
+            }
+        }
+        (0..3).for_each(|_| lut.extend(&identity));
+
+        let profile = build_profile(*b"RGB ", &[(b"A2B0", lut)]);
+        let parsed = IccProfile::parse(&profile).unwrap();
+        assert!(!parsed.is_matrix_shaper());
+        assert!(parsed.can_convert_from_device());
+
+        let mut image = Image::fill(0.5_f32, ColorSpace::RGB, 2, 2);
+        IccConvert::to_srgb()
+            .set_source(parsed)
+            .execute(&mut image)
+            .unwrap();
+
+        for channel in image.channels_ref(false) {
+            let value = channel.reinterpret_as::<f32>().unwrap()[0];
+            assert!((value - 0.7354).abs() < 0.005, "{value}");
+        }
+    }
+}
@@ -0,0 +1,370 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Integral images (summed-area tables)
+//!
+//! An integral image stores at each position the sum of all pixels above and to the left
+//! of it, which allows computing the sum (and hence the mean and variance) of any rectangle
+//! in constant time, regardless of its size.
+//!
+//! This is the building block for adaptive thresholding, fast box filters and
+//! feature detectors that need box statistics at many scales.
+//!
+//! # Algorithm
+//! The table is computed in a single pass using
+//! `I(x,y) = p(x,y) + I(x-1,y) + I(x,y-1) - I(x-1,y-1)`, implemented as a running row sum
+//! added to the row above.
+//!
+//! Tables have an extra leading row and column of zeroes, so a table for a `width x height`
+//! channel is `(width+1) x (height+1)` and rectangle sums need no bounds checks.
+//!
+//! ## Storage
+//! - For 8-bit images, sums are stored as [`u32`] using wrapping arithmetic, sums of any
+//!   rectangle with at most `16_843_009` pixels (4104x4104) are exact even if the table wraps.
+//! - For 16 bit and float images, sums are stored as [`f64`]
+//! - Squared sums are always stored as [`f64`]
+use alloc::vec;
+use alloc::vec::Vec;
+use std::sync::Mutex;
+
+use zune_core::bit_depth::BitType;
+use zune_image::channel::Channel;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::traits::NumOps;
+
+/// Integral images for all channels of an image
+#[derive(Clone, Debug, PartialEq)]
+pub struct IntegralImages {
+    /// Width of the image the tables were computed from (tables are one wider)
+    pub width:       usize,
+    /// Height of the image the tables were computed from (tables are one taller)
+    pub height:      usize,
+    /// Integral image per channel, [`u32`] for 8 bit images and [`f64`] otherwise
+    pub sum:         Vec<Channel>,
+    /// Squared integral image per channel, always [`f64`]
+    pub squared_sum: Vec<Channel>
+}
+
+impl IntegralImages {
+    /// Return the sum of pixels of `channel` inside the rectangle starting at `(x,y)`
+    /// with dimensions `width x height`
+    ///
+    /// # Panics
+    /// If the rectangle is outside the image or the channel doesn't exist
+    #[must_use]
+    pub fn box_sum(&self, channel: usize, x: usize, y: usize, width: usize, height: usize) -> f64 {
+        let table = &self.sum[channel];
+
+        if let Ok(table) = table.reinterpret_as::<u32>() {
+            let [tl, tr, bl, br] = self.corners(table, x, y, width, height);
+            // wrapping arithmetic recovers the exact sum even if the table overflowed
+            return f64::from(br.wrapping_sub(tr).wrapping_sub(bl).wrapping_add(tl));
+        }
+        let table = table.reinterpret_as::<f64>().unwrap();
+        let [tl, tr, bl, br] = self.corners(table, x, y, width, height);
+
+        br - tr - bl + tl
+    }
+    /// Return the sum of squared pixels of `channel` inside the rectangle starting at `(x,y)`
+    /// with dimensions `width x height`
+    ///
+    /// # Panics
+    /// If the rectangle is outside the image or the channel doesn't exist
+    #[must_use]
+    pub fn box_squared_sum(
+        &self, channel: usize, x: usize, y: usize, width: usize, height: usize
+    ) -> f64 {
+        let table = self.squared_sum[channel].reinterpret_as::<f64>().unwrap();
+        let [tl, tr, bl, br] = self.corners(table, x, y, width, height);
+
+        br - tr - bl + tl
+    }
+    /// Return the mean of pixels of `channel` inside a rectangle
+    ///
+    /// See [box_sum](Self::box_sum)
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn box_mean(&self, channel: usize, x: usize, y: usize, width: usize, height: usize) -> f64 {
+        let area = (width * height).max(1) as f64;
+
+        self.box_sum(channel, x, y, width, height) / area
+    }
+    /// Return the (population) variance of pixels of `channel` inside a rectangle
+    ///
+    /// See [box_sum](Self::box_sum)
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn box_variance(
+        &self, channel: usize, x: usize, y: usize, width: usize, height: usize
+    ) -> f64 {
+        let area = (width * height).max(1) as f64;
+
+        let mean = self.box_sum(channel, x, y, width, height) / area;
+        let mean_sq = self.box_squared_sum(channel, x, y, width, height) / area;
+        // rounding may make this slightly negative for constant regions
+        (mean_sq - mean * mean).max(0.0)
+    }
+
+    /// Return table values at the top-left, top-right, bottom-left and bottom-right corners
+    fn corners<T: Copy>(
+        &self, table: &[T], x: usize, y: usize, width: usize, height: usize
+    ) -> [T; 4] {
+        assert!(
+            x + width <= self.width && y + height <= self.height,
+            "Rectangle outside image"
+        );
+        let stride = self.width + 1;
+
+        let top = y * stride;
+        let bottom = (y + height) * stride;
+
+        [
+            table[top + x],
+            table[top + x + width],
+            table[bottom + x],
+            table[bottom + x + width]
+        ]
+    }
+}
+
+/// Compute integral and squared integral images
+///
+/// This does not modify the image, tables can be fetched via [`integral_images`](Self::integral_images)
+/// after calling `execute`.
+///
+/// Only the first frame of the image is analyzed, alpha channels are included
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::integral_image::IntegralImage;
+///
+/// let mut image = Image::fill(10_u8, ColorSpace::Luma, 20, 20);
+/// let integral = IntegralImage::new();
+/// integral.execute(&mut image).unwrap();
+///
+/// let tables = integral.integral_images().unwrap();
+/// assert_eq!(tables.box_sum(0, 5, 5, 4, 4), 160.0);
+/// assert_eq!(tables.box_variance(0, 0, 0, 20, 20), 0.0);
+/// ```
+#[derive(Default)]
+pub struct IntegralImage {
+    result: Mutex<Option<IntegralImages>>
+}
+
+impl IntegralImage {
+    /// Create a new integral image operation
+    #[must_use]
+    pub fn new() -> IntegralImage {
+        IntegralImage::default()
+    }
+    /// Return the tables from the last execution or `None` if the operation
+    /// hasn't been executed
+    pub fn integral_images(&self) -> Option<IntegralImages> {
+        self.result.lock().ok()?.clone()
+    }
+}
+
+impl OperationsTrait for IntegralImage {
+    fn name(&self) -> &'static str {
+        "Integral Image"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let depth = image.depth().bit_type();
+        let table_size = (width + 1) * (height + 1);
+
+        let mut result = IntegralImages {
+            width,
+            height,
+            sum: vec![],
+            squared_sum: vec![]
+        };
+
+        let colorspace = image.colorspace();
+
+        for channel in image.frames_ref()[0].channels_ref(colorspace, false) {
+            let mut sum = match depth {
+                BitType::U8 => Channel::new_with_length::<u32>(table_size * size_of::<u32>()),
+                _ => Channel::new_with_length::<f64>(table_size * size_of::<f64>())
+            };
+            let mut squared = Channel::new_with_length::<f64>(table_size * size_of::<f64>());
+
+            match depth {
+                BitType::U8 => {
+                    let pixels = channel.reinterpret_as::<u8>()?;
+                    integral_image_u8(pixels, width, height, sum.reinterpret_as_mut()?);
+                    squared_integral_image(pixels, width, height, squared.reinterpret_as_mut()?);
+                }
+                BitType::U16 => {
+                    let pixels = channel.reinterpret_as::<u16>()?;
+                    integral_image(pixels, width, height, sum.reinterpret_as_mut()?);
+                    squared_integral_image(pixels, width, height, squared.reinterpret_as_mut()?);
+                }
+                BitType::F32 => {
+                    let pixels = channel.reinterpret_as::<f32>()?;
+                    integral_image(pixels, width, height, sum.reinterpret_as_mut()?);
+                    squared_integral_image(pixels, width, height, squared.reinterpret_as_mut()?);
+                }
+                d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+            }
+            result.sum.push(sum);
+            result.squared_sum.push(squared);
+        }
+
+        *self
+            .result
+            .lock()
+            .map_err(|_| ImageErrors::GenericStr("Integral image result is poisoned"))? =
+            Some(result);
+
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Compute the integral image table of an 8 bit channel using wrapping arithmetic
+///
+/// # Arguments
+/// - pixels: The channel, should have `width*height` elements
+/// - width,height: Channel dimensions
+/// - out: Output table, should have `(width+1)*(height+1)` elements
+pub fn integral_image_u8(pixels: &[u8], width: usize, height: usize, out: &mut [u32]) {
+    summed_area_table(pixels, width, height, out, u32::from, u32::wrapping_add);
+}
+
+/// Compute the integral image table of a channel
+///
+/// # Arguments
+/// - pixels: The channel, should have `width*height` elements
+/// - width,height: Channel dimensions
+/// - out: Output table, should have `(width+1)*(height+1)` elements
+pub fn integral_image<T>(pixels: &[T], width: usize, height: usize, out: &mut [f64])
+where
+    T: NumOps<T> + Copy
+{
+    summed_area_table(pixels, width, height, out, T::to_f64, |a, b| a + b);
+}
+
+/// Compute the squared integral image table of a channel, i.e. the integral image of
+/// each pixel squared
+///
+/// # Arguments
+/// - pixels: The channel, should have `width*height` elements
+/// - width,height: Channel dimensions
+/// - out: Output table, should have `(width+1)*(height+1)` elements
+pub fn squared_integral_image<T>(pixels: &[T], width: usize, height: usize, out: &mut [f64])
+where
+    T: NumOps<T> + Copy
+{
+    summed_area_table(
+        pixels,
+        width,
+        height,
+        out,
+        |x| {
+            let x = x.to_f64();
+            x * x
+        },
+        |a, b| a + b
+    );
+}
+
+fn summed_area_table<T: Copy, O: Copy + Default>(
+    pixels: &[T], width: usize, height: usize, out: &mut [O], map: impl Fn(T) -> O,
+    add: impl Fn(O, O) -> O
+) {
+    let stride = width + 1;
+
+    assert!(pixels.len() >= width * height, "Pixels too small");
+    assert!(out.len() >= stride * (height + 1), "Output too small");
+
+    // leading row is zero
+    out[..stride].fill(O::default());
+
+    for y in 0..height {
+        let (previous, current) = out[y * stride..(y + 2) * stride].split_at_mut(stride);
+
+        current[0] = O::default();
+        let mut row_sum = O::default();
+
+        for x in 0..width {
+            row_sum = add(row_sum, map(pixels[y * width + x]));
+            current[x + 1] = add(row_sum, previous[x + 1]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::integral_image::{integral_image_u8, IntegralImage};
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation, clippy::float_cmp)]
+    fn test_box_statistics_match_naive() {
+        let (width, height) = (13, 9);
+        let mut image = Image::from_fn::<u16, _>(width, height, ColorSpace::Luma, |y, x, pix| {
+            pix[0] = ((x * 31 + y * 17) % 97) as u16 * 600;
+        });
+        let pixels = image.channels_ref(false)[0]
+            .reinterpret_as::<u16>()
+            .unwrap()
+            .to_vec();
+
+        let integral = IntegralImage::new();
+        integral.execute(&mut image).unwrap();
+        let tables = integral.integral_images().unwrap();
+
+        let (x, y, box_w, box_h) = (3, 2, 7, 5);
+        let mut naive_sum = 0.0;
+        let mut naive_sq = 0.0;
+
+        for row in pixels.chunks_exact(width).skip(y).take(box_h) {
+            for pix in &row[x..x + box_w] {
+                let value = f64::from(*pix);
+                naive_sum += value;
+                naive_sq += value * value;
+            }
+        }
+        assert_eq!(tables.box_sum(0, x, y, box_w, box_h), naive_sum);
+        assert_eq!(tables.box_squared_sum(0, x, y, box_w, box_h), naive_sq);
+    }
+
+    #[test]
+    #[ignore = "allocates a 70 MB table, run with --ignored"]
+    fn test_u8_wrapping_sums_are_exact() {
+        // the whole table overflows u32 but small boxes near the end are still exact
+        let (width, height) = (4200, 4100);
+        let pixels = vec![255_u8; width * height];
+        let mut table = vec![0_u32; (width + 1) * (height + 1)];
+
+        integral_image_u8(&pixels, width, height, &mut table);
+
+        let stride = width + 1;
+        let at = |x: usize, y: usize| table[y * stride + x];
+        let (left, top) = (width - 10, height - 10);
+
+        let sum = at(width, height)
+            .wrapping_sub(at(width, top))
+            .wrapping_sub(at(left, height))
+            .wrapping_add(at(left, top));
+        assert_eq!(sum, 25500);
+    }
+}
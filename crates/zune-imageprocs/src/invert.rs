@@ -14,7 +14,7 @@
 //!
 //! pixel = max_value-pixel
 //! ```
-use std::ops::Sub;
+use core::ops::Sub;
 
 use zune_core::bit_depth::BitType;
 use zune_core::colorspace::ColorSpace;
@@ -33,6 +33,7 @@ use crate::utils::execute_on;
 /// (255 for [`u8`],65535 for [`u16`], 1 for [`f32`])
 ///
 #[derive(Default)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Invert;
 
 impl Invert {
@@ -68,12 +69,16 @@ impl OperationsTrait for Invert {
             ColorSpace::RGB,
             ColorSpace::RGBA,
             ColorSpace::LumaA,
-            ColorSpace::Luma
+            ColorSpace::Luma,
+            ColorSpace::CMYK
         ]
     }
     fn supported_types(&self) -> &'static [BitType] {
         &[BitType::U8, BitType::U16, BitType::F32]
     }
+    fn is_point_op(&self) -> bool {
+        true
+    }
 }
 
 ///Invert a pixel
@@ -0,0 +1,305 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Letterbox: Expand the canvas to an aspect ratio or size
+//!
+//! This places the image on a larger canvas, adding bars at the top and bottom (letterbox)
+//! or left and right (pillarbox), which is useful for exporting to targets that require
+//! a fixed aspect ratio without cropping or distorting the image.
+//!
+//! The bars can either be a solid color or a blurred, enlarged copy of the image.
+//!
+//! # Algorithm
+//! - For a solid color, a new canvas is created and the image is copied into it
+//! - For a blurred background, the image is scaled to cover the canvas, center cropped
+//!   and blurred with a gaussian blur, then the original image is copied on top
+use alloc::format;
+use alloc::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use zune_core::bit_depth::BitType;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::crop::Crop;
+use crate::gaussian_blur::GaussianBlur;
+use crate::resize::{Resize, ResizeMethod};
+use crate::utils::{calculate_gravity, constant_channel, Gravity};
+use crate::DepthColor;
+
+/// The canvas size to expand to
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum LetterboxSize {
+    /// Expand to the smallest canvas with `width:height` aspect ratio that contains the image,
+    /// e.g. `AspectRatio(16, 9)`
+    AspectRatio(usize, usize),
+    /// Expand to exactly `(width,height)`, which must be at least as large as the image
+    Exact(usize, usize)
+}
+
+/// How the area around the image is filled
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LetterboxFill {
+    /// Fill with a solid color, see [DepthColor] for the range of the values
+    Color(DepthColor),
+    /// Fill with a copy of the image scaled to cover the canvas and blurred with
+    /// the given gaussian sigma
+    Blur(f32)
+}
+
+/// Expand the canvas to a target aspect ratio or size
+///
+/// # Example
+/// Pad an image to 16:9 with black bars
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::letterbox::{Letterbox, LetterboxFill, LetterboxSize};
+///
+/// let mut image = Image::fill(255_u8, ColorSpace::RGB, 100, 100);
+///
+/// Letterbox::new(LetterboxSize::AspectRatio(16, 9), LetterboxFill::Color([0.0; 4]))
+///     .execute(&mut image)
+///     .unwrap();
+///
+/// assert_eq!(image.dimensions(), (178, 100));
+/// ```
+pub struct Letterbox {
+    size:    LetterboxSize,
+    fill:    LetterboxFill,
+    gravity: Gravity
+}
+
+impl Letterbox {
+    /// Create a new letterbox operation
+    ///
+    /// # Arguments
+    /// - size: The canvas size to expand to
+    /// - fill: How to fill the area around the image
+    ///
+    /// The image is centered, this can be changed via [set_gravity](Self::set_gravity)
+    #[must_use]
+    pub fn new(size: LetterboxSize, fill: LetterboxFill) -> Letterbox {
+        Letterbox {
+            size,
+            fill,
+            gravity: Gravity::Center
+        }
+    }
+    /// Set where the image is placed on the canvas
+    #[must_use]
+    pub fn set_gravity(mut self, gravity: Gravity) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    /// Compute the canvas dimensions for an image of `width` and `height`
+    fn canvas_dimensions(
+        &self, width: usize, height: usize
+    ) -> Result<(usize, usize), ImageErrors> {
+        match self.size {
+            LetterboxSize::Exact(w, h) => {
+                if w < width || h < height {
+                    return Err(ImageErrors::GenericString(format!(
+                        "Letterbox dimensions ({w},{h}) are smaller than the image dimensions ({width},{height})"
+                    )));
+                }
+                Ok((w, h))
+            }
+            LetterboxSize::AspectRatio(rw, rh) => {
+                if rw == 0 || rh == 0 {
+                    return Err(ImageErrors::GenericStr(
+                        "Letterbox aspect ratio components must be non-zero"
+                    ));
+                }
+                // compare width/height against rw/rh without floating point
+                if width * rh < height * rw {
+                    // too narrow, add bars left and right
+                    Ok(((height * rw).div_ceil(rh), height))
+                } else {
+                    // too wide (or exact), add bars top and bottom
+                    Ok((width, (width * rh).div_ceil(rw)))
+                }
+            }
+        }
+    }
+
+    /// Create the canvas the image will be placed on
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn background(&self, image: &Image, width: usize, height: usize) -> Result<Image, ImageErrors> {
+        let colorspace = image.colorspace();
+        let depth = image.depth();
+
+        match self.fill {
+            LetterboxFill::Color(color) => {
+                let frames = image
+                    .frames_ref()
+                    .iter()
+                    .map(|frame| {
+                        (0..colorspace.num_components())
+                            .map(|i| {
+                                constant_channel(
+                                    width * height,
+                                    depth.bit_type(),
+                                    color[i],
+                                    "Letterbox"
+                                )
+                            })
+                            .collect::<Result<Vec<_>, _>>()
+                            .map(|channels| frame.with_channels(channels))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok(Image::new_frames(frames, depth, width, height, colorspace))
+            }
+            LetterboxFill::Blur(sigma) => {
+                let (img_w, img_h) = image.dimensions();
+                let mut background = image.clone();
+
+                // scale to cover the canvas, preserving aspect ratio
+                let scale = (width as f64 / img_w as f64).max(height as f64 / img_h as f64);
+                let cover_w = ((img_w as f64 * scale).ceil() as usize).max(width);
+                let cover_h = ((img_h as f64 * scale).ceil() as usize).max(height);
+
+                Resize::new(cover_w, cover_h, ResizeMethod::Bilinear).execute(&mut background)?;
+                Crop::new(width, height, (cover_w - width) / 2, (cover_h - height) / 2)
+                    .execute(&mut background)?;
+                GaussianBlur::new(sigma).execute(&mut background)?;
+
+                Ok(background)
+            }
+        }
+    }
+}
+
+impl OperationsTrait for Letterbox {
+    fn name(&self) -> &'static str {
+        "Letterbox"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let (canvas_w, canvas_h) = self.canvas_dimensions(width, height)?;
+
+        if (canvas_w, canvas_h) == (width, height) || width == 0 || height == 0 {
+            return Ok(());
+        }
+        let mut background = self.background(image, canvas_w, canvas_h)?;
+
+        let (x, y) = calculate_gravity(image, &background, self.gravity);
+        let colorspace = image.colorspace();
+        let depth = image.depth().bit_type();
+
+        for (frame, bg_frame) in image.frames_mut().iter_mut().zip(background.frames_mut()) {
+            for (src, dst) in frame
+                .channels_ref(colorspace, false)
+                .iter()
+                .zip(bg_frame.channels_mut(colorspace, false))
+            {
+                match depth {
+                    BitType::U8 => paste::<u8>(
+                        src.reinterpret_as()?,
+                        width,
+                        dst.reinterpret_as_mut()?,
+                        canvas_w,
+                        (x, y)
+                    ),
+                    BitType::U16 => paste::<u16>(
+                        src.reinterpret_as()?,
+                        width,
+                        dst.reinterpret_as_mut()?,
+                        canvas_w,
+                        (x, y)
+                    ),
+                    BitType::F32 => paste::<f32>(
+                        src.reinterpret_as()?,
+                        width,
+                        dst.reinterpret_as_mut()?,
+                        canvas_w,
+                        (x, y)
+                    ),
+                    d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+                }
+            }
+            core::mem::swap(frame.channels_vec(), bg_frame.channels_vec());
+        }
+        image.set_dimensions(canvas_w, canvas_h);
+
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Copy `src` into `dst` with its top left corner at `origin`, `src` must fit in `dst`
+fn paste<T: Copy>(
+    src: &[T], src_width: usize, dst: &mut [T], dst_width: usize, origin: (usize, usize)
+) {
+    for (src_row, dst_row) in src
+        .chunks_exact(src_width)
+        .zip(dst.chunks_exact_mut(dst_width).skip(origin.1))
+    {
+        dst_row[origin.0..origin.0 + src_width].copy_from_slice(src_row);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::letterbox::{Letterbox, LetterboxFill, LetterboxSize};
+    use crate::utils::Gravity;
+
+    #[test]
+    fn test_letterbox_color_and_gravity() {
+        let mut image = Image::fill(200_u8, ColorSpace::LumaA, 4, 2);
+
+        Letterbox::new(
+            LetterboxSize::AspectRatio(1, 1),
+            LetterboxFill::Color([10.0; 4])
+        )
+        .set_gravity(Gravity::BottomLeft)
+        .execute(&mut image)
+        .unwrap();
+
+        assert_eq!(image.dimensions(), (4, 4));
+        let luma = image.channels_ref(false)[0].reinterpret_as::<u8>().unwrap();
+        assert_eq!(&luma[..8], &[10; 8]);
+        assert_eq!(&luma[8..], &[200; 8]);
+    }
+
+    #[test]
+    fn test_letterbox_blur_exact() {
+        let mut image = Image::fill(1000_u16, ColorSpace::RGB, 30, 20);
+
+        Letterbox::new(LetterboxSize::Exact(50, 20), LetterboxFill::Blur(2.0))
+            .execute(&mut image)
+            .unwrap();
+        assert_eq!(image.dimensions(), (50, 20));
+
+        // the background is a blurred copy of a constant image
+        let red = image.channels_ref(false)[0]
+            .reinterpret_as::<u16>()
+            .unwrap();
+        assert!(red.iter().all(|x| x.abs_diff(1000) <= 1));
+
+        let result = Letterbox::new(LetterboxSize::Exact(10, 10), LetterboxFill::Blur(2.0))
+            .execute(&mut image);
+        assert!(result.is_err());
+    }
+}
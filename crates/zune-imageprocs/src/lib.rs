@@ -25,9 +25,15 @@
 //! // execute the filter
 //! exposure.execute(&mut image).unwrap();
 //! ```
+//!
+//! # `no_std`
+//! Without the default `std` feature the crate is `#[no_std]` and needs only `alloc`, the
+//! `libm` feature has to be enabled instead for float math. Threads, runtime simd detection
+//! and the operations storing their results for later, e.g. `contours`, need `std`.
 
 // Benchmark support needs sse
 #![cfg_attr(feature = "benchmarks", feature(test))]
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(
     clippy::correctness,
     clippy::perf,
@@ -48,40 +54,81 @@
     clippy::wildcard_imports
 )]
 
+extern crate alloc;
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("zune-imageprocs needs the `std` feature or the `libm` feature for float math");
+
 pub use zune_image;
 
+/// A color taken by operations that fill or draw, in the range of the image depth
+///
+/// Values are in the range of the image depth, i.e 0..255 for 8 bit images,
+/// 0..65535 for 16 bit images and 0..1 for float images.
+///
+/// Only the first `colorspace.num_components()` values are used
+pub type DepthColor = [f32; zune_image::image::MAX_CHANNELS];
+
 pub mod auto_orient;
 pub mod bilateral_filter;
 pub mod blend;
 pub mod box_blur;
 pub mod brighten;
+pub mod chromatic_adapt;
+pub mod color_blindness;
 pub mod color_matrix;
 pub mod composite;
+#[cfg(feature = "std")]
+pub mod connected_components;
+#[cfg(feature = "std")]
+pub mod contours;
 pub mod contrast;
 pub mod convolve;
 pub mod crop;
+pub mod demosaic;
 pub mod exposure;
+pub mod fft;
 pub mod flip;
+pub mod flood_fill;
+mod font;
 pub mod gamma;
+pub mod gamut_map;
 pub mod gaussian_blur;
+pub mod gradient_map;
 pub mod histogram;
 pub mod hsv_adjust;
+pub mod hsv_range;
+#[cfg(feature = "std")]
+pub mod icc_convert;
+#[cfg(feature = "std")]
+pub mod integral_image;
 pub mod invert;
+pub mod letterbox;
+pub mod linearize;
 pub mod mathops;
 pub mod median;
+pub mod metrics;
 pub mod mirror;
+pub mod montage;
 pub mod pad;
+pub mod photo_filter;
 pub mod premul_alpha;
 mod prewitt;
+pub mod pyramid;
+#[cfg(feature = "serde-support")]
+pub mod recipe;
 pub mod resize;
 pub mod rotate;
 pub mod scharr;
+pub mod shear;
 pub mod sobel;
 pub mod spatial;
 pub mod spatial_ops;
 pub mod stretch_contrast;
 pub mod threshold;
+pub mod tone_map;
 pub mod traits;
 pub mod transpose;
 pub mod unsharpen;
 mod utils;
+pub mod watermark;
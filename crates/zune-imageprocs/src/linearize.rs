@@ -0,0 +1,406 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Convert images between gamma encoded sRGB and linear light
+//!
+//! Most images store sRGB values, which are gamma encoded so that more values represent dark tones.
+//! Operations that mix pixels, e.g. resizing and blurring, should be done on linear values,
+//! mixing gamma encoded values darkens edges and fine details.
+//!
+//! The transfer characteristics of an image are tracked by [`ImageMetadata::color_trc`],
+//! images without one are assumed to be sRGB.
+//!
+//! HDR images encoded with the [PQ](ColorCharacteristics::PQ) or [HLG](ColorCharacteristics::HLG)
+//! transfer functions are linearized with those functions. Linear PQ values are display light
+//! where 1.0 is 10000 nits, linear HLG values are scene light in 0..1, see
+//! [`ToneMap`](crate::tone_map::ToneMap) to convert them for SDR displays.
+//!
+//! # Implementation details
+//! - For `u8` and `u16` , we use lookup tables to improve speed
+//! - For `f32` naive execution is used
+//! - Alpha channels are not modified
+//!
+//! Linear values need more precision than gamma encoded ones, linearizing a `u8` image
+//! loses dark tones, convert the image to `f32` first if the image will be delinearized later.
+//!
+//! [`ImageMetadata::color_trc`]: zune_image::metadata::ImageMetadata::color_trc
+use alloc::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use zune_core::bit_depth::{BitDepth, BitType};
+use zune_core::colorspace::{ColorCharacteristics, ColorSpace};
+use zune_image::channel::Channel;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::traits::NumOps;
+use crate::utils::execute_on;
+
+/// Colorspaces whose channels are sRGB encoded
+const SRGB_COLORSPACES: &[ColorSpace] = &[
+    ColorSpace::RGB,
+    ColorSpace::RGBA,
+    ColorSpace::BGR,
+    ColorSpace::BGRA,
+    ColorSpace::ARGB,
+    ColorSpace::Luma,
+    ColorSpace::LumaA
+];
+
+/// Convert a gamma encoded image to linear light
+///
+/// The transfer function is chosen by the transfer characteristics of the image.
+/// Does nothing if the image is already linear
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::{ColorCharacteristics, ColorSpace};
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::linearize::Linearize;
+///
+/// let mut image = Image::fill(0.5_f32, ColorSpace::RGB, 10, 10);
+/// Linearize::new().execute(&mut image).unwrap();
+///
+/// assert_eq!(image.metadata().color_trc(), Some(ColorCharacteristics::Linear));
+/// ```
+#[derive(Default)]
+pub struct Linearize;
+
+impl Linearize {
+    /// Create a new linearize operation
+    #[must_use]
+    pub fn new() -> Linearize {
+        Linearize
+    }
+}
+
+/// Convert a linear light image to gamma encoded sRGB, or another transfer
+/// function set by [`set_transfer`](Delinearize::set_transfer)
+///
+/// Does nothing if the image is already gamma encoded
+pub struct Delinearize {
+    transfer: ColorCharacteristics
+}
+
+impl Default for Delinearize {
+    fn default() -> Self {
+        Delinearize::new()
+    }
+}
+
+impl Delinearize {
+    /// Create a new delinearize operation encoding to sRGB
+    #[must_use]
+    pub fn new() -> Delinearize {
+        Delinearize {
+            transfer: ColorCharacteristics::sRGB
+        }
+    }
+    /// Set the transfer function to encode with, the default is sRGB
+    ///
+    /// Setting [`ColorCharacteristics::Linear`] makes the operation a no-op
+    #[must_use]
+    pub fn set_transfer(mut self, transfer: ColorCharacteristics) -> Self {
+        self.transfer = transfer;
+        self
+    }
+}
+
+impl OperationsTrait for Linearize {
+    fn name(&self) -> &'static str {
+        "Linearize"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        if is_linear(image) {
+            return Ok(());
+        }
+        let function = match image.metadata().color_trc() {
+            Some(ColorCharacteristics::PQ) => pq_to_linear,
+            Some(ColorCharacteristics::HLG) => hlg_to_linear,
+            _ => srgb_to_linear
+        };
+        apply_transfer(self.name(), image, function)?;
+        image
+            .metadata_mut()
+            .set_color_trc(ColorCharacteristics::Linear);
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        SRGB_COLORSPACES
+    }
+}
+
+impl OperationsTrait for Delinearize {
+    fn name(&self) -> &'static str {
+        "Delinearize"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        if !is_linear(image) {
+            return Ok(());
+        }
+        let function = match self.transfer {
+            ColorCharacteristics::Linear => return Ok(()),
+            ColorCharacteristics::sRGB => linear_to_srgb,
+            ColorCharacteristics::PQ => linear_to_pq,
+            ColorCharacteristics::HLG => linear_to_hlg
+        };
+        apply_transfer(self.name(), image, function)?;
+        image.metadata_mut().set_color_trc(self.transfer);
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        SRGB_COLORSPACES
+    }
+}
+
+fn is_linear(image: &Image) -> bool {
+    image.metadata().color_trc() == Some(ColorCharacteristics::Linear)
+}
+
+fn apply_transfer(
+    name: &'static str, image: &mut Image, function: fn(f32) -> f32
+) -> Result<(), ImageErrors> {
+    let depth = image.depth().bit_type();
+
+    let transfer_fn = |channel: &mut Channel| -> Result<(), ImageErrors> {
+        match depth {
+            BitType::U8 => transfer_lut(channel.reinterpret_as_mut::<u8>()?, function),
+            BitType::U16 => transfer_lut(channel.reinterpret_as_mut::<u16>()?, function),
+            BitType::F32 => channel
+                .reinterpret_as_mut::<f32>()?
+                .iter_mut()
+                .for_each(|x| *x = function(x.clamp(0.0, 1.0))),
+            d => return Err(ImageErrors::ImageOperationNotImplemented(name, d))
+        }
+        Ok(())
+    };
+    execute_on(transfer_fn, image, true)
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn transfer_lut<T: Copy + NumOps<T> + Default>(pixels: &mut [T], function: fn(f32) -> f32) {
+    let max = T::MAX_VAL.to_usize();
+    let max_f32 = max as f32;
+
+    let lut: Vec<T> = (0..=max)
+        .map(|x| T::from_f32((function(x as f32 / max_f32) * max_f32).round()))
+        .collect();
+
+    for px in pixels {
+        *px = lut[(*px).to_usize().min(max)];
+    }
+}
+
+/// Convert a gamma encoded sRGB value in 0..1 to linear light
+#[inline]
+#[must_use]
+pub fn srgb_to_linear(x: f32) -> f32 {
+    if x <= 0.040_45 {
+        x / 12.92
+    } else {
+        ((x + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Convert a linear light value in 0..1 to gamma encoded sRGB
+#[inline]
+#[must_use]
+pub fn linear_to_srgb(x: f32) -> f32 {
+    if x <= 0.003_130_8 {
+        x * 12.92
+    } else {
+        1.055 * x.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+const PQ_M1: f32 = 2610.0 / 16384.0;
+const PQ_M2: f32 = 2523.0 / 4096.0 * 128.0;
+const PQ_C1: f32 = 3424.0 / 4096.0;
+const PQ_C2: f32 = 2413.0 / 4096.0 * 32.0;
+const PQ_C3: f32 = 2392.0 / 4096.0 * 32.0;
+
+/// Convert a PQ encoded value in 0..1 to linear display light, where 1.0 is 10000 nits
+#[inline]
+#[must_use]
+pub fn pq_to_linear(x: f32) -> f32 {
+    let p = x.powf(1.0 / PQ_M2);
+
+    ((p - PQ_C1).max(0.0) / (PQ_C2 - PQ_C3 * p)).powf(1.0 / PQ_M1)
+}
+
+/// Convert linear display light in 0..1, where 1.0 is 10000 nits, to PQ
+#[inline]
+#[must_use]
+pub fn linear_to_pq(x: f32) -> f32 {
+    let y = x.powf(PQ_M1);
+
+    ((PQ_C1 + PQ_C2 * y) / (1.0 + PQ_C3 * y)).powf(PQ_M2)
+}
+
+const HLG_A: f32 = 0.178_832_77;
+const HLG_B: f32 = 0.284_668_92;
+const HLG_C: f32 = 0.559_910_7;
+
+/// Convert an HLG encoded value in 0..1 to linear scene light in 0..1
+#[inline]
+#[must_use]
+pub fn hlg_to_linear(x: f32) -> f32 {
+    if x <= 0.5 {
+        x * x / 3.0
+    } else {
+        (((x - HLG_C) / HLG_A).exp() + HLG_B) / 12.0
+    }
+}
+
+/// Convert linear scene light in 0..1 to HLG
+#[inline]
+#[must_use]
+pub fn linear_to_hlg(x: f32) -> f32 {
+    if x <= 1.0 / 12.0 {
+        (3.0 * x).sqrt()
+    } else {
+        HLG_A * (12.0 * x - HLG_B).ln() + HLG_C
+    }
+}
+
+/// Run `operation` on a linear light copy of the image
+///
+/// Gamma encoded images are converted to `f32` and linearized before running the
+/// operation and restored to their depth and transfer function afterwards. Images which are
+/// already linear, or are in a colorspace that isn't sRGB encoded, are passed as is.
+pub(crate) fn execute_in_linear_light(
+    image: &mut Image, operation: impl FnOnce(&mut Image) -> Result<(), ImageErrors>
+) -> Result<(), ImageErrors> {
+    if is_linear(image) || !SRGB_COLORSPACES.contains(&image.colorspace()) {
+        return operation(image);
+    }
+    let depth = image.depth();
+    let transfer = image
+        .metadata()
+        .color_trc()
+        .unwrap_or(ColorCharacteristics::sRGB);
+
+    if depth != BitDepth::Float32 {
+        image.convert_depth(BitDepth::Float32)?;
+    }
+    Linearize::new().execute_impl(image)?;
+    operation(image)?;
+    Delinearize::new()
+        .set_transfer(transfer)
+        .execute_impl(image)?;
+
+    if depth != BitDepth::Float32 {
+        image.convert_depth(depth)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::{ColorCharacteristics, ColorSpace};
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::linearize::{
+        hlg_to_linear, linear_to_hlg, linear_to_pq, pq_to_linear, Delinearize, Linearize
+    };
+    use crate::resize::{Resize, ResizeMethod};
+
+    #[test]
+    fn test_hdr_transfer_functions() {
+        // 100 nits is about 0.508 in PQ, the HLG knee is at 0.5
+        assert!((linear_to_pq(0.01) - 0.508).abs() < 1e-3);
+        assert!((linear_to_hlg(1.0 / 12.0) - 0.5).abs() < 1e-5);
+        assert!((linear_to_hlg(1.0) - 1.0).abs() < 1e-5);
+
+        for i in 0..=100_u8 {
+            let x = f32::from(i) / 100.0;
+            assert!((pq_to_linear(linear_to_pq(x)) - x).abs() < 1e-4, "{x}");
+            assert!((hlg_to_linear(linear_to_hlg(x)) - x).abs() < 1e-4, "{x}");
+        }
+    }
+
+    #[test]
+    fn test_pq_round_trip() {
+        let mut image = Image::fill(0.5_f32, ColorSpace::RGB, 4, 4);
+        image.metadata_mut().set_color_trc(ColorCharacteristics::PQ);
+
+        Linearize::new().execute(&mut image).unwrap();
+        let linear = image.flatten_frames::<f32>()[0][0];
+        // PQ 0.5 is about 92 nits
+        assert!((linear - 0.009_2).abs() < 2e-4, "{linear}");
+
+        Delinearize::new()
+            .set_transfer(ColorCharacteristics::PQ)
+            .execute(&mut image)
+            .unwrap();
+        assert_eq!(image.metadata().color_trc(), Some(ColorCharacteristics::PQ));
+        assert!((image.flatten_frames::<f32>()[0][0] - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_round_trip_u16() {
+        let pixels: Vec<u16> = (0..300).map(|x| x * 200).collect();
+        let mut image = Image::from_u16(&pixels, 10, 10, ColorSpace::RGB);
+
+        Linearize::new().execute(&mut image).unwrap();
+        assert_eq!(
+            image.metadata().color_trc(),
+            Some(ColorCharacteristics::Linear)
+        );
+        // mid gray is darker in linear light
+        assert!(image.flatten_frames::<u16>()[0][150] < 30000);
+
+        Delinearize::new().execute(&mut image).unwrap();
+        assert_eq!(
+            image.metadata().color_trc(),
+            Some(ColorCharacteristics::sRGB)
+        );
+
+        // one linear step near black is about 13 gamma encoded steps
+        for (a, e) in image.flatten_frames::<u16>()[0].iter().zip(&pixels) {
+            assert!(a.abs_diff(*e) <= 13, "{a} {e}");
+        }
+    }
+
+    #[test]
+    fn test_linear_light_resize_keeps_brightness() {
+        // a black and white checkerboard averages to about 188 in linear light, not 128
+        let pixels: Vec<u8> = (0..64)
+            .map(|i| if (i % 8 + i / 8) % 2 == 0 { 255 } else { 0 })
+            .collect();
+        let mut image = Image::from_u8(&pixels, 8, 8, ColorSpace::Luma);
+
+        Resize::new(4, 4, ResizeMethod::Bilinear)
+            .set_linear_light(true)
+            .execute(&mut image)
+            .unwrap();
+
+        assert!(image.metadata().color_trc() != Some(ColorCharacteristics::Linear));
+        let mean = image.flatten_frames::<u8>()[0]
+            .iter()
+            .map(|x| u32::from(*x))
+            .sum::<u32>()
+            / 16;
+        assert!(mean > 160, "{mean}");
+    }
+}
@@ -38,14 +38,16 @@
 //!
 //!
 
+use alloc::vec;
+
 use zune_core::bit_depth::BitType;
 use zune_image::channel::Channel;
 use zune_image::errors::ImageErrors;
 use zune_image::image::Image;
-use zune_image::traits::OperationsTrait;
+use zune_image::traits::{OperationsTrait, Parameter, ParameterKind, ParameterValue};
 
 use crate::pad::{pad, PadMethod};
-use crate::utils::{execute_on, z_prefetch};
+use crate::utils::{execute_on, for_each_padded_row_band, z_prefetch};
 
 /// Median returns a new image in which each pixel is the median of its neighbors.
 ///
@@ -53,14 +55,25 @@ use crate::utils::{execute_on, z_prefetch};
 ///
 /// for example a radius of R will result in a search window length of 2R+1 for each dimension.
 #[derive(Default)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Median {
-    radius: usize
+    radius:     usize,
+    pad_method: PadMethod
 }
 
 impl Median {
     #[must_use]
     pub fn new(radius: usize) -> Median {
-        Median { radius }
+        Median {
+            radius,
+            pad_method: PadMethod::Replicate
+        }
+    }
+    /// Set how pixels outside the image are computed, defaults to [PadMethod::Replicate]
+    #[must_use]
+    pub fn set_pad_method(mut self, pad_method: PadMethod) -> Self {
+        self.pad_method = pad_method;
+        self
     }
 }
 
@@ -69,6 +82,15 @@ impl OperationsTrait for Median {
         "Median Filter"
     }
 
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            name:        "radius",
+            description: "Radius of the neighbourhood",
+            kind:        ParameterKind::Integer { min: 0, max: 100 },
+            default:     ParameterValue::Integer(1)
+        }]
+    }
+
     fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
         let (width, height) = image.dimensions();
 
@@ -81,19 +103,21 @@ impl OperationsTrait for Median {
             let mut new_channel = Channel::new_with_bit_type(channel.len(), depth.bit_type());
 
             match depth.bit_type() {
-                BitType::U16 => median_u16(
+                BitType::U16 => median_u16_with_border(
                     channel.reinterpret_as::<u16>()?,
                     new_channel.reinterpret_as_mut::<u16>()?,
                     self.radius,
                     width,
-                    height
+                    height,
+                    self.pad_method
                 ),
-                BitType::U8 => median_u8(
+                BitType::U8 => median_u8_with_border(
                     channel.reinterpret_as::<u8>()?,
                     new_channel.reinterpret_as_mut::<u8>()?,
                     self.radius,
                     width,
-                    height
+                    height,
+                    self.pad_method
                 ),
                 d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
             }
@@ -107,9 +131,36 @@ impl OperationsTrait for Median {
         &[BitType::U8, BitType::U16]
     }
 }
-#[allow(clippy::cast_possible_truncation)]
 pub fn median_u16(
     in_channel: &[u16], out_channel: &mut [u16], radius: usize, width: usize, height: usize
+) {
+    median_u16_with_border(in_channel, out_channel, radius, width, height, PadMethod::Replicate)
+}
+
+/// [`median_u16`] with pixels outside the image computed by `pad_method`
+pub fn median_u16_with_border(
+    in_channel: &[u16], out_channel: &mut [u16], radius: usize, width: usize, height: usize,
+    pad_method: PadMethod
+) {
+    // pad input
+    let padded_input = pad(in_channel, width, height, radius, radius, pad_method);
+    // every band of rows gets its own histogram
+    for_each_padded_row_band(
+        &padded_input,
+        out_channel,
+        width,
+        height,
+        radius,
+        |in_band, out_band, band_height| {
+            median_band_u16(in_band, out_band, radius, width, band_height);
+        }
+    );
+}
+
+/// Median of a band of rows, `in_channel` is padded
+#[allow(clippy::cast_possible_truncation)]
+fn median_band_u16(
+    in_channel: &[u16], out_channel: &mut [u16], radius: usize, width: usize, height: usize
 ) {
     /*
      * Okay rico, we run a tight shift here
@@ -188,19 +239,37 @@ pub fn median_u16(
         median
     };
 
+    spatial_median(in_channel, out_channel, radius, width, height, func);
+}
+pub fn median_u8(
+    in_channel: &[u8], out_channel: &mut [u8], radius: usize, width: usize, height: usize
+) {
+    median_u8_with_border(in_channel, out_channel, radius, width, height, PadMethod::Replicate)
+}
+
+/// [`median_u8`] with pixels outside the image computed by `pad_method`
+pub fn median_u8_with_border(
+    in_channel: &[u8], out_channel: &mut [u8], radius: usize, width: usize, height: usize,
+    pad_method: PadMethod
+) {
     // pad input
-    let padded_input = pad(
-        in_channel,
+    let padded_input = pad(in_channel, width, height, radius, radius, pad_method);
+    // every band of rows gets its own histogram
+    for_each_padded_row_band(
+        &padded_input,
+        out_channel,
         width,
         height,
         radius,
-        radius,
-        PadMethod::Replicate
+        |in_band, out_band, band_height| {
+            median_band_u8(in_band, out_band, radius, width, band_height);
+        }
     );
-    spatial_median(&padded_input, out_channel, radius, width, height, func);
 }
+
+/// Median of a band of rows, `in_channel` is padded
 #[allow(clippy::cast_possible_truncation)]
-pub fn median_u8(
+fn median_band_u8(
     in_channel: &[u8], out_channel: &mut [u8], radius: usize, width: usize, height: usize
 ) {
     // duplicated from above, but uses array instead of vec, and
@@ -270,16 +339,7 @@ pub fn median_u8(
         median
     };
 
-    // pad input
-    let padded_input = pad(
-        in_channel,
-        width,
-        height,
-        radius,
-        radius,
-        PadMethod::Replicate
-    );
-    spatial_median(&padded_input, out_channel, radius, width, height, func);
+    spatial_median(in_channel, out_channel, radius, width, height, func);
 }
 
 pub fn spatial_median<T, F>(
@@ -325,3 +385,28 @@ pub fn spatial_median<T, F>(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nanorand::Rng;
+
+    use crate::median::{median_band_u8, median_u8};
+    use crate::pad::{pad, PadMethod};
+
+    // bands of rows can run on different threads, each with its own histogram
+    #[test]
+    fn test_median_bands_match_whole_image() {
+        let (width, height, radius) = (37, 101, 2);
+        let mut data = vec![0_u8; width * height];
+        nanorand::WyRand::new().fill(&mut data);
+
+        let mut banded = vec![0; width * height];
+        median_u8(&data, &mut banded, radius, width, height);
+
+        let padded = pad(&data, width, height, radius, radius, PadMethod::Replicate);
+        let mut whole = vec![0; width * height];
+        median_band_u8(&padded, &mut whole, radius, width, height);
+
+        assert_eq!(banded, whole);
+    }
+}
@@ -0,0 +1,400 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Image comparison metrics
+//!
+//! This contains full reference quality metrics, useful for measuring how
+//! much a lossy codec or an image operation changed an image.
+//!
+//! - [PSNR](psnr): Peak signal to noise ratio, in decibels
+//! - [SSIM](ssim): Structural similarity index, from Wang et al. 2004
+//! - [MS-SSIM](ms_ssim): Multi-scale structural similarity, from Wang et al. 2003
+//!
+//! # Harmonization
+//! Images are compared in the colorspace of the reference image, so the distorted image
+//! is converted to it if they differ.
+//! Both images are then converted to [BitDepth::Float32] so images of different bit depths can
+//! be compared, and scores do not depend on the bit depth.
+//!
+//! Only the first frame of each image is compared.
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use zune_core::bit_depth::BitDepth;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+
+/// Scores for a comparison between two images
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetricScore {
+    /// Score for each channel, in the order of the reference image's colorspace
+    pub channels: Vec<f64>,
+    /// Score for the whole image
+    pub global:   f64
+}
+
+const SSIM_K1: f64 = 0.01;
+const SSIM_K2: f64 = 0.03;
+const SSIM_SIGMA: f64 = 1.5;
+const SSIM_RADIUS: usize = 5;
+
+/// Weights for each scale of MS-SSIM, from the original paper
+const MS_SSIM_WEIGHTS: [f64; 5] = [0.0448, 0.2856, 0.3001, 0.2363, 0.1333];
+
+/// Convert both images into comparable channels of f32 pixels
+///
+/// Returns the channels of each image and the dimensions
+#[allow(clippy::type_complexity)]
+fn harmonize(
+    reference: &Image, distorted: &Image
+) -> Result<(Vec<Vec<f32>>, Vec<Vec<f32>>, usize, usize), ImageErrors> {
+    if reference.dimensions() != distorted.dimensions() {
+        return Err(ImageErrors::GenericString(format!(
+            "Image dimensions do not match, reference is {:?} but distorted is {:?}",
+            reference.dimensions(),
+            distorted.dimensions()
+        )));
+    }
+    let (width, height) = reference.dimensions();
+
+    let to_f32 = |image: &Image| -> Result<Vec<Vec<f32>>, ImageErrors> {
+        let mut image = image.clone();
+
+        if image.colorspace() != reference.colorspace() {
+            image.convert_color(reference.colorspace())?;
+        }
+        image.convert_depth(BitDepth::Float32)?;
+
+        let colorspace = image.colorspace();
+        let frame = image
+            .frames_ref()
+            .first()
+            .ok_or(ImageErrors::NoImageForOperations)?;
+
+        frame
+            .channels_ref(colorspace, false)
+            .iter()
+            .map(|c| Ok(c.reinterpret_as::<f32>()?.to_vec()))
+            .collect()
+    };
+
+    Ok((to_f32(reference)?, to_f32(distorted)?, width, height))
+}
+
+/// Compute the peak signal to noise ratio between two images
+///
+/// Higher is better. Identical images (or channels) have a score of [f64::INFINITY].
+///
+/// The global score is computed from the mean squared error of all channels
+///
+/// # Arguments
+/// - reference: The original image
+/// - distorted: The image to compare against the original
+///
+/// # Errors
+/// If the image dimensions differ or the images cannot be converted to a common colorspace
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_imageprocs::metrics::psnr;
+///
+/// let a = Image::fill(100_u8, ColorSpace::RGB, 10, 10);
+/// let b = Image::fill(101_u8, ColorSpace::RGB, 10, 10);
+///
+/// let score = psnr(&a, &b).unwrap();
+/// assert!((score.global - 48.13).abs() < 0.01);
+/// ```
+#[allow(clippy::cast_precision_loss)]
+pub fn psnr(reference: &Image, distorted: &Image) -> Result<MetricScore, ImageErrors> {
+    let (ref_channels, dist_channels, _, _) = harmonize(reference, distorted)?;
+
+    let to_psnr = |mse: f64| -> f64 {
+        if mse == 0.0 {
+            f64::INFINITY
+        } else {
+            // peak value is 1.0 for float images
+            -10.0 * mse.log10()
+        }
+    };
+    let errors: Vec<f64> = ref_channels
+        .iter()
+        .zip(&dist_channels)
+        .map(|(a, b)| {
+            let sum: f64 = a
+                .iter()
+                .zip(b)
+                .map(|(x, y)| (f64::from(*x) - f64::from(*y)).powi(2))
+                .sum();
+            sum / (a.len().max(1) as f64)
+        })
+        .collect();
+
+    let mean_error = errors.iter().sum::<f64>() / (errors.len().max(1) as f64);
+
+    Ok(MetricScore {
+        channels: errors.into_iter().map(to_psnr).collect(),
+        global:   to_psnr(mean_error)
+    })
+}
+
+/// Compute the structural similarity index between two images
+///
+/// Scores range from -1 to 1, 1 meaning the images are identical.
+///
+/// This uses an 11x11 gaussian window with a sigma of 1.5 as in the original paper,
+/// image borders are handled by replicating edge pixels.
+/// The global score is the mean of the channel scores.
+///
+/// # Arguments
+/// - reference: The original image
+/// - distorted: The image to compare against the original
+///
+/// # Errors
+/// If the image dimensions differ or the images cannot be converted to a common colorspace
+#[allow(clippy::cast_precision_loss)]
+pub fn ssim(reference: &Image, distorted: &Image) -> Result<MetricScore, ImageErrors> {
+    let (ref_channels, dist_channels, width, height) = harmonize(reference, distorted)?;
+
+    let channels: Vec<f64> = ref_channels
+        .iter()
+        .zip(&dist_channels)
+        .map(|(a, b)| {
+            let a: Vec<f64> = a.iter().map(|x| f64::from(*x)).collect();
+            let b: Vec<f64> = b.iter().map(|x| f64::from(*x)).collect();
+            ssim_channel(&a, &b, width, height).0
+        })
+        .collect();
+
+    let global = channels.iter().sum::<f64>() / (channels.len().max(1) as f64);
+
+    Ok(MetricScore { channels, global })
+}
+
+/// Compute the multi-scale structural similarity index between two images
+///
+/// Scores range from 0 to 1, 1 meaning the images are identical.
+///
+/// Five scales are used as in the original paper, but images too small to be
+/// downsampled five times use fewer scales with the weights re-normalized.
+/// The global score is the mean of the channel scores.
+///
+/// # Arguments
+/// - reference: The original image
+/// - distorted: The image to compare against the original
+///
+/// # Errors
+/// If the image dimensions differ or the images cannot be converted to a common colorspace
+#[allow(clippy::cast_precision_loss)]
+pub fn ms_ssim(reference: &Image, distorted: &Image) -> Result<MetricScore, ImageErrors> {
+    let (ref_channels, dist_channels, width, height) = harmonize(reference, distorted)?;
+
+    // use as many scales as the image allows, the smallest scale
+    // should still fit the gaussian window
+    let window = 2 * SSIM_RADIUS + 1;
+    let mut scales = 1;
+
+    while scales < MS_SSIM_WEIGHTS.len() && (width.min(height) >> scales) >= window {
+        scales += 1;
+    }
+    let weights = &MS_SSIM_WEIGHTS[..scales];
+    let weight_sum: f64 = weights.iter().sum();
+
+    let channels: Vec<f64> = ref_channels
+        .iter()
+        .zip(&dist_channels)
+        .map(|(a, b)| {
+            let mut a: Vec<f64> = a.iter().map(|x| f64::from(*x)).collect();
+            let mut b: Vec<f64> = b.iter().map(|x| f64::from(*x)).collect();
+            let (mut w, mut h) = (width, height);
+
+            let mut score = 1.0;
+
+            for (i, weight) in weights.iter().enumerate() {
+                let (ssim, cs) = ssim_channel(&a, &b, w, h);
+                let weight = weight / weight_sum;
+
+                if i + 1 == weights.len() {
+                    score *= ssim.max(0.0).powf(weight);
+                } else {
+                    score *= cs.max(0.0).powf(weight);
+
+                    a = downsample(&a, w, h);
+                    b = downsample(&b, w, h);
+                    w /= 2;
+                    h /= 2;
+                }
+            }
+            score
+        })
+        .collect();
+
+    let global = channels.iter().sum::<f64>() / (channels.len().max(1) as f64);
+
+    Ok(MetricScore { channels, global })
+}
+
+/// Compute mean ssim and mean contrast-structure for a single channel
+#[allow(clippy::cast_precision_loss)]
+fn ssim_channel(a: &[f64], b: &[f64], width: usize, height: usize) -> (f64, f64) {
+    let c1 = SSIM_K1 * SSIM_K1;
+    let c2 = SSIM_K2 * SSIM_K2;
+
+    let kernel = gaussian_kernel();
+
+    let blur = |data: &[f64]| gaussian_filter(data, width, height, &kernel);
+
+    let aa: Vec<f64> = a.iter().map(|x| x * x).collect();
+    let bb: Vec<f64> = b.iter().map(|x| x * x).collect();
+    let ab: Vec<f64> = a.iter().zip(b).map(|(x, y)| x * y).collect();
+
+    let mu_a = blur(a);
+    let mu_b = blur(b);
+    let sigma_aa = blur(&aa);
+    let sigma_bb = blur(&bb);
+    let sigma_ab = blur(&ab);
+
+    let mut ssim_sum = 0.0;
+    let mut cs_sum = 0.0;
+
+    for i in 0..a.len() {
+        let (ma, mb) = (mu_a[i], mu_b[i]);
+
+        let var_a = sigma_aa[i] - ma * ma;
+        let var_b = sigma_bb[i] - mb * mb;
+        let cov = sigma_ab[i] - ma * mb;
+
+        let cs = (2.0 * cov + c2) / (var_a + var_b + c2);
+        let luminance = (2.0 * ma * mb + c1) / (ma * ma + mb * mb + c1);
+
+        ssim_sum += luminance * cs;
+        cs_sum += cs;
+    }
+    let len = a.len().max(1) as f64;
+
+    (ssim_sum / len, cs_sum / len)
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn gaussian_kernel() -> [f64; 2 * SSIM_RADIUS + 1] {
+    let mut kernel = [0.0; 2 * SSIM_RADIUS + 1];
+
+    for (i, k) in kernel.iter_mut().enumerate() {
+        let x = i as f64 - SSIM_RADIUS as f64;
+        *k = (-(x * x) / (2.0 * SSIM_SIGMA * SSIM_SIGMA)).exp();
+    }
+    let sum: f64 = kernel.iter().sum();
+    for k in &mut kernel {
+        *k /= sum;
+    }
+
+    kernel
+}
+
+/// Separable gaussian filter, replicating edge pixels
+fn gaussian_filter(data: &[f64], width: usize, height: usize, kernel: &[f64]) -> Vec<f64> {
+    if width == 0 || height == 0 {
+        return vec![];
+    }
+    let radius = kernel.len() / 2;
+    let mut scratch = vec![0.0; data.len()];
+    let mut output = vec![0.0; data.len()];
+
+    // horizontal
+    for (in_row, out_row) in data
+        .chunks_exact(width)
+        .zip(scratch.chunks_exact_mut(width))
+    {
+        for (x, out) in out_row.iter_mut().enumerate() {
+            *out = kernel
+                .iter()
+                .enumerate()
+                .map(|(k, w)| w * in_row[(x + k).saturating_sub(radius).min(width - 1)])
+                .sum();
+        }
+    }
+    // vertical
+    for y in 0..height {
+        for x in 0..width {
+            output[y * width + x] = kernel
+                .iter()
+                .enumerate()
+                .map(|(k, w)| {
+                    let yy = (y + k).saturating_sub(radius).min(height - 1);
+                    w * scratch[yy * width + x]
+                })
+                .sum();
+        }
+    }
+    output
+}
+
+/// Downsample by two by averaging 2x2 blocks, odd rows and columns are dropped
+fn downsample(data: &[f64], width: usize, height: usize) -> Vec<f64> {
+    let (new_w, new_h) = (width / 2, height / 2);
+    let mut output = vec![0.0; new_w * new_h];
+
+    for y in 0..new_h {
+        for x in 0..new_w {
+            let top = (2 * y) * width + 2 * x;
+            let bottom = top + width;
+
+            output[y * new_w + x] =
+                (data[top] + data[top + 1] + data[bottom] + data[bottom + 1]) * 0.25;
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+#[allow(clippy::cast_possible_truncation)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+
+    use crate::metrics::{ms_ssim, psnr, ssim};
+
+    #[test]
+    fn test_identical_images() {
+        let a = Image::from_fn::<u8, _>(64, 64, ColorSpace::RGB, |y, x, pix| {
+            pix[0] = (x * 4) as u8;
+            pix[1] = (y * 4) as u8;
+            pix[2] = ((x + y) * 2) as u8;
+        });
+        assert!(psnr(&a, &a).unwrap().global.is_infinite());
+
+        // compare an 8 bit image with a 16 bit copy, they should be the same
+        // save for float rounding
+        let mut b = a.clone();
+        b.convert_depth(zune_core::bit_depth::BitDepth::Sixteen)
+            .unwrap();
+
+        assert!(psnr(&a, &b).unwrap().global > 100.0);
+        assert!((ssim(&a, &b).unwrap().global - 1.0).abs() < 1e-9);
+        assert!((ms_ssim(&a, &b).unwrap().global - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_noise_lowers_score() {
+        let a = Image::from_fn::<u8, _>(64, 64, ColorSpace::Luma, |_, x, pix| {
+            pix[0] = (x * 3) as u8;
+        });
+        let b = Image::from_fn::<u8, _>(64, 64, ColorSpace::Luma, |y, x, pix| {
+            // checkerboard noise
+            let noise = if (x + y) % 2 == 0 { 10 } else { 0 };
+            pix[0] = (x * 3) as u8 + noise;
+        });
+        let score = ssim(&a, &b).unwrap();
+        assert!(score.global < 1.0 && score.global > 0.0);
+        assert_eq!(score.channels.len(), 1);
+
+        assert!(ms_ssim(&a, &b).unwrap().global < 1.0);
+    }
+}
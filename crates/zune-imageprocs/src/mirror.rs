@@ -11,12 +11,13 @@ use zune_core::bit_depth::BitType;
 use zune_image::channel::Channel;
 use zune_image::errors::ImageErrors;
 use zune_image::image::Image;
-use zune_image::traits::OperationsTrait;
+use zune_image::traits::{OperationsTrait, Parameter, ParameterKind, ParameterValue};
 
 use crate::utils::execute_on;
 
 /// Supported mirror modes
 #[derive(Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub enum MirrorMode {
     ///
     /// ```text           
@@ -61,6 +62,7 @@ pub enum MirrorMode {
 /// To see the effect of this
 /// see the image [mirror-modes](crate::mirror::MirrorMode) documentation
 /// for each used mode
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mirror {
     mode: MirrorMode
 }
@@ -78,6 +80,15 @@ impl OperationsTrait for Mirror {
         "Mirror"
     }
 
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            name:        "mode",
+            description: "Half of the image that is kept",
+            kind:        ParameterKind::Choice(&["north", "south", "east", "west"]),
+            default:     ParameterValue::Choice("north")
+        }]
+    }
+
     fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
         let (width, height) = image.dimensions();
         let depth = image.depth();
@@ -0,0 +1,353 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Montage: Lay out multiple images into a grid
+//!
+//! This creates a single contact sheet from a list of images, useful for galleries
+//! and for visually comparing outputs.
+//!
+//! # Layout
+//! Images are placed left to right, top to bottom in cells of the same size.
+//! `padding` pixels of background separate cells from each other and from the sheet edges,
+//! so a sheet with `c` columns, `r` rows and cells of `w x h` is
+//! `c*w + (c+1)*padding` wide and `r*h + (r+1)*padding` tall.
+//!
+//! Each image is scaled into its cell according to the [MontageFit] mode and centered.
+use alloc::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use zune_core::bit_depth::BitType;
+use zune_image::channel::Channel;
+use zune_image::errors::ImageErrors;
+use zune_image::image::{Image, MAX_CHANNELS};
+use zune_image::traits::OperationsTrait;
+
+use crate::resize::{Resize, ResizeMethod};
+use crate::utils::constant_channel;
+use crate::DepthColor;
+
+/// How images are fitted into their cells
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MontageFit {
+    /// Scale the image to fit inside the cell, preserving aspect ratio.
+    ///
+    /// The unused area is left as background
+    Contain,
+    /// Scale the image to cover the whole cell, preserving aspect ratio.
+    ///
+    /// Parts that fall outside the cell are cropped
+    Cover,
+    /// Scale the image to exactly the cell size, ignoring aspect ratio
+    Stretch,
+    /// Don't scale the image, images larger than the cell are cropped
+    Center
+}
+
+/// Create a contact sheet from multiple images
+///
+/// The sheet has the colorspace and depth of the first image, other images are converted
+/// to match. Only the first frame of each image is used.
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_imageprocs::montage::{Montage, MontageFit};
+///
+/// let images = vec![
+///     Image::fill(10_u8, ColorSpace::RGB, 40, 30),
+///     Image::fill(200_u8, ColorSpace::RGB, 30, 40),
+///     Image::fill(100_u8, ColorSpace::RGB, 20, 20)
+/// ];
+///
+/// let sheet = Montage::new(&images, 2)
+///     .set_padding(4)
+///     .set_cell_size(32, 32)
+///     .set_fit(MontageFit::Contain)
+///     .create()
+///     .unwrap();
+///
+/// assert_eq!(sheet.dimensions(), (2 * 32 + 3 * 4, 2 * 32 + 3 * 4));
+/// ```
+pub struct Montage<'a> {
+    images:     &'a [Image],
+    columns:    usize,
+    padding:    usize,
+    background: DepthColor,
+    cell_size:  Option<(usize, usize)>,
+    fit:        MontageFit,
+    method:     ResizeMethod
+}
+
+impl<'a> Montage<'a> {
+    /// Create a new montage
+    ///
+    /// # Arguments
+    /// - images: Images to lay out
+    /// - columns: Maximum number of cells per row
+    ///
+    /// Defaults are no padding, a black transparent background, cells as large as the largest image
+    /// and [MontageFit::Contain]
+    #[must_use]
+    pub fn new(images: &'a [Image], columns: usize) -> Montage<'a> {
+        Montage {
+            images,
+            columns,
+            padding: 0,
+            background: [0.0; MAX_CHANNELS],
+            cell_size: None,
+            fit: MontageFit::Contain,
+            method: ResizeMethod::Bilinear
+        }
+    }
+    /// Set the number of background pixels between cells and around the sheet
+    #[must_use]
+    pub fn set_padding(mut self, padding: usize) -> Self {
+        self.padding = padding;
+        self
+    }
+    /// Set the background color, in the range of the sheet depth, see [DepthColor]
+    #[must_use]
+    pub fn set_background(mut self, background: DepthColor) -> Self {
+        self.background = background;
+        self
+    }
+    /// Set the size of every cell
+    ///
+    /// If not set, cells are as wide as the widest image and as tall as the tallest image
+    #[must_use]
+    pub fn set_cell_size(mut self, width: usize, height: usize) -> Self {
+        self.cell_size = Some((width, height));
+        self
+    }
+    /// Set how images are fitted into their cells
+    #[must_use]
+    pub fn set_fit(mut self, fit: MontageFit) -> Self {
+        self.fit = fit;
+        self
+    }
+    /// Set the resize method used when scaling images into cells
+    #[must_use]
+    pub fn set_resize_method(mut self, method: ResizeMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Create the contact sheet
+    ///
+    /// # Errors
+    /// - If there are no images or columns is zero
+    /// - If an image can't be converted to the sheet colorspace or depth
+    pub fn create(&self) -> Result<Image, ImageErrors> {
+        let Some(first) = self.images.first() else {
+            return Err(ImageErrors::GenericStr(
+                "Montage requires at least one image"
+            ));
+        };
+        if self.columns == 0 {
+            return Err(ImageErrors::GenericStr("Montage columns must be non-zero"));
+        }
+        let colorspace = first.colorspace();
+        let depth = first.depth();
+
+        let (cell_w, cell_h) = self.cell_size.unwrap_or_else(|| {
+            self.images.iter().fold((0, 0), |acc, img| {
+                let (w, h) = img.dimensions();
+                (acc.0.max(w), acc.1.max(h))
+            })
+        });
+
+        let columns = self.columns.min(self.images.len());
+        let rows = self.images.len().div_ceil(columns);
+
+        let width = columns * cell_w + (columns + 1) * self.padding;
+        let height = rows * cell_h + (rows + 1) * self.padding;
+
+        let mut channels = (0..colorspace.num_components())
+            .map(|i| {
+                constant_channel(
+                    width * height,
+                    depth.bit_type(),
+                    self.background[i],
+                    "Montage"
+                )
+            })
+            .collect::<Result<Vec<Channel>, ImageErrors>>()?;
+
+        for (i, image) in self.images.iter().enumerate() {
+            let cell_x = self.padding + (i % columns) * (cell_w + self.padding);
+            let cell_y = self.padding + (i / columns) * (cell_h + self.padding);
+
+            let mut image = image.clone();
+
+            if image.colorspace() != colorspace {
+                image.convert_color(colorspace)?;
+            }
+            if image.depth() != depth {
+                image.convert_depth(depth)?;
+            }
+            self.fit_to_cell(&mut image, cell_w, cell_h)?;
+
+            let (img_w, img_h) = image.dimensions();
+            let copy_w = img_w.min(cell_w);
+            let copy_h = img_h.min(cell_h);
+
+            let rect = CopyRect {
+                src_width: img_w,
+                src_x:     (img_w - copy_w) / 2,
+                src_y:     (img_h - copy_h) / 2,
+                width:     copy_w,
+                height:    copy_h,
+                dst_width: width,
+                dst_x:     cell_x + (cell_w - copy_w) / 2,
+                dst_y:     cell_y + (cell_h - copy_h) / 2
+            };
+
+            let src_channels = image.frames_ref()[0].channels_ref(colorspace, false);
+
+            for (src, dst) in src_channels.iter().zip(channels.iter_mut()) {
+                match depth.bit_type() {
+                    BitType::U8 => {
+                        rect.copy::<u8>(src.reinterpret_as()?, dst.reinterpret_as_mut()?);
+                    }
+                    BitType::U16 => {
+                        rect.copy::<u16>(src.reinterpret_as()?, dst.reinterpret_as_mut()?);
+                    }
+                    BitType::F32 => {
+                        rect.copy::<f32>(src.reinterpret_as()?, dst.reinterpret_as_mut()?);
+                    }
+                    d => return Err(ImageErrors::ImageOperationNotImplemented("Montage", d))
+                }
+            }
+        }
+        Ok(Image::new(channels, depth, width, height, colorspace))
+    }
+
+    /// Resize an image according to the fit mode, the result may be larger than the
+    /// cell, in which case the center is used
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn fit_to_cell(
+        &self, image: &mut Image, cell_w: usize, cell_h: usize
+    ) -> Result<(), ImageErrors> {
+        let (w, h) = image.dimensions();
+
+        if w == 0 || h == 0 || cell_w == 0 || cell_h == 0 {
+            return Ok(());
+        }
+        let scale_x = cell_w as f64 / w as f64;
+        let scale_y = cell_h as f64 / h as f64;
+
+        let (new_w, new_h) = match self.fit {
+            MontageFit::Center => return Ok(()),
+            MontageFit::Stretch => (cell_w, cell_h),
+            MontageFit::Contain | MontageFit::Cover => {
+                let scale = if self.fit == MontageFit::Contain {
+                    scale_x.min(scale_y)
+                } else {
+                    scale_x.max(scale_y)
+                };
+                let new_w = ((w as f64 * scale).round() as usize).max(1);
+                let new_h = ((h as f64 * scale).round() as usize).max(1);
+
+                (new_w, new_h)
+            }
+        };
+        if (new_w, new_h) != (w, h) {
+            Resize::new(new_w, new_h, self.method).execute(image)?;
+        }
+        Ok(())
+    }
+}
+
+/// A rectangle copied from one channel to another
+struct CopyRect {
+    src_width: usize,
+    src_x:     usize,
+    src_y:     usize,
+    width:     usize,
+    height:    usize,
+    dst_width: usize,
+    dst_x:     usize,
+    dst_y:     usize
+}
+
+impl CopyRect {
+    fn copy<T: Copy>(&self, src: &[T], dst: &mut [T]) {
+        for row in 0..self.height {
+            let src_start = (self.src_y + row) * self.src_width + self.src_x;
+            let dst_start = (self.dst_y + row) * self.dst_width + self.dst_x;
+
+            dst[dst_start..dst_start + self.width]
+                .copy_from_slice(&src[src_start..src_start + self.width]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+
+    use crate::montage::{Montage, MontageFit};
+
+    #[test]
+    fn test_montage_layout() {
+        let images = vec![
+            Image::fill(50_u8, ColorSpace::Luma, 4, 2),
+            Image::fill(100_u8, ColorSpace::Luma, 2, 4),
+            Image::fill(150_u8, ColorSpace::Luma, 4, 4),
+        ];
+        let sheet = Montage::new(&images, 2)
+            .set_padding(1)
+            .set_background([255.0; 4])
+            .set_fit(MontageFit::Center)
+            .create()
+            .unwrap();
+
+        // cells are 4x4
+        assert_eq!(sheet.dimensions(), (11, 11));
+        let pixels = sheet.channels_ref(false)[0].reinterpret_as::<u8>().unwrap();
+        let at = |x: usize, y: usize| pixels[y * 11 + x];
+
+        // padding
+        assert_eq!(at(0, 0), 255);
+        // first image is centered vertically within its cell
+        assert_eq!(at(1, 1), 255);
+        assert_eq!(at(1, 2), 50);
+        // second image is centered horizontally
+        assert_eq!(at(6, 1), 255);
+        assert_eq!(at(7, 1), 100);
+        // third image starts the second row, the last cell is empty
+        assert_eq!(at(1, 6), 150);
+        assert_eq!(at(7, 7), 255);
+    }
+
+    #[test]
+    fn test_montage_converts_to_first_image() {
+        let images = vec![
+            Image::fill(1000_u16, ColorSpace::RGB, 8, 8),
+            Image::fill(255_u8, ColorSpace::Luma, 16, 4),
+        ];
+        let sheet = Montage::new(&images, 3)
+            .set_fit(MontageFit::Cover)
+            .create()
+            .unwrap();
+
+        assert_eq!(sheet.colorspace(), ColorSpace::RGB);
+        assert_eq!(sheet.dimensions(), (32, 8));
+
+        let pixels = sheet.channels_ref(false)[0]
+            .reinterpret_as::<u16>()
+            .unwrap();
+        assert_eq!(pixels[4 * 32 + 20], 65535);
+    }
+}
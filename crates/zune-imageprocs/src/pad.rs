@@ -12,10 +12,23 @@
 //! or replicating values across the border
 //!
 
+use alloc::vec;
+use alloc::vec::Vec;
+
 /// Padding method to use
-#[derive(Copy, Clone)]
+///
+/// This decides how pixels outside the image are computed, e.g. when filters
+/// read past the image edges or when cropping a region that isn't fully inside the image.
+///
+/// Filters default to [PadMethod::Replicate]. Filters reading whole rows, such as
+/// [GaussianBlur](crate::gaussian_blur::GaussianBlur) with large sigmas, only pad
+/// as far as their weights matter, so other methods are approximate for them
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub enum PadMethod {
     /// Create a border with a constant value
+    ///
+    /// [pad] uses zero (black), operations may allow configuring the value
     Constant,
     /// Create a border that duplicates the current pixel
     /// at the original image border to the new border
@@ -31,7 +44,74 @@ pub enum PadMethod {
     /// d d,e,f f
     ///   d,e,f
     /// ```
-    Replicate
+    #[default]
+    Replicate,
+    /// Create a border that mirrors pixels across the image border,
+    /// without repeating the border pixel
+    ///
+    /// ```text
+    ///  a,b,c,d
+    /// ```
+    /// Becomes
+    /// ```text
+    /// c,b, a,b,c,d, c,b
+    /// ```
+    Reflect,
+    /// Create a border that wraps around to the other side of the image,
+    /// as if the image was tiled
+    ///
+    /// ```text
+    ///  a,b,c,d
+    /// ```
+    /// Becomes
+    /// ```text
+    /// c,d, a,b,c,d, a,b
+    /// ```
+    Wrap
+}
+
+/// Map a possibly out of bounds position into `0..len` according to
+/// the padding method
+///
+/// # Arguments
+/// - pos: The position, may be negative or larger than `len`
+/// - len: The length of the row or column
+/// - method: The padding method to use
+///
+/// # Returns
+/// - `Some(index)`: The index of the pixel to use
+/// - `None`: If the method is [PadMethod::Constant] and the position is out of bounds or len is zero,
+///   the caller should use its constant value
+#[must_use]
+#[allow(
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss,
+    clippy::cast_possible_truncation
+)]
+pub fn border_index(pos: isize, len: usize, method: PadMethod) -> Option<usize> {
+    let n = len as isize;
+
+    if (0..n).contains(&pos) {
+        return Some(pos as usize);
+    }
+    if len == 0 {
+        return None;
+    }
+    match method {
+        PadMethod::Constant => None,
+        PadMethod::Replicate => Some(pos.clamp(0, n - 1) as usize),
+        PadMethod::Reflect => {
+            if n == 1 {
+                return Some(0);
+            }
+            // reflection is periodic with a period of 2*(n-1)
+            let period = 2 * (n - 1);
+            let p = pos.rem_euclid(period);
+
+            Some(if p >= n { period - p } else { p } as usize)
+        }
+        PadMethod::Wrap => Some(pos.rem_euclid(n) as usize)
+    }
 }
 
 /// Pad pixels creating a buffer around actual pixels
@@ -80,10 +160,40 @@ pub fn pad<T: Copy + Default>(
 ) -> Vec<T> {
     match method {
         PadMethod::Constant => no_fill(pixels, width, height, pad_x, pad_y),
-        PadMethod::Replicate => replicate(pixels, width, height, pad_x, pad_y)
+        PadMethod::Replicate => replicate(pixels, width, height, pad_x, pad_y),
+        PadMethod::Reflect | PadMethod::Wrap => remap(pixels, width, height, pad_x, pad_y, method)
     }
 }
 
+/// Pad by looking up where each padded pixel comes from via [border_index]
+#[allow(clippy::cast_possible_wrap)]
+fn remap<T: Copy + Default>(
+    pixels: &[T], width: usize, height: usize, pad_x: usize, pad_y: usize, method: PadMethod
+) -> Vec<T> {
+    let padded_w = width + pad_x * 2;
+    let padded_h = height + pad_y * 2;
+
+    let mut out_pixels = vec![T::default(); padded_h * padded_w];
+
+    if width == 0 || height == 0 {
+        return out_pixels;
+    }
+    // source columns are the same for every row
+    let columns: Vec<usize> = (0..padded_w)
+        .map(|x| border_index(x as isize - pad_x as isize, width, method).unwrap_or(0))
+        .collect();
+
+    for (y, out) in out_pixels.chunks_exact_mut(padded_w).enumerate() {
+        let src_y = border_index(y as isize - pad_y as isize, height, method).unwrap_or(0);
+        let in_row = &pixels[src_y * width..(src_y + 1) * width];
+
+        for (pix, x) in out.iter_mut().zip(&columns) {
+            *pix = in_row[*x];
+        }
+    }
+    out_pixels
+}
+
 fn no_fill<T: Copy + Default>(
     pixels: &[T], width: usize, height: usize, pad_x: usize, pad_y: usize
 ) -> Vec<T> {
@@ -170,6 +280,38 @@ fn replicate<T: Copy + Default>(
     out_pixels
 }
 
+/// Run `filter` on `pixels` with the border made by `method`
+///
+/// `filter` is one that repeats the border pixels past the image edges and reads at
+/// most `reach` pixels past them, e.g. a blur. With [PadMethod::Replicate] it runs on
+/// `pixels` and `scratch` directly, with other methods on a copy padded by `reach`,
+/// whose image part is copied back to `pixels`.
+///
+/// `filter` is called with the pixels, a scratch buffer of the same size and their
+/// width and height.
+pub(crate) fn filter_with_border<T: Copy + Default>(
+    pixels: &mut [T], scratch: &mut [T], width: usize, height: usize, reach: usize,
+    method: PadMethod, filter: impl FnOnce(&mut [T], &mut [T], usize, usize)
+) {
+    if method == PadMethod::Replicate || reach == 0 || width == 0 || height == 0 {
+        return filter(pixels, scratch, width, height);
+    }
+    let padded_w = width + reach * 2;
+    let padded_h = height + reach * 2;
+
+    let mut padded = pad(pixels, width, height, reach, reach, method);
+    let mut padded_scratch = vec![T::default(); padded.len()];
+
+    filter(&mut padded, &mut padded_scratch, padded_w, padded_h);
+
+    for (out, padded_row) in pixels
+        .chunks_exact_mut(width)
+        .zip(padded.chunks_exact(padded_w).skip(reach))
+    {
+        out.copy_from_slice(&padded_row[reach..reach + width]);
+    }
+}
+
 #[cfg(feature = "benchmarks")]
 #[cfg(test)]
 mod benchmarks {
@@ -218,3 +360,100 @@ mod benchmarks {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nanorand::Rng;
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::box_blur::BoxBlur;
+    use crate::crop::Crop;
+    use crate::gaussian_blur::GaussianBlur;
+    use crate::pad::{border_index, pad, PadMethod};
+    use crate::unsharpen::Unsharpen;
+
+    /// Run the operation made by `op` with each border method and compare it
+    /// against running it on the image padded by that method, with a margin wider
+    /// than the operation reads
+    ///
+    /// Filters handle replicated borders without padding, so that one is checked
+    /// on a uniform image instead
+    fn check_border_methods<O: OperationsTrait>(op: impl Fn(PadMethod) -> O) {
+        let (width, height, margin) = (17, 11, 40);
+        let mut pixels = vec![0_u8; width * height];
+        nanorand::WyRand::new_seed(5).fill(&mut pixels);
+
+        for method in [PadMethod::Constant, PadMethod::Reflect, PadMethod::Wrap] {
+            let mut image = Image::from_u8(&pixels, width, height, ColorSpace::Luma);
+            op(method).execute(&mut image).unwrap();
+
+            let padded = pad(&pixels, width, height, margin, margin, method);
+            let (padded_w, padded_h) = (width + margin * 2, height + margin * 2);
+
+            let mut expected = Image::from_u8(&padded, padded_w, padded_h, ColorSpace::Luma);
+            op(method).execute(&mut expected).unwrap();
+            Crop::new(width, height, margin, margin)
+                .execute(&mut expected)
+                .unwrap();
+
+            assert_eq!(
+                image.flatten_frames::<u8>(),
+                expected.flatten_frames::<u8>(),
+                "{method:?}"
+            );
+        }
+        // pixels past the edges repeat the border, so nothing changes
+        let mut image = Image::fill(200_u8, ColorSpace::Luma, width, height);
+        op(PadMethod::Replicate).execute(&mut image).unwrap();
+        assert!(image.flatten_frames::<u8>()[0].iter().all(|x| *x == 200));
+    }
+
+    #[test]
+    fn test_blur_border_methods() {
+        check_border_methods(|method| BoxBlur::new(4).set_pad_method(method));
+        check_border_methods(|method| GaussianBlur::new(2.0).set_pad_method(method));
+        check_border_methods(|method| Unsharpen::new(2.0, 10, 50).set_pad_method(method));
+    }
+
+    #[test]
+    fn test_border_index() {
+        let map = |method| -> Vec<Option<usize>> {
+            (-3..7).map(|x| border_index(x, 4, method)).collect()
+        };
+        let s = Some;
+
+        assert_eq!(
+            map(PadMethod::Replicate),
+            [s(0), s(0), s(0), s(0), s(1), s(2), s(3), s(3), s(3), s(3)]
+        );
+        assert_eq!(
+            map(PadMethod::Reflect),
+            [s(3), s(2), s(1), s(0), s(1), s(2), s(3), s(2), s(1), s(0)]
+        );
+        assert_eq!(
+            map(PadMethod::Wrap),
+            [s(1), s(2), s(3), s(0), s(1), s(2), s(3), s(0), s(1), s(2)]
+        );
+        assert_eq!(map(PadMethod::Constant)[0], None);
+    }
+
+    #[test]
+    fn test_pad_reflect() {
+        #[rustfmt::skip]
+        let pixels = [
+            1, 2, 3,
+            4, 5, 6
+        ];
+        let padded = pad(&pixels, 3, 2, 1, 1, PadMethod::Reflect);
+        #[rustfmt::skip]
+        let expected = [
+            5, 4, 5, 6, 5,
+            2, 1, 2, 3, 2,
+            5, 4, 5, 6, 5,
+            2, 1, 2, 3, 2
+        ];
+        assert_eq!(padded, expected);
+    }
+}
@@ -0,0 +1,321 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Photo filters: Sepia and other preset looks
+//!
+//! This provides a small set of common photo filter presets, each with an intensity
+//! between 0 (no effect) and 1 (full effect).
+//!
+//! # Algorithm
+//! Every preset is a combination of a color matrix and a tone curve, applied to pixel values
+//! normalized to 0..1.
+//!
+//! ```text
+//! [r',g',b'] = curve(matrix * [r,g,b,1])
+//! ```
+//!
+//! The intensity linearly interpolates the matrix with the identity matrix and the curve
+//! with the identity curve.
+//!
+//! - Sepia: The classic sepia tone matrix, giving a warm brown monochrome look
+//! - Warming / Cooling: Scale the red and blue channels in opposite directions
+//! - Fade: Lift the blacks, lower the whites and slightly desaturate, giving a washed out look
+//! - Matte: Lift the shadows with a soft curve, keeping contrast in the midtones
+//!
+//! The output image is always RGB, or RGBA if the input had an alpha channel, which is preserved.
+use zune_core::bit_depth::BitType;
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::traits::NumOps;
+use crate::utils::round_to_depth;
+
+/// A photo filter preset
+///
+/// The value of each variant is the intensity of the effect, between 0 and 1
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PhotoFilterPreset {
+    /// A warm brown monochrome tone
+    Sepia(f32),
+    /// Shift colors towards orange
+    Warming(f32),
+    /// Shift colors towards blue
+    Cooling(f32),
+    /// Lift blacks, lower whites and desaturate
+    Fade(f32),
+    /// Lift shadows with a soft curve
+    Matte(f32)
+}
+
+impl PhotoFilterPreset {
+    fn intensity(self) -> f32 {
+        match self {
+            PhotoFilterPreset::Sepia(s)
+            | PhotoFilterPreset::Warming(s)
+            | PhotoFilterPreset::Cooling(s)
+            | PhotoFilterPreset::Fade(s)
+            | PhotoFilterPreset::Matte(s) => s.clamp(0.0, 1.0)
+        }
+    }
+    /// The color matrix of the preset, each row is `[r, g, b, offset]`
+    fn matrix(self) -> [[f32; 4]; 3] {
+        let s = self.intensity();
+
+        let preset = match self {
+            PhotoFilterPreset::Sepia(_) => [
+                [0.393, 0.769, 0.189, 0.0],
+                [0.349, 0.686, 0.168, 0.0],
+                [0.272, 0.534, 0.131, 0.0]
+            ],
+            PhotoFilterPreset::Warming(_) => [
+                [1.12, 0.0, 0.0, 0.0],
+                [0.0, 1.02, 0.0, 0.0],
+                [0.0, 0.0, 0.85, 0.0]
+            ],
+            PhotoFilterPreset::Cooling(_) => [
+                [0.88, 0.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 1.12, 0.0]
+            ],
+            PhotoFilterPreset::Fade(_) => {
+                // mix 20% of luminance into every channel
+                let [lr, lg, lb] = [0.299 * 0.2, 0.587 * 0.2, 0.114 * 0.2];
+                [
+                    [0.8 + lr, lg, lb, 0.0],
+                    [lr, 0.8 + lg, lb, 0.0],
+                    [lr, lg, 0.8 + lb, 0.0]
+                ]
+            }
+            PhotoFilterPreset::Matte(_) => IDENTITY
+        };
+
+        let mut matrix = IDENTITY;
+        for (row, preset_row) in matrix.iter_mut().zip(preset) {
+            for (value, preset_value) in row.iter_mut().zip(preset_row) {
+                *value += (preset_value - *value) * s;
+            }
+        }
+        matrix
+    }
+    /// The tone curve of the preset for a value in 0..1
+    fn curve(self, x: f32) -> f32 {
+        let s = self.intensity();
+
+        match self {
+            PhotoFilterPreset::Sepia(_)
+            | PhotoFilterPreset::Warming(_)
+            | PhotoFilterPreset::Cooling(_) => x,
+            // blacks to 0.2, whites to 0.9
+            PhotoFilterPreset::Fade(_) => 0.2 * s + x * (1.0 - 0.3 * s),
+            // blacks to 0.15, whites to 0.95, steeper in between
+            PhotoFilterPreset::Matte(_) => x + s * (0.15 * (1.0 - x) * (1.0 - x) - 0.05 * x * x)
+        }
+    }
+}
+
+const IDENTITY: [[f32; 4]; 3] = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0]
+];
+
+/// Apply a photo filter preset to an image
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::photo_filter::{PhotoFilter, PhotoFilterPreset};
+///
+/// let mut image = Image::fill(128_u8, ColorSpace::RGB, 100, 100);
+///
+/// PhotoFilter::new(PhotoFilterPreset::Matte(0.5))
+///     .execute(&mut image)
+///     .unwrap();
+/// ```
+pub struct PhotoFilter {
+    preset: PhotoFilterPreset
+}
+
+impl PhotoFilter {
+    /// Create a new photo filter applying `preset`
+    #[must_use]
+    pub fn new(preset: PhotoFilterPreset) -> PhotoFilter {
+        PhotoFilter { preset }
+    }
+}
+
+impl OperationsTrait for PhotoFilter {
+    fn name(&self) -> &'static str {
+        "Photo Filter"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let target =
+            if image.colorspace().has_alpha() { ColorSpace::RGBA } else { ColorSpace::RGB };
+        if image.colorspace() != target {
+            image.convert_color(target)?;
+        }
+        let depth = image.depth().bit_type();
+
+        for frame in image.frames_mut() {
+            let channels = frame.channels_mut(target, true);
+            let (r, rest) = channels.split_at_mut(1);
+            let (g, b) = rest.split_at_mut(1);
+
+            match depth {
+                BitType::U8 => photo_filter::<u8>(
+                    r[0].reinterpret_as_mut()?,
+                    g[0].reinterpret_as_mut()?,
+                    b[0].reinterpret_as_mut()?,
+                    self.preset
+                ),
+                BitType::U16 => photo_filter::<u16>(
+                    r[0].reinterpret_as_mut()?,
+                    g[0].reinterpret_as_mut()?,
+                    b[0].reinterpret_as_mut()?,
+                    self.preset
+                ),
+                BitType::F32 => photo_filter::<f32>(
+                    r[0].reinterpret_as_mut()?,
+                    g[0].reinterpret_as_mut()?,
+                    b[0].reinterpret_as_mut()?,
+                    self.preset
+                ),
+                d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+            }
+        }
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Apply a sepia tone to an image
+///
+/// This is a shorthand for [`PhotoFilter`] with [`PhotoFilterPreset::Sepia`]
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::photo_filter::Sepia;
+///
+/// let mut image = Image::fill(128_u16, ColorSpace::RGB, 100, 100);
+///
+/// Sepia::new(1.0).execute(&mut image).unwrap();
+/// ```
+pub struct Sepia {
+    filter: PhotoFilter
+}
+
+impl Sepia {
+    /// Create a new sepia filter
+    ///
+    /// # Arguments
+    /// - intensity: The strength of the tone, between 0 (no effect) and 1 (full sepia)
+    #[must_use]
+    pub fn new(intensity: f32) -> Sepia {
+        Sepia {
+            filter: PhotoFilter::new(PhotoFilterPreset::Sepia(intensity))
+        }
+    }
+}
+
+impl OperationsTrait for Sepia {
+    fn name(&self) -> &'static str {
+        "Sepia"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        self.filter.execute_impl(image)
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        self.filter.supported_types()
+    }
+}
+
+/// Apply a photo filter preset to the channels of an RGB image
+///
+/// # Arguments
+/// - r,g,b: The image channels, modified in place
+/// - preset: The preset to apply
+pub fn photo_filter<T: Copy + NumOps<T>>(
+    r: &mut [T], g: &mut [T], b: &mut [T], preset: PhotoFilterPreset
+) {
+    let matrix = preset.matrix();
+    let max = T::MAX_VAL.to_f32();
+    for ((r, g), b) in r.iter_mut().zip(g.iter_mut()).zip(b.iter_mut()) {
+        let rgb = [r.to_f32() / max, g.to_f32() / max, b.to_f32() / max];
+
+        let [nr, ng, nb] = matrix.map(|row| {
+            let value = row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2] + row[3];
+            round_to_depth(preset.curve(value.clamp(0.0, 1.0)).clamp(0.0, 1.0) * max)
+        });
+
+        *r = nr;
+        *g = ng;
+        *b = nb;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::photo_filter::{PhotoFilter, PhotoFilterPreset, Sepia};
+
+    #[test]
+    fn test_sepia_white() {
+        let mut image = Image::fill(255_u8, ColorSpace::RGB, 2, 2);
+        Sepia::new(1.0).execute(&mut image).unwrap();
+
+        let values: Vec<u8> = image
+            .channels_ref(false)
+            .iter()
+            .map(|c| c.reinterpret_as::<u8>().unwrap()[0])
+            .collect();
+        // the sepia matrix saturates red and green for white, blue is 0.937
+        assert_eq!(values, [255, 255, 239]);
+    }
+
+    #[test]
+    fn test_presets_zero_intensity_is_identity() {
+        for preset in [
+            PhotoFilterPreset::Sepia(0.0),
+            PhotoFilterPreset::Warming(0.0),
+            PhotoFilterPreset::Cooling(0.0),
+            PhotoFilterPreset::Fade(0.0),
+            PhotoFilterPreset::Matte(0.0)
+        ] {
+            let mut image = Image::fill(1234_u16, ColorSpace::RGB, 2, 2);
+            PhotoFilter::new(preset).execute(&mut image).unwrap();
+
+            for channel in image.channels_ref(false) {
+                assert_eq!(channel.reinterpret_as::<u16>().unwrap()[0], 1234);
+            }
+        }
+        // fade lifts blacks
+        let mut image = Image::fill(0.0_f32, ColorSpace::RGB, 2, 2);
+        PhotoFilter::new(PhotoFilterPreset::Fade(1.0))
+            .execute(&mut image)
+            .unwrap();
+        let red = image.channels_ref(false)[0]
+            .reinterpret_as::<f32>()
+            .unwrap()[0];
+        assert!((red - 0.2).abs() < 1e-5);
+    }
+}
@@ -43,6 +43,9 @@
 //! [`fastdiv_u32`]
 //! -
 
+use alloc::vec;
+use alloc::vec::Vec;
+
 use zune_core::bit_depth::{BitDepth, BitType};
 use zune_core::colorspace::ColorSpace;
 use zune_core::log::warn;
@@ -53,6 +56,7 @@ use zune_image::traits::OperationsTrait;
 
 use crate::mathops::{compute_mod_u32, fastdiv_u32};
 
+mod neon;
 mod std_simd;
 
 /// Carry out alpha pre-multiply and un-premultiply
@@ -238,12 +242,16 @@ pub fn create_unpremul_table_u16() -> Vec<u128> {
 /// Items in input are modified in place.
 #[allow(clippy::cast_possible_truncation)]
 pub fn premultiply_u8(input: &mut [u8], alpha: &[u8]) {
-    const MAX_VALUE: u16 = 255;
-
+    #[cfg(all(feature = "neon", target_arch = "aarch64", target_feature = "neon"))]
+    if input.len() >= 16 {
+        unsafe {
+            return neon::premultiply_neon_u8(input, alpha);
+        }
+    }
     input.iter_mut().zip(alpha).for_each(|(color, al)| {
         let temp = (u16::from(*al) * u16::from(*color)) + 0x80;
-
-        *color = ((temp + (temp >> 8)) / MAX_VALUE) as u8;
+        // (temp + (temp >> 8)) >> 8 is (al * color) / 255 correctly rounded
+        *color = ((temp + (temp >> 8)) >> 8) as u8;
     });
 }
 
@@ -257,11 +265,16 @@ pub fn premultiply_u8(input: &mut [u8], alpha: &[u8]) {
 /// returns: Array modified in place
 #[allow(clippy::cast_possible_truncation)]
 pub fn premultiply_u16(input: &mut [u16], alpha: &[u16]) {
-    const MAX_VALUE: u32 = 65535;
-
+    #[cfg(all(feature = "neon", target_arch = "aarch64", target_feature = "neon"))]
+    if input.len() >= 8 {
+        unsafe {
+            return neon::premultiply_neon_u16(input, alpha);
+        }
+    }
     input.iter_mut().zip(alpha).for_each(|(color, al)| {
-        let temp = (u32::from(*al) * u32::from(*color)) + ((MAX_VALUE + 1) / 2);
-        *color = ((temp + (temp >> 16)) / MAX_VALUE) as u16;
+        let temp = (u32::from(*al) * u32::from(*color)) + 32768;
+        // the 16 bit analogue of the u8 rounding, (al * color) / 65535 correctly rounded
+        *color = ((temp + (temp >> 16)) >> 16) as u16;
     });
 }
 
@@ -366,8 +379,65 @@ pub fn unpremultiply_f32(input: &mut [f32], alpha: &[f32]) {
         #[cfg(feature = "portable-simd")]
         {
             use crate::premul_alpha::std_simd::unpremultiply_std_simd;
-            unpremultiply_std_simd(input, alpha);
+            // the simd version handles the remainder itself
+            return unpremultiply_std_simd(input, alpha);
         }
     }
+    #[cfg(all(feature = "neon", target_arch = "aarch64", target_feature = "neon"))]
+    if input.len() >= 4 {
+        unsafe {
+            return neon::unpremultiply_neon_f32(input, alpha);
+        }
+    }
+    #[allow(unreachable_code)]
     unpremultiply_f32_scalar(input, alpha);
 }
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::metadata::AlphaState;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::composite::{Composite, CompositeMethod};
+    use crate::premul_alpha::{premultiply_u8, PremultiplyAlpha};
+    use crate::resize::{Resize, ResizeMethod};
+
+    #[test]
+    fn test_premultiply_u8_rounds_correctly() {
+        for alpha in 0..=255_u8 {
+            let mut colors: Vec<u8> = (0..=255).collect();
+            premultiply_u8(&mut colors, &[alpha; 256]);
+
+            for (color, result) in (0..=255_u32).zip(colors) {
+                // round(color * alpha / 255)
+                let expected = (2 * color * u32::from(alpha) + 255) / 510;
+                assert_eq!(u32::from(result), expected, "{color} * {alpha}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_operations_track_alpha_state() {
+        let mut image = Image::fill(0.5_f32, ColorSpace::RGBA, 8, 8);
+
+        Resize::new(4, 4, ResizeMethod::Bilinear)
+            .execute(&mut image)
+            .unwrap();
+        assert_eq!(image.metadata().alpha(), AlphaState::PreMultiplied);
+
+        // premultiplying again is a no-op instead of darkening the image twice
+        PremultiplyAlpha::new(AlphaState::PreMultiplied)
+            .execute(&mut image)
+            .unwrap();
+        assert!((image.flatten_frames::<f32>()[0][0] - 0.25).abs() < 1e-5);
+
+        let source = image.clone();
+        Composite::new(&source, CompositeMethod::Over, (0, 0))
+            .execute(&mut image)
+            .unwrap();
+        assert_eq!(image.metadata().alpha(), AlphaState::NonPreMultiplied);
+        assert!((image.flatten_frames::<f32>()[0][0] - 0.5).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,134 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+#![cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+#![cfg(feature = "neon")]
+//! NEON alpha pre-multiplication
+//!
+//! Uses the same rounding division as the scalar versions,
+//! `(t + (t >> 8)) >> 8` with `t = color * alpha + 128` for u8 and the 16 bit analogue
+//! for u16, which `vsraq` (shift right and accumulate) and `vshrn` (shift right and narrow)
+//! compute in two instructions
+use core::arch::aarch64::*;
+
+use crate::premul_alpha::{premultiply_u16, premultiply_u8, unpremultiply_f32_scalar};
+
+pub(crate) unsafe fn premultiply_neon_u8(input: &mut [u8], alpha: &[u8]) {
+    let length = input.len().min(alpha.len());
+    let (input, alpha) = (&mut input[..length], &alpha[..length]);
+
+    let round = vdupq_n_u16(0x80);
+
+    let mut in_chunks = input.chunks_exact_mut(16);
+    let mut alpha_chunks = alpha.chunks_exact(16);
+
+    for (color, al) in (&mut in_chunks).zip(&mut alpha_chunks) {
+        let c = vld1q_u8(color.as_ptr());
+        let a = vld1q_u8(al.as_ptr());
+
+        let low = vaddq_u16(vmull_u8(vget_low_u8(c), vget_low_u8(a)), round);
+        let high = vaddq_u16(vmull_high_u8(c, a), round);
+
+        let low = vshrn_n_u16::<8>(vsraq_n_u16::<8>(low, low));
+        let high = vshrn_n_u16::<8>(vsraq_n_u16::<8>(high, high));
+
+        vst1q_u8(color.as_mut_ptr(), vcombine_u8(low, high));
+    }
+    premultiply_u8(in_chunks.into_remainder(), alpha_chunks.remainder());
+}
+
+pub(crate) unsafe fn premultiply_neon_u16(input: &mut [u16], alpha: &[u16]) {
+    let length = input.len().min(alpha.len());
+    let (input, alpha) = (&mut input[..length], &alpha[..length]);
+
+    let round = vdupq_n_u32(32768);
+
+    let mut in_chunks = input.chunks_exact_mut(8);
+    let mut alpha_chunks = alpha.chunks_exact(8);
+
+    for (color, al) in (&mut in_chunks).zip(&mut alpha_chunks) {
+        let c = vld1q_u16(color.as_ptr());
+        let a = vld1q_u16(al.as_ptr());
+
+        let low = vaddq_u32(vmull_u16(vget_low_u16(c), vget_low_u16(a)), round);
+        let high = vaddq_u32(vmull_high_u16(c, a), round);
+
+        let low = vshrn_n_u32::<16>(vsraq_n_u32::<16>(low, low));
+        let high = vshrn_n_u32::<16>(vsraq_n_u32::<16>(high, high));
+
+        vst1q_u16(color.as_mut_ptr(), vcombine_u16(low, high));
+    }
+    premultiply_u16(in_chunks.into_remainder(), alpha_chunks.remainder());
+}
+
+/// Divides input by alpha, on encountering zero in alpha, stores zero in output
+pub(crate) unsafe fn unpremultiply_neon_f32(input: &mut [f32], alpha: &[f32]) {
+    let length = input.len().min(alpha.len());
+    let (input, alpha) = (&mut input[..length], &alpha[..length]);
+
+    let zeros = vdupq_n_f32(0.0);
+
+    let mut in_chunks = input.chunks_exact_mut(4);
+    let mut alpha_chunks = alpha.chunks_exact(4);
+
+    for (color, al) in (&mut in_chunks).zip(&mut alpha_chunks) {
+        let c = vld1q_f32(color.as_ptr());
+        let a = vld1q_f32(al.as_ptr());
+
+        let mask = vceqq_f32(a, zeros);
+        let result = vbslq_f32(mask, zeros, vdivq_f32(c, a));
+
+        vst1q_f32(color.as_mut_ptr(), result);
+    }
+    unpremultiply_f32_scalar(in_chunks.into_remainder(), alpha_chunks.remainder());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::premul_alpha::neon::{
+        premultiply_neon_u16, premultiply_neon_u8, unpremultiply_neon_f32
+    };
+    use crate::premul_alpha::{premultiply_u16, premultiply_u8, unpremultiply_f32_scalar};
+
+    #[test]
+    fn test_premultiply_neon_matches_scalar() {
+        // every color against every alpha, with a leftover
+        let color_u8: Vec<u8> = (0..=255).flat_map(|_| 0..=255).chain(0..5).collect();
+        let alpha_u8: Vec<u8> = (0..=255).flat_map(|a| [a; 256]).chain(250..=254).collect();
+
+        let (mut neon_u8, mut scalar_u8) = (color_u8.clone(), color_u8);
+        unsafe {
+            premultiply_neon_u8(&mut neon_u8, &alpha_u8);
+        }
+        premultiply_u8(&mut scalar_u8, &alpha_u8);
+        assert_eq!(neon_u8, scalar_u8);
+
+        let color_u16: Vec<u16> = (0..=u16::MAX).step_by(7).collect();
+        let alpha_u16: Vec<u16> = color_u16.iter().rev().copied().collect();
+
+        let (mut neon_u16, mut scalar_u16) = (color_u16.clone(), color_u16);
+        unsafe {
+            premultiply_neon_u16(&mut neon_u16, &alpha_u16);
+        }
+        premultiply_u16(&mut scalar_u16, &alpha_u16);
+        assert_eq!(neon_u16, scalar_u16);
+    }
+
+    #[test]
+    fn test_unpremultiply_neon_matches_scalar() {
+        let alpha: Vec<f32> = (0_u16..103).map(|x| f32::from(x % 10) / 9.0).collect();
+        let color: Vec<f32> = (0_u16..103).map(|x| f32::from(x) / 103.0).collect();
+
+        let (mut neon, mut scalar) = (color.clone(), color);
+        unsafe {
+            unpremultiply_neon_f32(&mut neon, &alpha);
+        }
+        unpremultiply_f32_scalar(&mut scalar, &alpha);
+        assert_eq!(neon, scalar);
+    }
+}
@@ -1,7 +1,7 @@
 #![cfg(feature = "portable-simd")]
 
+use core::mem::size_of;
 use core::simd::prelude::*;
-use std::mem::size_of;
 
 use crate::premul_alpha::unpremultiply_f32_scalar;
 
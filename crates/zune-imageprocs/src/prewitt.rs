@@ -4,6 +4,9 @@
  * This software is free software; You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
  */
 #![allow(dead_code)]
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
 use crate::pad::{pad, PadMethod};
 use crate::spatial::spatial_NxN;
 use crate::traits::NumOps;
@@ -17,7 +20,7 @@ use crate::traits::NumOps;
 fn prewitt_inner_f32<T>(c: &[T; 9]) -> T
     where
         T: NumOps<T> + Copy + Default,
-        f32: std::convert::From<T>
+        f32: core::convert::From<T>
 {
     // matrix
     //   +1, 0, -1,
@@ -51,7 +54,7 @@ fn prewitt_inner_f32<T>(c: &[T; 9]) -> T
 fn prewitt_inner_i32<T>(c: &[T; 9]) -> T
     where
         T: NumOps<T> + Copy + Default,
-        i32: std::convert::From<T>
+        i32: core::convert::From<T>
 {
     // Gx matrix
     //   -3, 0,  3,
@@ -86,11 +89,21 @@ fn prewitt_inner_i32<T>(c: &[T; 9]) -> T
 /// - height: Height of input channel
 pub fn prewitt_float<T>(in_channel: &[T], out_channel: &mut [T], width: usize, height: usize)
 where
-    T: Default + NumOps<T> + Copy,
-    f32: std::convert::From<T>
+    T: Default + NumOps<T> + Copy + Send + Sync,
+    f32: core::convert::From<T>
+{
+    prewitt_float_with_border(in_channel, out_channel, width, height, PadMethod::Replicate)
+}
+
+/// [`prewitt_float`] with pixels outside the image computed by `pad_method`
+pub fn prewitt_float_with_border<T>(
+    in_channel: &[T], out_channel: &mut [T], width: usize, height: usize, pad_method: PadMethod
+) where
+    T: Default + NumOps<T> + Copy + Send + Sync,
+    f32: core::convert::From<T>
 {
     //pad here
-    let padded_input = pad(in_channel, width, height, 1, 1, PadMethod::Replicate);
+    let padded_input = pad(in_channel, width, height, 1, 1, pad_method);
 
     spatial_NxN::<_, _, 1, 9>(&padded_input, out_channel, width, height, prewitt_inner_f32);
 }
@@ -106,11 +119,21 @@ where
 /// - height: Height of input channel
 pub fn prewitt_int<T>(in_channel: &[T], out_channel: &mut [T], width: usize, height: usize)
 where
-    T: Default + NumOps<T> + Copy,
-    i32: std::convert::From<T>
+    T: Default + NumOps<T> + Copy + Send + Sync,
+    i32: core::convert::From<T>
+{
+    prewitt_int_with_border(in_channel, out_channel, width, height, PadMethod::Replicate)
+}
+
+/// [`prewitt_int`] with pixels outside the image computed by `pad_method`
+pub fn prewitt_int_with_border<T>(
+    in_channel: &[T], out_channel: &mut [T], width: usize, height: usize, pad_method: PadMethod
+) where
+    T: Default + NumOps<T> + Copy + Send + Sync,
+    i32: core::convert::From<T>
 {
     //pad here
-    let padded_input = pad(in_channel, width, height, 1, 1, PadMethod::Replicate);
+    let padded_input = pad(in_channel, width, height, 1, 1, pad_method);
 
     spatial_NxN::<_, _, 1, 9>(&padded_input, out_channel, width, height, prewitt_inner_i32);
 }
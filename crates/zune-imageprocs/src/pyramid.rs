@@ -0,0 +1,416 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Gaussian and Laplacian image pyramids
+//!
+//! A gaussian pyramid is a list of successively blurred and halved copies of an image,
+//! a laplacian pyramid stores the detail lost between two gaussian levels, which
+//! allows processing an image band by band and reconstructing it afterwards.
+//!
+//! They are the basis of multi-resolution blending and exposure fusion.
+//!
+//! # Algorithm
+//! This follows Burt and Adelson, *The Laplacian Pyramid as a Compact Image Code* (1983).
+//!
+//! - [pyr_down] blurs the image with the separable 5 tap binomial kernel `[1,4,6,4,1]/16`
+//!   and drops every other row and column, a `w x h` image becomes `(w+1)/2 x (h+1)/2`
+//! - [pyr_up] doubles the image to a requested size, interpolating with the same kernel
+//! - Laplacian level `i` is `gaussian[i] - pyr_up(gaussian[i+1])`, the last level is the
+//!   smallest gaussian level, so collapsing is `pyr_up(level) + residual` from the bottom up.
+//!
+//! Borders are handled by replicating edge pixels.
+use alloc::vec;
+use alloc::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use zune_core::bit_depth::{BitDepth, BitType};
+use zune_image::channel::Channel;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+
+use crate::traits::NumOps;
+
+/// Binomial kernel used for blurring, from offset -2 to 2
+const KERNEL: [f32; 5] = [1.0 / 16.0, 4.0 / 16.0, 6.0 / 16.0, 4.0 / 16.0, 1.0 / 16.0];
+
+/// Blur and halve an image
+///
+/// The new image has dimensions `((width+1)/2, (height+1)/2)` and
+/// the same depth and colorspace as the input.
+///
+/// # Errors
+/// If the image depth is unsupported
+pub fn pyr_down(image: &Image) -> Result<Image, ImageErrors> {
+    let (width, height) = image.dimensions();
+    let (new_width, new_height) = (width.div_ceil(2), height.div_ceil(2));
+
+    map_channels(image, new_width, new_height, |pixels| {
+        reduce(pixels, width, height)
+    })
+}
+
+/// Upsample an image to `new_width` x `new_height`, which should be at most twice the image
+/// dimensions (typically the dimensions of the level the image was reduced from)
+///
+/// # Errors
+/// If the image depth is unsupported
+pub fn pyr_up(image: &Image, new_width: usize, new_height: usize) -> Result<Image, ImageErrors> {
+    let (width, height) = image.dimensions();
+
+    map_channels(image, new_width, new_height, |pixels| {
+        expand(pixels, width, height, new_width, new_height)
+    })
+}
+
+/// Build a gaussian pyramid
+///
+/// # Arguments
+/// - image: The image, stored as the first level
+/// - levels: Maximum number of levels to return, including the original image.
+///   Fewer levels are returned if the image can't be reduced further (both dimensions are 1)
+///
+/// # Returns
+/// Levels from the largest (a copy of `image`) to the smallest,
+/// all in the image's depth and colorspace
+///
+/// # Errors
+/// If the image depth is unsupported
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_imageprocs::pyramid::gaussian_pyramid;
+///
+/// let image = Image::fill(100_u8, ColorSpace::RGB, 64, 48);
+/// let levels = gaussian_pyramid(&image, 3).unwrap();
+///
+/// assert_eq!(levels.len(), 3);
+/// assert_eq!(levels[2].dimensions(), (16, 12));
+/// ```
+pub fn gaussian_pyramid(image: &Image, levels: usize) -> Result<Vec<Image>, ImageErrors> {
+    let mut pyramid: Vec<Image> = Vec::with_capacity(levels);
+
+    if levels == 0 {
+        return Ok(pyramid);
+    }
+    pyramid.push(image.clone());
+
+    while pyramid.len() < levels {
+        let last = &pyramid[pyramid.len() - 1];
+
+        if last.dimensions() == (1, 1) {
+            break;
+        }
+        let next = pyr_down(last)?;
+        pyramid.push(next);
+    }
+    Ok(pyramid)
+}
+
+/// Build a laplacian pyramid
+///
+/// Since residuals can be negative, all levels are in [`BitDepth::Float32`],
+/// the image is converted first if needed.
+///
+/// # Arguments
+/// - image: The image to decompose
+/// - levels: Maximum number of levels, see [gaussian_pyramid]
+///
+/// # Returns
+/// Detail levels from the finest to the coarsest, the last level is the smallest
+/// gaussian level itself, see [collapse_laplacian_pyramid]
+///
+/// # Errors
+/// If the image depth is unsupported
+pub fn laplacian_pyramid(image: &Image, levels: usize) -> Result<Vec<Image>, ImageErrors> {
+    let mut image = image.clone();
+
+    if image.depth() != BitDepth::Float32 {
+        image.convert_depth(BitDepth::Float32)?;
+    }
+    let mut gaussian = gaussian_pyramid(&image, levels)?;
+
+    for i in 0..gaussian.len().saturating_sub(1) {
+        let (width, height) = gaussian[i].dimensions();
+        let expanded = pyr_up(&gaussian[i + 1], width, height)?;
+
+        combine(&mut gaussian[i], &expanded, |a, b| a - b)?;
+    }
+    Ok(gaussian)
+}
+
+/// Reconstruct an image from a laplacian pyramid created by [laplacian_pyramid]
+///
+/// The result is in [`BitDepth::Float32`] and is not clamped, convert it to the desired depth
+/// afterwards.
+///
+/// # Errors
+/// If the pyramid is empty, levels aren't float images or the
+/// levels have a different colorspace
+///
+/// # Example
+/// ```
+/// use zune_core::bit_depth::BitDepth;
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_imageprocs::pyramid::{collapse_laplacian_pyramid, laplacian_pyramid};
+///
+/// let image = Image::fill(0.5_f32, ColorSpace::Luma, 30, 20);
+/// let pyramid = laplacian_pyramid(&image, 4).unwrap();
+/// let collapsed = collapse_laplacian_pyramid(&pyramid).unwrap();
+///
+/// assert_eq!(collapsed.dimensions(), (30, 20));
+/// ```
+pub fn collapse_laplacian_pyramid(pyramid: &[Image]) -> Result<Image, ImageErrors> {
+    let Some(last) = pyramid.last() else {
+        return Err(ImageErrors::GenericStr("Cannot collapse an empty pyramid"));
+    };
+    let mut result = last.clone();
+
+    for level in pyramid.iter().rev().skip(1) {
+        if level.depth() != BitDepth::Float32 || level.colorspace() != result.colorspace() {
+            return Err(ImageErrors::GenericStr(
+                "Laplacian pyramid levels must be float images with the same colorspace"
+            ));
+        }
+        let (width, height) = level.dimensions();
+        let mut expanded = pyr_up(&result, width, height)?;
+
+        combine(&mut expanded, level, |a, b| a + b)?;
+        result = expanded;
+    }
+    Ok(result)
+}
+
+/// Combine each float channel of `image` with the matching channel of `other`, storing
+/// the result in `image`
+fn combine(
+    image: &mut Image, other: &Image, op: impl Fn(f32, f32) -> f32
+) -> Result<(), ImageErrors> {
+    if image.dimensions() != other.dimensions() || image.frames_len() != other.frames_len() {
+        return Err(ImageErrors::GenericStr(
+            "Pyramid levels have mismatched dimensions"
+        ));
+    }
+    for (a, b) in image
+        .channels_mut(false)
+        .into_iter()
+        .zip(other.channels_ref(false))
+    {
+        for (x, y) in a
+            .reinterpret_as_mut::<f32>()?
+            .iter_mut()
+            .zip(b.reinterpret_as::<f32>()?)
+        {
+            *x = op(*x, *y);
+        }
+    }
+    Ok(())
+}
+
+/// Run `func` on every channel of every frame in native range f32, creating a new image
+/// of `new_width` x `new_height`
+fn map_channels(
+    image: &Image, new_width: usize, new_height: usize, func: impl Fn(&[f32]) -> Vec<f32>
+) -> Result<Image, ImageErrors> {
+    let depth = image.depth();
+    let colorspace = image.colorspace();
+
+    let mut frames = Vec::with_capacity(image.frames_len());
+
+    for frame in image.frames_ref() {
+        let mut channels = vec![];
+
+        for channel in frame.channels_ref(colorspace, false) {
+            let length = new_width * new_height;
+
+            let new_channel = match depth.bit_type() {
+                BitType::U8 => {
+                    let mut c = Channel::new_with_capacity::<u8>(length);
+                    c.extend(&process::<u8>(channel.reinterpret_as()?, &func, true));
+                    c
+                }
+                BitType::U16 => {
+                    let mut c = Channel::new_with_capacity::<u16>(length * 2);
+                    c.extend(&process::<u16>(channel.reinterpret_as()?, &func, true));
+                    c
+                }
+                BitType::F32 => {
+                    let mut c = Channel::new_with_capacity::<f32>(length * 4);
+                    c.extend(&process::<f32>(channel.reinterpret_as()?, &func, false));
+                    c
+                }
+                d => {
+                    return Err(ImageErrors::ImageOperationNotImplemented("Pyramid", d));
+                }
+            };
+            channels.push(new_channel);
+        }
+        frames.push(frame.with_channels(channels));
+    }
+
+    Ok(Image::new_frames(
+        frames, depth, new_width, new_height, colorspace
+    ))
+}
+
+/// Run `func` on a channel, rounding and clamping the result for integer types
+fn process<T>(pixels: &[T], func: impl Fn(&[f32]) -> Vec<f32>, round: bool) -> Vec<T>
+where
+    T: NumOps<T> + Copy
+{
+    let input: Vec<f32> = pixels.iter().map(|x| x.to_f32()).collect();
+    let (min, max) = (T::MIN_VAL.to_f32(), T::MAX_VAL.to_f32());
+
+    func(&input)
+        .into_iter()
+        .map(|value| {
+            if round {
+                T::from_f32(value.round().clamp(min, max))
+            } else {
+                T::from_f32(value)
+            }
+        })
+        .collect()
+}
+
+/// Blur and decimate a channel
+fn reduce(pixels: &[f32], width: usize, height: usize) -> Vec<f32> {
+    let (new_width, new_height) = (width.div_ceil(2), height.div_ceil(2));
+
+    // horizontal pass, producing a new_width x height image
+    let mut temp = vec![0.0; new_width * height];
+
+    for (in_row, out_row) in pixels
+        .chunks_exact(width)
+        .zip(temp.chunks_exact_mut(new_width))
+    {
+        for (x, out) in out_row.iter_mut().enumerate() {
+            *out = KERNEL
+                .iter()
+                .enumerate()
+                .map(|(k, w)| w * in_row[clamp_index(2 * x + k, 2, width)])
+                .sum();
+        }
+    }
+    // vertical pass
+    let mut output = vec![0.0; new_width * new_height];
+
+    for (y, out_row) in output.chunks_exact_mut(new_width).enumerate() {
+        for (k, w) in KERNEL.iter().enumerate() {
+            let src_y = clamp_index(2 * y + k, 2, height);
+            let in_row = &temp[src_y * new_width..(src_y + 1) * new_width];
+
+            for (out, pix) in out_row.iter_mut().zip(in_row) {
+                *out += w * pix;
+            }
+        }
+    }
+    output
+}
+
+/// Upsample a channel to `new_width` x `new_height`
+///
+/// Conceptually this inserts zeros between pixels and blurs with twice the kernel,
+/// so each output pixel only gathers the source pixels at even offsets
+fn expand(
+    pixels: &[f32], width: usize, height: usize, new_width: usize, new_height: usize
+) -> Vec<f32> {
+    // horizontal pass, producing a new_width x height image
+    let mut temp = vec![0.0; new_width * height];
+
+    for (in_row, out_row) in pixels
+        .chunks_exact(width)
+        .zip(temp.chunks_exact_mut(new_width))
+    {
+        for (x, out) in out_row.iter_mut().enumerate() {
+            *out = expand_sample(x, width, |i| in_row[i]);
+        }
+    }
+    // vertical pass
+    let mut output = vec![0.0; new_width * new_height];
+
+    for (y, out_row) in output.chunks_exact_mut(new_width).enumerate() {
+        for (x, out) in out_row.iter_mut().enumerate() {
+            *out = expand_sample(y, height, |i| temp[i * new_width + x]);
+        }
+    }
+    output
+}
+
+/// Compute a single upsampled value at position `pos` from a source of length `len`
+fn expand_sample(pos: usize, len: usize, get: impl Fn(usize) -> f32) -> f32 {
+    let mut sum = 0.0;
+
+    for (k, w) in KERNEL.iter().enumerate() {
+        // source position is (pos + 2 - k)/2, only valid when the numerator is even.
+        // Positions before the start map to the first pixel
+        let shifted = pos + 2;
+
+        if (shifted + k) & 1 == 0 {
+            let src = shifted.saturating_sub(k) / 2;
+            sum += 2.0 * w * get(src.min(len - 1));
+        }
+    }
+    sum
+}
+
+/// Compute `pos - offset` clamped to `0..len`
+fn clamp_index(pos: usize, offset: usize, len: usize) -> usize {
+    pos.saturating_sub(offset).min(len - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+
+    use crate::pyramid::{collapse_laplacian_pyramid, gaussian_pyramid, laplacian_pyramid};
+
+    #[test]
+    fn test_gaussian_pyramid_dimensions() {
+        let image = Image::fill(10_u16, ColorSpace::RGBA, 5, 3);
+        let pyramid = gaussian_pyramid(&image, 10).unwrap();
+
+        let dims: Vec<_> = pyramid.iter().map(Image::dimensions).collect();
+        assert_eq!(dims, [(5, 3), (3, 2), (2, 1), (1, 1)]);
+        // constant images stay constant
+        let last = pyramid[3].channels_ref(false)[0]
+            .reinterpret_as::<u16>()
+            .unwrap();
+        assert_eq!(last, &[10]);
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn test_laplacian_roundtrip() {
+        let image = Image::from_fn::<f32, _>(37, 21, ColorSpace::RGB, |y, x, pix| {
+            pix[0] = ((x * y) % 13) as f32 / 13.0;
+            pix[1] = x as f32 / 37.0;
+            pix[2] = y as f32 / 21.0;
+        });
+        let pyramid = laplacian_pyramid(&image, 5).unwrap();
+        assert_eq!(pyramid.len(), 5);
+
+        let collapsed = collapse_laplacian_pyramid(&pyramid).unwrap();
+
+        for (a, b) in image
+            .channels_ref(false)
+            .iter()
+            .zip(collapsed.channels_ref(false))
+        {
+            let a = a.reinterpret_as::<f32>().unwrap();
+            let b = b.reinterpret_as::<f32>().unwrap();
+
+            for (x, y) in a.iter().zip(b) {
+                assert!((x - y).abs() < 1e-5);
+            }
+        }
+    }
+}
@@ -0,0 +1,390 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Serializable processing recipes
+//!
+//! A [`Recipe`] is a list of operations and their parameters that can be stored in a file,
+//! versioned alongside other assets and replayed later, e.g. by the command line tool
+//! or a server, without recompiling anything.
+//!
+//! Recipes implement serde's `Serialize` and `Deserialize`, so any serde format works,
+//! e.g. JSON with `serde_json` or TOML with `toml`. Every step is tagged with the name of
+//! its operation in `op`, the other fields are the parameters of the operation.
+//!
+//! This requires the `serde-support` feature
+//!
+//! # Example
+//! ```
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_imageprocs::recipe::Recipe;
+//!
+//! let recipe: Recipe = serde_json::from_str(
+//!     r#"{
+//!         "version": 1,
+//!         "operations": [
+//!             { "op": "resize", "width": 50, "height": 25 },
+//!             { "op": "brighten", "value": 0.1 },
+//!             { "op": "flip", "direction": "vertical" }
+//!         ]
+//!     }"#
+//! )
+//! .unwrap();
+//!
+//! let mut image = Image::fill(100_u8, ColorSpace::RGB, 100, 100);
+//! recipe.execute(&mut image).unwrap();
+//! assert_eq!(image.dimensions(), (50, 25));
+//!
+//! // and back to json
+//! let json = serde_json::to_string(&recipe).unwrap();
+//! ```
+use serde::{Deserialize, Serialize};
+use zune_core::colorspace::ColorSpace;
+use zune_image::core_filters::colorspace::ColorspaceConv;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::pipelines::Pipeline;
+use zune_image::traits::OperationsTrait;
+
+use crate::box_blur::BoxBlur;
+use crate::brighten::Brighten;
+use crate::contrast::Contrast;
+use crate::crop::Crop;
+use crate::exposure::Exposure;
+use crate::flip::{Flip, FlipDirection};
+use crate::gamma::Gamma;
+use crate::gaussian_blur::GaussianBlur;
+use crate::hsv_adjust::HsvAdjust;
+use crate::invert::Invert;
+use crate::median::Median;
+use crate::mirror::{Mirror, MirrorMode};
+use crate::resize::{Resize, ResizeMethod};
+use crate::rotate::Rotate;
+use crate::spatial::SpatialOps;
+use crate::spatial_ops::SpatialOperations;
+use crate::stretch_contrast::StretchContrast;
+use crate::threshold::{Threshold, ThresholdMethod};
+use crate::transpose::Transpose;
+use crate::unsharpen::Unsharpen;
+
+/// A list of operations to run on an image, in order
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Recipe {
+    /// Version of the recipe format, see [`Recipe::VERSION`]
+    #[serde(default = "current_version")]
+    pub version:    u32,
+    /// The operations to run
+    #[serde(default)]
+    pub operations: Vec<Step>
+}
+
+/// A single operation of a [`Recipe`] and its parameters
+///
+/// Fields are named after the [`parameters`](OperationsTrait::parameters) of the operation,
+/// enumerated parameters, e.g. the method of a threshold, are stored by name
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Step {
+    /// See [`Invert`]
+    Invert,
+    /// See [`Brighten`]
+    Brighten { value: f32 },
+    /// See [`Gamma`]
+    Gamma { value: f32 },
+    /// See [`Contrast`]
+    Contrast { contrast: f32 },
+    /// See [`Exposure`]
+    Exposure {
+        exposure: f32,
+        #[serde(default)]
+        black:    f32
+    },
+    /// See [`StretchContrast`]
+    StretchContrast { lower: f32, upper: f32 },
+    /// See [`Threshold`], method is one of `binary`, `binary_inv`, `thresh_trunc`
+    /// and `thresh_to_zero`
+    Threshold { threshold: f32, method: String },
+    /// Convert the image to grayscale
+    Grayscale,
+    /// See [`Flip`], direction is one of `horizontal`, `vertical` and `x_axis`
+    Flip { direction: String },
+    /// See [`Mirror`], mode is one of `north`, `south`, `east` and `west`
+    Mirror { mode: String },
+    /// See [`Transpose`]
+    Transpose,
+    /// See [`Rotate`]
+    Rotate { angle: f32 },
+    /// See [`Crop`]
+    Crop {
+        width:  usize,
+        height: usize,
+        x:      usize,
+        y:      usize
+    },
+    /// See [`Resize`], method is `bilinear` or `bicubic`, defaulting to `bilinear`
+    Resize {
+        width:  usize,
+        height: usize,
+        #[serde(default = "default_resize_method")]
+        method: String
+    },
+    /// See [`GaussianBlur`]
+    GaussianBlur { sigma: f32 },
+    /// See [`BoxBlur`]
+    BoxBlur { radius: usize },
+    /// See [`Median`]
+    Median { radius: usize },
+    /// See [`Unsharpen`]
+    Unsharpen {
+        sigma:      f32,
+        threshold:  u16,
+        percentage: u8
+    },
+    /// See [`HsvAdjust`]
+    HsvAdjust {
+        hue:        f32,
+        saturation: f32,
+        lightness:  f32
+    },
+    /// See [`SpatialOps`], mode is one of `contrast`, `maximum`, `gradient`,
+    /// `minimum` and `mean`
+    Statistic { radius: usize, mode: String }
+}
+
+fn current_version() -> u32 {
+    Recipe::VERSION
+}
+
+fn default_resize_method() -> String {
+    "bilinear".to_string()
+}
+
+impl Recipe {
+    /// The newest version of the recipe format this library reads
+    pub const VERSION: u32 = 1;
+
+    /// Create an empty recipe
+    #[must_use]
+    pub fn new() -> Recipe {
+        Recipe {
+            version:    Recipe::VERSION,
+            operations: vec![]
+        }
+    }
+
+    /// Add a step to the end of the recipe
+    #[must_use]
+    pub fn then(mut self, step: Step) -> Recipe {
+        self.operations.push(step);
+        self
+    }
+
+    /// Create the operations of the recipe
+    ///
+    /// # Errors
+    /// If the recipe is from a newer version or a parameter is invalid
+    pub fn to_operations(&self) -> Result<Vec<Box<dyn OperationsTrait>>, ImageErrors> {
+        if self.version > Recipe::VERSION {
+            return Err(ImageErrors::GenericString(format!(
+                "Recipe version {} is newer than the supported version {}",
+                self.version,
+                Recipe::VERSION
+            )));
+        }
+        self.operations.iter().map(Step::to_operation).collect()
+    }
+
+    /// Run the recipe on an image
+    ///
+    /// # Errors
+    /// See [`to_operations`](Self::to_operations), and errors from the operations
+    pub fn execute(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        for operation in self.to_operations()? {
+            operation.execute(image)?;
+        }
+        Ok(())
+    }
+
+    /// Add the operations of the recipe to a pipeline
+    ///
+    /// # Errors
+    /// See [`to_operations`](Self::to_operations)
+    pub fn chain_to(&self, pipeline: &mut Pipeline) -> Result<(), ImageErrors> {
+        for operation in self.to_operations()? {
+            pipeline.chain_operations(operation);
+        }
+        Ok(())
+    }
+}
+
+impl Default for Recipe {
+    fn default() -> Self {
+        Recipe::new()
+    }
+}
+
+impl Step {
+    /// Create the operation of this step
+    ///
+    /// # Errors
+    /// If an enumerated parameter has an unknown name
+    pub fn to_operation(&self) -> Result<Box<dyn OperationsTrait>, ImageErrors> {
+        let operation: Box<dyn OperationsTrait> = match self {
+            Step::Invert => Box::new(Invert::new()),
+            Step::Brighten { value } => Box::new(Brighten::new(*value)),
+            Step::Gamma { value } => Box::new(Gamma::new(*value)),
+            Step::Contrast { contrast } => Box::new(Contrast::new(*contrast)),
+            Step::Exposure { exposure, black } => Box::new(Exposure::new(*exposure, *black)),
+            Step::StretchContrast { lower, upper } => {
+                Box::new(StretchContrast::new(*lower, *upper))
+            }
+            Step::Threshold { threshold, method } => {
+                let method = ThresholdMethod::from_string_result(method)
+                    .map_err(ImageErrors::GenericString)?;
+                Box::new(Threshold::new(*threshold, method))
+            }
+            Step::Grayscale => Box::new(ColorspaceConv::new(ColorSpace::Luma)),
+            Step::Flip { direction } => {
+                let direction = match direction.as_str() {
+                    "horizontal" => FlipDirection::Horizontal,
+                    "vertical" => FlipDirection::Vertical,
+                    "x_axis" => FlipDirection::MirrorXAxis,
+                    _ => return Err(unknown("flip direction", direction))
+                };
+                Box::new(Flip::new(direction))
+            }
+            Step::Mirror { mode } => {
+                let mode = match mode.as_str() {
+                    "north" => MirrorMode::North,
+                    "south" => MirrorMode::South,
+                    "east" => MirrorMode::East,
+                    "west" => MirrorMode::West,
+                    _ => return Err(unknown("mirror mode", mode))
+                };
+                Box::new(Mirror::new(mode))
+            }
+            Step::Transpose => Box::new(Transpose::new()),
+            Step::Rotate { angle } => Box::new(Rotate::new(*angle)),
+            Step::Crop {
+                width,
+                height,
+                x,
+                y
+            } => Box::new(Crop::new(*width, *height, *x, *y)),
+            Step::Resize {
+                width,
+                height,
+                method
+            } => {
+                let method = match method.as_str() {
+                    "bilinear" => ResizeMethod::Bilinear,
+                    "bicubic" => ResizeMethod::Bicubic,
+                    _ => return Err(unknown("resize method", method))
+                };
+                Box::new(Resize::new(*width, *height, method))
+            }
+            Step::GaussianBlur { sigma } => Box::new(GaussianBlur::new(*sigma)),
+            Step::BoxBlur { radius } => Box::new(BoxBlur::new(*radius)),
+            Step::Median { radius } => Box::new(Median::new(*radius)),
+            Step::Unsharpen {
+                sigma,
+                threshold,
+                percentage
+            } => Box::new(Unsharpen::new(*sigma, *threshold, *percentage)),
+            Step::HsvAdjust {
+                hue,
+                saturation,
+                lightness
+            } => Box::new(HsvAdjust::new(*hue, *saturation, *lightness)),
+            Step::Statistic { radius, mode } => {
+                let mode = SpatialOperations::from_string_result(mode)
+                    .map_err(ImageErrors::GenericString)?;
+                Box::new(SpatialOps::new(*radius, mode))
+            }
+        };
+        Ok(operation)
+    }
+}
+
+fn unknown(parameter: &str, value: &str) -> ImageErrors {
+    ImageErrors::GenericString(format!("Unknown {parameter} {value:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+
+    use crate::recipe::{Recipe, Step};
+
+    #[test]
+    fn test_recipe_round_trip() {
+        let recipe = Recipe::new()
+            .then(Step::Invert)
+            .then(Step::Crop {
+                width:  2,
+                height: 3,
+                x:      1,
+                y:      1
+            })
+            .then(Step::Threshold {
+                threshold: 100.0,
+                method:    "binary".to_string()
+            });
+        let json = serde_json::to_string(&recipe).unwrap();
+        assert_eq!(serde_json::from_str::<Recipe>(&json).unwrap(), recipe);
+
+        let mut image = Image::fill(10_u8, ColorSpace::Luma, 8, 8);
+        recipe.execute(&mut image).unwrap();
+        assert_eq!(image.dimensions(), (2, 3));
+        assert_eq!(image.flatten_frames::<u8>()[0], [255; 6]);
+
+        // unknown parameters and newer versions are rejected
+        let bad = Recipe::new().then(Step::Flip {
+            direction: "sideways".to_string()
+        });
+        assert!(bad.to_operations().is_err());
+        let newer: Recipe = serde_json::from_str(r#"{"version": 2, "operations": []}"#).unwrap();
+        assert!(newer.to_operations().is_err());
+    }
+
+    #[test]
+    fn test_steps_match_parameters() {
+        let steps = [
+            Step::Exposure {
+                exposure: 1.0,
+                black:    0.0
+            },
+            Step::Resize {
+                width:  1,
+                height: 1,
+                method: "bicubic".to_string()
+            },
+            Step::HsvAdjust {
+                hue:        0.0,
+                saturation: 1.0,
+                lightness:  1.0
+            },
+            Step::Flip {
+                direction: "vertical".to_string()
+            }
+        ];
+        for step in steps {
+            let json = serde_json::to_value(&step).unwrap();
+            let parameters = step.to_operation().unwrap().parameters();
+
+            assert!(!parameters.is_empty());
+            for parameter in parameters {
+                assert!(
+                    json.get(parameter.name).is_some(),
+                    "{step:?} {}",
+                    parameter.name
+                );
+            }
+        }
+    }
+}
@@ -9,23 +9,34 @@
 //! Resize an image to a new width and height
 //!
 //!
-//! Currently only implements a simple bilinear resizer, future plans are to have
+//! Currently implements bilinear and bicubic resizers, future plans are to have
 //! more complicated resizers implemented.
 //!
+//! Both are separable, so images are resampled horizontally then vertically,
+//! computing in `f32` for every depth.
+//! The passes have AVX2 and NEON versions, used for [`Resize`] and
+//! [`resize_u8`], [`resize_u16`] and [`resize_f32`].
 //!
 use zune_core::bit_depth::BitType;
 use zune_image::channel::Channel;
 use zune_image::errors::ImageErrors;
 use zune_image::image::Image;
-use zune_image::traits::OperationsTrait;
+use zune_image::metadata::AlphaState;
+use zune_image::traits::{OperationsTrait, Parameter, ParameterKind, ParameterValue};
 
+use crate::linearize::execute_in_linear_light;
+use crate::resize::separable::{Taps, VerticalFn};
 use crate::traits::NumOps;
 use crate::utils::execute_on;
 
+mod avx2;
 mod bicubic;
 mod bilinear;
+mod neon;
+mod separable;
 
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub enum ResizeMethod {
     Bilinear,
     Bicubic,
@@ -38,10 +49,12 @@ pub enum ResizeMethod {
 /// Resize an image to a new width and height
 /// using the resize method specified
 #[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Resize {
     new_width: usize,
     new_height: usize,
     method: ResizeMethod,
+    linear_light: bool,
 }
 
 impl Resize {
@@ -57,8 +70,20 @@ impl Resize {
             new_width,
             new_height,
             method,
+            linear_light: false,
         }
     }
+    /// Resize in linear light
+    ///
+    /// When set, gamma encoded sRGB images are linearized before resizing and
+    /// encoded back afterwards, this avoids darkened edges and fine details.
+    ///
+    /// See [`linearize`](crate::linearize) for more details
+    #[must_use]
+    pub fn set_linear_light(mut self, linear_light: bool) -> Self {
+        self.linear_light = linear_light;
+        self
+    }
 }
 
 impl OperationsTrait for Resize {
@@ -66,8 +91,35 @@ impl OperationsTrait for Resize {
         "Resize"
     }
 
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                name:        "width",
+                description: "New width",
+                kind:        ParameterKind::Integer { min: 1, max: 65535 },
+                default:     ParameterValue::Integer(1)
+            },
+            Parameter {
+                name:        "height",
+                description: "New height",
+                kind:        ParameterKind::Integer { min: 1, max: 65535 },
+                default:     ParameterValue::Integer(1)
+            },
+            Parameter {
+                name:        "method",
+                description: "Resampling filter",
+                kind:        ParameterKind::Choice(&["bilinear", "bicubic"]),
+                default:     ParameterValue::Choice("bilinear")
+            }
+        ]
+    }
+
     #[allow(clippy::too_many_lines)]
     fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        if self.linear_light {
+            let resize = self.set_linear_light(false);
+            return execute_in_linear_light(image, |image| resize.execute_impl(image));
+        }
         let (old_w, old_h) = image.dimensions();
         let depth = image.depth().bit_type();
 
@@ -76,7 +128,7 @@ impl OperationsTrait for Resize {
         let resize_fn = |channel: &mut Channel| -> Result<(), ImageErrors> {
             let mut new_channel = Channel::new_with_bit_type(new_length, depth);
             match depth {
-                BitType::U8 => resize::<u8>(
+                BitType::U8 => resize_u8(
                     channel.reinterpret_as()?,
                     new_channel.reinterpret_as_mut()?,
                     self.method,
@@ -85,7 +137,7 @@ impl OperationsTrait for Resize {
                     self.new_width,
                     self.new_height,
                 ),
-                BitType::U16 => resize::<u16>(
+                BitType::U16 => resize_u16(
                     channel.reinterpret_as()?,
                     new_channel.reinterpret_as_mut()?,
                     self.method,
@@ -96,7 +148,7 @@ impl OperationsTrait for Resize {
                 ),
 
                 BitType::F32 => {
-                    resize::<f32>(
+                    resize_f32(
                         channel.reinterpret_as()?,
                         new_channel.reinterpret_as_mut()?,
                         self.method,
@@ -114,11 +166,28 @@ impl OperationsTrait for Resize {
         execute_on(resize_fn, image, false)?;
         image.set_dimensions(self.new_width, self.new_height);
 
+        // keep the physical size of the image
+        if let Some(resolution) = image.metadata().resolution() {
+            #[allow(clippy::cast_precision_loss)]
+            let (x_scale, y_scale) = (
+                self.new_width as f32 / old_w.max(1) as f32,
+                self.new_height as f32 / old_h.max(1) as f32
+            );
+
+            image
+                .metadata_mut()
+                .set_resolution(resolution.scaled(x_scale, y_scale));
+        }
+
         Ok(())
     }
     fn supported_types(&self) -> &'static [BitType] {
         &[BitType::U8, BitType::U16, BitType::F32]
     }
+
+    fn alpha_state(&self) -> Option<AlphaState> {
+        Some(AlphaState::PreMultiplied)
+    }
 }
 
 /// Return the image resize dimensions that would not cause a distortion
@@ -177,21 +246,103 @@ pub fn resize<T>(
     in_image: &[T], out_image: &mut [T], method: ResizeMethod, in_width: usize, in_height: usize,
     out_width: usize, out_height: usize,
 ) where
-    T: Copy + NumOps<T> + Default,
-    f32: std::convert::From<T>,
+    T: Copy + NumOps<T> + Default + Send + Sync,
+    f32: core::convert::From<T>,
 {
-    match method {
-        ResizeMethod::Bilinear => {
-            bilinear::bilinear_impl(
-                in_image, out_image, in_width, in_height, out_width, out_height,
-            );
-        }
-        ResizeMethod::Bicubic => {
-            bicubic::bicubic_resample(
-                in_image, out_image, in_width, in_height, out_width, out_height,
-            );
-        }
+    resize_with(
+        in_image,
+        out_image,
+        method,
+        (in_width, in_height),
+        (out_width, out_height),
+        separable::vertical_scalar::<T>,
+    );
+}
+
+/// Resize a `u8` channel to new dimensions
+///
+/// Same as [`resize`], with the SIMD versions of both passes
+///
+/// # Panics
+/// Same as [`resize`].
+pub fn resize_u8(
+    in_image: &[u8], out_image: &mut [u8], method: ResizeMethod, in_width: usize,
+    in_height: usize, out_width: usize, out_height: usize,
+) {
+    resize_with(
+        in_image,
+        out_image,
+        method,
+        (in_width, in_height),
+        (out_width, out_height),
+        separable::VERTICAL_U8.get(),
+    );
+}
+
+/// Resize a `u16` channel to new dimensions
+///
+/// Same as [`resize`], with the SIMD versions of both passes
+///
+/// # Panics
+/// Same as [`resize`].
+pub fn resize_u16(
+    in_image: &[u16], out_image: &mut [u16], method: ResizeMethod, in_width: usize,
+    in_height: usize, out_width: usize, out_height: usize,
+) {
+    resize_with(
+        in_image,
+        out_image,
+        method,
+        (in_width, in_height),
+        (out_width, out_height),
+        separable::VERTICAL_U16.get(),
+    );
+}
+
+/// Resize a `f32` channel to new dimensions
+///
+/// Same as [`resize`], with the SIMD versions of both passes
+///
+/// # Panics
+/// Same as [`resize`].
+pub fn resize_f32(
+    in_image: &[f32], out_image: &mut [f32], method: ResizeMethod, in_width: usize,
+    in_height: usize, out_width: usize, out_height: usize,
+) {
+    resize_with(
+        in_image,
+        out_image,
+        method,
+        (in_width, in_height),
+        (out_width, out_height),
+        separable::VERTICAL_F32.get(),
+    );
+}
+
+/// Resize a `(width, height)` channel to `(width, height)`, blending output
+/// rows with `vertical`
+fn resize_with<T>(
+    in_image: &[T], out_image: &mut [T], method: ResizeMethod, (in_width, in_height): (usize, usize),
+    (out_width, out_height): (usize, usize), vertical: VerticalFn<T>,
+) where
+    T: Copy + Send + Sync,
+    f32: core::convert::From<T>,
+{
+    if [in_width, in_height, out_width, out_height].contains(&0) {
+        return;
     }
+    let taps: fn(usize, usize) -> Taps = match method {
+        ResizeMethod::Bilinear => bilinear::bilinear_taps,
+        ResizeMethod::Bicubic => bicubic::bicubic_taps,
+    };
+    separable::resample(
+        in_image,
+        out_image,
+        in_width,
+        &taps(in_width, out_width),
+        &taps(in_height, out_height),
+        vertical,
+    );
 }
 
 
@@ -261,7 +412,58 @@ mod benchmarks {
 }
 #[cfg(test)]
 mod tests {
-    use crate::resize::{resize, ResizeMethod};
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::metadata::Resolution;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::resize::{resize, resize_u16, resize_u8, Resize, ResizeMethod};
+
+    #[test]
+    fn test_resize_keeps_physical_size() {
+        let mut image = Image::fill(0_u8, ColorSpace::RGB, 300, 150);
+        image
+            .metadata_mut()
+            .set_resolution(Resolution::from_dpi(300.0));
+
+        Resize::new(150, 150, ResizeMethod::Bilinear)
+            .execute(&mut image)
+            .unwrap();
+
+        let resolution = image.metadata().resolution().unwrap();
+        assert_eq!(resolution.dpi(), Some((150.0, 300.0)));
+        assert_eq!(image.metadata().physical_size(), Some((1.0, 0.5)));
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation)]
+    fn test_resize_simd_matches_scalar() {
+        let (width, height) = (301, 97);
+        let in_vec: Vec<u16> = (0..width * height)
+            .map(|x| ((x * 37) % 1021 * 64) as u16)
+            .collect();
+        let in_u8: Vec<u8> = in_vec.iter().map(|x| (x >> 8) as u8).collect();
+
+        for method in [ResizeMethod::Bilinear, ResizeMethod::Bicubic] {
+            for (new_width, new_height) in [(1, 1), (150, 40), (640, 301), (13, 300)] {
+                let mut expected = vec![0_u16; new_width * new_height];
+                let mut out = vec![0_u16; new_width * new_height];
+
+                resize(&in_vec, &mut expected, method, width, height, new_width, new_height);
+                resize_u16(&in_vec, &mut out, method, width, height, new_width, new_height);
+
+                assert!(out.iter().zip(&expected).all(|(a, b)| a.abs_diff(*b) <= 1));
+
+                let mut expected = vec![0_u8; new_width * new_height];
+                let mut out = vec![0_u8; new_width * new_height];
+
+                resize(&in_u8, &mut expected, method, width, height, new_width, new_height);
+                resize_u8(&in_u8, &mut out, method, width, height, new_width, new_height);
+
+                assert!(out.iter().zip(&expected).all(|(a, b)| a.abs_diff(*b) <= 1));
+            }
+        }
+    }
 
     #[test]
     fn bench_resize_cubic() {
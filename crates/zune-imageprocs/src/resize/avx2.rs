@@ -0,0 +1,131 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+#![cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#![cfg(feature = "avx2")]
+//! AVX2 resampling passes
+//!
+//! The horizontal pass gathers tap `k` of eight consecutive output pixels at once, and
+//! the vertical pass blends eight pixels of every row at once, both with fused multiply
+//! adds, so these need FMA3 besides AVX2.
+//!
+//! Integer outputs are truncated and saturated like the scalar `as` casts, by packing
+//! the truncated 32 bit integers with unsigned saturation.
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+use crate::resize::separable::{horizontal_scalar_from, vertical_scalar_from, Taps};
+
+#[target_feature(enable = "avx2,fma")]
+pub(crate) unsafe fn horizontal_avx2(row: &[f32], out: &mut [f32], taps: &Taps) {
+    taps.check(row, out);
+
+    let out_len = out.len();
+    let (starts, weights) = (taps.starts(), taps.weights());
+    let chunks = out_len / 8;
+
+    for i in (0..chunks).map(|x| x * 8) {
+        // SAFETY: the taps of every output pixel are within the row, and
+        // there are `taps` rows of `out_len` weights
+        let mut index = _mm256_loadu_si256(starts.as_ptr().add(i).cast());
+        let mut sum = _mm256_setzero_ps();
+
+        for k in 0..taps.taps() {
+            let pixels = _mm256_i32gather_ps::<4>(row.as_ptr(), index);
+            let weight = _mm256_loadu_ps(weights.as_ptr().add(k * out_len + i));
+
+            sum = _mm256_fmadd_ps(pixels, weight, sum);
+            index = _mm256_add_epi32(index, _mm256_set1_epi32(1));
+        }
+        _mm256_storeu_ps(out.as_mut_ptr().add(i), sum);
+    }
+    horizontal_scalar_from(row, out, taps, chunks * 8);
+}
+
+/// Blend pixels `x..x + 8` of every row
+#[target_feature(enable = "avx2,fma")]
+unsafe fn blend(rows: &[&[f32]], weights: &[f32], x: usize) -> __m256 {
+    let mut sum = _mm256_setzero_ps();
+
+    for (row, weight) in rows.iter().zip(weights) {
+        let pixels = _mm256_loadu_ps(row.as_ptr().add(x));
+        sum = _mm256_fmadd_ps(pixels, _mm256_set1_ps(*weight), sum);
+    }
+    sum
+}
+
+/// Truncate eight floats and pack them to 16 bits with unsigned saturation
+#[target_feature(enable = "avx2")]
+unsafe fn pack_u16(sum: __m256) -> __m128i {
+    let values = _mm256_cvttps_epi32(sum);
+
+    _mm_packus_epi32(
+        _mm256_castsi256_si128(values),
+        _mm256_extracti128_si256::<1>(values)
+    )
+}
+
+/// Truncate eight floats and pack them to 16 bits with signed saturation,
+/// since packing to u8 takes signed 16 bit integers
+#[target_feature(enable = "avx2")]
+unsafe fn pack_i16(sum: __m256) -> __m128i {
+    let values = _mm256_cvttps_epi32(sum);
+
+    _mm_packs_epi32(
+        _mm256_castsi256_si128(values),
+        _mm256_extracti128_si256::<1>(values)
+    )
+}
+
+#[target_feature(enable = "avx2,fma")]
+pub(crate) unsafe fn vertical_avx2_u8(rows: &[&[f32]], weights: &[f32], out: &mut [u8]) {
+    assert!(rows.iter().all(|x| x.len() >= out.len()));
+
+    let chunks = out.len() / 8;
+
+    for x in (0..chunks).map(|x| x * 8) {
+        let values = pack_i16(blend(rows, weights, x));
+        // SAFETY: x + 8 <= out.len()
+        _mm_storel_epi64(
+            out.as_mut_ptr().add(x).cast(),
+            _mm_packus_epi16(values, values)
+        );
+    }
+    vertical_scalar_from(rows, weights, out, chunks * 8);
+}
+
+#[target_feature(enable = "avx2,fma")]
+pub(crate) unsafe fn vertical_avx2_u16(rows: &[&[f32]], weights: &[f32], out: &mut [u16]) {
+    assert!(rows.iter().all(|x| x.len() >= out.len()));
+
+    let chunks = out.len() / 8;
+
+    for x in (0..chunks).map(|x| x * 8) {
+        // SAFETY: x + 8 <= out.len()
+        _mm_storeu_si128(
+            out.as_mut_ptr().add(x).cast(),
+            pack_u16(blend(rows, weights, x))
+        );
+    }
+    vertical_scalar_from(rows, weights, out, chunks * 8);
+}
+
+#[target_feature(enable = "avx2,fma")]
+pub(crate) unsafe fn vertical_avx2_f32(rows: &[&[f32]], weights: &[f32], out: &mut [f32]) {
+    assert!(rows.iter().all(|x| x.len() >= out.len()));
+
+    let chunks = out.len() / 8;
+
+    for x in (0..chunks).map(|x| x * 8) {
+        // SAFETY: x + 8 <= out.len()
+        _mm256_storeu_ps(out.as_mut_ptr().add(x), blend(rows, weights, x));
+    }
+    vertical_scalar_from(rows, weights, out, chunks * 8);
+}
@@ -1,12 +1,14 @@
 #![allow(dead_code)]
 
-use crate::traits::NumOps;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use crate::resize::separable::Taps;
 
 #[cfg(feature = "portable-simd")]
 mod std_simd {
-    use std::simd::num::SimdFloat;
-    use std::simd::{f32x4};
-    use std::simd::cmp::SimdPartialOrd;
+    use core::simd::num::SimdFloat;
+    use core::simd::{f32x4};
+    use core::simd::cmp::SimdPartialOrd;
 
     #[inline(always)]
     pub fn bicubic_kernel_simd_inner(x: f32x4) -> [f32; 4] {
@@ -110,99 +112,21 @@ fn bicubic_function(y0: isize, src_y: f32) -> [f32; 4] {
     let dy3 = src_y - yy3 as f32;
     return bicubic_scalar([dy0, dy1, dy2, dy3]);
 }
-pub fn bicubic_resample<T>(input: &[T], output: &mut [T], input_width: usize, input_height: usize, new_width: usize, new_height: usize)
-where
-    T: Copy + NumOps<T>,
-    f32: std::convert::From<T>,
-{
-    let scale_y = input_height as f32 / new_height as f32;
-    let scale_x = input_width as f32 / new_width as f32;
-
-    // pre-calculate all the x coefficients, since they will be repeated for every y-value
-    //
-    // Before
-    //  test resize::benchmarks::bench_resize_cubic     ... bench:  18,673,279.20 ns/iter (+/- 1,320,342.58)
-    //
-    // After
-    //  test resize::benchmarks::bench_resize_cubic     ... bench:  12,923,833.40 ns/iter (+/- 528,285.41)
-    let mut x_mega_coeffs = vec![[0.0; 4]; new_width];
-
-    for x in 0..new_width {
-        let src_x = x as f32 * scale_x;
-        let x0 = inline_floor(src_x) as isize;
-
-        x_mega_coeffs[x] = bicubic_function(x0, src_x);
-    }
-
-    for (y, output_stride) in (0..new_height).zip(output.chunks_exact_mut(new_width)) {
-        let src_y = y as f32 * scale_y;
-        // the ideal one is src_y.floor(), but
-        // trunk == floor for +ve values and
-        // src_y can't be negative.
-        let y0 = src_y.trunc() as isize;
-
-        let y_coeffs = bicubic_function(y0, src_y);
-
-        for (x, x_coeffs) in (0..new_width).zip(x_mega_coeffs.iter()) {
-            let src_x = x as f32 * scale_x;
-            let x0 = src_x.trunc() as usize;
-            let mut sum = 0.0;
-            let mut weight_sum = 0.0;
-
-            if (y0 > 0 && y0 + 2 < input_height as isize) && (x0 > 0 && x0 + 2 < input_width) {
-                // common happy path
-                // inside the boundaries
-                let mut yy = y0 - 1;
-                // Improves perf by 15% in m3 so important
-                for weight_y in y_coeffs {
-                    let width_stride = yy as usize * input_width;
-                    let start = x0 + width_stride;
-                    let input_stride_c = &input[start - 1..start + 3];
-
-                    debug_assert!(input_stride_c.len() == 4);
-
-                    for (pix, weight_x) in input_stride_c.iter().zip(x_coeffs) {
-                        let weight = weight_y * weight_x;
-                        let pixel = f32::from(*pix);
-                        sum += pixel * weight;
-                        weight_sum += weight;
-                    }
-                    yy += 1;
-                }
-                // [cae]: I assume weight sum can never be zero, since we must multiply something
-                debug_assert!(weight_sum != 0.0);
-                if weight_sum != 0.0 {
-                    output_stride[x] = T::from_f32(sum / weight_sum);
-                }
-            } else {
-                // path that is taken in boundaries
-                // less taken but has to be careful to always be in bounds
-                for ky in -1..=2 {
-                    let yy = y0 + ky;
-
-                    if yy >= 0 && yy < input_height as isize {
-                        let weight_y = y_coeffs[(ky + 1) as usize];
-                        let width_stride = yy as usize * input_width;
-
-                        for kx in -1..=2 {
-                            let xx = (x0 as isize) + kx;
-
-                            if xx >= 0 && xx < input_width as isize {
-                                let weight_x = x_coeffs[(kx + 1) as usize];
-                                let weight = weight_y * weight_x;
-
-                                let pixel = f32::from(input[width_stride + xx as usize]);
-
-                                sum += pixel * weight;
-                                weight_sum += weight;
-                            }
-                        }
-                    }
-                }
-                output_stride[x] = if weight_sum > 0.0 { T::from_f32(sum / weight_sum) } else { T::from_f32(0.0) };
-            }
-        }
-    }
+/// Taps of bicubic interpolation, every output pixel blends the four input pixels around it
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+)]
+pub(crate) fn bicubic_taps(in_len: usize, out_len: usize) -> Taps {
+    let scale = in_len as f32 / out_len as f32;
+
+    // pixels past the edges are dropped and the rest normalized
+    Taps::new(in_len, out_len, 4, |i| {
+        let src = i as f32 * scale;
+        let x0 = inline_floor(src) as isize;
+
+        (x0 - 1, bicubic_function(x0, src))
+    })
 }
 
 
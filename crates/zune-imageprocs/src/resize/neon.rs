@@ -0,0 +1,99 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+#![cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+#![cfg(feature = "neon")]
+//! NEON resampling passes
+//!
+//! Same as the [AVX2](super::avx2) versions four pixels at a time, NEON has no gather
+//! so the horizontal pass loads the taps of four output pixels into a register lane by lane.
+//!
+//! Float to unsigned conversions truncate and saturate negative values to zero,
+//! and narrowing saturates, so integer outputs match the scalar `as` casts.
+use core::arch::aarch64::*;
+
+use crate::resize::separable::{horizontal_scalar_from, vertical_scalar_from, Taps};
+
+pub(crate) unsafe fn horizontal_neon(row: &[f32], out: &mut [f32], taps: &Taps) {
+    taps.check(row, out);
+
+    let out_len = out.len();
+    let (starts, weights) = (taps.starts(), taps.weights());
+    let chunks = out_len / 4;
+
+    for i in (0..chunks).map(|x| x * 4) {
+        let start = &starts[i..i + 4];
+        let mut sum = vdupq_n_f32(0.0);
+
+        for k in 0..taps.taps() {
+            let pixels = [
+                row[start[0] as usize + k],
+                row[start[1] as usize + k],
+                row[start[2] as usize + k],
+                row[start[3] as usize + k]
+            ];
+            // SAFETY: there are `taps` rows of `out_len` weights
+            let weight = vld1q_f32(weights.as_ptr().add(k * out_len + i));
+
+            sum = vfmaq_f32(sum, vld1q_f32(pixels.as_ptr()), weight);
+        }
+        vst1q_f32(out.as_mut_ptr().add(i), sum);
+    }
+    horizontal_scalar_from(row, out, taps, chunks * 4);
+}
+
+/// Blend pixels `x..x + 4` of every row
+unsafe fn blend(rows: &[&[f32]], weights: &[f32], x: usize) -> float32x4_t {
+    let mut sum = vdupq_n_f32(0.0);
+
+    for (row, weight) in rows.iter().zip(weights) {
+        sum = vfmaq_n_f32(sum, vld1q_f32(row.as_ptr().add(x)), *weight);
+    }
+    sum
+}
+
+pub(crate) unsafe fn vertical_neon_u8(rows: &[&[f32]], weights: &[f32], out: &mut [u8]) {
+    assert!(rows.iter().all(|x| x.len() >= out.len()));
+
+    let chunks = out.len() / 8;
+
+    for x in (0..chunks).map(|x| x * 8) {
+        let low = vqmovn_u32(vcvtq_u32_f32(blend(rows, weights, x)));
+        let high = vqmovn_u32(vcvtq_u32_f32(blend(rows, weights, x + 4)));
+        // SAFETY: x + 8 <= out.len()
+        vst1_u8(out.as_mut_ptr().add(x), vqmovn_u16(vcombine_u16(low, high)));
+    }
+    vertical_scalar_from(rows, weights, out, chunks * 8);
+}
+
+pub(crate) unsafe fn vertical_neon_u16(rows: &[&[f32]], weights: &[f32], out: &mut [u16]) {
+    assert!(rows.iter().all(|x| x.len() >= out.len()));
+
+    let chunks = out.len() / 4;
+
+    for x in (0..chunks).map(|x| x * 4) {
+        // SAFETY: x + 4 <= out.len()
+        vst1_u16(
+            out.as_mut_ptr().add(x),
+            vqmovn_u32(vcvtq_u32_f32(blend(rows, weights, x)))
+        );
+    }
+    vertical_scalar_from(rows, weights, out, chunks * 4);
+}
+
+pub(crate) unsafe fn vertical_neon_f32(rows: &[&[f32]], weights: &[f32], out: &mut [f32]) {
+    assert!(rows.iter().all(|x| x.len() >= out.len()));
+
+    let chunks = out.len() / 4;
+
+    for x in (0..chunks).map(|x| x * 4) {
+        // SAFETY: x + 4 <= out.len()
+        vst1q_f32(out.as_mut_ptr().add(x), blend(rows, weights, x));
+    }
+    vertical_scalar_from(rows, weights, out, chunks * 4);
+}
@@ -0,0 +1,421 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Separable resampling shared by the resize filters
+//!
+//! Every filter weighs input pixels by their horizontal distance times their vertical
+//! distance, so resizing is done in two passes, a horizontal one resampling input rows
+//! to the new width and a vertical one blending those rows into output rows.
+//!
+//! Both passes work on `f32` rows for every sample type, input rows are converted
+//! when resampled horizontally and output rows are converted back when blended.
+//! The vertical pass weighs whole rows by the same weights, and the horizontal pass
+//! gathers the inputs of consecutive output pixels, so both have SIMD versions,
+//! see [`avx2`](super::avx2) and [`neon`](super::neon).
+//!
+//! Output rows only need the horizontally resampled rows under their taps, which move
+//! down as output rows do, so each band of output rows keeps just the last `taps` of
+//! them, and rows a downscale skips are never resampled.
+use alloc::vec;
+use alloc::vec::Vec;
+
+use zune_core::simd::{Dispatcher, Isa};
+
+use crate::traits::NumOps;
+use crate::utils::for_each_row_band;
+
+/// Most input pixels an output pixel is made of along an axis
+pub(crate) const MAX_TAPS: usize = 4;
+
+/// The input pixels and weights making up every output pixel along one axis
+pub(crate) struct Taps {
+    /// Input pixels along the axis
+    in_len:    usize,
+    /// Input pixels per output pixel
+    per_pixel: usize,
+    /// First input pixel of every output pixel, the next `taps` ones are always in bounds
+    starts:    Vec<u32>,
+    /// Weight `k` of output pixel `i` is at `k * starts.len() + i`
+    weights:   Vec<f32>
+}
+
+impl Taps {
+    /// Create the taps for resampling `in_len` pixels to `out_len`
+    ///
+    /// `filter` returns the first input pixel of an output pixel and the weights of `taps`
+    /// pixels from there, pixels falling outside the input are dropped and the
+    /// remaining weights normalized
+    ///
+    /// # Panics
+    /// If `in_len` is zero or doesn't fit in an `i32`
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap,
+        clippy::cast_sign_loss
+    )]
+    pub(crate) fn new(
+        in_len: usize, out_len: usize, taps: usize,
+        filter: impl Fn(usize) -> (isize, [f32; MAX_TAPS])
+    ) -> Taps {
+        assert!(in_len > 0 && i32::try_from(in_len).is_ok());
+        assert!(taps <= MAX_TAPS);
+
+        // tiny inputs have less pixels than the filter has taps
+        let used = taps.min(in_len);
+
+        let mut starts = vec![0; out_len];
+        let mut weights = vec![0.0; used * out_len];
+
+        for (i, start) in starts.iter_mut().enumerate() {
+            let (first, filter_weights) = filter(i);
+            let window = first.clamp(0, (in_len - used) as isize);
+
+            for (k, weight) in filter_weights[..taps].iter().enumerate() {
+                let pos = first + k as isize;
+
+                if pos >= 0 && (pos as usize) < in_len {
+                    weights[(pos - window) as usize * out_len + i] += weight;
+                }
+            }
+            let sum: f32 = (0..used).map(|k| weights[k * out_len + i]).sum();
+
+            if sum != 0.0 {
+                (0..used).for_each(|k| weights[k * out_len + i] /= sum);
+            }
+            *start = window as u32;
+        }
+        Taps {
+            in_len,
+            per_pixel: used,
+            starts,
+            weights
+        }
+    }
+
+    /// Return the weights of output pixel `i`
+    fn weights_of(&self, i: usize) -> [f32; MAX_TAPS] {
+        let out_len = self.starts.len();
+        let mut weights = [0.0; MAX_TAPS];
+
+        for (k, weight) in weights[..self.per_pixel].iter_mut().enumerate() {
+            *weight = self.weights[k * out_len + i];
+        }
+        weights
+    }
+
+    /// Input pixels per output pixel
+    #[allow(dead_code)] // only the SIMD kernels need these
+    pub(crate) const fn taps(&self) -> usize {
+        self.per_pixel
+    }
+
+    /// The first input pixel of every output pixel
+    #[allow(dead_code)]
+    pub(crate) fn starts(&self) -> &[u32] {
+        &self.starts
+    }
+
+    /// The weights of every output pixel, weight `k` of
+    /// output pixel `i` is at `k * starts().len() + i`
+    #[allow(dead_code)]
+    pub(crate) fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    /// Check that a horizontal kernel can resample `row` into `out`
+    ///
+    /// # Panics
+    /// If the lengths don't match those the taps were created with
+    pub(crate) fn check(&self, row: &[f32], out: &[f32]) {
+        assert_eq!(row.len(), self.in_len);
+        assert_eq!(out.len(), self.starts.len());
+    }
+}
+
+/// Resamples a row to the new width, `(row, out, taps)`
+///
+/// Kernels must [check](Taps::check) their arguments
+pub(crate) type HorizontalFn = unsafe fn(&[f32], &mut [f32], &Taps);
+
+/// Blends horizontally resampled rows into an output row, weighing row `k`
+/// by weight `k`, `(rows, weights, out)`
+///
+/// Every row must be at least as long as `out`
+pub(crate) type VerticalFn<T> = unsafe fn(&[&[f32]], &[f32], &mut [T]);
+
+/// Resample `row` from output pixel `first` to the end of `out`, for kernels that
+/// handle the pixels up to there
+pub(crate) fn horizontal_scalar_from(row: &[f32], out: &mut [f32], taps: &Taps, first: usize) {
+    taps.check(row, out);
+
+    let out_len = out.len();
+
+    for (i, out) in out.iter_mut().enumerate().skip(first) {
+        let start = taps.starts[i] as usize;
+        let pixels = &row[start..start + taps.per_pixel];
+
+        *out = pixels
+            .iter()
+            .enumerate()
+            .map(|(k, pixel)| pixel * taps.weights[k * out_len + i])
+            .sum();
+    }
+}
+
+pub(crate) fn horizontal_scalar(row: &[f32], out: &mut [f32], taps: &Taps) {
+    horizontal_scalar_from(row, out, taps, 0);
+}
+
+/// Blend the rows from pixel `first` to the end of `out`, for kernels that
+/// handle the pixels up to there
+pub(crate) fn vertical_scalar_from<T: NumOps<T>>(
+    rows: &[&[f32]], weights: &[f32], out: &mut [T], first: usize
+) {
+    for (x, out) in out.iter_mut().enumerate().skip(first) {
+        let sum = rows.iter().zip(weights).map(|(row, w)| row[x] * w).sum();
+        *out = T::from_f32(sum);
+    }
+}
+
+pub(crate) fn vertical_scalar<T: NumOps<T>>(rows: &[&[f32]], weights: &[f32], out: &mut [T]) {
+    vertical_scalar_from(rows, weights, out, 0);
+}
+
+// SAFETY: every kernel only uses instructions of its instruction set
+static HORIZONTAL: Dispatcher<HorizontalFn> = unsafe {
+    Dispatcher::new(
+        "resize_horizontal",
+        &[
+            #[cfg(all(feature = "avx2", any(target_arch = "x86", target_arch = "x86_64")))]
+            (Isa::Avx2Fma, super::avx2::horizontal_avx2),
+            #[cfg(all(feature = "neon", target_arch = "aarch64", target_feature = "neon"))]
+            (Isa::Neon, super::neon::horizontal_neon),
+            (Isa::Scalar, horizontal_scalar)
+        ]
+    )
+};
+
+// SAFETY: every kernel only uses instructions of its instruction set
+pub(crate) static VERTICAL_U8: Dispatcher<VerticalFn<u8>> = unsafe {
+    Dispatcher::new(
+        "resize_vertical_u8",
+        &[
+            #[cfg(all(feature = "avx2", any(target_arch = "x86", target_arch = "x86_64")))]
+            (Isa::Avx2Fma, super::avx2::vertical_avx2_u8),
+            #[cfg(all(feature = "neon", target_arch = "aarch64", target_feature = "neon"))]
+            (Isa::Neon, super::neon::vertical_neon_u8),
+            (Isa::Scalar, vertical_scalar::<u8>)
+        ]
+    )
+};
+
+// SAFETY: every kernel only uses instructions of its instruction set
+pub(crate) static VERTICAL_U16: Dispatcher<VerticalFn<u16>> = unsafe {
+    Dispatcher::new(
+        "resize_vertical_u16",
+        &[
+            #[cfg(all(feature = "avx2", any(target_arch = "x86", target_arch = "x86_64")))]
+            (Isa::Avx2Fma, super::avx2::vertical_avx2_u16),
+            #[cfg(all(feature = "neon", target_arch = "aarch64", target_feature = "neon"))]
+            (Isa::Neon, super::neon::vertical_neon_u16),
+            (Isa::Scalar, vertical_scalar::<u16>)
+        ]
+    )
+};
+
+// SAFETY: every kernel only uses instructions of its instruction set
+pub(crate) static VERTICAL_F32: Dispatcher<VerticalFn<f32>> = unsafe {
+    Dispatcher::new(
+        "resize_vertical_f32",
+        &[
+            #[cfg(all(feature = "avx2", any(target_arch = "x86", target_arch = "x86_64")))]
+            (Isa::Avx2Fma, super::avx2::vertical_avx2_f32),
+            #[cfg(all(feature = "neon", target_arch = "aarch64", target_feature = "neon"))]
+            (Isa::Neon, super::neon::vertical_neon_f32),
+            (Isa::Scalar, vertical_scalar::<f32>)
+        ]
+    )
+};
+
+/// Resize a `in_width` by `in_height` channel to the length of the horizontal and
+/// vertical taps, blending output rows with `vertical`
+///
+/// # Panics
+/// If a dimension is zero, or the channels are smaller than their dimensions
+pub(crate) fn resample<T>(
+    input: &[T], output: &mut [T], in_width: usize, horizontal: &Taps, vertical: &Taps,
+    vertical_fn: VerticalFn<T>
+) where
+    T: Copy + Send + Sync,
+    f32: From<T>
+{
+    let (out_width, out_height) = (horizontal.starts.len(), vertical.starts.len());
+    let in_height = vertical.in_len;
+
+    assert_eq!(horizontal.in_len, in_width);
+    assert!(input.len() >= in_width * in_height);
+
+    let taps = vertical.per_pixel;
+    let horizontal_fn = HORIZONTAL.get();
+
+    // output rows are independent, so bands of them are resampled in parallel
+    for_each_row_band(
+        &mut output[..out_width * out_height],
+        out_width,
+        |first_row, band| {
+            let mut row = vec![0.0; in_width];
+            // the last resampled rows, input row `y` is kept at `y % taps`
+            let mut resampled = vec![0.0; taps * out_width];
+            let mut kept = [usize::MAX; MAX_TAPS];
+
+            for (y, out) in (first_row..).zip(band.chunks_exact_mut(out_width)) {
+                let start = vertical.starts[y] as usize;
+
+                for input_y in start..start + taps {
+                    let slot = input_y % taps;
+
+                    if kept[slot] != input_y {
+                        let input_row = &input[input_y * in_width..(input_y + 1) * in_width];
+
+                        for (value, pixel) in row.iter_mut().zip(input_row) {
+                            *value = f32::from(*pixel);
+                        }
+                        let out = &mut resampled[slot * out_width..(slot + 1) * out_width];
+                        // SAFETY: the dispatcher only returns kernels the cpu supports
+                        unsafe { horizontal_fn(&row, out, horizontal) };
+
+                        kept[slot] = input_y;
+                    }
+                }
+                let mut rows: [&[f32]; MAX_TAPS] = [&[]; MAX_TAPS];
+
+                for (k, rows) in rows[..taps].iter_mut().enumerate() {
+                    let slot = (start + k) % taps;
+                    *rows = &resampled[slot * out_width..(slot + 1) * out_width];
+                }
+                let weights = vertical.weights_of(y);
+                // SAFETY: the dispatcher only returns kernels the cpu supports
+                unsafe { vertical_fn(&rows[..taps], &weights[..taps], out) };
+            }
+        }
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use nanorand::Rng;
+
+    use crate::resize::separable::{
+        horizontal_scalar, vertical_scalar, Taps, HORIZONTAL, MAX_TAPS, VERTICAL_F32, VERTICAL_U16,
+        VERTICAL_U8
+    };
+
+    /// Taps of a made up filter, drifting over the edges of the input
+    #[allow(clippy::cast_possible_wrap, clippy::cast_precision_loss)]
+    fn taps(in_len: usize, out_len: usize, taps: usize) -> Taps {
+        Taps::new(in_len, out_len, taps, |i| {
+            let first = (i * in_len / out_len) as isize - 1;
+            let mut weights = [0.0; MAX_TAPS];
+
+            for (k, weight) in weights.iter_mut().enumerate() {
+                *weight = (i * 7 + k * 3) as f32 % 5.0 + 1.0;
+            }
+            (first, weights)
+        })
+    }
+
+    #[test]
+    fn test_taps_stay_in_bounds() {
+        for (in_len, out_len) in [(1, 9), (2, 5), (3, 3), (100, 37), (40, 213)] {
+            for n in [2, 4] {
+                let taps = taps(in_len, out_len, n);
+
+                assert_eq!(taps.taps(), n.min(in_len));
+                assert!(taps
+                    .starts()
+                    .iter()
+                    .all(|x| *x as usize + taps.taps() <= in_len));
+
+                for i in 0..out_len {
+                    let sum: f32 = taps.weights_of(i).iter().sum();
+                    assert!((sum - 1.0).abs() < 1e-5);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_resize_kernels_match_scalar() {
+        let mut rng = nanorand::WyRand::new_seed(7);
+
+        for (in_len, out_len) in [(3, 1), (100, 37), (40, 213), (517, 517)] {
+            let row: Vec<f32> = (0..in_len).map(|_| rng.generate::<f32>() * 300.0).collect();
+
+            for n in [2, 4] {
+                let taps = taps(in_len, out_len, n);
+
+                let mut expected = vec![0.0; out_len];
+                horizontal_scalar(&row, &mut expected, &taps);
+
+                for (isa, kernel) in HORIZONTAL.kernels() {
+                    if !isa.is_supported() {
+                        continue;
+                    }
+                    let mut out = vec![0.0; out_len];
+                    unsafe { kernel(&row, &mut out, &taps) };
+
+                    for (a, b) in out.iter().zip(&expected) {
+                        assert!((a - b).abs() < 1e-3, "{isa:?} {a} {b}");
+                    }
+                }
+            }
+        }
+        // values outside the sample range saturate
+        let rows: Vec<Vec<f32>> = (0..4)
+            .map(|_| {
+                (0..300)
+                    .map(|_| rng.generate::<f32>() * 80000.0 - 5000.0)
+                    .collect()
+            })
+            .collect();
+        let rows: Vec<&[f32]> = rows.iter().map(Vec::as_slice).collect();
+
+        for length in [1, 8, 31, 300] {
+            for n in [2, 4] {
+                let weights = [0.1, 0.6, 0.2, 0.1];
+                let (rows, weights) = (&rows[..n], &weights[..n]);
+
+                macro_rules! check {
+                    ($dispatcher:expr, $type:ty) => {
+                        let mut expected = vec![<$type>::default(); length];
+                        vertical_scalar::<$type>(rows, weights, &mut expected);
+
+                        for (isa, kernel) in $dispatcher.kernels() {
+                            if !isa.is_supported() {
+                                continue;
+                            }
+                            let mut out = vec![<$type>::default(); length];
+                            unsafe { kernel(rows, weights, &mut out) };
+
+                            for (a, b) in out.iter().zip(&expected) {
+                                // fused multiply adds round once less
+                                assert!(
+                                    (f32::from(*a) - f32::from(*b)).abs() <= 1.0,
+                                    "{isa:?} {a} {b}"
+                                );
+                            }
+                        }
+                    };
+                }
+                check!(VERTICAL_U8, u8);
+                check!(VERTICAL_U16, u16);
+                check!(VERTICAL_F32, f32);
+            }
+        }
+    }
+}
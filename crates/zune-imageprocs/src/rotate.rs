@@ -17,10 +17,11 @@ use zune_core::bit_depth::BitType;
 use zune_image::channel::Channel;
 use zune_image::errors::ImageErrors;
 use zune_image::image::Image;
-use zune_image::traits::OperationsTrait;
+use zune_image::traits::{OperationsTrait, Parameter, ParameterKind, ParameterValue};
 
 use crate::utils::execute_on;
 
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rotate {
     angle: f32
 }
@@ -37,6 +38,18 @@ impl OperationsTrait for Rotate {
         "Rotate"
     }
 
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            name:        "angle",
+            description: "Angle in degrees",
+            kind:        ParameterKind::Float {
+                min: 0.0,
+                max: 360.0
+            },
+            default:     ParameterValue::Float(0.0)
+        }]
+    }
+
     fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
         let im_type = image.depth().bit_type();
 
@@ -95,11 +108,12 @@ impl OperationsTrait for Rotate {
 
 fn change_image_dims(image: &mut Image, angle: f32) {
     let (ow, oh) = image.dimensions();
-    if (angle - 90.0).abs() < f32::EPSILON {
-        image.set_dimensions(oh, ow);
-    }
-    if (angle - 270.0).abs() < f32::EPSILON {
+    if (angle - 90.0).abs() < f32::EPSILON || (angle - 270.0).abs() < f32::EPSILON {
         image.set_dimensions(oh, ow);
+
+        if let Some(resolution) = image.metadata().resolution() {
+            image.metadata_mut().set_resolution(resolution.transposed());
+        }
     }
 }
 
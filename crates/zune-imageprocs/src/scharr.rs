@@ -6,6 +6,8 @@
  * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
  */
 //! Scharr derivative filter
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
 use zune_core::bit_depth::BitType;
 use zune_image::channel::Channel;
 use zune_image::errors::ImageErrors;
@@ -15,6 +17,7 @@ use zune_image::traits::OperationsTrait;
 use crate::pad::{pad, PadMethod};
 use crate::spatial::spatial_NxN;
 use crate::traits::NumOps;
+use crate::utils::execute_on;
 
 /// Perform a scharr image derivative.
 ///
@@ -40,13 +43,23 @@ use crate::traits::NumOps;
 ///
 /// The window is a 3x3 window.
 #[derive(Default, Copy, Clone)]
-pub struct Scharr;
+pub struct Scharr {
+    pad_method: PadMethod
+}
 
 impl Scharr {
     /// Create a new scharr filter
     #[must_use]
     pub fn new() -> Scharr {
-        Self
+        Self {
+            pad_method: PadMethod::Replicate
+        }
+    }
+    /// Set how pixels outside the image are computed, defaults to [PadMethod::Replicate]
+    #[must_use]
+    pub fn set_pad_method(mut self, pad_method: PadMethod) -> Self {
+        self.pad_method = pad_method;
+        self
     }
 }
 
@@ -58,81 +71,39 @@ impl OperationsTrait for Scharr {
         let depth = image.depth().bit_type();
         let (width, height) = image.dimensions();
 
-        #[cfg(not(feature = "threads"))]
-        {
-            for channel in image.channels_mut(true) {
+        execute_on(
+            |channel| {
                 let mut out_channel = Channel::new_with_bit_type(channel.len(), depth);
                 match depth {
-                    BitType::U8 => scharr_int::<u8>(
+                    BitType::U8 => scharr_int_with_border::<u8>(
                         channel.reinterpret_as()?,
                         out_channel.reinterpret_as_mut()?,
                         width,
-                        height
+                        height,
+                        self.pad_method
                     ),
-                    BitType::U16 => scharr_int::<u16>(
+                    BitType::U16 => scharr_int_with_border::<u16>(
                         channel.reinterpret_as()?,
                         out_channel.reinterpret_as_mut()?,
                         width,
-                        height
+                        height,
+                        self.pad_method
                     ),
-                    BitType::F32 => scharr_float::<f32>(
+                    BitType::F32 => scharr_float_with_border::<f32>(
                         channel.reinterpret_as()?,
                         out_channel.reinterpret_as_mut()?,
                         width,
-                        height
+                        height,
+                        self.pad_method
                     ),
                     d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
                 }
                 *channel = out_channel;
-            }
-        }
-        #[cfg(feature = "threads")]
-        {
-            std::thread::scope(|s| {
-                let mut t_results = vec![];
-                for channel in image.channels_mut(true) {
-                    let result = s.spawn(|| {
-                        let mut out_channel = Channel::new_with_bit_type(channel.len(), depth);
-                        match depth {
-                            BitType::U8 => scharr_int::<u8>(
-                                channel.reinterpret_as()?,
-                                out_channel.reinterpret_as_mut()?,
-                                width,
-                                height
-                            ),
-                            BitType::U16 => scharr_int::<u16>(
-                                channel.reinterpret_as()?,
-                                out_channel.reinterpret_as_mut()?,
-                                width,
-                                height
-                            ),
-                            BitType::F32 => scharr_float::<f32>(
-                                channel.reinterpret_as()?,
-                                out_channel.reinterpret_as_mut()?,
-                                width,
-                                height
-                            ),
-                            d => {
-                                return Err(ImageErrors::ImageOperationNotImplemented(
-                                    self.name(),
-                                    d
-                                ))
-                            }
-                        }
-                        *channel = out_channel;
-                        Ok(())
-                    });
-                    t_results.push(result);
-                }
-
-                t_results
-                    .into_iter()
-                    .map(|x| x.join().unwrap())
-                    .collect::<Result<Vec<()>, ImageErrors>>()
-            })?;
-        }
-
-        Ok(())
+                Ok(())
+            },
+            image,
+            true
+        )
     }
 
     fn supported_types(&self) -> &'static [BitType] {
@@ -148,7 +119,7 @@ impl OperationsTrait for Scharr {
 fn scharr_inner_f32<T>(c: &[T; 9]) -> T
     where
         T: NumOps<T> + Copy + Default,
-        f32: std::convert::From<T>
+        f32: core::convert::From<T>
 {
     // matrix
     //   -3, 0,  3,
@@ -182,7 +153,7 @@ fn scharr_inner_f32<T>(c: &[T; 9]) -> T
 fn scharr_inner_i32<T>(c: &[T; 9]) -> T
     where
         T: NumOps<T> + Copy + Default,
-        i32: std::convert::From<T>
+        i32: core::convert::From<T>
 {
     // Gx matrix
     //   -3, 0,  3,
@@ -217,11 +188,21 @@ fn scharr_inner_i32<T>(c: &[T; 9]) -> T
 /// - height: Height of input channel
 pub fn scharr_float<T>(in_channel: &[T], out_channel: &mut [T], width: usize, height: usize)
 where
-    T: Default + NumOps<T> + Copy,
-    f32: std::convert::From<T>
+    T: Default + NumOps<T> + Copy + Send + Sync,
+    f32: core::convert::From<T>
+{
+    scharr_float_with_border(in_channel, out_channel, width, height, PadMethod::Replicate)
+}
+
+/// [`scharr_float`] with pixels outside the image computed by `pad_method`
+pub fn scharr_float_with_border<T>(
+    in_channel: &[T], out_channel: &mut [T], width: usize, height: usize, pad_method: PadMethod
+) where
+    T: Default + NumOps<T> + Copy + Send + Sync,
+    f32: core::convert::From<T>
 {
     //pad here
-    let padded_input = pad(in_channel, width, height, 1, 1, PadMethod::Replicate);
+    let padded_input = pad(in_channel, width, height, 1, 1, pad_method);
 
     spatial_NxN::<_, _, 1, 9>(&padded_input, out_channel, width, height, scharr_inner_f32);
 }
@@ -237,11 +218,21 @@ where
 /// - height: Height of input channel
 pub fn scharr_int<T>(in_channel: &[T], out_channel: &mut [T], width: usize, height: usize)
 where
-    T: Default + NumOps<T> + Copy,
-    i32: std::convert::From<T>
+    T: Default + NumOps<T> + Copy + Send + Sync,
+    i32: core::convert::From<T>
+{
+    scharr_int_with_border(in_channel, out_channel, width, height, PadMethod::Replicate)
+}
+
+/// [`scharr_int`] with pixels outside the image computed by `pad_method`
+pub fn scharr_int_with_border<T>(
+    in_channel: &[T], out_channel: &mut [T], width: usize, height: usize, pad_method: PadMethod
+) where
+    T: Default + NumOps<T> + Copy + Send + Sync,
+    i32: core::convert::From<T>
 {
     //pad here
-    let padded_input = pad(in_channel, width, height, 1, 1, PadMethod::Replicate);
+    let padded_input = pad(in_channel, width, height, 1, 1, pad_method);
 
     spatial_NxN::<_, _, 1, 9>(&padded_input, out_channel, width, height, scharr_inner_i32);
 }
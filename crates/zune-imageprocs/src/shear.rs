@@ -0,0 +1,426 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Shear: Skew an image horizontally or vertically
+//!
+//! A horizontal shear by a factor `k` moves every row sideways in proportion to its distance
+//! from the image center, `x' = x + k*y`, turning rectangles into parallelograms.
+//! A vertical shear does the same for columns, `y' = y + k*x`.
+//!
+//! The canvas is expanded to fit the sheared image, with the newly exposed area filled with
+//! a background color.
+//!
+//! # Algorithm
+//! Every row (or column) is shifted by a fractional amount, pixels are linearly interpolated
+//! between their two nearest source pixels, with the background used for samples outside the image,
+//! which gives anti-aliased edges.
+//!
+//! # Rotation
+//! [`ShearRotate`] implements arbitrary rotation via three shears (Paeth's algorithm)
+//!
+//! ```text
+//! R(θ) = Sx(-tan(θ/2)) * Sy(sin(θ)) * Sx(-tan(θ/2))
+//! ```
+//!
+//! Each shear is a one dimensional resampling, which makes it fast and preserves detail
+//! better than a single two dimensional resampling.
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use zune_core::bit_depth::BitType;
+use zune_image::channel::Channel;
+use zune_image::errors::ImageErrors;
+use zune_image::image::{Image, MAX_CHANNELS};
+use zune_image::traits::OperationsTrait;
+
+use crate::crop::Crop;
+use crate::rotate::Rotate;
+use crate::traits::NumOps;
+use crate::utils::round_to_depth;
+use crate::DepthColor;
+
+/// The axis along which pixels are moved
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ShearDirection {
+    /// Rows are moved left and right, `x' = x + k*y`
+    Horizontal,
+    /// Columns are moved up and down, `y' = y + k*x`
+    Vertical
+}
+
+/// Shear an image, expanding the canvas to fit
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::shear::{Shear, ShearDirection};
+///
+/// let mut image = Image::fill(255_u8, ColorSpace::RGB, 100, 50);
+///
+/// Shear::new(ShearDirection::Horizontal, 0.5)
+///     .execute(&mut image)
+///     .unwrap();
+///
+/// assert_eq!(image.dimensions(), (126, 50));
+/// ```
+pub struct Shear {
+    direction:  ShearDirection,
+    factor:     f32,
+    background: DepthColor
+}
+
+impl Shear {
+    /// Create a new shear operation
+    ///
+    /// # Arguments
+    /// - direction: The axis along which pixels are moved
+    /// - factor: How far pixels move per pixel of distance from the center, i.e
+    ///   the tangent of the shear angle
+    ///
+    /// The background defaults to black, and transparent for images with alpha
+    #[must_use]
+    pub fn new(direction: ShearDirection, factor: f32) -> Shear {
+        Shear {
+            direction,
+            factor,
+            background: [0.0; MAX_CHANNELS]
+        }
+    }
+    /// Create a new shear operation from an angle
+    ///
+    /// # Arguments
+    /// - direction: The axis along which pixels are moved
+    /// - angle: The shear angle in degrees, must be between -90 and 90 exclusive
+    #[must_use]
+    pub fn new_angle(direction: ShearDirection, angle: f32) -> Shear {
+        Shear::new(direction, angle.to_radians().tan())
+    }
+    /// Set the color of the area exposed by the shear, see [DepthColor] for the
+    /// range of the values
+    #[must_use]
+    pub fn set_background(mut self, background: DepthColor) -> Self {
+        self.background = background;
+        self
+    }
+}
+
+impl OperationsTrait for Shear {
+    fn name(&self) -> &'static str {
+        "Shear"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        if !self.factor.is_finite() {
+            return Err(ImageErrors::GenericStr("Shear factor must be finite"));
+        }
+        let (width, height) = image.dimensions();
+        let (out_width, out_height) =
+            sheared_dimensions(width, height, self.direction, self.factor);
+
+        let colorspace = image.colorspace();
+        let bit_depth = image.depth();
+        let depth = bit_depth.bit_type();
+
+        for frame in image.frames_mut() {
+            for (channel, background) in frame
+                .channels_mut(colorspace, false)
+                .iter_mut()
+                .zip(self.background)
+            {
+                let mut new_channel =
+                    Channel::new_with_bit_type(out_width * out_height * bit_depth.size_of(), depth);
+
+                match depth {
+                    BitType::U8 => shear::<u8>(
+                        channel.reinterpret_as()?,
+                        width,
+                        height,
+                        new_channel.reinterpret_as_mut()?,
+                        self.direction,
+                        self.factor,
+                        background
+                    ),
+                    BitType::U16 => shear::<u16>(
+                        channel.reinterpret_as()?,
+                        width,
+                        height,
+                        new_channel.reinterpret_as_mut()?,
+                        self.direction,
+                        self.factor,
+                        background
+                    ),
+                    BitType::F32 => shear::<f32>(
+                        channel.reinterpret_as()?,
+                        width,
+                        height,
+                        new_channel.reinterpret_as_mut()?,
+                        self.direction,
+                        self.factor,
+                        background
+                    ),
+                    d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+                }
+                *channel = new_channel;
+            }
+        }
+        image.set_dimensions(out_width, out_height);
+
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Rotate an image by an arbitrary angle using three shears
+///
+/// The canvas is expanded to fit the rotated image, with the exposed corners
+/// filled with the background color.
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::shear::ShearRotate;
+///
+/// let mut image = Image::fill(255_u8, ColorSpace::RGBA, 100, 100);
+///
+/// ShearRotate::new(45.0).execute(&mut image).unwrap();
+///
+/// assert_eq!(image.dimensions(), (141, 141));
+/// ```
+pub struct ShearRotate {
+    angle:      f32,
+    background: DepthColor
+}
+
+impl ShearRotate {
+    /// Create a new rotation
+    ///
+    /// # Arguments
+    /// - angle: The rotation angle in degrees, positive angles rotate clockwise
+    ///
+    /// The background defaults to black, and transparent for images with alpha
+    #[must_use]
+    pub fn new(angle: f32) -> ShearRotate {
+        ShearRotate {
+            angle,
+            background: [0.0; MAX_CHANNELS]
+        }
+    }
+    /// Set the color of the corners exposed by the rotation, see [DepthColor] for
+    /// the range of the values
+    #[must_use]
+    pub fn set_background(mut self, background: DepthColor) -> Self {
+        self.background = background;
+        self
+    }
+}
+
+impl OperationsTrait for ShearRotate {
+    fn name(&self) -> &'static str {
+        "Shear Rotate"
+    }
+
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        if !self.angle.is_finite() {
+            return Err(ImageErrors::GenericStr("Rotation angle must be finite"));
+        }
+        // bring the angle to -180..=180
+        let mut angle = self.angle % 360.0;
+        if angle > 180.0 {
+            angle -= 360.0;
+        } else if angle < -180.0 {
+            angle += 360.0;
+        }
+        // shears become very large near 180 degrees, so do an exact half turn first
+        if angle.abs() > 90.0 {
+            Rotate::new(180.0).execute(image)?;
+            angle -= 180.0_f32.copysign(angle);
+        }
+        let (width, height) = image.dimensions();
+
+        let theta = angle.to_radians();
+        let (sin, cos) = theta.sin_cos();
+        let tan_half = (theta / 2.0).tan();
+
+        for (direction, factor) in [
+            (ShearDirection::Horizontal, -tan_half),
+            (ShearDirection::Vertical, sin),
+            (ShearDirection::Horizontal, -tan_half)
+        ] {
+            Shear::new(direction, factor)
+                .set_background(self.background)
+                .execute(image)?;
+        }
+        // trim the canvas to the bounding box of the rotated image
+        let (sheared_w, sheared_h) = image.dimensions();
+        let w = width as f32;
+        let h = height as f32;
+
+        let fit_w = ((w * cos.abs() + h * sin.abs()).round() as usize).min(sheared_w);
+        let fit_h = ((w * sin.abs() + h * cos.abs()).round() as usize).min(sheared_h);
+
+        if (fit_w, fit_h) != (sheared_w, sheared_h) {
+            Crop::new(
+                fit_w,
+                fit_h,
+                (sheared_w - fit_w) / 2,
+                (sheared_h - fit_h) / 2
+            )
+            .execute(image)?;
+        }
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Return the dimensions of a `width` x `height` image after shearing
+#[must_use]
+pub fn sheared_dimensions(
+    width: usize, height: usize, direction: ShearDirection, factor: f32
+) -> (usize, usize) {
+    match direction {
+        ShearDirection::Horizontal => (width + expansion(factor, height), height),
+        ShearDirection::Vertical => (width, height + expansion(factor, width))
+    }
+}
+
+/// Number of pixels a line grows by when `lines` lines are sheared by `factor`
+///
+/// This is rounded up to an even number so that the sheared image can be exactly centered
+/// in the canvas, otherwise every line would be shifted by an extra half pixel
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn expansion(factor: f32, lines: usize) -> usize {
+    let extra = (factor.abs() * lines as f32 - 1e-3).ceil().max(0.0) as usize;
+    extra + (extra & 1)
+}
+
+/// Shear a single channel
+///
+/// # Arguments
+/// - input: The input channel, of `width*height` pixels
+/// - width, height: Input dimensions
+/// - output: The output channel, its dimensions are given by [`sheared_dimensions`]
+/// - direction: The axis along which pixels are moved
+/// - factor: How far pixels move per pixel of distance from the center
+/// - background: Value used for the area outside the input image
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss
+)]
+pub fn shear<T: Copy + NumOps<T>>(
+    input: &[T], width: usize, height: usize, output: &mut [T], direction: ShearDirection,
+    factor: f32, background: f32
+) {
+    let (out_width, out_height) = sheared_dimensions(width, height, direction, factor);
+
+    // a line is a row for horizontal shears and a column for vertical ones
+    let (lines, len, out_len) = match direction {
+        ShearDirection::Horizontal => (height, width, out_width),
+        ShearDirection::Vertical => (width, height, out_height)
+    };
+    let in_index = |line: usize, pos: usize| match direction {
+        ShearDirection::Horizontal => line * width + pos,
+        ShearDirection::Vertical => pos * width + line
+    };
+    let out_index = |line: usize, pos: usize| match direction {
+        ShearDirection::Horizontal => line * out_width + pos,
+        ShearDirection::Vertical => pos * out_width + line
+    };
+    let max = T::MAX_VAL.to_f32();
+    let background = background.clamp(T::MIN_VAL.to_f32(), max);
+
+    let sample = |line: usize, pos: isize| {
+        if pos >= 0 && (pos as usize) < len {
+            input[in_index(line, pos as usize)].to_f32()
+        } else {
+            background
+        }
+    };
+    // shift lines around the image center, and keep the result centered in the canvas
+    let center = lines as f32 / 2.0;
+    let padding = ((out_len - len) / 2) as f32;
+
+    for line in 0..lines {
+        let shift = factor * (line as f32 + 0.5 - center) + padding;
+
+        for pos in 0..out_len {
+            let src = pos as f32 - shift;
+            let floor = src.floor();
+            let frac = src - floor;
+            let start = floor as isize;
+
+            let a = sample(line, start);
+            let b = sample(line, start + 1);
+
+            output[out_index(line, pos)] = round_to_depth(a + (b - a) * frac);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::shear::{Shear, ShearDirection, ShearRotate};
+
+    #[test]
+    fn test_shear_horizontal() {
+        let mut image = Image::fill(100_u8, ColorSpace::Luma, 2, 2);
+
+        Shear::new(ShearDirection::Horizontal, 1.0)
+            .set_background([0.0; 4])
+            .execute(&mut image)
+            .unwrap();
+
+        assert_eq!(image.dimensions(), (4, 2));
+        let pixels = image.channels_ref(false)[0].reinterpret_as::<u8>().unwrap();
+        // the top row moves left and the bottom row right, by half a pixel each
+        assert_eq!(pixels, &[50, 100, 50, 0, 0, 50, 100, 50]);
+    }
+
+    #[test]
+    fn test_shear_rotate() {
+        let mut image = Image::fill(0.0_f32, ColorSpace::Luma, 9, 5);
+        // mark a pixel right of the center
+        image.frames_mut()[0].channels_mut(ColorSpace::Luma, false)[0]
+            .reinterpret_as_mut::<f32>()
+            .unwrap()[2 * 9 + 7] = 1.0;
+
+        ShearRotate::new(90.0).execute(&mut image).unwrap();
+        assert_eq!(image.dimensions(), (5, 9));
+
+        let pixels = image.channels_ref(false)[0]
+            .reinterpret_as::<f32>()
+            .unwrap();
+        // rotating clockwise moves it below the center
+        assert!(pixels[7 * 5 + 2] > 0.9);
+
+        ShearRotate::new(-90.0).execute(&mut image).unwrap();
+        assert_eq!(image.dimensions(), (9, 5));
+    }
+}
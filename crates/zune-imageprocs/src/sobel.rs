@@ -7,6 +7,8 @@
  */
 
 //! Sobel derivative filter
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
 use zune_core::bit_depth::BitType;
 use zune_image::channel::Channel;
 use zune_image::errors::ImageErrors;
@@ -42,12 +44,22 @@ use crate::utils::execute_on;
 ///
 /// The window is a 3x3 window.
 #[derive(Default, Copy, Clone)]
-pub struct Sobel;
+pub struct Sobel {
+    pad_method: PadMethod
+}
 
 impl Sobel {
     #[must_use]
     pub fn new() -> Sobel {
-        Self
+        Self {
+            pad_method: PadMethod::Replicate
+        }
+    }
+    /// Set how pixels outside the image are computed, defaults to [PadMethod::Replicate]
+    #[must_use]
+    pub fn set_pad_method(mut self, pad_method: PadMethod) -> Self {
+        self.pad_method = pad_method;
+        self
     }
 }
 
@@ -62,23 +74,26 @@ impl OperationsTrait for Sobel {
         let sobel_fn = |channel: &mut Channel| -> Result<(), ImageErrors> {
             let mut out_channel = Channel::new_with_bit_type(channel.len(), depth);
             match depth {
-                BitType::U8 => sobel_int::<u8>(
+                BitType::U8 => sobel_int_with_border::<u8>(
                     channel.reinterpret_as()?,
                     out_channel.reinterpret_as_mut()?,
                     width,
-                    height
+                    height,
+                    self.pad_method
                 ),
-                BitType::U16 => sobel_int::<u16>(
+                BitType::U16 => sobel_int_with_border::<u16>(
                     channel.reinterpret_as()?,
                     out_channel.reinterpret_as_mut()?,
                     width,
-                    height
+                    height,
+                    self.pad_method
                 ),
-                BitType::F32 => sobel_float::<f32>(
+                BitType::F32 => sobel_float_with_border::<f32>(
                     channel.reinterpret_as()?,
                     out_channel.reinterpret_as_mut()?,
                     width,
-                    height
+                    height,
+                    self.pad_method
                 ),
                 d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
             }
@@ -102,7 +117,7 @@ impl OperationsTrait for Sobel {
 fn sobel_inner_f32<T>(c: &[T; 9]) -> T
     where
         T: NumOps<T> + Copy + Default,
-        f32: std::convert::From<T>
+        f32: core::convert::From<T>
 {
     // matrix
     //  -1, 0, 1,
@@ -136,7 +151,7 @@ fn sobel_inner_f32<T>(c: &[T; 9]) -> T
 fn sobel_inner_i32<T>(c: &[T; 9]) -> T
     where
         T: NumOps<T> + Copy + Default,
-        i32: std::convert::From<T>
+        i32: core::convert::From<T>
 {
     // matrix
     //  -1, 0, 1,
@@ -171,11 +186,21 @@ fn sobel_inner_i32<T>(c: &[T; 9]) -> T
 /// - height: Height of input channel
 pub fn sobel_float<T>(in_channel: &[T], out_channel: &mut [T], width: usize, height: usize)
 where
-    T: Default + NumOps<T> + Copy,
-    f32: std::convert::From<T>
+    T: Default + NumOps<T> + Copy + Send + Sync,
+    f32: core::convert::From<T>
+{
+    sobel_float_with_border(in_channel, out_channel, width, height, PadMethod::Replicate)
+}
+
+/// [`sobel_float`] with pixels outside the image computed by `pad_method`
+pub fn sobel_float_with_border<T>(
+    in_channel: &[T], out_channel: &mut [T], width: usize, height: usize, pad_method: PadMethod
+) where
+    T: Default + NumOps<T> + Copy + Send + Sync,
+    f32: core::convert::From<T>
 {
     //pad here
-    let padded_input = pad(in_channel, width, height, 1, 1, PadMethod::Replicate);
+    let padded_input = pad(in_channel, width, height, 1, 1, pad_method);
 
     spatial_NxN::<_, _, 1, 9>(&padded_input, out_channel, width, height, sobel_inner_f32);
 }
@@ -191,11 +216,21 @@ where
 /// - height: Height of input channel
 pub fn sobel_int<T>(in_channel: &[T], out_channel: &mut [T], width: usize, height: usize)
 where
-    T: Default + NumOps<T> + Copy,
-    i32: std::convert::From<T>
+    T: Default + NumOps<T> + Copy + Send + Sync,
+    i32: core::convert::From<T>
+{
+    sobel_int_with_border(in_channel, out_channel, width, height, PadMethod::Replicate)
+}
+
+/// [`sobel_int`] with pixels outside the image computed by `pad_method`
+pub fn sobel_int_with_border<T>(
+    in_channel: &[T], out_channel: &mut [T], width: usize, height: usize, pad_method: PadMethod
+) where
+    T: Default + NumOps<T> + Copy + Send + Sync,
+    i32: core::convert::From<T>
 {
     //pad here
-    let padded_input = pad(in_channel, width, height, 1, 1, PadMethod::Replicate);
+    let padded_input = pad(in_channel, width, height, 1, 1, pad_method);
 
     spatial_NxN::<_, _, 1, 9>(&padded_input, out_channel, width, height, sobel_inner_i32);
 }
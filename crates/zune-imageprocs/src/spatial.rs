@@ -22,15 +22,27 @@
 /// larger radius means more compute time.
 ///
 /// for example a radius of R will result in a search window length of 2R+1 for each dimension.
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct SpatialOps {
-    radius:    usize,
-    operation: SpatialOperations
+    radius:     usize,
+    operation:  SpatialOperations,
+    pad_method: PadMethod
 }
 
 impl SpatialOps {
     #[must_use]
     pub fn new(radius: usize, operation: SpatialOperations) -> SpatialOps {
-        SpatialOps { radius, operation }
+        SpatialOps {
+            radius,
+            operation,
+            pad_method: PadMethod::Replicate
+        }
+    }
+    /// Set how pixels outside the image are computed, defaults to [PadMethod::Replicate]
+    #[must_use]
+    pub fn set_pad_method(mut self, pad_method: PadMethod) -> Self {
+        self.pad_method = pad_method;
+        self
     }
 }
 
@@ -48,21 +60,23 @@ impl OperationsTrait for SpatialOps {
             let mut new_channel = Channel::new_with_bit_type(channel.len(), depth.bit_type());
 
             match depth.bit_type() {
-                BitType::U16 => spatial_ops(
+                BitType::U16 => spatial_ops_with_border(
                     channel.reinterpret_as::<u16>()?,
                     new_channel.reinterpret_as_mut::<u16>()?,
                     self.radius,
                     width,
                     height,
-                    self.operation
+                    self.operation,
+                    self.pad_method
                 ),
-                BitType::U8 => spatial_ops(
+                BitType::U8 => spatial_ops_with_border(
                     channel.reinterpret_as::<u8>()?,
                     new_channel.reinterpret_as_mut::<u8>()?,
                     self.radius,
                     width,
                     height,
-                    self.operation
+                    self.operation,
+                    self.pad_method
                 ),
                 d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
             }
@@ -77,14 +91,17 @@ impl OperationsTrait for SpatialOps {
     }
 }
 
+use alloc::vec;
+
 use zune_core::bit_depth::BitType;
 use zune_image::channel::Channel;
 use zune_image::errors::ImageErrors;
 use zune_image::image::Image;
 use zune_image::traits::OperationsTrait;
 
-use crate::spatial_ops::{spatial_ops, SpatialOperations};
-use crate::utils::{execute_on, z_prefetch};
+use crate::pad::PadMethod;
+use crate::spatial_ops::{spatial_ops_with_border, SpatialOperations};
+use crate::utils::{execute_on, for_each_padded_row_band, z_prefetch};
 
 /// Go through image neighbord, execute a function on it and return the result
 /// The parameter `function` is the function that receives the list of neighbors and returns the selected
@@ -106,6 +123,30 @@ use crate::utils::{execute_on, z_prefetch};
 pub fn spatial<T, F>(
     in_channel: &[T], out_channel: &mut [T], radius: usize, width: usize, height: usize,
     function: F
+) where
+    T: Default + Copy + Send + Sync,
+    F: Fn(&[T]) -> T + Send + Sync
+{
+    assert_eq!(
+        (height + radius * 2) * (width + radius * 2),
+        in_channel.len()
+    );
+    // windows of a row only read the rows around it, so bands of rows run in parallel
+    for_each_padded_row_band(
+        in_channel,
+        out_channel,
+        width,
+        height,
+        radius,
+        |in_band, out_band, band_height| {
+            spatial_band(in_band, out_band, radius, width, band_height, &function);
+        }
+    );
+}
+
+fn spatial_band<T, F>(
+    in_channel: &[T], out_channel: &mut [T], radius: usize, width: usize, height: usize,
+    function: F
 ) where
     T: Default + Copy,
     F: Fn(&[T]) -> T
@@ -152,6 +193,35 @@ pub fn spatial<T, F>(
 #[allow(non_snake_case)]
 pub(crate) fn spatial_NxN<T, F, const RADIUS: usize, const OUT_SIZE: usize>(
     in_channel: &[T], out_channel: &mut [T], width: usize, height: usize, function: F
+) where
+    T: Default + Copy + Send + Sync,
+    F: Fn(&[T; OUT_SIZE]) -> T + Send + Sync
+{
+    assert_eq!(
+        (height + RADIUS * 2) * (width + RADIUS * 2),
+        in_channel.len()
+    );
+    for_each_padded_row_band(
+        in_channel,
+        out_channel,
+        width,
+        height,
+        RADIUS,
+        |in_band, out_band, band_height| {
+            spatial_NxN_band::<T, _, RADIUS, OUT_SIZE>(
+                in_band,
+                out_band,
+                width,
+                band_height,
+                &function
+            );
+        }
+    );
+}
+
+#[allow(non_snake_case)]
+fn spatial_NxN_band<T, F, const RADIUS: usize, const OUT_SIZE: usize>(
+    in_channel: &[T], out_channel: &mut [T], width: usize, height: usize, function: F
 ) where
     T: Default + Copy,
     F: Fn(&[T; OUT_SIZE]) -> T
@@ -6,8 +6,9 @@
  * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
  */
 //! Simple spatial operations implemented for images
-use std::fmt::Debug;
-use std::ops::{Add, Div, Sub};
+use alloc::string::{String, ToString};
+use core::fmt::Debug;
+use core::ops::{Add, Div, Sub};
 
 use crate::pad::{pad, PadMethod};
 use crate::spatial::spatial;
@@ -15,6 +16,7 @@ use crate::traits::NumOps;
 
 /// Spatial operations implemented for images
 #[derive(Copy, Clone, Debug, PartialOrd, PartialEq)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub enum SpatialOperations {
     /// (max-min)/(max+min)
     Contrast,
@@ -114,7 +116,7 @@ fn find_max<T: PartialOrd + Copy + NumOps<T>>(data: &[T]) -> T {
 fn find_mean<T>(data: &[T]) -> T
 where
     T: Default + Copy + NumOps<T> + Add<Output = T> + Div<Output = T>,
-    u32: std::convert::From<T>
+    u32: core::convert::From<T>
 {
     //https://godbolt.org/z/6Y8ncehd5
     let mut maximum = u32::default();
@@ -148,18 +150,40 @@ pub fn spatial_ops<T>(
         + NumOps<T>
         + Sub<Output = T>
         + Add<Output = T>
-        + Div<Output = T>,
-    u32: std::convert::From<T>
+        + Div<Output = T>
+        + Send
+        + Sync,
+    u32: core::convert::From<T>
 {
-    //pad here
-    let padded_input = pad(
+    spatial_ops_with_border(
         in_channel,
+        out_channel,
+        radius,
         width,
         height,
-        radius,
-        radius,
+        operations,
         PadMethod::Replicate
-    );
+    )
+}
+
+/// [`spatial_ops`] with pixels outside the image computed by `pad_method`
+pub fn spatial_ops_with_border<T>(
+    in_channel: &[T], out_channel: &mut [T], radius: usize, width: usize, height: usize,
+    operations: SpatialOperations, pad_method: PadMethod
+) where
+    T: PartialOrd
+        + Default
+        + Copy
+        + NumOps<T>
+        + Sub<Output = T>
+        + Add<Output = T>
+        + Div<Output = T>
+        + Send
+        + Sync,
+    u32: core::convert::From<T>
+{
+    //pad here
+    let padded_input = pad(in_channel, width, height, radius, radius, pad_method);
 
     // Note: It's faster to do it like this,
     // Because of our tied and tested enemy called cache misses
@@ -10,6 +10,7 @@
 /// Linearly stretches the contrast in an image in place,
 /// sending lower to image minimum and upper to image maximum.
 #[derive(Default)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct StretchContrast {
     lower: f32,
     upper: f32
@@ -32,6 +33,23 @@ impl OperationsTrait for StretchContrast {
         "Stretch Contrast"
     }
 
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                name:        "lower",
+                description: "Values below this become the minimum",
+                kind:        ParameterKind::Float { min: 0.0, max: 65535.0 },
+                default:     ParameterValue::Float(0.0)
+            },
+            Parameter {
+                name:        "upper",
+                description: "Values above this become the maximum",
+                kind:        ParameterKind::Float { min: 0.0, max: 65535.0 },
+                default:     ParameterValue::Float(255.0)
+            }
+        ]
+    }
+
     #[allow(
         clippy::cast_precision_loss,
         clippy::cast_possible_truncation,
@@ -68,14 +86,17 @@ impl OperationsTrait for StretchContrast {
     fn supported_types(&self) -> &'static [BitType] {
         &[BitType::U8, BitType::U16]
     }
+    fn is_point_op(&self) -> bool {
+        true
+    }
 }
-use std::ops::Sub;
+use core::ops::Sub;
 
 use zune_core::bit_depth::BitType;
 use zune_image::channel::Channel;
 use zune_image::errors::ImageErrors;
 use zune_image::image::Image;
-use zune_image::traits::OperationsTrait;
+use zune_image::traits::{OperationsTrait, Parameter, ParameterKind, ParameterValue};
 
 use crate::mathops::{compute_mod_u32, fastdiv_u32};
 use crate::traits::NumOps;
@@ -101,7 +122,7 @@ pub fn stretch_contrast<T>(
 ) -> Result<(), &'static str>
 where
     T: Ord + Sub<Output = T> + NumOps<T> + Copy,
-    u32: std::convert::From<T>
+    u32: core::convert::From<T>
 {
     if upper < lower {
         return Err("upper must be strictly greater than lower");
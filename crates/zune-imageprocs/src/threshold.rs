@@ -6,17 +6,20 @@
  * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
  */
 //! Threshold filter: Binarize an image
+use alloc::string::{String, ToString};
+
 use zune_core::bit_depth::BitType;
 use zune_core::log::warn;
 use zune_image::channel::Channel;
 use zune_image::errors::ImageErrors;
 use zune_image::image::Image;
-use zune_image::traits::OperationsTrait;
+use zune_image::traits::{OperationsTrait, Parameter, ParameterKind, ParameterValue};
 
 use crate::traits::NumOps;
 use crate::utils::execute_on;
 
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub enum ThresholdMethod {
     Binary,
     BinaryInv,
@@ -50,6 +53,7 @@ impl ThresholdMethod {
 ///  - [ThreshToZero](ThresholdMethod::ThreshToZero) => src(x,y) if src(x,y) > thresh 0 otherwise
 ///           
 ///  See [Wikipedia Article on Thresholding](https://en.wikipedia.org/wiki/Thresholding_(image_processing))
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Threshold {
     method:    ThresholdMethod,
     threshold: f32
@@ -74,6 +78,31 @@ impl OperationsTrait for Threshold {
         "Threshold"
     }
 
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                name:        "threshold",
+                description: "Values above this are considered foreground",
+                kind:        ParameterKind::Float {
+                    min: 0.0,
+                    max: 65535.0
+                },
+                default:     ParameterValue::Float(128.0)
+            },
+            Parameter {
+                name:        "method",
+                description: "How values are mapped",
+                kind:        ParameterKind::Choice(&[
+                    "binary",
+                    "binary_inv",
+                    "thresh_trunc",
+                    "thresh_to_zero"
+                ]),
+                default:     ParameterValue::Choice("binary")
+            }
+        ]
+    }
+
     #[allow(
         clippy::cast_sign_loss,
         clippy::cast_precision_loss,
@@ -111,6 +140,9 @@ impl OperationsTrait for Threshold {
     fn supported_types(&self) -> &'static [BitType] {
         &[BitType::U8, BitType::U16, BitType::F32]
     }
+    fn is_point_op(&self) -> bool {
+        true
+    }
 }
 #[rustfmt::skip]
 pub fn threshold<T>(in_channel: &mut [T], threshold: T, method: ThresholdMethod)
@@ -0,0 +1,280 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Tone map HDR images for SDR displays
+//!
+//! HDR images store brightness far above the white of an SDR display. Tone mapping
+//! compresses highlights into the displayable range and encodes the result as sRGB.
+//!
+//! The brightness of the image is found from its transfer characteristics, see
+//! [`ImageMetadata::color_trc`]
+//!
+//! - [PQ](ColorCharacteristics::PQ): absolute display light, [`REFERENCE_WHITE_NITS`] become SDR white
+//! - [HLG](ColorCharacteristics::HLG): scene light, displayed on a 1000 nits reference display
+//! - [Linear](ColorCharacteristics::Linear): relative light where 1.0 is SDR white, e.g. Radiance HDR images
+//!
+//! Images that are already sRGB are left untouched.
+//!
+//! HDR content usually uses [Rec.2020](zune_core::colorspace::RgbPrimaries::Rec2020) primaries,
+//! tone mapping keeps the primaries of the image, use [`GamutMap`](crate::gamut_map::GamutMap)
+//! afterwards to convert it for sRGB displays.
+//!
+//! [`ImageMetadata::color_trc`]: zune_image::metadata::ImageMetadata::color_trc
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use zune_core::bit_depth::{BitDepth, BitType};
+use zune_core::colorspace::{ColorCharacteristics, ColorSpace};
+use zune_image::core_filters::colorspace::primaries::rgb_to_xyz_matrix;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::linearize::{linear_to_srgb, Linearize};
+
+/// Brightness of SDR white in HDR content, as recommended by ITU-R BT.2408
+pub const REFERENCE_WHITE_NITS: f32 = 203.0;
+
+/// Peak brightness of the reference display for HLG content
+const HLG_DISPLAY_NITS: f32 = 1000.0;
+
+/// System gamma of HLG on the reference display
+const HLG_SYSTEM_GAMMA: f32 = 1.2;
+
+/// Curve compressing HDR brightness into the SDR range
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Default)]
+pub enum ToneMapOperator {
+    /// Extended Reinhard on luminance
+    ///
+    /// Keeps hues and maps the peak of the image to white
+    #[default]
+    Reinhard,
+    /// Filmic curve approximating the ACES reference rendering transform
+    ///
+    /// Higher contrast than Reinhard, bright saturated colors desaturate towards white
+    Aces,
+    /// Clip everything above SDR white
+    Clip
+}
+
+/// Tone map an HDR image to SDR sRGB
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::{ColorCharacteristics, ColorSpace};
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::tone_map::ToneMap;
+///
+/// let mut image = Image::fill(0.75_f32, ColorSpace::RGB, 10, 10);
+/// image.metadata_mut().set_color_trc(ColorCharacteristics::PQ);
+///
+/// ToneMap::new().execute(&mut image).unwrap();
+///
+/// assert_eq!(image.metadata().color_trc(), Some(ColorCharacteristics::sRGB));
+/// ```
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ToneMap {
+    operator: ToneMapOperator,
+    peak:     Option<f32>
+}
+
+impl ToneMap {
+    /// Create a new tone mapping operation using [`ToneMapOperator::Reinhard`]
+    #[must_use]
+    pub fn new() -> ToneMap {
+        ToneMap::default()
+    }
+    /// Set the tone mapping curve
+    #[must_use]
+    pub fn set_operator(mut self, operator: ToneMapOperator) -> Self {
+        self.operator = operator;
+        self
+    }
+    /// Set the brightness mapped to white relative to SDR white, e.g. 4.9 for
+    /// 1000 nits PQ content
+    ///
+    /// By default this is the brightest pixel of the image. Only used by
+    /// [`ToneMapOperator::Reinhard`]
+    #[must_use]
+    pub fn set_peak(mut self, peak: f32) -> Self {
+        self.peak = Some(peak);
+        self
+    }
+}
+
+impl OperationsTrait for ToneMap {
+    fn name(&self) -> &'static str {
+        "Tone Map"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let transfer = match image.metadata().color_trc() {
+            None | Some(ColorCharacteristics::sRGB) => return Ok(()),
+            Some(transfer) => transfer
+        };
+        let luminance = rgb_to_xyz_matrix(image.metadata().primaries())[1];
+        let depth = image.depth();
+
+        image.convert_depth(BitDepth::Float32)?;
+        Linearize::new().execute_impl(image)?;
+
+        // scale to brightness relative to SDR white
+        match transfer {
+            ColorCharacteristics::PQ => {
+                let scale = 10000.0 / REFERENCE_WHITE_NITS;
+                for_each_pixel(image, |rgb| *rgb = rgb.map(|x| x * scale))?;
+            }
+            ColorCharacteristics::HLG => {
+                let scale = HLG_DISPLAY_NITS / REFERENCE_WHITE_NITS;
+                for_each_pixel(image, |rgb| {
+                    let y = dot(luminance, *rgb).max(0.0);
+                    let ootf = scale * y.powf(HLG_SYSTEM_GAMMA - 1.0);
+                    *rgb = rgb.map(|x| x * ootf);
+                })?;
+            }
+            _ => ()
+        }
+        let peak = if let Some(peak) = self.peak {
+            peak
+        } else {
+            let mut peak = 1.0_f32;
+            for_each_pixel(image, |rgb| peak = peak.max(dot(luminance, *rgb)))?;
+            peak
+        };
+        let operator = self.operator;
+
+        for_each_pixel(image, |rgb| {
+            let mapped = match operator {
+                ToneMapOperator::Reinhard => reinhard(*rgb, luminance, peak),
+                ToneMapOperator::Aces => rgb.map(aces),
+                ToneMapOperator::Clip => *rgb
+            };
+            *rgb = mapped.map(|x| linear_to_srgb(x.clamp(0.0, 1.0)));
+        })?;
+        image
+            .metadata_mut()
+            .set_color_trc(ColorCharacteristics::sRGB);
+        image.convert_depth(depth)?;
+
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[ColorSpace::RGB, ColorSpace::RGBA]
+    }
+}
+
+#[inline]
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Extended Reinhard, mapping `peak` luminance to 1
+#[inline]
+fn reinhard(rgb: [f32; 3], luminance: [f32; 3], peak: f32) -> [f32; 3] {
+    let l = dot(luminance, rgb);
+
+    if l <= 0.0 {
+        return [0.0; 3];
+    }
+    let mapped = l * (1.0 + l / (peak * peak)) / (1.0 + l);
+    let scale = mapped / l;
+
+    rgb.map(|x| x * scale)
+}
+
+/// Krzysztof Narkowicz's fit of the ACES curve
+#[inline]
+fn aces(x: f32) -> f32 {
+    let x = x.max(0.0);
+
+    (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+}
+
+/// Call `function` on the red, green and blue values of every pixel of an `f32` image
+fn for_each_pixel(
+    image: &mut Image, mut function: impl FnMut(&mut [f32; 3])
+) -> Result<(), ImageErrors> {
+    let colorspace = image.colorspace();
+
+    for frame in image.frames_mut() {
+        let channels = frame.channels_mut(colorspace, true);
+        let (r, rest) = channels.split_at_mut(1);
+        let (g, b) = rest.split_at_mut(1);
+
+        let r = r[0].reinterpret_as_mut::<f32>()?;
+        let g = g[0].reinterpret_as_mut::<f32>()?;
+        let b = b[0].reinterpret_as_mut::<f32>()?;
+
+        for ((r, g), b) in r.iter_mut().zip(g.iter_mut()).zip(b.iter_mut()) {
+            let mut rgb = [*r, *g, *b];
+            function(&mut rgb);
+            [*r, *g, *b] = rgb;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::{ColorCharacteristics, ColorSpace};
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::linearize::linear_to_pq;
+    use crate::tone_map::{ToneMap, ToneMapOperator, REFERENCE_WHITE_NITS};
+
+    fn pq_image(nits: &[f32]) -> Image {
+        let pixels: Vec<f32> = nits
+            .iter()
+            .flat_map(|x| [linear_to_pq(x / 10000.0); 3])
+            .collect();
+        let mut image = Image::from_f32(&pixels, nits.len(), 1, ColorSpace::RGB);
+        image.metadata_mut().set_color_trc(ColorCharacteristics::PQ);
+        image
+    }
+
+    #[test]
+    fn test_pq_highlights_are_compressed() {
+        for operator in [ToneMapOperator::Reinhard, ToneMapOperator::Aces] {
+            let mut image = pq_image(&[10.0, 100.0, REFERENCE_WHITE_NITS, 1000.0, 4000.0]);
+
+            ToneMap::new()
+                .set_operator(operator)
+                .execute(&mut image)
+                .unwrap();
+
+            let pixels = &image.flatten_frames::<f32>()[0];
+            let values: Vec<f32> = pixels.chunks(3).map(|x| x[0]).collect();
+
+            // brightness order is kept and everything fits in the SDR range
+            assert!(
+                values.windows(2).all(|x| x[0] < x[1]),
+                "{operator:?} {values:?}"
+            );
+            assert!(values.iter().all(|x| (0.0..=1.0).contains(x)));
+            assert_eq!(
+                image.metadata().color_trc(),
+                Some(ColorCharacteristics::sRGB)
+            );
+        }
+    }
+
+    #[test]
+    fn test_sdr_is_untouched() {
+        let mut image = Image::fill(0.5_f32, ColorSpace::RGB, 4, 4);
+        ToneMap::new().execute(&mut image).unwrap();
+
+        assert!(image.flatten_frames::<f32>()[0]
+            .iter()
+            .all(|x| (x - 0.5).abs() < 1e-6));
+    }
+}
@@ -8,10 +8,8 @@
 
 //!  Interchange row and columns in an image
 //!
-use std::sync::Once;
-
 use zune_core::bit_depth::BitType;
-use zune_core::log::trace;
+use zune_core::simd::{Dispatcher, Isa};
 use zune_image::channel::Channel;
 use zune_image::errors::ImageErrors;
 use zune_image::image::Image;
@@ -20,12 +18,34 @@ use zune_image::traits::OperationsTrait;
 use crate::transpose::scalar::transpose_scalar;
 use crate::utils::execute_on;
 
+/// Interleave rows `distance` apart, for every distance halving down to one
+///
+/// This transposes each 128 bit lane of the rows, row `k` gets column `k` of every lane
+#[allow(unused_macros)]
+macro_rules! interleave {
+    ($rows:expr, $lo:ident, $hi:ident) => {{
+        let rows = $rows;
+        let mut distance = rows.len() / 2;
+
+        while distance > 0 {
+            for i in 0..rows.len() {
+                if (i / distance) % 2 == 0 {
+                    let (a, b) = (rows[i], rows[i + distance]);
+                    rows[i] = $lo(a, b);
+                    rows[i + distance] = $hi(a, b);
+                }
+            }
+            distance /= 2;
+        }
+    }};
+}
+
+pub(crate) mod avx2;
+pub(crate) mod neon;
 pub(crate) mod scalar;
 pub(crate) mod sse41;
 mod tests;
 
-static START: Once = Once::new();
-
 /// Transpose an image
 ///
 /// This mirrors the image along the image top left to bottom-right
@@ -33,6 +53,7 @@ static START: Once = Once::new();
 ///
 /// Done by swapping X and Y indices of the array representation
 #[derive(Default)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Transpose;
 
 impl Transpose {
@@ -53,44 +74,63 @@ impl OperationsTrait for Transpose {
 
         let depth = image.depth();
 
-        let transpose_fn = |channel: &mut Channel| -> Result<(), ImageErrors> {
-            let mut out_channel = Channel::new_with_bit_type(out_dim, depth.bit_type());
-
-            match depth.bit_type() {
-                BitType::U8 => {
-                    transpose_u8(
-                        channel.reinterpret_as::<u8>()?,
-                        out_channel.reinterpret_as_mut::<u8>()?,
-                        width,
-                        height
-                    );
-                }
-                BitType::U16 => {
-                    transpose_u16(
-                        channel.reinterpret_as::<u16>()?,
-                        out_channel.reinterpret_as_mut::<u16>()?,
-                        width,
-                        height
-                    );
+        if width == height {
+            // square channels keep their shape, so swap in place instead of
+            // allocating a new channel
+            let transpose_fn = |channel: &mut Channel| -> Result<(), ImageErrors> {
+                match depth.bit_type() {
+                    BitType::U8 => transpose_in_place(channel.reinterpret_as_mut::<u8>()?, width),
+                    BitType::U16 => transpose_in_place(channel.reinterpret_as_mut::<u16>()?, width),
+                    BitType::F32 => transpose_in_place(channel.reinterpret_as_mut::<f32>()?, width),
+                    d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
                 }
-                BitType::F32 => {
-                    transpose_float(
-                        channel.reinterpret_as()?,
-                        out_channel.reinterpret_as_mut()?,
-                        width,
-                        height
-                    );
-                }
-                d => {
-                    return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d));
+                Ok(())
+            };
+            execute_on(transpose_fn, image, false)?;
+        } else {
+            let transpose_fn = |channel: &mut Channel| -> Result<(), ImageErrors> {
+                let mut out_channel = Channel::new_with_bit_type(out_dim, depth.bit_type());
+
+                match depth.bit_type() {
+                    BitType::U8 => {
+                        transpose_u8(
+                            channel.reinterpret_as::<u8>()?,
+                            out_channel.reinterpret_as_mut::<u8>()?,
+                            width,
+                            height
+                        );
+                    }
+                    BitType::U16 => {
+                        transpose_u16(
+                            channel.reinterpret_as::<u16>()?,
+                            out_channel.reinterpret_as_mut::<u16>()?,
+                            width,
+                            height
+                        );
+                    }
+                    BitType::F32 => {
+                        transpose_float(
+                            channel.reinterpret_as()?,
+                            out_channel.reinterpret_as_mut()?,
+                            width,
+                            height
+                        );
+                    }
+                    d => {
+                        return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d));
+                    }
                 }
+                *channel = out_channel;
+                Ok(())
             };
-            *channel = out_channel;
-            Ok(())
-        };
 
-        execute_on(transpose_fn, image, false)?;
-        image.set_dimensions(height, width);
+            execute_on(transpose_fn, image, false)?;
+            image.set_dimensions(height, width);
+        }
+
+        if let Some(resolution) = image.metadata().resolution() {
+            image.metadata_mut().set_resolution(resolution.transposed());
+        }
 
         Ok(())
     }
@@ -99,98 +139,192 @@ impl OperationsTrait for Transpose {
     }
 }
 
+/// A transpose kernel, `(in_matrix, out_matrix, width, height)`
+type TransposeFn<T> = unsafe fn(&[T], &mut [T], usize, usize);
+
+// SAFETY: every kernel only uses instructions of its instruction set
+static TRANSPOSE_U16: Dispatcher<TransposeFn<u16>> = unsafe {
+    Dispatcher::new(
+        "transpose_u16",
+        &[
+            #[cfg(all(feature = "avx2", any(target_arch = "x86", target_arch = "x86_64")))]
+            (Isa::Avx2, avx2::transpose_avx2_u16),
+            #[cfg(all(feature = "sse41", any(target_arch = "x86", target_arch = "x86_64")))]
+            (Isa::Sse41, sse41::transpose_sse41_u16),
+            #[cfg(all(feature = "neon", target_arch = "aarch64", target_feature = "neon"))]
+            (Isa::Neon, neon::transpose_neon_u16),
+            (Isa::Scalar, transpose_scalar)
+        ]
+    )
+};
+
+// SAFETY: every kernel only uses instructions of its instruction set
+static TRANSPOSE_U8: Dispatcher<TransposeFn<u8>> = unsafe {
+    Dispatcher::new(
+        "transpose_u8",
+        &[
+            #[cfg(all(feature = "avx2", any(target_arch = "x86", target_arch = "x86_64")))]
+            (Isa::Avx2, avx2::transpose_avx2_u8),
+            #[cfg(all(feature = "sse41", any(target_arch = "x86", target_arch = "x86_64")))]
+            (Isa::Sse41, sse41::transpose_sse41_u8),
+            #[cfg(all(feature = "neon", target_arch = "aarch64", target_feature = "neon"))]
+            (Isa::Neon, neon::transpose_neon_u8),
+            (Isa::Scalar, transpose_scalar)
+        ]
+    )
+};
+
+// SAFETY: every kernel only uses instructions of its instruction set
+static TRANSPOSE_F32: Dispatcher<TransposeFn<f32>> = unsafe {
+    Dispatcher::new(
+        "transpose_float",
+        &[
+            #[cfg(all(feature = "sse41", any(target_arch = "x86", target_arch = "x86_64")))]
+            (Isa::Sse41, sse41::transpose_sse_float),
+            #[cfg(all(feature = "neon", target_arch = "aarch64", target_feature = "neon"))]
+            (Isa::Neon, neon::transpose_neon_f32),
+            (Isa::Scalar, transpose_scalar)
+        ]
+    )
+};
+
+// SAFETY: every kernel only uses instructions of its instruction set
+static TRANSPOSE_U32: Dispatcher<TransposeFn<u32>> = unsafe {
+    Dispatcher::new(
+        "transpose_u32",
+        &[
+            #[cfg(all(feature = "sse41", any(target_arch = "x86", target_arch = "x86_64")))]
+            (Isa::Sse41, sse41::transpose_sse_u32),
+            (Isa::Scalar, transpose_scalar)
+        ]
+    )
+};
+
 pub fn transpose_u16(in_matrix: &[u16], out_matrix: &mut [u16], width: usize, height: usize) {
-    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-    {
-        #[cfg(feature = "sse41")]
-        {
-            use crate::transpose::sse41::transpose_sse41_u16;
-
-            if is_x86_feature_detected!("sse4.1") {
-                START.call_once(|| {
-                    trace!("Using SSE4.1 transpose_u16 algorithm");
-                });
-                unsafe {
-                    return transpose_sse41_u16(in_matrix, out_matrix, width, height);
-                }
-            }
-        }
-    }
-    START.call_once(|| {
-        trace!("Using scalar transpose_u16 algorithm");
-    });
-    transpose_scalar(in_matrix, out_matrix, width, height);
+    // SAFETY: the dispatcher only returns kernels the cpu supports
+    unsafe { TRANSPOSE_U16.get()(in_matrix, out_matrix, width, height) }
 }
 
 pub fn transpose_u8(in_matrix: &[u8], out_matrix: &mut [u8], width: usize, height: usize) {
-    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-    {
-        #[cfg(feature = "sse41")]
-        {
-            use crate::transpose::sse41::transpose_sse41_u8;
-
-            if is_x86_feature_detected!("sse4.1") {
-                START.call_once(|| {
-                    trace!("Using SSE4.1 transpose u8 algorithm");
-                });
-                unsafe {
-                    return transpose_sse41_u8(in_matrix, out_matrix, width, height);
-                }
-            }
-        }
-    }
-    START.call_once(|| {
-        trace!("Using scalar transpose u8 algorithm");
-    });
-    transpose_scalar(in_matrix, out_matrix, width, height);
+    // SAFETY: the dispatcher only returns kernels the cpu supports
+    unsafe { TRANSPOSE_U8.get()(in_matrix, out_matrix, width, height) }
 }
 
 pub fn transpose_float(in_matrix: &[f32], out_matrix: &mut [f32], width: usize, height: usize) {
-    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-    {
-        #[cfg(feature = "sse41")]
-        {
-            use crate::transpose::sse41::transpose_sse_float;
-
-            if is_x86_feature_detected!("sse4.1") {
-                START.call_once(|| {
-                    trace!("Using SSE4.1 transpose u8 algorithm");
-                });
-                unsafe {
-                    return transpose_sse_float(in_matrix, out_matrix, width, height);
-                }
-            }
-        }
-    }
-    START.call_once(|| {
-        trace!("Using scalar transpose u8 algorithm");
-    });
-    transpose_scalar(in_matrix, out_matrix, width, height);
+    // SAFETY: the dispatcher only returns kernels the cpu supports
+    unsafe { TRANSPOSE_F32.get()(in_matrix, out_matrix, width, height) }
 }
 
 pub fn transpose_u32(in_matrix: &[u32], out_matrix: &mut [u32], width: usize, height: usize) {
-    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-    {
-        #[cfg(feature = "sse41")]
-        {
-            use crate::transpose::sse41::transpose_sse_u32;
-
-            if is_x86_feature_detected!("sse") {
-                START.call_once(|| {
-                    trace!("Using SSE4.1 transpose u8 algorithm");
-                });
-                unsafe {
-                    return transpose_sse_u32(in_matrix, out_matrix, width, height);
+    // SAFETY: the dispatcher only returns kernels the cpu supports
+    unsafe { TRANSPOSE_U32.get()(in_matrix, out_matrix, width, height) }
+}
+
+/// Transpose a `size` by `size` matrix without a second buffer
+///
+/// # Panics
+/// If the matrix doesn't have `size * size` elements
+pub fn transpose_in_place<T: Copy>(matrix: &mut [T], size: usize) {
+    scalar::transpose_square_in_place(matrix, size);
+}
+
+/// Width in bytes of the square blocks [`transpose_tiled`] goes through
+///
+/// Every tile of a row of tiles is written to different rows of the output, so going
+/// through whole rows of tiles of large images touches more output rows than the
+/// caches (and the TLB) hold, and every tile then misses on its writes.
+///
+/// Blocks are as many rows tall as elements wide, so a block of u8 reads and writes
+/// 64 KiB, which stays in the L2 cache and lets the tiles of a block reuse the
+/// output lines the previous ones loaded.
+#[cfg(any(
+    all(feature = "sse41", any(target_arch = "x86", target_arch = "x86_64")),
+    all(feature = "neon", target_arch = "aarch64", target_feature = "neon")
+))]
+const BLOCK_BYTES: usize = 256;
+
+/// Transpose in tiles of `TILE` by `TILE` with `kernel`, the rest is done
+/// one element at a time
+///
+/// Tiles are transposed block by block, see [`BLOCK_BYTES`], so the speed
+/// doesn't drop once images stop fitting in the cache
+///
+/// Used by the SIMD versions, with a kernel transposing one tile
+#[cfg(any(
+    all(feature = "sse41", any(target_arch = "x86", target_arch = "x86_64")),
+    all(feature = "neon", target_arch = "aarch64", target_feature = "neon")
+))]
+pub(crate) unsafe fn transpose_tiled<T: Copy + Default, const TILE: usize>(
+    in_matrix: &[T], out_matrix: &mut [T], width: usize, height: usize,
+    kernel: unsafe fn(&[T], &mut [T], usize, usize)
+) {
+    let dimensions = width * height;
+
+    assert_eq!(
+        in_matrix.len(),
+        dimensions,
+        "In matrix dimensions do not match width and height"
+    );
+
+    assert_eq!(
+        out_matrix.len(),
+        dimensions,
+        "Out matrix dimensions do not match width and height"
+    );
+
+    if width < TILE || height < TILE {
+        return crate::transpose::transpose_scalar(in_matrix, out_matrix, width, height);
+    }
+    let (tiles_w, tiles_h) = (width / TILE, height / TILE);
+    // tiles per side of a block
+    let block = (BLOCK_BYTES / (TILE * core::mem::size_of::<T>())).max(1);
+
+    for block_i in (0..tiles_h).step_by(block) {
+        for block_j in (0..tiles_w).step_by(block) {
+            for i in block_i..(block_i + block).min(tiles_h) {
+                let in_width_stride = &in_matrix[i * TILE * width..];
+
+                for j in block_j..(block_j + block).min(tiles_w) {
+                    let out_height_stride = &mut out_matrix[(j * height * TILE) + (i * TILE)..];
+
+                    kernel(
+                        &in_width_stride[(j * TILE)..],
+                        out_height_stride,
+                        width,
+                        height
+                    );
                 }
             }
         }
     }
-    START.call_once(|| {
-        trace!("Using scalar transpose u8 algorithm");
-    });
-    transpose_scalar(in_matrix, out_matrix, width, height);
+    // Deal with the part that hasn't been copied
+    //
+    //┌──────────┬─────┐
+    //│          │     │
+    //│          │     │
+    //│  Done    │ B   │
+    //│          │     │
+    //│          │     │
+    //├──────────┘-----│
+    //│      C         │
+    //└────────────────┘
+    // Everything in region b and C isn't done
+    let rem_w = width - (width % TILE);
+    let rem_h = height - (height % TILE);
+
+    for i in rem_h..height {
+        for j in 0..width {
+            out_matrix[(j * height) + i] = in_matrix[(i * width) + j];
+        }
+    }
+    for i in rem_w..width {
+        for j in 0..height {
+            out_matrix[(i * height) + j] = in_matrix[(j * width) + i];
+        }
+    }
 }
 
+
 pub fn transpose_generic<T: Default + Copy>(
     in_matrix: &[T], out_matrix: &mut [T], width: usize, height: usize
 ) {
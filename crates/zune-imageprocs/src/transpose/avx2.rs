@@ -0,0 +1,103 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+#![cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#![cfg(feature = "avx2")]
+//! AVX2 transposition
+//!
+//! Same tiling as the [SSE4.1](super::sse41) version, with bigger tiles, 16 by 16 for u16
+//! and 32 by 32 for u8, so every load and store moves a whole 256 bit register.
+//!
+//! AVX2 unpack instructions work on each 128 bit lane on its own, so interleaving the rows
+//! of a tile transposes the left half and the right half of it separately, row `k`
+//! ends up holding column `k` of the left half in its low lane and column `k` of the
+//! right half in its high lane. Doing that for the top and the bottom rows of the tile and
+//! swapping lanes between the results with `_mm256_permute2x128_si256` completes
+//! the transposition
+//!```text
+//!  in            per lane         out
+//! ┌────┬────┐   ┌────┬────┐   ┌────┬────┐
+//! │ A  │ B  │   │ Aᵀ │ Bᵀ │   │ Aᵀ │ Cᵀ │
+//! ├────┼────┤ → ├────┼────┤ → ├────┼────┤
+//! │ C  │ D  │   │ Cᵀ │ Dᵀ │   │ Bᵀ │ Dᵀ │
+//! └────┴────┘   └────┴────┘   └────┴────┘
+//!```
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+use crate::transpose::transpose_tiled;
+
+#[target_feature(enable = "avx2")]
+unsafe fn transpose_16_by_16_u16(
+    in_matrix: &[u16], out: &mut [u16], in_stride: usize, out_stride: usize
+) {
+    // ensure the reads and writes are in bounds
+    assert!(in_stride * 15 + 16 <= in_matrix.len());
+    assert!(out_stride * 15 + 16 <= out.len());
+
+    let mut top = [_mm256_setzero_si256(); 8];
+    let mut bottom = [_mm256_setzero_si256(); 8];
+
+    for (i, (t, b)) in top.iter_mut().zip(bottom.iter_mut()).enumerate() {
+        *t = _mm256_loadu_si256(in_matrix.as_ptr().add(in_stride * i).cast());
+        *b = _mm256_loadu_si256(in_matrix.as_ptr().add(in_stride * (i + 8)).cast());
+    }
+    interleave!(&mut top, _mm256_unpacklo_epi16, _mm256_unpackhi_epi16);
+    interleave!(&mut bottom, _mm256_unpacklo_epi16, _mm256_unpackhi_epi16);
+
+    for (i, (t, b)) in top.iter().zip(bottom.iter()).enumerate() {
+        let left = _mm256_permute2x128_si256::<0x20>(*t, *b);
+        let right = _mm256_permute2x128_si256::<0x31>(*t, *b);
+
+        _mm256_storeu_si256(out.as_mut_ptr().add(out_stride * i).cast(), left);
+        _mm256_storeu_si256(out.as_mut_ptr().add(out_stride * (i + 8)).cast(), right);
+    }
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn transpose_32_by_32_u8(
+    in_matrix: &[u8], out: &mut [u8], in_stride: usize, out_stride: usize
+) {
+    // ensure the reads and writes are in bounds
+    assert!(in_stride * 31 + 32 <= in_matrix.len());
+    assert!(out_stride * 31 + 32 <= out.len());
+
+    let mut top = [_mm256_setzero_si256(); 16];
+    let mut bottom = [_mm256_setzero_si256(); 16];
+
+    for (i, (t, b)) in top.iter_mut().zip(bottom.iter_mut()).enumerate() {
+        *t = _mm256_loadu_si256(in_matrix.as_ptr().add(in_stride * i).cast());
+        *b = _mm256_loadu_si256(in_matrix.as_ptr().add(in_stride * (i + 16)).cast());
+    }
+    interleave!(&mut top, _mm256_unpacklo_epi8, _mm256_unpackhi_epi8);
+    interleave!(&mut bottom, _mm256_unpacklo_epi8, _mm256_unpackhi_epi8);
+
+    for (i, (t, b)) in top.iter().zip(bottom.iter()).enumerate() {
+        let left = _mm256_permute2x128_si256::<0x20>(*t, *b);
+        let right = _mm256_permute2x128_si256::<0x31>(*t, *b);
+
+        _mm256_storeu_si256(out.as_mut_ptr().add(out_stride * i).cast(), left);
+        _mm256_storeu_si256(out.as_mut_ptr().add(out_stride * (i + 16)).cast(), right);
+    }
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn transpose_avx2_u16(
+    in_matrix: &[u16], out_matrix: &mut [u16], width: usize, height: usize
+) {
+    transpose_tiled::<u16, 16>(in_matrix, out_matrix, width, height, transpose_16_by_16_u16);
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn transpose_avx2_u8(
+    in_matrix: &[u8], out_matrix: &mut [u8], width: usize, height: usize
+) {
+    transpose_tiled::<u8, 32>(in_matrix, out_matrix, width, height, transpose_32_by_32_u8);
+}
@@ -0,0 +1,135 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+#![cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+#![cfg(feature = "neon")]
+//! NEON transposition
+//!
+//! Same tiling as the [SSE4.1](super::sse41) version, `zip1` and `zip2` interleave
+//! whole registers like the SSE unpack instructions do, so one 128 bit register
+//! per row of the tile is enough, 16 by 16 for u8, 8 by 8 for u16 and 4 by 4 for f32.
+//!
+//! NEON is part of every aarch64 cpu, so it's enabled at compile time instead of being
+//! detected at runtime
+use core::arch::aarch64::*;
+
+use crate::transpose::transpose_tiled;
+
+unsafe fn transpose_16_by_16_u8(
+    in_matrix: &[u8], out: &mut [u8], in_stride: usize, out_stride: usize
+) {
+    // ensure the reads and writes are in bounds
+    assert!(in_stride * 15 + 16 <= in_matrix.len());
+    assert!(out_stride * 15 + 16 <= out.len());
+
+    let mut rows = [vdupq_n_u8(0); 16];
+
+    for (i, row) in rows.iter_mut().enumerate() {
+        *row = vld1q_u8(in_matrix.as_ptr().add(in_stride * i));
+    }
+    interleave!(&mut rows, vzip1q_u8, vzip2q_u8);
+
+    for (i, row) in rows.iter().enumerate() {
+        vst1q_u8(out.as_mut_ptr().add(out_stride * i), *row);
+    }
+}
+
+unsafe fn transpose_8_by_8_u16(
+    in_matrix: &[u16], out: &mut [u16], in_stride: usize, out_stride: usize
+) {
+    // ensure the reads and writes are in bounds
+    assert!(in_stride * 7 + 8 <= in_matrix.len());
+    assert!(out_stride * 7 + 8 <= out.len());
+
+    let mut rows = [vdupq_n_u16(0); 8];
+
+    for (i, row) in rows.iter_mut().enumerate() {
+        *row = vld1q_u16(in_matrix.as_ptr().add(in_stride * i));
+    }
+    interleave!(&mut rows, vzip1q_u16, vzip2q_u16);
+
+    for (i, row) in rows.iter().enumerate() {
+        vst1q_u16(out.as_mut_ptr().add(out_stride * i), *row);
+    }
+}
+
+unsafe fn transpose_4_by_4_f32(
+    in_matrix: &[f32], out: &mut [f32], in_stride: usize, out_stride: usize
+) {
+    // ensure the reads and writes are in bounds
+    assert!(in_stride * 3 + 4 <= in_matrix.len());
+    assert!(out_stride * 3 + 4 <= out.len());
+
+    let mut rows = [vdupq_n_f32(0.0); 4];
+
+    for (i, row) in rows.iter_mut().enumerate() {
+        *row = vld1q_f32(in_matrix.as_ptr().add(in_stride * i));
+    }
+    interleave!(&mut rows, vzip1q_f32, vzip2q_f32);
+
+    for (i, row) in rows.iter().enumerate() {
+        vst1q_f32(out.as_mut_ptr().add(out_stride * i), *row);
+    }
+}
+
+pub unsafe fn transpose_neon_u8(
+    in_matrix: &[u8], out_matrix: &mut [u8], width: usize, height: usize
+) {
+    transpose_tiled::<u8, 16>(in_matrix, out_matrix, width, height, transpose_16_by_16_u8);
+}
+
+pub unsafe fn transpose_neon_u16(
+    in_matrix: &[u16], out_matrix: &mut [u16], width: usize, height: usize
+) {
+    transpose_tiled::<u16, 8>(in_matrix, out_matrix, width, height, transpose_8_by_8_u16);
+}
+
+pub unsafe fn transpose_neon_f32(
+    in_matrix: &[f32], out_matrix: &mut [f32], width: usize, height: usize
+) {
+    transpose_tiled::<f32, 4>(in_matrix, out_matrix, width, height, transpose_4_by_4_f32);
+}
+
+#[cfg(test)]
+mod tests {
+    use nanorand::Rng;
+
+    use crate::transpose::neon::{transpose_neon_f32, transpose_neon_u16, transpose_neon_u8};
+    use crate::transpose::scalar::transpose_scalar;
+
+    #[test]
+    fn test_transpose_neon_scalar_identical() {
+        let mut rng = nanorand::WyRand::new();
+
+        // full tiles and leftover rows and columns of every tile size
+        for (width, height) in [(32, 16), (70, 45), (17, 100), (9, 9), (5, 3)] {
+            let mut in_u8: Vec<u8> = vec![0; width * height];
+            let mut in_u16: Vec<u16> = vec![0; width * height];
+            rng.fill(&mut in_u8);
+            rng.fill(&mut in_u16);
+            let in_f32: Vec<f32> = in_u16.iter().map(|x| f32::from(*x)).collect();
+
+            let (mut neon_u8, mut scalar_u8) = (vec![0; width * height], vec![0; width * height]);
+            let (mut neon_u16, mut scalar_u16) = (vec![0; width * height], vec![0; width * height]);
+            let (mut neon_f32, mut scalar_f32) =
+                (vec![0.0; width * height], vec![0.0; width * height]);
+            unsafe {
+                transpose_neon_u8(&in_u8, &mut neon_u8, width, height);
+                transpose_neon_u16(&in_u16, &mut neon_u16, width, height);
+                transpose_neon_f32(&in_f32, &mut neon_f32, width, height);
+            }
+            transpose_scalar(&in_u8, &mut scalar_u8, width, height);
+            transpose_scalar(&in_u16, &mut scalar_u16, width, height);
+            transpose_scalar(&in_f32, &mut scalar_f32, width, height);
+
+            assert_eq!(neon_u8, scalar_u8, "u8 {width}x{height}");
+            assert_eq!(neon_u16, scalar_u16, "u16 {width}x{height}");
+            assert_eq!(neon_f32, scalar_f32, "f32 {width}x{height}");
+        }
+    }
+}
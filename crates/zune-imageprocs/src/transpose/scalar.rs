@@ -50,7 +50,7 @@ pub fn transpose_scalar<T: Copy + Default>(
                     // to listen to me, the MASTER.
                     #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
                     unsafe {
-                        std::arch::asm!("");
+                        core::arch::asm!("");
                     }
                 }
             }
@@ -63,8 +63,8 @@ pub fn transpose_scalar<T: Copy + Default>(
             }
         }
     }
-    let rem_w = width - (width & 7) - 1;
-    let rem_h = height - (height & 7) - 1;
+    let rem_w = width - (width & 7);
+    let rem_h = height - (height & 7);
 
     for i in rem_h..height {
         for j in 0..width {
@@ -77,3 +77,66 @@ pub fn transpose_scalar<T: Copy + Default>(
         }
     }
 }
+
+/// Transpose a `size` by `size` matrix in place
+///
+/// Tiles above the diagonal are swapped with their mirror below it, tile by tile,
+/// so both tiles stay in cache while their elements are swapped
+pub fn transpose_square_in_place<T: Copy>(matrix: &mut [T], size: usize) {
+    const TILE: usize = 32;
+
+    assert_eq!(
+        matrix.len(),
+        size * size,
+        "Matrix dimensions do not match size"
+    );
+
+    for tile_y in (0..size).step_by(TILE) {
+        for tile_x in (tile_y..size).step_by(TILE) {
+            for y in tile_y..(tile_y + TILE).min(size) {
+                // tiles on the diagonal only swap the elements above it
+                let start = if tile_x == tile_y { y + 1 } else { tile_x };
+
+                for x in start..(tile_x + TILE).min(size) {
+                    matrix.swap(y * size + x, x * size + y);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transpose::scalar::{transpose_scalar, transpose_square_in_place};
+
+    #[test]
+    fn test_transpose_scalar_small_and_odd_sizes() {
+        for (width, height) in [(1, 1), (5, 3), (3, 9), (8, 8), (13, 17), (16, 2)] {
+            let in_matrix: Vec<u16> = (0..).take(width * height).collect();
+            let mut out_matrix = vec![0; width * height];
+
+            transpose_scalar(&in_matrix, &mut out_matrix, width, height);
+
+            for y in 0..height {
+                for x in 0..width {
+                    assert_eq!(out_matrix[x * height + y], in_matrix[y * width + x]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_transpose_square_in_place_matches_scalar() {
+        // within a tile, full tiles and partial tiles at the edges
+        for size in [1, 2, 7, 32, 64, 77] {
+            let in_matrix: Vec<u32> = (0..u32::try_from(size * size).unwrap()).collect();
+            let mut out_matrix = vec![0; size * size];
+            let mut in_place = in_matrix.clone();
+
+            transpose_scalar(&in_matrix, &mut out_matrix, size, size);
+            transpose_square_in_place(&mut in_place, size);
+
+            assert_eq!(in_place, out_matrix, "{size}x{size}");
+        }
+    }
+}
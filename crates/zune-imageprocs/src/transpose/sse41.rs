@@ -64,12 +64,14 @@
 //! A good choice for me was 8 by 8 u8 sizes, so the gist of the algorithm
 //! becomes tile into 8 by 8 sub-matrices, transpose_u16 in place, write out transposition
 //!
-//!
-//!
+//! Large images add one more level, tiles are visited in blocks small enough for their
+//! input and output rows to stay in the cache, see [`transpose_tiled`]
 #[cfg(target_arch = "x86")]
-use std::arch::x86::*;
+use core::arch::x86::*;
 #[cfg(target_arch = "x86_64")]
-use std::arch::x86_64::*;
+use core::arch::x86_64::*;
+
+use crate::transpose::transpose_tiled;
 
 #[allow(clippy::erasing_op, clippy::identity_op)]
 #[rustfmt::skip]
@@ -225,155 +227,13 @@ unsafe fn transpose_8_by_8_u8(
 pub unsafe fn transpose_sse41_u16(
     in_matrix: &[u16], out_matrix: &mut [u16], width: usize, height: usize
 ) {
-    const SMALL_WIDTH_THRESHOLD: usize = 8;
-
-    let dimensions = width * height;
-
-    assert_eq!(
-        in_matrix.len(),
-        dimensions,
-        "In matrix dimensions do not match width and height"
-    );
-
-    assert_eq!(
-        out_matrix.len(),
-        dimensions,
-        "Out matrix dimensions do not match width and height"
-    );
-
-    if width < SMALL_WIDTH_THRESHOLD {
-        return crate::transpose::transpose_scalar(in_matrix, out_matrix, width, height);
-    }
-
-    // get how many iterations we can go per width
-    //
-    // ┌───────┬─────────┬────────┬───────┬──────┬──┐
-    // │       │         │        │       │      │  │
-    // │   8   │    8    │   8    │   8   │  8   │ l│
-    // │       │         │        │       │      │  │
-    // │       │         │        │       │      │  │
-    // └───────┴─────────┴────────┴───────┴──────┴──┘
-    //
-    // We want to figure out how many times we can divide the width into
-    // 8
-    let width_iterations = width / 8;
-    let sin_height = 8 * width;
-
-    for (i, in_width_stride) in in_matrix.chunks_exact(sin_height).enumerate() {
-        for j in 0..width_iterations {
-            let out_height_stride = &mut out_matrix[(j * height * 8) + (i * 8)..];
-
-            transpose_8_by_8_u16(
-                &in_width_stride[(j * 8)..],
-                out_height_stride,
-                width,
-                height
-            );
-        }
-    }
-    // Deal with the part that hasn't been copied
-    //
-    //
-    //┌──────────┬─────┐
-    //│          │     │
-    //│          │     │
-    //│  Done    │ B   │
-    //│          │     │
-    //│          │     │
-    //├──────────┘-----│
-    //│      C         │
-    //└────────────────┘
-    // Everything in region b and C isn't done
-    let rem_w = width - (width & 7);
-    let rem_h = height - (height & 7);
-
-    for i in rem_h..height {
-        for j in 0..width {
-            out_matrix[(j * height) + i] = in_matrix[(i * width) + j];
-        }
-    }
-    for i in rem_w..width {
-        for j in 0..height {
-            out_matrix[(i * height) + j] = in_matrix[(j * width) + i];
-        }
-    }
+    transpose_tiled::<u16, 8>(in_matrix, out_matrix, width, height, transpose_8_by_8_u16);
 }
 
 pub unsafe fn transpose_sse41_u8(
     in_matrix: &[u8], out_matrix: &mut [u8], width: usize, height: usize
 ) {
-    const SMALL_WIDTH_THRESHOLD: usize = 8;
-
-    let dimensions = width * height;
-
-    assert_eq!(
-        in_matrix.len(),
-        dimensions,
-        "In matrix dimensions do not match width and height"
-    );
-
-    assert_eq!(
-        out_matrix.len(),
-        dimensions,
-        "Out matrix dimensions do not match width and height"
-    );
-
-    if width < SMALL_WIDTH_THRESHOLD {
-        return crate::transpose::transpose_scalar(in_matrix, out_matrix, width, height);
-    }
-
-    // get how many iterations we can go per width
-    //
-    // ┌───────┬─────────┬────────┬───────┬──────┬──┐
-    // │       │         │        │       │      │  │
-    // │   8   │    8    │   8    │   8   │  8   │ l│
-    // │       │         │        │       │      │  │
-    // │       │         │        │       │      │  │
-    // └───────┴─────────┴────────┴───────┴──────┴──┘
-    //
-    // We want to figure out how many times we can divide the width into
-    // 8
-    let width_iterations = width / 8;
-    let sin_height = 8 * width;
-
-    for (i, in_width_stride) in in_matrix.chunks_exact(sin_height).enumerate() {
-        for j in 0..width_iterations {
-            let out_height_stride = &mut out_matrix[(j * height * 8) + (i * 8)..];
-
-            transpose_8_by_8_u8(
-                &in_width_stride[(j * 8)..],
-                out_height_stride,
-                width,
-                height
-            );
-        }
-    }
-    // Deal with the part that hasn't been copied
-    //
-    //
-    //┌──────────┬─────┐
-    //│          │     │
-    //│          │     │
-    //│  Done    │ B   │
-    //│          │     │
-    //│          │     │
-    //├──────────┘-----│
-    //│      C         │
-    //└────────────────┘
-    // Everything in region b and C isn't done
-    let rem_w = width - (width & 7);
-    let rem_h = height - (height & 7);
-
-    for i in rem_h..height {
-        for j in 0..width {
-            out_matrix[(j * height) + i] = in_matrix[(i * width) + j];
-        }
-    }
-    for i in rem_w..width {
-        for j in 0..height {
-            out_matrix[(i * height) + j] = in_matrix[(j * width) + i];
-        }
-    }
+    transpose_tiled::<u8, 8>(in_matrix, out_matrix, width, height, transpose_8_by_8_u8);
 }
 
 unsafe fn transpose_sse_float_4x4_inner(
@@ -408,69 +268,13 @@ unsafe fn transpose_sse_float_4x4_inner(
 pub unsafe fn transpose_sse_float(
     in_matrix: &[f32], out_matrix: &mut [f32], width: usize, height: usize
 ) {
-    const SMALL_WIDTH_THRESHOLD: usize = 4;
-
-    let dimensions = width * height;
-
-    assert_eq!(
-        in_matrix.len(),
-        dimensions,
-        "In matrix dimensions do not match width and height"
-    );
-
-    assert_eq!(
-        out_matrix.len(),
-        dimensions,
-        "Out matrix dimensions do not match width and height"
+    transpose_tiled::<f32, 4>(
+        in_matrix,
+        out_matrix,
+        width,
+        height,
+        transpose_sse_float_4x4_inner
     );
-
-    if width < SMALL_WIDTH_THRESHOLD {
-        return crate::transpose::transpose_scalar(in_matrix, out_matrix, width, height);
-    }
-
-    // We want to figure out how many times we can divide the width into
-    // 4 since inner loop transposes by 4
-    let width_iterations = width / 4;
-    let sin_height = 4 * width;
-
-    for (i, in_width_stride) in in_matrix.chunks_exact(sin_height).enumerate() {
-        for j in 0..width_iterations {
-            let out_height_stride = &mut out_matrix[(j * height * 4) + (i * 4)..];
-
-            transpose_sse_float_4x4_inner(
-                &in_width_stride[(j * 4)..],
-                out_height_stride,
-                width,
-                height
-            );
-        }
-    }
-    // Deal with the part that hasn't been copied
-    //
-    //
-    //┌──────────┬─────┐
-    //│          │     │
-    //│          │     │
-    //│  Done    │ B   │
-    //│          │     │
-    //│          │     │
-    //├──────────┘-----│
-    //│      C         │
-    //└────────────────┘
-    // Everything in region b and C isn't done
-    let rem_w = width - (width & 3);
-    let rem_h = height - (height & 3);
-
-    for i in rem_h..height {
-        for j in 0..width {
-            out_matrix[(j * height) + i] = in_matrix[(i * width) + j];
-        }
-    }
-    for i in rem_w..width {
-        for j in 0..height {
-            out_matrix[(i * height) + j] = in_matrix[(j * width) + i];
-        }
-    }
 }
 
 pub unsafe fn transpose_sse_u32_inner(
@@ -505,67 +309,11 @@ pub unsafe fn transpose_sse_u32_inner(
 pub unsafe fn transpose_sse_u32(
     in_matrix: &[u32], out_matrix: &mut [u32], width: usize, height: usize
 ) {
-    const SMALL_WIDTH_THRESHOLD: usize = 4;
-
-    let dimensions = width * height;
-
-    assert_eq!(
-        in_matrix.len(),
-        dimensions,
-        "In matrix dimensions do not match width and height"
+    transpose_tiled::<u32, 4>(
+        in_matrix,
+        out_matrix,
+        width,
+        height,
+        transpose_sse_u32_inner
     );
-
-    assert_eq!(
-        out_matrix.len(),
-        dimensions,
-        "Out matrix dimensions do not match width and height"
-    );
-
-    if width < SMALL_WIDTH_THRESHOLD {
-        return crate::transpose::transpose_scalar(in_matrix, out_matrix, width, height);
-    }
-
-    // We want to figure out how many times we can divide the width into
-    // 4 since inner loop transposes by 4
-    let width_iterations = width / 4;
-    let sin_height = 4 * width;
-
-    for (i, in_width_stride) in in_matrix.chunks_exact(sin_height).enumerate() {
-        for j in 0..width_iterations {
-            let out_height_stride = &mut out_matrix[(j * height * 4) + (i * 4)..];
-
-            transpose_sse_u32_inner(
-                &in_width_stride[(j * 4)..],
-                out_height_stride,
-                width,
-                height
-            );
-        }
-    }
-    // Deal with the part that hasn't been copied
-    //
-    //
-    //┌──────────┬─────┐
-    //│          │     │
-    //│          │     │
-    //│  Done    │ B   │
-    //│          │     │
-    //│          │     │
-    //├──────────┘-----│
-    //│      C         │
-    //└────────────────┘
-    // Everything in region b and C isn't done
-    let rem_w = width - (width & 3);
-    let rem_h = height - (height & 3);
-
-    for i in rem_h..height {
-        for j in 0..width {
-            out_matrix[(j * height) + i] = in_matrix[(i * width) + j];
-        }
-    }
-    for i in rem_w..width {
-        for j in 0..height {
-            out_matrix[(i * height) + j] = in_matrix[(j * width) + i];
-        }
-    }
 }
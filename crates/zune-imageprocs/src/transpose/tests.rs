@@ -64,3 +64,87 @@ fn test_transpose_sse_float_scalar_identical() {
         assert_eq!(a, b);
     }
 }
+
+#[test]
+#[cfg(feature = "avx2")]
+fn test_transpose_avx2_scalar_identical() {
+    use nanorand::Rng;
+
+    use crate::transpose;
+
+    if !is_x86_feature_detected!("avx2") {
+        return;
+    }
+    let mut rng = nanorand::WyRand::new();
+
+    // full tiles and leftover rows and columns of both tile sizes
+    for (width, height) in [(64, 32), (70, 45), (33, 100), (16, 16), (20, 9)] {
+        let mut in_u8: Vec<u8> = vec![0; width * height];
+        let mut in_u16: Vec<u16> = vec![0; width * height];
+        rng.fill(&mut in_u8);
+        rng.fill(&mut in_u16);
+
+        let mut avx_u8 = vec![0; width * height];
+        let mut scalar_u8 = vec![0; width * height];
+        let mut avx_u16 = vec![0; width * height];
+        let mut scalar_u16 = vec![0; width * height];
+        unsafe {
+            transpose::avx2::transpose_avx2_u8(&in_u8, &mut avx_u8, width, height);
+            transpose::avx2::transpose_avx2_u16(&in_u16, &mut avx_u16, width, height);
+        }
+        transpose::scalar::transpose_scalar(&in_u8, &mut scalar_u8, width, height);
+        transpose::scalar::transpose_scalar(&in_u16, &mut scalar_u16, width, height);
+
+        assert_eq!(avx_u8, scalar_u8, "u8 {width}x{height}");
+        assert_eq!(avx_u16, scalar_u16, "u16 {width}x{height}");
+    }
+}
+
+#[test]
+fn test_transpose_blocked_kernels_match_scalar() {
+    use nanorand::Rng;
+
+    use crate::transpose::{scalar, TRANSPOSE_F32, TRANSPOSE_U16, TRANSPOSE_U8};
+
+    let mut rng = nanorand::WyRand::new();
+
+    // more than one block in each direction, with blocks and tiles left over
+    let (width, height) = (1000, 555);
+
+    let mut in_u8: Vec<u8> = vec![0; width * height];
+    let mut in_u16: Vec<u16> = vec![0; width * height];
+    rng.fill(&mut in_u8);
+    rng.fill(&mut in_u16);
+    let in_f32: Vec<f32> = in_u16.iter().map(|x| f32::from(*x)).collect();
+
+    let mut expected_u8 = vec![0; width * height];
+    let mut expected_u16 = vec![0; width * height];
+    let mut expected_f32 = vec![0.0; width * height];
+    scalar::transpose_scalar(&in_u8, &mut expected_u8, width, height);
+    scalar::transpose_scalar(&in_u16, &mut expected_u16, width, height);
+    scalar::transpose_scalar(&in_f32, &mut expected_f32, width, height);
+
+    for (isa, kernel) in TRANSPOSE_U8.kernels().iter().filter(|x| x.0.is_supported()) {
+        let mut out = vec![0; width * height];
+        unsafe { kernel(&in_u8, &mut out, width, height) };
+        assert!(out == expected_u8, "u8 {isa:?}");
+    }
+    for (isa, kernel) in TRANSPOSE_U16
+        .kernels()
+        .iter()
+        .filter(|x| x.0.is_supported())
+    {
+        let mut out = vec![0; width * height];
+        unsafe { kernel(&in_u16, &mut out, width, height) };
+        assert!(out == expected_u16, "u16 {isa:?}");
+    }
+    for (isa, kernel) in TRANSPOSE_F32
+        .kernels()
+        .iter()
+        .filter(|x| x.0.is_supported())
+    {
+        let mut out = vec![0.0; width * height];
+        unsafe { kernel(&in_f32, &mut out, width, height) };
+        assert!(out == expected_f32, "f32 {isa:?}");
+    }
+}
@@ -6,23 +6,29 @@
  * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
  */
 //! (BROKEN): Do not use
+use alloc::vec;
+
 use zune_core::bit_depth::BitType;
 use zune_core::log::trace;
 use zune_image::errors::ImageErrors;
 use zune_image::image::Image;
-use zune_image::traits::OperationsTrait;
+use zune_image::traits::{OperationsTrait, Parameter, ParameterKind, ParameterValue};
 
-use crate::gaussian_blur::{gaussian_blur_u16, gaussian_blur_u8};
+use crate::gaussian_blur::{blur_reach, gaussian_blur_u16, gaussian_blur_u8};
+use crate::pad::{filter_with_border, PadMethod};
+use crate::utils::execute_on;
 
 /// Perform an unsharpen mask
 ///
 /// This uses the result of a gaussian filter and thresholding to
 /// perform the mask calculation
 #[derive(Default)]
+#[cfg_attr(feature = "serde-support", derive(serde::Serialize, serde::Deserialize))]
 pub struct Unsharpen {
     sigma:      f32,
     threshold:  u16,
-    percentage: u8
+    percentage: u8,
+    pad_method: PadMethod
 }
 
 impl Unsharpen {
@@ -41,9 +47,20 @@ impl Unsharpen {
         Unsharpen {
             sigma,
             threshold,
-            percentage
+            percentage,
+            pad_method: PadMethod::Replicate
         }
     }
+    /// Set how pixels outside the image are computed when blurring,
+    /// defaults to [PadMethod::Replicate]
+    ///
+    /// See [GaussianBlur::set_pad_method](crate::gaussian_blur::GaussianBlur::set_pad_method)
+    /// for how exact the blur is with large sigmas
+    #[must_use]
+    pub fn set_pad_method(mut self, pad_method: PadMethod) -> Self {
+        self.pad_method = pad_method;
+        self
+    }
 }
 
 impl OperationsTrait for Unsharpen {
@@ -51,107 +68,83 @@ impl OperationsTrait for Unsharpen {
         "Unsharpen"
     }
 
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                name:        "sigma",
+                description: "Standard deviation of the blur",
+                kind:        ParameterKind::Float {
+                    min: 0.0,
+                    max: 100.0
+                },
+                default:     ParameterValue::Float(1.0)
+            },
+            Parameter {
+                name:        "threshold",
+                description: "Minimum difference to sharpen",
+                kind:        ParameterKind::Integer { min: 0, max: 65535 },
+                default:     ParameterValue::Integer(0)
+            },
+            Parameter {
+                name:        "percentage",
+                description: "Strength of the sharpening",
+                kind:        ParameterKind::Integer { min: 0, max: 255 },
+                default:     ParameterValue::Integer(50)
+            }
+        ]
+    }
+
     fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
         let (width, height) = image.dimensions();
 
         let depth = image.depth();
 
-        #[cfg(not(feature = "threads"))]
-        {
-            trace!("Running unsharpen in single threaded mode");
+        trace!("Running unsharpen");
 
-            match depth.bit_type() {
-                BitType::U16 => {
-                    let mut blur_buffer = vec![0; width * height];
-                    let mut blur_scratch = vec![0; width * height];
+        // channels are sharpened in parallel with the threads feature,
+        // so each one gets its own buffers
+        execute_on(
+            |channel| {
+                match depth.bit_type() {
+                    BitType::U16 => {
+                        let mut blur_buffer = vec![0; width * height];
+                        let mut blur_scratch = vec![0; width * height];
 
-                    for channel in image.channels_mut(true) {
-                        unsharpen_u16(
+                        unsharpen_u16_with_border(
                             channel.reinterpret_as_mut::<u16>()?,
                             &mut blur_buffer,
                             &mut blur_scratch,
                             self.sigma,
                             self.threshold,
-                            self.percentage as u16,
+                            u16::from(self.percentage),
                             width,
-                            height
+                            height,
+                            self.pad_method
                         );
                     }
-                }
-
-                BitType::U8 => {
-                    let mut blur_buffer = vec![0; width * height];
-                    let mut blur_scratch = vec![0; width * height];
+                    BitType::U8 => {
+                        let mut blur_buffer = vec![0; width * height];
+                        let mut blur_scratch = vec![0; width * height];
 
-                    for channel in image.channels_mut(true) {
-                        unsharpen_u8(
+                        unsharpen_u8_with_border(
                             channel.reinterpret_as_mut::<u8>()?,
                             &mut blur_buffer,
                             &mut blur_scratch,
                             self.sigma,
-                            self.threshold as u8,
+                            u8::try_from(self.threshold.clamp(0, 255)).unwrap_or(u8::MAX),
                             self.percentage,
                             width,
-                            height
+                            height,
+                            self.pad_method
                         );
                     }
+                    d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
                 }
-                d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
-            }
-        }
-        #[cfg(feature = "threads")]
-        {
-            trace!("Running unsharpen in multithreaded mode");
-            std::thread::scope(|s| {
-                let mut errors = vec![];
-                // blur each channel on a separate thread
-                for channel in image.channels_mut(true) {
-                    let result = s.spawn(|| match depth.bit_type() {
-                        BitType::U16 => {
-                            let mut blur_buffer = vec![0; width * height];
-                            let mut blur_scratch = vec![0; width * height];
-
-                            unsharpen_u16(
-                                channel.reinterpret_as_mut::<u16>()?,
-                                &mut blur_buffer,
-                                &mut blur_scratch,
-                                self.sigma,
-                                self.threshold,
-                                u16::from(self.percentage),
-                                width,
-                                height
-                            );
-                            Ok(())
-                        }
-
-                        BitType::U8 => {
-                            let mut blur_buffer = vec![0; width * height];
-                            let mut blur_scratch = vec![0; width * height];
-
-                            unsharpen_u8(
-                                channel.reinterpret_as_mut::<u8>()?,
-                                &mut blur_buffer,
-                                &mut blur_scratch,
-                                self.sigma,
-                                u8::try_from(self.threshold.clamp(0, 255)).unwrap_or(u8::MAX),
-                                self.percentage,
-                                width,
-                                height
-                            );
-                            Ok(())
-                        }
-                        d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
-                    });
-                    errors.push(result);
-                }
-                errors
-                    .into_iter()
-                    .map(|x| x.join().unwrap())
-                    .collect::<Result<Vec<()>, ImageErrors>>()
-            })?;
-        }
-
-        Ok(())
+                Ok(())
+            },
+            image,
+            true
+        )
     }
     fn supported_types(&self) -> &'static [BitType] {
         &[BitType::U8, BitType::U16]
@@ -181,12 +174,39 @@ impl OperationsTrait for Unsharpen {
 #[allow(clippy::too_many_arguments)]
 pub fn unsharpen_u16(
     channel: &mut [u16], blur_buffer: &mut [u16], blur_scratch_buffer: &mut [u16], sigma: f32,
-    threshold: u16, _percentage: u16, width: usize, height: usize
+    threshold: u16, percentage: u16, width: usize, height: usize
+) {
+    unsharpen_u16_with_border(
+        channel,
+        blur_buffer,
+        blur_scratch_buffer,
+        sigma,
+        threshold,
+        percentage,
+        width,
+        height,
+        PadMethod::Replicate
+    )
+}
+
+/// [`unsharpen_u16`] with pixels outside the image computed by `pad_method`
+#[allow(clippy::too_many_arguments)]
+pub fn unsharpen_u16_with_border(
+    channel: &mut [u16], blur_buffer: &mut [u16], blur_scratch_buffer: &mut [u16], sigma: f32,
+    threshold: u16, _percentage: u16, width: usize, height: usize, pad_method: PadMethod
 ) {
     // copy channel to scratch space
     blur_buffer.copy_from_slice(channel);
     // carry out gaussian blur
-    gaussian_blur_u16(blur_buffer, blur_scratch_buffer, width, height, sigma);
+    filter_with_border(
+        blur_buffer,
+        blur_scratch_buffer,
+        width,
+        height,
+        blur_reach(sigma),
+        pad_method,
+        |data, scratch, width, height| gaussian_blur_u16(data, scratch, width, height, sigma)
+    );
     // blur buffer now contains gaussian blurred pixels
     // so iterate replacing them
     for (in_pix, blur_pix) in channel.iter_mut().zip(blur_buffer.iter()) {
@@ -229,12 +249,39 @@ pub fn unsharpen_u16(
 #[allow(clippy::too_many_arguments)]
 pub fn unsharpen_u8(
     channel: &mut [u8], blur_buffer: &mut [u8], blur_scratch_buffer: &mut [u8], sigma: f32,
-    threshold: u8, _percentage: u8, width: usize, height: usize
+    threshold: u8, percentage: u8, width: usize, height: usize
+) {
+    unsharpen_u8_with_border(
+        channel,
+        blur_buffer,
+        blur_scratch_buffer,
+        sigma,
+        threshold,
+        percentage,
+        width,
+        height,
+        PadMethod::Replicate
+    )
+}
+
+/// [`unsharpen_u8`] with pixels outside the image computed by `pad_method`
+#[allow(clippy::too_many_arguments)]
+pub fn unsharpen_u8_with_border(
+    channel: &mut [u8], blur_buffer: &mut [u8], blur_scratch_buffer: &mut [u8], sigma: f32,
+    threshold: u8, _percentage: u8, width: usize, height: usize, pad_method: PadMethod
 ) {
     // copy channel to scratch space
     blur_buffer.copy_from_slice(channel);
     // carry out gaussian blur
-    gaussian_blur_u8(blur_buffer, blur_scratch_buffer, width, height, sigma);
+    filter_with_border(
+        blur_buffer,
+        blur_scratch_buffer,
+        width,
+        height,
+        blur_reach(sigma),
+        pad_method,
+        |data, scratch, width, height| gaussian_blur_u8(data, scratch, width, height, sigma)
+    );
     // blur buffer now contains gaussian blurred pixels
     // so iterate replacing them
     for (in_pix, blur_pix) in channel.iter_mut().zip(blur_buffer.iter()) {
@@ -5,9 +5,18 @@
  *
  * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
  */
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use zune_core::bit_depth::BitType;
 use zune_image::channel::Channel;
 use zune_image::errors::ImageErrors;
 use zune_image::image::Image;
+#[cfg(feature = "std")]
+use zune_image::progress::check_cancelled;
+#[cfg(feature = "threads")]
+use zune_image::progress::{current_progress, with_progress};
+
+use crate::traits::NumOps;
 
 /// Prefetch data at offset position
 ///
@@ -83,32 +92,145 @@ pub fn calculate_gravity(src_image: &Image, dst_image: &Image, gravity: Gravity)
         )
     };
 }
+/// Create a channel of `length` pixels all set to `value`
+///
+/// `value` is in the range of the depth and is clamped to it, `operation` is
+/// the name of the calling operation, used for errors
+pub(crate) fn constant_channel(
+    length: usize, depth: BitType, value: f32, operation: &'static str
+) -> Result<Channel, ImageErrors> {
+    fn clamped<T: NumOps<T>>(value: f32) -> T {
+        T::from_f32(value.clamp(T::MIN_VAL.to_f32(), T::MAX_VAL.to_f32()))
+    }
+    let channel = match depth {
+        BitType::U8 => Channel::from_elm::<u8>(length, clamped(value)),
+        BitType::U16 => Channel::from_elm::<u16>(length, clamped(value)),
+        BitType::F32 => Channel::from_elm::<f32>(length, clamped(value)),
+        d => return Err(ImageErrors::ImageOperationNotImplemented(operation, d))
+    };
+    Ok(channel)
+}
+
+/// Rows per band of [`for_each_row_band`], smaller bands aren't worth a task
+#[cfg(feature = "threads")]
+const MIN_BAND_ROWS: usize = 16;
+
+/// Images with less pixels than this, e.g. thumbnails, take less time to process
+/// than to hand out to threads, so they are processed on the calling thread
+#[cfg(feature = "threads")]
+const MIN_PARALLEL_PIXELS: usize = 1 << 16;
+
+/// Split `out` into bands of whole rows of `width` values and run `function` on each,
+/// in parallel on the current rayon thread pool with the `threads` feature
+///
+/// `function` receives the index of the first row of the band and the band, bands
+/// are independent so `function` must only write to its band.
+///
+/// Without the `threads` feature, or for small images, the whole of `out` is one band
+pub(crate) fn for_each_row_band<T: Send>(
+    out: &mut [T], width: usize, function: impl Fn(usize, &mut [T]) + Send + Sync
+) {
+    if width == 0 || out.is_empty() {
+        return;
+    }
+    let height = out.len() / width;
+
+    #[cfg(feature = "threads")]
+    if out.len() >= MIN_PARALLEL_PIXELS {
+        use rayon::prelude::*;
+
+        // a few bands per thread so uneven bands even out
+        let band_rows = height
+            .div_ceil(rayon::current_num_threads() * 4)
+            .max(MIN_BAND_ROWS);
+
+        out.par_chunks_mut(band_rows * width)
+            .enumerate()
+            .for_each(|(i, band)| function(i * band_rows, band));
+        return;
+    }
+    function(0, &mut out[..height * width]);
+}
+
+/// [`for_each_row_band`] for windowed filters, whose input is padded by `radius`
+/// values on each side, see [`pad`](crate::pad::pad)
+///
+/// `function` receives the padded input rows the band's windows read,
+/// the band and the height of the band
+pub(crate) fn for_each_padded_row_band<T: Send + Sync>(
+    padded: &[T], out: &mut [T], width: usize, height: usize, radius: usize,
+    function: impl Fn(&[T], &mut [T], usize) + Send + Sync
+) {
+    let padded_width = width + radius * 2;
+
+    for_each_row_band(&mut out[..width * height], width, |first_row, band| {
+        let band_height = band.len() / width;
+        let rows = first_row..first_row + band_height + radius * 2;
+
+        function(
+            &padded[rows.start * padded_width..rows.end * padded_width],
+            band,
+            band_height
+        );
+    });
+}
+
+/// Euclidean remainder of `value` divided by `modulus`, like `f32::rem_euclid`
+/// which isn't available without std
+pub(crate) fn rem_euclid(value: f32, modulus: f32) -> f32 {
+    let remainder = value % modulus;
+
+    if remainder < 0.0 {
+        remainder + modulus.abs()
+    } else {
+        remainder
+    }
+}
+
+/// Convert `value` to `T`, rounding to the nearest value for integer types
+///
+/// Floats, whose maximum is `1.0`, are kept as is
+#[inline]
+pub(crate) fn round_to_depth<T: NumOps<T>>(value: f32) -> T {
+    if T::MAX_VAL.to_f32() > 1.0 {
+        T::from_f32(value.round())
+    } else {
+        T::from_f32(value)
+    }
+}
+
 /// A simple helper function to execute on threads
+///
+/// With the `threads` feature channels run in parallel on the current rayon
+/// thread pool, see [`Pipeline::set_threads`](zune_image::pipelines::Pipeline::set_threads),
+/// except for small images
 pub fn execute_on<T: Fn(&mut Channel) -> Result<(), ImageErrors> + Send + Sync>(
     function: T, image: &mut Image, ignore_alpha: bool
 ) -> Result<(), ImageErrors> {
     #[cfg(feature = "threads")]
-    {
-        std::thread::scope(|s| {
-            let mut t_results = vec![];
-            for channel in image.channels_mut(ignore_alpha) {
-                let result = s.spawn(|| function(channel));
-                t_results.push(result);
-            }
-
-            t_results
-                .into_iter()
-                .map(|x| x.join().unwrap())
-                .collect::<Result<Vec<()>, ImageErrors>>()
-        })?;
-
-        Ok(())
+    if image.dimensions().0 * image.dimensions().1 >= MIN_PARALLEL_PIXELS {
+        use rayon::prelude::*;
+
+        let progress = current_progress();
+
+        return image
+            .channels_mut(ignore_alpha)
+            .into_par_iter()
+            .try_for_each(|channel| {
+                // let long running functions check for cancellation
+                match progress.clone() {
+                    Some(progress) => with_progress(progress, || {
+                        check_cancelled()?;
+                        function(channel)
+                    }),
+                    None => function(channel)
+                }
+            });
     }
-    #[cfg(not(feature = "threads"))]
-    {
-        for channel in image.channels_mut(ignore_alpha) {
-            function(channel)?;
-        }
-        Ok(())
+    for channel in image.channels_mut(ignore_alpha) {
+        #[cfg(feature = "std")]
+        check_cancelled()?;
+        function(channel)?;
     }
+    Ok(())
 }
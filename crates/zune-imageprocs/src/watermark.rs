@@ -0,0 +1,433 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+//! Watermark: Tile a logo or text across an image
+//!
+//! This repeats a (possibly semi-transparent) logo over the whole image in a rotated grid,
+//! which is a common way of protecting previews.
+//!
+//! # Algorithm
+//! For every pixel of the image, its coordinates are rotated by `-angle` around the image
+//! center and wrapped into a grid of `logo + spacing` sized tiles, if the wrapped coordinates
+//! land inside the logo, the pixel is blended with the nearest logo pixel
+//!
+//! ```text
+//! weight = opacity * logo_alpha
+//! dst = dst + (logo - dst) * weight
+//! ```
+//!
+//! The alpha channel of the image, if present, is left untouched.
+//!
+//! # Text
+//! Text is turned into a logo first, [`logo_from_text`] draws it with a small built-in
+//! bitmap font of the printable ASCII characters. For other fonts, rasterize the text with
+//! a font library into a coverage mask of one alpha value per pixel, which
+//! [`logo_from_mask`] turns into a logo of a single color.
+use alloc::vec;
+use alloc::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use zune_core::bit_depth::{BitDepth, BitType};
+use zune_core::colorspace::ColorSpace;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::font::{glyph_pixel, GLYPH_HEIGHT, GLYPH_WIDTH};
+use crate::traits::NumOps;
+use crate::utils::{rem_euclid, round_to_depth};
+
+/// Tile a logo across an image
+///
+/// For text, see the [module docs](self)
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::watermark::Watermark;
+///
+/// let logo = Image::fill(255_u8, ColorSpace::RGBA, 16, 8);
+/// let mut image = Image::fill(0_u8, ColorSpace::RGB, 200, 100);
+///
+/// Watermark::new(&logo)
+///     .set_angle(30.0)
+///     .set_spacing(20, 20)
+///     .set_opacity(0.3)
+///     .execute(&mut image)
+///     .unwrap();
+/// ```
+pub struct Watermark<'a> {
+    logo:      &'a Image,
+    opacity:   f32,
+    angle:     f32,
+    spacing_x: usize,
+    spacing_y: usize
+}
+
+impl<'a> Watermark<'a> {
+    /// Create a new watermark operation
+    ///
+    /// # Arguments
+    /// - logo: The image to tile, its alpha channel (if any) is used as a mask.
+    ///   It is converted to the colorspace of the image being watermarked
+    ///
+    /// Defaults are an opacity of `0.5`, no rotation and no spacing between tiles
+    #[must_use]
+    pub fn new(logo: &'a Image) -> Watermark<'a> {
+        Watermark {
+            logo,
+            opacity: 0.5,
+            angle: 0.0,
+            spacing_x: 0,
+            spacing_y: 0
+        }
+    }
+    /// Set the opacity of the logo, between 0 (invisible) and 1 (opaque)
+    #[must_use]
+    pub fn set_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        self
+    }
+    /// Set the angle of the tile grid in degrees, positive angles rotate clockwise
+    #[must_use]
+    pub fn set_angle(mut self, angle: f32) -> Self {
+        self.angle = angle;
+        self
+    }
+    /// Set the horizontal and vertical gap between tiles in pixels
+    #[must_use]
+    pub fn set_spacing(mut self, spacing_x: usize, spacing_y: usize) -> Self {
+        self.spacing_x = spacing_x;
+        self.spacing_y = spacing_y;
+        self
+    }
+
+    /// Compute, for every image pixel, the logo pixel covering it and its blend weight
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn coverage(
+        &self, width: usize, height: usize, logo_alpha: &[f32], logo_w: usize, logo_h: usize
+    ) -> Vec<(usize, f32)> {
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        let period_x = (logo_w + self.spacing_x) as f32;
+        let period_y = (logo_h + self.spacing_y) as f32;
+
+        // place a tile at the center of the image
+        let cx = width as f32 / 2.0;
+        let cy = height as f32 / 2.0;
+        let half_w = logo_w as f32 / 2.0;
+        let half_h = logo_h as f32 / 2.0;
+
+        let mut coverage = Vec::with_capacity(width * height);
+
+        for y in 0..height {
+            let dy = y as f32 + 0.5 - cy;
+
+            for x in 0..width {
+                let dx = x as f32 + 0.5 - cx;
+
+                let u = rem_euclid(dx * cos + dy * sin + half_w, period_x);
+                let v = rem_euclid(dy * cos - dx * sin + half_h, period_y);
+
+                let (lx, ly) = (u as usize, v as usize);
+
+                if lx < logo_w && ly < logo_h {
+                    let pos = ly * logo_w + lx;
+                    coverage.push((pos, self.opacity * logo_alpha[pos]));
+                } else {
+                    coverage.push((0, 0.0));
+                }
+            }
+        }
+        coverage
+    }
+}
+
+impl OperationsTrait for Watermark<'_> {
+    fn name(&self) -> &'static str {
+        "Watermark"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let (logo_w, logo_h) = self.logo.dimensions();
+
+        if logo_w == 0 || logo_h == 0 || self.opacity == 0.0 {
+            return Ok(());
+        }
+        let colorspace = image.colorspace();
+        let depth = image.depth().bit_type();
+
+        // bring the logo to float, extract the mask and match the image colors
+        let mut logo = self.logo.clone();
+        logo.convert_depth(BitDepth::Float32)?;
+
+        let logo_alpha = match logo.frames_ref()[0].separate_color_and_alpha_ref(logo.colorspace())
+        {
+            Some((_, alpha)) => alpha.reinterpret_as::<f32>()?.to_vec(),
+            None => vec![1.0; logo_w * logo_h]
+        };
+        if logo.colorspace() != colorspace {
+            logo.convert_color(colorspace)?;
+        }
+        let coverage = self.coverage(width, height, &logo_alpha, logo_w, logo_h);
+
+        let logo_channels = logo.frames_ref()[0].channels_ref(colorspace, true);
+
+        for frame in image.frames_mut() {
+            for (logo_channel, channel) in logo_channels
+                .iter()
+                .zip(frame.channels_mut(colorspace, true))
+            {
+                let logo_pixels = logo_channel.reinterpret_as::<f32>()?;
+
+                match depth {
+                    BitType::U8 => {
+                        blend::<u8>(channel.reinterpret_as_mut()?, logo_pixels, &coverage);
+                    }
+                    BitType::U16 => {
+                        blend::<u16>(channel.reinterpret_as_mut()?, logo_pixels, &coverage);
+                    }
+                    BitType::F32 => {
+                        blend::<f32>(channel.reinterpret_as_mut()?, logo_pixels, &coverage);
+                    }
+                    d => return Err(ImageErrors::ImageOperationNotImplemented(self.name(), d))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Create a logo of a single color from a coverage mask, e.g. of rasterized text
+///
+/// Every pixel of the logo is `color` with the mask value as its alpha, so the logo only
+/// covers the image where the mask does.
+///
+/// # Arguments
+/// - mask: Coverage of every pixel, row by row, `0` is transparent and `255` opaque
+/// - width, height: Dimensions of the mask
+/// - color: RGB color of the logo
+///
+/// # Errors
+/// If `mask` has less than `width * height` values
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::watermark::{logo_from_mask, Watermark};
+///
+/// // a mask from a font rasterizer, here a 3x2 block
+/// let mask = [0, 255, 0, 255, 255, 255];
+/// let logo = logo_from_mask(&mask, 3, 2, [255, 255, 255]).unwrap();
+///
+/// let mut image = Image::fill(0_u8, ColorSpace::RGB, 200, 100);
+/// Watermark::new(&logo)
+///     .set_spacing(10, 10)
+///     .execute(&mut image)
+///     .unwrap();
+/// ```
+pub fn logo_from_mask(
+    mask: &[u8], width: usize, height: usize, color: [u8; 3]
+) -> Result<Image, ImageErrors> {
+    let size = width
+        .checked_mul(height)
+        .ok_or(ImageErrors::GenericStr("Mask dimensions overflow"))?;
+
+    if mask.len() < size {
+        return Err(ImageErrors::DimensionsMisMatch(size, mask.len()));
+    }
+    let pixels: Vec<u8> = mask[..size]
+        .iter()
+        .flat_map(|alpha| [color[0], color[1], color[2], *alpha])
+        .collect();
+
+    Ok(Image::from_u8(&pixels, width, height, ColorSpace::RGBA))
+}
+
+/// Create a logo of a single color showing `text`
+///
+/// The text is drawn with a built-in 5x7 pixel font, every font pixel becomes a
+/// `scale x scale` block. Lines are split on `\n` and characters outside of printable
+/// ASCII are drawn as `?`
+///
+/// # Arguments
+/// - text: The text to draw
+/// - scale: Size of a font pixel in logo pixels
+/// - color: RGB color of the text
+///
+/// # Errors
+/// If `text` is empty or `scale` is zero
+///
+/// # Example
+/// ```
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_imageprocs::watermark::{logo_from_text, Watermark};
+///
+/// let logo = logo_from_text("PREVIEW", 2, [255, 255, 255]).unwrap();
+///
+/// let mut image = Image::fill(0_u8, ColorSpace::RGB, 200, 100);
+/// Watermark::new(&logo)
+///     .set_angle(-30.0)
+///     .set_spacing(16, 16)
+///     .set_opacity(0.4)
+///     .execute(&mut image)
+///     .unwrap();
+/// ```
+pub fn logo_from_text(text: &str, scale: usize, color: [u8; 3]) -> Result<Image, ImageErrors> {
+    if scale == 0 {
+        return Err(ImageErrors::GenericStr("Text scale must be non-zero"));
+    }
+    let lines: Vec<&str> = text.lines().collect();
+    let columns = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+
+    if columns == 0 {
+        return Err(ImageErrors::GenericStr("Cannot create a logo from empty text"));
+    }
+    // one empty font pixel between characters and lines
+    let cell_w = (GLYPH_WIDTH + 1) * scale;
+    let cell_h = (GLYPH_HEIGHT + 1) * scale;
+    let width = columns * cell_w - scale;
+    let height = lines.len() * cell_h - scale;
+
+    let mut mask = vec![0_u8; width * height];
+
+    for (row, line) in lines.iter().enumerate() {
+        for (column, c) in line.chars().enumerate() {
+            for y in 0..GLYPH_HEIGHT * scale {
+                let start = (row * cell_h + y) * width + column * cell_w;
+
+                for (x, alpha) in mask[start..start + GLYPH_WIDTH * scale]
+                    .iter_mut()
+                    .enumerate()
+                {
+                    if glyph_pixel(c, x / scale, y / scale) {
+                        *alpha = 255;
+                    }
+                }
+            }
+        }
+    }
+    logo_from_mask(&mask, width, height, color)
+}
+
+/// Blend `logo` (in the range 0..1) into `channel` using the precomputed coverage
+fn blend<T: Copy + NumOps<T>>(channel: &mut [T], logo: &[f32], coverage: &[(usize, f32)]) {
+    let max = T::MAX_VAL.to_f32();
+
+    for (pixel, &(pos, weight)) in channel.iter_mut().zip(coverage) {
+        if weight > 0.0 {
+            let dst = pixel.to_f32();
+            let src = logo[pos].clamp(0.0, 1.0) * max;
+
+            *pixel = round_to_depth(dst + (src - dst) * weight);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+    use zune_image::traits::OperationsTrait;
+
+    use crate::watermark::{logo_from_mask, logo_from_text, Watermark};
+
+    #[test]
+    fn test_watermark_tiles() {
+        let logo = Image::fill(200_u8, ColorSpace::Luma, 2, 2);
+        let mut image = Image::fill(0_u8, ColorSpace::Luma, 8, 8);
+
+        Watermark::new(&logo)
+            .set_spacing(2, 2)
+            .set_opacity(0.5)
+            .execute(&mut image)
+            .unwrap();
+
+        let pixels = image.channels_ref(false)[0].reinterpret_as::<u8>().unwrap();
+        // tiles repeat every 4 pixels, with one centered in the image
+        let row: Vec<u8> = pixels[3 * 8..4 * 8].to_vec();
+        assert_eq!(row, [100, 0, 0, 100, 100, 0, 0, 100]);
+        assert_eq!(&pixels[8..16], &[0; 8]);
+    }
+
+    #[test]
+    fn test_watermark_uses_logo_alpha() {
+        let mut logo = Image::fill(255_u8, ColorSpace::LumaA, 4, 4);
+        // make the logo fully transparent
+        logo.frames_mut()[0].channels_mut(ColorSpace::LumaA, false)[1]
+            .reinterpret_as_mut::<u8>()
+            .unwrap()
+            .fill(0);
+
+        let mut image = Image::fill(10_u16, ColorSpace::RGB, 9, 9);
+        Watermark::new(&logo)
+            .set_angle(45.0)
+            .set_opacity(1.0)
+            .execute(&mut image)
+            .unwrap();
+
+        for channel in image.channels_ref(false) {
+            assert!(channel
+                .reinterpret_as::<u16>()
+                .unwrap()
+                .iter()
+                .all(|x| *x == 10));
+        }
+    }
+
+    #[test]
+    fn test_watermark_logo_from_mask() {
+        // an L shape, like a glyph from a font rasterizer
+        let mask = [255, 0, 255, 255];
+        let logo = logo_from_mask(&mask, 2, 2, [200, 100, 0]).unwrap();
+        let mut image = Image::fill(0_u8, ColorSpace::RGB, 2, 2);
+
+        Watermark::new(&logo)
+            .set_opacity(1.0)
+            .execute(&mut image)
+            .unwrap();
+
+        let pixels = image.flatten_frames::<u8>();
+        assert_eq!(pixels[0], [200, 100, 0, 0, 0, 0, 200, 100, 0, 200, 100, 0]);
+    }
+
+    #[test]
+    fn test_watermark_logo_from_mask_too_short() {
+        assert!(logo_from_mask(&[255; 3], 2, 2, [0; 3]).is_err());
+        assert!(logo_from_mask(&[], usize::MAX, 2, [0; 3]).is_err());
+    }
+
+    #[test]
+    fn test_watermark_logo_from_text() {
+        let logo = logo_from_text("-\n|", 2, [255, 0, 0]).unwrap();
+        // two 5x7 glyphs with a font pixel between the lines, scaled twice
+        assert_eq!(logo.dimensions(), (10, 30));
+
+        let alpha = logo.channels_ref(false)[3].reinterpret_as::<u8>().unwrap();
+        // middle row of '-' is fully set, the column of '|' is in the middle of its cell
+        assert!(alpha[6 * 10..7 * 10].iter().all(|x| *x == 255));
+        assert_eq!(&alpha[16 * 10..17 * 10], &[0, 0, 0, 0, 255, 255, 0, 0, 0, 0]);
+
+        assert!(logo_from_text("", 1, [0; 3]).is_err());
+        assert!(logo_from_text("a", 0, [0; 3]).is_err());
+    }
+}
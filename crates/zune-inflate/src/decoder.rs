@@ -250,6 +250,38 @@ impl<'a> DeflateDecoder<'a> {
     ///
     #[cfg(feature = "zlib")]
     pub fn decode_zlib(&mut self) -> Result<Vec<u8>, InflateDecodeErrors> {
+        self.decode_zlib_inner(&mut |_| ())
+    }
+
+    /// Decode zlib-encoded data like [`decode_zlib`](Self::decode_zlib), passing the
+    /// bytes of every deflate block to `on_block` as soon as the block is decoded
+    ///
+    /// This allows processing the output while the rest is still being decoded,
+    /// e.g. on another thread, every byte is passed exactly once and in order.
+    ///
+    /// # Note
+    /// The checksum is confirmed after the last block, so bytes passed to `on_block`
+    /// should be discarded if this returns an error
+    ///
+    /// # Example
+    /// ```no_run
+    /// let data = [37];
+    /// let mut decoder = zune_inflate::DeflateDecoder::new(&data);
+    /// let mut seen = 0;
+    /// let bytes = decoder.decode_zlib_with_blocks(|block| seen += block.len()).unwrap();
+    /// assert_eq!(seen, bytes.len());
+    /// ```
+    #[cfg(feature = "zlib")]
+    pub fn decode_zlib_with_blocks(
+        &mut self, mut on_block: impl FnMut(&[u8])
+    ) -> Result<Vec<u8>, InflateDecodeErrors> {
+        self.decode_zlib_inner(&mut on_block)
+    }
+
+    #[cfg(feature = "zlib")]
+    fn decode_zlib_inner(
+        &mut self, on_block: &mut dyn FnMut(&[u8])
+    ) -> Result<Vec<u8>, InflateDecodeErrors> {
         use crate::utils::calc_adler_hash;
 
         if self.data.len()
@@ -303,7 +335,7 @@ impl<'a> DeflateDecoder<'a> {
 
         self.position = 2;
 
-        let data = self.decode_deflate()?;
+        let data = self.start_deflate_block(on_block)?;
 
         if self.options.confirm_checksum {
             // Get number of consumed bytes from the input
@@ -531,12 +563,16 @@ impl<'a> DeflateDecoder<'a> {
     ///
     ///  [InflateDecodeErrors]:crate::errors::InflateDecodeErrors
     pub fn decode_deflate(&mut self) -> Result<Vec<u8>, InflateDecodeErrors> {
-        self.start_deflate_block()
+        self.start_deflate_block(&mut |_| ())
     }
     /// Main inner loop for decompressing deflate data
     #[allow(unused_assignments)]
     #[allow(clippy::never_loop)] // wrong submission
-    fn start_deflate_block(&mut self) -> Result<Vec<u8>, InflateDecodeErrors> {
+    ///
+    /// `on_block` receives the bytes of every block once it's decoded
+    fn start_deflate_block(
+        &mut self, on_block: &mut dyn FnMut(&[u8])
+    ) -> Result<Vec<u8>, InflateDecodeErrors> {
         // start deflate decode
         // re-read the stream so that we can remove code read by zlib
         self.stream = BitStreamReader::new(&self.data[self.position..]);
@@ -549,6 +585,8 @@ impl<'a> DeflateDecoder<'a> {
 
         let mut src_offset = 0;
         let mut dest_offset = 0;
+        // end of the bytes passed to on_block
+        let mut passed = 0;
 
         loop {
             self.stream.refill();
@@ -634,6 +672,9 @@ impl<'a> DeflateDecoder<'a> {
 
                 self.stream.reset();
 
+                on_block(&out_block[passed..dest_offset]);
+                passed = dest_offset;
+
                 if self.is_last_block {
                     break;
                 }
@@ -1104,6 +1145,9 @@ impl<'a> DeflateDecoder<'a> {
                 return Err(error);
             }
 
+            on_block(&out_block[passed..dest_offset]);
+            passed = dest_offset;
+
             if self.is_last_block {
                 break;
             }
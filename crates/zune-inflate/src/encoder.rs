@@ -152,7 +152,8 @@ impl<'a> DeflateEncoder<'a> {
     /// # Argument
     /// - `bytes`: number of bytes to compress from input as non-compressed
     /// bytes
-    fn encode_no_compression(&mut self, bytes: usize) {
+    /// - `last`: Whether the last block is marked as the final one of the stream
+    fn encode_no_compression(&mut self, bytes: usize, last: bool) {
         let final_position = self.input_position + bytes;
 
         /*
@@ -161,6 +162,9 @@ impl<'a> DeflateEncoder<'a> {
          * specially to avoid potentially passing NULL to memcpy() below.
          */
         if self.data.is_empty() {
+            if !last {
+                return;
+            }
             /* BFINAL and BTYPE */
             self.output[self.output_position] = (1 | (DEFLATE_BLOCKTYPE_UNCOMPRESSED << 1)) as u8;
             self.output_position += 1;
@@ -176,7 +180,7 @@ impl<'a> DeflateEncoder<'a> {
             let mut len = usize::from(u16::MAX);
 
             if final_position - self.input_position <= usize::from(u16::MAX) {
-                bfinal = 1;
+                bfinal = u64::from(last);
                 len = final_position - self.input_position;
             }
             /*
@@ -214,9 +218,35 @@ impl<'a> DeflateEncoder<'a> {
     pub fn encode_deflate(&mut self) {
         match self.options.strategy {
             DeflateEncodingStrategy::NoCompression => {
-                self.encode_no_compression(self.data.len());
+                self.encode_no_compression(self.data.len(), true);
+            }
+        }
+    }
+
+    /// Encode the data as a part of a zlib stream, which [`join_zlib_parts`] joins with
+    /// the parts before and after it
+    ///
+    /// Parts don't refer to data of other parts and end on a byte boundary without
+    /// a final block, like zlib's `Z_SYNC_FLUSH`, so the pieces of large data can be
+    /// encoded in parallel, one encoder per piece.
+    #[cfg(feature = "zlib")]
+    pub fn encode_zlib_part(&mut self) -> ZlibPart {
+        let extra = 40 * ((self.data.len() + 41) / 40);
+        self.output = vec![0_u8; self.data.len() + extra];
+        self.output_position = 0;
+
+        match self.options.strategy {
+            DeflateEncodingStrategy::NoCompression => {
+                self.encode_no_compression(self.data.len(), false);
             }
         }
+        self.output.truncate(self.output_position);
+
+        ZlibPart {
+            data:   core::mem::take(&mut self.output),
+            adler:  crate::utils::calc_adler_hash(self.data),
+            length: self.data.len()
+        }
     }
 
     #[cfg(feature = "zlib")]
@@ -240,6 +270,44 @@ impl<'a> DeflateEncoder<'a> {
     }
 }
 
+/// A piece of a zlib stream, see [`DeflateEncoder::encode_zlib_part`]
+#[cfg(feature = "zlib")]
+pub struct ZlibPart {
+    data:   Vec<u8>,
+    adler:  u32,
+    length: usize
+}
+
+/// Join parts encoded by [`DeflateEncoder::encode_zlib_part`], in the order of their data,
+/// into a zlib stream
+///
+/// The stream decodes to the data of every part one after the other.
+#[cfg(feature = "zlib")]
+pub fn join_zlib_parts(parts: &[ZlibPart]) -> Vec<u8> {
+    let length = parts.iter().map(|x| x.data.len()).sum::<usize>();
+
+    // the empty encoder writes the header and a final empty block
+    let mut last = DeflateEncoder::new(&[]);
+    last.output = vec![0_u8; 16];
+    last.write_zlib_header();
+    last.output_position = 2;
+    last.encode_no_compression(0, true);
+
+    let mut output = Vec::with_capacity(length + 16);
+    output.extend_from_slice(&last.output[..2]);
+
+    let mut adler = 1;
+
+    for part in parts {
+        output.extend_from_slice(&part.data);
+        adler = crate::utils::combine_adler_hashes(adler, part.adler, part.length);
+    }
+    output.extend_from_slice(&last.output[2..last.output_position]);
+    output.extend_from_slice(&adler.to_be_bytes());
+
+    output
+}
+
 #[inline(always)]
 pub fn v_hash(bytes: &[u8], num_bits: usize, min_length: usize) -> usize {
     debug_assert!(num_bits <= 32);
@@ -15,13 +15,14 @@
 //! Use it if
 //! - You want a smaller library footprint when compared to flate/miniz-oxide
 //! - You want faster speeds than zlib-ng/zlib/miniz-oxide.
-//! - You do full buffer decompression and not streaming decompression.
+//! - You do full buffer decompression, or can live with slower streaming decompression
+//!   through [`InflateStream`].
 //! - You don't need compression support for now, it will come soon enough.
 //! - You want a 100% safe, pure rust implementation with above.
 //!
 //!Do not use it if
 //!  - You want compression support, not yet there
-//!  - You stream your data, not compatible with this library
+//!  - You stream your data and need it to be fast
 //!
 //! ## Alternatives
 //!- For the fastest speeds, check out [libdeflate] (C), if using Rust there is [libdeflater] which
@@ -91,6 +92,9 @@ extern crate alloc;
 
 pub use crate::decoder::{DeflateDecoder, DeflateOptions};
 pub use crate::encoder::DeflateEncoder;
+#[cfg(feature = "zlib")]
+pub use crate::encoder::{join_zlib_parts, ZlibPart};
+pub use crate::stream::InflateStream;
 
 mod bitstream;
 mod constants;
@@ -99,4 +103,5 @@ mod decoder;
 mod encoder;
 pub mod errors;
 mod gzip_constants;
+mod stream;
 mod utils;
@@ -0,0 +1,651 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Incremental decoding, for when neither the compressed nor the decompressed
+//! data fits in memory
+//!
+//! [`DeflateDecoder`](crate::DeflateDecoder) needs all compressed data up front and returns
+//! all decompressed data at once, which is what makes it fast. [`InflateStream`] instead
+//! takes compressed data in pieces and hands out decompressed data in pieces, keeping
+//! only the last 32 KiB window matches may refer to, so memory use doesn't depend on
+//! the size of the data.
+//!
+//! It decodes one symbol at a time and is a lot slower than [`DeflateDecoder`](crate::DeflateDecoder),
+//! use it when memory, not speed, is what matters.
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::constants::DEFLATE_PRECODE_LENS_PERMUTATION;
+use crate::errors::{DecodeErrorStatus, InflateDecodeErrors};
+#[cfg(feature = "zlib")]
+use crate::utils::{calc_adler_hash, combine_adler_hashes};
+use crate::DeflateOptions;
+
+/// Bytes matches can refer back to
+const WINDOW_SIZE: usize = 1 << 15;
+
+/// Base lengths of length symbols 257..=285, and their extra bits
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0
+];
+/// Base distances of distance symbols 0..=29, and their extra bits
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13
+];
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum State {
+    /// Before the two bytes of the zlib header
+    #[cfg(feature = "zlib")]
+    ZlibHeader,
+    /// Before the three bits of a block header
+    BlockHeader,
+    /// Inside a stored block, with this many bytes left
+    Stored(usize),
+    /// Inside a block coded with the current tables
+    Codes,
+    /// After the last block, before the adler32 checksum
+    #[cfg(feature = "zlib")]
+    Checksum,
+    Done
+}
+
+/// A canonical Huffman code, decoded with a single lookup
+#[derive(Default)]
+struct Huffman {
+    /// Indexed by the next `bits` bits of the stream, `symbol << 4 | length`,
+    /// zero for codes the table doesn't contain
+    table: Vec<u16>,
+    bits:  u8
+}
+
+impl Huffman {
+    /// Build the code of symbols with code lengths `lengths`, where
+    /// zero means the symbol isn't used
+    fn build(&mut self, lengths: &[u8]) -> Result<(), DecodeErrorStatus> {
+        let mut counts = [0_u16; 16];
+
+        for length in lengths {
+            counts[usize::from(*length)] += 1;
+        }
+        counts[0] = 0;
+
+        // an over-subscribed code can't be decoded, incomplete ones are allowed
+        let mut left = 1_i32;
+
+        for count in &counts[1..] {
+            left = (left << 1) - i32::from(*count);
+
+            if left < 0 {
+                return Err(DecodeErrorStatus::Generic("Over-subscribed Huffman code"));
+            }
+        }
+        let bits = counts.iter().rposition(|x| *x > 0).unwrap_or(0).max(1);
+
+        let mut next_code = [0_u16; 16];
+        let mut code = 0;
+
+        for length in 1..16 {
+            code = (code + counts[length - 1]) << 1;
+            next_code[length] = code;
+        }
+        self.table.clear();
+        self.table.resize(1 << bits, 0);
+        self.bits = bits as u8;
+
+        for (symbol, length) in lengths.iter().enumerate() {
+            let length = usize::from(*length);
+
+            if length == 0 {
+                continue;
+            }
+            let code = next_code[length];
+            next_code[length] += 1;
+
+            // deflate stores codes starting from their highest bit
+            let reversed = usize::from(code.reverse_bits() >> (16 - length));
+            let entry = ((symbol as u16) << 4) | length as u16;
+
+            for index in (reversed..self.table.len()).step_by(1 << length) {
+                self.table[index] = entry;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads bits from the compressed data, least significant bit first
+///
+/// Steps of the decoder read through a copy and only store it back once
+/// they have all the bits they need, so a step that runs out of input can be
+/// retried once more input arrives.
+#[derive(Copy, Clone)]
+struct Bits<'a> {
+    data:     &'a [u8],
+    position: usize
+}
+
+impl Bits<'_> {
+    fn available(&self) -> usize {
+        self.data.len() * 8 - self.position
+    }
+    /// Return the next `count` bits, up to 24, zero padded if there are less
+    fn peek(&self, count: u8) -> u32 {
+        let start = self.position / 8;
+        let mut bytes = [0; 4];
+
+        for (byte, value) in bytes.iter_mut().zip(self.data.iter().skip(start)) {
+            *byte = *value;
+        }
+        (u32::from_le_bytes(bytes) >> (self.position % 8)) & ((1 << count) - 1)
+    }
+    /// Return the next `count` bits, or `None` if there aren't that many
+    fn get(&mut self, count: u8) -> Option<u32> {
+        if self.available() < usize::from(count) {
+            return None;
+        }
+        let value = self.peek(count);
+        self.position += usize::from(count);
+        Some(value)
+    }
+    /// Decode a symbol of `code`, or `None` if there aren't enough bits
+    fn decode(&mut self, code: &Huffman) -> Result<Option<usize>, DecodeErrorStatus> {
+        let entry = code.table[self.peek(code.bits) as usize];
+        let length = usize::from(entry & 15);
+
+        if length == 0 {
+            if self.available() < usize::from(code.bits) {
+                return Ok(None);
+            }
+            return Err(DecodeErrorStatus::CorruptData);
+        }
+        if self.available() < length {
+            return Ok(None);
+        }
+        self.position += length;
+        Ok(Some(usize::from(entry >> 4)))
+    }
+    fn align_to_byte(&mut self) {
+        self.position = self.position.next_multiple_of(8);
+    }
+}
+
+/// A deflate or zlib decoder taking compressed data and returning decompressed
+/// data in pieces
+///
+/// See the [module docs](self) for when to use it
+///
+/// # Example
+/// ```
+/// use zune_inflate::{DeflateEncoder, DeflateOptions, InflateStream};
+///
+/// let data = vec![7_u8; 100_000];
+/// let compressed = DeflateEncoder::new(&data).encode_zlib();
+///
+/// let mut stream = InflateStream::new_zlib(DeflateOptions::default());
+/// let mut decoded = vec![];
+/// let mut out = [0; 1000];
+///
+/// // feed compressed data in pieces, and take out what they decode to
+/// for piece in compressed.chunks(100) {
+///     stream.add_input(piece);
+///
+///     loop {
+///         let length = stream.read(&mut out).unwrap();
+///         if length == 0 {
+///             break;
+///         }
+///         decoded.extend_from_slice(&out[..length]);
+///     }
+/// }
+/// assert!(stream.is_done());
+/// assert_eq!(decoded, data);
+/// ```
+pub struct InflateStream {
+    options:     DeflateOptions,
+    state:       State,
+    is_last:     bool,
+    #[cfg(feature = "zlib")]
+    is_zlib:     bool,
+    /// Compressed data not yet decoded
+    input:       Vec<u8>,
+    /// The next bit of `input` to decode
+    position:    usize,
+    /// Decompressed data, the bytes from `handed_out` on haven't been read yet
+    /// and the ones before are kept for matches
+    window:      Vec<u8>,
+    handed_out:  usize,
+    total:       usize,
+    #[cfg(feature = "zlib")]
+    adler:       u32,
+    #[cfg(feature = "zlib")]
+    hashed:      usize,
+    literals:    Huffman,
+    distances:   Huffman,
+    code_length: Huffman
+}
+
+impl InflateStream {
+    /// Create a decoder of raw deflate data
+    ///
+    /// The [limit](DeflateOptions::set_limit) of the options is the maximum number of
+    /// decompressed bytes, the size hint is not used
+    pub fn new_deflate(options: DeflateOptions) -> InflateStream {
+        InflateStream {
+            options,
+            state: State::BlockHeader,
+            is_last: false,
+            #[cfg(feature = "zlib")]
+            is_zlib: false,
+            input: vec![],
+            position: 0,
+            window: vec![],
+            handed_out: 0,
+            total: 0,
+            #[cfg(feature = "zlib")]
+            adler: 1,
+            #[cfg(feature = "zlib")]
+            hashed: 0,
+            literals: Huffman::default(),
+            distances: Huffman::default(),
+            code_length: Huffman::default()
+        }
+    }
+    /// Create a decoder of zlib data
+    ///
+    /// The adler32 checksum is confirmed once the end of the data is reached,
+    /// unless turned off in the options
+    #[cfg(feature = "zlib")]
+    pub fn new_zlib(options: DeflateOptions) -> InflateStream {
+        InflateStream {
+            state: State::ZlibHeader,
+            is_zlib: true,
+            ..InflateStream::new_deflate(options)
+        }
+    }
+    /// Add the next piece of compressed data
+    pub fn add_input(&mut self, data: &[u8]) {
+        // drop what was already decoded before growing
+        let consumed = self.position / 8;
+
+        if consumed > 0 && consumed >= self.input.len() / 2 {
+            self.input.drain(..consumed);
+            self.position -= consumed * 8;
+        }
+        self.input.extend_from_slice(data);
+    }
+    /// Return true once the end of the compressed data was reached and every
+    /// decompressed byte read
+    pub fn is_done(&self) -> bool {
+        self.state == State::Done && self.handed_out == self.window.len()
+    }
+    /// Return true if [`read`](Self::read) can't return anything until more input is added
+    pub fn needs_input(&self) -> bool {
+        self.handed_out == self.window.len() && self.state != State::Done
+    }
+    /// Decompress into `out`, returning how many bytes were written
+    ///
+    /// This writes less than `out.len()` bytes if it runs out of input, in which case
+    /// [`needs_input`](Self::needs_input) is true, or reaches the end of the data, and
+    /// returns 0 when it can't write anything.
+    ///
+    /// # Errors
+    /// The data is corrupt, the limit is exceeded or the checksum doesn't match,
+    /// the error contains no data
+    pub fn read(&mut self, out: &mut [u8]) -> Result<usize, InflateDecodeErrors> {
+        self.decode(out.len())
+            .map_err(InflateDecodeErrors::new_with_error)?;
+
+        let pending = &self.window[self.handed_out..];
+        let length = pending.len().min(out.len());
+
+        out[..length].copy_from_slice(&pending[..length]);
+        self.handed_out += length;
+
+        // keep the bytes matches may refer to
+        let keep_from = self
+            .handed_out
+            .min(self.window.len().saturating_sub(WINDOW_SIZE));
+
+        if keep_from >= WINDOW_SIZE {
+            self.hash_window();
+            self.window.drain(..keep_from);
+            self.handed_out -= keep_from;
+            #[cfg(feature = "zlib")]
+            {
+                self.hashed -= keep_from;
+            }
+        }
+        Ok(length)
+    }
+
+    /// Decode until `wanted` bytes are pending, input runs out or the data ends
+    fn decode(&mut self, wanted: usize) -> Result<(), DecodeErrorStatus> {
+        // steps borrow the decoder mutably while reading the input
+        let input = core::mem::take(&mut self.input);
+        let result = self.decode_input(&input, wanted);
+        self.input = input;
+
+        result
+    }
+
+    fn decode_input(&mut self, input: &[u8], wanted: usize) -> Result<(), DecodeErrorStatus> {
+        while self.window.len() - self.handed_out < wanted {
+            let mut bits = Bits {
+                data:     input,
+                position: self.position
+            };
+            let progress = match self.state {
+                #[cfg(feature = "zlib")]
+                State::ZlibHeader => match bits.get(16) {
+                    Some(header) => {
+                        let (cmf, flg) = (header & 0xff, header >> 8);
+
+                        if cmf & 15 != 8 || (cmf >> 4) > 7 {
+                            return Err(DecodeErrorStatus::Generic("Unknown zlib compression"));
+                        }
+                        if ((cmf << 8) | flg) % 31 != 0 {
+                            return Err(DecodeErrorStatus::Generic(
+                                "FCHECK integrity not preserved"
+                            ));
+                        }
+                        if flg & 0x20 != 0 {
+                            return Err(DecodeErrorStatus::Generic(
+                                "Preset dictionaries unsupported"
+                            ));
+                        }
+                        self.state = State::BlockHeader;
+                        true
+                    }
+                    None => false
+                },
+                State::BlockHeader => self.block_header(&mut bits)?,
+                State::Stored(left) => {
+                    let length = left.min(bits.available() / 8);
+                    let start = bits.position / 8;
+
+                    self.window
+                        .extend_from_slice(&bits.data[start..start + length]);
+                    bits.position += length * 8;
+                    self.total += length;
+
+                    self.state = match left - length {
+                        0 => self.next_block(),
+                        left => State::Stored(left)
+                    };
+                    length > 0
+                }
+                State::Codes => self.codes(&mut bits, wanted)?,
+                #[cfg(feature = "zlib")]
+                State::Checksum => {
+                    bits.align_to_byte();
+                    let start = bits.position / 8;
+
+                    match bits.data.get(start..start + 4) {
+                        Some(adler) => {
+                            let expected = u32::from_be_bytes(adler.try_into().unwrap());
+                            bits.position += 32;
+
+                            self.hash_window();
+
+                            if self.options.get_confirm_checksum() && expected != self.adler {
+                                return Err(DecodeErrorStatus::MismatchedAdler(
+                                    expected, self.adler
+                                ));
+                            }
+                            self.state = State::Done;
+                            true
+                        }
+                        None => false
+                    }
+                }
+                State::Done => false
+            };
+            let position = bits.position;
+
+            if !progress {
+                break;
+            }
+            self.position = position;
+
+            if self.total > self.options.get_limit() {
+                return Err(DecodeErrorStatus::OutputLimitExceeded(
+                    self.options.get_limit(),
+                    self.total
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The state after the current block
+    fn next_block(&self) -> State {
+        match self.is_last {
+            #[cfg(feature = "zlib")]
+            true if self.is_zlib => State::Checksum,
+            true => State::Done,
+            false => State::BlockHeader
+        }
+    }
+
+    /// Read a block header, and the tables of the block, returning false if
+    /// there isn't enough input
+    fn block_header(&mut self, bits: &mut Bits) -> Result<bool, DecodeErrorStatus> {
+        let Some(header) = bits.get(3) else {
+            return Ok(false);
+        };
+        let is_last = header & 1 == 1;
+
+        match header >> 1 {
+            0 => {
+                bits.align_to_byte();
+
+                let Some((length, inverse)) = bits.get(16).zip(bits.get(16)) else {
+                    return Ok(false);
+                };
+                if length != !inverse & 0xffff {
+                    return Err(DecodeErrorStatus::Generic("Len and nlen do not match"));
+                }
+                self.is_last = is_last;
+                self.state = match length {
+                    0 => self.next_block(),
+                    length => State::Stored(length as usize)
+                };
+            }
+            1 => {
+                let mut lengths = [0; 320];
+
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..288].fill(8);
+                lengths[288..].fill(5);
+
+                self.literals.build(&lengths[..288])?;
+                self.distances.build(&lengths[288..])?;
+
+                self.is_last = is_last;
+                self.state = State::Codes;
+            }
+            2 => {
+                if !self.dynamic_tables(bits)? {
+                    return Ok(false);
+                }
+                self.is_last = is_last;
+                self.state = State::Codes;
+            }
+            _ => {
+                return Err(DecodeErrorStatus::Generic(
+                    "Reserved block type 0b11 encountered"
+                ))
+            }
+        }
+        Ok(true)
+    }
+
+    /// Read the code lengths of a dynamic block, returning false if
+    /// there isn't enough input
+    fn dynamic_tables(&mut self, bits: &mut Bits) -> Result<bool, DecodeErrorStatus> {
+        let Some(counts) = bits.get(14) else {
+            return Ok(false);
+        };
+        let literals = (counts & 31) as usize + 257;
+        let distances = ((counts >> 5) & 31) as usize + 1;
+        let code_lengths = (counts >> 10) as usize + 4;
+
+        if literals > 286 || distances > 30 {
+            return Err(DecodeErrorStatus::CorruptData);
+        }
+        let mut precode = [0; 19];
+
+        for position in &DEFLATE_PRECODE_LENS_PERMUTATION[..code_lengths] {
+            let Some(length) = bits.get(3) else {
+                return Ok(false);
+            };
+            precode[usize::from(*position)] = length as u8;
+        }
+        self.code_length.build(&precode)?;
+
+        let mut lengths = [0; 320];
+        let mut i = 0;
+
+        while i < literals + distances {
+            let Some(symbol) = bits.decode(&self.code_length)? else {
+                return Ok(false);
+            };
+            let (value, repeat) = match symbol {
+                0..=15 => (symbol as u8, 1),
+                16 => {
+                    let Some(previous) = i.checked_sub(1).map(|x| lengths[x]) else {
+                        return Err(DecodeErrorStatus::CorruptData);
+                    };
+                    match bits.get(2) {
+                        Some(extra) => (previous, 3 + extra as usize),
+                        None => return Ok(false)
+                    }
+                }
+                17 => match bits.get(3) {
+                    Some(extra) => (0, 3 + extra as usize),
+                    None => return Ok(false)
+                },
+                _ => match bits.get(7) {
+                    Some(extra) => (0, 11 + extra as usize),
+                    None => return Ok(false)
+                }
+            };
+            let Some(run) = lengths.get_mut(i..i + repeat) else {
+                return Err(DecodeErrorStatus::CorruptData);
+            };
+            run.fill(value);
+            i += repeat;
+        }
+        if i != literals + distances {
+            return Err(DecodeErrorStatus::CorruptData);
+        }
+        if lengths[256] == 0 {
+            return Err(DecodeErrorStatus::Generic("No end of block code"));
+        }
+        self.literals.build(&lengths[..literals])?;
+        self.distances
+            .build(&lengths[literals..literals + distances])?;
+
+        Ok(true)
+    }
+
+    /// Decode literals and matches until `wanted` bytes are pending, returning false
+    /// if nothing could be decoded for lack of input
+    fn codes(&mut self, bits: &mut Bits, wanted: usize) -> Result<bool, DecodeErrorStatus> {
+        let mut progress = false;
+
+        while self.window.len() - self.handed_out < wanted {
+            // symbols are only taken once all their bits are there
+            let mut symbol_bits = *bits;
+
+            let Some(symbol) = symbol_bits.decode(&self.literals)? else {
+                break;
+            };
+            match symbol {
+                0..=255 => {
+                    self.window.push(symbol as u8);
+                    self.total += 1;
+                }
+                256 => {
+                    *bits = symbol_bits;
+                    self.state = self.next_block();
+                    return Ok(true);
+                }
+                _ => {
+                    let index = symbol - 257;
+
+                    let (Some(base), Some(extra)) =
+                        (LENGTH_BASE.get(index), LENGTH_EXTRA.get(index))
+                    else {
+                        return Err(DecodeErrorStatus::CorruptData);
+                    };
+                    let Some(extra) = symbol_bits.get(*extra) else {
+                        break;
+                    };
+                    let length = usize::from(*base) + extra as usize;
+
+                    let Some(index) = symbol_bits.decode(&self.distances)? else {
+                        break;
+                    };
+                    let (Some(base), Some(extra)) =
+                        (DISTANCE_BASE.get(index), DISTANCE_EXTRA.get(index))
+                    else {
+                        return Err(DecodeErrorStatus::CorruptData);
+                    };
+                    let Some(extra) = symbol_bits.get(*extra) else {
+                        break;
+                    };
+                    let distance = usize::from(*base) + extra as usize;
+
+                    if distance > self.window.len() {
+                        return Err(DecodeErrorStatus::CorruptData);
+                    }
+                    let start = self.window.len() - distance;
+
+                    if distance >= length {
+                        self.window.extend_from_within(start..start + length);
+                    } else {
+                        // the match overlaps what it writes
+                        for i in start..start + length {
+                            self.window.push(self.window[i]);
+                        }
+                    }
+                    self.total += length;
+                }
+            }
+            *bits = symbol_bits;
+            progress = true;
+        }
+        Ok(progress)
+    }
+
+    /// Add the bytes not yet hashed to the checksum
+    fn hash_window(&mut self) {
+        #[cfg(feature = "zlib")]
+        {
+            let bytes = &self.window[self.hashed..];
+
+            if !bytes.is_empty() {
+                self.adler = combine_adler_hashes(self.adler, calc_adler_hash(bytes), bytes.len());
+                self.hashed = self.window.len();
+            }
+        }
+    }
+}
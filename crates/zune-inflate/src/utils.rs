@@ -83,3 +83,35 @@ pub fn calc_adler_hash(data: &[u8]) -> u32 {
 
     hasher.finish()
 }
+
+/// Return the adler hash of two pieces of data one after the other
+/// from the hashes of each piece, and the length of the second one
+///
+/// This is zlib's `adler32_combine`
+#[cfg(feature = "zlib")]
+#[allow(clippy::cast_possible_truncation)]
+pub fn combine_adler_hashes(first: u32, second: u32, second_length: usize) -> u32 {
+    const BASE: u32 = 65521;
+
+    let rem = (second_length % BASE as usize) as u32;
+
+    let mut sum1 = first & 0xffff;
+    let mut sum2 = (rem * sum1) % BASE;
+
+    sum1 += (second & 0xffff) + BASE - 1;
+    sum2 += (first >> 16) + (second >> 16) + BASE - rem;
+
+    if sum1 >= BASE {
+        sum1 -= BASE;
+    }
+    if sum1 >= BASE {
+        sum1 -= BASE;
+    }
+    if sum2 >= BASE << 1 {
+        sum2 -= BASE << 1;
+    }
+    if sum2 >= BASE {
+        sum2 -= BASE;
+    }
+    sum1 | (sum2 << 16)
+}
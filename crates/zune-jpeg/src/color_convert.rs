@@ -28,10 +28,11 @@
 //!
 //! Hey there, if your reading this it means you probably need something, so let me help you.
 //!
-//! There are 3 supported cpu extensions here.
+//! There are 4 supported cpu extensions here.
 //! 1. Scalar
 //! 2. SSE
 //! 3. AVX
+//! 4. AVX-512, on cpus which don't slow down running it
 //!
 //! There are two types of the color convert functions
 //!
@@ -54,9 +55,13 @@ use zune_core::options::DecoderOptions;
 #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
 #[cfg(feature = "x86")]
 pub use crate::color_convert::avx::{ycbcr_to_rgb_avx2, ycbcr_to_rgba_avx2};
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[cfg(feature = "x86")]
+pub use crate::color_convert::avx512::{ycbcr_to_rgb_avx512, ycbcr_to_rgba_avx512};
 use crate::decoder::ColorConvert16Ptr;
 
 mod avx;
+mod avx512;
 mod scalar;
 #[allow(unused_variables)]
 pub fn choose_ycbcr_to_rgb_convert_func(
@@ -66,6 +71,17 @@ pub fn choose_ycbcr_to_rgb_convert_func(
     #[cfg(feature = "x86")]
     {
         use zune_core::log::debug;
+        if options.use_avx512() {
+            debug!("Using AVX-512 optimised color conversion functions");
+
+            match type_need {
+                ColorSpace::RGB => return Some(ycbcr_to_rgb_avx512::<false>),
+                ColorSpace::RGBA => return Some(ycbcr_to_rgba_avx512::<false>),
+                ColorSpace::BGR => return Some(ycbcr_to_rgb_avx512::<true>),
+                ColorSpace::BGRA => return Some(ycbcr_to_rgba_avx512::<true>),
+                _ => ()
+            }
+        }
         if options.use_avx2() {
             debug!("Using AVX optimised color conversion functions");
 
@@ -87,3 +103,59 @@ pub fn choose_ycbcr_to_rgb_convert_func(
         _ => None
     };
 }
+
+#[cfg(test)]
+mod tests {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    #[cfg(feature = "x86")]
+    #[test]
+    fn test_avx512_matches_avx2() {
+        use zune_core::simd::Isa;
+
+        use crate::color_convert::scalar::{
+            ycbcr_to_rgb_inner_16_scalar, ycbcr_to_rgba_inner_16_scalar
+        };
+        use crate::color_convert::{
+            ycbcr_to_rgb_avx2, ycbcr_to_rgb_avx512, ycbcr_to_rgba_avx2, ycbcr_to_rgba_avx512
+        };
+        use crate::decoder::ColorConvert16Ptr;
+
+        if !(Isa::Avx2.is_supported() && Isa::Avx512Bw.is_supported()) {
+            return;
+        }
+        // outputs out of range too, so that clamping is exercised
+        let ramp = |start: i16| core::array::from_fn::<i16, 16, _>(|i| start + 23 * i as i16);
+
+        // (expected, converted, channels), there is no AVX2 BGR version
+        let functions: [(ColorConvert16Ptr, ColorConvert16Ptr, usize); 4] = [
+            (ycbcr_to_rgb_avx2, ycbcr_to_rgb_avx512::<false>, 3),
+            (ycbcr_to_rgba_avx2, ycbcr_to_rgba_avx512::<false>, 4),
+            (
+                ycbcr_to_rgb_inner_16_scalar::<true>,
+                ycbcr_to_rgb_avx512::<true>,
+                3
+            ),
+            (
+                ycbcr_to_rgba_inner_16_scalar::<true>,
+                ycbcr_to_rgba_avx512::<true>,
+                4
+            )
+        ];
+        for (expected_fn, function, channels) in functions {
+            for start in [-60, 0, 40, 120, 200] {
+                let (y, cb, cr) = (ramp(start), ramp(255 - start), ramp(start / 2));
+
+                // a margin on both sides, to catch writes outside the pixels
+                let mut expected = vec![7; 16 * channels + 4];
+                let mut out = expected.clone();
+                let (mut expected_offset, mut offset) = (2, 2);
+
+                expected_fn(&y, &cb, &cr, &mut expected, &mut expected_offset);
+                function(&y, &cb, &cr, &mut out, &mut offset);
+
+                assert_eq!(offset, expected_offset);
+                assert_eq!(out, expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,169 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! AVX-512 color conversion routines
+//!
+//! These use the same integer approximation as the [AVX2](super::avx) routines, so
+//! they produce the same pixels, what AVX-512 brings is `vpermb` (from VBMI), which shuffles
+//! bytes across a whole 512 bit register.
+//!
+//! That means the channels of 16 pixels are interleaved by a single shuffle and written
+//! by a single (masked) store, for RGB too where the AVX2 routine writes pixels one by one,
+//! and BGR orders come for free by shuffling differently.
+//!
+//! The math stays on 256 bit registers, only the shuffle and store are 512 bit wide.
+
+#![cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#![cfg(feature = "x86")]
+#![allow(
+    clippy::wildcard_imports,
+    clippy::cast_possible_truncation,
+    clippy::inline_always
+)]
+
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+/// Where byte `i` of the output comes from in the packed channels of [`convert`]
+///
+/// Packing two registers interleaves their 128 bit lanes, so the first 8 values of
+/// a channel are at `0..8` and the next 8 at `16..24`, offset by 8 for green, 32 for blue
+/// and 40 for alpha.
+const fn interleave(components: usize, bgr: bool) -> [u8; 64] {
+    let offsets = if bgr { [32, 8, 0, 40] } else { [0, 8, 32, 40] };
+
+    let mut table = [0; 64];
+    let mut i = 0;
+
+    while i < 16 * components {
+        let (pixel, channel) = (i / components, i % components);
+        let position = if pixel < 8 { pixel } else { pixel + 8 };
+
+        table[i] = (position + offsets[channel]) as u8;
+        i += 1;
+    }
+    table
+}
+
+const RGB: [u8; 64] = interleave(3, false);
+const BGR: [u8; 64] = interleave(3, true);
+const RGBA: [u8; 64] = interleave(4, false);
+const BGRA: [u8; 64] = interleave(4, true);
+
+/// Convert YCbCr to RGB or BGR using AVX-512 instructions
+///
+/// # Note
+/// **IT IS THE RESPONSIBILITY OF THE CALLER TO CALL THIS IN CPUS SUPPORTING
+/// AVX-512 F, BW, VL AND VBMI OTHERWISE THIS IS UB**
+///
+/// This library itself will ensure that it's never called in CPU's not
+/// supporting them
+#[inline(always)]
+pub fn ycbcr_to_rgb_avx512<const BGR_ORDER: bool>(
+    y: &[i16; 16], cb: &[i16; 16], cr: &[i16; 16], out: &mut [u8], offset: &mut usize
+) {
+    let table = if BGR_ORDER { &BGR } else { &RGB };
+
+    let tmp: &mut [u8; 48] = out
+        .get_mut(*offset..*offset + 48)
+        .expect("Slice to small cannot write")
+        .try_into()
+        .unwrap();
+
+    unsafe {
+        ycbcr_to_rgb_avx512_inner(y, cb, cr, tmp, table);
+    }
+    *offset += 48;
+}
+
+/// Convert YCbCr to RGBA or BGRA using AVX-512 instructions
+///
+/// # Note
+/// **IT IS THE RESPONSIBILITY OF THE CALLER TO CALL THIS IN CPUS SUPPORTING
+/// AVX-512 F, BW, VL AND VBMI OTHERWISE THIS IS UB**
+#[inline(always)]
+pub fn ycbcr_to_rgba_avx512<const BGR_ORDER: bool>(
+    y: &[i16; 16], cb: &[i16; 16], cr: &[i16; 16], out: &mut [u8], offset: &mut usize
+) {
+    let table = if BGR_ORDER { &BGRA } else { &RGBA };
+
+    let tmp: &mut [u8; 64] = out
+        .get_mut(*offset..*offset + 64)
+        .expect("Slice to small cannot write")
+        .try_into()
+        .unwrap();
+
+    unsafe {
+        ycbcr_to_rgba_avx512_inner(y, cb, cr, tmp, table);
+    }
+    *offset += 64;
+}
+
+#[inline]
+#[target_feature(enable = "avx2,avx512f,avx512bw,avx512vl,avx512vbmi")]
+unsafe fn ycbcr_to_rgb_avx512_inner(
+    y: &[i16; 16], cb: &[i16; 16], cr: &[i16; 16], out: &mut [u8; 48], table: &[u8; 64]
+) {
+    let pixels = convert(y, cb, cr, table);
+    // write the 48 bytes of the 16 pixels, and nothing past them
+    _mm512_mask_storeu_epi8(out.as_mut_ptr().cast(), (1 << 48) - 1, pixels);
+}
+
+#[inline]
+#[target_feature(enable = "avx2,avx512f,avx512bw,avx512vl,avx512vbmi")]
+unsafe fn ycbcr_to_rgba_avx512_inner(
+    y: &[i16; 16], cb: &[i16; 16], cr: &[i16; 16], out: &mut [u8; 64], table: &[u8; 64]
+) {
+    let pixels = convert(y, cb, cr, table);
+    _mm512_storeu_si512(out.as_mut_ptr().cast(), pixels);
+}
+
+/// Convert 16 pixels to RGB, with opaque alpha, and shuffle their
+/// bytes with `table`
+#[inline]
+#[target_feature(enable = "avx2,avx512f,avx512bw,avx512vl,avx512vbmi")]
+unsafe fn convert(y: &[i16; 16], cb: &[i16; 16], cr: &[i16; 16], table: &[u8; 64]) -> __m512i {
+    let y_c = _mm256_loadu_si256(y.as_ptr().cast());
+    // Cb = Cb - 128, Cr = Cr - 128
+    let cb_r = _mm256_sub_epi16(
+        _mm256_loadu_si256(cb.as_ptr().cast()),
+        _mm256_set1_epi16(128)
+    );
+    let cr_r = _mm256_sub_epi16(
+        _mm256_loadu_si256(cr.as_ptr().cast()),
+        _mm256_set1_epi16(128)
+    );
+
+    // r = Y + 45 * Cr / 32
+    let r = _mm256_add_epi16(
+        y_c,
+        _mm256_srai_epi16::<5>(_mm256_mullo_epi16(_mm256_set1_epi16(45), cr_r))
+    );
+    // g = Y - (11 * Cb + 23 * Cr) / 32
+    let g = _mm256_sub_epi16(
+        y_c,
+        _mm256_srai_epi16::<5>(_mm256_add_epi16(
+            _mm256_mullo_epi16(_mm256_set1_epi16(11), cb_r),
+            _mm256_mullo_epi16(_mm256_set1_epi16(23), cr_r)
+        ))
+    );
+    // b = Y + 113 * Cb / 64
+    let b = _mm256_add_epi16(
+        y_c,
+        _mm256_srai_epi16::<6>(_mm256_mullo_epi16(_mm256_set1_epi16(113), cb_r))
+    );
+
+    // packing with unsigned saturation clamps to 0..=255
+    let rg = _mm256_packus_epi16(r, g);
+    let ba = _mm256_packus_epi16(b, _mm256_set1_epi16(255));
+    let packed = _mm512_inserti64x4::<1>(_mm512_castsi256_si512(rg), ba);
+
+    _mm512_permutexvar_epi8(_mm512_loadu_si512(table.as_ptr().cast()), packed)
+}
@@ -22,12 +22,14 @@ use crate::color_convert::choose_ycbcr_to_rgb_convert_func;
 use crate::components::{Components, SampleRatios};
 use crate::errors::{DecodeErrors, UnsupportedSchemes};
 use crate::headers::{
-    parse_app1, parse_app14, parse_app2, parse_dqt, parse_huffman, parse_sos, parse_start_of_frame
+    parse_app1, parse_app13, parse_app14, parse_app2, parse_dqt, parse_huffman, parse_sos,
+    parse_start_of_frame
 };
 use crate::huffman::HuffmanTable;
 use crate::idct::choose_idct_func;
 use crate::marker::Marker;
 use crate::misc::SOFMarkers;
+use crate::rows::RowState;
 use crate::upsampler::{
     choose_horizontal_samp_function, choose_hv_samp_function, choose_v_samp_function,
     upsample_no_op
@@ -145,10 +147,18 @@ pub struct JpegDecoder<T: ZByteReaderTrait> {
     pub(crate) seen_sof:         bool,
     // exif data, lifted from app2
     pub(crate) exif_data:        Option<Vec<u8>>,
-
-    pub(crate) icc_data: Vec<ICCChunk>,
-    pub(crate) is_mjpeg: bool,
-    pub(crate) coeff:    usize // Solves some weird bug :)
+    // xmp packet, lifted from app1
+    pub(crate) xmp_data:         Option<Vec<u8>>,
+    // iptc records, lifted from app13
+    pub(crate) iptc_data:        Option<Vec<u8>>,
+
+    pub(crate) icc_data:   Vec<ICCChunk>,
+    pub(crate) is_mjpeg:   bool,
+    pub(crate) coeff:      usize, // Solves some weird bug :)
+    /// Bytes from the start of one output row to the start of the next
+    pub(crate) out_stride: usize,
+    /// Set by `start_rows`, for decoding a few rows at a time
+    pub(crate) rows:       Option<RowState>
 }
 
 impl<T> JpegDecoder<T>
@@ -190,9 +200,13 @@ where
             headers_decoded:   false,
             seen_sof:          false,
             exif_data:         None,
+            xmp_data:          None,
+            iptc_data:         None,
             icc_data:          vec![],
             is_mjpeg:          false,
-            coeff:             1
+            coeff:             1,
+            out_stride:        0,
+            rows:              None
         }
     }
     /// Decode a buffer already in memory
@@ -343,7 +357,7 @@ where
     ///  - SOF(n) -> Decoder images which are not baseline/progressive
     ///  - DAC -> Images using Arithmetic tables
     ///  - JPG(n)
-    fn decode_headers_internal(&mut self) -> Result<(), DecodeErrors> {
+    pub(crate) fn decode_headers_internal(&mut self) -> Result<(), DecodeErrors> {
         if self.headers_decoded {
             trace!("Headers decoded!");
             return Ok(());
@@ -426,6 +440,8 @@ where
                     self.parse_marker_inner(n)?;
 
                     if n == Marker::SOS {
+                        self.set_ycbcr_fallback();
+                        self.check_limits()?;
                         self.headers_decoded = true;
                         trace!("Input colorspace {:?}", self.input_colorspace);
                         return Ok(());
@@ -451,6 +467,39 @@ where
             bytes_before_marker += 1;
         }
     }
+    /// Fall back to RGB if YCbCr output was requested for an
+    /// image that isn't stored as YCbCr
+    ///
+    /// YCbCr output returns the decoded samples without color conversion,
+    /// which only makes sense if the image is YCbCr to begin with
+    fn set_ycbcr_fallback(&mut self) {
+        if self.options.jpeg_get_out_colorspace() == ColorSpace::YCbCr
+            && self.input_colorspace != ColorSpace::YCbCr
+            && self.input_colorspace != ColorSpace::Luma
+        {
+            warn!(
+                "Cannot return {:?} image as YCbCr, decoding to RGB",
+                self.input_colorspace
+            );
+            self.options = self.options.jpeg_set_out_colorspace(ColorSpace::RGB);
+            self.color_convert_16 =
+                choose_ycbcr_to_rgb_convert_func(ColorSpace::RGB, &self.options).unwrap();
+        }
+    }
+    /// Check the size of the output against the pixel, frame and memory limits
+    /// of the options
+    fn check_limits(&self) -> Result<(), DecodeErrors> {
+        let (width, height) = (usize::from(self.width()), usize::from(self.height()));
+        let components = self.options.jpeg_get_out_colorspace().num_components();
+
+        if let Some((limit, found, max)) = self.options.exceeded_limit(width, height, components, 1)
+        {
+            return Err(DecodeErrors::Format(format!(
+                "Image {limit} {found} greater than {limit} limit {max}. Use the options to raise the limit if you want to support such images"
+            )));
+        }
+        Ok(())
+    }
     #[allow(clippy::too_many_lines)]
     pub(crate) fn parse_marker_inner(&mut self, m: Marker) -> Result<(), DecodeErrors> {
         match m {
@@ -496,6 +545,15 @@ where
                         self.is_mjpeg = true;
                     }
                     length -= 5;
+
+                    // version, density unit and x and y density
+                    if &buffer == b"JFIF\0" && length >= 2 + 7 {
+                        self.stream.skip(2)?;
+                        self.info.set_density_unit(self.stream.read_u8_err()?);
+                        self.info.set_x(self.stream.get_u16_be_err()?);
+                        self.info.set_y(self.stream.get_u16_be_err()?);
+                        length -= 7;
+                    }
                 }
 
                 self.stream.skip(length.saturating_sub(2) as usize)?;
@@ -545,6 +603,9 @@ where
                 self.restart_interval = usize::from(self.stream.get_u16_be_err()?);
                 self.todo = self.restart_interval;
             }
+            Marker::APP(13) => {
+                parse_app13(self)?;
+            }
             Marker::APP(14) => {
                 parse_app14(self)?;
             }
@@ -638,6 +699,30 @@ where
     pub fn exif(&self) -> Option<&Vec<u8>> {
         return self.exif_data.as_ref();
     }
+    /// Return the XMP packet of the file
+    ///
+    /// This returns the raw XML of the packet, without the
+    /// APP1 namespace header. Extended XMP is not supported
+    ///
+    /// # Returns
+    /// -`Some(data)`: The XMP packet, if present in the image
+    /// - None: The image doesn't have XMP metadata or the headers haven't been decoded
+    #[must_use]
+    pub fn xmp(&self) -> Option<&Vec<u8>> {
+        self.xmp_data.as_ref()
+    }
+    /// Return the IPTC-IIM records of the file
+    ///
+    /// This returns the raw records stored in the Photoshop APP13
+    /// segment, other Photoshop resources are skipped
+    ///
+    /// # Returns
+    /// -`Some(data)`: The IPTC-IIM records, if present in the image
+    /// - None: The image doesn't have IPTC metadata or the headers haven't been decoded
+    #[must_use]
+    pub fn iptc(&self) -> Option<&Vec<u8>> {
+        self.iptc_data.as_ref()
+    }
     /// Get the output colorspace the image pixels will be decoded into
     ///
     ///
@@ -695,7 +780,65 @@ where
     pub fn decode_into(&mut self, out: &mut [u8]) -> Result<(), DecodeErrors> {
         self.decode_headers_internal()?;
 
-        let expected_size = self.output_buffer_size().unwrap();
+        let row_size = self.output_row_size();
+        self.decode_into_with_stride(out, row_size)
+    }
+
+    /// Decode into a pre-allocated buffer whose rows start `stride` bytes apart,
+    /// e.g. a framebuffer or a GPU staging buffer with padded rows
+    ///
+    /// Pixels are written in the [output colorspace](Self::output_colorspace), which
+    /// can be set to BGR or BGRA for framebuffers expecting that order. The bytes
+    /// between the end of one row and the start of the next are left as they are.
+    ///
+    /// It is an error if `stride` is smaller than a row of the image, `width * components`
+    /// bytes, or if the buffer is smaller than `stride * (height - 1)` bytes plus a row, the
+    /// last row doesn't need padding.
+    ///
+    /// # Example
+    /// Decode into a BGRA buffer whose rows are aligned to 256 bytes
+    ///
+    /// ```no_run
+    /// use zune_core::bytestream::ZCursor;
+    /// use zune_core::colorspace::ColorSpace;
+    /// use zune_core::options::DecoderOptions;
+    /// use zune_jpeg::JpegDecoder;
+    ///
+    /// let options = DecoderOptions::default().jpeg_set_out_colorspace(ColorSpace::BGRA);
+    /// let mut decoder = JpegDecoder::new_with_options(ZCursor::new(&[]), options);
+    /// decoder.decode_headers().unwrap();
+    ///
+    /// let (width, height) = decoder.dimensions().unwrap();
+    /// let stride = (width * 4).next_multiple_of(256);
+    ///
+    /// let mut framebuffer = vec![0; stride * height];
+    /// decoder
+    ///     .decode_into_with_stride(&mut framebuffer, stride)
+    ///     .unwrap();
+    /// ```
+    pub fn decode_into_with_stride(
+        &mut self, out: &mut [u8], stride: usize
+    ) -> Result<(), DecodeErrors> {
+        self.decode_headers_internal()?;
+
+        if self.rows.is_some() {
+            return Err(DecodeErrors::FormatStatic(
+                "Image is being decoded by rows, it can't be decoded whole"
+            ));
+        }
+        let row_size = self.output_row_size();
+
+        if stride < row_size {
+            return Err(DecodeErrors::Format(format!(
+                "Stride {stride} is smaller than a row of the image, {row_size} bytes"
+            )));
+        }
+        let expected_size = stride
+            .checked_mul(usize::from(self.height()).saturating_sub(1))
+            .and_then(|x| x.checked_add(row_size))
+            .ok_or(DecodeErrors::FormatStatic(
+                "Output buffer size overflows a usize"
+            ))?;
 
         if out.len() < expected_size {
             // too small of a size
@@ -703,8 +846,8 @@ where
         }
 
         // ensure we don't touch anyone else's scratch space
-        let out_len = core::cmp::min(out.len(), expected_size);
-        let out = &mut out[0..out_len];
+        let out = &mut out[0..expected_size];
+        self.out_stride = stride;
 
         if self.is_progressive {
             self.decode_mcu_ycbcr_progressive(out)
@@ -713,6 +856,11 @@ where
         }
     }
 
+    /// Bytes of a row of output pixels
+    pub(crate) fn output_row_size(&self) -> usize {
+        usize::from(self.width()) * self.options.jpeg_get_out_colorspace().num_components()
+    }
+
     /// Read only headers from a jpeg image buffer
     ///
     /// This allows you to extract important information like
@@ -855,6 +1003,9 @@ pub struct ImageInfo {
     pub x_density:     u16,
     /// Vertical sample
     pub y_density:     u16,
+    /// Unit of the x and y density, 0 if they only give the aspect ratio
+    /// of a pixel, 1 for dots per inch and 2 for dots per centimeter
+    pub density_unit:  u8,
     /// Number of components
     pub components:    u8
 }
@@ -892,10 +1043,16 @@ impl ImageInfo {
         self.sof = marker;
     }
 
+    /// Set the unit of the x and y density
+    ///
+    /// Found in the APP(0) marker
+    pub(crate) fn set_density_unit(&mut self, unit: u8) {
+        self.density_unit = unit;
+    }
+
     /// Set image x-density(dots per pixel)
     ///
     /// Found in the APP(0) marker
-    #[allow(dead_code)]
     pub(crate) fn set_x(&mut self, sample: u16) {
         self.x_density = sample;
     }
@@ -903,7 +1060,6 @@ impl ImageInfo {
     /// Set image y-density
     ///
     /// Found in the APP(0) marker
-    #[allow(dead_code)]
     pub(crate) fn set_y(&mut self, sample: u16) {
         self.y_density = sample;
     }
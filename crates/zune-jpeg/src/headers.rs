@@ -459,6 +459,9 @@ pub(crate) fn parse_app14<T: ZByteReaderTrait>(
     Ok(())
 }
 
+/// Namespace header of an XMP packet in an APP1 segment
+const XMP_HEADER: &[u8] = b"http://ns.adobe.com/xap/1.0/\x00";
+
 /// Parse the APP1 segment
 ///
 /// This contains the exif tag
@@ -483,6 +486,14 @@ pub(crate) fn parse_app1<T: ZByteReaderTrait>(
         let exif_bytes = decoder.stream.peek_at(0, length)?.to_vec();
 
         decoder.exif_data = Some(exif_bytes);
+    } else if length > XMP_HEADER.len()
+        && decoder.stream.peek_at(0, XMP_HEADER.len())? == XMP_HEADER
+    {
+        trace!("XMP segment present");
+        decoder.stream.skip(XMP_HEADER.len())?;
+        length -= XMP_HEADER.len();
+
+        decoder.xmp_data = Some(decoder.stream.peek_at(0, length)?.to_vec());
     } else {
         warn!("Wrongly formatted exif tag");
     }
@@ -491,6 +502,56 @@ pub(crate) fn parse_app1<T: ZByteReaderTrait>(
     Ok(())
 }
 
+/// Parse the APP13 segment
+///
+/// This contains Photoshop image resources, of which we keep
+/// the IPTC-IIM records
+pub(crate) fn parse_app13<T: ZByteReaderTrait>(
+    decoder: &mut JpegDecoder<T>
+) -> Result<(), DecodeErrors> {
+    const PHOTOSHOP_HEADER: &[u8] = b"Photoshop 3.0\x00";
+    // resource id of IPTC-IIM records
+    const IPTC_RESOURCE: u16 = 0x0404;
+
+    let length = usize::from(decoder.stream.get_u16_be_err()?);
+
+    if length < 2 {
+        return Err(DecodeErrors::FormatStatic("Too small app13 length"));
+    }
+    let length = length - 2;
+    let data = decoder.stream.peek_at(0, length)?;
+
+    if let Some(mut resources) = data.strip_prefix(PHOTOSHOP_HEADER) {
+        // resources are "8BIM", a 2 byte id, a pascal string name padded to an even length
+        // and the data size, with the data padded to an even length
+        while resources.len() >= 7 && &resources[..4] == b"8BIM" {
+            let id = u16::from_be_bytes([resources[4], resources[5]]);
+            let name_length = (usize::from(resources[6]) + 2) & !1;
+            let start = 6 + name_length + 4;
+
+            let Some(size) = resources.get(start - 4..start) else {
+                break;
+            };
+            let size = u32::from_be_bytes([size[0], size[1], size[2], size[3]]) as usize;
+
+            let Some(data) = resources.get(start..start + size) else {
+                warn!("Truncated Photoshop resource");
+                break;
+            };
+            if id == IPTC_RESOURCE {
+                trace!("IPTC records present");
+                decoder.iptc_data = Some(data.to_vec());
+                break;
+            }
+            resources = resources
+                .get(start + ((size + 1) & !1)..)
+                .unwrap_or_default();
+        }
+    }
+    decoder.stream.skip(length)?;
+    Ok(())
+}
+
 pub(crate) fn parse_app2<T: ZByteReaderTrait>(
     decoder: &mut JpegDecoder<T>
 ) -> Result<(), DecodeErrors> {
@@ -17,6 +17,7 @@
 //!  - Fast color convert functions
 //!  - RGBA and RGBX (4-Channel) color conversion functions
 //!  - YCbCr to Luma(Grayscale) conversion.
+//!  - Native YCbCr output without color conversion.
 //!
 //! # Usage
 //! Add zune-jpeg to the dependencies in the project Cargo.toml
@@ -53,6 +54,28 @@
 //! let pixels = decoder.decode().unwrap();
 //! ```
 //!
+//! ## Decode a JPEG file to YCbCr
+//!
+//! This returns the decoded samples without converting them to RGB, useful when
+//! color conversion happens later, e.g. on the GPU.
+//!
+//! Chroma channels are upsampled to the image dimensions, images not stored as
+//! YCbCr, e.g. CMYK images, are decoded to RGB instead.
+//!
+//!```no_run
+//! use zune_core::bytestream::ZCursor;
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_core::options::DecoderOptions;
+//! use zune_jpeg::JpegDecoder;
+//!
+//! let options = DecoderOptions::default().jpeg_set_out_colorspace(ColorSpace::YCbCr);
+//!
+//! let mut decoder = JpegDecoder::new_with_options(ZCursor::new(&[]), options);
+//! let pixels = decoder.decode().unwrap();
+//! // check what the decoder actually returned
+//! let colorspace = decoder.output_colorspace().unwrap();
+//! ```
+//!
 //! ## Decode an image and get it's width and height.
 //!```no_run
 //! use zune_core::bytestream::ZCursor;
@@ -125,7 +148,9 @@ pub mod idct;
 mod marker;
 mod mcu;
 mod mcu_prog;
+mod mcu_restarts;
 mod misc;
+mod rows;
 mod unsafe_utils;
 mod unsafe_utils_avx2;
 mod unsafe_utils_neon;
@@ -86,6 +86,92 @@ impl<T: ZByteReaderTrait> JpegDecoder<T> {
     pub(crate) fn decode_mcu_ycbcr_baseline(
         &mut self, pixels: &mut [u8]
     ) -> Result<(), DecodeErrors> {
+        let (mcu_width, mcu_height) = self.prepare_baseline()?;
+
+        let width = usize::from(self.info.width);
+
+        let padded_width = calculate_padded_width(width, self.sub_sample_ratio);
+
+        let mut stream = BitStream::new();
+        let mut tmp = [0_i32; DCT_BLOCK];
+
+        let mut pixels_written = 0;
+
+        let is_hv = usize::from(self.is_interleaved);
+        let upsampler_scratch_size = is_hv * self.components[0].width_stride;
+        let mut upsampler_scratch_space = vec![0; upsampler_scratch_size];
+
+        #[cfg(feature = "threads")]
+        if let Some(planes) = self.decode_restart_segments(mcu_width, mcu_height)? {
+            for i in 0..mcu_height {
+                // bring back the row of MCU's the post processor expects
+                for (component, plane) in self.components.iter_mut().zip(&planes) {
+                    if component.needed {
+                        let len = component.raw_coeff.len();
+                        component
+                            .raw_coeff
+                            .copy_from_slice(&plane[i * len..(i + 1) * len]);
+                    }
+                }
+                self.post_process(
+                    pixels,
+                    i,
+                    mcu_height,
+                    width,
+                    padded_width,
+                    &mut pixels_written,
+                    &mut upsampler_scratch_space
+                )?;
+            }
+            trace!("Finished decoding image");
+
+            return Ok(());
+        }
+
+        for i in 0..mcu_height {
+            // Report if we have no more bytes
+            // This may generate false negatives since we over-read bytes
+            // hence that why 37 is chosen(we assume if we over-read more than 37 bytes, we have a problem)
+            if stream.overread_by > 37
+            // favourite number :)
+            {
+                if self.options.strict_mode() {
+                    return Err(DecodeErrors::FormatStatic("Premature end of buffer"));
+                };
+
+                error!("Premature end of buffer");
+                break;
+            }
+            // decode a whole MCU width,
+            // this takes into account interleaved components.
+            let terminate = self.decode_mcu_width(mcu_width, &mut tmp, &mut stream)?;
+            // process that width up until it's impossible
+            self.post_process(
+                pixels,
+                i,
+                mcu_height,
+                width,
+                padded_width,
+                &mut pixels_written,
+                &mut upsampler_scratch_space
+            )?;
+            if terminate {
+                warn!("Got terminate signal, will not process further");
+                return Ok(());
+            }
+        }
+        // it may happen that some images don't have the whole buffer
+        // so we can't panic in case of that
+        // assert_eq!(pixels_written, pixels.len());
+
+        trace!("Finished decoding image");
+
+        Ok(())
+    }
+    /// Set up components for decoding a baseline image, returning the number of
+    /// MCUs in a row and in a column
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn prepare_baseline(&mut self) -> Result<(usize, usize), DecodeErrors> {
         setup_component_params(self)?;
 
         // check dc and AC tables
@@ -141,13 +227,6 @@ impl<T: ZByteReaderTrait> JpegDecoder<T> {
             mcu_width = ((self.info.width + 7) / 8) as usize;
             mcu_height = ((self.info.height + 7) / 8) as usize;
         }
-        let width = usize::from(self.info.width);
-
-        let padded_width = calculate_padded_width(width, self.sub_sample_ratio);
-
-        let mut stream = BitStream::new();
-        let mut tmp = [0_i32; DCT_BLOCK];
-
         let comp_len = self.components.len();
 
         for (pos, comp) in self.components.iter_mut().enumerate() {
@@ -173,54 +252,10 @@ impl<T: ZByteReaderTrait> JpegDecoder<T> {
                 comp.needed = false;
             }
         }
-
-        let mut pixels_written = 0;
-
-        let is_hv = usize::from(self.is_interleaved);
-        let upsampler_scratch_size = is_hv * self.components[0].width_stride;
-        let mut upsampler_scratch_space = vec![0; upsampler_scratch_size];
-
-        for i in 0..mcu_height {
-            // Report if we have no more bytes
-            // This may generate false negatives since we over-read bytes
-            // hence that why 37 is chosen(we assume if we over-read more than 37 bytes, we have a problem)
-            if stream.overread_by > 37
-            // favourite number :)
-            {
-                if self.options.strict_mode() {
-                    return Err(DecodeErrors::FormatStatic("Premature end of buffer"));
-                };
-
-                error!("Premature end of buffer");
-                break;
-            }
-            // decode a whole MCU width,
-            // this takes into account interleaved components.
-            let terminate = self.decode_mcu_width(mcu_width, &mut tmp, &mut stream)?;
-            // process that width up until it's impossible
-            self.post_process(
-                pixels,
-                i,
-                mcu_height,
-                width,
-                padded_width,
-                &mut pixels_written,
-                &mut upsampler_scratch_space
-            )?;
-            if terminate {
-                warn!("Got terminate signal, will not process further");
-                return Ok(());
-            }
-        }
-        // it may happen that some images don't have the whole buffer
-        // so we can't panic in case of that
-        // assert_eq!(pixels_written, pixels.len());
-
-        trace!("Finished decoding image");
-
-        Ok(())
+        Ok((mcu_width, mcu_height))
     }
-    fn decode_mcu_width(
+
+    pub(crate) fn decode_mcu_width(
         &mut self, mcu_width: usize, tmp: &mut [i32; 64], stream: &mut BitStream
     ) -> Result<bool, DecodeErrors> {
         for j in 0..mcu_width {
@@ -370,13 +405,16 @@ impl<T: ZByteReaderTrait> JpegDecoder<T> {
         if out_colorspace_components < comp_len && self.options.jpeg_get_out_colorspace() == Luma {
             comp_len = out_colorspace_components;
         }
+        let row_size = width * out_colorspace_components;
+        let stride = self.out_stride;
+
         let mut color_conv_function =
             |num_iters: usize, samples: [&[i16]; 4]| -> Result<(), DecodeErrors> {
-                for (pos, output) in pixels[px..]
-                    .chunks_exact_mut(width * out_colorspace_components)
-                    .take(num_iters)
-                    .enumerate()
-                {
+                for pos in 0..num_iters {
+                    // rows past the image are padding
+                    let Some(output) = pixels.get_mut(px..px + row_size) else {
+                        break;
+                    };
                     let mut raw_samples: [&[i16]; 4] = [&[], &[], &[], &[]];
 
                     // iterate over each line, since color-convert needs only
@@ -393,7 +431,7 @@ impl<T: ZByteReaderTrait> JpegDecoder<T> {
                         width,
                         padded_width
                     )?;
-                    px += width * out_colorspace_components;
+                    px += stride;
                 }
                 Ok(())
             };
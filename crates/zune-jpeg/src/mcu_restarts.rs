@@ -0,0 +1,288 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Parallel decoding of baseline images with restart markers
+//!
+//! The bitstream can't be split in general since huffman codes have no boundaries,
+//! but restart markers reset the bitstream and dc predictions, so the
+//! segments between markers can be decoded independently.
+//!
+//! When every segment covers whole MCU rows, segments are entropy decoded and
+//! de-quantized into a coefficient plane per component on many threads,
+//! after which upsampling and color conversion run row by row as for the serial decoder.
+#![cfg(feature = "threads")]
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+use std::thread;
+
+use zune_core::bytestream::{ZByteReaderTrait, ZCursor, ZReader};
+use zune_core::log::trace;
+
+use crate::bitstream::BitStream;
+use crate::components::Components;
+use crate::decoder::{IDCTPtr, MAX_COMPONENTS};
+use crate::errors::DecodeErrors;
+use crate::huffman::HuffmanTable;
+use crate::mcu::DCT_BLOCK;
+use crate::JpegDecoder;
+
+/// Bytes read at a time when loading the entropy coded data
+const READ_SIZE: usize = 1 << 16;
+
+/// Images with less pixels than this, e.g. thumbnails, decode faster on one thread
+/// than it takes to split them and start threads
+const MIN_PARALLEL_PIXELS: usize = 1 << 16;
+
+impl<T: ZByteReaderTrait> JpegDecoder<T> {
+    /// Decode the scan of a baseline image on multiple threads if it has restart
+    /// markers at MCU row boundaries, returning a plane of coefficients for every component
+    ///
+    /// Planes hold `mcu_height` rows, each laid out as `raw_coeff` is for a single row.
+    ///
+    /// Returns `None` with the stream where it was if the image can't be decoded
+    /// this way, e.g. it has no restart markers, or is truncated
+    pub(crate) fn decode_restart_segments(
+        &mut self, mcu_width: usize, mcu_height: usize
+    ) -> Result<Option<Vec<Vec<i16>>>, DecodeErrors> {
+        let interval = self.restart_interval;
+        let pixels = usize::from(self.info.width) * usize::from(self.info.height);
+
+        if pixels < MIN_PARALLEL_PIXELS
+            || interval == 0
+            || mcu_width == 0
+            || !interval.is_multiple_of(mcu_width)
+            || self.coeff != 1
+            || usize::from(self.num_scans) != self.components.len()
+        {
+            return Ok(None);
+        }
+        // asking the os is slower than the checks above
+        let threads = match self.options.max_threads() {
+            0 => thread::available_parallelism().map_or(1, usize::from),
+            threads => threads
+        };
+        if threads < 2 {
+            return Ok(None);
+        }
+        let rows_per_segment = interval / mcu_width;
+        let num_segments = mcu_height.div_ceil(rows_per_segment);
+
+        if num_segments < 2 {
+            return Ok(None);
+        }
+
+        let start = self.stream.position()?;
+        let data = read_to_end(&mut self.stream)?;
+
+        let segments = match split_segments(&data) {
+            Some(segments) if segments.len() == num_segments => segments,
+            _ => {
+                trace!("Restart markers don't match the restart interval, decoding serially");
+                self.stream
+                    .set_position(usize::try_from(start).unwrap_or(usize::MAX))?;
+                return Ok(None);
+            }
+        };
+        // leave the stream after the scan, like the serial decoder
+        let end = start + segments.last().map_or(0, |x| x.end) as u64;
+        self.stream
+            .set_position(usize::try_from(end).unwrap_or(usize::MAX))?;
+
+        let workers = threads.min(num_segments);
+
+        trace!("Decoding {num_segments} restart segments on {workers} threads");
+
+        // how many coefficients a row of MCU's takes up in every plane
+        let row_lengths = self.components.iter().map(|x| {
+            if x.needed {
+                x.width_stride * x.vertical_sample * 8
+            } else {
+                0
+            }
+        });
+        let row_lengths: Vec<usize> = row_lengths.collect();
+
+        let mut planes: Vec<Vec<i16>> = row_lengths
+            .iter()
+            .map(|x| vec![0; x * mcu_height])
+            .collect();
+
+        // split every plane into the part each segment writes to
+        let mut outputs: Vec<[&mut [i16]; MAX_COMPONENTS]> =
+            (0..num_segments).map(|_| Default::default()).collect();
+
+        for (pos, plane) in planes.iter_mut().enumerate() {
+            if row_lengths[pos] == 0 {
+                continue;
+            }
+            let chunks = plane.chunks_mut(row_lengths[pos] * rows_per_segment);
+
+            for (output, chunk) in outputs.iter_mut().zip(chunks) {
+                output[pos] = chunk;
+            }
+        }
+
+        let segment = SegmentDecoder {
+            components: &self.components,
+            dc_tables: &self.dc_huffman_tables,
+            ac_tables: &self.ac_huffman_tables,
+            idct: self.idct_func,
+            mcu_width,
+            row_lengths: &row_lengths
+        };
+        let per_worker = num_segments.div_ceil(workers);
+
+        thread::scope(|s| {
+            let workers: Vec<_> = segments
+                .chunks(per_worker)
+                .zip(outputs.chunks_mut(per_worker))
+                .map(|(ranges, outputs)| {
+                    let (data, segment) = (&data, &segment);
+
+                    s.spawn(move || {
+                        for (range, output) in ranges.iter().zip(outputs) {
+                            segment.decode(&data[range.clone()], output)?;
+                        }
+                        Ok::<(), DecodeErrors>(())
+                    })
+                })
+                .collect();
+
+            workers
+                .into_iter()
+                .try_for_each(|worker| worker.join().unwrap())
+        })?;
+
+        Ok(Some(planes))
+    }
+}
+
+/// Read everything left in the stream
+fn read_to_end<T: ZByteReaderTrait>(stream: &mut ZReader<T>) -> Result<Vec<u8>, DecodeErrors> {
+    let mut data = Vec::new();
+    let mut buf = vec![0; READ_SIZE];
+
+    loop {
+        let read = stream.read_bytes(&mut buf)?;
+
+        if read == 0 {
+            return Ok(data);
+        }
+        data.extend_from_slice(&buf[..read]);
+    }
+}
+
+/// Split entropy coded data into the segments between restart markers, each including
+/// the marker ending it
+///
+/// Returns `None` if the data ends before a marker ends the scan
+fn split_segments(data: &[u8]) -> Option<Vec<Range<usize>>> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+
+    while i + 1 < data.len() {
+        if data[i] != 0xFF {
+            i += 1;
+            continue;
+        }
+        match data[i + 1] {
+            // stuffed byte, fill bytes
+            0x00 => i += 2,
+            0xFF => i += 1,
+            // restart marker
+            0xD0..=0xD7 => {
+                segments.push(start..i + 2);
+                start = i + 2;
+                i = start;
+            }
+            // anything else ends the scan
+            _ => {
+                segments.push(start..i + 2);
+                return Some(segments);
+            }
+        }
+    }
+    None
+}
+
+/// What decoding a segment needs from the decoder
+struct SegmentDecoder<'a> {
+    components:  &'a [Components],
+    dc_tables:   &'a [Option<HuffmanTable>; MAX_COMPONENTS],
+    ac_tables:   &'a [Option<HuffmanTable>; MAX_COMPONENTS],
+    idct:        IDCTPtr,
+    mcu_width:   usize,
+    row_lengths: &'a [usize]
+}
+
+impl SegmentDecoder<'_> {
+    /// Decode the MCU rows of one segment into `output`, the rows of every
+    /// plane the segment covers
+    fn decode(
+        &self, data: &[u8], output: &mut [&mut [i16]; MAX_COMPONENTS]
+    ) -> Result<(), DecodeErrors> {
+        let mut reader = ZReader::new(ZCursor::new(data));
+        let mut stream = BitStream::new();
+        let mut dc_preds = [0; MAX_COMPONENTS];
+        let mut tmp = [0_i32; DCT_BLOCK];
+
+        let rows = self
+            .row_lengths
+            .iter()
+            .zip(output.iter())
+            .find(|(len, _)| **len != 0)
+            .map_or(0, |(len, output)| output.len() / len);
+
+        for row in 0..rows {
+            for j in 0..self.mcu_width {
+                for (pos, component) in self.components.iter().enumerate() {
+                    let dc_table = self.dc_tables[component.dc_huff_table % MAX_COMPONENTS]
+                        .as_ref()
+                        .unwrap();
+                    let ac_table = self.ac_tables[component.ac_huff_table % MAX_COMPONENTS]
+                        .as_ref()
+                        .unwrap();
+
+                    for v_samp in 0..component.vertical_sample {
+                        for h_samp in 0..component.horizontal_sample {
+                            tmp.fill(0);
+
+                            stream.decode_mcu_block(
+                                &mut reader,
+                                dc_table,
+                                ac_table,
+                                &component.quantization_table,
+                                &mut tmp,
+                                &mut dc_preds[pos]
+                            )?;
+
+                            if component.needed {
+                                // same position as the serial decoder, a row further down
+                                let c2 = v_samp * 8;
+                                let c3 = ((j * component.horizontal_sample) + h_samp) * 8;
+
+                                let idct_position =
+                                    row * self.row_lengths[pos] + component.width_stride * c2 + c3;
+
+                                (self.idct)(
+                                    &mut tmp,
+                                    &mut output[pos][idct_position..],
+                                    component.width_stride
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
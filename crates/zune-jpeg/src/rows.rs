@@ -0,0 +1,203 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Decoding a few rows at a time
+//!
+//! Baseline images are decoded one row of MCUs at a time anyway, [`decode_rows`](JpegDecoder::decode_rows)
+//! keeps the pixels of the current MCU row and hands them out as rows are asked for, so
+//! memory use depends on the width of the image, not its height.
+//!
+//! Progressive images need every scan before any pixel is known, so they can't be decoded
+//! this way.
+use alloc::vec;
+use alloc::vec::Vec;
+
+use zune_core::bytestream::ZByteReaderTrait;
+use zune_core::log::error;
+
+use crate::bitstream::BitStream;
+use crate::errors::DecodeErrors;
+use crate::mcu::DCT_BLOCK;
+use crate::misc::calculate_padded_width;
+use crate::JpegDecoder;
+
+/// Where row decoding is
+pub(crate) struct RowState {
+    stream:            BitStream,
+    tmp:               [i32; DCT_BLOCK],
+    mcu_width:         usize,
+    mcu_height:        usize,
+    width:             usize,
+    padded_width:      usize,
+    upsampler_scratch: Vec<i16>,
+    /// The next row of MCUs to decode
+    mcu_row:           usize,
+    /// Pixels of the last row of MCUs, how many rows they are and how many were handed out
+    band:              Vec<u8>,
+    band_rows:         usize,
+    band_read:         usize,
+    /// Rows handed out
+    rows:              usize,
+    /// True once no more MCUs will be decoded, rows after that are zero
+    finished:          bool
+}
+
+impl<T: ZByteReaderTrait> JpegDecoder<T> {
+    /// Decode the headers and prepare for [`decode_rows`](Self::decode_rows)
+    ///
+    /// # Errors
+    /// The headers are corrupt, or the image is progressive
+    pub fn start_rows(&mut self) -> Result<(), DecodeErrors> {
+        if self.rows.is_some() {
+            return Ok(());
+        }
+        self.decode_headers_internal()?;
+
+        if self.is_progressive {
+            return Err(DecodeErrors::FormatStatic(
+                "Progressive images can't be decoded row by row"
+            ));
+        }
+        let (mcu_width, mcu_height) = self.prepare_baseline()?;
+
+        let width = usize::from(self.info.width);
+        let row_size = self.output_row_size();
+
+        let is_hv = usize::from(self.is_interleaved);
+        let upsampler_scratch_size = is_hv * self.components[0].width_stride;
+
+        // an MCU row gives up to 8 rows per vertical sample, plus the last row of the
+        // previous one, which couldn't be up-sampled before
+        let band_rows = 9 * self.coeff * self.v_max;
+
+        self.out_stride = row_size;
+        self.rows = Some(RowState {
+            stream: BitStream::new(),
+            tmp: [0; DCT_BLOCK],
+            mcu_width,
+            mcu_height,
+            width,
+            padded_width: calculate_padded_width(width, self.sub_sample_ratio),
+            upsampler_scratch: vec![0; upsampler_scratch_size],
+            mcu_row: 0,
+            band: vec![0; band_rows * row_size],
+            band_rows: 0,
+            band_read: 0,
+            rows: 0,
+            finished: false
+        });
+        Ok(())
+    }
+
+    /// Return the number of bytes of a decoded row, or `None` if headers weren't decoded
+    pub fn row_bytes(&self) -> Option<usize> {
+        self.headers_decoded.then(|| self.output_row_size())
+    }
+
+    /// Decode the next rows of the image into `out`
+    ///
+    /// As many whole rows as fit in `out`, [`row_bytes`](Self::row_bytes) each, are decoded,
+    /// in the [output colorspace](Self::output_colorspace). Returns how many rows were decoded,
+    /// which is zero once every row was.
+    ///
+    /// Calls [`start_rows`](Self::start_rows) if it wasn't called. Only baseline images can be
+    /// decoded by rows, decode progressive ones with [`decode_into`](Self::decode_into).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use zune_core::bytestream::ZCursor;
+    /// use zune_jpeg::JpegDecoder;
+    ///
+    /// let file = std::fs::read("image.jpg").unwrap();
+    /// let mut decoder = JpegDecoder::new(ZCursor::new(file));
+    ///
+    /// decoder.start_rows().unwrap();
+    /// let mut rows = vec![0; decoder.row_bytes().unwrap() * 16];
+    ///
+    /// while decoder.decode_rows(&mut rows).unwrap() > 0 {
+    ///     // send the rows on
+    /// }
+    /// ```
+    pub fn decode_rows(&mut self, out: &mut [u8]) -> Result<usize, DecodeErrors> {
+        self.start_rows()?;
+
+        let mut state = self.rows.take().unwrap();
+        let result = self.decode_rows_inner(&mut state, out);
+        self.rows = Some(state);
+
+        result
+    }
+
+    fn decode_rows_inner(
+        &mut self, state: &mut RowState, out: &mut [u8]
+    ) -> Result<usize, DecodeErrors> {
+        let row_size = self.output_row_size();
+        let height = usize::from(self.info.height);
+
+        if row_size == 0 {
+            return Ok(0);
+        }
+        let mut decoded = 0;
+
+        for out_row in out.chunks_exact_mut(row_size) {
+            if state.rows == height {
+                break;
+            }
+            while state.band_read == state.band_rows && !state.finished {
+                self.decode_mcu_row(state)?;
+            }
+            if state.band_read < state.band_rows {
+                let start = state.band_read * row_size;
+                out_row.copy_from_slice(&state.band[start..start + row_size]);
+                state.band_read += 1;
+            } else {
+                // the image data ended early
+                out_row.fill(0);
+            }
+            state.rows += 1;
+            decoded += 1;
+        }
+        Ok(decoded)
+    }
+
+    /// Decode the next row of MCUs into the band
+    fn decode_mcu_row(&mut self, state: &mut RowState) -> Result<(), DecodeErrors> {
+        if state.mcu_row == state.mcu_height {
+            state.finished = true;
+            return Ok(());
+        }
+        // see decode_mcu_ycbcr_baseline
+        if state.stream.overread_by > 37 {
+            if self.options.strict_mode() {
+                return Err(DecodeErrors::FormatStatic("Premature end of buffer"));
+            }
+            error!("Premature end of buffer");
+            state.finished = true;
+            return Ok(());
+        }
+        let terminate =
+            self.decode_mcu_width(state.mcu_width, &mut state.tmp, &mut state.stream)?;
+
+        let mut written = 0;
+        self.post_process(
+            &mut state.band,
+            state.mcu_row,
+            state.mcu_height,
+            state.width,
+            state.padded_width,
+            &mut written,
+            &mut state.upsampler_scratch
+        )?;
+        state.band_rows = written / self.out_stride;
+        state.band_read = 0;
+        state.mcu_row += 1;
+        state.finished = terminate;
+
+        Ok(())
+    }
+}
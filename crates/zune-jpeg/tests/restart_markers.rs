@@ -0,0 +1,94 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Images with restart markers decode the same on one thread and on many
+
+use jpeg_encoder::{ColorType, Encoder, SamplingFactor};
+use zune_core::bytestream::ZCursor;
+use zune_core::colorspace::ColorSpace;
+use zune_core::options::DecoderOptions;
+use zune_jpeg::JpegDecoder;
+
+fn encode(
+    width: u16, height: u16, color: ColorType, sampling: SamplingFactor, restart_interval: u16
+) -> Vec<u8> {
+    let channels = if color == ColorType::Luma { 1 } else { 3 };
+
+    let pixels: Vec<u8> = (0..usize::from(width) * usize::from(height) * channels)
+        .map(|x| ((x * 7) ^ (x / 97)) as u8)
+        .collect();
+
+    let mut out = vec![];
+    let mut encoder = Encoder::new(&mut out, 90);
+    encoder.set_sampling_factor(sampling);
+    encoder.set_restart_interval(restart_interval);
+    encoder.encode(&pixels, width, height, color).unwrap();
+
+    out
+}
+
+fn decode(data: &[u8], threads: usize, colorspace: ColorSpace) -> Vec<u8> {
+    let options = DecoderOptions::default()
+        .set_max_threads(threads)
+        .jpeg_set_out_colorspace(colorspace);
+
+    JpegDecoder::new_with_options(ZCursor::new(data), options)
+        .decode()
+        .unwrap()
+}
+
+fn test_threads_match(data: &[u8], colorspace: ColorSpace) {
+    let serial = decode(data, 1, colorspace);
+
+    for threads in [2, 3, 8] {
+        assert!(
+            serial == decode(data, threads, colorspace),
+            "{threads} threads"
+        );
+    }
+}
+
+#[test]
+fn test_restarts_every_mcu_row() {
+    // 21 MCU's per row for 2x2 sampling, 42 for 1x1
+    for (sampling, mcu_width) in [
+        (SamplingFactor::F_2_2, 21),
+        (SamplingFactor::F_2_1, 21),
+        (SamplingFactor::F_1_1, 42)
+    ] {
+        let data = encode(333, 201, ColorType::Rgb, sampling, mcu_width);
+
+        test_threads_match(&data, ColorSpace::RGB);
+        test_threads_match(&data, ColorSpace::YCbCr);
+        test_threads_match(&data, ColorSpace::Luma);
+    }
+}
+
+#[test]
+fn test_restarts_every_few_mcu_rows() {
+    let data = encode(333, 201, ColorType::Rgb, SamplingFactor::F_2_2, 21 * 3);
+    test_threads_match(&data, ColorSpace::RGB);
+
+    let data = encode(333, 201, ColorType::Luma, SamplingFactor::F_1_1, 42 * 2);
+    test_threads_match(&data, ColorSpace::Luma);
+}
+
+#[test]
+fn test_restarts_inside_mcu_rows() {
+    // can't be decoded in parallel, goes back to the serial decoder
+    let data = encode(333, 201, ColorType::Rgb, SamplingFactor::F_2_2, 5);
+    test_threads_match(&data, ColorSpace::RGB);
+}
+
+#[test]
+fn test_restarts_mjpeg() {
+    let path = env!("CARGO_MANIFEST_DIR").to_string() + "/../../test-images/jpeg/mjpeg_huffman.jpg";
+    let data = std::fs::read(path).unwrap();
+
+    test_threads_match(&data, ColorSpace::RGB);
+}
@@ -0,0 +1,98 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Decoding a few rows at a time gives the same pixels as decoding whole images
+
+use jpeg_encoder::{ColorType, Encoder, SamplingFactor};
+use zune_core::bytestream::ZCursor;
+use zune_core::colorspace::ColorSpace;
+use zune_core::options::DecoderOptions;
+use zune_jpeg::JpegDecoder;
+
+fn encode(
+    width: u16, height: u16, color: ColorType, sampling: SamplingFactor, progressive: bool,
+    restart_interval: u16
+) -> Vec<u8> {
+    let components = if color == ColorType::Luma { 1 } else { 3 };
+    let pixels: Vec<u8> = (0..usize::from(width) * usize::from(height) * components)
+        .map(|x| ((x * 7) ^ (x / 97)) as u8)
+        .collect();
+
+    let mut out = vec![];
+    let mut encoder = Encoder::new(&mut out, 90);
+    encoder.set_sampling_factor(sampling);
+    encoder.set_progressive(progressive);
+    encoder.set_restart_interval(restart_interval);
+    encoder.encode(&pixels, width, height, color).unwrap();
+
+    out
+}
+
+fn decoder(data: &[u8], colorspace: ColorSpace) -> JpegDecoder<ZCursor<&[u8]>> {
+    let options = DecoderOptions::default().jpeg_set_out_colorspace(colorspace);
+    JpegDecoder::new_with_options(ZCursor::new(data), options)
+}
+
+fn decode_by_rows(data: &[u8], colorspace: ColorSpace, rows: usize) -> Vec<u8> {
+    let mut decoder = decoder(data, colorspace);
+    decoder.start_rows().unwrap();
+
+    let row_bytes = decoder.row_bytes().unwrap();
+    let mut buffer = vec![0; row_bytes * rows];
+    let mut pixels = vec![];
+
+    loop {
+        let decoded = decoder.decode_rows(&mut buffer).unwrap();
+        if decoded == 0 {
+            break;
+        }
+        pixels.extend_from_slice(&buffer[..decoded * row_bytes]);
+    }
+    pixels
+}
+
+#[test]
+fn test_rows_match_whole_image() {
+    let samplings = [
+        SamplingFactor::F_1_1,
+        SamplingFactor::F_2_1,
+        SamplingFactor::F_1_2,
+        SamplingFactor::F_2_2
+    ];
+    for sampling in samplings {
+        for restart_interval in [0, 3] {
+            let data = encode(101, 37, ColorType::Rgb, sampling, false, restart_interval);
+
+            for colorspace in [ColorSpace::RGB, ColorSpace::BGRA, ColorSpace::Luma] {
+                let expected = decoder(&data, colorspace).decode().unwrap();
+
+                for rows in [1, 5, 64] {
+                    assert!(
+                        decode_by_rows(&data, colorspace, rows) == expected,
+                        "{sampling:?} {colorspace:?}, {rows} rows at a time"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_rows_grayscale() {
+    let data = encode(77, 50, ColorType::Luma, SamplingFactor::F_1_1, false, 0);
+    let expected = decoder(&data, ColorSpace::Luma).decode().unwrap();
+
+    assert!(decode_by_rows(&data, ColorSpace::Luma, 3) == expected);
+}
+
+#[test]
+fn test_rows_progressive() {
+    let data = encode(64, 64, ColorType::Rgb, SamplingFactor::F_2_2, true, 0);
+
+    assert!(decoder(&data, ColorSpace::RGB).start_rows().is_err());
+}
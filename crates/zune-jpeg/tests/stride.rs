@@ -0,0 +1,79 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Decoding into buffers with padded rows writes the same pixels as packed buffers
+
+use jpeg_encoder::{ColorType, Encoder, SamplingFactor};
+use zune_core::bytestream::ZCursor;
+use zune_core::colorspace::ColorSpace;
+use zune_core::options::DecoderOptions;
+use zune_jpeg::errors::DecodeErrors;
+use zune_jpeg::JpegDecoder;
+
+fn encode(width: u16, height: u16, sampling: SamplingFactor, progressive: bool) -> Vec<u8> {
+    let pixels: Vec<u8> = (0..usize::from(width) * usize::from(height) * 3)
+        .map(|x| ((x * 7) ^ (x / 97)) as u8)
+        .collect();
+
+    let mut out = vec![];
+    let mut encoder = Encoder::new(&mut out, 90);
+    encoder.set_sampling_factor(sampling);
+    encoder.set_progressive(progressive);
+    encoder
+        .encode(&pixels, width, height, ColorType::Rgb)
+        .unwrap();
+
+    out
+}
+
+fn decoder(data: &[u8], colorspace: ColorSpace) -> JpegDecoder<ZCursor<&[u8]>> {
+    let options = DecoderOptions::default().jpeg_set_out_colorspace(colorspace);
+    JpegDecoder::new_with_options(ZCursor::new(data), options)
+}
+
+#[test]
+fn test_stride_matches_packed() {
+    for sampling in [SamplingFactor::F_1_1, SamplingFactor::F_2_2] {
+        for progressive in [false, true] {
+            let data = encode(101, 37, sampling, progressive);
+
+            for colorspace in [ColorSpace::BGRA, ColorSpace::RGB, ColorSpace::Luma] {
+                let packed = decoder(&data, colorspace).decode().unwrap();
+                let row_size = 101 * colorspace.num_components();
+                let stride = row_size + 13;
+
+                // no padding after the last row
+                let mut out = vec![0xAB; stride * 36 + row_size];
+                decoder(&data, colorspace)
+                    .decode_into_with_stride(&mut out, stride)
+                    .unwrap();
+
+                for (row, expected) in out.chunks(stride).zip(packed.chunks_exact(row_size)) {
+                    assert_eq!(&row[..row_size], expected, "{colorspace:?}");
+                    assert!(row[row_size..].iter().all(|x| *x == 0xAB));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_stride_too_small() {
+    let data = encode(16, 16, SamplingFactor::F_1_1, false);
+
+    let mut out = vec![0; 64 * 16];
+    assert!(decoder(&data, ColorSpace::RGBA)
+        .decode_into_with_stride(&mut out, 63)
+        .is_err());
+
+    let mut out = vec![0; 64 * 15 + 63];
+    assert!(matches!(
+        decoder(&data, ColorSpace::RGBA).decode_into_with_stride(&mut out, 64),
+        Err(DecodeErrors::TooSmallOutput(1024, 1023))
+    ));
+}
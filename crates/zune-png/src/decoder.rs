@@ -6,11 +6,10 @@
 
 use alloc::vec::Vec;
 use alloc::{format, vec};
-use core::cmp::min;
 
 use zune_core::bit_depth::{BitDepth, ByteEndian};
 use zune_core::bytestream::{ZByteReaderTrait, ZReader};
-use zune_core::colorspace::ColorSpace;
+use zune_core::colorspace::{ColorCharacteristics, ColorSpace};
 use zune_core::log::{trace, warn};
 use zune_core::options::DecoderOptions;
 use zune_core::result::DecodingResult;
@@ -21,13 +20,11 @@ use crate::constants::PNG_SIGNATURE;
 use crate::enums::{FilterMethod, InterlaceMethod, PngChunkType, PngColor};
 use crate::error::PngDecodeErrors;
 use crate::error::PngDecodeErrors::GenericStatic;
-use crate::filters::de_filter::{
-    handle_avg, handle_avg_first, handle_paeth, handle_paeth_first, handle_sub, handle_up
-};
 use crate::options::default_chunk_handler;
+use crate::rows::{Defilter, RowPostProcessor};
+use crate::streaming::RowState;
 use crate::utils::{
-    add_alpha, convert_be_to_target_endian_u16, convert_u16_to_u8_slice, expand_bits_to_byte,
-    expand_palette, expand_trns, is_le
+    convert_be_to_target_endian_u16, convert_u16_to_u8_slice, is_le
 };
 
 /// A palette entry.
@@ -76,6 +73,20 @@ pub struct TimeInfo {
     pub second: u8
 }
 
+/// Physical pixel dimensions
+///
+/// Extracted from pHYs chunk
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct PhysicalDimensions {
+    /// Pixels per unit in the horizontal direction
+    pub x:             u32,
+    /// Pixels per unit in the vertical direction
+    pub y:             u32,
+    /// Whether the unit is the meter, otherwise the values only
+    /// give the aspect ratio of the pixels
+    pub unit_is_meter: bool
+}
+
 /// iTXt details
 ///
 /// UTF-8 encoded text
@@ -118,10 +129,17 @@ pub struct PngInfo {
     pub height:               usize,
     /// Image gamma
     pub gamma:                Option<f32>,
+    /// Coding independent code points from the cICP chunk
+    ///
+    /// These are the ITU-T H.273 color primaries, transfer characteristics,
+    /// matrix coefficients and the full range flag, in that order
+    pub cicp:                 Option<[u8; 4]>,
     /// Image interlace method
     pub interlace_method:     InterlaceMethod,
     /// Image time info
     pub time_info:            Option<TimeInfo>,
+    /// Physical dimensions of a pixel
+    pub physical_dimensions:  Option<PhysicalDimensions>,
     /// Image exif data
     pub exif:                 Option<Vec<u8>>,
     /// Icc profile
@@ -165,6 +183,7 @@ where
     pub(crate) options:                 DecoderOptions,
     pub(crate) png_info:                PngInfo,
     pub(crate) palette:                 Vec<PLTEEntry>,
+    pub(crate) palette_len:             usize,
     pub(crate) frames:                  Vec<SingleFrame>,
     pub(crate) actl_info:               Option<ActlChunk>,
     pub(crate) previous_stride:         Vec<u8>,
@@ -175,7 +194,23 @@ where
     pub(crate) seen_trns:               bool,
     pub(crate) seen_iend:               bool,
     pub(crate) current_frame:           usize,
-    pub(crate) called_from_decode_into: bool
+    pub(crate) called_from_decode_into: bool,
+    /// Frames of animated images composed so far, see `decode_composed_frame`
+    #[cfg(feature = "std")]
+    pub(crate) composed:                Vec<u8>,
+    /// Set by `start_rows`, for decoding a few rows at a time
+    pub(crate) rows:                    Option<RowState>
+}
+
+impl PngInfo {
+    /// Return the XMP packet of the image, stored in an iTXt chunk
+    /// with the `XML:com.adobe.xmp` keyword
+    pub fn xmp(&self) -> Option<&[u8]> {
+        self.itxt_chunk
+            .iter()
+            .find(|x| x.keyword == b"XML:com.adobe.xmp")
+            .map(|x| x.text.as_slice())
+    }
 }
 
 impl<T: ZByteReaderTrait> PngDecoder<T> {
@@ -210,6 +245,7 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
             stream:                  ZReader::new(data),
             options:                 options,
             palette:                 Vec::new(),
+            palette_len:             0,
             png_info:                PngInfo::default(),
             actl_info:               None,
             previous_stride:         vec![],
@@ -220,7 +256,10 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
             seen_iend:               false,
             trns_bytes:              [0; 4],
             current_frame:           0,
-            called_from_decode_into: true
+            called_from_decode_into: true,
+            #[cfg(feature = "std")]
+            composed:                vec![],
+            rows:                    None
         }
     }
 
@@ -274,6 +313,9 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
         if !self.seen_hdr {
             return None;
         }
+        if self.keeps_indices() {
+            return Some(ColorSpace::Luma);
+        }
         if self.options.png_get_add_alpha_channel() {
             return match self.png_info.color {
                 PngColor::Luma | PngColor::LumaA => Some(ColorSpace::LumaA),
@@ -303,6 +345,43 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
             }
         }
     }
+    /// Return true if the image is paletted and decodes to palette indices
+    const fn keeps_indices(&self) -> bool {
+        self.options.png_get_keep_indices() && matches!(self.png_info.color, PngColor::Palette)
+    }
+    /// Return the transfer characteristics of the image from its cICP chunk
+    ///
+    /// This is how HDR PNG images signal PQ or HLG encoded pixels
+    ///
+    /// # Returns
+    ///  - `Some(characteristics)`: The transfer characteristics of the image
+    ///  - `None`: If the headers haven't been decoded, there is no cICP chunk or it
+    ///    specifies unsupported transfer characteristics
+    pub fn color_trc(&self) -> Option<ColorCharacteristics> {
+        self.png_info
+            .cicp
+            .and_then(|cicp| ColorCharacteristics::from_h273(cicp[1]))
+    }
+    /// Return the palette of a paletted image
+    ///
+    /// Entries are in RGBA order, the alpha comes from the tRNS chunk and is 255 for
+    /// entries without one. Decoding expands indices to the colors of the palette unless
+    /// [`DecoderOptions::png_set_keep_indices`] is set.
+    ///
+    /// # Returns
+    ///  - `Some(palette)`: The palette entries, as many as the pLTE chunk contains
+    ///  - `None`: If the image headers haven't been decoded or the image isn't paletted
+    pub fn palette(&self) -> Option<Vec<[u8; 4]>> {
+        if !self.seen_ptle || self.png_info.color != PngColor::Palette {
+            return None;
+        }
+        let entries = self.palette[..self.palette_len]
+            .iter()
+            .map(|x| [x.red, x.green, x.blue, x.alpha])
+            .collect();
+
+        Some(entries)
+    }
     /// Returns true if the image is animated
     ///
     /// # Note
@@ -318,6 +397,17 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
         self.actl_info.is_some() && self.frames.len() > self.current_frame
     }
 
+    /// Return the number of times an animated image should play,
+    /// zero means the animation repeats forever
+    ///
+    /// Returns `None` if the image isn't animated or headers haven't been decoded
+    pub const fn num_plays(&self) -> Option<u32> {
+        match self.actl_info {
+            Some(info) => Some(info.num_plays),
+            None => None
+        }
+    }
+
     /// Return true if image has more frames available
     pub fn more_frames(&self) -> bool {
         self.actl_info.is_some() && self.frames.len() > self.current_frame
@@ -353,6 +443,7 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
             b"zTXt" => PngChunkType::zTXt,
             b"tEXt" => PngChunkType::tEXt,
             b"fdAT" => PngChunkType::fdAT,
+            b"cICP" => PngChunkType::cICP,
             _ => PngChunkType::unkn
         };
 
@@ -393,20 +484,13 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
         if self.seen_headers && self.seen_iend {
             return Ok(());
         }
-        if !self.seen_hdr {
-            // READ PNG signature
-            let signature = self.stream.get_u64_be_err()?;
-
-            if signature != PNG_SIGNATURE {
-                return Err(PngDecodeErrors::BadSignature);
-            }
-            // check if first chunk is ihdr here
-            if self.stream.peek_at(4, 4)? != b"IHDR" {
-                return Err(PngDecodeErrors::GenericStatic(
-                    "First chunk not IHDR, Corrupt PNG"
-                ));
-            }
+        if self.rows.is_some() {
+            return Err(PngDecodeErrors::GenericStatic(
+                "Image is being decoded by rows, finish decoding them first"
+            ));
         }
+        self.read_signature()?;
+
         loop {
             let header = self.read_chunk_header()?;
 
@@ -421,10 +505,55 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
                 break;
             }
         }
+        if !self.seen_headers {
+            // animated images are decoded a frame at a time, so check all of them up front
+            let frames = match self.actl_info {
+                Some(actl) if self.options.png_decode_animated() => actl.num_frames as usize,
+                _ => 1
+            };
+            self.check_limits(frames)?;
+        }
         self.seen_headers = true;
         Ok(())
     }
 
+    /// Check `frames` frames of the decoded image against the pixel, frame and memory
+    /// limits of the options
+    fn check_limits(&self, frames: usize) -> Result<(), PngDecodeErrors> {
+        let info = &self.png_info;
+        let bytes = if info.depth == 16 && !self.options.png_get_strip_to_8bit() { 2 } else { 1 };
+        let components = self.colorspace().map_or(0, |c| c.num_components());
+
+        if let Some((limit, found, max)) =
+            self.options
+                .exceeded_limit(info.width, info.height, components * bytes, frames)
+        {
+            return Err(PngDecodeErrors::Generic(format!(
+                "Image {limit} {found}, larger than maximum configured {limit} {max}, aborting"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Read the PNG signature, if the header wasn't read yet
+    pub(crate) fn read_signature(&mut self) -> Result<(), PngDecodeErrors> {
+        if self.seen_hdr {
+            return Ok(());
+        }
+        let signature = self.stream.get_u64_be_err()?;
+
+        if signature != PNG_SIGNATURE {
+            return Err(PngDecodeErrors::BadSignature);
+        }
+        // check if first chunk is ihdr here
+        if self.stream.peek_at(4, 4)? != b"IHDR" {
+            return Err(PngDecodeErrors::GenericStatic(
+                "First chunk not IHDR, Corrupt PNG"
+            ));
+        }
+        Ok(())
+    }
+
     pub(crate) fn parse_header(&mut self, header: PngChunk) -> Result<(), PngDecodeErrors> {
         match header.chunk_type {
             PngChunkType::IHDR => {
@@ -442,12 +571,18 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
             PngChunkType::gAMA => {
                 self.parse_gama(header)?;
             }
+            PngChunkType::cICP => {
+                self.parse_cicp(header)?;
+            }
             PngChunkType::acTL => {
                 self.parse_actl(header)?;
             }
             PngChunkType::tIME => {
                 self.parse_time(header)?;
             }
+            PngChunkType::pHYs => {
+                self.parse_phys(header)?;
+            }
             PngChunkType::eXIf => {
                 self.parse_exif(header)?;
             }
@@ -641,6 +776,20 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
 
         let out = &mut out[..image_len];
 
+        #[cfg(feature = "threads")]
+        if png_info.interlace_method == InterlaceMethod::Standard {
+            let dims = self.frame_info().unwrap();
+
+            if self.should_pipeline(dims.width, dims.height, &png_info) {
+                // also converts to the set endian
+                self.create_png_image_pipelined(dims.width, dims.height, out, &png_info)?;
+
+                self.frames[self.current_frame].fdat = vec![];
+                self.current_frame += 1;
+                return Ok(());
+            }
+        }
+
         // go parse IDAT chunks returning the inflate
         let deflate_data = self.inflate()?;
 
@@ -749,6 +898,68 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
         None
     }
 
+    /// Decode the next frame of an animated image and compose it over the frames before it
+    ///
+    /// Returns the information of the frame and the fully rendered image, which has the
+    /// dimensions of the whole image, or `None` after the last frame.
+    ///
+    /// Only the rendered image is kept between calls, so long animations can be processed
+    /// one frame at a time. Images with 16 bits per sample are not supported
+    ///
+    /// # Example
+    /// ```no_run
+    /// use zune_core::bytestream::ZCursor;
+    /// use zune_png::PngDecoder;
+    /// let mut decoder = PngDecoder::new(ZCursor::new(&[]));
+    ///
+    /// while let Some((info, pixels)) = decoder.decode_composed_frame().unwrap() {
+    ///     // pixels contains the whole image as it should be shown for this frame
+    /// }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn decode_composed_frame(
+        &mut self
+    ) -> Result<Option<(FrameInfo, &[u8])>, PngDecodeErrors> {
+        self.decode_headers()?;
+
+        if !self.more_frames() {
+            return Ok(None);
+        }
+        // the animation control chunk may have fewer frames than the image
+        self.check_limits(self.current_frame + 1)?;
+
+        let colorspace = self.colorspace().unwrap();
+        let mut frame = self
+            .frame_info()
+            .ok_or(GenericStatic("Missing frame control chunk for frame"))?;
+
+        if frame.dispose_op == crate::apng::DisposeOp::Previous {
+            // we don't clear the composed image, so it always contains the previous frame
+            frame.dispose_op = crate::apng::DisposeOp::None;
+        }
+        let pixels = match self.decode()? {
+            DecodingResult::U8(pixels) => pixels,
+            _ => {
+                return Err(GenericStatic(
+                    "Composing frames of animated images with a depth of 16 is not supported"
+                ))
+            }
+        };
+        let length = self.png_info.width * self.png_info.height * colorspace.num_components();
+        self.composed.resize(length, 0);
+
+        crate::apng::post_process_image(
+            &self.png_info,
+            colorspace,
+            &frame,
+            &pixels,
+            None,
+            &mut self.composed,
+            None
+        )?;
+        Ok(Some((frame, &self.composed)))
+    }
+
     fn decode_interlaced(
         &mut self, deflate_data: &[u8], out: &mut [u8], info: &PngInfo, frame_info: &FrameInfo
     ) -> Result<(), PngDecodeErrors> {
@@ -927,27 +1138,12 @@ e                           .copy_from_slice(&final_out[out_start..out_start + o
     /// This is to allow reuse e.g interlaced images use one big allocation
     /// to and since that ends up calling this multiple times, allocation was moved
     /// away from this method to the caller of this method
-    #[allow(clippy::manual_memcpy, clippy::comparison_chain)]
     fn create_png_image_raw(
         &mut self, deflate_data: &[u8], width: usize, height: usize, out: &mut [u8], info: &PngInfo
     ) -> Result<(), PngDecodeErrors> {
-        let use_sse4 = self.options.use_sse41();
-        let use_sse2 = self.options.use_sse2();
-
-        let bytes = if info.depth == 16 { 2 } else { 1 };
+        let defilter = self.defilter(width, info);
 
-        let out_colorspace = self.colorspace().unwrap();
-
-        let mut img_width_bytes;
-
-        img_width_bytes = usize::from(info.component) * width;
-        img_width_bytes *= usize::from(info.depth);
-        img_width_bytes += 7;
-        img_width_bytes /= 8;
-
-        let out_n = usize::from(info.color.num_components());
-
-        let image_len = img_width_bytes * height;
+        let image_len = (defilter.in_stride - 1) * height;
 
         if deflate_data.len() < image_len + height
         // account for filter bytes
@@ -959,315 +1155,81 @@ e                           .copy_from_slice(&final_out[out_start..out_start + o
             );
             return Err(PngDecodeErrors::Generic(msg));
         }
-        // do png  un-filtering
-        let mut chunk_size;
-        let mut components = usize::from(info.color.num_components()) * bytes;
-
-        if info.depth < 8 {
-            // if the bit depth is 8, the spec says the byte before
-            // X to be used by the filter
-            components = 1;
-        }
+        let out_stride = defilter.out_stride;
 
-        // add width plus colour component, this gives us number of bytes per every scan line
-        chunk_size = width * out_n;
-        chunk_size *= usize::from(info.depth);
-        chunk_size += 7;
-        chunk_size /= 8;
-        // filter type
-        chunk_size += 1;
-
-        let out_chunk_size = width * out_colorspace.num_components() * bytes;
+        let scratch = core::mem::take(&mut self.previous_stride);
+        let mut post_processor = self.row_post_processor(&defilter, width, info, scratch)?;
 
         // each chunk is a width stride of unfiltered data
-        let chunks = deflate_data.chunks_exact(chunk_size);
+        let chunks = deflate_data.chunks_exact(defilter.in_stride);
 
         // Begin doing loop un-filtering.
-        let width_stride = chunk_size - 1;
-
-        let mut prev_row_start = 0;
-        let mut first_row = true;
-        let mut out_position = 0;
-
-        let mut will_post_process = self.seen_trns | self.seen_ptle | (info.depth < 8);
-
-        let add_alpha_channel =
-            self.options.png_get_add_alpha_channel() && (!self.png_info.color.has_alpha());
-
-        will_post_process |= add_alpha_channel;
-
-        if will_post_process && self.previous_stride.len() < out_chunk_size {
-            self.previous_stride.resize(out_chunk_size, 0);
-        }
-        let n_components = usize::from(info.color.num_components());
-
         for (i, in_stride) in chunks.take(height).enumerate() {
             // Split output into current and previous
             // current points to the start of the row where we are writing de-filtered output to
             // prev is all rows we already wrote output to.
+            let (prev, current) = out.split_at_mut(i * out_stride);
 
-            let (prev, mut current) = out.split_at_mut(out_position);
-
-            current = &mut current[0..out_chunk_size];
+            let prev_row = i.checked_sub(1).map(|row| &prev[row * out_stride..]);
 
-            // get the previous row.
-            //Set this to a dummy to handle special case of first row, if we aren't in the first
-            // row, we actually take the real slice a line down
-            let mut prev_row: &[u8] = &[0_u8];
+            defilter.row(in_stride, prev_row, &mut current[..out_stride])?;
 
-            if !first_row {
-                // normal bit depth, use the previous row as normal
-                prev_row = &prev[prev_row_start..prev_row_start + out_chunk_size];
-                prev_row_start += out_chunk_size;
-            }
-
-            out_position += out_chunk_size;
-
-            // take filter
-            let filter_byte = in_stride[0];
-            // raw image bytes
-            let raw = &in_stride[1..];
-
-            // get its type
-            let mut filter = FilterMethod::from_int(filter_byte)
-                .ok_or_else(|| PngDecodeErrors::Generic(format!("Unknown filter {filter_byte}")))?;
-
-            if first_row {
-                // match our filters to special filters for first row
-                // these special filters do not need the previous scanline and treat it
-                // as zero
-
-                if filter == FilterMethod::Paeth {
-                    filter = FilterMethod::PaethFirst;
-                }
-                if filter == FilterMethod::Up {
-                    // up for the first row becomes a memcpy
-                    filter = FilterMethod::None;
-                }
-                if filter == FilterMethod::Average {
-                    filter = FilterMethod::AvgFirst;
-                }
-
-                first_row = false;
-            }
-
-            match filter {
-                FilterMethod::None => current[0..width_stride].copy_from_slice(raw),
-
-                FilterMethod::Average => handle_avg(prev_row, raw, current, components, use_sse4),
-
-                FilterMethod::Sub => handle_sub(raw, current, components, use_sse2),
-
-                FilterMethod::Up => handle_up(prev_row, raw, current),
-
-                FilterMethod::Paeth => handle_paeth(prev_row, raw, current, components, use_sse4),
-
-                FilterMethod::PaethFirst => handle_paeth_first(raw, current, components),
-
-                FilterMethod::AvgFirst => handle_avg_first(raw, current, components),
-
-                FilterMethod::Unknown => unreachable!()
-            }
-
-            if will_post_process && i > 0 {
-                // run the post processor two scanlines behind so that we
-                // don't mess with any filters that require previous row
-
-                // read the row we are about to filter
-                let to_filter_row = &mut prev[(i - 1) * out_chunk_size..(i) * out_chunk_size];
-
-                if info.depth < 8 {
-                    // check if we will run any other transform
-                    let extra_transform = self.seen_ptle | self.seen_trns | add_alpha_channel;
-
-                    if extra_transform {
-                        // input data is  in_to_filter_row,
-                        // we write output to previous_stride
-                        // since other parts use previous_stride
-                        expand_bits_to_byte(
-                            width,
-                            usize::from(info.depth),
-                            n_components,
-                            self.seen_ptle,
-                            to_filter_row,
-                            &mut self.previous_stride
-                        )
-                    } else {
-                        // no extra transform, just depth upscaling, so let's
-                        // do that,
-
-                        // copy the row to a temporary space
-                        self.previous_stride[..width_stride]
-                            .copy_from_slice(&to_filter_row[..width_stride]);
-
-                        expand_bits_to_byte(
-                            width,
-                            usize::from(info.depth),
-                            n_components,
-                            self.seen_ptle,
-                            &self.previous_stride,
-                            to_filter_row
-                        )
-                    }
-                } else {
-                    // copy the row to a temporary space
-                    self.previous_stride[..width_stride]
-                        .copy_from_slice(&to_filter_row[..width_stride]);
-                }
-
-                if self.seen_trns && self.png_info.color != PngColor::Palette {
-                    // the expansion is a trns expansion
-                    // bytes are already in position, so finish the business
-
-                    if info.depth <= 8 {
-                        expand_trns::<false>(
-                            &self.previous_stride,
-                            to_filter_row,
-                            info.color,
-                            self.trns_bytes,
-                            info.depth
-                        );
-                    } else if info.depth == 16 {
-                        // Tested by test_palette_trns_16bit.
-                        expand_trns::<true>(
-                            &self.previous_stride,
-                            to_filter_row,
-                            info.color,
-                            self.trns_bytes,
-                            info.depth
-                        );
-                    }
-                }
-
-                if self.seen_ptle && self.png_info.color == PngColor::Palette {
-                    if self.palette.is_empty() {
-                        return Err(PngDecodeErrors::EmptyPalette);
-                    }
-                    let plte_entry: &[PLTEEntry; 256] = self.palette[..256].try_into().unwrap();
-
-                    // so now we have two things
-                    // the palette entries stored in self.previous_stride
-                    // the row to fill the palette sored in to_filter row,
-                    // so we can finally expand the entries
-
-                    if self.seen_trns | add_alpha_channel {
-                        // if tRNS chunk is present in paletted images, it contains
-                        // alpha byte values, so that means we create alpha data from
-                        // raw bytes
-
-                        // if we are to add alpha channel for palette images , we simply just
-                        // read four entries from the palette.
-                        //
-                        // The palette is set that the alpha channel is initialized as 255 for non alpha
-                        // images,
-                        expand_palette(&self.previous_stride, to_filter_row, plte_entry, 4);
-                    } else {
-                        // Normal expansion
-                        expand_palette(&self.previous_stride, to_filter_row, plte_entry, 3);
-                    }
-                } else if add_alpha_channel {
-                    // the image is a normal RGB/ Luma image, which we need to add the alpha channel
-                    // do it here
-                    add_alpha(
-                        &self.previous_stride,
-                        to_filter_row,
-                        self.png_info.color,
-                        self.depth().unwrap()
-                    );
+            if let Some(post_processor) = &mut post_processor {
+                if i > 0 {
+                    // run the post processor two scanlines behind so that we
+                    // don't mess with any filters that require previous row
+                    post_processor.process(&mut prev[(i - 1) * out_stride..]);
                 }
             }
         }
 
-        if will_post_process {
-            for i in height..height + min(height, 1) {
-                let to_filter_row = &mut out[(i - 1) * out_chunk_size..i * out_chunk_size];
-
-                // check if we will run any other transform
-                let extra_transform = self.seen_ptle | self.seen_trns;
-
-                if info.depth < 8 {
-                    if extra_transform {
-                        // input data is  in_to_filter_row,
-                        // we write output to previous_stride
-                        // since other parts use previous_stride
-                        expand_bits_to_byte(
-                            width,
-                            usize::from(info.depth),
-                            n_components,
-                            self.seen_ptle,
-                            to_filter_row,
-                            &mut self.previous_stride
-                        )
-                    } else {
-                        // no extra transform, just depth upscaling, so let's
-                        // do that,
-
-                        // copy the row to a temporary space
-                        self.previous_stride[..width_stride]
-                            .copy_from_slice(&to_filter_row[..width_stride]);
-
-                        expand_bits_to_byte(
-                            width,
-                            usize::from(info.depth),
-                            n_components,
-                            self.seen_ptle,
-                            &self.previous_stride,
-                            to_filter_row
-                        )
-                    }
-                } else {
-                    // copy the row to a temporary space
-                    self.previous_stride[..width_stride]
-                        .copy_from_slice(&to_filter_row[..width_stride]);
-                }
-                if self.seen_trns && self.png_info.color != PngColor::Palette {
-                    // the expansion is a trns expansion
-                    // bytes are already in position, so finish the business
-
-                    if info.depth <= 8 {
-                        expand_trns::<false>(
-                            &self.previous_stride,
-                            to_filter_row,
-                            info.color,
-                            self.trns_bytes,
-                            info.depth
-                        );
-                    } else if info.depth == 16 {
-                        // Tested by test_palette_trns_16bit.
-                        expand_trns::<true>(
-                            &self.previous_stride,
-                            to_filter_row,
-                            info.color,
-                            self.trns_bytes,
-                            info.depth
-                        );
-                    }
-                }
-                if self.seen_ptle && self.png_info.color == PngColor::Palette {
-                    if self.palette.is_empty() {
-                        return Err(PngDecodeErrors::EmptyPalette);
-                    }
-
-                    let plte_entry: &[PLTEEntry; 256] = self.palette[..256].try_into().unwrap();
-
-                    if self.seen_trns | add_alpha_channel {
-                        expand_palette(&self.previous_stride, to_filter_row, plte_entry, 4);
-                    } else {
-                        expand_palette(&self.previous_stride, to_filter_row, plte_entry, 3);
-                    }
-                } else if add_alpha_channel {
-                    add_alpha(
-                        &self.previous_stride,
-                        to_filter_row,
-                        self.png_info.color,
-                        self.depth().unwrap()
-                    );
-                }
+        if let Some(mut post_processor) = post_processor {
+            if height > 0 {
+                post_processor.process(&mut out[(height - 1) * out_stride..height * out_stride]);
             }
+            self.previous_stride = post_processor.into_scratch();
         }
         Ok(())
     }
 
+    /// The scanline de-filterer of a `width` wide image, or frame
+    pub(crate) fn defilter(&self, width: usize, info: &PngInfo) -> Defilter {
+        let out_components = self.colorspace().unwrap().num_components();
+
+        Defilter::new(
+            info,
+            width,
+            out_components,
+            self.options.use_sse2(),
+            self.options.use_sse41()
+        )
+    }
+
+    /// The scanline post processor of a `width` wide image, or `None`
+    /// if scanlines need no post processing
+    pub(crate) fn row_post_processor(
+        &self, defilter: &Defilter, width: usize, info: &PngInfo, scratch: Vec<u8>
+    ) -> Result<Option<RowPostProcessor<'_>>, PngDecodeErrors> {
+        let keep_indices = self.keeps_indices();
+        let add_alpha_channel = self.options.png_get_add_alpha_channel()
+            && (!self.png_info.color.has_alpha())
+            && !keep_indices;
+
+        RowPostProcessor::new(
+            info,
+            defilter,
+            width,
+            self.depth().unwrap(),
+            self.seen_ptle,
+            self.seen_trns && !keep_indices,
+            add_alpha_channel,
+            !keep_indices,
+            self.trns_bytes,
+            &self.palette,
+            scratch
+        )
+    }
+
     /// Undo deflate decoding
     #[allow(clippy::manual_memcpy)]
     fn inflate(&mut self) -> Result<Vec<u8>, PngDecodeErrors> {
@@ -1289,6 +1251,17 @@ e                           .copy_from_slice(&final_out[out_start..out_start + o
         // because it controls the allocation and doesn't have to check for near EOB
         // runs.
         //
+        let option = self.inflate_options();
+
+        let mut decoder = zune_inflate::DeflateDecoder::new_with_options(&flat_data.fdat, option);
+
+        decoder
+            .decode_zlib()
+            .map_err(PngDecodeErrors::ZlibDecodeErrors)
+    }
+
+    /// Options for inflating the IDAT chunks of the current frame
+    pub(crate) fn inflate_options(&self) -> DeflateOptions {
         let depth_scale = if self.png_info.depth == 16 { 2 } else { 1 };
 
         let size_hint = (self.png_info.width + 1)
@@ -1296,15 +1269,9 @@ e                           .copy_from_slice(&final_out[out_start..out_start + o
             * depth_scale
             * usize::from(self.png_info.color.num_components());
 
-        let option = DeflateOptions::default()
+        DeflateOptions::default()
             .set_size_hint(size_hint)
             .set_limit(size_hint + 4 * (self.png_info.height))
-            .set_confirm_checksum(self.options.inflate_get_confirm_adler());
-
-        let mut decoder = zune_inflate::DeflateDecoder::new_with_options(&flat_data.fdat, option);
-
-        decoder
-            .decode_zlib()
-            .map_err(PngDecodeErrors::ZlibDecodeErrors)
+            .set_confirm_checksum(self.options.inflate_get_confirm_adler())
     }
 }
@@ -5,19 +5,25 @@
  */
 
 use alloc::vec::Vec;
+use core::ops::Range;
 
 use zune_core::bytestream::{ZByteIoError, ZByteWriterTrait, ZWriter};
 use zune_core::options::EncoderOptions;
 use zune_inflate::DeflateEncoder;
 
 use crate::constants::PNG_SIGNATURE;
-use crate::decoder::PngChunk;
+use crate::decoder::{PhysicalDimensions, PngChunk};
 use crate::enums::{FilterMethod, PngChunkType};
 use crate::filters::{choose_compression_filter, filter_scanline};
 use crate::headers::writers::{
-    write_chunk, write_exif, write_gamma, write_header_fn, write_iend, write_ihdr
+    write_chunk, write_exif, write_gamma, write_header_fn, write_iccp, write_iend, write_ihdr,
+    write_phys, write_plte, write_trns, write_xmp
 };
 
+/// Images with less filtered bytes than this aren't worth starting threads for
+#[cfg(feature = "threads")]
+const PARALLEL_MIN_BYTES: usize = 1 << 20;
+
 #[derive(Default)]
 pub struct PngEncoder<'a> {
     pub(crate) options:         EncoderOptions,
@@ -26,7 +32,11 @@ pub struct PngEncoder<'a> {
     pub(crate) encoded_chunks:  Vec<u8>,
     pub(crate) filter_scanline: Vec<u8>,
     pub(crate) gamma:           Option<f32>,
-    pub(crate) exif:            Option<&'a [u8]>
+    pub(crate) exif:            Option<&'a [u8]>,
+    pub(crate) xmp:             Option<&'a [u8]>,
+    pub(crate) icc_profile:     Option<&'a [u8]>,
+    pub(crate) physical:        Option<PhysicalDimensions>,
+    pub(crate) palette:         Option<&'a [[u8; 4]]>
 }
 
 impl<'a> PngEncoder<'a> {
@@ -51,6 +61,39 @@ impl<'a> PngEncoder<'a> {
         self.exif = Some(exif);
     }
 
+    /// Add an XMP packet which will be encoded
+    ///
+    /// The packet is stored uncompressed in an iTXt chunk with
+    /// the `XML:com.adobe.xmp` keyword
+    pub fn add_xmp_segment(&mut self, xmp: &'a [u8]) {
+        self.xmp = Some(xmp);
+    }
+
+    /// Add an ICC profile which will be encoded
+    ///
+    /// The profile is compressed and stored in an iCCP chunk
+    pub fn add_icc_profile(&mut self, icc_profile: &'a [u8]) {
+        self.icc_profile = Some(icc_profile);
+    }
+
+    /// Set the physical dimensions of a pixel, which will be encoded
+    /// in a pHYs chunk
+    pub fn set_physical_dimensions(&mut self, dimensions: PhysicalDimensions) {
+        self.physical = Some(dimensions);
+    }
+
+    /// Encode the image as a paletted image
+    ///
+    /// The data passed to the encoder is then treated as one byte palette indices,
+    /// the colorspace of the options should be [`ColorSpace::Luma`](zune_core::colorspace::ColorSpace::Luma)
+    /// and the depth [`BitDepth::Eight`](zune_core::bit_depth::BitDepth::Eight).
+    ///
+    /// Entries are in RGBA order, a tRNS chunk is written if any entry is not opaque.
+    /// A palette can have at most 256 entries.
+    pub fn set_palette(&mut self, palette: &'a [[u8; 4]]) {
+        self.palette = Some(palette);
+    }
+
     pub fn encode_headers<T: ZByteWriterTrait>(
         &self, writer: &mut ZWriter<T>
     ) -> Result<(), ZByteIoError> {
@@ -59,6 +102,18 @@ impl<'a> PngEncoder<'a> {
         // write ihdr
         write_header_fn(self, writer, b"IHDR", write_ihdr)?;
 
+        // iCCP has to come before PLTE
+        if self.icc_profile.is_some() {
+            write_header_fn(self, writer, b"iCCP", write_iccp)?;
+        }
+        if let Some(palette) = self.palette {
+            write_header_fn(self, writer, b"PLTE", write_plte)?;
+
+            if palette.iter().any(|x| x[3] != 255) {
+                write_header_fn(self, writer, b"tRNS", write_trns)?;
+            }
+        }
+
         // extra headers
         // need to check their existence because  write_header_fn will do
         // some writing even if they don't exist
@@ -68,6 +123,12 @@ impl<'a> PngEncoder<'a> {
         if self.gamma.is_some() {
             write_header_fn(self, writer, b"gAMA", write_gamma)?;
         }
+        if self.physical.is_some() {
+            write_header_fn(self, writer, b"pHYs", write_phys)?;
+        }
+        if self.xmp.is_some() {
+            write_header_fn(self, writer, b"iTXt", write_xmp)?;
+        }
         Ok(())
     }
 
@@ -82,6 +143,11 @@ impl<'a> PngEncoder<'a> {
             .checked_mul(self.options.colorspace().num_components())
             .ok_or(ZByteIoError::Generic("Overflow"))?;
 
+        if let Some(palette) = self.palette {
+            if palette.is_empty() || palette.len() > 256 {
+                return Err(ZByteIoError::Generic("Palettes must have 1 to 256 entries"));
+            }
+        }
         if self.data.len() != expected_data_size {
             return Err(ZByteIoError::NotEnoughBytes(
                 expected_data_size,
@@ -115,30 +181,42 @@ impl<'a> PngEncoder<'a> {
         let scanline_length = (self.calculate_scanline_size() + 1)
             .checked_mul(self.options.height())
             .unwrap();
-        let components =
-            self.options.colorspace().num_components() * self.options.depth().size_of();
 
+        #[cfg(feature = "threads")]
+        {
+            let threads = usize::from(self.options.num_threads());
+
+            if threads > 1 && scanline_length >= PARALLEL_MIN_BYTES {
+                self.encoded_chunks = self.encode_bands(threads);
+                return;
+            }
+        }
         // allocate space for filtered scanline
-        self.filter_scanline.resize(scanline_length, 0);
+        let mut filtered = core::mem::take(&mut self.filter_scanline);
+        filtered.resize(scanline_length, 0);
 
-        // one row above the current processing row
-        let mut previous_scanline: &[u8] = &[];
+        self.filter_rows(0..self.options.height(), &mut filtered);
 
-        let scanline_size = self.calculate_scanline_size();
+        // encode filtered scanline
+        self.encoded_chunks = DeflateEncoder::new(&filtered).encode_zlib();
+        self.filter_scanline = filtered;
+    }
 
-        for (i, filter_s) in self
-            .filter_scanline
-            .chunks_exact_mut(scanline_size + 1)
-            .take(self.options.height())
-            .enumerate()
-        {
-            let (previous, current) = self.data.split_at(i * scanline_size);
+    /// Filter `rows` of the image into `out`, a filter byte
+    /// followed by the filtered scanline per row
+    fn filter_rows(&self, rows: Range<usize>, out: &mut [u8]) {
+        let components =
+            self.options.colorspace().num_components() * self.options.depth().size_of();
 
-            if i > 0 {
-                // previous row now becomes defined
-                previous_scanline = &previous[(i - 1) * scanline_size..];
-            }
-            let current_scanline = &current[0..scanline_size];
+        let scanline_size = self.calculate_scanline_size();
+
+        for (i, filter_s) in rows.zip(out.chunks_exact_mut(scanline_size + 1)) {
+            let current_scanline = &self.data[i * scanline_size..(i + 1) * scanline_size];
+            // the first row has none above it
+            let previous_scanline = match i {
+                0 => &[],
+                _ => &self.data[(i - 1) * scanline_size..i * scanline_size]
+            };
             let filter = choose_compression_filter(previous_scanline, current_scanline);
 
             filter_scanline(
@@ -149,8 +227,42 @@ impl<'a> PngEncoder<'a> {
                 components
             );
         }
-        // encode filtered scanline
-        self.encoded_chunks = DeflateEncoder::new(&self.filter_scanline).encode_zlib();
+    }
+
+    /// Filter and deflate bands of rows on `threads` threads, and join
+    /// the deflated bands into one zlib stream
+    ///
+    /// Filters only look at the unfiltered rows above, and the bands are
+    /// deflated without referring to each other, so bands are independent
+    #[cfg(feature = "threads")]
+    fn encode_bands(&self, threads: usize) -> Vec<u8> {
+        use zune_inflate::{join_zlib_parts, ZlibPart};
+
+        let height = self.options.height();
+        let band_rows = height.div_ceil(threads);
+        let filtered_size = self.calculate_scanline_size() + 1;
+
+        let parts = std::thread::scope(|s| {
+            let bands = (0..height)
+                .step_by(band_rows)
+                .map(|start| {
+                    s.spawn(move || {
+                        let rows = start..(start + band_rows).min(height);
+
+                        let mut filtered = alloc::vec![0; rows.len() * filtered_size];
+                        self.filter_rows(rows, &mut filtered);
+
+                        DeflateEncoder::new(&filtered).encode_zlib_part()
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            bands
+                .into_iter()
+                .map(|x| x.join().unwrap())
+                .collect::<Vec<ZlibPart>>()
+        });
+        join_zlib_parts(&parts)
     }
     fn write_idat_chunks<T: ZByteWriterTrait>(
         &self, writer: &mut ZWriter<T>
@@ -198,3 +310,66 @@ fn test_simple_write() {
     let bytes = hello.decode_raw().unwrap();
     assert_eq!(&data, &bytes);
 }
+
+#[test]
+fn test_palette_write() {
+    use zune_core::bit_depth::BitDepth;
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::PngDecoder;
+
+    let palette = [[255, 0, 0, 255], [0, 0, 255, 128]];
+    let data: Vec<u8> = (0..40 * 10).map(|x| (x % 2) as u8).collect();
+
+    let options = EncoderOptions::default()
+        .set_colorspace(ColorSpace::Luma)
+        .set_width(40)
+        .set_height(10)
+        .set_depth(BitDepth::Eight);
+
+    let mut encoder = PngEncoder::new(&data, options);
+    encoder.set_palette(&palette);
+    let mut sink = vec![];
+    encoder.encode(&mut sink).unwrap();
+
+    let mut decoder = PngDecoder::new(ZCursor::new(&sink));
+    let bytes = decoder.decode_raw().unwrap();
+
+    assert_eq!(decoder.palette().unwrap(), palette);
+    assert_eq!(decoder.colorspace(), Some(ColorSpace::RGBA));
+    assert_eq!(&bytes[..8], &[255, 0, 0, 255, 0, 0, 255, 128]);
+}
+
+#[cfg(feature = "threads")]
+#[test]
+fn test_threaded_write() {
+    use zune_core::bit_depth::BitDepth;
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+
+    use crate::PngDecoder;
+
+    // large enough to be split in bands, with stored blocks spanning rows
+    let (width, height) = (1021, 353);
+    let data: Vec<u8> = (0..width * height * 3)
+        .map(|x| ((x * 7) ^ (x / 101)) as u8)
+        .collect();
+
+    for threads in [0, 3, 8] {
+        let options = EncoderOptions::default()
+            .set_colorspace(ColorSpace::RGB)
+            .set_width(width)
+            .set_height(height)
+            .set_depth(BitDepth::Eight)
+            .set_num_threads(threads);
+
+        let mut sink = vec![];
+        PngEncoder::new(&data, options).encode(&mut sink).unwrap();
+
+        // confirms the adler checksum of the joined bands too
+        let options = zune_core::options::DecoderOptions::default().set_max_threads(1);
+        let mut decoder = PngDecoder::new_with_options(ZCursor::new(&sink), options);
+        assert!(decoder.decode_raw().unwrap() == data, "{threads} threads");
+    }
+}
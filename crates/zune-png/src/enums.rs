@@ -33,6 +33,7 @@ pub enum PngChunkType {
     fcTL,
     acTL,
     fdAT,
+    cICP,
     unkn
 }
 
@@ -88,6 +88,8 @@ pub fn handle_sub(raw: &[u8], current: &mut [u8], components: usize, use_sse2: b
     {
         if use_sse2 {
             match components {
+                1 => return crate::filters::sse4::de_filter_sub_prefix::<1>(raw, current),
+                2 => return crate::filters::sse4::de_filter_sub_prefix::<2>(raw, current),
                 3 => return crate::filters::sse4::de_filter_sub_sse2::<3>(raw, current),
                 4 => return crate::filters::sse4::de_filter_sub_sse2::<4>(raw, current),
                 6 => return crate::filters::sse4::de_filter_sub_sse2::<6>(raw, current),
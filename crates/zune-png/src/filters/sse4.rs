@@ -198,6 +198,64 @@ pub fn de_filter_sub_sse2<const SIZE: usize>(raw: &[u8], current: &mut [u8]) {
     unsafe { de_filter_sub_generic_sse2::<SIZE>(raw, current) }
 }
 
+/// Sub de-filtering of 1 and 2 byte pixels, i.e 8 and 16 bit grayscale.
+///
+/// Pixels are too small for the per pixel loop above to be worth it, so 16 bytes are
+/// decoded at a time as a prefix sum, adding the register shifted by one, two, four
+/// and eight pixels, then the last pixel of the previous 16 bytes to everything
+#[target_feature(enable = "sse2")]
+unsafe fn de_filter_sub_prefix_sse2<const SIZE: usize>(raw: &[u8], current: &mut [u8]) {
+    let end = raw.len().min(current.len());
+    let (raw, current) = (&raw[..end], &mut current[..end]);
+
+    let mut last = _mm_setzero_si128();
+
+    let mut raw_chunks = raw.chunks_exact(16);
+    let mut out_chunks = current.chunks_exact_mut(16);
+
+    for (raw, out) in (&mut raw_chunks).zip(&mut out_chunks) {
+        let mut x = _mm_loadu_si128(raw.as_ptr().cast());
+
+        if SIZE == 1 {
+            x = _mm_add_epi8(x, _mm_slli_si128::<1>(x));
+        }
+        x = _mm_add_epi8(x, _mm_slli_si128::<2>(x));
+        x = _mm_add_epi8(x, _mm_slli_si128::<4>(x));
+        x = _mm_add_epi8(x, _mm_slli_si128::<8>(x));
+        x = _mm_add_epi8(x, last);
+
+        _mm_storeu_si128(out.as_mut_ptr().cast(), x);
+
+        // broadcast the last pixel for the next 16 bytes
+        let last_pixel = _mm_extract_epi16::<7>(x);
+
+        last = if SIZE == 1 {
+            _mm_set1_epi8((last_pixel >> 8) as i8)
+        } else {
+            _mm_set1_epi16(last_pixel as i16)
+        };
+    }
+    let done = end - raw_chunks.remainder().len();
+
+    for i in done..end {
+        let a = if i >= SIZE { current[i - SIZE] } else { 0 };
+        current[i] = raw[i].wrapping_add(a);
+    }
+}
+
+/// Sub de-filtering for 1 and 2 byte pixels, see [`de_filter_sub_prefix_sse2`]
+pub fn de_filter_sub_prefix<const SIZE: usize>(raw: &[u8], current: &mut [u8]) {
+    debug_assert!(SIZE == 1 || SIZE == 2);
+
+    #[cfg(feature = "std")]
+    {
+        if !is_x86_feature_detected!("sse2") {
+            panic!("Internal error, calling platform specific function where not supported")
+        }
+    }
+    unsafe { de_filter_sub_prefix_sse2::<SIZE>(raw, current) }
+}
+
 #[inline]
 #[target_feature(enable = "sse4.1")]
 unsafe fn if_then_else(c: __m128i, t: __m128i, e: __m128i) -> __m128i {
@@ -11,7 +11,9 @@ use zune_core::log::{trace, warn};
 use zune_inflate::DeflateDecoder;
 
 use crate::apng::{ActlChunk, BlendOp, DisposeOp, FrameInfo, SingleFrame};
-use crate::decoder::{ItxtChunk, PLTEEntry, PngChunk, TextChunk, TimeInfo, ZtxtChunk};
+use crate::decoder::{
+    ItxtChunk, PLTEEntry, PhysicalDimensions, PngChunk, TextChunk, TimeInfo, ZtxtChunk
+};
 use crate::enums::{FilterMethod, InterlaceMethod, PngChunkType, PngColor};
 use crate::error::PngDecodeErrors;
 use crate::PngDecoder;
@@ -149,6 +151,8 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
             pal_chunk.blue = self.stream.read_u8();
         }
 
+        self.palette_len = (chunk.length / 3).min(256);
+
         // skip crc chunk
         self.stream.skip(4)?;
         self.seen_ptle = true;
@@ -236,6 +240,20 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
         Ok(())
     }
 
+    pub(crate) fn parse_cicp(&mut self, chunk: PngChunk) -> Result<(), PngDecodeErrors> {
+        if chunk.length != 4 {
+            warn!("Invalid chunk length for cICP, skipping");
+            self.stream.skip(chunk.length + 4)?;
+            return Ok(());
+        }
+        let cicp = self.stream.read_fixed_bytes_or_error::<4>()?;
+        self.png_info.cicp = Some(cicp);
+        // skip crc
+        self.stream.skip(4)?;
+
+        Ok(())
+    }
+
     /// Parse the animation control chunk
     pub(crate) fn parse_actl(&mut self, chunk: PngChunk) -> Result<(), PngDecodeErrors> {
         if chunk.length != 8 {
@@ -292,6 +310,31 @@ impl<T: ZByteReaderTrait> PngDecoder<T> {
         Ok(())
     }
 
+    pub(crate) fn parse_phys(&mut self, chunk: PngChunk) -> Result<(), PngDecodeErrors> {
+        if chunk.length != 9 {
+            if self.options.strict_mode() {
+                return Err(PngDecodeErrors::GenericStatic("Invalid pHYs chunk length"));
+            }
+            warn!("Invalid pHYs chunk length {:?}", chunk.length);
+            // skip chunk + crc
+            self.stream.skip(chunk.length + 4)?;
+            return Ok(());
+        }
+        let x = self.stream.get_u32_be();
+        let y = self.stream.get_u32_be();
+        let unit_is_meter = self.stream.read_u8() == 1;
+
+        self.png_info.physical_dimensions = Some(PhysicalDimensions {
+            x,
+            y,
+            unit_is_meter
+        });
+        // skip past crc
+        self.stream.skip(4)?;
+
+        Ok(())
+    }
+
     pub(crate) fn parse_exif(&mut self, chunk: PngChunk) -> Result<(), PngDecodeErrors> {
         let data = self.stream.peek_at(0, chunk.length).unwrap();
 
@@ -8,6 +8,7 @@ use alloc::vec::Vec;
 
 use zune_core::bytestream::{ZByteIoError, ZWriter, ZByteWriterTrait};
 use zune_core::colorspace::ColorSpace;
+use zune_inflate::DeflateEncoder;
 
 use crate::crc::{calc_crc, calc_crc_with_bytes};
 use crate::decoder::PngChunk;
@@ -23,6 +24,7 @@ pub(crate) fn write_ihdr(ctx: &PngEncoder, output: &mut ZWriter<&mut Vec<u8>>) {
     let color = ctx.options.colorspace();
 
     let color_int = match color {
+        _ if ctx.palette.is_some() => 3,
         ColorSpace::Luma => 0,
         ColorSpace::RGB => 2,
         ColorSpace::LumaA => 4,
@@ -44,6 +46,32 @@ pub fn write_exif(ctx: &PngEncoder, writer: &mut ZWriter<&mut alloc::vec::Vec<u8
     }
 }
 
+pub fn write_xmp(ctx: &PngEncoder, writer: &mut ZWriter<&mut Vec<u8>>) {
+    if let Some(xmp) = ctx.xmp {
+        // keyword, no compression, no language tag and no translated keyword
+        writer.write_all(b"XML:com.adobe.xmp\x00\x00\x00\x00\x00").unwrap();
+        writer.write_all(xmp).unwrap();
+    }
+}
+
+pub fn write_iccp(ctx: &PngEncoder, writer: &mut ZWriter<&mut Vec<u8>>) {
+    if let Some(icc_profile) = ctx.icc_profile {
+        // profile name and compression method, zlib is the only one defined
+        writer.write_all(b"ICC Profile\x00\x00").unwrap();
+        writer
+            .write_all(&DeflateEncoder::new(icc_profile).encode_zlib())
+            .unwrap();
+    }
+}
+
+pub fn write_phys(ctx: &PngEncoder, writer: &mut ZWriter<&mut Vec<u8>>) {
+    if let Some(physical) = ctx.physical {
+        writer.write_u32_be(physical.x);
+        writer.write_u32_be(physical.y);
+        writer.write_u8(u8::from(physical.unit_is_meter));
+    }
+}
+
 pub fn write_gamma(ctx: &PngEncoder, writer: &mut ZWriter<&mut Vec<u8>>) {
     if let Some(gamma) = ctx.gamma {
         // scale by 100000.0
@@ -52,6 +80,28 @@ pub fn write_gamma(ctx: &PngEncoder, writer: &mut ZWriter<&mut Vec<u8>>) {
     }
 }
 
+pub fn write_plte(ctx: &PngEncoder, writer: &mut ZWriter<&mut Vec<u8>>) {
+    if let Some(palette) = ctx.palette {
+        for entry in palette {
+            writer.write_all(&entry[..3]).unwrap();
+        }
+    }
+}
+
+pub fn write_trns(ctx: &PngEncoder, writer: &mut ZWriter<&mut Vec<u8>>) {
+    if let Some(palette) = ctx.palette {
+        // trailing opaque entries can be omitted
+        let len = palette
+            .iter()
+            .rposition(|x| x[3] != 255)
+            .map_or(0, |x| x + 1);
+
+        for entry in &palette[..len] {
+            writer.write_u8(entry[3]);
+        }
+    }
+}
+
 // iend is a no-op
 pub fn write_iend(_: &PngEncoder, _: &mut ZWriter<&mut Vec<u8>>) {}
 
@@ -168,7 +168,9 @@ extern crate core;
 #[cfg(feature = "std")]
 pub use apng::post_process_image;
 pub use apng::{BlendOp, DisposeOp};
-pub use decoder::{ItxtChunk, PngDecoder, PngInfo, TextChunk, TimeInfo, ZtxtChunk};
+pub use decoder::{
+    ItxtChunk, PhysicalDimensions, PngDecoder, PngInfo, TextChunk, TimeInfo, ZtxtChunk
+};
 pub use encoder::PngEncoder;
 pub use enums::InterlaceMethod;
 pub use zune_core;
@@ -183,4 +185,7 @@ pub mod error;
 mod filters;
 mod headers;
 mod options;
+mod pipeline;
+mod rows;
+mod streaming;
 mod utils;
@@ -0,0 +1,166 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Pipelined decoding of large non-interlaced images
+//!
+//! Decoding runs as three stages on three threads, each working on what the stage
+//! before it already finished
+//!
+//! 1. Inflating, which sends every deflate block once it's decoded
+//! 2. De-filtering, which undoes the filter of every scanline whose bytes have arrived
+//! 3. Post processing, which expands finished scanlines and converts 16 bit samples
+//!    to the set endian
+//!
+//! A scanline is finished once the scanline below it is de-filtered,
+//! since filters read the row above as it was before expansion.
+#![cfg(feature = "threads")]
+
+use alloc::format;
+use alloc::vec::Vec;
+use std::sync::mpsc::sync_channel;
+use std::thread;
+
+use zune_core::bit_depth::BitDepth;
+use zune_core::bytestream::ZByteReaderTrait;
+use zune_core::log::trace;
+use zune_inflate::DeflateDecoder;
+
+use crate::decoder::{PngDecoder, PngInfo};
+use crate::error::PngDecodeErrors;
+use crate::utils::convert_be_to_target_endian_u16;
+
+/// Images with less inflated bytes than this aren't worth starting threads for
+const PIPELINE_MIN_BYTES: usize = 1 << 20;
+
+/// Deflate blocks the inflater can get ahead of the de-filterer by
+const BLOCKS_IN_FLIGHT: usize = 8;
+
+/// Scanlines the de-filterer can get ahead of the post processor by
+const ROWS_IN_FLIGHT: usize = 64;
+
+impl<T: ZByteReaderTrait> PngDecoder<T> {
+    /// Return true if a non interlaced `width` by `height` image is large enough
+    /// to be decoded by [`create_png_image_pipelined`](Self::create_png_image_pipelined),
+    /// and the options allow a thread per stage
+    ///
+    /// See [`DecoderOptions::set_max_threads`](zune_core::options::DecoderOptions::set_max_threads)
+    /// for how the thread limit is chosen
+    pub(crate) fn should_pipeline(&self, width: usize, height: usize, info: &PngInfo) -> bool {
+        let inflated = self.defilter(width, info).in_stride.saturating_mul(height);
+
+        let threads = match self.options.max_threads() {
+            0 => thread::available_parallelism().map_or(1, |x| x.get()),
+            threads => threads
+        };
+        // the calling thread and the two spawned ones
+        inflated >= PIPELINE_MIN_BYTES && threads >= 3
+    }
+
+    /// Decode the current non interlaced frame into `out`, running inflating, de-filtering
+    /// and post processing on different threads
+    ///
+    /// Unlike [`create_png_image_raw`](Self::create_png_image_raw) this inflates the IDAT
+    /// chunks itself, and converts 16 bit images to the set endian
+    pub(crate) fn create_png_image_pipelined(
+        &mut self, width: usize, height: usize, out: &mut [u8], info: &PngInfo
+    ) -> Result<(), PngDecodeErrors> {
+        trace!("Decoding {width}x{height} image in a pipeline");
+
+        let defilter = self.defilter(width, info);
+        let (in_stride, out_stride) = (defilter.in_stride, defilter.out_stride);
+
+        let convert_endian = self.depth() == Some(BitDepth::Sixteen);
+        let (endian, use_sse41) = (self.byte_endian(), self.options.use_sse41());
+
+        let scratch = core::mem::take(&mut self.previous_stride);
+        let mut post_processor = self.row_post_processor(&defilter, width, info, scratch)?;
+
+        let options = self.inflate_options();
+        let fdat = &self.frames[self.current_frame].fdat;
+
+        let (block_tx, block_rx) = sync_channel::<Vec<u8>>(BLOCKS_IN_FLIGHT);
+        let (row_tx, row_rx) = sync_channel::<&mut [u8]>(ROWS_IN_FLIGHT);
+
+        let mut rows = out.chunks_exact_mut(out_stride).take(height);
+
+        let (result, scratch) = thread::scope(|s| {
+            let inflater = s.spawn(move || {
+                let mut decoder = DeflateDecoder::new_with_options(fdat, options);
+                // if sending fails, de-filtering failed and the error is returned from there
+                decoder.decode_zlib_with_blocks(|block| {
+                    let _ = block_tx.send(block.to_vec());
+                })
+            });
+
+            let post = s.spawn(move || {
+                for row in row_rx {
+                    if let Some(post_processor) = &mut post_processor {
+                        post_processor.process(row);
+                    }
+                    if convert_endian {
+                        convert_be_to_target_endian_u16(row, endian, use_sse41);
+                    }
+                }
+                post_processor.map(|x| x.into_scratch())
+            });
+
+            let defilter_rows = || -> Result<usize, PngDecodeErrors> {
+                // inflated bytes not yet de-filtered
+                let mut pending = Vec::with_capacity(2 * in_stride);
+                let mut prev_row: Option<&mut [u8]> = None;
+                let mut rows_done = 0;
+
+                for block in block_rx {
+                    pending.extend_from_slice(&block);
+
+                    let mut in_rows = pending.chunks_exact(in_stride);
+
+                    for in_row in &mut in_rows {
+                        let Some(current) = rows.next() else {
+                            break;
+                        };
+                        defilter.row(in_row, prev_row.as_deref(), current)?;
+
+                        // the row above is final, hand it over
+                        if let Some(finished) = prev_row.replace(current) {
+                            let _ = row_tx.send(finished);
+                        }
+                        rows_done += 1;
+                    }
+                    let used = pending.len() - in_rows.remainder().len();
+                    pending.drain(..used);
+                }
+                if let Some(finished) = prev_row {
+                    let _ = row_tx.send(finished);
+                }
+                Ok(rows_done)
+            };
+            let rows_done = defilter_rows();
+            // let the post processor finish
+            drop(row_tx);
+
+            let inflated = inflater.join().unwrap();
+            let scratch = post.join().unwrap();
+
+            let result = match (inflated, rows_done) {
+                (Err(e), _) => Err(PngDecodeErrors::ZlibDecodeErrors(e)),
+                (Ok(_), Err(e)) => Err(e),
+                (Ok(_), Ok(rows_done)) if rows_done < height => Err(PngDecodeErrors::Generic(
+                    format!("Not enough pixels, expected {height} rows but found {rows_done}")
+                )),
+                (Ok(_), Ok(_)) => Ok(())
+            };
+            (result, scratch)
+        });
+
+        if let Some(scratch) = scratch {
+            self.previous_stride = scratch;
+        }
+        result
+    }
+}
@@ -0,0 +1,280 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Per scanline work of non-interlaced decoding
+//!
+//! Shared by the serial decoder and the pipelined one, which runs the stages of
+//! these on different threads.
+use alloc::format;
+use alloc::vec::Vec;
+
+use zune_core::bit_depth::BitDepth;
+
+use crate::decoder::{PLTEEntry, PngInfo};
+use crate::enums::{FilterMethod, PngColor};
+use crate::error::PngDecodeErrors;
+use crate::filters::de_filter::{
+    handle_avg, handle_avg_first, handle_paeth, handle_paeth_first, handle_sub, handle_up
+};
+use crate::utils::{add_alpha, expand_bits_to_byte, expand_palette, expand_trns};
+
+/// Undoes the filter of one scanline at a time
+#[derive(Copy, Clone)]
+pub(crate) struct Defilter {
+    /// Bytes of a filtered scanline, including the filter byte
+    pub(crate) in_stride:  usize,
+    /// Bytes of a scanline in the output, after post processing
+    pub(crate) out_stride: usize,
+    /// Bytes of a de-filtered scanline, before post processing
+    width_stride:          usize,
+    /// Bytes the filters look back by
+    components:            usize,
+    use_sse2:              bool,
+    use_sse4:              bool
+}
+
+impl Defilter {
+    pub(crate) fn new(
+        info: &PngInfo, width: usize, out_components: usize, use_sse2: bool, use_sse4: bool
+    ) -> Defilter {
+        let bytes = if info.depth == 16 { 2 } else { 1 };
+        let in_components = usize::from(info.color.num_components());
+
+        // if the bit depth is less than 8, the spec says the byte before
+        // X is to be used by the filter
+        let components = if info.depth < 8 { 1 } else { in_components * bytes };
+
+        // number of bytes per every scan line, plus the filter type
+        let width_stride = (width * in_components * usize::from(info.depth)).div_ceil(8);
+
+        Defilter {
+            in_stride: width_stride + 1,
+            out_stride: width * out_components * bytes,
+            width_stride,
+            components,
+            use_sse2,
+            use_sse4
+        }
+    }
+
+    /// Undo the filter of `in_stride` into `current`
+    ///
+    /// `prev_row` is the de-filtered row above, or `None` for the first row
+    pub(crate) fn row(
+        &self, in_stride: &[u8], prev_row: Option<&[u8]>, current: &mut [u8]
+    ) -> Result<(), PngDecodeErrors> {
+        // take filter
+        let filter_byte = in_stride[0];
+        // raw image bytes
+        let raw = &in_stride[1..];
+
+        // get its type
+        let mut filter = FilterMethod::from_int(filter_byte)
+            .ok_or_else(|| PngDecodeErrors::Generic(format!("Unknown filter {filter_byte}")))?;
+
+        let prev_row = match prev_row {
+            Some(row) => row,
+            None => {
+                // match our filters to special filters for first row
+                // these special filters do not need the previous scanline and treat it
+                // as zero
+                filter = match filter {
+                    FilterMethod::Paeth => FilterMethod::PaethFirst,
+                    // up for the first row becomes a memcpy
+                    FilterMethod::Up => FilterMethod::None,
+                    FilterMethod::Average => FilterMethod::AvgFirst,
+                    filter => filter
+                };
+                &[0]
+            }
+        };
+        let components = self.components;
+
+        match filter {
+            FilterMethod::None => current[0..self.width_stride].copy_from_slice(raw),
+
+            FilterMethod::Average => handle_avg(prev_row, raw, current, components, self.use_sse4),
+
+            FilterMethod::Sub => handle_sub(raw, current, components, self.use_sse2),
+
+            FilterMethod::Up => handle_up(prev_row, raw, current),
+
+            FilterMethod::Paeth => handle_paeth(prev_row, raw, current, components, self.use_sse4),
+
+            FilterMethod::PaethFirst => handle_paeth_first(raw, current, components),
+
+            FilterMethod::AvgFirst => handle_avg_first(raw, current, components),
+
+            FilterMethod::Unknown => unreachable!()
+        }
+        Ok(())
+    }
+}
+
+/// Expands de-filtered scanlines in place, i.e. bits to bytes, tRNS to alpha,
+/// palette indexes to colors, or adds an alpha channel
+///
+/// This must run after the filter of the row below is undone, since filters
+/// need the row above as it was before expansion
+pub(crate) struct RowPostProcessor<'a> {
+    width:             usize,
+    depth:             u8,
+    out_depth:         BitDepth,
+    color:             PngColor,
+    n_components:      usize,
+    width_stride:      usize,
+    seen_ptle:         bool,
+    seen_trns:         bool,
+    add_alpha_channel: bool,
+    trns_bytes:        [u16; 4],
+    palette:           Option<&'a [PLTEEntry; 256]>,
+    /// Holds the row before expansion
+    scratch:           Vec<u8>
+}
+
+impl<'a> RowPostProcessor<'a> {
+    /// Create a post processor, or return `None` if rows are already in their final form
+    ///
+    /// `scratch` is reused for the rows before expansion, get it back
+    /// with [`into_scratch`](Self::into_scratch)
+    /// `expand_palette` is false when the indices of paletted images are kept
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        info: &PngInfo, defilter: &Defilter, width: usize, out_depth: BitDepth, seen_ptle: bool,
+        seen_trns: bool, add_alpha_channel: bool, expand_palette: bool, trns_bytes: [u16; 4],
+        palette: &'a [PLTEEntry], mut scratch: Vec<u8>
+    ) -> Result<Option<RowPostProcessor<'a>>, PngDecodeErrors> {
+        let palette = if seen_ptle && expand_palette && info.color == PngColor::Palette {
+            if palette.is_empty() {
+                return Err(PngDecodeErrors::EmptyPalette);
+            }
+            Some(palette[..256].try_into().unwrap())
+        } else {
+            None
+        };
+        if !(seen_trns | palette.is_some() | add_alpha_channel | (info.depth < 8)) {
+            return Ok(None);
+        }
+        if scratch.len() < defilter.out_stride {
+            scratch.resize(defilter.out_stride, 0);
+        }
+        Ok(Some(RowPostProcessor {
+            width,
+            depth: info.depth,
+            out_depth,
+            color: info.color,
+            n_components: usize::from(info.color.num_components()),
+            width_stride: defilter.width_stride,
+            seen_ptle,
+            seen_trns,
+            add_alpha_channel,
+            trns_bytes,
+            palette,
+            scratch
+        }))
+    }
+
+    /// Expand one de-filtered row in place
+    pub(crate) fn process(&mut self, to_filter_row: &mut [u8]) {
+        let depth = usize::from(self.depth);
+
+        if self.depth < 8 {
+            // check if we will run any other transform
+            let extra_transform =
+                self.palette.is_some() | self.seen_trns | self.add_alpha_channel;
+
+            if extra_transform {
+                // input data is  in_to_filter_row,
+                // we write output to scratch
+                // since other parts use scratch
+                expand_bits_to_byte(
+                    self.width,
+                    depth,
+                    self.n_components,
+                    self.seen_ptle,
+                    to_filter_row,
+                    &mut self.scratch
+                );
+            } else {
+                // no extra transform, just depth upscaling, so let's
+                // do that,
+
+                // copy the row to a temporary space
+                self.scratch[..self.width_stride]
+                    .copy_from_slice(&to_filter_row[..self.width_stride]);
+
+                expand_bits_to_byte(
+                    self.width,
+                    depth,
+                    self.n_components,
+                    self.seen_ptle,
+                    &self.scratch,
+                    to_filter_row
+                );
+            }
+        } else {
+            // copy the row to a temporary space
+            self.scratch[..self.width_stride].copy_from_slice(&to_filter_row[..self.width_stride]);
+        }
+
+        if self.seen_trns && self.color != PngColor::Palette {
+            // the expansion is a trns expansion
+            // bytes are already in position, so finish the business
+            if self.depth <= 8 {
+                expand_trns::<false>(
+                    &self.scratch,
+                    to_filter_row,
+                    self.color,
+                    self.trns_bytes,
+                    self.depth
+                );
+            } else if self.depth == 16 {
+                // Tested by test_palette_trns_16bit.
+                expand_trns::<true>(
+                    &self.scratch,
+                    to_filter_row,
+                    self.color,
+                    self.trns_bytes,
+                    self.depth
+                );
+            }
+        }
+
+        if let Some(plte_entry) = self.palette {
+            // so now we have two things
+            // the palette entries stored in scratch
+            // the row to fill the palette sored in to_filter row,
+            // so we can finally expand the entries
+
+            if self.seen_trns | self.add_alpha_channel {
+                // if tRNS chunk is present in paletted images, it contains
+                // alpha byte values, so that means we create alpha data from
+                // raw bytes
+
+                // if we are to add alpha channel for palette images , we simply just
+                // read four entries from the palette.
+                //
+                // The palette is set that the alpha channel is initialized as 255 for non alpha
+                // images,
+                expand_palette(&self.scratch, to_filter_row, plte_entry, 4);
+            } else {
+                // Normal expansion
+                expand_palette(&self.scratch, to_filter_row, plte_entry, 3);
+            }
+        } else if self.add_alpha_channel {
+            // the image is a normal RGB/ Luma image, which we need to add the alpha channel
+            // do it here
+            add_alpha(&self.scratch, to_filter_row, self.color, self.out_depth);
+        }
+    }
+
+    /// Return the scratch space passed to [`new`](Self::new)
+    pub(crate) fn into_scratch(self) -> Vec<u8> {
+        self.scratch
+    }
+}
@@ -0,0 +1,308 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Decoding a few rows at a time
+//!
+//! [`decode_rows`](PngDecoder::decode_rows) reads IDAT chunks only as far as the rows
+//! asked for need, and inflates them with an [`InflateStream`], so neither the compressed
+//! nor the decompressed image is ever held whole. Only non-interlaced images can be
+//! decoded this way, interlaced ones spread every row over seven passes.
+use alloc::vec;
+use alloc::vec::Vec;
+
+use zune_core::bit_depth::BitDepth;
+use zune_core::bytestream::ZByteReaderTrait;
+use zune_inflate::InflateStream;
+
+use crate::decoder::{PngChunk, PngDecoder};
+use crate::enums::{InterlaceMethod, PngChunkType};
+use crate::error::PngDecodeErrors;
+use crate::utils::convert_be_to_target_endian_u16;
+
+/// Compressed bytes read from an IDAT chunk at a time
+const READ_SIZE: usize = 1 << 15;
+
+/// Where row decoding is
+pub(crate) struct RowState {
+    inflater:  InflateStream,
+    /// Bytes of the current IDAT chunk not read yet
+    idat_left: usize,
+    /// True once there are no more IDAT chunks
+    idat_done: bool,
+    /// True once the chunks after the image data were read
+    read_all:  bool,
+    /// Compressed data read from IDAT chunks
+    buffer:    Vec<u8>,
+    /// The filtered row being inflated, and how much of it is there
+    filtered:  Vec<u8>,
+    filled:    usize,
+    /// The row above, de-filtered but not post processed, and the current one
+    previous:  Vec<u8>,
+    current:   Vec<u8>,
+    row:       usize
+}
+
+impl<T: ZByteReaderTrait> PngDecoder<T> {
+    /// Decode the headers up to the image data and prepare for [`decode_rows`](Self::decode_rows)
+    ///
+    /// Unlike [`decode_headers`](Self::decode_headers), this doesn't read the image data, so
+    /// metadata stored after it, e.g. some eXIf and tEXt chunks, is only available
+    /// once every row was decoded. [`info`](Self::info) and the other getters work
+    /// once this returns.
+    ///
+    /// Animated images decode as their default image, not their frames
+    ///
+    /// # Errors
+    /// The headers are corrupt, or the image is interlaced
+    pub fn start_rows(&mut self) -> Result<(), PngDecodeErrors> {
+        if self.rows.is_some() {
+            return Ok(());
+        }
+        let mut data = vec![];
+        let mut idat_left = 0;
+
+        if self.seen_headers {
+            // decode_headers already read the image data
+            data = core::mem::take(&mut self.frames[self.current_frame].fdat);
+        } else {
+            self.read_signature()?;
+
+            loop {
+                let header = self.read_chunk_header()?;
+
+                match header.chunk_type {
+                    PngChunkType::IDAT => {
+                        idat_left = header.length;
+                        break;
+                    }
+                    PngChunkType::IEND => {
+                        return Err(PngDecodeErrors::GenericStatic("No image data found"));
+                    }
+                    PngChunkType::fcTL => {
+                        // frames of animated images aren't decoded by rows
+                        self.parse_fctl_external(header)?;
+                    }
+                    _ => self.parse_header(header)?
+                }
+            }
+            self.seen_headers = true;
+        }
+        if self.png_info.interlace_method == InterlaceMethod::Adam7 {
+            return Err(PngDecodeErrors::GenericStatic(
+                "Interlaced images can't be decoded row by row"
+            ));
+        }
+        let defilter = self.defilter(self.png_info.width, &self.png_info);
+
+        let mut inflater = InflateStream::new_zlib(self.inflate_options());
+        inflater.add_input(&data);
+
+        self.rows = Some(RowState {
+            inflater,
+            idat_left,
+            idat_done: !data.is_empty(),
+            read_all: !data.is_empty(),
+            buffer: vec![0; READ_SIZE],
+            filtered: vec![0; defilter.in_stride],
+            filled: 0,
+            previous: vec![0; defilter.out_stride],
+            current: vec![0; defilter.out_stride],
+            row: 0
+        });
+        Ok(())
+    }
+
+    /// Return the number of bytes of a decoded row
+    ///
+    /// That is [`output_buffer_size`](Self::output_buffer_size) divided by the height,
+    /// or `None` if headers weren't decoded
+    pub fn row_bytes(&self) -> Option<usize> {
+        let bytes = self.output_buffer_size()?;
+
+        Some(bytes.checked_div(self.png_info.height).unwrap_or(0))
+    }
+
+    /// Decode the next rows of the image into `out`
+    ///
+    /// As many whole rows as fit in `out`, [`row_bytes`](Self::row_bytes) each, are decoded,
+    /// in the same layout as [`decode_into`](Self::decode_into). Returns how many
+    /// rows were decoded, which is zero once every row was.
+    ///
+    /// Calls [`start_rows`](Self::start_rows) if it wasn't called, memory use stays the same
+    /// whatever the size of the image.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use zune_core::bytestream::ZCursor;
+    /// use zune_png::PngDecoder;
+    ///
+    /// let file = std::fs::read("image.png").unwrap();
+    /// let mut decoder = PngDecoder::new(ZCursor::new(file));
+    ///
+    /// decoder.start_rows().unwrap();
+    /// let mut rows = vec![0; decoder.row_bytes().unwrap() * 16];
+    ///
+    /// loop {
+    ///     let decoded = decoder.decode_rows(&mut rows).unwrap();
+    ///     if decoded == 0 {
+    ///         break;
+    ///     }
+    ///     // send the rows on
+    /// }
+    /// ```
+    pub fn decode_rows(&mut self, out: &mut [u8]) -> Result<usize, PngDecodeErrors> {
+        self.start_rows()?;
+
+        let mut state = self.rows.take().unwrap();
+        let result = self.decode_rows_inner(&mut state, out);
+        self.rows = Some(state);
+
+        result
+    }
+
+    fn decode_rows_inner(
+        &mut self, state: &mut RowState, out: &mut [u8]
+    ) -> Result<usize, PngDecodeErrors> {
+        let (width, height) = (self.png_info.width, self.png_info.height);
+        let row_bytes = self.row_bytes().unwrap();
+
+        if row_bytes == 0 {
+            return Ok(0);
+        }
+        let info = self.png_info.clone();
+        let defilter = self.defilter(width, &info);
+
+        let strip = info.depth == 16 && self.options.png_get_strip_to_8bit();
+        let endian = self.byte_endian();
+
+        let mut decoded = 0;
+
+        for out_row in out.chunks_exact_mut(row_bytes) {
+            if state.row == height {
+                break;
+            }
+            self.fill_filtered_row(state)?;
+
+            let previous = (state.row > 0).then_some(state.previous.as_slice());
+            defilter.row(&state.filtered, previous, &mut state.current)?;
+            state.filled = 0;
+
+            // the filter of the next row needs this one before post processing
+            core::mem::swap(&mut state.previous, &mut state.current);
+            state.current.copy_from_slice(&state.previous);
+
+            let scratch = core::mem::take(&mut self.previous_stride);
+
+            if let Some(mut post_processor) =
+                self.row_post_processor(&defilter, width, &info, scratch)?
+            {
+                post_processor.process(&mut state.current);
+                self.previous_stride = post_processor.into_scratch();
+            }
+            if strip {
+                // keep the high byte of big endian samples
+                for (output, input) in out_row.iter_mut().zip(state.current.chunks_exact(2)) {
+                    *output = input[0];
+                }
+            } else {
+                out_row.copy_from_slice(&state.current[..row_bytes]);
+
+                if self.depth() == Some(BitDepth::Sixteen) {
+                    convert_be_to_target_endian_u16(out_row, endian, self.options.use_sse41());
+                }
+            }
+            state.row += 1;
+            decoded += 1;
+        }
+        if state.row == height && decoded > 0 {
+            self.finish_rows(state)?;
+        }
+        Ok(decoded)
+    }
+
+    /// Inflate the next filtered row into `state.filtered`, reading IDAT chunks as needed
+    fn fill_filtered_row(&mut self, state: &mut RowState) -> Result<(), PngDecodeErrors> {
+        while state.filled < state.filtered.len() {
+            let length = state
+                .inflater
+                .read(&mut state.filtered[state.filled..])
+                .map_err(PngDecodeErrors::ZlibDecodeErrors)?;
+            state.filled += length;
+
+            if length > 0 || state.filled == state.filtered.len() {
+                continue;
+            }
+            if !state.inflater.needs_input() || !self.read_idat(state)? {
+                return Err(PngDecodeErrors::GenericStatic(
+                    "Not enough pixels, image data ended early"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Pass the next piece of the IDAT chunks to the inflater, returning false
+    /// if there are no more
+    fn read_idat(&mut self, state: &mut RowState) -> Result<bool, PngDecodeErrors> {
+        while state.idat_left == 0 {
+            if state.idat_done {
+                return Ok(false);
+            }
+            let header = self.read_chunk_header()?;
+
+            if header.chunk_type != PngChunkType::IDAT {
+                state.idat_done = true;
+                self.parse_trailing_chunk(state, header)?;
+                return Ok(false);
+            }
+            state.idat_left = header.length;
+
+            if header.length == 0 {
+                self.stream.skip(4)?;
+            }
+        }
+        let length = state.idat_left.min(READ_SIZE);
+
+        self.stream.read_exact_bytes(&mut state.buffer[..length])?;
+        state.inflater.add_input(&state.buffer[..length]);
+        state.idat_left -= length;
+
+        if state.idat_left == 0 {
+            // skip crc
+            self.stream.skip(4)?;
+        }
+        Ok(true)
+    }
+
+    /// Read the chunks after the image data, for the metadata stored there
+    fn finish_rows(&mut self, state: &mut RowState) -> Result<(), PngDecodeErrors> {
+        // skip what's left of the image data, e.g. the checksum
+        while self.read_idat(state)? {}
+
+        while !state.read_all {
+            let header = self.read_chunk_header()?;
+            self.parse_trailing_chunk(state, header)?;
+        }
+        Ok(())
+    }
+
+    /// Parse a chunk after the image data, reading stops at the end of the image or
+    /// at the frames of animated images
+    fn parse_trailing_chunk(
+        &mut self, state: &mut RowState, header: PngChunk
+    ) -> Result<(), PngDecodeErrors> {
+        if matches!(header.chunk_type, PngChunkType::fcTL | PngChunkType::fdAT) {
+            state.read_all = true;
+            return Ok(());
+        }
+        self.parse_header(header)?;
+        state.read_all = self.seen_iend;
+
+        Ok(())
+    }
+}
@@ -105,3 +105,37 @@ fn test_palette_8bpp() {
         test_decoding(path);
     }
 }
+
+#[test]
+fn test_palette_keep_indices() {
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::DecoderOptions;
+
+    let names = [
+        "basi3p01", "basn3p02", "basi3p04", "basn3p08", "s09i3p02", "tbbn3p08", "tm3n3p02"
+    ];
+    for name in names {
+        let path = env!("CARGO_MANIFEST_DIR").to_string() + "/tests/png_suite/" + name + ".png";
+        let contents = open_and_read(path);
+
+        let options = DecoderOptions::default()
+            .png_set_keep_indices(true)
+            .png_set_add_alpha_channel(true);
+        let mut decoder = zune_png::PngDecoder::new_with_options(ZCursor::new(&contents), options);
+        let indices = decoder.decode_raw().unwrap();
+        assert_eq!(decoder.colorspace(), Some(ColorSpace::Luma), "{name}");
+
+        // looking the indices up in the palette gives the expanded colors
+        let palette = decoder.palette().unwrap();
+        let expanded: Vec<u8> = indices
+            .iter()
+            .flat_map(|x| palette[usize::from(*x)])
+            .collect();
+
+        let options = DecoderOptions::default().png_set_add_alpha_channel(true);
+        let colors = zune_png::PngDecoder::new_with_options(ZCursor::new(&contents), options)
+            .decode_raw()
+            .unwrap();
+        assert_eq!(expanded, colors, "{name}");
+    }
+}
@@ -0,0 +1,149 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Images large enough to be decoded in a pipeline, with the filters
+//! that have SIMD versions for every pixel size
+
+use nanorand::Rng;
+use zune_core::bit_depth::ByteEndian;
+use zune_core::bytestream::ZCursor;
+use zune_core::options::DecoderOptions;
+
+fn encode(
+    width: u32, height: u32, color: png::ColorType, depth: png::BitDepth, filter: png::FilterType,
+    palette: bool
+) -> Vec<u8> {
+    let samples = color.samples() * if depth == png::BitDepth::Sixteen { 2 } else { 1 };
+    let mut data = vec![0_u8; samples * width as usize * height as usize];
+
+    // mostly smooth with some noise, so every filter has something to do
+    let mut rng = nanorand::WyRand::new_seed(42);
+    for (i, value) in data.iter_mut().enumerate() {
+        *value = (i / 7 % 251) as u8 ^ (rng.generate::<u8>() & 3);
+    }
+    if palette {
+        data.iter_mut().for_each(|x| *x &= 3);
+    }
+
+    let mut out = vec![];
+    let mut encoder = png::Encoder::new(&mut out, width, height);
+    encoder.set_color(color);
+    encoder.set_depth(depth);
+    encoder.set_filter(filter);
+    encoder.set_adaptive_filter(png::AdaptiveFilterType::NonAdaptive);
+
+    if palette {
+        encoder.set_palette(vec![0, 0, 0, 255, 0, 0, 0, 255, 0, 0, 0, 255]);
+        encoder.set_trns(vec![0, 128]);
+    }
+    encoder
+        .write_header()
+        .unwrap()
+        .write_image_data(&data)
+        .unwrap();
+
+    out
+}
+
+fn decode_ref(data: &[u8]) -> Vec<u8> {
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(png::Transformations::EXPAND);
+    let mut reader = decoder.read_info().unwrap();
+
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let _ = reader.next_frame(&mut buf).unwrap();
+
+    buf
+}
+
+fn test_against_ref(data: &[u8]) {
+    // the reference keeps 16 bit samples big endian, and more than one thread
+    // is allowed even for single core machines
+    let options = DecoderOptions::default()
+        .set_byte_endian(ByteEndian::BE)
+        .set_max_threads(3);
+
+    let zune_results = zune_png::PngDecoder::new_with_options(ZCursor::new(data), options)
+        .decode_raw()
+        .unwrap();
+
+    assert!(zune_results == decode_ref(data));
+}
+
+#[test]
+fn test_pipeline_gray_16bit_sub() {
+    let data = encode(
+        1024,
+        600,
+        png::ColorType::Grayscale,
+        png::BitDepth::Sixteen,
+        png::FilterType::Sub,
+        false
+    );
+    test_against_ref(&data);
+}
+
+#[test]
+fn test_pipeline_gray_8bit_sub() {
+    // small enough to skip the pipeline, then large enough not to
+    for (width, height) in [(77, 13), (1501, 800)] {
+        let data = encode(
+            width,
+            height,
+            png::ColorType::Grayscale,
+            png::BitDepth::Eight,
+            png::FilterType::Sub,
+            false
+        );
+        test_against_ref(&data);
+    }
+}
+
+#[test]
+fn test_pipeline_rgba_16bit_paeth() {
+    let data = encode(
+        513,
+        300,
+        png::ColorType::Rgba,
+        png::BitDepth::Sixteen,
+        png::FilterType::Paeth,
+        false
+    );
+    test_against_ref(&data);
+}
+
+#[test]
+fn test_pipeline_palette_trns_avg() {
+    let data = encode(
+        1200,
+        1000,
+        png::ColorType::Indexed,
+        png::BitDepth::Eight,
+        png::FilterType::Avg,
+        true
+    );
+    test_against_ref(&data);
+}
+
+#[test]
+fn test_pipeline_corrupt_data() {
+    let mut data = encode(
+        1024,
+        600,
+        png::ColorType::Grayscale,
+        png::BitDepth::Sixteen,
+        png::FilterType::Up,
+        false
+    );
+    // corrupt the deflate stream halfway, errors should be returned, not hang
+    let middle = data.len() / 2;
+    data[middle..middle + 64].fill(0xFF);
+
+    let result = zune_png::PngDecoder::new(ZCursor::new(&data)).decode_raw();
+    assert!(result.is_err());
+}
@@ -0,0 +1,91 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Decoding a few rows at a time gives the same pixels as decoding whole images
+
+use std::fs::read;
+use std::path::Path;
+
+use zune_core::bytestream::ZCursor;
+use zune_core::options::DecoderOptions;
+use zune_png::PngDecoder;
+
+fn decode_by_rows(data: &[u8], options: DecoderOptions, rows: usize) -> Vec<u8> {
+    let mut decoder = PngDecoder::new_with_options(ZCursor::new(data), options);
+    decoder.start_rows().unwrap();
+
+    let row_bytes = decoder.row_bytes().unwrap();
+    let mut buffer = vec![0; row_bytes * rows];
+    let mut pixels = vec![];
+
+    loop {
+        let decoded = decoder.decode_rows(&mut buffer).unwrap();
+        if decoded == 0 {
+            break;
+        }
+        pixels.extend_from_slice(&buffer[..decoded * row_bytes]);
+    }
+    pixels
+}
+
+fn test_png_suite(options: DecoderOptions) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/png_suite");
+
+    for entry in path.read_dir().unwrap() {
+        let path = entry.unwrap().path();
+        let name = path.file_name().unwrap().to_str().unwrap().to_string();
+
+        // corrupt images start with x, the fourth letter is i for interlaced ones
+        if !name.ends_with(".png") || name.starts_with('x') || name == "PngSuite.png" {
+            continue;
+        }
+        let data = read(&path).unwrap();
+
+        if name.as_bytes()[3] == b'i' {
+            let mut decoder = PngDecoder::new_with_options(ZCursor::new(&data), options);
+            assert!(decoder.start_rows().is_err(), "{name}");
+            continue;
+        }
+        let expected = PngDecoder::new_with_options(ZCursor::new(&data), options)
+            .decode_raw()
+            .unwrap();
+
+        for rows in [1, 3, 64] {
+            assert!(
+                decode_by_rows(&data, options, rows) == expected,
+                "{name}, {rows} rows at a time"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_rows_png_suite() {
+    test_png_suite(DecoderOptions::default());
+}
+
+#[test]
+fn test_rows_png_suite_strip() {
+    test_png_suite(DecoderOptions::default().png_set_strip_to_8bit(true));
+}
+
+#[test]
+fn test_rows_after_headers() {
+    // image data read by decode_headers is decoded from memory
+    let data =
+        read(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/png_suite/basn6a16.png")).unwrap();
+    let expected = PngDecoder::new(ZCursor::new(&data)).decode_raw().unwrap();
+
+    let mut decoder = PngDecoder::new(ZCursor::new(&data));
+    decoder.decode_headers().unwrap();
+
+    let mut pixels = vec![0; decoder.output_buffer_size().unwrap()];
+    assert_eq!(decoder.decode_rows(&mut pixels).unwrap(), 32);
+    assert_eq!(decoder.decode_rows(&mut pixels).unwrap(), 0);
+    assert!(pixels == expected);
+}
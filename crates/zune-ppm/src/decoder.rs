@@ -7,17 +7,20 @@
  */
 
 use alloc::string::{String, ToString};
-use alloc::vec::Vec;
 use alloc::{format, vec};
-use core::fmt::{Debug, Formatter};
+use core::fmt::{Debug, Display, Formatter};
 
 use zune_core::bit_depth::{BitDepth, BitType, ByteEndian};
-use zune_core::bytestream::{ZByteIoError, ZByteReaderTrait, ZReader};
+use zune_core::bytestream::{ZByteIoError, ZByteReaderTrait, ZReader, ZSeekFrom};
 use zune_core::colorspace::ColorSpace;
 use zune_core::log::trace;
 use zune_core::options::DecoderOptions;
 use zune_core::result::DecodingResult;
 
+/// Header tokens are read into a buffer of this size, longer tokens are
+/// cut short which makes them invalid
+const HEADER_TOKEN_SIZE: usize = 64;
+
 /// An instance of a PPM decoder
 ///
 /// The decoder can currently decode P5 and P6 formats
@@ -31,7 +34,10 @@ where
     reader:          ZReader<T>,
     colorspace:      ColorSpace,
     bit_depth:       BitDepth,
-    options:         DecoderOptions
+    options:         DecoderOptions,
+    /// Rows returned by `decode_rows`, and where their pixels start
+    decoded_rows:    usize,
+    data_start:      u64
 }
 
 /// Decoding errors that may occur
@@ -80,6 +86,14 @@ impl Debug for PPMDecodeErrors {
     }
 }
 
+impl Display for PPMDecodeErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl core::error::Error for PPMDecodeErrors {}
+
 impl<T> PPMDecoder<T>
 where
     T: ZByteReaderTrait
@@ -124,7 +138,9 @@ where
             reader,
             colorspace: ColorSpace::Unknown,
             bit_depth: BitDepth::Eight,
-            options
+            options,
+            decoded_rows: 0,
+            data_start: 0
         }
     }
     /// Read PPM headers and store them in internal state
@@ -165,6 +181,14 @@ where
 
             return Err(PPMDecodeErrors::InvalidHeader(msg));
         }
+        let pixel_bytes = self.colorspace.num_components() * self.bit_depth.size_of();
+
+        if let Some((_, found, max)) =
+            self.options
+                .exceeded_limit(self.width, self.height, pixel_bytes, 1)
+        {
+            return Err(PPMDecodeErrors::LargeDimensions(max, found));
+        }
 
         Ok(())
     }
@@ -177,12 +201,10 @@ where
         self.width = self.get_integer()?;
 
         if self.width > self.options.max_width() {
-            let msg = format!(
-                "Width {} greater than max width {}",
-                self.width,
-                self.options.max_width()
-            );
-            return Err(PPMDecodeErrors::Generic(msg));
+            return Err(PPMDecodeErrors::LargeDimensions(
+                self.options.max_width(),
+                self.width
+            ));
         }
         // skip whitespace
         skip_spaces(&mut self.reader)?;
@@ -190,19 +212,17 @@ where
         self.height = self.get_integer()?;
 
         if self.height > self.options.max_height() {
-            let msg = format!(
-                "Height {} greater than max height {}",
-                self.width,
-                self.options.max_height()
-            );
-            return Err(PPMDecodeErrors::Generic(msg));
+            return Err(PPMDecodeErrors::LargeDimensions(
+                self.options.max_height(),
+                self.height
+            ));
         }
 
         trace!("Width: {}, height: {}", self.width, self.height);
 
         skip_spaces(&mut self.reader)?;
 
-        let mut byte_header = Vec::with_capacity(20);
+        let mut byte_header = [0; HEADER_TOKEN_SIZE];
 
         let value_size = get_bytes_until_whitespace(&mut self.reader, &mut byte_header)?;
         let value = &byte_header[..value_size];
@@ -241,7 +261,7 @@ where
         let mut seen_max_val = false;
         let mut seen_tuple_type = false;
 
-        let mut byte_header = Vec::with_capacity(20);
+        let mut byte_header = [0; HEADER_TOKEN_SIZE];
 
         'infinite: loop {
             if self.reader.eof()? {
@@ -374,12 +394,10 @@ where
         self.width = self.get_integer()?;
 
         if self.width > self.options.max_width() {
-            let msg = format!(
-                "Width {} greater than max width {}",
-                self.width,
-                self.options.max_width()
-            );
-            return Err(PPMDecodeErrors::Generic(msg));
+            return Err(PPMDecodeErrors::LargeDimensions(
+                self.options.max_width(),
+                self.width
+            ));
         }
         // skip whitespace
         skip_spaces(&mut self.reader)?;
@@ -387,12 +405,10 @@ where
         self.height = self.get_integer()?;
 
         if self.height > self.options.max_height() {
-            let msg = format!(
-                "Height {} greater than max height {}",
-                self.width,
-                self.options.max_height()
-            );
-            return Err(PPMDecodeErrors::Generic(msg));
+            return Err(PPMDecodeErrors::LargeDimensions(
+                self.options.max_height(),
+                self.height
+            ));
         }
 
         trace!("Width: {}, height: {}", self.width, self.height);
@@ -618,6 +634,159 @@ where
             _ => unreachable!()
         };
     }
+
+    /// Return the number of bytes needed to hold the whole image decoded by
+    /// [`decode_into`](Self::decode_into) or none if headers weren't decoded or the
+    /// size overflows a usize
+    pub fn output_buffer_size(&self) -> Option<usize> {
+        let (_, height) = self.dimensions()?;
+
+        self.row_bytes()?.checked_mul(height)
+    }
+
+    /// Decode the whole image into `out`, which must be at least
+    /// [`output_buffer_size`](Self::output_buffer_size) bytes
+    ///
+    /// Samples are in the layout [`decode_rows`](Self::decode_rows) uses, the bytes of
+    /// the values [`decode`](Self::decode) returns in native endian.
+    ///
+    /// This and [`decode_headers`](Self::decode_headers) don't allocate when decoding
+    /// succeeds, so they can be used on targets without a heap.
+    ///
+    /// # Example
+    /// ```
+    /// use zune_core::bytestream::ZCursor;
+    /// use zune_ppm::PPMDecoder;
+    /// // a 2 by 2 grayscale image
+    /// let mut decoder = PPMDecoder::new(ZCursor::new(b"P5 2 2 255 abcd"));
+    ///
+    /// decoder.decode_headers().unwrap();
+    /// let mut pixels = [0; 4];
+    ///
+    /// decoder.decode_into(&mut pixels).unwrap();
+    /// assert_eq!(&pixels, b"abcd");
+    /// ```
+    pub fn decode_into(&mut self, out: &mut [u8]) -> Result<(), PPMDecodeErrors> {
+        if !self.decoded_headers {
+            self.decode_headers()?;
+        }
+        let size = self
+            .output_buffer_size()
+            .ok_or(PPMDecodeErrors::GenericStatic("Image size overflows a usize"))?;
+
+        if out.len() < size {
+            return Err(PPMDecodeErrors::GenericStatic("Too small output buffer size"));
+        }
+        if self.decoded_rows != 0 {
+            return Err(PPMDecodeErrors::GenericStatic(
+                "Rows were already decoded with decode_rows"
+            ));
+        }
+        self.decode_rows(&mut out[..size])?;
+
+        Ok(())
+    }
+
+    /// Return the number of bytes of a row decoded by [`decode_rows`](Self::decode_rows)
+    /// or none if headers weren't decoded
+    pub fn row_bytes(&self) -> Option<usize> {
+        let (width, _) = self.dimensions()?;
+
+        Some(width * self.colorspace.num_components() * self.bit_depth.size_of())
+    }
+
+    /// Decode the next rows of the image into `out`
+    ///
+    /// As many whole rows as fit in `out`, [`row_bytes`](Self::row_bytes) each, are decoded
+    /// and the number of rows decoded is returned, which is zero once every row was.
+    /// This reads only the bytes of those rows, so memory use doesn't depend on
+    /// the image size.
+    ///
+    /// Samples are the bytes of the values [`decode`](Self::decode) returns, in native endian.
+    ///
+    /// PFM images store rows bottom to top, they are still returned top to bottom, which
+    /// needs the reader to seek backwards
+    ///
+    /// # Example
+    /// ```
+    /// use zune_core::bytestream::ZCursor;
+    /// use zune_ppm::PPMDecoder;
+    /// // a 2 by 2 grayscale image
+    /// let mut decoder = PPMDecoder::new(ZCursor::new(b"P5 2 2 255 abcd"));
+    ///
+    /// decoder.decode_headers().unwrap();
+    /// let mut row = vec![0; decoder.row_bytes().unwrap()];
+    ///
+    /// assert_eq!(decoder.decode_rows(&mut row).unwrap(), 1);
+    /// assert_eq!(&row, b"ab");
+    /// assert_eq!(decoder.decode_rows(&mut row).unwrap(), 1);
+    /// assert_eq!(&row, b"cd");
+    /// assert_eq!(decoder.decode_rows(&mut row).unwrap(), 0);
+    /// ```
+    pub fn decode_rows(&mut self, out: &mut [u8]) -> Result<usize, PPMDecodeErrors> {
+        if !self.decoded_headers {
+            self.decode_headers()?;
+        }
+        if self.width == 0 || self.height == 0 {
+            return Err(PPMDecodeErrors::GenericStatic(
+                "Zero dimensions not allowed"
+            ));
+        }
+        let row_bytes = self.row_bytes().unwrap();
+
+        if self.decoded_rows == 0 {
+            self.data_start = self.reader.position()?;
+        }
+        let mut decoded = 0;
+
+        for row in out.chunks_exact_mut(row_bytes) {
+            if self.decoded_rows == self.height {
+                break;
+            }
+            match self.bit_depth.bit_type() {
+                BitType::U8 => self.reader.read_exact_bytes(row)?,
+                BitType::U16 => {
+                    self.reader.read_exact_bytes(row)?;
+
+                    for sample in row.chunks_exact_mut(2) {
+                        let value = u16::from_be_bytes([sample[0], sample[1]]);
+                        sample.copy_from_slice(&value.to_ne_bytes());
+                    }
+                }
+                BitType::F32 => {
+                    // pfm uses bottom-top orientation
+                    let position = (self.height - 1 - self.decoded_rows) * row_bytes;
+                    self.reader
+                        .seek(ZSeekFrom::Start(self.data_start + position as u64))?;
+                    self.reader.read_exact_bytes(row)?;
+
+                    let is_le = self.options.byte_endian() == ByteEndian::LE;
+
+                    for sample in row.chunks_exact_mut(4) {
+                        let bytes = [sample[0], sample[1], sample[2], sample[3]];
+                        let value = if is_le {
+                            u32::from_le_bytes(bytes)
+                        } else {
+                            u32::from_be_bytes(bytes)
+                        };
+                        sample.copy_from_slice(&value.to_ne_bytes());
+                    }
+                }
+                _ => unreachable!()
+            }
+            self.decoded_rows += 1;
+            decoded += 1;
+        }
+        if decoded > 0
+            && self.decoded_rows == self.height
+            && self.bit_depth.bit_type() == BitType::F32
+        {
+            // leave the reader after the pixels, like decoding whole images does
+            let end = self.data_start + (self.height * row_bytes) as u64;
+            self.reader.seek(ZSeekFrom::Start(end))?;
+        }
+        Ok(decoded)
+    }
 }
 
 /// Skip all whitespace characters and comments
@@ -645,7 +814,10 @@ where
     Ok(())
 }
 
-/// Return a reference to all bytes preceding a whitespace.
+/// Read all bytes preceding a whitespace, and the whitespace, into `write_to`
+/// returning how many bytes were written.
+///
+/// Tokens longer than `write_to` are consumed but only their start is written
 ///
 /// # Note
 /// This skips all whitespaces after it finds one. That is the desired implementation
@@ -653,16 +825,19 @@ where
 /// # Panics
 /// If end < start
 fn get_bytes_until_whitespace<T: ZByteReaderTrait>(
-    z: &mut ZReader<T>, write_to: &mut Vec<u8>
+    z: &mut ZReader<T>, write_to: &mut [u8; HEADER_TOKEN_SIZE]
 ) -> Result<usize, PPMDecodeErrors> {
     let start = z.position()?;
     let mut end = start;
-    // clear out buffer for the next iteration
-    write_to.clear();
+    let mut read = 0;
 
     while !z.eof()? {
         let byte = z.read_u8();
-        write_to.push(byte);
+
+        if let Some(out) = write_to.get_mut(read) {
+            *out = byte;
+        }
+        read += 1;
 
         if byte.is_ascii_whitespace() {
             // mark where the text ends
@@ -671,8 +846,7 @@ fn get_bytes_until_whitespace<T: ZByteReaderTrait>(
             skip_spaces(z)?;
             break;
         }
-        // push the byte read
     }
     // z.skip(end - start);
-    Ok((end - start) as usize)
+    Ok(((end - start) as usize).min(HEADER_TOKEN_SIZE))
 }
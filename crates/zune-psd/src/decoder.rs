@@ -155,6 +155,15 @@ where
 
         self.depth = im_depth;
 
+        if let Some((_, found, max)) = self.options.exceeded_limit(
+            width,
+            height,
+            self.channel_count * im_depth.size_of(),
+            1
+        ) {
+            return Err(PSDDecodeErrors::LargeDimensions(max, found));
+        }
+
         let color_mode = self.stream.get_u16_be_err()?;
 
         let color_enum = ColorModes::from_int(color_mode);
@@ -6,7 +6,7 @@
  * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
  */
 
-use core::fmt::{Debug, Formatter};
+use core::fmt::{Debug, Display, Formatter};
 
 use zune_core::bytestream::ZByteIoError;
 
@@ -88,6 +88,14 @@ impl Debug for PSDDecodeErrors {
     }
 }
 
+impl Display for PSDDecodeErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl core::error::Error for PSDDecodeErrors {}
+
 impl From<&'static str> for PSDDecodeErrors {
     fn from(r: &'static str) -> Self {
         Self::Generic(r)
@@ -28,7 +28,9 @@ use py_functions::*;
 use py_image::*;
 use pyo3::prelude::*;
 
-use crate::py_enums::{ColorSpace, ImageDepth, ImageFormat, ImageThresholdType, ResizeMethod};
+use crate::py_enums::{
+    ColorSpace, ImageDepth, ImageFormat, ImageThresholdType, MirrorMode, ResizeMethod
+};
 
 mod py_enums;
 mod py_functions;
@@ -53,6 +55,8 @@ fn zune_image(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<ImageDepth>()?;
     m.add_class::<ImageThresholdType>()?;
     m.add_class::<ResizeMethod>()?;
+    m.add_class::<MirrorMode>()?;
+    m.add_class::<PixelBuffer>()?;
     m.add_function(wrap_pyfunction!(init_logger, m)?)?;
 
     m.add_function(wrap_pyfunction!(guess_format, m)?)?;
@@ -11,6 +11,7 @@ use zune_core::bit_depth::BitDepth;
 use zune_core::colorspace::ColorSpace as ZColorSpace;
 use zune_image::codecs::ImageFormat as ZImageFormat;
 use zune_image::errors::ImageErrors;
+use zune_imageprocs::mirror::MirrorMode as ZMirrorMode;
 use zune_imageprocs::resize::ResizeMethod as ZResizeMethod;
 use zune_imageprocs::threshold::ThresholdMethod;
 
@@ -217,3 +218,24 @@ impl From<ZResizeMethod> for ResizeMethod {
         }
     }
 }
+
+/// Which half of the image is reflected onto the other by `Image.mirror`
+#[pyclass]
+#[derive(Copy, Clone)]
+pub enum MirrorMode {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl MirrorMode {
+    pub(crate) fn to_mirror_mode(self) -> ZMirrorMode {
+        match self {
+            MirrorMode::North => ZMirrorMode::North,
+            MirrorMode::South => ZMirrorMode::South,
+            MirrorMode::East => ZMirrorMode::East,
+            MirrorMode::West => ZMirrorMode::West,
+        }
+    }
+}
@@ -5,29 +5,38 @@
  *
  * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
  */
+mod buffer;
 mod numpy_bindings;
 
 use std::any::TypeId;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 use numpy::{
     dtype_bound, Element, PyArray2, PyArray3, PyArrayDescrMethods, PyArrayMethods, PyUntypedArray,
     PyUntypedArrayMethods,
 };
-use pyo3::exceptions::PyException;
+use pyo3::exceptions::{PyException, PyValueError};
 use pyo3::prelude::*;
+use pyo3::types::PyBytes;
 use zune_core::bit_depth::BitType;
 use zune_core::bytestream::ZCursor;
 use zune_core::colorspace::ColorSpace as ZColorSpace;
 use zune_core::log::warn;
-use zune_core::options::DecoderOptions;
+use zune_core::options::{DecoderOptions, EncoderOptions};
 use zune_image::core_filters::colorspace::ColorspaceConv;
 use zune_image::core_filters::depth::Depth;
+use zune_image::errors::ImageErrors;
 use zune_image::image::Image as ZImage;
+use zune_image::metadata::{AlphaState, PixelLayout};
 use zune_image::traits::OperationsTrait;
 use zune_imageprocs::auto_orient::AutoOrient;
 use zune_imageprocs::bilateral_filter::BilateralFilter;
 use zune_imageprocs::blend::Blend;
 use zune_imageprocs::box_blur::BoxBlur;
+use zune_imageprocs::brighten::Brighten;
+use zune_imageprocs::contrast::Contrast;
+use zune_imageprocs::convolve::Convolve;
 use zune_imageprocs::crop::Crop;
 use zune_imageprocs::exposure::Exposure;
 use zune_imageprocs::flip::{Flip, FlipDirection};
@@ -36,16 +45,22 @@ use zune_imageprocs::gaussian_blur::GaussianBlur;
 use zune_imageprocs::hsv_adjust::HsvAdjust;
 use zune_imageprocs::invert::Invert;
 use zune_imageprocs::median::Median;
+use zune_imageprocs::mirror::Mirror;
+use zune_imageprocs::premul_alpha::PremultiplyAlpha;
 use zune_imageprocs::resize::Resize;
+use zune_imageprocs::rotate::Rotate;
 use zune_imageprocs::scharr::Scharr;
 use zune_imageprocs::sobel::Sobel;
 use zune_imageprocs::stretch_contrast::StretchContrast;
 use zune_imageprocs::threshold::Threshold;
 use zune_imageprocs::transpose::Transpose;
+use zune_imageprocs::unsharpen::Unsharpen;
 
 use crate::py_enums::{
-    ColorSpace, ImageDepth, ImageFormat, ImageThresholdType, ResizeMethod, ZImageErrors,
+    ColorSpace, ImageDepth, ImageFormat, ImageThresholdType, MirrorMode, ResizeMethod,
+    ZImageErrors
 };
+pub use crate::py_image::buffer::PixelBuffer;
 
 /// Execute a single filter on an image
 ///
@@ -56,8 +71,19 @@ use crate::py_enums::{
 fn exec_filter<T: OperationsTrait>(
     img: &mut Image, filter: T, in_place: bool,
 ) -> PyResult<Option<Image>> {
+    exec_with(img, in_place, |image| filter.execute(image))
+}
+
+/// Execute a function modifying the image either in place or on a copy
+///
+/// Running in place is refused while numpy views of the image are alive, since
+/// the function may reallocate the memory they point to
+fn exec_with<F>(img: &mut Image, in_place: bool, func: F) -> PyResult<Option<Image>>
+where
+    F: Fn(&mut ZImage) -> Result<(), ImageErrors>
+{
     let exec = |image: &mut Image| -> PyResult<()> {
-        if let Err(e) = filter.execute(&mut image.image) {
+        if let Err(e) = func(&mut image.image) {
             return Err(PyErr::new::<PyException, _>(format!(
                 "Error converting: {e:?}"
             )));
@@ -65,6 +91,12 @@ fn exec_filter<T: OperationsTrait>(
         Ok(())
     };
     if in_place {
+        let views = img.exports.load(Ordering::Acquire);
+        if views > 0 {
+            return Err(PyErr::new::<PyException, _>(format!(
+                "The image has {views} live numpy views, delete them before modifying the image in place or use in_place=False"
+            )));
+        }
         exec(img)?;
         Ok(None)
     } else {
@@ -95,15 +127,41 @@ fn exec_filter<T: OperationsTrait>(
 /// The library supports animated images from the following formats
 /// - png: Animated PNG: The images will be decoded and any blending done,
 /// - jpeg-xl: Animated JXL: Decoding is offloaded to the jxl crate, images are rendered to be individual frames
+///
+/// # Numpy views
+/// `to_numpy` returns a copy of the pixels, `view` and `channel_view` return numpy arrays
+/// pointing to the image memory, writes to them change the image.
+/// While views are alive, operations with `in_place=True` return an error.
 #[pyclass]
-#[derive(Clone)]
 pub struct Image {
-    image: ZImage,
+    image:   ZImage,
+    /// Number of buffers currently exported to python through views
+    exports: Arc<AtomicUsize>
 }
 
 impl Image {
     pub(crate) fn new(image: ZImage) -> Image {
-        Image { image }
+        Image {
+            image,
+            exports: Arc::new(AtomicUsize::new(0))
+        }
+    }
+}
+
+impl Clone for Image {
+    fn clone(&self) -> Self {
+        let mut image = self.image.clone();
+
+        if self.exports.load(Ordering::Acquire) > 0 {
+            // views write to the channels behind the image's back,
+            // so the clone can't share them
+            for frame in image.frames_mut() {
+                for channel in frame.channels_vec() {
+                    channel.make_unique();
+                }
+            }
+        }
+        Image::new(image)
     }
 }
 
@@ -208,11 +266,34 @@ impl Image {
     /// # Arguments
     ///  - file: Filename to save the file to
     ///  - format:  The format to save the file in
+    ///  - quality: Quality for lossy encoders, between 0 and 100, defaults to 80
+    ///  - effort: How much time the encoder spends compressing, between 0 and 9, defaults to 4
+    ///  - strip_metadata: Whether to leave out metadata like exif
+    ///  - progressive: Whether to write progressive jpeg images
     ///
     /// # Returns
     ///  - Nothing on success, or Exception  on error
-    pub fn save(&self, file: String, format: ImageFormat) -> PyResult<()> {
-        if let Err(e) = self.image.save_to(file, format.to_imageformat()) {
+    #[pyo3(signature = (file, format, quality = None, effort = None, strip_metadata = false, progressive = false))]
+    pub fn save(
+        &self, file: String, format: ImageFormat, quality: Option<u8>, effort: Option<u8>,
+        strip_metadata: bool, progressive: bool
+    ) -> PyResult<()> {
+        let options = encoder_options(quality, effort, strip_metadata, progressive);
+
+        let result = std::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(file)
+            .map_err(ImageErrors::from)
+            .and_then(|file| {
+                let mut writer = std::io::BufWriter::new(file);
+                format
+                    .to_imageformat()
+                    .encode(&self.image, options, &mut writer)
+            });
+
+        if let Err(e) = result {
             return Err(PyErr::new::<PyException, _>(format!(
                 "Error encoding: {e:?}"
             )));
@@ -220,6 +301,33 @@ impl Image {
         Ok(())
     }
 
+    /// Encode an image to bytes
+    ///
+    /// # Arguments
+    ///  - format:  The format to encode the image in
+    ///  - quality, effort, strip_metadata, progressive: Encoder options, see `save`
+    ///
+    /// # Returns
+    ///  - The encoded bytes on success, or Exception  on error
+    #[pyo3(signature = (format, quality = None, effort = None, strip_metadata = false, progressive = false))]
+    pub fn encode<'py>(
+        &self, py: Python<'py>, format: ImageFormat, quality: Option<u8>, effort: Option<u8>,
+        strip_metadata: bool, progressive: bool
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        let options = encoder_options(quality, effort, strip_metadata, progressive);
+        let mut sink = vec![];
+
+        if let Err(e) = format
+            .to_imageformat()
+            .encode(&self.image, options, &mut sink)
+        {
+            return Err(PyErr::new::<PyException, _>(format!(
+                "Error encoding: {e:?}"
+            )));
+        }
+        Ok(PyBytes::new_bound(py, &sink))
+    }
+
     /// Resize an image
     ///
     /// # Arguments
@@ -462,7 +570,7 @@ impl Image {
     /// - in-place: Whether to carry the operation in place or clone and operate on the copy
     #[pyo3(signature = (in_place = false))]
     pub fn sobel(&mut self, in_place: bool) -> PyResult<Option<Image>> {
-        exec_filter(self, Sobel, in_place)
+        exec_filter(self, Sobel::new(), in_place)
     }
     /// Calculate the scharr derivative of an image
     ///
@@ -486,7 +594,7 @@ impl Image {
     /// - in-place: Whether to carry the operation in place or clone and operate on the copy
     #[pyo3(signature = (in_place = false))]
     pub fn scharr(&mut self, in_place: bool) -> PyResult<Option<Image>> {
-        exec_filter(self, Scharr, in_place)
+        exec_filter(self, Scharr::new(), in_place)
     }
 
     /// Linearly stretches the contrast in an image in place,
@@ -648,6 +756,202 @@ impl Image {
         let filter = Median::new(radius);
         exec_filter(self, filter, in_place)
     }
+
+    /// Brighten or darken an image
+    ///
+    /// # Arguments
+    /// - value: Value added to each pixel, for 8 and 16 bit images this is between -1.0 and 1.0
+    /// and is scaled to the maximum value of the depth, negative values darken the image
+    ///
+    /// # Returns
+    ///  - If `in_place=True`: Nothing on success, on error returns error that occurred
+    ///  - If `in_place=False`: An image copy on success on error, returns error that occurred
+    #[pyo3(signature = (value, in_place = false))]
+    pub fn brighten(&mut self, value: f32, in_place: bool) -> PyResult<Option<Image>> {
+        exec_filter(self, Brighten::new(value), in_place)
+    }
+
+    /// Adjust the contrast of an image
+    ///
+    /// # Arguments
+    /// - contrast: Contrast adjustment, between -255.0 and 255.0, positive values
+    /// increase contrast and negative values reduce it
+    ///
+    /// This is currently only implemented for 8 bit images
+    ///
+    /// # Returns
+    ///  - If `in_place=True`: Nothing on success, on error returns error that occurred
+    ///  - If `in_place=False`: An image copy on success on error, returns error that occurred
+    #[pyo3(signature = (contrast, in_place = false))]
+    pub fn contrast(&mut self, contrast: f32, in_place: bool) -> PyResult<Option<Image>> {
+        exec_filter(self, Contrast::new(contrast), in_place)
+    }
+
+    /// Rotate an image
+    ///
+    /// # Arguments
+    /// - angle: Angle in degrees, currently only 90, 180 and 270 are supported
+    ///
+    /// # Returns
+    ///  - If `in_place=True`: Nothing on success, on error returns error that occurred
+    ///  - If `in_place=False`: An image copy on success on error, returns error that occurred
+    #[pyo3(signature = (angle, in_place = false))]
+    pub fn rotate(&mut self, angle: f32, in_place: bool) -> PyResult<Option<Image>> {
+        exec_filter(self, Rotate::new(angle), in_place)
+    }
+
+    /// Sharpen an image using an unsharp mask
+    ///
+    /// # Arguments
+    /// - sigma: Strength of the gaussian blur used to create the mask
+    /// - threshold: Minimum difference between the blurred and the original pixel for it to be sharpened
+    /// - percentage: How much of the difference is added back
+    ///
+    /// # Returns
+    ///  - If `in_place=True`: Nothing on success, on error returns error that occurred
+    ///  - If `in_place=False`: An image copy on success on error, returns error that occurred
+    #[pyo3(signature = (sigma, threshold = 0, percentage = 50, in_place = false))]
+    pub fn unsharpen(
+        &mut self, sigma: f32, threshold: u16, percentage: u8, in_place: bool
+    ) -> PyResult<Option<Image>> {
+        exec_filter(self, Unsharpen::new(sigma, threshold, percentage), in_place)
+    }
+
+    /// Convolve an image with a 3x3, 5x5 or 7x7 kernel
+    ///
+    /// # Arguments
+    /// - weights: The kernel weights in row-major order, should have 9, 25 or 49 elements
+    /// - scale: Value each pixel is multiplied by after the kernel is applied, e.g
+    /// `1/sum(weights)` to keep brightness the same
+    ///
+    /// # Returns
+    ///  - If `in_place=True`: Nothing on success, on error returns error that occurred
+    ///  - If `in_place=False`: An image copy on success on error, returns error that occurred
+    #[pyo3(signature = (weights, scale = 1.0, in_place = false))]
+    pub fn convolve(
+        &mut self, weights: Vec<f32>, scale: f32, in_place: bool
+    ) -> PyResult<Option<Image>> {
+        exec_filter(self, Convolve::new(weights, scale), in_place)
+    }
+
+    /// Mirror the image along an axis
+    ///
+    /// # Arguments
+    /// - mode: Which half of the image is reflected onto the other
+    ///
+    /// # Returns
+    ///  - If `in_place=True`: Nothing on success, on error returns error that occurred
+    ///  - If `in_place=False`: An image copy on success on error, returns error that occurred
+    #[pyo3(signature = (mode, in_place = false))]
+    pub fn mirror(&mut self, mode: MirrorMode, in_place: bool) -> PyResult<Option<Image>> {
+        exec_filter(self, Mirror::new(mode.to_mirror_mode()), in_place)
+    }
+
+    /// Multiply the color channels by the alpha channel
+    ///
+    /// This is a no-op for images without an alpha channel or with already
+    /// pre-multiplied alpha
+    #[pyo3(signature = (in_place = false))]
+    pub fn premultiply_alpha(&mut self, in_place: bool) -> PyResult<Option<Image>> {
+        exec_filter(
+            self,
+            PremultiplyAlpha::new(AlphaState::PreMultiplied),
+            in_place
+        )
+    }
+
+    /// Divide the color channels by the alpha channel, undoing `premultiply_alpha`
+    ///
+    /// This is a no-op for images without an alpha channel or with straight alpha
+    #[pyo3(signature = (in_place = false))]
+    pub fn unpremultiply_alpha(&mut self, in_place: bool) -> PyResult<Option<Image>> {
+        exec_filter(
+            self,
+            PremultiplyAlpha::new(AlphaState::NonPreMultiplied),
+            in_place
+        )
+    }
+
+    /// Store the channels of each frame interleaved, i.e `RGBRGBRGB` instead of
+    /// separate `RRR`,`GGG`,`BBB` planes
+    ///
+    /// This is what `view` needs to show all channels of an image
+    /// without copying, operations convert the image back to separate planes when they need to
+    ///
+    /// # Returns
+    ///  - If `in_place=True`: Nothing on success, on error returns error that occurred
+    ///  - If `in_place=False`: An image copy on success on error, returns error that occurred
+    #[pyo3(signature = (in_place = false))]
+    pub fn interleave(&mut self, in_place: bool) -> PyResult<Option<Image>> {
+        exec_with(self, in_place, |image| {
+            image.convert_layout(PixelLayout::Interleaved)
+        })
+    }
+
+    /// Return a numpy array pointing to the image pixels without copying them
+    ///
+    /// The array has the same shape and dtype as `to_numpy`, writes to it modify the image.
+    ///
+    /// This works for single channel images and interleaved images, for other images
+    /// call `image.interleave(in_place=True)` first or use `channel_view`.
+    ///
+    /// The array keeps the image alive, while it exists the image can't be
+    /// modified in place.
+    ///
+    /// # Arguments
+    /// - frame: The frame to view, for animated images
+    #[pyo3(signature = (frame = 0))]
+    pub fn view<'py>(slf: &Bound<'py, Self>, frame: usize) -> PyResult<Bound<'py, PyAny>> {
+        Image::make_view(slf, frame, None)
+    }
+
+    /// Return a 2-D numpy array of `[height,width]` pointing to one channel of the image
+    /// without copying it
+    ///
+    /// Writes to the array modify the image. The image should not be interleaved.
+    ///
+    /// # Arguments
+    /// - channel: The channel index, e.g 0 for R in an RGB image
+    /// - frame: The frame to view, for animated images
+    #[pyo3(signature = (channel, frame = 0))]
+    pub fn channel_view<'py>(
+        slf: &Bound<'py, Self>, channel: usize, frame: usize
+    ) -> PyResult<Bound<'py, PyAny>> {
+        Image::make_view(slf, frame, Some(channel))
+    }
+
+    /// Support for `numpy.asarray(image)`, this returns a copy, see `to_numpy`
+    #[pyo3(signature = (dtype = None, copy = None))]
+    pub fn __array__<'py>(
+        &self, py: Python<'py>, dtype: Option<&Bound<'py, PyAny>>, copy: Option<bool>
+    ) -> PyResult<Bound<'py, PyAny>> {
+        if copy == Some(false) {
+            return Err(PyValueError::new_err(
+                "Converting an image to an array always copies, use image.view() for an array without copies"
+            ));
+        }
+        let array = self.to_numpy(py)?;
+
+        match dtype {
+            Some(dtype) => array.call_method1("astype", (dtype,)),
+            None => Ok(array)
+        }
+    }
+}
+
+impl Image {
+    fn make_view<'py>(
+        slf: &Bound<'py, Self>, frame: usize, channel: Option<usize>
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let py = slf.py();
+        let exports = slf.borrow().exports.clone();
+        let buffer = Bound::new(
+            py,
+            PixelBuffer::new(slf.clone().unbind(), exports, frame, channel)
+        )?;
+
+        py.import_bound("numpy")?.call_method1("asarray", (buffer,))
+    }
 }
 
 #[allow(clippy::cast_possible_truncation)]
@@ -807,37 +1111,27 @@ pub fn from_numpy(
             if d_type.is_equiv_to(&dtype_bound::<u8>(py)) {
                 let c: &Bound<'_, PyArray2<u8>> = array.downcast()?;
                 // single dimension
-                return Ok(Image {
-                    image: convert_2d(c)?,
-                });
+                return Ok(Image::new(convert_2d(c)?));
             }
             if d_type.is_equiv_to(&dtype_bound::<u16>(py)) {
                 let c: &Bound<'_, PyArray2<u16>> = array.downcast()?;
                 // single dimension
-                return Ok(Image {
-                    image: convert_2d(c)?,
-                });
+                return Ok(Image::new(convert_2d(c)?));
             }
             if d_type.is_equiv_to(&dtype_bound::<f32>(py)) {
                 let c: &Bound<'_, PyArray2<f32>> = array.downcast()?;
                 // single dimension
-                return Ok(Image {
-                    image: convert_2d(c)?,
-                });
+                return Ok(Image::new(convert_2d(c)?));
             }
             if d_type.is_equiv_to(&dtype_bound::<f64>(py)) {
                 let c: &Bound<'_, PyArray2<f64>> = array.downcast()?;
                 // single dimension
-                return Ok(Image {
-                    image: convert_2d(c)?,
-                });
+                return Ok(Image::new(convert_2d(c)?));
             }
             if d_type.is_equiv_to(&dtype_bound::<u32>(py)) {
                 let c: &Bound<'_, PyArray2<u32>> = array.downcast()?;
                 // single dimension
-                return Ok(Image {
-                    image: convert_2d(c)?,
-                });
+                return Ok(Image::new(convert_2d(c)?));
             }
         }
 
@@ -845,37 +1139,27 @@ pub fn from_numpy(
             if d_type.is_equiv_to(&dtype_bound::<u8>(py)) {
                 let c: &Bound<'_, PyArray3<u8>> = array.downcast()?;
                 // single dimension
-                return Ok(Image {
-                    image: convert_3d(c, colorspace)?,
-                });
+                return Ok(Image::new(convert_3d(c, colorspace)?));
             }
             if d_type.is_equiv_to(&dtype_bound::<u16>(py)) {
                 let c: &Bound<'_, PyArray3<u16>> = array.downcast()?;
                 // single dimension
-                return Ok(Image {
-                    image: convert_3d(c, colorspace)?,
-                });
+                return Ok(Image::new(convert_3d(c, colorspace)?));
             }
             if d_type.is_equiv_to(&dtype_bound::<f32>(py)) {
                 let c: &Bound<'_, PyArray3<f32>> = array.downcast()?;
                 // single dimension
-                return Ok(Image {
-                    image: convert_3d(c, colorspace)?,
-                });
+                return Ok(Image::new(convert_3d(c, colorspace)?));
             }
             if d_type.is_equiv_to(&dtype_bound::<f64>(py)) {
                 let c: &Bound<'_, PyArray3<f64>> = array.downcast()?;
                 // single dimension
-                return Ok(Image {
-                    image: convert_3d(c, colorspace)?,
-                });
+                return Ok(Image::new(convert_3d(c, colorspace)?));
             }
             if d_type.is_equiv_to(&dtype_bound::<u32>(py)) {
                 let c: &Bound<'_, PyArray3<u32>> = array.downcast()?;
                 // single dimension
-                return Ok(Image {
-                    image: convert_3d(c, colorspace)?,
-                });
+                return Ok(Image::new(convert_3d(c, colorspace)?));
             }
         }
         Err(PyErr::new::<PyException, _>(format!(
@@ -884,6 +1168,22 @@ pub fn from_numpy(
     });
 }
 
+fn encoder_options(
+    quality: Option<u8>, effort: Option<u8>, strip_metadata: bool, progressive: bool
+) -> EncoderOptions {
+    let mut options = EncoderOptions::default()
+        .set_strip_metadata(strip_metadata)
+        .set_jpeg_encode_progressive(progressive);
+
+    if let Some(quality) = quality {
+        options = options.set_quality(quality);
+    }
+    if let Some(effort) = effort {
+        options = options.set_effort(effort);
+    }
+    options
+}
+
 pub fn decode_image(bytes: &[u8]) -> PyResult<Image> {
     let im_result = ZImage::read(ZCursor::new(bytes), DecoderOptions::new_fast());
     match im_result {
@@ -0,0 +1,229 @@
+/*
+ * Copyright (c) 2023.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Zero copy numpy views over image pixels
+//!
+//! Numpy arrays are created over a [`PixelBuffer`] using the python buffer protocol,
+//! the array points to the memory of an image channel instead of a copy of it.
+//!
+//! While a view is alive the image counts it in its export counter, operations
+//! that would move the channel memory (anything running with `in_place=True`) refuse
+//! to run until all views are released.
+use std::ffi::c_int;
+use std::ptr::null_mut;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use pyo3::exceptions::{PyBufferError, PyException};
+use pyo3::prelude::*;
+use pyo3::{ffi, PyErr};
+use zune_core::bit_depth::BitType;
+use zune_image::metadata::PixelLayout;
+
+use crate::py_image::Image;
+
+/// A python buffer over the pixels of an image frame.
+///
+/// This isn't meant to be used directly, see `Image.view` and `Image.channel_view`
+/// which wrap it in a numpy array.
+#[pyclass]
+pub struct PixelBuffer {
+    image:   Py<Image>,
+    frame:   usize,
+    /// The channel exposed, `None` exposes all channels which requires
+    /// an interleaved or a single channel image
+    channel: Option<usize>,
+    /// The export counter of the image, kept here so releasing a buffer
+    /// doesn't need to borrow the image
+    exports: Arc<AtomicUsize>,
+    shape:   [isize; 3],
+    strides: [isize; 3]
+}
+
+impl PixelBuffer {
+    pub(crate) fn new(
+        image: Py<Image>, exports: Arc<AtomicUsize>, frame: usize, channel: Option<usize>
+    ) -> PixelBuffer {
+        PixelBuffer {
+            image,
+            frame,
+            channel,
+            exports,
+            shape: [0; 3],
+            strides: [0; 3]
+        }
+    }
+}
+
+/// Where the pixels of an exported buffer live and how they are arranged
+struct ExportedPixels {
+    ptr:       *mut u8,
+    len:       usize,
+    item_size: usize,
+    format:    &'static [u8],
+    ndim:      usize
+}
+
+impl Image {
+    /// Return a pointer to the pixels of a frame or one of its channels, filling in
+    /// the numpy shape and strides describing them
+    ///
+    /// This makes the channel unique, so writes through the pointer are not
+    /// seen by clones of the image
+    #[allow(clippy::cast_possible_wrap)]
+    fn export_pixels(
+        &mut self, frame: usize, channel: Option<usize>, shape: &mut [isize; 3],
+        strides: &mut [isize; 3]
+    ) -> PyResult<ExportedPixels> {
+        let (width, height) = self.image.dimensions();
+        let colorspace = self.image.colorspace();
+        let layout = self.image.layout();
+        let components = colorspace.num_components();
+        let bit_type = self.image.depth().bit_type();
+
+        let frames_len = self.image.frames_len();
+        if frame >= frames_len {
+            return Err(PyErr::new::<PyException, _>(format!(
+                "Frame {frame} is out of bounds, the image has {frames_len} frames"
+            )));
+        }
+
+        let (index, ndim) = match (channel, layout) {
+            (Some(c), PixelLayout::Planar) if c < components => (c, 2),
+            (Some(c), PixelLayout::Planar) => {
+                return Err(PyErr::new::<PyException, _>(format!(
+                    "Channel {c} is out of bounds, the image colorspace {colorspace:?} has {components} channels"
+                )));
+            }
+            (Some(_), PixelLayout::Interleaved) => {
+                return Err(PyErr::new::<PyException, _>(
+                    "The image is interleaved, its channels have no separate memory, use view() instead"
+                ));
+            }
+            (None, PixelLayout::Interleaved) => (0, 3),
+            (None, PixelLayout::Planar) if components == 1 => (0, 2),
+            (None, PixelLayout::Planar) => {
+                return Err(PyErr::new::<PyException, _>(
+                    "The image stores each channel separately, call interleave(in_place=True) first or use channel_view()"
+                ));
+            }
+        };
+
+        let channel = &mut self.image.frames_mut()[frame].channels_vec()[index];
+
+        // reinterpreting mutably makes the channel unique, so clones don't see
+        // writes made through the buffer
+        let (ptr, item_size, format): (*mut u8, usize, &'static [u8]) = match bit_type {
+            BitType::U8 => (
+                channel
+                    .reinterpret_as_mut::<u8>()
+                    .map_err(|x| PyErr::new::<PyException, _>(format!("{x:?}")))?
+                    .as_mut_ptr(),
+                1,
+                b"B\0"
+            ),
+            BitType::U16 => (
+                channel
+                    .reinterpret_as_mut::<u16>()
+                    .map_err(|x| PyErr::new::<PyException, _>(format!("{x:?}")))?
+                    .as_mut_ptr()
+                    .cast(),
+                2,
+                b"H\0"
+            ),
+            BitType::F32 => (
+                channel
+                    .reinterpret_as_mut::<f32>()
+                    .map_err(|x| PyErr::new::<PyException, _>(format!("{x:?}")))?
+                    .as_mut_ptr()
+                    .cast(),
+                4,
+                b"f\0"
+            ),
+            d => {
+                return Err(PyErr::new::<PyException, _>(format!(
+                    "Cannot create a view of depth {d:?}"
+                )));
+            }
+        };
+        let len = channel.len();
+
+        // dimensions fit since the channel allocation holds them
+        let item = item_size as isize;
+        if ndim == 3 {
+            let c = components as isize;
+            *shape = [height as isize, width as isize, c];
+            *strides = [width as isize * c * item, c * item, item];
+        } else {
+            *shape = [height as isize, width as isize, 0];
+            *strides = [width as isize * item, item, 0];
+        }
+
+        Ok(ExportedPixels {
+            ptr,
+            len,
+            item_size,
+            format,
+            ndim
+        })
+    }
+}
+
+#[pymethods]
+impl PixelBuffer {
+    #[allow(clippy::cast_possible_wrap, clippy::cast_possible_truncation)]
+    unsafe fn __getbuffer__(
+        slf: &Bound<'_, Self>, view: *mut ffi::Py_buffer, flags: c_int
+    ) -> PyResult<()> {
+        if view.is_null() {
+            return Err(PyBufferError::new_err("View is null"));
+        }
+        let py = slf.py();
+        let mut this = slf.borrow_mut();
+        let this = &mut *this;
+
+        let mut image = this.image.try_borrow_mut(py).map_err(|_| {
+            PyBufferError::new_err("The image is in use and cannot be viewed right now")
+        })?;
+
+        let pixels =
+            image.export_pixels(this.frame, this.channel, &mut this.shape, &mut this.strides)?;
+        this.exports.fetch_add(1, Ordering::AcqRel);
+
+        // the buffer keeps itself, and through it the image, alive
+        (*view).obj = slf.clone().into_any().into_ptr();
+        (*view).buf = pixels.ptr.cast();
+        (*view).len = pixels.len as isize;
+        (*view).readonly = 0;
+        (*view).itemsize = pixels.item_size as isize;
+        (*view).format = if flags & ffi::PyBUF_FORMAT == ffi::PyBUF_FORMAT {
+            pixels.format.as_ptr() as *mut _
+        } else {
+            null_mut()
+        };
+        (*view).ndim = pixels.ndim as c_int;
+        (*view).shape = if flags & ffi::PyBUF_ND == ffi::PyBUF_ND {
+            this.shape.as_mut_ptr()
+        } else {
+            null_mut()
+        };
+        (*view).strides = if flags & ffi::PyBUF_STRIDES == ffi::PyBUF_STRIDES {
+            this.strides.as_mut_ptr()
+        } else {
+            null_mut()
+        };
+        (*view).suboffsets = null_mut();
+        (*view).internal = null_mut();
+
+        Ok(())
+    }
+
+    unsafe fn __releasebuffer__(&self, _view: *mut ffi::Py_buffer) {
+        self.exports.fetch_sub(1, Ordering::AcqRel);
+    }
+}
@@ -9,11 +9,31 @@
 use numpy::{PyArray2, PyArray3, PyArray4, PyArrayMethods};
 use pyo3::exceptions::PyException;
 use pyo3::{Bound, PyAny, PyErr, PyResult, Python};
+use zune_core::colorspace::ColorSpace;
+use zune_image::frame::Frame;
+use zune_image::metadata::PixelLayout;
 use zune_image::utils::swizzle_channels;
 
 use crate::py_enums::ImageDepth;
 use crate::py_image::Image;
 
+/// Write the pixels of a frame interleaved into `output`
+fn interleave_frame<T: Copy + Default + 'static>(
+    frame: &Frame, colorspace: ColorSpace, layout: PixelLayout, output: &mut [T]
+) -> PyResult<()> {
+    if layout == PixelLayout::Interleaved {
+        // already interleaved, just copy
+        let pixels = frame.channels_vec_ref()[0]
+            .reinterpret_as()
+            .map_err(|x| PyErr::new::<PyException, _>(format!("{x:?}")))?;
+        output.copy_from_slice(pixels);
+        return Ok(());
+    }
+    swizzle_channels(frame.channels_ref(colorspace, false), output)
+        .map_err(|x| PyErr::new::<PyException, _>(format!("{x:?}")))?;
+    Ok(())
+}
+
 impl Image {
     pub(crate) fn to_numpy_generic<'py, T>(
         &self, py: Python<'py>, expected: ImageDepth
@@ -22,6 +42,7 @@ impl Image {
         T: Copy + Default + 'static + numpy::Element + Send
     {
         let colorspace = self.image.colorspace();
+        let layout = self.image.layout();
 
         // handle animated images
         return if self.image.is_animated() {
@@ -49,8 +70,7 @@ impl Image {
                 .chunks_exact_mut(single_im_frame_dims)
                 .zip(self.image.frames_ref())
             {
-                swizzle_channels(frame.channels_ref(colorspace, false), im_chunk)
-                    .map_err(|x| PyErr::new::<PyException, _>(format!("{x:?}")))?;
+                interleave_frame(frame, colorspace, layout, im_chunk)?;
             }
 
             Ok(arr.into_any())
@@ -84,8 +104,8 @@ impl Image {
                 };
 
                 //obtain first channel
-                let channels = self.image.frames_ref()[0].channels_ref(colorspace, false);
-                for chan in channels {
+                let frame = &self.image.frames_ref()[0];
+                for chan in frame.channels_vec_ref() {
                     if chan.reinterpret_as::<T>().is_err() {
                         return Err(PyErr::new::<PyException, _>(format!(
                             "The image depth {:?} is not u8 use image.convert_depth({:?}) to convert to 8 bit \nWe do not implicitly convert to desired depth", self.image.depth(), expected
@@ -108,8 +128,7 @@ impl Image {
                     .expect("This should be safe as we own the array and haven't exposed it");
                 let pix_values = arr_v.as_slice_mut().unwrap();
 
-                swizzle_channels(channels, pix_values)
-                    .map_err(|x| PyErr::new::<PyException, _>(format!("{x:?}")))?;
+                interleave_frame(frame, colorspace, layout, pix_values)?;
 
                 arr
             };
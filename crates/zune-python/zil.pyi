@@ -20,10 +20,32 @@ class ResizeMethod(enum.Enum):
     Bilinear = ...
     Bicubic = ...
 
+class MirrorMode(enum.Enum):
+    North = ...
+    South = ...
+    East = ...
+    West = ...
+
 class Image:
     @staticmethod
     def open(file: str) -> Image: ...
-    def save(self, file: str, *, format: ImageFormat) -> None: ...
+    def save(
+        self,
+        file: str,
+        format: ImageFormat,
+        quality: int | None = None,
+        effort: int | None = None,
+        strip_metadata: bool = False,
+        progressive: bool = False,
+    ) -> None: ...
+    def encode(
+        self,
+        format: ImageFormat,
+        quality: int | None = None,
+        effort: int | None = None,
+        strip_metadata: bool = False,
+        progressive: bool = False,
+    ) -> bytes: ...
     def dimensions(self) -> tuple[int, int]: ...
     def width(self) -> int: ...
     def height(self) -> int: ...
@@ -64,4 +86,61 @@ class Image:
     def crop(
         self, width: int, height: int, x: int, y: int, *, in_place: bool
     ) -> Image | None: ...
+    @overload
+    def brighten(self, value: float, *, in_place: Literal[False] = ...) -> Image: ...
+    @overload
+    def brighten(self, value: float, *, in_place: Literal[True]) -> None: ...
+    @overload
+    def contrast(self, contrast: float, *, in_place: Literal[False] = ...) -> Image: ...
+    @overload
+    def contrast(self, contrast: float, *, in_place: Literal[True]) -> None: ...
+    @overload
+    def rotate(self, angle: float, *, in_place: Literal[False] = ...) -> Image: ...
+    @overload
+    def rotate(self, angle: float, *, in_place: Literal[True]) -> None: ...
+    @overload
+    def unsharpen(
+        self,
+        sigma: float,
+        threshold: int = 0,
+        percentage: int = 50,
+        *,
+        in_place: Literal[False] = ...,
+    ) -> Image: ...
+    @overload
+    def unsharpen(
+        self,
+        sigma: float,
+        threshold: int = 0,
+        percentage: int = 50,
+        *,
+        in_place: Literal[True],
+    ) -> None: ...
+    @overload
+    def convolve(
+        self, weights: list[float], scale: float = 1.0, *, in_place: Literal[False] = ...
+    ) -> Image: ...
+    @overload
+    def convolve(
+        self, weights: list[float], scale: float = 1.0, *, in_place: Literal[True]
+    ) -> None: ...
+    @overload
+    def mirror(self, mode: MirrorMode, *, in_place: Literal[False] = ...) -> Image: ...
+    @overload
+    def mirror(self, mode: MirrorMode, *, in_place: Literal[True]) -> None: ...
+    @overload
+    def premultiply_alpha(self, *, in_place: Literal[False] = ...) -> Image: ...
+    @overload
+    def premultiply_alpha(self, *, in_place: Literal[True]) -> None: ...
+    @overload
+    def unpremultiply_alpha(self, *, in_place: Literal[False] = ...) -> Image: ...
+    @overload
+    def unpremultiply_alpha(self, *, in_place: Literal[True]) -> None: ...
+    @overload
+    def interleave(self, *, in_place: Literal[False] = ...) -> Image: ...
+    @overload
+    def interleave(self, *, in_place: Literal[True]) -> None: ...
     def to_numpy(self) -> NDArray[np.uint8]: ...
+    def view(self, frame: int = 0) -> NDArray[np.uint8]: ...
+    def channel_view(self, channel: int, frame: int = 0) -> NDArray[np.uint8]: ...
+    def __array__(self, dtype: np.dtype | None = None, copy: bool | None = None) -> NDArray[np.uint8]: ...
@@ -49,7 +49,28 @@ where
     colorspace_layout: QoiColorspace,
     decoded_headers:   bool,
     stream:            ZReader<T>,
-    options:           DecoderOptions
+    options:           DecoderOptions,
+    state:             QoiState,
+    decoded_rows:      usize
+}
+
+/// What a QOI stream refers to from previous pixels, kept between
+/// calls to `decode_rows`
+struct QoiState {
+    index: [[u8; 4]; 64],
+    px:    [u8; 4],
+    run:   usize
+}
+
+impl Default for QoiState {
+    fn default() -> Self {
+        QoiState {
+            index: [[0_u8; 4]; 64],
+            // starting pixel
+            px:    [0, 0, 0, 255],
+            run:   0
+        }
+    }
 }
 
 impl<T> QoiDecoder<T>
@@ -102,7 +123,9 @@ where
             colorspace_layout: QoiColorspace::Linear,
             decoded_headers:   false,
             stream:            ZReader::new(data),
-            options:           options
+            options:           options,
+            state:             QoiState::default(),
+            decoded_rows:      0
         }
     }
     /// Decode a QOI header storing needed information into
@@ -168,6 +191,13 @@ where
                 }
             }
         };
+        if let Some((limit, found, max)) =
+            self.options
+                .exceeded_limit(width, height, self.colorspace.num_components(), 1)
+        {
+            let msg = format!("Image {limit} {found} greater than max configured {limit} {max}");
+            return Err(QoiErrors::Generic(msg));
+        }
         self.width = width;
         self.height = height;
 
@@ -236,6 +266,11 @@ where
     /// pixels
     ///
     /// returns: Result<(), QoiErrors>
+    ///
+    /// # Allocations
+    /// Neither this nor [`decode_headers`](Self::decode_headers) allocate when
+    /// decoding succeeds, only errors allocate their messages,
+    /// so they can be used on targets without a heap.
     #[allow(clippy::identity_op)]
     pub fn decode_into(&mut self, pixels: &mut [u8]) -> Result<(), QoiErrors> {
         if !self.decoded_headers {
@@ -249,23 +284,78 @@ where
             ));
         }
 
+        self.decode_pixels(&mut pixels[..self.output_buffer_size().unwrap()]);
+        self.decoded_rows = self.height;
+
+        self.check_end()
+    }
+
+    /// Return the number of bytes of a row decoded by [`decode_rows`](Self::decode_rows)
+    /// or none if headers weren't decoded
+    pub fn row_bytes(&self) -> Option<usize> {
+        let (width, _) = self.dimensions()?;
+
+        width.checked_mul(self.colorspace.num_components())
+    }
+
+    /// Decode the next rows of the image into `out`
+    ///
+    /// As many whole rows as fit in `out`, [`row_bytes`](Self::row_bytes) each, are decoded,
+    /// in the same layout as [`decode_into`](Self::decode_into), and the number of rows
+    /// decoded is returned, which is zero once every row was.
+    ///
+    /// Only the pixels of those rows are read and decoded, so memory use doesn't depend
+    /// on the image size.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use zune_core::bytestream::ZCursor;
+    /// use zune_qoi::QoiDecoder;
+    ///
+    /// let file = std::fs::read("image.qoi").unwrap();
+    /// let mut decoder = QoiDecoder::new(ZCursor::new(file));
+    ///
+    /// decoder.decode_headers().unwrap();
+    /// let mut rows = vec![0; decoder.row_bytes().unwrap() * 16];
+    ///
+    /// while decoder.decode_rows(&mut rows).unwrap() > 0 {
+    ///     // send the rows on
+    /// }
+    /// ```
+    pub fn decode_rows(&mut self, out: &mut [u8]) -> Result<usize, QoiErrors> {
+        if !self.decoded_headers {
+            self.decode_headers()?;
+        }
+        let row_bytes = self.row_bytes().unwrap();
+
+        if row_bytes == 0 || self.decoded_rows == self.height {
+            return Ok(0);
+        }
+        let rows = (out.len() / row_bytes).min(self.height - self.decoded_rows);
+
+        self.decode_pixels(&mut out[..rows * row_bytes]);
+        self.decoded_rows += rows;
+
+        if rows > 0 && self.decoded_rows == self.height {
+            self.check_end()?;
+        }
+        Ok(rows)
+    }
+
+    fn decode_pixels(&mut self, pixels: &mut [u8]) {
         match self.colorspace.num_components() {
-            3 => self.decode_inner_generic::<3>(pixels)?,
-            4 => self.decode_inner_generic::<4>(pixels)?,
+            3 => self.decode_inner_generic::<3>(pixels),
+            4 => self.decode_inner_generic::<4>(pixels),
             _ => unreachable!()
         }
-        Ok(())
     }
-    fn decode_inner_generic<const SIZE: usize>(
-        &mut self, pixels: &mut [u8]
-    ) -> Result<(), QoiErrors> {
-        const LAST_BYTES: [u8; 8] = [0, 0, 0, 0, 0, 0, 0, 1];
 
-        let mut index = [[0_u8; 4]; 64];
-        // starting pixel
-        let mut px = [0, 0, 0, 255];
-
-        let mut run = 0;
+    fn decode_inner_generic<const SIZE: usize>(&mut self, pixels: &mut [u8]) {
+        let QoiState {
+            mut index,
+            mut px,
+            mut run
+        } = self.state;
 
         for pix_chunk in pixels.chunks_exact_mut(SIZE) {
             if run > 0 {
@@ -314,6 +404,13 @@ where
                 index[color_hash] = px;
             }
         }
+        self.state = QoiState { index, px, run };
+    }
+
+    /// Confirm the image ends with the QOI end marker
+    fn check_end(&mut self) -> Result<(), QoiErrors> {
+        const LAST_BYTES: [u8; 8] = [0, 0, 0, 0, 0, 0, 0, 1];
+
         let remaining = self.stream.read_fixed_bytes_or_error()?;
 
         if remaining != LAST_BYTES {
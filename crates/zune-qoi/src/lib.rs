@@ -16,6 +16,10 @@
 //!
 //! ## `no_std`
 //! You can use `no_std` with alloc feature to compile for `no_std` endpoints
+//!
+//! [`QoiDecoder::decode_into`] and [`QoiDecoder::decode_rows`] decode into buffers
+//! provided by the caller without allocating, for targets like microcontrollers
+//! where there is no heap to spare.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 #![macro_use]
@@ -169,13 +169,17 @@ impl Into<SpatialOperations> for WasmSpatialOperations {
 #[wasm_bindgen(js_name=PadMethod)]
 pub enum WasmPadMethod {
     Constant,
-    Replicate
+    Replicate,
+    Reflect,
+    Wrap
 }
 impl From<PadMethod> for WasmPadMethod {
     fn from(value: PadMethod) -> Self {
         match value {
             PadMethod::Constant => WasmPadMethod::Constant,
-            PadMethod::Replicate => WasmPadMethod::Replicate
+            PadMethod::Replicate => WasmPadMethod::Replicate,
+            PadMethod::Reflect => WasmPadMethod::Reflect,
+            PadMethod::Wrap => WasmPadMethod::Wrap
         }
     }
 }
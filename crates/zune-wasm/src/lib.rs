@@ -9,10 +9,13 @@
 use std::ops::{Deref, DerefMut};
 
 use wasm_bindgen::prelude::*;
+use wasm_bindgen::Clamped;
+use web_sys::ImageData;
 use zune_core::bit_depth::BitDepth;
 use zune_core::bytestream::ZCursor;
 use zune_core::colorspace::ColorSpace;
 use zune_core::log::{debug, error, info};
+use zune_core::options::{DecoderOptions, EncoderOptions};
 // use zune_core::colorspace::ColorSpace;
 use zune_image::codecs::ImageFormat;
 use zune_image::core_filters::colorspace::ColorspaceConv;
@@ -37,10 +40,15 @@ use zune_imageprocs::hsv_adjust::HsvAdjust;
 use zune_imageprocs::invert::Invert;
 use zune_imageprocs::median::Median;
 use zune_imageprocs::premul_alpha::PremultiplyAlpha;
+use zune_imageprocs::resize::{Resize, ResizeMethod};
+use zune_imageprocs::scharr::Scharr;
+use zune_imageprocs::sobel::Sobel;
 use zune_imageprocs::spatial::SpatialOps;
 use zune_imageprocs::spatial_ops::SpatialOperations;
 use zune_imageprocs::stretch_contrast::StretchContrast;
 use zune_imageprocs::threshold::{Threshold, ThresholdMethod};
+use zune_imageprocs::transpose::Transpose;
+use zune_imageprocs::unsharpen::Unsharpen;
 
 use crate::enums::{WasmColorspace, WasmImageFormats, WasmSpatialOperations};
 use crate::utils::set_panic_hook;
@@ -484,18 +492,185 @@ impl WasmImage {
         Ok(dest)
     }
 
-    // /// Create a new image from in memory bytes of a compressed image
-    // ///
-    // /// @param bytes The bytes containing encoded pixels in a specific format.
-    // /// The library will infer the image format from the bytes themselves
-    // ///
-    // /// @returns An image representation if everything goes well, otherwise panics
-    // pub fn from_bytes(bytes: &[u8]) -> Result<WasmImage, JsError> {
-    //     let c = Image::read(ZCursor::from(bytes), DecoderOptions::new_fast())
-    //         .map_err(<ImageErrors as Into<JsError>>::into)?;
-    //
-    //     Ok(WasmImage { image: c })
-    // }
+    /// Encode the image to a format with custom options, returning the encoded bytes
+    ///
+    /// The bytes are returned in a new `Uint8Array` which owns its buffer, so it can be
+    /// transferred to a worker or turned into a `Blob` without another copy.
+    ///
+    /// @param format - The image format to encode to
+    /// @param quality - Quality for lossy encoders between 0 and 100, defaults to 80
+    /// @param effort - How much time the encoder spends compressing between 0 and 9, defaults to 4
+    pub fn encode(
+        &self, format: WasmImageFormats, quality: Option<u8>, effort: Option<u8>
+    ) -> Result<Vec<u8>, JsError> {
+        let mut options = EncoderOptions::default();
+
+        if let Some(quality) = quality {
+            options = options.set_quality(quality);
+        }
+        if let Some(effort) = effort {
+            options = options.set_effort(effort);
+        }
+        let mut dest = Vec::with_capacity(1000);
+
+        format
+            .to_format()
+            .encode(&self.image, options, &mut dest)
+            .map_err(<ImageErrors as Into<JsError>>::into)?;
+
+        Ok(dest)
+    }
+
+    /// Create a new image from in memory bytes of a compressed image
+    ///
+    /// Like {@link decode}, the image is converted to 8 bits
+    ///
+    /// @param bytes The bytes containing encoded pixels in a specific format.
+    /// The library will infer the image format from the bytes themselves
+    ///
+    /// @returns An image representation or an error if the image could not be decoded
+    pub fn from_bytes(bytes: &[u8]) -> Result<WasmImage, JsError> {
+        let mut image = Image::read(ZCursor::new(bytes), DecoderOptions::new_fast())
+            .map_err(<ImageErrors as Into<JsError>>::into)?;
+
+        image
+            .convert_depth(BitDepth::Eight)
+            .map_err(<ImageErrors as Into<JsError>>::into)?;
+
+        Ok(WasmImage { image })
+    }
+
+    /// Resize an image
+    ///
+    /// @param width - The new image width
+    /// @param height - The new image height
+    /// @param bicubic - Use bicubic interpolation instead of the faster bilinear interpolation
+    pub fn resize(&mut self, width: usize, height: usize, bicubic: bool) -> Result<(), JsError> {
+        let method = if bicubic {
+            ResizeMethod::Bicubic
+        } else {
+            ResizeMethod::Bilinear
+        };
+        self.execute_ops(&Resize::new(width, height, method))
+    }
+
+    /// Transpose the image, swapping its rows and columns
+    pub fn transpose(&mut self) -> Result<(), JsError> {
+        self.execute_ops(&Transpose)
+    }
+
+    /// Calculate the sobel derivative of the image, useful for edge detection
+    pub fn sobel(&mut self) -> Result<(), JsError> {
+        self.execute_ops(&Sobel::new())
+    }
+
+    /// Calculate the scharr derivative of the image, useful for edge detection
+    pub fn scharr(&mut self) -> Result<(), JsError> {
+        self.execute_ops(&Scharr::new())
+    }
+
+    /// Sharpen the image using an unsharp mask
+    ///
+    /// @param sigma - Strength of the gaussian blur used to create the mask
+    /// @param threshold - Minimum difference between the blurred and the original pixel for it to be sharpened
+    /// @param percentage - How much of the difference is added back
+    pub fn unsharpen(&mut self, sigma: f32, threshold: u16, percentage: u8) -> Result<(), JsError> {
+        self.execute_ops(&Unsharpen::new(sigma, threshold, percentage))
+    }
+}
+
+/// Canvas and typed array interop
+#[wasm_bindgen]
+impl WasmImage {
+    /// Create an image from a canvas `ImageData`,
+    /// e.g one returned by `CanvasRenderingContext2D.getImageData`
+    ///
+    /// The image is an 8 bit RGBA image
+    ///
+    /// @param data - The canvas pixels
+    pub fn from_image_data(data: &ImageData) -> Result<WasmImage, JsError> {
+        let pixels = data.data();
+
+        Self::from_pixels(
+            &pixels,
+            data.width() as usize,
+            data.height() as usize,
+            WasmColorspace::RGBA
+        )
+    }
+
+    /// Convert the image to a canvas `ImageData` that can be drawn with
+    /// `CanvasRenderingContext2D.putImageData`
+    ///
+    /// `ImageData` is always 8 bit RGBA, other images are converted first, the image
+    /// itself is not modified.
+    /// For animated images, this returns the first frame.
+    pub fn to_image_data(&self) -> Result<ImageData, JsError> {
+        let (width, height) = self.image.dimensions();
+        let pixels = self.rgba_pixels()?;
+
+        ImageData::new_with_u8_clamped_array_and_sh(Clamped(&pixels), width as u32, height as u32)
+            .map_err(|e| JsError::new(&format!("Could not create ImageData: {e:?}")))
+    }
+
+    /// Create an image from interleaved 8 bit pixels, e.g a `Uint8Array` or
+    /// `Uint8ClampedArray` containing `RGBARGBA` samples
+    ///
+    /// @param pixels - The pixels, the length should be `width * height * components`
+    /// @param width - The image width
+    /// @param height - The image height
+    /// @param colorspace - The colorspace of the pixels
+    pub fn from_pixels(
+        pixels: &[u8], width: usize, height: usize, colorspace: WasmColorspace
+    ) -> Result<WasmImage, JsError> {
+        let colorspace = colorspace.to_colorspace();
+        let expected = width * height * colorspace.num_components();
+
+        if pixels.len() != expected {
+            return Err(JsError::new(&format!(
+                "Expected {expected} bytes for a {width}x{height} {colorspace:?} image but found {}",
+                pixels.len()
+            )));
+        }
+        Ok(WasmImage {
+            image: Image::from_u8(pixels, width, height, colorspace)
+        })
+    }
+
+    /// Return the interleaved pixels of the image in its current colorspace
+    ///
+    /// The pixels are returned in a new `Uint8Array` which owns its buffer, so it can be
+    /// transferred with `postMessage` without another copy.
+    /// For animated images, this returns the first frame.
+    pub fn pixels(&self) -> Result<Vec<u8>, JsError> {
+        let mut image = self.image.clone();
+
+        if image.depth() != BitDepth::Eight {
+            image
+                .convert_depth(BitDepth::Eight)
+                .map_err(<ImageErrors as Into<JsError>>::into)?;
+        }
+        Ok(image.frames_ref()[0].flatten::<u8>())
+    }
+
+    /// Interleaved 8 bit RGBA pixels of the first frame
+    fn rgba_pixels(&self) -> Result<Vec<u8>, JsError> {
+        if self.image.colorspace() == ColorSpace::RGBA && self.image.depth() == BitDepth::Eight {
+            return Ok(self.image.frames_ref()[0].flatten::<u8>());
+        }
+        // clones share channels until they are written to, so this only copies
+        // what the conversions replace
+        let mut image = self.image.clone();
+
+        image
+            .convert_color(ColorSpace::RGBA)
+            .map_err(<ImageErrors as Into<JsError>>::into)?;
+        image
+            .convert_depth(BitDepth::Eight)
+            .map_err(<ImageErrors as Into<JsError>>::into)?;
+
+        Ok(image.frames_ref()[0].flatten::<u8>())
+    }
 }
 
 /// Decode an image returning the pixels if the image is decodable
@@ -0,0 +1,331 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+use std::sync::mpsc::channel;
+use std::sync::Arc;
+
+use wgpu::util::DeviceExt;
+use zune_core::bit_depth::BitType;
+use zune_image::channel::Channel;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+
+use crate::propagate_wgpu_error;
+
+/// Width and height of the workgroups of every shader
+const WORKGROUP_SIZE: usize = 16;
+
+/// A GPU device and what every shader shares to run on it
+///
+/// Every shader binds the same buffers
+/// - `0`: A uniform of four 32 bit values, the parameters of the pass
+/// - `1`: The input, as `f32`
+/// - `2`: The output, as `f32`
+/// - `3`: Extra data, e.g. weights or tables, as `f32`
+pub struct WgpuContext {
+    device:          wgpu::Device,
+    queue:           wgpu::Queue,
+    layout:          wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout
+}
+
+/// One compute pass, reading what the previous pass wrote
+pub(crate) struct Pass<'a> {
+    pub(crate) pipeline:   &'a wgpu::ComputePipeline,
+    /// The `Params` uniform of the shader
+    pub(crate) params:     [u32; 4],
+    /// Width and height of the output, one invocation runs per output value
+    pub(crate) dimensions: (usize, usize)
+}
+
+impl WgpuContext {
+    /// Try to create a context on the most powerful GPU available
+    ///
+    /// # Returns
+    /// - Ok(context): A context to create operations with
+    /// - Err(e): No GPU was found, or it couldn't be opened
+    pub fn try_new() -> Result<Arc<WgpuContext>, ImageErrors> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))
+        .ok_or(ImageErrors::GenericStr("No GPU adapter found"))?;
+
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label:             Some("zune-wgpu"),
+                required_features: wgpu::Features::empty(),
+                // large images need large buffers
+                required_limits:   adapter.limits()
+            },
+            None
+        ))
+        .map_err(propagate_wgpu_error)?;
+
+        let buffer = |binding, ty| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty,
+                has_dynamic_offset: false,
+                min_binding_size: None
+            },
+            count: None
+        };
+        let storage = |read_only| wgpu::BufferBindingType::Storage { read_only };
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label:   Some("zune-wgpu"),
+            entries: &[
+                buffer(0, wgpu::BufferBindingType::Uniform),
+                buffer(1, storage(true)),
+                buffer(2, storage(false)),
+                buffer(3, storage(true))
+            ]
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label:                Some("zune-wgpu"),
+            bind_group_layouts:   &[&layout],
+            push_constant_ranges: &[]
+        });
+
+        Ok(Arc::new(WgpuContext {
+            device,
+            queue,
+            layout,
+            pipeline_layout
+        }))
+    }
+
+    /// The GPU device, e.g. to create textures with
+    pub fn device(&self) -> &wgpu::Device {
+        &self.device
+    }
+
+    /// The queue commands are submitted to
+    pub fn queue(&self) -> &wgpu::Queue {
+        &self.queue
+    }
+
+    /// Compile the `main` entry point of a WGSL shader
+    pub(crate) fn pipeline(
+        &self, label: &'static str, source: &'static str
+    ) -> Result<wgpu::ComputePipeline, ImageErrors> {
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+        let module = self
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label:  Some(label),
+                source: wgpu::ShaderSource::Wgsl(source.into())
+            });
+        let pipeline = self
+            .device
+            .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label:       Some(label),
+                layout:      Some(&self.pipeline_layout),
+                module:      &module,
+                entry_point: "main"
+            });
+
+        self.pop_errors()?;
+        Ok(pipeline)
+    }
+
+    fn pop_errors(&self) -> Result<(), ImageErrors> {
+        match pollster::block_on(self.device.pop_error_scope()) {
+            Some(error) => Err(propagate_wgpu_error(error)),
+            None => Ok(())
+        }
+    }
+
+    /// Run `passes` on every channel of the image, replacing it by the output
+    /// of the last pass
+    pub(crate) fn run_on_channels(
+        &self, image: &mut Image, ignore_alpha: bool, extra: &[f32], passes: &[Pass]
+    ) -> Result<(), ImageErrors> {
+        let depth = image.depth().bit_type();
+
+        for channel in image.channels_mut(ignore_alpha) {
+            let output = self.run(&to_floats(channel, depth)?, extra, passes)?;
+            *channel = from_floats(&output, depth)?;
+        }
+        Ok(())
+    }
+
+    /// Upload `input` and `extra`, run `passes` one after the other and download
+    /// the output of the last one
+    fn run(&self, input: &[f32], extra: &[f32], passes: &[Pass]) -> Result<Vec<f32>, ImageErrors> {
+        let output_len = passes
+            .last()
+            .map_or(input.len(), |x| x.dimensions.0 * x.dimensions.1);
+
+        if input.is_empty() || output_len == 0 {
+            return Ok(vec![0.0; output_len]);
+        }
+        // passes read from one buffer and write to the other, back and forth
+        let length = passes
+            .iter()
+            .map(|x| x.dimensions.0 * x.dimensions.1)
+            .fold(input.len(), usize::max);
+
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+        let usage = wgpu::BufferUsages::STORAGE
+            | wgpu::BufferUsages::COPY_SRC
+            | wgpu::BufferUsages::COPY_DST;
+
+        let buffers = [0, 1].map(|_| {
+            self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("zune-wgpu values"),
+                size: (length * 4) as u64,
+                usage,
+                mapped_at_creation: false
+            })
+        });
+        self.queue
+            .write_buffer(&buffers[0], 0, bytemuck::cast_slice(input));
+
+        // bindings can't be empty
+        let extra = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label:    Some("zune-wgpu extra"),
+                contents: bytemuck::cast_slice(if extra.is_empty() { &[0.0][..] } else { extra }),
+                usage:    wgpu::BufferUsages::STORAGE
+            });
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label:              Some("zune-wgpu staging"),
+            size:               (output_len * 4) as u64,
+            usage:              wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+
+        for (i, pass) in passes.iter().enumerate() {
+            let (input, output) = (&buffers[i % 2], &buffers[(i + 1) % 2]);
+
+            let params = self
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label:    Some("zune-wgpu params"),
+                    contents: bytemuck::cast_slice(&pass.params),
+                    usage:    wgpu::BufferUsages::UNIFORM
+                });
+            let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label:   Some("zune-wgpu"),
+                layout:  &self.layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding:  0,
+                        resource: params.as_entire_binding()
+                    },
+                    wgpu::BindGroupEntry {
+                        binding:  1,
+                        resource: input.as_entire_binding()
+                    },
+                    wgpu::BindGroupEntry {
+                        binding:  2,
+                        resource: output.as_entire_binding()
+                    },
+                    wgpu::BindGroupEntry {
+                        binding:  3,
+                        resource: extra.as_entire_binding()
+                    }
+                ]
+            });
+            let (width, height) = pass.dimensions;
+
+            let mut compute = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            compute.set_pipeline(pass.pipeline);
+            compute.set_bind_group(0, &bind_group, &[]);
+            compute.dispatch_workgroups(
+                width.div_ceil(WORKGROUP_SIZE) as u32,
+                height.div_ceil(WORKGROUP_SIZE) as u32,
+                1
+            );
+        }
+        encoder.copy_buffer_to_buffer(
+            &buffers[passes.len() % 2],
+            0,
+            &staging,
+            0,
+            (output_len * 4) as u64
+        );
+        self.queue.submit([encoder.finish()]);
+
+        let (sender, receiver) = channel();
+        let slice = staging.slice(..);
+
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        self.pop_errors()?;
+
+        receiver
+            .recv()
+            .map_err(propagate_wgpu_error)?
+            .map_err(propagate_wgpu_error)?;
+
+        let output = bytemuck::cast_slice::<u8, f32>(&slice.get_mapped_range()).to_vec();
+        staging.unmap();
+
+        Ok(output)
+    }
+}
+
+/// Convert a channel to floats for uploading
+fn to_floats(channel: &Channel, depth: BitType) -> Result<Vec<f32>, ImageErrors> {
+    let values = match depth {
+        BitType::U8 => channel
+            .reinterpret_as::<u8>()?
+            .iter()
+            .map(|x| f32::from(*x))
+            .collect(),
+        BitType::U16 => channel
+            .reinterpret_as::<u16>()?
+            .iter()
+            .map(|x| f32::from(*x))
+            .collect(),
+        BitType::F32 => channel.reinterpret_as::<f32>()?.to_vec(),
+        d => return Err(ImageErrors::ImageOperationNotImplemented("wgpu upload", d))
+    };
+    Ok(values)
+}
+
+/// Convert downloaded floats back to a channel of `depth`, rounding and clamping integers
+fn from_floats(values: &[f32], depth: BitType) -> Result<Channel, ImageErrors> {
+    let channel = match depth {
+        BitType::U8 => Channel::from_vec(
+            values
+                .iter()
+                .map(|x| x.round().clamp(0.0, 255.0) as u8)
+                .collect::<Vec<u8>>()
+        ),
+        BitType::U16 => Channel::from_vec(
+            values
+                .iter()
+                .map(|x| x.round().clamp(0.0, 65535.0) as u16)
+                .collect::<Vec<u16>>()
+        ),
+        BitType::F32 => Channel::from_vec(values.to_vec()),
+        d => {
+            return Err(ImageErrors::ImageOperationNotImplemented(
+                "wgpu download",
+                d
+            ))
+        }
+    };
+    Ok(channel)
+}
@@ -0,0 +1,66 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! wgpu compute shader versions of heavy image filters
+//!
+//! The operations here implement [`OperationsTrait`](zune_image::traits::OperationsTrait)
+//! like their CPU versions in `zune-imageprocs`, so they can be mixed with them, e.g in a
+//! [`Pipeline`](zune_image::pipelines::Pipeline).
+//!
+//! - [`WgpuResize`](wgpu_resize::WgpuResize): Bilinear resizing
+//! - [`WgpuGaussianBlur`](wgpu_blur::WgpuGaussianBlur): Gaussian blur, in two passes
+//! - [`WgpuConvolve`](wgpu_convolve::WgpuConvolve): Convolution with square matrices
+//! - [`WgpuLut`](wgpu_lut::WgpuLut): Lookup tables for 8 and 16 bit images
+//!
+//! [`texture`] moves images to and from `wgpu` textures, for renderers using zune-image
+//! to load and save their images.
+//!
+//! Every channel is uploaded to the GPU as floats, goes through the compute passes
+//! of the operation without leaving the GPU, and is downloaded back into the image
+//! as the image depth, rounding and clamping integers.
+//!
+//! Operations share a [`WgpuContext`], which holds the GPU device, and compile their shaders
+//! when created, so editors create them once and run them on every image.
+//!
+//! # Warning
+//! Moving images to and from the GPU takes time, so these may be slower than their
+//! CPU versions for small images or fast filters, please benchmark before using them.
+//!
+//! # Example
+//! ```no_run
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_image::traits::OperationsTrait;
+//! use zune_wgpu::wgpu_blur::WgpuGaussianBlur;
+//! use zune_wgpu::wgpu_resize::WgpuResize;
+//! use zune_wgpu::WgpuContext;
+//!
+//! let context = WgpuContext::try_new()?;
+//! let blur = WgpuGaussianBlur::try_new(context.clone(), 5.0)?;
+//! let resize = WgpuResize::try_new(context, 400, 300)?;
+//!
+//! let mut image = Image::fill(100_u8, ColorSpace::RGB, 1920, 1080);
+//! blur.execute(&mut image)?;
+//! resize.execute(&mut image)?;
+//! # Ok::<(), zune_image::errors::ImageErrors>(())
+//! ```
+use zune_image::errors::ImageErrors;
+
+mod context;
+pub mod texture;
+pub mod wgpu_blur;
+pub mod wgpu_convolve;
+pub mod wgpu_lut;
+pub mod wgpu_resize;
+
+pub use context::WgpuContext;
+
+fn propagate_wgpu_error<E: std::fmt::Display>(error: E) -> ImageErrors {
+    let message = format!("WGPU_ERROR:\n{}", error);
+    ImageErrors::GenericString(message)
+}
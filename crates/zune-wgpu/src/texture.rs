@@ -0,0 +1,386 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+//! Moving images to and from `wgpu` textures
+//!
+//! [`upload_texture`] creates a 2D texture holding the first frame of an image and
+//! [`download_texture`] reads a texture back into an image, so renderers can decode
+//! and encode every format zune-image supports.
+//!
+//! Both take the device and queue of the renderer, a [`WgpuContext`](crate::WgpuContext)
+//! gives them with [`device`](crate::WgpuContext::device) and
+//! [`queue`](crate::WgpuContext::queue).
+//!
+//! GPUs have no three component formats, so images are uploaded as follows,
+//! see [`texture_format`]
+//!
+//! | Colorspace         | `u8`               | `u16`                    | `F16`          | `f32`          |
+//! |--------------------|--------------------|--------------------------|----------------|----------------|
+//! | Luma               | `R8Unorm`          | `R16Unorm`               | `R16Float`     | `R32Float`     |
+//! | LumaA              | `Rg8Unorm`         | `Rg16Unorm`              | `Rg16Float`    | `Rg32Float`    |
+//! | BGR, BGRA          | `Bgra8UnormSrgb`   | `Rgba16Unorm`            | `Rgba16Float`  | `Rgba32Float`  |
+//! | Everything else    | `Rgba8UnormSrgb`   | `Rgba16Unorm`            | `Rgba16Float`  | `Rgba32Float`  |
+//!
+//! Images without alpha get an opaque alpha channel, other colorspaces are converted to RGBA.
+//! The 16 bit normalized formats need [`Features::TEXTURE_FORMAT_16BIT_NORM`](wgpu::Features::TEXTURE_FORMAT_16BIT_NORM),
+//! without it 16 bit images are uploaded as 32 bit floats.
+//!
+//! # Example
+//! ```no_run
+//! use zune_core::colorspace::ColorSpace;
+//! use zune_image::image::Image;
+//! use zune_wgpu::texture::{download_texture, upload_texture};
+//! use zune_wgpu::WgpuContext;
+//!
+//! let context = WgpuContext::try_new()?;
+//! let image = Image::fill(100_u8, ColorSpace::RGB, 640, 480);
+//!
+//! let texture = upload_texture(
+//!     context.device(),
+//!     context.queue(),
+//!     &image,
+//!     wgpu::TextureUsages::TEXTURE_BINDING
+//! )?;
+//! assert_eq!(texture.format(), wgpu::TextureFormat::Rgba8UnormSrgb);
+//!
+//! let back = download_texture(context.device(), context.queue(), &texture)?;
+//! assert_eq!(back.colorspace(), ColorSpace::RGBA);
+//! # Ok::<(), zune_image::errors::ImageErrors>(())
+//! ```
+use std::borrow::Cow;
+use std::mem::size_of;
+use std::sync::mpsc::channel;
+
+use bytemuck::Pod;
+use wgpu::TextureFormat;
+use zune_core::bit_depth::{BitDepth, BitType};
+use zune_core::colorspace::ColorSpace;
+use zune_image::core_filters::colorspace::ColorspaceConv;
+use zune_image::core_filters::depth::Depth;
+use zune_image::errors::ImageErrors;
+use zune_image::half::F16;
+use zune_image::image::Image;
+use zune_image::metadata::PixelLayout;
+use zune_image::traits::{OperationsTrait, ZuneInts};
+
+use crate::propagate_wgpu_error;
+
+/// Return the format [`upload_texture`] uses for an image on a device with `features`
+///
+/// See the [module docs](self) for the formats used
+pub fn texture_format(image: &Image, features: wgpu::Features) -> TextureFormat {
+    upload_target(image.colorspace(), image.depth(), features).2
+}
+
+/// Create a texture holding the first frame of an image
+///
+/// The texture has a single mip level and the format returned by [`texture_format`],
+/// the image is converted to it if needed, it is left as it is.
+///
+/// `usage` is the usage of the texture, `COPY_DST` is always added since the pixels
+/// are copied into it.
+///
+/// # Returns
+/// - Ok(texture): The texture, the copy of the pixels is queued on `queue`
+/// - Err(e): The image is empty, too large for the device or couldn't be converted
+pub fn upload_texture(
+    device: &wgpu::Device, queue: &wgpu::Queue, image: &Image, usage: wgpu::TextureUsages
+) -> Result<wgpu::Texture, ImageErrors> {
+    let (width, height) = image.dimensions();
+    let max_dimension = device.limits().max_texture_dimension_2d;
+
+    let size = match (u32::try_from(width), u32::try_from(height)) {
+        (Ok(w), Ok(h)) if w > 0 && h > 0 && w <= max_dimension && h <= max_dimension => {
+            wgpu::Extent3d {
+                width:                 w,
+                height:                h,
+                depth_or_array_layers: 1
+            }
+        }
+        _ => {
+            return Err(ImageErrors::GenericString(format!(
+                "Image dimensions {width}x{height} can't be a texture, textures on this device are at most {max_dimension}x{max_dimension}"
+            )));
+        }
+    };
+    let (colorspace, depth, format) =
+        upload_target(image.colorspace(), image.depth(), device.features());
+
+    let image = converted(image, colorspace, depth)?;
+    let pixels = image.frames_ref()[0].channels_vec_ref()[0].reinterpret_as::<u8>()?;
+
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("zune-wgpu texture"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: usage | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[]
+    });
+    // writing from memory has no row alignment requirements, unlike copying from buffers
+    queue.write_texture(
+        texture.as_image_copy(),
+        pixels,
+        wgpu::ImageDataLayout {
+            offset:         0,
+            bytes_per_row:  Some(size.width * bytes_per_pixel(format)?),
+            rows_per_image: None
+        },
+        size
+    );
+    pop_errors(device)?;
+
+    Ok(texture)
+}
+
+/// Read the first layer of a texture back into an image
+///
+/// The texture must have been created with `COPY_SRC` usage and one of the formats
+/// listed in the [module docs](self), `Rgba8Unorm` and `Bgra8Unorm` are also
+/// supported. The image is interleaved.
+///
+/// This waits for the GPU to finish every submitted command.
+///
+/// # Returns
+/// - Ok(image): The pixels of the texture
+/// - Err(e): The format of the texture isn't supported or the copy failed
+pub fn download_texture(
+    device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture
+) -> Result<Image, ImageErrors> {
+    let format = texture.format();
+    let (colorspace, depth) = download_target(format)?;
+
+    let (width, height) = (texture.width(), texture.height());
+    let row_bytes = width * bytes_per_pixel(format)?;
+    // copies to buffers need rows aligned to 256 bytes, the padding is removed below
+    let padded_row_bytes = row_bytes.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+    let staging = device.create_buffer(&wgpu::BufferDescriptor {
+        label:              Some("zune-wgpu texture staging"),
+        size:               u64::from(padded_row_bytes) * u64::from(height),
+        usage:              wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false
+    });
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &staging,
+            layout: wgpu::ImageDataLayout {
+                offset:         0,
+                bytes_per_row:  Some(padded_row_bytes),
+                rows_per_image: None
+            }
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1
+        }
+    );
+    queue.submit([encoder.finish()]);
+
+    let (sender, receiver) = channel();
+    let slice = staging.slice(..);
+
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = sender.send(result);
+    });
+    device.poll(wgpu::Maintain::Wait);
+    pop_errors(device)?;
+
+    receiver
+        .recv()
+        .map_err(propagate_wgpu_error)?
+        .map_err(propagate_wgpu_error)?;
+
+    // the mapped range has to be dropped before unmapping
+    let mapped = slice.get_mapped_range();
+    let rows = Rows {
+        bytes:        &mapped,
+        width:        width as usize,
+        height:       height as usize,
+        row_bytes:    row_bytes as usize,
+        padded_bytes: padded_row_bytes as usize
+    };
+    let image = match depth.bit_type() {
+        BitType::U8 => rows.to_image::<u8>(colorspace),
+        BitType::U16 => rows.to_image::<u16>(colorspace),
+        BitType::F16 => rows.to_image::<F16>(colorspace),
+        _ => rows.to_image::<f32>(colorspace)
+    };
+    drop(mapped);
+    staging.unmap();
+
+    Ok(image)
+}
+
+/// The rows of a downloaded texture, each followed by padding
+struct Rows<'a> {
+    bytes:        &'a [u8],
+    width:        usize,
+    height:       usize,
+    row_bytes:    usize,
+    padded_bytes: usize
+}
+
+impl Rows<'_> {
+    fn to_image<T>(&self, colorspace: ColorSpace) -> Image
+    where
+        T: Copy + 'static + ZuneInts<T> + Pod
+    {
+        let mut pixels = vec![T::zeroed(); self.height * self.row_bytes / size_of::<T>()];
+
+        for (output, input) in bytemuck::cast_slice_mut::<T, u8>(&mut pixels)
+            .chunks_exact_mut(self.row_bytes)
+            .zip(self.bytes.chunks(self.padded_bytes))
+        {
+            output.copy_from_slice(&input[..self.row_bytes]);
+        }
+        Image::from_interleaved_vec(pixels, self.width, self.height, colorspace)
+    }
+}
+
+/// The colorspace, depth and texture format an image with `colorspace` and `depth`
+/// is uploaded as
+fn upload_target(
+    colorspace: ColorSpace, depth: BitDepth, features: wgpu::Features
+) -> (ColorSpace, BitDepth, TextureFormat) {
+    let has_16bit_norm = features.contains(wgpu::Features::TEXTURE_FORMAT_16BIT_NORM);
+
+    let depth = match depth.bit_type() {
+        BitType::U8 => BitDepth::Eight,
+        BitType::U16 if has_16bit_norm => BitDepth::Sixteen,
+        BitType::F16 => BitDepth::Float16,
+        _ => BitDepth::Float32
+    };
+    let colorspace = match colorspace {
+        ColorSpace::Luma | ColorSpace::LumaA => colorspace,
+        ColorSpace::BGR | ColorSpace::BGRA if depth == BitDepth::Eight => ColorSpace::BGRA,
+        _ => ColorSpace::RGBA
+    };
+    let format = match (colorspace, depth) {
+        (ColorSpace::Luma, BitDepth::Eight) => TextureFormat::R8Unorm,
+        (ColorSpace::Luma, BitDepth::Sixteen) => TextureFormat::R16Unorm,
+        (ColorSpace::Luma, BitDepth::Float16) => TextureFormat::R16Float,
+        (ColorSpace::Luma, _) => TextureFormat::R32Float,
+        (ColorSpace::LumaA, BitDepth::Eight) => TextureFormat::Rg8Unorm,
+        (ColorSpace::LumaA, BitDepth::Sixteen) => TextureFormat::Rg16Unorm,
+        (ColorSpace::LumaA, BitDepth::Float16) => TextureFormat::Rg16Float,
+        (ColorSpace::LumaA, _) => TextureFormat::Rg32Float,
+        (ColorSpace::BGRA, _) => TextureFormat::Bgra8UnormSrgb,
+        (_, BitDepth::Eight) => TextureFormat::Rgba8UnormSrgb,
+        (_, BitDepth::Sixteen) => TextureFormat::Rgba16Unorm,
+        (_, BitDepth::Float16) => TextureFormat::Rgba16Float,
+        (_, _) => TextureFormat::Rgba32Float
+    };
+    (colorspace, depth, format)
+}
+
+/// The colorspace and depth of an image holding a texture of `format`
+fn download_target(format: TextureFormat) -> Result<(ColorSpace, BitDepth), ImageErrors> {
+    let target = match format {
+        TextureFormat::R8Unorm => (ColorSpace::Luma, BitDepth::Eight),
+        TextureFormat::Rg8Unorm => (ColorSpace::LumaA, BitDepth::Eight),
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => {
+            (ColorSpace::RGBA, BitDepth::Eight)
+        }
+        TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => {
+            (ColorSpace::BGRA, BitDepth::Eight)
+        }
+        TextureFormat::R16Unorm => (ColorSpace::Luma, BitDepth::Sixteen),
+        TextureFormat::Rg16Unorm => (ColorSpace::LumaA, BitDepth::Sixteen),
+        TextureFormat::Rgba16Unorm => (ColorSpace::RGBA, BitDepth::Sixteen),
+        TextureFormat::R16Float => (ColorSpace::Luma, BitDepth::Float16),
+        TextureFormat::Rg16Float => (ColorSpace::LumaA, BitDepth::Float16),
+        TextureFormat::Rgba16Float => (ColorSpace::RGBA, BitDepth::Float16),
+        TextureFormat::R32Float => (ColorSpace::Luma, BitDepth::Float32),
+        TextureFormat::Rg32Float => (ColorSpace::LumaA, BitDepth::Float32),
+        TextureFormat::Rgba32Float => (ColorSpace::RGBA, BitDepth::Float32),
+        f => {
+            return Err(ImageErrors::GenericString(format!(
+                "Texture format {f:?} can't be read into an image"
+            )));
+        }
+    };
+    Ok(target)
+}
+
+fn bytes_per_pixel(format: TextureFormat) -> Result<u32, ImageErrors> {
+    format
+        .block_copy_size(None)
+        .ok_or(ImageErrors::GenericStr("Texture format has no single copy size"))
+}
+
+/// Return the image with `colorspace`, `depth` and interleaved, copying it only
+/// if it isn't already
+fn converted(
+    image: &Image, colorspace: ColorSpace, depth: BitDepth
+) -> Result<Cow<'_, Image>, ImageErrors> {
+    let is_interleaved =
+        image.layout() == PixelLayout::Interleaved || colorspace.num_components() == 1;
+
+    if image.colorspace() == colorspace && image.depth() == depth && is_interleaved {
+        return Ok(Cow::Borrowed(image));
+    }
+    let mut image = image.clone();
+
+    if image.colorspace() != colorspace {
+        // colorspace conversions don't run on half floats
+        if image.depth() == BitDepth::Float16 {
+            Depth::new(BitDepth::Float32).execute(&mut image)?;
+        }
+        ColorspaceConv::new(colorspace).execute(&mut image)?;
+    }
+    if image.depth() != depth {
+        Depth::new(depth).execute(&mut image)?;
+    }
+    image.convert_layout(PixelLayout::Interleaved)?;
+
+    Ok(Cow::Owned(image))
+}
+
+fn pop_errors(device: &wgpu::Device) -> Result<(), ImageErrors> {
+    match pollster::block_on(device.pop_error_scope()) {
+        Some(error) => Err(propagate_wgpu_error(error)),
+        None => Ok(())
+    }
+}
+
+#[test]
+#[cfg(feature = "tests")]
+fn test_texture_round_trip() {
+    use crate::WgpuContext;
+
+    let context = WgpuContext::try_new().unwrap();
+    let usage = wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_SRC;
+
+    // odd widths need padded rows when reading back
+    let image = Image::fill(100_u8, ColorSpace::RGB, 37, 20);
+    let texture = upload_texture(context.device(), context.queue(), &image, usage).unwrap();
+    assert_eq!(texture.format(), TextureFormat::Rgba8UnormSrgb);
+
+    let back = download_texture(context.device(), context.queue(), &texture).unwrap();
+    assert_eq!(back.dimensions(), (37, 20));
+    assert_eq!(back.colorspace(), ColorSpace::RGBA);
+    assert!(back.flatten_to_u8()[0]
+        .chunks_exact(4)
+        .all(|x| x == [100, 100, 100, 255]));
+
+    let image = Image::fill(0.25_f32, ColorSpace::Luma, 5, 3);
+    let texture = upload_texture(context.device(), context.queue(), &image, usage).unwrap();
+    let back = download_texture(context.device(), context.queue(), &texture).unwrap();
+    assert_eq!(back.flatten_frames::<f32>()[0], vec![0.25; 15]);
+}
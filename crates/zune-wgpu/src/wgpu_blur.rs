@@ -0,0 +1,114 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+use std::sync::Arc;
+
+use zune_core::bit_depth::BitType;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::context::{Pass, WgpuContext};
+
+/// Gaussian blur on the GPU
+///
+/// The blur is separable, so it runs as a horizontal pass followed by a vertical one,
+/// both on the GPU, with a kernel extending three standard deviations on each side.
+/// Pixels outside the image replicate the edges.
+///
+/// The alpha channel is ignored
+///
+/// # Example
+/// ```no_run
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_wgpu::wgpu_blur::WgpuGaussianBlur;
+/// use zune_wgpu::WgpuContext;
+///
+/// let mut image = Image::fill(100_u8, ColorSpace::RGB, 1000, 1000);
+/// WgpuGaussianBlur::try_new(WgpuContext::try_new()?, 10.0)?.execute(&mut image)?;
+/// # Ok::<(), zune_image::errors::ImageErrors>(())
+/// ```
+pub struct WgpuGaussianBlur {
+    context:  Arc<WgpuContext>,
+    pipeline: wgpu::ComputePipeline,
+    weights:  Vec<f32>
+}
+
+impl WgpuGaussianBlur {
+    /// Try to create a new gaussian blur
+    ///
+    /// This compiles the shader, and it's done outside execution to allow the
+    /// operation to be reused on multiple images without recompiling it.
+    ///
+    /// # Returns
+    /// - Ok(WgpuGaussianBlur): The operation
+    /// - Err(e): Sigma isn't positive, or the shader couldn't be compiled
+    pub fn try_new(context: Arc<WgpuContext>, sigma: f32) -> Result<Self, ImageErrors> {
+        if !(sigma > 0.0) {
+            return Err(ImageErrors::GenericStr(
+                "Gaussian blur sigma must be positive"
+            ));
+        }
+        let pipeline = context.pipeline("gaussian blur", include_str!("./wgsl/blur.wgsl"))?;
+
+        let radius = (sigma * 3.0).ceil() as i32;
+        let mut weights: Vec<f32> = (-radius..=radius)
+            .map(|x| (-((x * x) as f32) / (2.0 * sigma * sigma)).exp())
+            .collect();
+
+        let sum: f32 = weights.iter().sum();
+        weights.iter_mut().for_each(|x| *x /= sum);
+
+        Ok(WgpuGaussianBlur {
+            context,
+            pipeline,
+            weights
+        })
+    }
+}
+
+impl OperationsTrait for WgpuGaussianBlur {
+    fn name(&self) -> &'static str {
+        "WGPU Gaussian Blur"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let radius = (self.weights.len() / 2) as u32;
+
+        let pass = |vertical| Pass {
+            pipeline:   &self.pipeline,
+            params:     [width as u32, height as u32, radius, vertical],
+            dimensions: (width, height)
+        };
+        self.context
+            .run_on_channels(image, true, &self.weights, &[pass(0), pass(1)])
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+#[test]
+#[cfg(feature = "tests")]
+fn test_wgpu_gaussian_blur() {
+    use zune_core::colorspace::ColorSpace;
+    // constant images stay constant, for all supported bit types
+    let mut image = Image::fill(100_u8, ColorSpace::RGB, 100, 80);
+    let blur = WgpuGaussianBlur::try_new(WgpuContext::try_new().unwrap(), 3.0).unwrap();
+
+    for d_type in blur.supported_types() {
+        image.convert_depth(d_type.to_depth()).unwrap();
+        let blurred = blur.clone_and_execute(&image).unwrap();
+
+        assert!(blurred.flatten_to_u8()[0].iter().all(|x| *x == 100));
+    }
+}
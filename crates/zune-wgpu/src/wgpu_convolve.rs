@@ -0,0 +1,130 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+use std::sync::Arc;
+
+use zune_core::bit_depth::BitType;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::context::{Pass, WgpuContext};
+
+/// Convolution with a square matrix on the GPU
+///
+/// This matches `Convolve` of `zune-imageprocs` with its default pad method, which
+/// replicates the edges, but takes matrices of any odd size.
+///
+/// The alpha channel is ignored
+///
+/// # Example
+/// - Sharpen with a 3x3 matrix
+/// ```no_run
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_wgpu::wgpu_convolve::WgpuConvolve;
+/// use zune_wgpu::WgpuContext;
+///
+/// let matrix = vec![0.0, -1.0,  0.0,
+///                  -1.0,  5.0, -1.0,
+///                   0.0, -1.0,  0.0];
+///
+/// let mut image = Image::fill(100_u8, ColorSpace::RGB, 1000, 1000);
+/// WgpuConvolve::try_new(WgpuContext::try_new()?, matrix, 1.0)?.execute(&mut image)?;
+/// # Ok::<(), zune_image::errors::ImageErrors>(())
+/// ```
+pub struct WgpuConvolve {
+    context:  Arc<WgpuContext>,
+    pipeline: wgpu::ComputePipeline,
+    weights:  Vec<f32>,
+    size:     usize,
+    scale:    f32
+}
+
+impl WgpuConvolve {
+    /// Try to create a new convolution
+    ///
+    /// `weights` is the matrix row by row, `scale` is multiplied by the result
+    /// of the convolution.
+    ///
+    /// This compiles the shader, and it's done outside execution to allow the
+    /// operation to be reused on multiple images without recompiling it.
+    ///
+    /// # Returns
+    /// - Ok(WgpuConvolve): The operation
+    /// - Err(e): The weights aren't a square matrix of an odd size, or the shader
+    ///   couldn't be compiled
+    pub fn try_new(
+        context: Arc<WgpuContext>, weights: Vec<f32>, scale: f32
+    ) -> Result<Self, ImageErrors> {
+        let size = (weights.len() as f64).sqrt() as usize;
+
+        if size * size != weights.len() || size % 2 == 0 {
+            return Err(ImageErrors::GenericStr(
+                "Convolution weights must be a square matrix of an odd size"
+            ));
+        }
+        let pipeline = context.pipeline("convolve", include_str!("./wgsl/convolve.wgsl"))?;
+
+        Ok(WgpuConvolve {
+            context,
+            pipeline,
+            weights,
+            size,
+            scale
+        })
+    }
+}
+
+impl OperationsTrait for WgpuConvolve {
+    fn name(&self) -> &'static str {
+        "WGPU 2D convolution"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+
+        let pass = Pass {
+            pipeline:   &self.pipeline,
+            params:     [
+                width as u32,
+                height as u32,
+                self.size as u32,
+                self.scale.to_bits()
+            ],
+            dimensions: (width, height)
+        };
+        self.context
+            .run_on_channels(image, true, &self.weights, &[pass])
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+#[test]
+#[cfg(feature = "tests")]
+fn test_wgpu_convolve() {
+    use zune_core::colorspace::ColorSpace;
+    // the identity matrix leaves images as they are, for all supported bit types
+    let mut image = Image::from_fn(100, 80, ColorSpace::RGB, |y, x, px| {
+        px.fill((x * 2 + y) as u8);
+    });
+    let mut weights = vec![0.0; 25];
+    weights[12] = 1.0;
+    let convolve = WgpuConvolve::try_new(WgpuContext::try_new().unwrap(), weights, 1.0).unwrap();
+
+    for d_type in convolve.supported_types() {
+        image.convert_depth(d_type.to_depth()).unwrap();
+        let convolved = convolve.clone_and_execute(&image).unwrap();
+
+        assert!(convolved.flatten_to_u8() == image.flatten_to_u8());
+    }
+}
@@ -0,0 +1,119 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+use std::sync::Arc;
+
+use zune_core::bit_depth::BitType;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::context::{Pass, WgpuContext};
+
+/// Lookup table application on the GPU
+///
+/// Every value of the color channels is replaced by its entry in the table,
+/// which has 256 entries for 8 bit images and 65536 for 16 bit images.
+/// See [`PointOps`](zune_image::point_ops::PointOps) for running point operations
+/// on the CPU.
+///
+/// The alpha channel is ignored
+///
+/// # Example
+/// - Invert an 8 bit image
+/// ```no_run
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_wgpu::wgpu_lut::WgpuLut;
+/// use zune_wgpu::WgpuContext;
+///
+/// let table: Vec<u16> = (0..=255).rev().collect();
+///
+/// let mut image = Image::fill(100_u8, ColorSpace::RGB, 1000, 1000);
+/// WgpuLut::try_new(WgpuContext::try_new()?, &table)?.execute(&mut image)?;
+/// # Ok::<(), zune_image::errors::ImageErrors>(())
+/// ```
+pub struct WgpuLut {
+    context:  Arc<WgpuContext>,
+    pipeline: wgpu::ComputePipeline,
+    table:    Vec<f32>
+}
+
+impl WgpuLut {
+    /// Try to create a new lookup table operation
+    ///
+    /// This compiles the shader, and it's done outside execution to allow the
+    /// operation to be reused on multiple images without recompiling it.
+    ///
+    /// # Returns
+    /// - Ok(WgpuLut): The operation
+    /// - Err(e): The table doesn't have 256 or 65536 entries, or the shader
+    ///   couldn't be compiled
+    pub fn try_new(context: Arc<WgpuContext>, table: &[u16]) -> Result<Self, ImageErrors> {
+        if table.len() != 256 && table.len() != 65536 {
+            return Err(ImageErrors::GenericStr(
+                "Lookup tables must have 256 or 65536 entries"
+            ));
+        }
+        let pipeline = context.pipeline("lut", include_str!("./wgsl/lut.wgsl"))?;
+
+        Ok(WgpuLut {
+            context,
+            pipeline,
+            table: table.iter().map(|x| f32::from(*x)).collect()
+        })
+    }
+}
+
+impl OperationsTrait for WgpuLut {
+    fn name(&self) -> &'static str {
+        "WGPU Lookup table"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+        let depth = image.depth();
+
+        if self.table.len() != usize::from(depth.max_value()) + 1 {
+            return Err(ImageErrors::GenericString(format!(
+                "Lookup table has {} entries, but {:?} images need {}",
+                self.table.len(),
+                depth,
+                usize::from(depth.max_value()) + 1
+            )));
+        }
+        let pass = Pass {
+            pipeline:   &self.pipeline,
+            params:     [width as u32, height as u32, u32::from(depth.max_value()), 0],
+            dimensions: (width, height)
+        };
+        self.context
+            .run_on_channels(image, true, &self.table, &[pass])
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16]
+    }
+}
+
+#[test]
+#[cfg(feature = "tests")]
+fn test_wgpu_lut() {
+    use zune_core::colorspace::ColorSpace;
+
+    let mut image = Image::fill(100_u8, ColorSpace::RGB, 100, 80);
+    let table: Vec<u16> = (0..=255).rev().collect();
+
+    WgpuLut::try_new(WgpuContext::try_new().unwrap(), &table)
+        .unwrap()
+        .execute(&mut image)
+        .unwrap();
+
+    assert!(image.flatten_to_u8()[0].iter().all(|x| *x == 155));
+}
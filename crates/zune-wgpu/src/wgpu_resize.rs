@@ -0,0 +1,114 @@
+/*
+ * Copyright (c) 2024.
+ *
+ * This software is free software;
+ *
+ * You can redistribute it or modify it under terms of the MIT, Apache License or Zlib license
+ */
+
+use std::sync::Arc;
+
+use zune_core::bit_depth::BitType;
+use zune_image::errors::ImageErrors;
+use zune_image::image::Image;
+use zune_image::traits::OperationsTrait;
+
+use crate::context::{Pass, WgpuContext};
+
+/// Bilinear resize on the GPU
+///
+/// Samples the same positions as the bilinear resizer of `zune-imageprocs`, every
+/// channel including alpha is resized
+///
+/// # Example
+/// ```no_run
+/// use zune_core::colorspace::ColorSpace;
+/// use zune_image::image::Image;
+/// use zune_image::traits::OperationsTrait;
+/// use zune_wgpu::wgpu_resize::WgpuResize;
+/// use zune_wgpu::WgpuContext;
+///
+/// let mut image = Image::fill(100_u8, ColorSpace::RGB, 1000, 1000);
+/// WgpuResize::try_new(WgpuContext::try_new()?, 300, 200)?.execute(&mut image)?;
+///
+/// assert_eq!(image.dimensions(), (300, 200));
+/// # Ok::<(), zune_image::errors::ImageErrors>(())
+/// ```
+pub struct WgpuResize {
+    context:    Arc<WgpuContext>,
+    pipeline:   wgpu::ComputePipeline,
+    new_width:  usize,
+    new_height: usize
+}
+
+impl WgpuResize {
+    /// Try to create a new resize operation
+    ///
+    /// This compiles the shader, and it's done outside execution to allow the
+    /// operation to be reused on multiple images without recompiling it.
+    ///
+    /// # Returns
+    /// - Ok(WgpuResize): The operation
+    /// - Err(e): A dimension is zero, or the shader couldn't be compiled
+    pub fn try_new(
+        context: Arc<WgpuContext>, new_width: usize, new_height: usize
+    ) -> Result<Self, ImageErrors> {
+        if new_width == 0 || new_height == 0 {
+            return Err(ImageErrors::GenericStr("Resize dimensions can't be zero"));
+        }
+        let pipeline = context.pipeline("resize", include_str!("./wgsl/resize.wgsl"))?;
+
+        Ok(WgpuResize {
+            context,
+            pipeline,
+            new_width,
+            new_height
+        })
+    }
+}
+
+impl OperationsTrait for WgpuResize {
+    fn name(&self) -> &'static str {
+        "WGPU Resize"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+
+        let pass = Pass {
+            pipeline:   &self.pipeline,
+            params:     [
+                width as u32,
+                height as u32,
+                self.new_width as u32,
+                self.new_height as u32
+            ],
+            dimensions: (self.new_width, self.new_height)
+        };
+        self.context.run_on_channels(image, false, &[], &[pass])?;
+        image.set_dimensions(self.new_width, self.new_height);
+
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+#[test]
+#[cfg(feature = "tests")]
+fn test_wgpu_resize() {
+    use zune_core::colorspace::ColorSpace;
+    // constant images stay constant, for all supported bit types
+    let mut image = Image::fill(100_u8, ColorSpace::RGB, 100, 80);
+    let resize = WgpuResize::try_new(WgpuContext::try_new().unwrap(), 37, 150).unwrap();
+
+    for d_type in resize.supported_types() {
+        image.convert_depth(d_type.to_depth()).unwrap();
+        let resized = resize.clone_and_execute(&image).unwrap();
+
+        assert_eq!(resized.dimensions(), (37, 150));
+        assert!(resized.flatten_to_u8()[0].iter().all(|x| *x == 100));
+    }
+}
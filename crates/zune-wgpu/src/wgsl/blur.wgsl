@@ -0,0 +1,37 @@
+// One dimension of a separable blur, horizontal or vertical,
+// replicating the pixels at the edges
+struct Params {
+    width: u32,
+    height: u32,
+    radius: u32,
+    // 0 for a horizontal pass, 1 for a vertical one
+    vertical: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> input: array<f32>;
+@group(0) @binding(2) var<storage, read_write> output: array<f32>;
+// 2 * radius + 1 weights
+@group(0) @binding(3) var<storage, read> weights: array<f32>;
+
+@compute @workgroup_size(16, 16)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= params.width || id.y >= params.height) {
+        return;
+    }
+    let radius = i32(params.radius);
+    var sum = 0.0;
+
+    for (var k = -radius; k <= radius; k++) {
+        var x = i32(id.x);
+        var y = i32(id.y);
+
+        if (params.vertical == 1u) {
+            y = clamp(y + k, 0, i32(params.height) - 1);
+        } else {
+            x = clamp(x + k, 0, i32(params.width) - 1);
+        }
+        sum += input[u32(y) * params.width + u32(x)] * weights[u32(k + radius)];
+    }
+    output[id.y * params.width + id.x] = sum;
+}
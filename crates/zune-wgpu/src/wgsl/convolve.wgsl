@@ -0,0 +1,36 @@
+// Convolution with a square matrix, replicating the pixels at the edges
+// like the default pad method of zune-imageprocs
+struct Params {
+    width: u32,
+    height: u32,
+    // width and height of the matrix, odd
+    size: u32,
+    scale: f32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> input: array<f32>;
+@group(0) @binding(2) var<storage, read_write> output: array<f32>;
+// size * size weights, row by row
+@group(0) @binding(3) var<storage, read> weights: array<f32>;
+
+@compute @workgroup_size(16, 16)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= params.width || id.y >= params.height) {
+        return;
+    }
+    let radius = i32(params.size / 2u);
+    var sum = 0.0;
+
+    for (var ky = 0; ky < i32(params.size); ky++) {
+        let y = clamp(i32(id.y) + ky - radius, 0, i32(params.height) - 1);
+
+        for (var kx = 0; kx < i32(params.size); kx++) {
+            let x = clamp(i32(id.x) + kx - radius, 0, i32(params.width) - 1);
+            let weight = weights[u32(ky) * params.size + u32(kx)];
+
+            sum += input[u32(y) * params.width + u32(x)] * weight;
+        }
+    }
+    output[id.y * params.width + id.x] = sum * params.scale;
+}
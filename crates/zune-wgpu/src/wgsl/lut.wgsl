@@ -0,0 +1,24 @@
+// Replace every value by its entry in a lookup table
+struct Params {
+    width: u32,
+    height: u32,
+    // the largest value with an entry
+    max_value: u32,
+    padding: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> input: array<f32>;
+@group(0) @binding(2) var<storage, read_write> output: array<f32>;
+// max_value + 1 entries
+@group(0) @binding(3) var<storage, read> table: array<f32>;
+
+@compute @workgroup_size(16, 16)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= params.width || id.y >= params.height) {
+        return;
+    }
+    let position = id.y * params.width + id.x;
+
+    output[position] = table[min(u32(input[position]), params.max_value)];
+}
@@ -0,0 +1,36 @@
+// Bilinear resize, sampling the same positions as the bilinear
+// resizer of zune-imageprocs
+struct Params {
+    in_width: u32,
+    in_height: u32,
+    out_width: u32,
+    out_height: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> input: array<f32>;
+@group(0) @binding(2) var<storage, read_write> output: array<f32>;
+
+@compute @workgroup_size(16, 16)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= params.out_width || id.y >= params.out_height) {
+        return;
+    }
+    let in_size = vec2<f32>(f32(params.in_width), f32(params.in_height));
+    let out_size = vec2<f32>(f32(params.out_width), f32(params.out_height));
+    let position = vec2<f32>(id.xy) * (in_size / out_size);
+
+    // clamp to ensure that reads never go out of place
+    let x0 = min(u32(position.x), params.in_width - 1u);
+    let y0 = min(u32(position.y), params.in_height - 1u);
+    let x1 = min(x0 + 1u, params.in_width - 1u);
+    let y1 = min(y0 + 1u, params.in_height - 1u);
+
+    let a = position.x - f32(x0);
+    let b = position.y - f32(y0);
+
+    let top = mix(input[y0 * params.in_width + x0], input[y0 * params.in_width + x1], a);
+    let bottom = mix(input[y1 * params.in_width + x0], input[y1 * params.in_width + x1], a);
+
+    output[id.y * params.out_width + id.x] = mix(top, bottom, b);
+}